@@ -0,0 +1,88 @@
+use cosmwasm_std::{
+    entry_point, from_binary, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response,
+    StdResult,
+};
+use cw2::set_contract_version;
+use cw20::Cw20ReceiveMsg;
+
+use pfc_steak::coordinator::{ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg};
+
+use crate::error::ContractError;
+use crate::state::State;
+use crate::{execute, queries};
+
+/// Contract name that is used for migration.
+pub const CONTRACT_NAME: &str = "steak-coordinator";
+/// Contract version that is used for migration.
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[entry_point]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    execute::instantiate(deps, msg)
+}
+
+#[entry_point]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let api = deps.api;
+    match msg {
+        ExecuteMsg::Receive(cw20_msg) => receive(deps, env, info, cw20_msg),
+        ExecuteMsg::AddHub { hub } => execute::add_hub(deps, info.sender, api.addr_validate(&hub)?),
+        ExecuteMsg::RemoveHub { hub } => {
+            execute::remove_hub(deps, info.sender, api.addr_validate(&hub)?)
+        }
+        ExecuteMsg::Mint { recipient, amount } => {
+            execute::mint(deps, info.sender, api.addr_validate(&recipient)?, amount)
+        }
+        ExecuteMsg::Reserve { amount } => execute::reserve(deps, info.sender, amount),
+    }
+}
+
+fn receive(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let api = deps.api;
+
+    let steak_token = State::default().steak_token.load(deps.storage)?;
+    if info.sender != steak_token {
+        return Err(ContractError::UnexpectedToken {
+            expected: "Steak".to_string(),
+            received: info.sender.to_string(),
+        });
+    }
+
+    let sender = api.addr_validate(&cw20_msg.sender)?;
+    match from_binary(&cw20_msg.msg)? {
+        ReceiveMsg::Burn {} => execute::burn(deps, sender, cw20_msg.amount),
+        ReceiveMsg::QueueUnbond { receiver } => execute::queue_unbond(
+            deps,
+            receiver
+                .map(|s| api.addr_validate(&s))
+                .transpose()?
+                .unwrap_or(sender),
+            cw20_msg.amount,
+        ),
+    }
+}
+
+#[entry_point]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&queries::config(deps)?),
+        QueryMsg::HubSupply { hub } => to_binary(&queries::hub_supply(deps, hub)?),
+        QueryMsg::Hubs {} => to_binary(&queries::hubs(deps)?),
+    }
+}
@@ -0,0 +1,38 @@
+use cosmwasm_std::{OverflowError, StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
+    #[error("unauthorized: sender is not owner")]
+    Unauthorized {},
+
+    #[error("unauthorized: sender is not a registered hub")]
+    UnauthorizedHub {},
+
+    #[error("expecting {expected} token, received {received}")]
+    UnexpectedToken { expected: String, received: String },
+
+    #[error("{hub} is already registered")]
+    HubAlreadyRegistered { hub: String },
+
+    #[error("{hub} still has {supply} Steak outstanding")]
+    HubNotEmpty { hub: String, supply: Uint128 },
+
+    #[error("hub can not burn {amount} Steak, only {supply} is attributed to it")]
+    BurnExceedsSupply { amount: Uint128, supply: Uint128 },
+
+    #[error("hub can not burn {amount} Steak, only {queued} is queued with it")]
+    BurnNotQueued { amount: Uint128, queued: Uint128 },
+
+    #[error("hub can not reserve {amount} Steak, only {available} of its supply is unqueued")]
+    ReserveExceedsSupply { amount: Uint128, available: Uint128 },
+
+    #[error("only {available} Steak can be routed to the hubs for unbonding")]
+    InsufficientHubSupply { available: Uint128 },
+}
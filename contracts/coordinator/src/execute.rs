@@ -0,0 +1,228 @@
+use cosmwasm_std::{
+    to_binary, Addr, CosmosMsg, DepsMut, Event, Order, Response, StdResult, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+
+use pfc_steak::coordinator::{HubSupply, InstantiateMsg};
+use pfc_steak::hub::ReceiveMsg as HubReceiveMsg;
+
+use crate::error::ContractError;
+use crate::state::State;
+
+pub fn instantiate(deps: DepsMut, msg: InstantiateMsg) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state
+        .owner
+        .save(deps.storage, &deps.api.addr_validate(&msg.owner)?)?;
+    state
+        .steak_token
+        .save(deps.storage, &deps.api.addr_validate(&msg.steak_token)?)?;
+
+    Ok(Response::new())
+}
+
+pub fn add_hub(deps: DepsMut, sender: Addr, hub: Addr) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    if sender != state.owner.load(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    if state.hubs.has(deps.storage, &hub) {
+        return Err(ContractError::HubAlreadyRegistered { hub: hub.into() });
+    }
+    state.hubs.save(
+        deps.storage,
+        &hub,
+        &HubSupply {
+            hub: hub.clone(),
+            supply: Uint128::zero(),
+            queued: Uint128::zero(),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "steakcoordinator/add_hub")
+        .add_attribute("hub", hub))
+}
+
+pub fn remove_hub(deps: DepsMut, sender: Addr, hub: Addr) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    if sender != state.owner.load(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    let supply = state.hubs.load(deps.storage, &hub)?.supply;
+    if !supply.is_zero() {
+        return Err(ContractError::HubNotEmpty {
+            hub: hub.into(),
+            supply,
+        });
+    }
+    state.hubs.remove(deps.storage, &hub);
+
+    Ok(Response::new()
+        .add_attribute("action", "steakcoordinator/remove_hub")
+        .add_attribute("hub", hub))
+}
+
+pub fn mint(
+    deps: DepsMut,
+    sender: Addr,
+    recipient: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let mut hub = state
+        .hubs
+        .may_load(deps.storage, &sender)?
+        .ok_or(ContractError::UnauthorizedHub {})?;
+    hub.supply = hub.supply.checked_add(amount)?;
+    state.hubs.save(deps.storage, &sender, &hub)?;
+
+    let mint_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: state.steak_token.load(deps.storage)?.into(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: recipient.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    });
+
+    Ok(Response::new()
+        .add_message(mint_msg)
+        .add_attribute("action", "steakcoordinator/mint")
+        .add_attribute("hub", sender)
+        .add_attribute("amount", amount))
+}
+
+pub fn burn(deps: DepsMut, sender: Addr, amount: Uint128) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let mut hub = state
+        .hubs
+        .may_load(deps.storage, &sender)?
+        .ok_or(ContractError::UnauthorizedHub {})?;
+    hub.supply = hub
+        .supply
+        .checked_sub(amount)
+        .map_err(|_| ContractError::BurnExceedsSupply {
+            amount,
+            supply: hub.supply,
+        })?;
+    // a burn not queued beforehand would eat into what the hub's pending batch has queued, and
+    // leave that batch unable to burn
+    hub.queued = hub
+        .queued
+        .checked_sub(amount)
+        .map_err(|_| ContractError::BurnNotQueued {
+            amount,
+            queued: hub.queued,
+        })?;
+    state.hubs.save(deps.storage, &sender, &hub)?;
+
+    let burn_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: state.steak_token.load(deps.storage)?.into(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+        funds: vec![],
+    });
+
+    Ok(Response::new()
+        .add_message(burn_msg)
+        .add_attribute("action", "steakcoordinator/burn")
+        .add_attribute("hub", sender)
+        .add_attribute("amount", amount))
+}
+
+pub fn reserve(deps: DepsMut, sender: Addr, amount: Uint128) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let mut hub = state
+        .hubs
+        .may_load(deps.storage, &sender)?
+        .ok_or(ContractError::UnauthorizedHub {})?;
+    let available = hub.supply - hub.queued;
+    if amount > available {
+        return Err(ContractError::ReserveExceedsSupply { amount, available });
+    }
+    hub.queued += amount;
+    state.hubs.save(deps.storage, &sender, &hub)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "steakcoordinator/reserve")
+        .add_attribute("hub", sender)
+        .add_attribute("amount", amount))
+}
+
+/// Split `amount` across the hubs pro rata to the supply they have not queued for unbonding yet.
+/// Each hub prices the Steak against its own delegations, so routing it all to one hub would let
+/// Steak minted cheaply at one hub be redeemed at a richer one. The remainder left by rounding down
+/// goes to the hubs with the most unqueued supply, one unit each
+pub fn queue_unbond(
+    deps: DepsMut,
+    receiver: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let hubs = state
+        .hubs
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, hub)| hub))
+        .collect::<StdResult<Vec<_>>>()?;
+    let available: Uint128 = hubs.iter().map(|hub| hub.supply - hub.queued).sum();
+    if amount > available {
+        return Err(ContractError::InsufficientHubSupply { available });
+    }
+
+    let mut routed = hubs
+        .iter()
+        .map(|hub| (hub.supply - hub.queued).multiply_ratio(amount, available))
+        .collect::<Vec<_>>();
+    let mut remainder = amount - routed.iter().copied().sum::<Uint128>();
+    let mut by_size = (0..hubs.len()).collect::<Vec<_>>();
+    by_size.sort_by_key(|&i| std::cmp::Reverse(hubs[i].supply - hubs[i].queued));
+    for i in by_size {
+        if remainder.is_zero() {
+            break;
+        }
+        if routed[i] < hubs[i].supply - hubs[i].queued {
+            routed[i] += Uint128::new(1);
+            remainder -= Uint128::new(1);
+        }
+    }
+
+    let steak_token = state.steak_token.load(deps.storage)?;
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut event = Event::new("steakcoordinator/unbond_routed")
+        .add_attribute("receiver", &receiver)
+        .add_attribute("amount", amount);
+    for (mut hub, routed) in hubs.into_iter().zip(routed) {
+        if routed.is_zero() {
+            continue;
+        }
+        hub.queued += routed;
+        state.hubs.save(deps.storage, &hub.hub, &hub)?;
+
+        msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: steak_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: hub.hub.to_string(),
+                amount: routed,
+                msg: to_binary(&HubReceiveMsg::QueueUnbond {
+                    receiver: Some(receiver.to_string()),
+                    min_out: None,
+                    deadline: None,
+                })?,
+            })?,
+            funds: vec![],
+        }));
+        event = event.add_attribute(hub.hub, routed);
+    }
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_event(event)
+        .add_attribute("action", "steakcoordinator/queue_unbond"))
+}
@@ -0,0 +1,10 @@
+#[cfg(not(feature = "library"))]
+pub mod contract;
+
+pub mod error;
+pub mod execute;
+pub mod queries;
+pub mod state;
+
+#[cfg(test)]
+mod testing;
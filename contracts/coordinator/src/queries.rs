@@ -0,0 +1,31 @@
+use cosmwasm_std::{Deps, Order, StdResult};
+
+use pfc_steak::coordinator::{ConfigResponse, HubSupply};
+
+use crate::state::State;
+
+pub fn config(deps: Deps) -> StdResult<ConfigResponse> {
+    let state = State::default();
+
+    Ok(ConfigResponse {
+        owner: state.owner.load(deps.storage)?.into(),
+        steak_token: state.steak_token.load(deps.storage)?.into(),
+    })
+}
+
+pub fn hub_supply(deps: Deps, hub: String) -> StdResult<HubSupply> {
+    let state = State::default();
+
+    let hub = deps.api.addr_validate(&hub)?;
+    state.hubs.load(deps.storage, &hub)
+}
+
+pub fn hubs(deps: Deps) -> StdResult<Vec<HubSupply>> {
+    let state = State::default();
+
+    state
+        .hubs
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, hub)| hub))
+        .collect()
+}
@@ -0,0 +1,23 @@
+use cosmwasm_std::Addr;
+use cw_storage_plus::{Item, Map};
+
+use pfc_steak::coordinator::HubSupply;
+
+pub(crate) struct State<'a> {
+    /// Account who can register and deregister hubs
+    pub owner: Item<'a, Addr>,
+    /// Address of the Steak token shared by the hubs
+    pub steak_token: Item<'a, Addr>,
+    /// Steak attributed to each registered hub
+    pub hubs: Map<'a, &'a Addr, HubSupply>,
+}
+
+impl Default for State<'static> {
+    fn default() -> Self {
+        Self {
+            owner: Item::new("owner"),
+            steak_token: Item::new("steak_token"),
+            hubs: Map::new("hubs"),
+        }
+    }
+}
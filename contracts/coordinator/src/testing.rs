@@ -0,0 +1,359 @@
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier};
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, CosmosMsg, Empty, MemoryStorage, OwnedDeps, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+
+use pfc_steak::coordinator::{ExecuteMsg, HubSupply, InstantiateMsg, QueryMsg, ReceiveMsg};
+use pfc_steak::hub::ReceiveMsg as HubReceiveMsg;
+
+use crate::contract::{execute, instantiate, query};
+use crate::error::ContractError;
+
+fn setup_test() -> OwnedDeps<MemoryStorage, MockApi, MockQuerier, Empty> {
+    let mut deps = mock_dependencies();
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("deployer", &[]),
+        InstantiateMsg {
+            owner: "larry".to_string(),
+            steak_token: "steak_token".to_string(),
+        },
+    )
+    .unwrap();
+    for hub in ["hub_a", "hub_b"] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("larry", &[]),
+            ExecuteMsg::AddHub {
+                hub: hub.to_string(),
+            },
+        )
+        .unwrap();
+    }
+
+    deps
+}
+
+fn receive(sender: &str, amount: u128, msg: ReceiveMsg) -> ExecuteMsg {
+    ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: sender.to_string(),
+        amount: Uint128::new(amount),
+        msg: to_binary(&msg).unwrap(),
+    })
+}
+
+fn hub_supply(
+    deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier, Empty>,
+    hub: &str,
+) -> HubSupply {
+    from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::HubSupply {
+                hub: hub.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn registering_hubs() {
+    let mut deps = setup_test();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::AddHub {
+            hub: "hub_c".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::AddHub {
+            hub: "hub_a".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::HubAlreadyRegistered {
+            hub: "hub_a".to_string()
+        }
+    );
+
+    // Hubs can only be removed once they have no Steak outstanding
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("hub_a", &[]),
+        ExecuteMsg::Mint {
+            recipient: "alice".to_string(),
+            amount: Uint128::new(100),
+        },
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::RemoveHub {
+            hub: "hub_a".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::HubNotEmpty {
+            hub: "hub_a".to_string(),
+            supply: Uint128::new(100)
+        }
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::RemoveHub {
+            hub: "hub_b".to_string(),
+        },
+    )
+    .unwrap();
+    let res: Vec<HubSupply> =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Hubs {}).unwrap()).unwrap();
+    assert_eq!(res.len(), 1);
+    assert_eq!(res[0].hub, Addr::unchecked("hub_a"));
+}
+
+#[test]
+fn minting_and_burning() {
+    let mut deps = setup_test();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::Mint {
+            recipient: "jake".to_string(),
+            amount: Uint128::new(100),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::UnauthorizedHub {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("hub_a", &[]),
+        ExecuteMsg::Mint {
+            recipient: "alice".to_string(),
+            amount: Uint128::new(100),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "alice".to_string(),
+                amount: Uint128::new(100),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_eq!(hub_supply(&deps, "hub_a").supply, Uint128::new(100));
+
+    // Burns are only accepted in the Steak token, and never beyond the hub's supply
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("fake_token", &[]),
+        receive("hub_a", 40, ReceiveMsg::Burn {}),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnexpectedToken {
+            expected: "Steak".to_string(),
+            received: "fake_token".to_string(),
+        }
+    );
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("steak_token", &[]),
+        receive("hub_b", 40, ReceiveMsg::Burn {}),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::BurnExceedsSupply {
+            amount: Uint128::new(40),
+            supply: Uint128::zero(),
+        }
+    );
+
+    // Steak the hub queued outside of the coordinator must be reserved before it is burned
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("steak_token", &[]),
+        receive("hub_a", 40, ReceiveMsg::Burn {}),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::BurnNotQueued {
+            amount: Uint128::new(40),
+            queued: Uint128::zero(),
+        }
+    );
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("hub_a", &[]),
+        ExecuteMsg::Reserve {
+            amount: Uint128::new(101),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ReserveExceedsSupply {
+            amount: Uint128::new(101),
+            available: Uint128::new(100),
+        }
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("hub_a", &[]),
+        ExecuteMsg::Reserve {
+            amount: Uint128::new(40),
+        },
+    )
+    .unwrap();
+    assert_eq!(hub_supply(&deps, "hub_a").queued, Uint128::new(40));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("steak_token", &[]),
+        receive("hub_a", 40, ReceiveMsg::Burn {}),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::new(40),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_eq!(
+        hub_supply(&deps, "hub_a"),
+        HubSupply {
+            hub: Addr::unchecked("hub_a"),
+            supply: Uint128::new(60),
+            queued: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn routing_unbonds() {
+    let mut deps = setup_test();
+
+    for (hub, amount) in [("hub_a", 100u128), ("hub_b", 300)] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(hub, &[]),
+            ExecuteMsg::Mint {
+                recipient: "alice".to_string(),
+                amount: Uint128::new(amount),
+            },
+        )
+        .unwrap();
+    }
+
+    // Each hub takes its share of the unbond, the rounding remainder going to the largest one
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("steak_token", &[]),
+        receive("alice", 350, ReceiveMsg::QueueUnbond { receiver: None }),
+    )
+    .unwrap();
+    let send_msg = |hub: &str, amount: u128| {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: hub.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_binary(&HubReceiveMsg::QueueUnbond {
+                    receiver: Some("alice".to_string()),
+                    min_out: None,
+                    deadline: None,
+                })
+                .unwrap(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    };
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(res.messages[0].msg, send_msg("hub_a", 87));
+    assert_eq!(res.messages[1].msg, send_msg("hub_b", 263));
+    assert_eq!(hub_supply(&deps, "hub_a").queued, Uint128::new(87));
+    assert_eq!(hub_supply(&deps, "hub_b").queued, Uint128::new(263));
+
+    // Supply already queued is not routed again
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("steak_token", &[]),
+        receive("alice", 51, ReceiveMsg::QueueUnbond { receiver: None }),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientHubSupply {
+            available: Uint128::new(50)
+        }
+    );
+
+    // Once a hub burns what was queued with it, its supply and queue both shrink
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("steak_token", &[]),
+        receive("hub_b", 263, ReceiveMsg::Burn {}),
+    )
+    .unwrap();
+    assert_eq!(
+        hub_supply(&deps, "hub_b"),
+        HubSupply {
+            hub: Addr::unchecked("hub_b"),
+            supply: Uint128::new(37),
+            queued: Uint128::zero(),
+        }
+    );
+}
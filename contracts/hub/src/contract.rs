@@ -1,15 +1,17 @@
 use cosmwasm_std::{
-    entry_point, from_binary, to_binary, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Reply,
-    Response, StdError, StdResult, Uint128,
+    entry_point, from_binary, to_binary, to_vec, Binary, Decimal, Deps, DepsMut, Env, MessageInfo,
+    Reply, Response, StdError, StdResult, Uint128,
 };
 use cw20::Cw20ReceiveMsg;
 
 use pfc_steak::hub::{
-    CallbackMsg, ExecuteMsg, FeeType, InstantiateMsg, MigrateMsg, QueryMsg, ReceiveMsg,
+    AggregateResponse, AggregateResult, CallbackMsg, ExecuteMsg, FeeType, InstantiateMsg,
+    MigrateMsg, MigrationReport, MiningAllocation, QueryMsg, ReceiveMsg, SudoMsg,
 };
 
+use crate::error::ContractError;
 use crate::helpers::{get_denom_balance, unwrap_reply};
-use crate::migrations::ConfigV100;
+use crate::migrations::{ConfigV100, DryRunStorage, LegacyTerraHub};
 use crate::state::State;
 use crate::{execute, queries};
 use cw2::{get_contract_version, set_contract_version, ContractVersion};
@@ -20,6 +22,13 @@ pub const CONTRACT_NAME: &str = "steak-hub";
 pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 pub const REPLY_INSTANTIATE_TOKEN: u64 = 1;
 pub const REPLY_REGISTER_RECEIVED_COINS: u64 = 2;
+pub const REPLY_BALANCE_HOOK_FAILED: u64 = 3;
+pub const REPLY_AUTOMATION_TASK_CREATED: u64 = 4;
+pub const REPLY_CLOCK_TASK_FAILED: u64 = 5;
+pub const REPLY_INTEGRATOR_CALLBACK_DONE: u64 = 6;
+pub const REPLY_FEE_FORWARD_DONE: u64 = 7;
+/// Most queries a single `QueryMsg::Aggregate` can run
+pub const MAX_AGGREGATE_QUERIES: usize = 10;
 
 #[entry_point]
 pub fn instantiate(
@@ -27,19 +36,25 @@ pub fn instantiate(
     env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     execute::instantiate(deps, env, msg)
 }
 
 #[entry_point]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
     let api = deps.api;
     match msg {
         ExecuteMsg::Receive(cw20_msg) => receive(deps, env, info, cw20_msg),
         ExecuteMsg::Bond { receiver } => execute::bond(
             deps,
             env,
+            info.sender.clone(),
             receiver
                 .map(|s| api.addr_validate(&s))
                 .transpose()?
@@ -55,37 +70,117 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
                 .transpose()?
                 .unwrap_or(info.sender),
         ),
+        ExecuteMsg::ConsolidateRequests {} => execute::consolidate_requests(deps, env, info.sender),
         ExecuteMsg::WithdrawUnbondedAdmin { address } => {
             execute::withdraw_unbonded_admin(deps, env, info.sender, api.addr_validate(&address)?)
         }
-        ExecuteMsg::AddValidator { validator } => {
-            execute::add_validator(deps, info.sender, validator)
+        ExecuteMsg::AddValidator { validator, reason } => {
+            execute::add_validator(deps, env, info.sender, validator, reason)
+        }
+        ExecuteMsg::RemoveValidator { validator, reason } => {
+            execute::remove_validator(deps, env, info.sender, validator, reason)
         }
-        ExecuteMsg::RemoveValidator { validator } => {
-            execute::remove_validator(deps, env, info.sender, validator)
+        ExecuteMsg::DrainValidators { max_amount } => {
+            execute::drain_validators(deps, env, info.sender, max_amount)
         }
-        ExecuteMsg::RemoveValidatorEx { validator } => {
-            execute::remove_validator_ex(deps, env, info.sender, validator)
+        ExecuteMsg::RemoveValidatorEx { validator, reason } => {
+            execute::remove_validator_ex(deps, env, info.sender, validator, reason)
         }
         ExecuteMsg::TransferOwnership { new_owner } => {
             execute::transfer_ownership(deps, info.sender, new_owner)
         }
         ExecuteMsg::AcceptOwnership {} => execute::accept_ownership(deps, info.sender),
         ExecuteMsg::Harvest {} => execute::harvest(deps, env, info.sender),
+        ExecuteMsg::DepositRebate { validator } => {
+            execute::deposit_rebate(deps, info.sender, validator, info.funds)
+        }
         ExecuteMsg::Rebalance { minimum } => execute::rebalance(deps, env, minimum),
+        ExecuteMsg::UpdateDriftTolerance { bps } => {
+            execute::update_drift_tolerance(deps, info.sender, bps)
+        }
+        ExecuteMsg::UpdateMaxMsgsPerTx { max } => {
+            execute::update_max_msgs_per_tx(deps, info.sender, max)
+        }
         ExecuteMsg::Reconcile {} => execute::reconcile(deps, env),
-        ExecuteMsg::SubmitBatch {} => execute::submit_batch(deps, env),
+        ExecuteMsg::SubmitBatch {} => execute::submit_batch(deps, env, info.sender),
+        ExecuteMsg::UpdateBatchExpiry { expiry } => {
+            execute::update_batch_expiry(deps, info.sender, expiry)
+        }
+        ExecuteMsg::UpdateBatchLimits { min_gap, min_size } => {
+            execute::update_batch_limits(deps, info.sender, min_gap, min_size)
+        }
+        ExecuteMsg::UpdateMaxUnbondRequests { max } => {
+            execute::update_max_unbond_requests(deps, info.sender, max)
+        }
+        ExecuteMsg::UpdateRewardDrip { period } => {
+            execute::update_reward_drip(deps, info.sender, period)
+        }
+        ExecuteMsg::UpdateMinReinvestAmount { amount } => {
+            execute::update_min_reinvest_amount(deps, info.sender, amount)
+        }
+        ExecuteMsg::SweepExpiredBatch { id } => execute::sweep_expired_batch(deps, env, id),
+        ExecuteMsg::Prune { kind, limit } => execute::prune(deps, env, kind, limit),
         ExecuteMsg::TransferFeeAccount {
             fee_account_type,
             new_fee_account,
-        } => execute::transfer_fee_account(deps, info.sender, fee_account_type, new_fee_account),
-        ExecuteMsg::UpdateFee { new_fee } => execute::update_fee(deps, info.sender, new_fee),
+            reason,
+        } => execute::transfer_fee_account(
+            deps,
+            env,
+            info.sender,
+            fee_account_type,
+            new_fee_account,
+            reason,
+        ),
+        ExecuteMsg::RetryFeeForward {} => execute::retry_fee_forward(deps),
+        ExecuteMsg::UpdateTokenMarketing {
+            project,
+            description,
+            marketing,
+        } => execute::update_token_marketing(deps, info.sender, project, description, marketing),
+        ExecuteMsg::UploadTokenLogo { logo } => execute::upload_token_logo(deps, info.sender, logo),
+        ExecuteMsg::SetCoordinator { coordinator } => {
+            execute::set_coordinator(deps, info.sender, api.addr_validate(&coordinator)?)
+        }
+        ExecuteMsg::UpdateFee { new_fee, reason } => {
+            execute::update_fee(deps, env, info.sender, new_fee, reason)
+        }
+        ExecuteMsg::UpdateRefundableDenoms { denoms } => {
+            execute::update_refundable_denoms(deps, info.sender, denoms)
+        }
+        ExecuteMsg::UpdateZapper { zapper } => execute::update_zapper(
+            deps,
+            info.sender,
+            zapper.map(|z| api.addr_validate(&z)).transpose()?,
+        ),
+        ExecuteMsg::UpdateSurplusPolicy { policy } => {
+            execute::update_surplus_policy(deps, info.sender, policy)
+        }
+        ExecuteMsg::SkimSurplus {} => execute::skim_surplus(deps, env),
+        ExecuteMsg::UpdateRewardDenoms {
+            reward_denoms,
+            converter,
+        } => execute::update_reward_denoms(
+            deps,
+            info.sender,
+            reward_denoms,
+            converter.map(|c| api.addr_validate(&c)).transpose()?,
+        ),
         ExecuteMsg::Callback(callback_msg) => callback(deps, env, info, callback_msg),
-        ExecuteMsg::PauseValidator { validator } => {
-            execute::pause_validator(deps, env, info.sender, validator)
+        ExecuteMsg::PauseValidator { validator, reason } => {
+            execute::pause_validator(deps, env, info.sender, validator, reason)
+        }
+        ExecuteMsg::ReceiveOnlyPauseValidator { validator, reason } => {
+            execute::receive_only_pause_validator(deps, env, info.sender, validator, reason)
         }
-        ExecuteMsg::UnPauseValidator { validator } => {
-            execute::unpause_validator(deps, env, info.sender, validator)
+        ExecuteMsg::UnPauseValidator { validator, reason } => {
+            execute::unpause_validator(deps, env, info.sender, validator, reason)
+        }
+        ExecuteMsg::BlockValidator { validator, reason } => {
+            execute::block_validator(deps, env, info.sender, validator, reason)
+        }
+        ExecuteMsg::UnblockValidator { validator, reason } => {
+            execute::unblock_validator(deps, env, info.sender, validator, reason)
         }
         ExecuteMsg::SetUnbondPeriod { unbond_period } => {
             execute::set_unbond_period(deps, env, info.sender, unbond_period)
@@ -93,8 +188,299 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
         ExecuteMsg::UpdateEntropy { entropy } => {
             execute::update_entropy(deps, env, info.sender, entropy)
         }
-        ExecuteMsg::SubmitProof { nonce, validator } => {
-            execute::submit_proof(deps, env, info.sender, nonce, validator)
+        ExecuteMsg::SubmitProof { nonce, validator } => execute::submit_proof(
+            deps,
+            env,
+            info.sender,
+            nonce,
+            vec![MiningAllocation {
+                validator,
+                weight: Decimal::one(),
+            }],
+        ),
+        ExecuteMsg::SubmitProofSplit { nonce, allocations } => {
+            execute::submit_proof(deps, env, info.sender, nonce, allocations)
+        }
+        ExecuteMsg::PurgeMiningPower { validators } => {
+            execute::purge_mining_power(deps, env, validators)
+        }
+        ExecuteMsg::ReportSlashing { validator } => {
+            execute::report_slashing(deps, env, info.sender, validator)
+        }
+        ExecuteMsg::UpdateIdleRedelegation {
+            zero_reward_harvests,
+            min_idle_period,
+            max_amount,
+        } => execute::update_idle_redelegation(
+            deps,
+            info.sender,
+            zero_reward_harvests,
+            min_idle_period,
+            max_amount,
+        ),
+        ExecuteMsg::RedelegateIdle {} => execute::redelegate_idle(deps, env, info.sender),
+        ExecuteMsg::UpdateVotingPowerCap { max_share } => {
+            execute::update_voting_power_cap(deps, info.sender, max_share)
+        }
+        ExecuteMsg::RefreshVotingPower {} => execute::refresh_voting_power(deps, env),
+        ExecuteMsg::UpdateMinerFeeShare { bps } => {
+            execute::update_miner_fee_share(deps, info.sender, bps)
+        }
+        ExecuteMsg::UpdateProofCooldown { blocks } => {
+            execute::update_proof_cooldown(deps, info.sender, blocks)
+        }
+        ExecuteMsg::UpdateEntropyRateLimit {
+            window,
+            max_contributions,
+        } => execute::update_entropy_rate_limit(deps, info.sender, window, max_contributions),
+        ExecuteMsg::UpdateMiningSchedule {
+            epoch_duration,
+            initial_share,
+            halving_epochs,
+        } => execute::update_mining_schedule(
+            deps,
+            env,
+            info.sender,
+            epoch_duration,
+            initial_share,
+            halving_epochs,
+        ),
+        ExecuteMsg::BondAndCall {
+            receiver,
+            contract,
+            callback,
+        } => execute::bond_and_call(
+            deps,
+            env,
+            info.sender.clone(),
+            receiver
+                .map(|s| api.addr_validate(&s))
+                .transpose()?
+                .unwrap_or_else(|| info.sender.clone()),
+            api.addr_validate(&contract)?,
+            callback,
+            info.funds,
+        ),
+        ExecuteMsg::BondWithCallback { receiver, callback } => execute::bond_with_callback(
+            deps,
+            env,
+            info.sender.clone(),
+            receiver
+                .map(|s| api.addr_validate(&s))
+                .transpose()?
+                .unwrap_or_else(|| info.sender.clone()),
+            callback,
+            info.funds,
+        ),
+        ExecuteMsg::BondLocked { receiver, duration } => execute::bond_locked(
+            deps,
+            env,
+            info.sender.clone(),
+            receiver
+                .map(|s| api.addr_validate(&s))
+                .transpose()?
+                .unwrap_or(info.sender),
+            duration,
+            info.funds,
+        ),
+        ExecuteMsg::UnlockBond { id } => execute::unlock_bond(deps, env, info.sender, id),
+        ExecuteMsg::ClaimLockBoost {} => execute::claim_lock_boost(deps, env, info.sender),
+        ExecuteMsg::Unlock {} => execute::unlock(deps, env, info.sender),
+        ExecuteMsg::UpdateLockBoost { boost_rate } => {
+            execute::update_lock_boost(deps, info.sender, boost_rate)
+        }
+        ExecuteMsg::UpdateLockTiers { tiers } => {
+            execute::update_lock_tiers(deps, info.sender, tiers)
+        }
+        ExecuteMsg::EnableYieldSplit { principal_token } => {
+            execute::enable_yield_split(deps, info.sender, principal_token)
+        }
+        ExecuteMsg::ClaimYield { receiver } => execute::claim_yield(
+            deps,
+            env,
+            info.sender.clone(),
+            receiver
+                .map(|s| api.addr_validate(&s))
+                .transpose()?
+                .unwrap_or(info.sender),
+        ),
+        ExecuteMsg::TransferRebasing { recipient, amount } => execute::transfer_rebasing(
+            deps,
+            env,
+            info.sender,
+            api.addr_validate(&recipient)?,
+            amount,
+        ),
+        ExecuteMsg::UnwrapRebasing { amount, receiver } => execute::unwrap_rebasing(
+            deps,
+            env,
+            info.sender.clone(),
+            receiver
+                .map(|s| api.addr_validate(&s))
+                .transpose()?
+                .unwrap_or(info.sender),
+            amount,
+        ),
+        ExecuteMsg::ConfigurePol {
+            pair,
+            lp_token,
+            fee_rate,
+        } => execute::configure_pol(deps, env, info.sender, pair, lp_token, fee_rate),
+        ExecuteMsg::AddPol {
+            bond_amount,
+            slippage_tolerance,
+        } => execute::add_pol(
+            deps,
+            env,
+            info.sender,
+            bond_amount,
+            slippage_tolerance,
+            info.funds,
+        ),
+        ExecuteMsg::RemovePol { lp_amount } => {
+            execute::remove_pol(deps, env, info.sender, lp_amount)
+        }
+        ExecuteMsg::CollectPol { receiver } => {
+            execute::collect_pol(deps, env, info.sender, api.addr_validate(&receiver)?)
+        }
+        ExecuteMsg::UpdateArbConfig {
+            min_discount,
+            max_per_execution,
+            max_in_flight,
+        } => execute::update_arb_config(
+            deps,
+            info.sender,
+            min_discount,
+            max_per_execution,
+            max_in_flight,
+        ),
+        ExecuteMsg::FundArbReserve {} => {
+            execute::fund_arb_reserve(deps, env, info.sender, info.funds)
+        }
+        ExecuteMsg::WithdrawArbReserve { amount, receiver } => execute::withdraw_arb_reserve(
+            deps,
+            env,
+            info.sender,
+            amount,
+            api.addr_validate(&receiver)?,
+        ),
+        ExecuteMsg::Arbitrage {} => execute::arbitrage(deps, env),
+        ExecuteMsg::SettleArbitrage {} => execute::settle_arbitrage(deps, env),
+        ExecuteMsg::UpdateRoundingPolicy { policy } => {
+            execute::update_rounding_policy(deps, info.sender, policy)
+        }
+        ExecuteMsg::ProposeDenomChange { new_denom } => {
+            execute::propose_denom_change(deps, info.sender, new_denom)
+        }
+        ExecuteMsg::ConfirmDenomChange {} => execute::confirm_denom_change(deps, env, info.sender),
+        ExecuteMsg::CancelDenomChange {} => execute::cancel_denom_change(deps, info.sender),
+        ExecuteMsg::ProposeEmergencyUndelegation {} => {
+            execute::propose_emergency_undelegation(deps, env, info.sender)
+        }
+        ExecuteMsg::EmergencyUndelegateAll {} => {
+            execute::emergency_undelegate_all(deps, env, info.sender)
+        }
+        ExecuteMsg::CancelEmergencyUndelegation {} => {
+            execute::cancel_emergency_undelegation(deps, info.sender)
+        }
+        ExecuteMsg::ProposeHaltRecovery {
+            batch_shift,
+            reset_mining_clock,
+        } => {
+            execute::propose_halt_recovery(deps, env, info.sender, batch_shift, reset_mining_clock)
+        }
+        ExecuteMsg::ApplyHaltRecovery {} => execute::apply_halt_recovery(deps, env, info.sender),
+        ExecuteMsg::CancelHaltRecovery {} => execute::cancel_halt_recovery(deps, info.sender),
+        ExecuteMsg::EnterSafeMode {
+            until_height,
+            until_time,
+            reason,
+        } => execute::enter_safe_mode(deps, env, info.sender, until_height, until_time, reason),
+        ExecuteMsg::ExitSafeMode { reason } => {
+            execute::exit_safe_mode(deps, env, info.sender, reason)
+        }
+        ExecuteMsg::ProposeGuardianChange { guardian } => {
+            execute::propose_guardian_change(deps, env, info.sender, guardian)
+        }
+        ExecuteMsg::ApplyGuardianChange {} => {
+            execute::apply_guardian_change(deps, env, info.sender)
+        }
+        ExecuteMsg::CancelGuardianChange {} => execute::cancel_guardian_change(deps, info.sender),
+        ExecuteMsg::SetFeeCommittee { members, threshold } => {
+            execute::set_fee_committee(deps, env, info.sender, members, threshold)
+        }
+        ExecuteMsg::ProposeCommitteeAction { action } => {
+            execute::propose_committee_action(deps, env, info.sender, action)
+        }
+        ExecuteMsg::ApproveCommitteeAction { id } => {
+            execute::approve_committee_action(deps, env, info.sender, id)
+        }
+        ExecuteMsg::AddBalanceHook { contract } => {
+            execute::add_balance_hook(deps, info.sender, api.addr_validate(&contract)?)
+        }
+        ExecuteMsg::RemoveBalanceHook { contract } => {
+            execute::remove_balance_hook(deps, info.sender, api.addr_validate(&contract)?)
+        }
+        ExecuteMsg::AddHarvester { address } => {
+            execute::add_harvester(deps, info.sender, api.addr_validate(&address)?)
+        }
+        ExecuteMsg::RemoveHarvester { address } => {
+            execute::remove_harvester(deps, info.sender, api.addr_validate(&address)?)
+        }
+        ExecuteMsg::AddIntegrator { address } => {
+            execute::add_integrator(deps, info.sender, api.addr_validate(&address)?)
+        }
+        ExecuteMsg::RemoveIntegrator { address } => {
+            execute::remove_integrator(deps, info.sender, api.addr_validate(&address)?)
+        }
+        ExecuteMsg::UpdateVoteDelegates { delegates } => execute::update_vote_delegates(
+            deps,
+            info.sender,
+            delegates
+                .iter()
+                .map(|delegate| api.addr_validate(delegate))
+                .collect::<StdResult<_>>()?,
+        ),
+        ExecuteMsg::DelegateVote {
+            proposal_id,
+            vote,
+            voting_end_time,
+        } => execute::delegate_vote(deps, env, info.sender, proposal_id, vote, voting_end_time),
+        ExecuteMsg::OptOutOfDelegatedVote { proposal_id } => {
+            execute::opt_out_of_delegated_vote(deps, info.sender, proposal_id)
+        }
+        ExecuteMsg::UpdateAutomation { contract } => execute::update_automation(
+            deps,
+            info.sender,
+            contract.map(|c| api.addr_validate(&c)).transpose()?,
+        ),
+        ExecuteMsg::CreateAutomationTask {
+            task,
+            interval,
+            gas_limit,
+        } => execute::create_automation_task(
+            deps,
+            env,
+            info.sender,
+            info.funds,
+            task,
+            interval,
+            gas_limit,
+        ),
+        ExecuteMsg::RefillAutomationTask { task } => {
+            execute::refill_automation_task(deps, info.sender, info.funds, task)
+        }
+        ExecuteMsg::RemoveAutomationTask { task } => {
+            execute::remove_automation_task(deps, info.sender, task)
+        }
+        ExecuteMsg::UpdateClockHarvestInterval { interval } => {
+            execute::update_clock_harvest_interval(deps, info.sender, interval)
+        }
+        ExecuteMsg::AutomationTaskEnded { task_hash } => {
+            execute::end_automation_task(deps, info.sender, task_hash)
+        }
+        ExecuteMsg::BalanceChanged(change) => {
+            execute::dispatch_balance_change(deps, info.sender, change)
         }
     }
 }
@@ -104,27 +490,177 @@ fn receive(
     env: Env,
     info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let api = deps.api;
     match from_binary(&cw20_msg.msg)? {
-        ReceiveMsg::QueueUnbond { receiver } => {
+        ReceiveMsg::QueueUnbond {
+            receiver,
+            min_out,
+            deadline,
+        } => {
             let state = State::default();
 
             let steak_token = state.steak_token.load(deps.storage)?;
             if info.sender != steak_token {
-                return Err(StdError::generic_err(format!(
-                    "expecting Steak token, received {}",
-                    info.sender
-                )));
+                return Err(ContractError::UnexpectedToken {
+                    expected: "Steak".to_string(),
+                    received: info.sender.to_string(),
+                });
             }
 
+            let sender = api.addr_validate(&cw20_msg.sender)?;
+            if let Some(coordinator) = state.coordinator.may_load(deps.storage)? {
+                if sender != coordinator {
+                    return Err(ContractError::UnbondThroughCoordinator {});
+                }
+            }
+            execute::assert_before_deadline(&env, deadline)?;
+            if let Some(min_out) = min_out {
+                execute::assert_unbond_min_out(deps.as_ref(), &env, cw20_msg.amount, min_out)?;
+            }
             execute::queue_unbond(
                 deps,
                 env,
+                sender,
                 api.addr_validate(&receiver.unwrap_or(cw20_msg.sender))?,
                 cw20_msg.amount,
             )
         }
+        ReceiveMsg::QueueUnbondWithCallback { receiver, callback } => {
+            let state = State::default();
+
+            let steak_token = state.steak_token.load(deps.storage)?;
+            if info.sender != steak_token {
+                return Err(ContractError::UnexpectedToken {
+                    expected: "Steak".to_string(),
+                    received: info.sender.to_string(),
+                });
+            }
+
+            let sender = api.addr_validate(&cw20_msg.sender)?;
+            if let Some(coordinator) = state.coordinator.may_load(deps.storage)? {
+                if sender != coordinator {
+                    return Err(ContractError::UnbondThroughCoordinator {});
+                }
+            }
+            execute::queue_unbond_with_callback(
+                deps,
+                env,
+                sender,
+                api.addr_validate(&receiver.unwrap_or(cw20_msg.sender))?,
+                cw20_msg.amount,
+                callback,
+            )
+        }
+        ReceiveMsg::SplitYield { receiver } => {
+            let state = State::default();
+
+            let steak_token = state.steak_token.load(deps.storage)?;
+            if info.sender != steak_token {
+                return Err(ContractError::UnexpectedToken {
+                    expected: "Steak".to_string(),
+                    received: info.sender.to_string(),
+                });
+            }
+
+            let user = api.addr_validate(&cw20_msg.sender)?;
+            execute::split_yield(
+                deps,
+                env,
+                user.clone(),
+                receiver
+                    .map(|s| api.addr_validate(&s))
+                    .transpose()?
+                    .unwrap_or(user),
+                cw20_msg.amount,
+            )
+        }
+        ReceiveMsg::RedeemPrincipal { receiver } => {
+            let state = State::default();
+
+            let principal_token = state.principal_token.may_load(deps.storage)?;
+            if Some(&info.sender) != principal_token.as_ref() {
+                return Err(ContractError::UnexpectedToken {
+                    expected: "principal".to_string(),
+                    received: info.sender.to_string(),
+                });
+            }
+
+            execute::redeem_principal(
+                deps,
+                env,
+                api.addr_validate(&receiver.unwrap_or(cw20_msg.sender))?,
+                cw20_msg.amount,
+            )
+        }
+        ReceiveMsg::LiquidUnstake {
+            min_out,
+            receiver,
+            deadline,
+        } => {
+            let state = State::default();
+
+            let steak_token = state.steak_token.load(deps.storage)?;
+            if info.sender != steak_token {
+                return Err(ContractError::UnexpectedToken {
+                    expected: "Steak".to_string(),
+                    received: info.sender.to_string(),
+                });
+            }
+
+            let sender = api.addr_validate(&cw20_msg.sender)?;
+            execute::liquid_unstake(
+                deps,
+                env,
+                sender,
+                api.addr_validate(&receiver.unwrap_or(cw20_msg.sender))?,
+                cw20_msg.amount,
+                min_out,
+                deadline,
+            )
+        }
+        ReceiveMsg::WrapRebasing { receiver } => {
+            let state = State::default();
+
+            let steak_token = state.steak_token.load(deps.storage)?;
+            if info.sender != steak_token {
+                return Err(ContractError::UnexpectedToken {
+                    expected: "Steak".to_string(),
+                    received: info.sender.to_string(),
+                });
+            }
+
+            let user = api.addr_validate(&cw20_msg.sender)?;
+            execute::wrap_rebasing(
+                deps,
+                env,
+                user.clone(),
+                receiver
+                    .map(|s| api.addr_validate(&s))
+                    .transpose()?
+                    .unwrap_or(user),
+                cw20_msg.amount,
+            )
+        }
+        ReceiveMsg::Lock { duration } => {
+            let state = State::default();
+
+            let steak_token = state.steak_token.load(deps.storage)?;
+            if info.sender != steak_token {
+                return Err(ContractError::UnexpectedToken {
+                    expected: "Steak".to_string(),
+                    received: info.sender.to_string(),
+                });
+            }
+
+            execute::lock(
+                deps,
+                env,
+                api.addr_validate(&cw20_msg.sender)?,
+                cw20_msg.amount,
+                duration,
+            )
+        }
     }
 }
 
@@ -133,37 +669,77 @@ fn callback(
     env: Env,
     info: MessageInfo,
     callback_msg: CallbackMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     if env.contract.address != info.sender {
-        return Err(StdError::generic_err(
-            "callbacks can only be invoked by the contract itself",
-        ));
+        return Err(ContractError::CallbackOnly {});
     }
 
     match callback_msg {
+        CallbackMsg::ConvertRewards {} => execute::convert_rewards(deps, env),
         CallbackMsg::Reinvest {} => execute::reinvest(deps, env),
+        CallbackMsg::SettlePolWithdrawal {
+            native_before,
+            usteak_before,
+        } => execute::settle_pol_withdrawal(deps, env, native_before, usteak_before),
+        CallbackMsg::SettleLiquidUnstake {
+            receiver,
+            native_before,
+            native_at_par,
+            min_out,
+        } => {
+            let receiver = deps.api.addr_validate(&receiver)?;
+            execute::settle_liquid_unstake(
+                deps,
+                env,
+                receiver,
+                native_before,
+                native_at_par,
+                min_out,
+            )
+        }
+        CallbackMsg::NotifyIntegrator {
+            integrator,
+            flow,
+            callback,
+        } => {
+            let integrator = deps.api.addr_validate(&integrator)?;
+            execute::notify_integrator(deps, env, integrator, flow, callback)
+        }
     }
 }
 
 #[entry_point]
-pub fn reply(deps: DepsMut, env: Env, reply: Reply) -> StdResult<Response> {
+pub fn reply(deps: DepsMut, env: Env, reply: Reply) -> Result<Response, ContractError> {
     match reply.id {
         1 => execute::register_steak_token(deps, unwrap_reply(reply)?),
         REPLY_REGISTER_RECEIVED_COINS => {
             execute::register_received_coins(deps, env, unwrap_reply(reply)?.events)
         }
-        id => Err(StdError::generic_err(format!(
-            "invalid reply id: {}; must be 1-2",
-            id
-        ))),
+        REPLY_BALANCE_HOOK_FAILED => execute::record_balance_hook_failure(deps, reply.result),
+        REPLY_AUTOMATION_TASK_CREATED => {
+            execute::register_automation_task(deps, unwrap_reply(reply)?)
+        }
+        REPLY_CLOCK_TASK_FAILED => execute::record_clock_task_failure(deps, reply.result),
+        REPLY_INTEGRATOR_CALLBACK_DONE => execute::clear_integrator_callback(deps),
+        REPLY_FEE_FORWARD_DONE => execute::settle_fee_forward(deps, reply.result),
+        id => Err(ContractError::InvalidReplyId { id }),
+    }
+}
+
+#[entry_point]
+pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::ClockEndBlock {} => execute::clock_end_block(deps, env),
     }
 }
 
 #[entry_point]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
-        QueryMsg::Config {} => to_binary(&queries::config(deps)?),
+        QueryMsg::Config {} => to_binary(&queries::config(deps, env)?),
+        QueryMsg::Ownership {} => to_binary(&queries::ownership(deps)?),
         QueryMsg::State {} => to_binary(&queries::state(deps, env)?),
+        QueryMsg::StateV2 {} => to_binary(&queries::state_v2(deps, env)?),
         QueryMsg::PendingBatch {} => to_binary(&queries::pending_batch(deps)?),
         QueryMsg::PreviousBatch(id) => to_binary(&queries::previous_batch(deps, id)?),
         QueryMsg::PreviousBatches { start_after, limit } => {
@@ -179,6 +755,24 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             start_after,
             limit,
         )?),
+        QueryMsg::UnbondRequestsBySender {
+            sender,
+            start_after,
+            limit,
+        } => to_binary(&queries::unbond_requests_by_sender(
+            deps,
+            sender,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::BatchFunders {
+            id,
+            start_after,
+            limit,
+        } => to_binary(&queries::batch_funders(deps, id, start_after, limit)?),
+        QueryMsg::PendingBatchRequest { user } => {
+            to_binary(&queries::pending_batch_request(deps, env, user)?)
+        }
         QueryMsg::UnbondRequestsByUser {
             user,
             start_after,
@@ -189,15 +783,215 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             start_after,
             limit,
         )?),
+        QueryMsg::UnbondRequestsByUserDetails {
+            user,
+            start_after,
+            limit,
+        } => to_binary(&queries::unbond_requests_by_user_details(
+            deps,
+            env,
+            user,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ConsolidatedClaim { user } => {
+            to_binary(&queries::consolidated_claim(deps, user)?)
+        }
         QueryMsg::MinerParams {} => to_binary(&queries::miner_params(deps)?),
+        QueryMsg::MiningEmission {} => to_binary(&queries::mining_emission(deps, env)?),
+        QueryMsg::VerifyProof { miner, nonce } => {
+            to_binary(&queries::verify_proof(deps, miner, nonce)?)
+        }
+        QueryMsg::MinerStatus { miner } => to_binary(&queries::miner_status(deps, miner)?),
+        QueryMsg::CurrentMiner {} => to_binary(&queries::current_miner(deps)?),
+        QueryMsg::EntropyContributor { contributor } => {
+            to_binary(&queries::entropy_contributor(deps, contributor)?)
+        }
+        QueryMsg::EntropyContributions { start_after, limit } => {
+            to_binary(&queries::entropy_contributions(deps, start_after, limit)?)
+        }
         QueryMsg::ValidatorMiningPowers { start_after, limit } => {
             to_binary(&queries::validator_mining_powers(deps, start_after, limit)?)
         }
+        QueryMsg::ValidatorRewards {} => to_binary(&queries::validator_rewards(deps)?),
+        QueryMsg::ValidatorScores {} => to_binary(&queries::validator_scores(deps, env)?),
+        QueryMsg::DelegationDrift {} => to_binary(&queries::delegation_drift(deps, env)?),
+        QueryMsg::VotingPowerCap {} => to_binary(&queries::voting_power_cap(deps, env)?),
+        QueryMsg::ReceivePausedValidators {} => {
+            to_binary(&queries::receive_paused_validators(deps)?)
+        }
+        QueryMsg::DrainingValidators {} => to_binary(&queries::draining_validators(deps)?),
+        QueryMsg::BlockedValidators { start_after, limit } => {
+            to_binary(&queries::blocked_validators(deps, start_after, limit)?)
+        }
+        QueryMsg::AdminActions { start_after, limit } => {
+            to_binary(&queries::admin_actions(deps, start_after, limit)?)
+        }
+        QueryMsg::ValidatorSetChanges { start_after, limit } => {
+            to_binary(&queries::validator_set_changes(deps, start_after, limit)?)
+        }
+        QueryMsg::LockState {} => to_binary(&queries::lock_state(deps)?),
+        QueryMsg::LockedBonds {
+            user,
+            start_after,
+            limit,
+        } => to_binary(&queries::locked_bonds(deps, user, start_after, limit)?),
+        QueryMsg::YieldPool {} => to_binary(&queries::yield_pool(deps, env)?),
+        QueryMsg::YieldPosition { user } => to_binary(&queries::yield_position(deps, env, user)?),
+        QueryMsg::RebasingBalance { user } => {
+            to_binary(&queries::rebasing_balance(deps, env, user)?)
+        }
+        QueryMsg::RebasingSupply {} => to_binary(&queries::rebasing_supply(deps, env)?),
+        QueryMsg::PolState {} => to_binary(&queries::pol_state(deps, env)?),
+        QueryMsg::ArbState {} => to_binary(&queries::arb_state(deps)?),
+        QueryMsg::UserHistory {
+            address,
+            start_after,
+            limit,
+        } => to_binary(&queries::user_history(deps, address, start_after, limit)?),
+        QueryMsg::DailySnapshots { start_after, limit } => {
+            to_binary(&queries::daily_snapshots(deps, start_after, limit)?)
+        }
+        QueryMsg::CheckInvariants {} => to_binary(&queries::check_invariants(deps, env)?),
+        QueryMsg::RoundingPolicy {} => to_binary(&queries::rounding_policy(deps)?),
+        QueryMsg::DenomChangePreview {} => to_binary(&queries::denom_change_preview(deps, env)?),
+        QueryMsg::EmergencyUndelegation {} => to_binary(&queries::emergency_undelegation(deps)?),
+        QueryMsg::HaltRecovery {} => to_binary(&queries::halt_recovery(deps)?),
+        QueryMsg::SafeMode {} => to_binary(&queries::safe_mode(deps, env)?),
+        QueryMsg::FeeCommittee {} => to_binary(&queries::fee_committee(deps)?),
+        QueryMsg::BalanceHooks {} => to_binary(&queries::balance_hooks(deps)?),
+        QueryMsg::Harvesters {} => to_binary(&queries::harvesters(deps)?),
+        QueryMsg::Integrators {} => to_binary(&queries::integrators(deps)?),
+        QueryMsg::VoteDelegates {} => to_binary(&queries::vote_delegates(deps)?),
+        QueryMsg::DelegatedVote { proposal_id } => {
+            to_binary(&queries::delegated_vote(deps, proposal_id)?)
+        }
+        QueryMsg::LiquiditySchedule { days } => {
+            to_binary(&queries::liquidity_schedule(deps, env, days)?)
+        }
+        QueryMsg::UnbondingDelegations {} => to_binary(&queries::unbonding_delegations(deps, env)?),
+        QueryMsg::UndelegationSlots {} => to_binary(&queries::undelegation_slots(deps, env)?),
+        QueryMsg::SimulateReconcile {} => to_binary(&queries::simulate_reconcile(deps, env)?),
+        QueryMsg::SlashingRecords { start_after, limit } => {
+            to_binary(&queries::slashing_records(deps, start_after, limit)?)
+        }
+        QueryMsg::BatchExpiry {} => {
+            to_binary(&State::default().batch_expiry.may_load(deps.storage)?)
+        }
+        QueryMsg::RewardDrip {} => to_binary(&queries::reward_drip(deps, env)?),
+        QueryMsg::PendingReinvest {} => to_binary(&queries::pending_reinvest(deps)?),
+        QueryMsg::SweptBatches { start_after, limit } => {
+            to_binary(&queries::swept_batches(deps, start_after, limit)?)
+        }
+        QueryMsg::ClockHarvestInterval {} => to_binary(
+            &State::default()
+                .clock_harvest_interval
+                .may_load(deps.storage)?,
+        ),
+        QueryMsg::Automation {} => to_binary(&queries::automation(deps)?),
+        QueryMsg::ContractInfo {} => to_binary(&queries::contract_info()),
+        QueryMsg::RewardDenoms {} => to_binary(&queries::reward_denoms(deps)?),
+        QueryMsg::RefundableDenoms {} => to_binary(&queries::refundable_denoms(deps)?),
+        QueryMsg::Surplus {} => to_binary(&queries::surplus(deps, env)?),
+        QueryMsg::Zapper {} => to_binary(&queries::zapper(deps)?),
+        QueryMsg::Metrics {} => to_binary(
+            &State::default()
+                .metrics
+                .may_load(deps.storage)?
+                .unwrap_or_default(),
+        ),
+        QueryMsg::EscrowedFees {} => to_binary(
+            &State::default()
+                .escrowed_fees
+                .may_load(deps.storage)?
+                .unwrap_or_default(),
+        ),
+        QueryMsg::Aggregate { queries } => to_binary(&aggregate(deps, env, queries)?),
     }
 }
 
+/// Run each of `queries`, recording its response or the error it failed with
+fn aggregate(deps: Deps, env: Env, queries: Vec<QueryMsg>) -> StdResult<AggregateResponse> {
+    if queries.len() > MAX_AGGREGATE_QUERIES {
+        return Err(StdError::generic_err(format!(
+            "at most {} queries can be aggregated",
+            MAX_AGGREGATE_QUERIES
+        )));
+    }
+
+    let results = queries
+        .into_iter()
+        .map(|msg| {
+            let res = match msg {
+                QueryMsg::Aggregate { .. } => {
+                    Err(StdError::generic_err("aggregate queries can not be nested"))
+                }
+                msg => query(deps, env.clone(), msg),
+            };
+            match res {
+                Ok(data) => AggregateResult {
+                    data: Some(data),
+                    error: None,
+                },
+                Err(err) => AggregateResult {
+                    data: None,
+                    error: Some(err.to_string()),
+                },
+            }
+        })
+        .collect();
+
+    Ok(AggregateResponse { results })
+}
+
 #[entry_point]
-pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    match msg {
+        MigrateMsg::Migrate {} => {
+            let report = run_migration(deps, env)?;
+            // refuse to upgrade a hub whose accounting is already inconsistent
+            if !report.invariants.healthy {
+                return Err(ContractError::InvariantsViolated {
+                    violations: report.invariants.violations,
+                });
+            }
+
+            Ok(Response::new()
+                .add_attribute("previous_contract_name", report.previous_contract_name)
+                .add_attribute(
+                    "previous_contract_version",
+                    report.previous_contract_version,
+                )
+                .add_attribute("new_contract_name", CONTRACT_NAME)
+                .add_attribute("new_contract_version", report.new_contract_version)
+                .add_attribute(
+                    "unbond_requests_reindexed",
+                    report.unbond_requests_reindexed.to_string(),
+                )
+                .add_attribute("mining_power_purged", report.mining_power_purged))
+        }
+        // the migration runs against a copy-on-write view of the state, and its writes are
+        // dropped along with the view. Failing also keeps the chain from switching the code
+        MigrateMsg::DryRun {} => {
+            let mut storage = DryRunStorage::new(deps.storage);
+            let mut report = run_migration(
+                DepsMut {
+                    storage: &mut storage,
+                    api: deps.api,
+                    querier: deps.querier,
+                },
+                env,
+            )?;
+            report.keys_written = storage.keys_written();
+
+            Err(ContractError::MigrationDryRun {
+                report: String::from_utf8(to_vec(&report)?).map_err(StdError::from)?,
+            })
+        }
+    }
+}
+
+fn run_migration(deps: DepsMut, env: Env) -> Result<MigrationReport, ContractError> {
     let contract_version = match get_contract_version(deps.storage) {
         Ok(version) => version,
         Err(_) => ContractVersion {
@@ -205,9 +999,16 @@ pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> StdResult<Response>
             version: "0".to_string(),
         },
     };
+    let mut batches_converted = 0;
     match contract_version.contract.as_ref() {
         #[allow(clippy::single_match)]
         "pfc-steak-hub" | "steak-hub" => match contract_version.version.as_ref() {
+            // the Terra codebase was released as 1.x, this one as 2.x
+            version if contract_version.contract == "steak-hub" && version.starts_with("1.") => {
+                batches_converted =
+                    LegacyTerraHub::upgrade_stores(deps.storage, &deps.querier, &env)?
+                        .batches_converted;
+            }
             #[allow(clippy::single_match)]
             "0" => {
                 let state = State::default();
@@ -221,17 +1022,29 @@ pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> StdResult<Response>
                 state
                     .fee_account_type
                     .save(deps.storage, &FeeType::Wallet)?;
-                ConfigV100::upgrade_stores(deps.storage, &deps.querier, env.contract.address)?;
+                ConfigV100::upgrade_stores(
+                    deps.storage,
+                    &deps.querier,
+                    env.contract.address.clone(),
+                )?;
             }
             "2.1.4" => {
                 let state = State::default();
-                ConfigV100::upgrade_stores(deps.storage, &deps.querier, env.contract.address)?;
+                ConfigV100::upgrade_stores(
+                    deps.storage,
+                    &deps.querier,
+                    env.contract.address.clone(),
+                )?;
                 state
                     .fee_account_type
                     .save(deps.storage, &FeeType::Wallet)?;
             }
             "2.1.5" => {
-                ConfigV100::upgrade_stores(deps.storage, &deps.querier, env.contract.address)?;
+                ConfigV100::upgrade_stores(
+                    deps.storage,
+                    &deps.querier,
+                    env.contract.address.clone(),
+                )?;
                 let state = State::default();
                 state
                     .fee_account_type
@@ -243,7 +1056,7 @@ pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> StdResult<Response>
                 let denom = state.denom.load(deps.storage)?;
                 state.prev_denom.save(
                     deps.storage,
-                    &get_denom_balance(&deps.querier, env.contract.address, denom)?,
+                    &get_denom_balance(&deps.querier, env.contract.address.clone(), denom)?,
                 )?;
 
                 state
@@ -288,12 +1101,19 @@ pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> StdResult<Response>
             }
             _ => {}
         },
-        _ => {
-            return Err(StdError::generic_err(
-                "contract name is not the same. aborting {}",
-            ))
+        name => {
+            return Err(ContractError::ContractNameMismatch {
+                name: name.to_string(),
+            })
         }
     }
+
+    // removals used to leave the validator's mining power behind
+    let mining_power_purged = execute::purge_orphaned_mining_power(deps.storage)?;
+    // requests queued before the `sender` index was added are missing from it
+    let unbond_requests_reindexed = execute::reindex_unbond_requests(deps.storage)?;
+
+    let invariants = queries::check_invariants(deps.as_ref(), env)?;
     /*
     let state = State::default();
 
@@ -303,9 +1123,14 @@ pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> StdResult<Response>
      */
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    Ok(Response::new()
-        .add_attribute("previous_contract_name", &contract_version.contract)
-        .add_attribute("previous_contract_version", &contract_version.version)
-        .add_attribute("new_contract_name", CONTRACT_NAME)
-        .add_attribute("new_contract_version", CONTRACT_VERSION))
+    Ok(MigrationReport {
+        previous_contract_name: contract_version.contract,
+        previous_contract_version: contract_version.version,
+        new_contract_version: CONTRACT_VERSION.to_string(),
+        unbond_requests_reindexed,
+        mining_power_purged,
+        batches_converted,
+        keys_written: 0,
+        invariants,
+    })
 }
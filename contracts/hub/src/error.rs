@@ -0,0 +1,466 @@
+use cosmwasm_std::{Decimal, OverflowError, StdError, Uint128};
+use thiserror::Error;
+
+use pfc_steak::hub::ProofRejection;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
+    //----------------------------------------------------------------------------------------------
+    // Authorization
+    //----------------------------------------------------------------------------------------------
+    #[error("unauthorized: sender is not owner")]
+    Unauthorized {},
+
+    #[error("unauthorized: sender is not new owner")]
+    UnauthorizedNewOwner {},
+
+    #[error("callbacks can only be invoked by the contract itself")]
+    CallbackOnly {},
+
+    #[error("only the contract itself or a whitelisted harvester can harvest rewards")]
+    HarvestOnly {},
+
+    #[error("{address} is already a harvester")]
+    HarvesterAlreadyRegistered { address: String },
+
+    #[error("{address} is not a harvester")]
+    HarvesterNotRegistered { address: String },
+
+    #[error("at most {max} harvesters can be whitelisted")]
+    TooManyHarvesters { max: usize },
+
+    #[error("{address} is already an integrator")]
+    IntegratorAlreadyRegistered { address: String },
+
+    #[error("{address} is not an integrator")]
+    IntegratorNotRegistered { address: String },
+
+    #[error("at most {max} integrators can be whitelisted")]
+    TooManyIntegrators { max: usize },
+
+    #[error("callbacks can not be requested while an integrator is being called back")]
+    IntegratorCallbackInProgress {},
+
+    #[error("at most {max} vote delegates can be set")]
+    TooManyVoteDelegates { max: usize },
+
+    #[error("{address} is not a vote delegate")]
+    NotVoteDelegate { address: String },
+
+    #[error("voting on proposal {proposal_id} ended")]
+    DelegatedVotingEnded { proposal_id: u64 },
+
+    #[error("proposal {proposal_id} is opted out of the delegated vote")]
+    DelegatedVoteOptedOut { proposal_id: u64 },
+
+    #[error("the hub already voted on proposal {proposal_id}")]
+    DelegatedVoteCast { proposal_id: u64 },
+
+    #[error("expecting {expected} token, received {received}")]
+    UnexpectedToken { expected: String, received: String },
+
+    #[error("invalid reply id: {id}; must be 1-7")]
+    InvalidReplyId { id: u64 },
+
+    //----------------------------------------------------------------------------------------------
+    // Funds
+    //----------------------------------------------------------------------------------------------
+    #[error("must deposit exactly one coin; received {count}")]
+    InvalidFundsCount { count: usize },
+
+    #[error("expected {expected} deposit, received {received}")]
+    UnexpectedDenom { expected: String, received: String },
+
+    #[error("expected {expected} deposit, also received {received}")]
+    UnexpectedFunds { expected: String, received: String },
+
+    #[error("{denom} is the staked denom and cannot be refunded")]
+    RefundableNativeDenom { denom: String },
+
+    #[error("deposit amount must be non-zero")]
+    ZeroDeposit {},
+
+    //----------------------------------------------------------------------------------------------
+    // Fees
+    //----------------------------------------------------------------------------------------------
+    #[error("Max fee can not exceed 1/100%")]
+    MaxFeeTooHigh {},
+
+    #[error("fee {fee} can not exceed max fee {max_fee}")]
+    FeeAboveMax { fee: Decimal, max_fee: Decimal },
+
+    #[error("Invalid Fee type: Wallet, FeeSplit or None only")]
+    InvalidFeeType {},
+
+    #[error("fees are disabled: the fee account type is None")]
+    FeesDisabled {},
+
+    #[error("{address} does not answer the queries of a fee split contract")]
+    NotFeeSplitContract { address: String },
+
+    #[error("no fees are escrowed")]
+    NoEscrowedFees {},
+
+    #[error("{name} can not exceed 100%")]
+    RateAboveOne { name: String },
+
+    #[error("a fee committee is already registered")]
+    FeeCommitteeExists {},
+
+    #[error("no fee committee is registered")]
+    FeeCommitteeNotSet {},
+
+    #[error("sender is not a member of the fee committee")]
+    NotCommitteeMember {},
+
+    #[error("threshold must be a two-thirds supermajority of the {members} members")]
+    InvalidCommitteeThreshold { members: usize },
+
+    #[error("the fee committee can have between 1 and {max} distinct members")]
+    InvalidCommitteeMembers { max: usize },
+
+    #[error("committee proposal {id} is not pending")]
+    CommitteeProposalNotFound { id: u64 },
+
+    #[error("committee proposal {id} is already approved by the sender")]
+    CommitteeProposalApproved { id: u64 },
+
+    //----------------------------------------------------------------------------------------------
+    // Staking and unbonding
+    //----------------------------------------------------------------------------------------------
+    #[error("no rewards")]
+    NoRewards {},
+
+    #[error("no native held beyond the hub's liabilities")]
+    NoSurplus {},
+
+    #[error("batch can only be submitted for unbonding after {ready_at}")]
+    BatchNotReady { ready_at: u64 },
+
+    #[error("batch must burn at least {min_size} usteak to be submitted")]
+    BatchTooSmall { min_size: Uint128 },
+
+    #[error("withdrawable amount is zero")]
+    NothingToWithdraw {},
+
+    #[error("no unbonding requests in reconciled batches to consolidate")]
+    NothingToConsolidate {},
+
+    #[error("receiver already holds {max} unbonding requests; withdraw or consolidate them first")]
+    TooManyUnbondRequests { max: u32 },
+
+    #[error("only the hub itself can queue unbonding requests for the hub")]
+    HubAsUnbondReceiver {},
+
+    #[error("validator is already whitelisted")]
+    ValidatorAlreadyWhitelisted { validator: String },
+
+    #[error("validator is not already whitelisted")]
+    ValidatorNotWhitelisted { validator: String },
+
+    #[error("validator address not found in staking module")]
+    ValidatorNotFound { validator: String },
+
+    #[error("no active validators")]
+    NoActiveValidators {},
+
+    #[error("validator {validator} is blocked: {reason}")]
+    ValidatorBlocked { validator: String, reason: String },
+
+    #[error("validator {validator} is not active")]
+    ValidatorNotActive { validator: String },
+
+    #[error("validator {validator} is not blocked")]
+    ValidatorNotBlocked { validator: String },
+
+    #[error("validator {validator} is being removed")]
+    ValidatorDraining { validator: String },
+
+    #[error("no validators are being removed")]
+    NoDrainingValidators {},
+
+    #[error("idle redelegation is not configured")]
+    IdleRedelegationNotConfigured {},
+
+    #[error("zero reward harvests must be non-zero")]
+    ZeroIdleHarvests {},
+
+    #[error("min idle period must be non-zero")]
+    ZeroIdlePeriod {},
+
+    #[error("no idle validators to redelegate from")]
+    NoIdleValidators {},
+
+    #[error("at least one reward denom must be given")]
+    NoRewardDenoms {},
+
+    #[error("reward denom {denom} is given more than once")]
+    DuplicateRewardDenom { denom: String },
+
+    #[error("a converter is required for rewards paid in other denoms than {denom}")]
+    RewardConverterRequired { denom: String },
+
+    #[error("batch expiry must be at least {min} seconds")]
+    BatchExpiryTooShort { min: u64 },
+
+    #[error("drift tolerance can not exceed {max} bps")]
+    DriftToleranceTooHigh { max: u16 },
+
+    #[error("voting power cap must be above zero and at most one")]
+    InvalidVotingPowerCap {},
+
+    #[error("max messages per transaction must be non-zero")]
+    ZeroMaxMsgsPerTx {},
+
+    #[error("the {max} largest delegations can not cover the batch; raise max_msgs_per_tx")]
+    BatchExceedsMaxMsgs { max: u32 },
+
+    #[error("reward drip period must be between 1 and {max} seconds")]
+    InvalidRewardDripPeriod { max: u64 },
+
+    #[error("batch expiry is not configured")]
+    BatchExpiryNotConfigured {},
+
+    #[error("batch {id} can only be swept once reconciled and after {expires_at}")]
+    BatchNotExpired { id: u64, expires_at: u64 },
+
+    #[error("batch {id} has nothing left to sweep")]
+    NothingToSweep { id: u64 },
+
+    #[error("hub has been wound down by an emergency undelegation")]
+    WoundDown {},
+
+    #[error("no emergency undelegation has been proposed")]
+    NoPendingEmergency {},
+
+    #[error("emergency undelegation can only be executed after {ready_at}")]
+    EmergencyTimelocked { ready_at: u64 },
+
+    #[error("batches can be shifted by at most {max} seconds")]
+    HaltShiftTooLarge { max: u64 },
+
+    #[error("halt recovery must shift batches or reset the mining clock")]
+    EmptyHaltRecovery {},
+
+    #[error("no halt recovery has been proposed")]
+    NoPendingHaltRecovery {},
+
+    #[error("halt recovery can only be applied after {ready_at}")]
+    HaltRecoveryTimelocked { ready_at: u64 },
+
+    #[error("hub is in safe mode; bonds, unbonding requests and proofs are paused")]
+    InSafeMode {},
+
+    #[error("safe mode must lift at a future height or time")]
+    InvalidSafeModeExpiry {},
+
+    #[error("no guardian change has been proposed")]
+    NoPendingGuardianChange {},
+
+    #[error("guardian change can only be applied after {ready_at}")]
+    GuardianChangeTimelocked { ready_at: u64 },
+
+    #[error("no denom change has been proposed")]
+    NoPendingDenomChange {},
+
+    #[error("denom can not be changed: {}", blockers.join("; "))]
+    DenomChangeBlocked { blockers: Vec<String> },
+
+    //----------------------------------------------------------------------------------------------
+    // Locked bonds
+    //----------------------------------------------------------------------------------------------
+    #[error("lock duration must be between 1 and {max} seconds")]
+    InvalidLockDuration { max: u64 },
+
+    #[error("locked bond {id} not found")]
+    LockedBondNotFound { id: u64 },
+
+    #[error("bond is locked until {unlock_time}")]
+    BondStillLocked { unlock_time: u64 },
+
+    #[error("claimable boost is zero")]
+    NoBoostToClaim {},
+
+    #[error("no expired locks to release")]
+    NothingToUnlock {},
+
+    #[error("invalid lock tiers: {reason}")]
+    InvalidLockTiers { reason: String },
+
+    //----------------------------------------------------------------------------------------------
+    // Yield splitting and rebasing
+    //----------------------------------------------------------------------------------------------
+    #[error("yield splitting is already enabled")]
+    YieldSplittingEnabled {},
+
+    #[error("yield splitting is not enabled")]
+    YieldSplittingNotEnabled {},
+
+    #[error("split amount is too small")]
+    SplitAmountTooSmall {},
+
+    #[error("no yield shares found")]
+    NoYieldShares {},
+
+    #[error("claimable yield is zero")]
+    NoYieldToClaim {},
+
+    #[error("insufficient rebasing balance")]
+    InsufficientRebasingBalance {
+        balance: Uint128,
+        requested: Uint128,
+    },
+
+    //----------------------------------------------------------------------------------------------
+    // Protocol-owned liquidity, liquid unstaking and arbitrage
+    //----------------------------------------------------------------------------------------------
+    #[error("protocol-owned liquidity is not configured")]
+    PolNotConfigured {},
+
+    #[error("liquidity must be removed before changing pairs")]
+    PolLiquidityNotRemoved {},
+
+    #[error("bond amount exceeds the native available")]
+    InsufficientPolNative { available: Uint128 },
+
+    #[error("both sides of the pair must be provided")]
+    PolAssetsMissing {},
+
+    #[error("no reserves to collect")]
+    NoPolReserves {},
+
+    #[error("no pair is configured for liquid unstaking")]
+    LiquidUnstakeNotConfigured {},
+
+    #[error("min discount must be greater than 0% and less than 100%")]
+    InvalidMinDiscount {},
+
+    #[error("amount exceeds the arbitrage reserve")]
+    InsufficientArbReserve { available: Uint128 },
+
+    #[error("no pair is configured for arbitrage")]
+    ArbPairNotConfigured {},
+
+    #[error("arbitrage is not configured")]
+    ArbNotConfigured {},
+
+    #[error("no arbitrage capacity available")]
+    NoArbCapacity {},
+
+    #[error("discount to the redemption rate is below the threshold")]
+    DiscountBelowThreshold { min_discount: Decimal },
+
+    #[error("no arbitrage is ready to be settled")]
+    NoArbToSettle {},
+
+    //----------------------------------------------------------------------------------------------
+    // Mining
+    //----------------------------------------------------------------------------------------------
+    #[error("epoch duration must be non-zero")]
+    ZeroEpochDuration {},
+
+    #[error("proof rejected ({reason}): hash {hash} against entropy {entropy} does not start with {prefix} required at difficulty {difficulty}")]
+    DifficultyNotMet {
+        reason: ProofRejection,
+        difficulty: u64,
+        prefix: String,
+        hash: String,
+        entropy: String,
+    },
+
+    #[error("miner can not submit another proof before height {next_height}")]
+    ProofRateLimited { next_height: u64 },
+
+    #[error("proof has already been accepted")]
+    ProofReplayed {},
+
+    #[error("allocation weights must be positive and sum to 100%")]
+    InvalidAllocationWeights {},
+
+    #[error("validator {validator} is allocated more than once")]
+    DuplicateAllocation { validator: String },
+
+    #[error("miner fee share can not exceed {max} bps")]
+    MinerFeeShareTooHigh { max: u16 },
+
+    #[error("entropy rate limit window must be non-zero")]
+    ZeroEntropyWindow {},
+
+    #[error("address can not contribute entropy again before {next_time}")]
+    EntropyRateLimited { next_time: u64 },
+
+    //----------------------------------------------------------------------------------------------
+    // Balance hooks
+    //----------------------------------------------------------------------------------------------
+    #[error("contract {contract} is already registered as a balance hook")]
+    BalanceHookAlreadyRegistered { contract: String },
+
+    #[error("contract {contract} is not registered as a balance hook")]
+    BalanceHookNotRegistered { contract: String },
+
+    #[error("at most {max} balance hooks can be registered")]
+    TooManyBalanceHooks { max: usize },
+
+    //----------------------------------------------------------------------------------------------
+    // Automation
+    //----------------------------------------------------------------------------------------------
+    #[error("no automation contract is configured")]
+    AutomationNotConfigured {},
+
+    #[error("the automation contract can not be changed while tasks are registered with it")]
+    AutomationTasksRegistered {},
+
+    #[error("{task} task is already registered")]
+    AutomationTaskExists { task: String },
+
+    #[error("{task} task is not registered")]
+    AutomationTaskNotFound { task: String },
+
+    #[error("only the automation contract can report its tasks")]
+    AutomationOnly {},
+
+    #[error("automation contract did not report a task hash")]
+    MissingTaskHash {},
+
+    #[error("clock harvest interval must be non-zero")]
+    ZeroClockHarvestInterval {},
+
+    //----------------------------------------------------------------------------------------------
+    // Coordinator
+    //----------------------------------------------------------------------------------------------
+    #[error("the hub already mints through a coordinator")]
+    CoordinatorAlreadySet {},
+
+    #[error("only a hub with no Steak outstanding can join a coordinator")]
+    HubNotEmpty {},
+
+    #[error("Steak shared through a coordinator must be unbonded through the coordinator")]
+    UnbondThroughCoordinator {},
+
+    #[error("deadline {deadline} has passed; block time is {now}")]
+    DeadlinePassed { deadline: u64, now: u64 },
+
+    #[error("unbonding is estimated to return {estimated}, less than the minimum {min_out}")]
+    MinOutNotMet {
+        estimated: Uint128,
+        min_out: Uint128,
+    },
+
+    //----------------------------------------------------------------------------------------------
+    // Migration
+    //----------------------------------------------------------------------------------------------
+    #[error("contract name is not the same. aborting {name}")]
+    ContractNameMismatch { name: String },
+
+    #[error("invariants do not hold, aborting migration: {}", violations.join("; "))]
+    InvariantsViolated { violations: Vec<String> },
+
+    #[error("migration dry run: {report}")]
+    MigrationDryRun { report: String },
+}
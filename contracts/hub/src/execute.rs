@@ -1,55 +1,146 @@
-use std::cmp::Ordering;
-use std::convert::TryInto;
-use std::ops::Mul;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use std::str::FromStr;
 
+#[cfg(feature = "debug-events")]
+use cosmwasm_std::{attr, Attribute};
 use cosmwasm_std::{
-    to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Decimal256, DepsMut, Env, Event, Order,
-    Response, StdError, StdResult, Storage, SubMsg, SubMsgResponse, Uint128, Uint64, WasmMsg,
+    to_binary, Addr, Api, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, Event,
+    GovMsg, Order, QuerierWrapper, Response, StdError, StdResult, Storage, SubMsg, SubMsgResponse,
+    SubMsgResult, Uint128, Uint64, VoteOption, WasmMsg,
 };
-use cw20::{Cw20ExecuteMsg, MinterResponse};
-use cw20_base::msg::InstantiateMsg as Cw20InstantiateMsg;
+use cw20::{Cw20ExecuteMsg, Logo, MinterResponse};
+use cw20_base::msg::{
+    InstantiateMarketingInfo as Cw20InstantiateMarketingInfo, InstantiateMsg as Cw20InstantiateMsg,
+};
+use cw_storage_plus::Bound;
 use sha2::{Digest, Sha256};
+use sha3::Keccak256;
 
-use crate::contract::{REPLY_INSTANTIATE_TOKEN, REPLY_REGISTER_RECEIVED_COINS};
+use crate::contract::{
+    REPLY_AUTOMATION_TASK_CREATED, REPLY_BALANCE_HOOK_FAILED, REPLY_CLOCK_TASK_FAILED,
+    REPLY_FEE_FORWARD_DONE, REPLY_INSTANTIATE_TOKEN, REPLY_INTEGRATOR_CALLBACK_DONE,
+    REPLY_REGISTER_RECEIVED_COINS,
+};
+use crate::error::ContractError;
+use pfc_steak::automation::{Action, AutomationExecuteMsg, Interval, TaskRequest};
+use pfc_steak::coordinator::{
+    ConfigResponse as CoordinatorConfigResponse, ExecuteMsg as CoordinatorExecuteMsg, HubSupply,
+    QueryMsg as CoordinatorQueryMsg, ReceiveMsg as CoordinatorReceiveMsg,
+};
 use pfc_steak::hub::{
-    Batch, CallbackMsg, ExecuteMsg, FeeType, InstantiateMsg, PendingBatch, UnbondRequest,
+    AdminAction, AdminActionRecord, AutomationTask, BalanceChange, BalanceHookMsg, Batch,
+    BondCallback, BondCallbackMsg, BondResponseData, CallbackMsg, CommitteeAction,
+    CommitteeProposal, CurrentMiner, DailySnapshot, DelegateBallot, DelegatedVote, EmergencyHeld,
+    EntropyContribution, EntropyRateLimit, ExchangeRateSnapshot, ExecuteMsg, FeeCommittee, FeeType,
+    GuardianChange, HaltRecovery, HistoryAction, IdleRedelegation, InstantiateMsg,
+    IntegratorCallback, IntegratorCallbackMsg, IntegratorFlow, LockTier, LockedBond, Metrics,
+    MiningAllocation, MiningSchedule, PendingBatch, ProofHash, ProofRejection, PruneKind,
+    RewardConverterMsg, RewardDrip, RoundingPolicy, SafeMode, SlashingRecord,
+    SubmitBatchResponseData, SurplusPolicy, SweptBatch, UnbondRequest, UserHistoryEntry,
+    ValidatorLoss, ValidatorSetAction, ValidatorSetChange, YieldPool, YieldPosition,
+};
+use pfc_steak::pair::{
+    Asset, AssetInfo, PairCw20HookMsg, PairExecuteMsg, PairQueryMsg, SimulationResponse,
 };
 use pfc_steak::DecimalCheckedOps;
 
 use crate::helpers::{
-    get_denom_balance, parse_received_fund, query_cw20_total_supply, query_delegation,
-    query_delegations,
+    get_denom_balance, normalize_denom, parse_received_fund, query_chain_unbondings,
+    query_chain_voting_power, query_cw20_balance, query_cw20_total_supply, query_delegation,
+    query_delegations, refund_msg, split_msgs,
 };
 use crate::math::{
-    compute_mint_amount, compute_redelegations_for_rebalancing, compute_redelegations_for_removal,
-    compute_target_delegation_from_mining_power, compute_unbond_amount, compute_undelegations,
-    reconcile_batches,
+    accrue_yield, cap_target_delegations, compute_fee_with_carry, compute_loss_attribution,
+    compute_miner_share, compute_mint_amount, compute_native_for_usteak,
+    compute_redelegations_for_rebalancing, compute_redelegations_for_removal,
+    compute_target_delegation, compute_unbond_amount, compute_undelegations,
+    compute_unreleased_rewards, compute_usteak_for_native, compute_voting_power_cap,
+    reconcile_batches, settle_yield_position,
 };
+use crate::queries::{denom_change_blockers, native_liabilities};
 use crate::state::State;
-use crate::types::{Coins, Delegation, RewardWithdrawal};
+use crate::types::{Coins, Delegation, RewardWithdrawal, Undelegation};
 
 // minimum amount of time it should take to mine a block (20 seconds)
 pub const TARGET_MINING_DURATION_FLOOR_SECONDS: u64 = 20u64;
 // maximum amount of time it should take to mine a block (5 minutes)
 pub const TARGET_MINING_DURATION_CEILING_SECONDS: u64 = 300u64;
+// number of recent proof intervals the difficulty is retargeted over
+pub const DIFFICULTY_RETARGET_WINDOW: usize = 8;
+// most levels the difficulty may move by in a single retarget
+pub const MAX_DIFFICULTY_ADJUSTMENT: u64 = 2;
+// each difficulty level requires one more leading zero hex digit, i.e. 16 times the work
+const DIFFICULTY_LEVEL_FACTOR: u64 = 16;
+// weight of each new observation in a validator's smoothed reward rate
+pub const REWARD_RATE_SMOOTHING_PERCENT: u64 = 20u64;
+// how long pauses and slashing incidents count against a validator's score (30 days)
+pub const SCORE_WINDOW_SECONDS: u64 = 2_592_000u64;
+// length of the period covered by each accounting snapshot
+pub const SECONDS_PER_DAY: u64 = 86_400u64;
+// time the owner must wait between proposing and executing an emergency undelegation (3 days)
+pub const EMERGENCY_UNDELEGATION_DELAY_SECONDS: u64 = 259_200u64;
+// time the owner must wait between proposing and applying a halt recovery (1 day)
+pub const HALT_RECOVERY_DELAY_SECONDS: u64 = 86_400u64;
+// how long a proposed guardian change must wait before it can be applied (1 day)
+pub const GUARDIAN_CHANGE_DELAY_SECONDS: u64 = 86_400u64;
+// most a halt recovery may delay batches by (14 days)
+pub const MAX_HALT_SHIFT_SECONDS: u64 = 1_209_600u64;
+// most contracts that may be notified of changes in Steak balances, bounding the cost of transfers
+pub const MAX_BALANCE_HOOKS: usize = 5;
+/// Maximum number of addresses whitelisted to trigger harvests
+pub const MAX_HARVESTERS: usize = 10;
+/// Maximum number of contracts whitelisted to request callbacks
+pub const MAX_INTEGRATORS: usize = 10;
+/// Maximum number of vote delegates
+pub const MAX_VOTE_DELEGATES: usize = 10;
+/// Maximum number of members of the fee committee
+pub const MAX_COMMITTEE_MEMBERS: usize = 20;
+/// Basis points in 100%
+pub const MAX_BPS: u16 = 10_000;
+/// Deviation from the target delegations past which rebalancing is recommended, unless configured
+pub const DEFAULT_DRIFT_TOLERANCE_BPS: u16 = 500;
+// maximum amount of time a bond can be locked for (4 years)
+pub const MAX_LOCK_DURATION_SECONDS: u64 = 126_144_000u64;
+// most tiers locks can be boosted by
+pub const MAX_LOCK_TIERS: usize = 10;
+// shortest time unclaimed funds must sit in a finished batch before they can be swept (90 days)
+pub const MIN_BATCH_EXPIRY_SECONDS: u64 = 7_776_000u64;
+// longest period reinvested rewards may be released over (7 days)
+pub const MAX_REWARD_DRIP_SECONDS: u64 = 604_800u64;
+// most entries a single `Prune` scans
+pub const MAX_PRUNE_LIMIT: u32 = 100;
+// how long daily snapshots are kept before they can be pruned
+pub const SNAPSHOT_RETENTION_DAYS: u64 = 365;
+// unbonding entries the staking module allows per delegator and validator (`MaxEntries`)
+pub const MAX_UNBONDING_ENTRIES: u32 = 7;
 
 //--------------------------------------------------------------------------------------------------
 // Instantiation
 //--------------------------------------------------------------------------------------------------
 
-pub fn instantiate(deps: DepsMut, env: Env, msg: InstantiateMsg) -> StdResult<Response> {
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
     let state = State::default();
 
     if msg.max_fee_amount > Decimal::from_str("1.00")? {
-        return Err(StdError::generic_err("Max fee can not exceed 1/100%"));
+        return Err(ContractError::MaxFeeTooHigh {});
     }
 
     if msg.fee_amount > msg.max_fee_amount {
-        return Err(StdError::generic_err("fee can not exceed max fee"));
+        return Err(ContractError::FeeAboveMax {
+            fee: msg.fee_amount,
+            max_fee: msg.max_fee_amount,
+        });
+    }
+    let fee_type =
+        FeeType::from_str(&msg.fee_account_type).map_err(|_| ContractError::InvalidFeeType {})?;
+    if fee_type == FeeType::None && !msg.fee_amount.is_zero() {
+        return Err(ContractError::FeesDisabled {});
     }
-    let fee_type = FeeType::from_str(&msg.fee_account_type)
-        .map_err(|_| StdError::generic_err("Invalid Fee type: Wallet or FeeSplit only"))?;
 
     state
         .owner
@@ -64,9 +155,16 @@ pub fn instantiate(deps: DepsMut, env: Env, msg: InstantiateMsg) -> StdResult<Re
     state.fee_rate.save(deps.storage, &msg.fee_amount)?;
     state.fee_account_type.save(deps.storage, &fee_type)?;
 
-    state
-        .fee_account
-        .save(deps.storage, &deps.api.addr_validate(&msg.fee_account)?)?;
+    // with the fee module disabled there is no fee account; the hub stands in for it
+    let fee_account = match fee_type {
+        FeeType::None => env.contract.address.clone(),
+        _ => deps.api.addr_validate(&msg.fee_account)?,
+    };
+    state.fee_account.save(deps.storage, &fee_account)?;
+    let token_admin = deps
+        .api
+        .addr_validate(msg.token_admin.as_ref().unwrap_or(&msg.owner))?;
+    state.token_admin.save(deps.storage, &token_admin)?;
 
     state.pending_batch.save(
         deps.storage,
@@ -79,6 +177,17 @@ pub fn instantiate(deps: DepsMut, env: Env, msg: InstantiateMsg) -> StdResult<Re
     state
         .validators_active
         .save(deps.storage, &msg.validators)?;
+    let owner = state.owner.load(deps.storage)?;
+    for validator in &msg.validators {
+        record_validator_set_change(
+            deps.storage,
+            &env,
+            validator,
+            ValidatorSetAction::Add,
+            &owner,
+            None,
+        )?;
+    }
 
     state.miner_entropy.save(
         deps.storage,
@@ -91,6 +200,9 @@ pub fn instantiate(deps: DepsMut, env: Env, msg: InstantiateMsg) -> StdResult<Re
         &env.contract.address.to_string(),
     )?;
 
+    state
+        .proof_hash
+        .save(deps.storage, &msg.proof_hash.unwrap_or_default())?;
     // difficulty starts at one
     state.miner_difficulty.save(deps.storage, &1u64.into())?;
     // last mined block starts at current timestamp
@@ -108,7 +220,8 @@ pub fn instantiate(deps: DepsMut, env: Env, msg: InstantiateMsg) -> StdResult<Re
 
     Ok(Response::new().add_submessage(SubMsg::reply_on_success(
         CosmosMsg::Wasm(WasmMsg::Instantiate {
-            admin: Some(msg.owner), // use the owner as admin for now; can be changed later by a `MsgUpdateAdmin`
+            // can be changed later by a `MsgUpdateAdmin`
+            admin: Some(token_admin.into()),
             code_id: msg.cw20_code_id,
             msg: to_binary(&Cw20InstantiateMsg {
                 name: msg.name,
@@ -116,10 +229,19 @@ pub fn instantiate(deps: DepsMut, env: Env, msg: InstantiateMsg) -> StdResult<Re
                 decimals: msg.decimals,
                 initial_balances: vec![],
                 mint: Some(MinterResponse {
-                    minter: env.contract.address.into(),
+                    minter: env.contract.address.to_string(),
                     cap: None,
                 }),
-                marketing: msg.marketing,
+                // the hub administers the token's marketing info on the owner's behalf
+                marketing: Some(Cw20InstantiateMarketingInfo {
+                    marketing: Some(env.contract.address.to_string()),
+                    ..msg.marketing.unwrap_or(Cw20InstantiateMarketingInfo {
+                        project: None,
+                        description: None,
+                        marketing: None,
+                        logo: None,
+                    })
+                }),
             })?,
             funds: vec![],
             label: msg.label.unwrap_or_else(|| "steak_token".to_string()),
@@ -128,7 +250,10 @@ pub fn instantiate(deps: DepsMut, env: Env, msg: InstantiateMsg) -> StdResult<Re
     )))
 }
 
-pub fn register_steak_token(deps: DepsMut, response: SubMsgResponse) -> StdResult<Response> {
+pub fn register_steak_token(
+    deps: DepsMut,
+    response: SubMsgResponse,
+) -> Result<Response, ContractError> {
     let state = State::default();
 
     let event = response
@@ -162,34 +287,186 @@ pub fn register_steak_token(deps: DepsMut, response: SubMsgResponse) -> StdResul
 /// smallest amount of delegation. If delegations become severely unbalance as a result of this
 /// (e.g. when a single user makes a very big deposit), anyone can invoke `ExecuteMsg::Rebalance`
 /// to balance the delegations.
-pub fn bond(deps: DepsMut, env: Env, receiver: Addr, funds: Vec<Coin>) -> StdResult<Response> {
+pub fn bond(
+    deps: DepsMut,
+    env: Env,
+    funder: Addr,
+    receiver: Addr,
+    funds: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    let (response, _) = bond_internal(deps, env, funder, receiver, funds)?;
+    Ok(response)
+}
+
+/// Same as `bond`, but once the Steak is minted, `contract` is invoked with the minted amount so that
+/// zappers and vaults don't need to diff balances to learn it
+pub fn bond_and_call(
+    deps: DepsMut,
+    env: Env,
+    funder: Addr,
+    receiver: Addr,
+    contract: Addr,
+    callback: Binary,
+    funds: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    let amount_bonded = deposited_amount(deps.storage, &funds)?;
+    let (response, usteak_minted) = bond_internal(deps, env, funder, receiver.clone(), funds)?;
+
+    let callback_msg = BondCallbackMsg::SteakBondCallback(BondCallback {
+        receiver: receiver.to_string(),
+        amount_bonded,
+        usteak_minted,
+        msg: callback,
+    })
+    .into_cosmos_msg(contract.to_string())?;
+
+    Ok(response
+        .add_message(callback_msg)
+        .add_attribute("callback_contract", contract))
+}
+
+/// Same as `bond`, but once bonding has completed the integrator is called back with the hub's
+/// state, so that routers can act on the post-bond exchange rate without a second transaction
+pub fn bond_with_callback(
+    deps: DepsMut,
+    env: Env,
+    integrator: Addr,
+    receiver: Addr,
+    callback: Binary,
+    funds: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_integrator(deps.storage, &integrator)?;
+
+    let contract_addr = env.contract.address.clone();
+    let amount_bonded = deposited_amount(deps.storage, &funds)?;
+    let (response, usteak_minted) =
+        bond_internal(deps, env, integrator.clone(), receiver.clone(), funds)?;
+
+    // Dispatched last, so that it only runs once the delegation and the mint have been executed
+    let notify_msg = CallbackMsg::NotifyIntegrator {
+        integrator: integrator.to_string(),
+        flow: IntegratorFlow::Bond {
+            receiver: receiver.into(),
+            amount_bonded,
+            usteak_minted,
+        },
+        callback,
+    }
+    .into_cosmos_msg(&contract_addr)?;
+
+    Ok(response
+        .add_message(notify_msg)
+        .add_attribute("callback_contract", integrator))
+}
+
+/// Call back an integrator once the flow it requested the callback for has completed. The
+/// integrator must still be whitelisted, and may not request further callbacks until its callback
+/// has returned
+pub fn notify_integrator(
+    deps: DepsMut,
+    env: Env,
+    integrator: Addr,
+    flow: IntegratorFlow,
+    callback: Binary,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_integrator(deps.storage, &integrator)?;
+
+    let hub_state = crate::queries::state(deps.as_ref(), env)?;
+    let callback_msg = IntegratorCallbackMsg::SteakIntegratorCallback(IntegratorCallback {
+        flow,
+        total_usteak: hub_state.total_usteak,
+        total_native: hub_state.total_native,
+        exchange_rate: hub_state.exchange_rate,
+        msg: callback,
+    })
+    .into_cosmos_msg(integrator.to_string())?;
+
+    // Cleared once the callback succeeds; should it fail, the whole transaction reverts anyway
+    state.integrator_callback.save(deps.storage, &integrator)?;
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(
+            callback_msg,
+            REPLY_INTEGRATOR_CALLBACK_DONE,
+        ))
+        .add_attribute("action", "steakhub/notify_integrator")
+        .add_attribute("integrator", integrator))
+}
+
+pub fn clear_integrator_callback(deps: DepsMut) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.integrator_callback.remove(deps.storage);
+
+    Ok(Response::new())
+}
+
+/// Delegates the deposit and mints Steak to `receiver`, returning the response along with the amount
+/// of usteak minted, so that callers building on top of bonding don't have to recompute it
+fn bond_internal(
+    deps: DepsMut,
+    env: Env,
+    funder: Addr,
+    receiver: Addr,
+    funds: Vec<Coin>,
+) -> Result<(Response, Uint128), ContractError> {
     let state = State::default();
+    state.assert_not_wound_down(deps.storage)?;
+    state.assert_not_in_safe_mode(deps.storage, &env.block)?;
     let denom = state.denom.load(deps.storage)?;
-    let amount_to_bond = parse_received_fund(&funds, &denom)?;
-    let steak_token = state.steak_token.load(deps.storage)?;
+    let (amount_to_bond, refunds) = received_deposit(deps.storage, &funds, &denom)?;
     let validators = state.validators_active.load(deps.storage)?;
 
-    // Query the current delegations made to validators, and find the validator with the smallest
-    // delegated amount through a linear search
-    // The code for linear search is a bit uglier than using `sort_by` but cheaper: O(n) vs O(n * log(n))
     let delegations = query_delegations(&deps.querier, &validators, &env.contract.address, &denom)?;
-    let mut validator = &delegations[0].validator;
-    let mut amount = delegations[0].amount;
-    for d in &delegations[1..] {
-        if d.amount < amount {
-            validator = &d.validator;
-            amount = d.amount;
-        }
-    }
+    let receiving = receiving_delegations(deps.storage, &delegations)?;
+    // validators the deposit would lift above their voting power cap are passed over, unless all are
+    let caps = voting_power_caps(deps.storage, &delegations)?.unwrap_or_default();
+    let below_cap = receiving
+        .iter()
+        .filter(|d| {
+            caps.get(&d.validator)
+                .map_or(true, |cap| d.amount + amount_to_bond.u128() <= cap.u128())
+        })
+        .cloned()
+        .collect::<Vec<_>>();
     let new_delegation = Delegation {
-        validator: validator.clone(),
+        validator: smallest_delegation(if below_cap.is_empty() {
+            &receiving
+        } else {
+            &below_cap
+        })
+        .validator
+        .clone(),
         amount: amount_to_bond.u128(),
         denom: denom.clone(),
     };
 
     // Query the current supply of Steak and compute the amount to mint
-    let usteak_supply = query_cw20_total_supply(&deps.querier, &steak_token)?;
-    let usteak_to_mint = compute_mint_amount(usteak_supply, amount_to_bond, &delegations);
+    let usteak_supply = query_usteak_supply(deps.storage, &deps.querier, &env.contract.address)?;
+    record_daily_snapshot(deps.storage, &env, usteak_supply, &delegations)?;
+    let rounding = state
+        .rounding_policy
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let rate = block_exchange_rate(deps.storage, &env, usteak_supply, &delegations)?;
+    let usteak_to_mint = compute_mint_amount(
+        rate.usteak_supply,
+        amount_to_bond,
+        rate.native_bonded,
+        rounding,
+    )?;
+    record_history(
+        deps.storage,
+        &env,
+        &receiver,
+        HistoryAction::Bond,
+        amount_to_bond,
+        usteak_to_mint,
+    )?;
     state.prev_denom.save(
         deps.storage,
         &get_denom_balance(&deps.querier, env.contract.address.clone(), denom.clone())?,
@@ -200,46 +477,151 @@ pub fn bond(deps: DepsMut, env: Env, receiver: Addr, funds: Vec<Coin>) -> StdRes
         REPLY_REGISTER_RECEIVED_COINS,
     );
 
-    let mint_msg: CosmosMsg = CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: steak_token.into(),
-        msg: to_binary(&Cw20ExecuteMsg::Mint {
-            recipient: receiver.to_string(),
-            amount: usteak_to_mint,
-        })?,
-        funds: vec![],
-    });
+    let mint_msg = usteak_mint_msg(deps.storage, &receiver, usteak_to_mint)?;
+    let refund_msg = refund_msg(&funder, refunds);
+
+    let data = BondResponseData {
+        receiver: receiver.to_string(),
+        amount_bonded: amount_to_bond,
+        usteak_minted: usteak_to_mint,
+        exchange_rate: if rate.usteak_supply.is_zero() {
+            Decimal::one()
+        } else {
+            Decimal::from_ratio(rate.native_bonded, rate.usteak_supply)
+        },
+    };
 
     let event = Event::new("steakhub/bonded")
         .add_attribute("time", env.block.time.seconds().to_string())
         .add_attribute("height", env.block.height.to_string())
+        .add_attribute("funder", funder)
         .add_attribute("receiver", receiver)
         .add_attribute("denom_bonded", denom)
         .add_attribute("denom_amount", amount_to_bond)
         .add_attribute("usteak_minted", usteak_to_mint);
 
-    Ok(Response::new()
-        .add_submessage(delegate_submsg)
-        .add_message(mint_msg)
-        .add_event(event)
-        .add_attribute("action", "steakhub/bond"))
+    Ok((
+        Response::new()
+            .add_submessage(delegate_submsg)
+            .add_message(mint_msg)
+            .add_messages(refund_msg)
+            .add_event(event)
+            .add_attribute("action", "steakhub/bond")
+            .set_data(to_binary(&data)?),
+        usteak_to_mint,
+    ))
 }
 
-pub fn harvest(deps: DepsMut, env: Env, sender: Addr) -> StdResult<Response> {
-    if sender != env.contract.address {
-        return Err(StdError::generic_err(
-            "only the contract itself can harvest rewards for DPOW",
-        ));
+/// Amount of `denom` deposited along with `funds`, and the coins riding along it to be refunded
+fn received_deposit(
+    storage: &dyn Storage,
+    funds: &[Coin],
+    denom: &str,
+) -> Result<(Uint128, Vec<Coin>), ContractError> {
+    let refundable = State::default()
+        .refundable_denoms
+        .may_load(storage)?
+        .unwrap_or_default();
+    parse_received_fund(funds, denom, &refundable)
+}
+
+/// Amount of the Native Token in `funds`, leaving out the coins that are refunded
+fn deposited_amount(storage: &dyn Storage, funds: &[Coin]) -> StdResult<Uint128> {
+    let denom = State::default().denom.load(storage)?;
+    Ok(funds
+        .iter()
+        .filter(|coin| coin.denom == denom)
+        .map(|coin| coin.amount)
+        .sum())
+}
+
+/// Find the validator with the smallest delegated amount through a linear search
+///
+/// The code for linear search is a bit uglier than using `sort_by` but cheaper: O(n) vs O(n * log(n))
+fn smallest_delegation(delegations: &[Delegation]) -> &Delegation {
+    let mut smallest = &delegations[0];
+    for d in &delegations[1..] {
+        if d.amount < smallest.amount {
+            smallest = d;
+        }
+    }
+    smallest
+}
+
+/// Delegations to the validators that can receive new stake, leaving out the ones paused from
+/// receiving only
+pub(crate) fn receiving_delegations(
+    storage: &dyn Storage,
+    delegations: &[Delegation],
+) -> Result<Vec<Delegation>, ContractError> {
+    let paused = State::default()
+        .receive_paused_validators
+        .may_load(storage)?
+        .unwrap_or_default();
+    let receiving = delegations
+        .iter()
+        .filter(|d| !paused.contains(&d.validator))
+        .cloned()
+        .collect::<Vec<_>>();
+    if receiving.is_empty() {
+        return Err(ContractError::NoActiveValidators {});
     }
+    Ok(receiving)
+}
+
+pub fn harvest(deps: DepsMut, env: Env, sender: Addr) -> Result<Response, ContractError> {
     let state = State::default();
+    let is_automation = state.automation_contract.may_load(deps.storage)?.as_ref() == Some(&sender);
+    if sender != env.contract.address
+        && !is_automation
+        && !state
+            .harvesters
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+            .contains(&sender)
+    {
+        return Err(ContractError::HarvestOnly {});
+    }
+    state.assert_not_wound_down(deps.storage)?;
     let denom = state.denom.load(deps.storage)?;
+    // rebates deposited and rewards deferred since the last harvest are left out of the balance the
+    // reinvest measures rewards against, so that they are reinvested along with them
+    let pending_rebates = state
+        .pending_rebates
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    state.pending_rebates.remove(deps.storage);
+    let pending_reinvest = state
+        .pending_reinvest
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    state.pending_reinvest.remove(deps.storage);
     state.prev_denom.save(
         deps.storage,
-        &get_denom_balance(&deps.querier, env.contract.address.clone(), denom)?,
+        &get_denom_balance(&deps.querier, env.contract.address.clone(), denom.clone())?
+            .saturating_sub(pending_rebates + pending_reinvest),
     )?;
 
-    let withdraw_submsgs = deps
-        .querier
-        .query_all_delegations(&env.contract.address)?
+    // past `max_msgs_per_tx`, each harvest withdraws from the validators after the last one the
+    // previous harvest withdrew from; the others keep accruing until their turn
+    let mut delegations = deps.querier.query_all_delegations(&env.contract.address)?;
+    delegations.sort_by(|a, b| a.validator.cmp(&b.validator));
+    if let Some(cursor) = state.harvest_cursor.may_load(deps.storage)? {
+        let start = delegations
+            .iter()
+            .position(|d| d.validator > cursor)
+            .unwrap_or(0);
+        delegations.rotate_left(start);
+    }
+    let (delegations, deferred) =
+        split_msgs(delegations, state.max_msgs_per_tx.may_load(deps.storage)?);
+    match delegations.last().filter(|_| !deferred.is_empty()) {
+        Some(last) => state.harvest_cursor.save(deps.storage, &last.validator)?,
+        None => state.harvest_cursor.remove(deps.storage),
+    }
+    record_validator_rewards(deps.storage, &deps.querier, &env, &denom, &delegations)?;
+
+    let withdraw_submsgs = delegations
         .into_iter()
         .map(|d| -> StdResult<SubMsg> {
             Ok(SubMsg::reply_on_success(
@@ -252,24 +634,210 @@ pub fn harvest(deps: DepsMut, env: Env, sender: Addr) -> StdResult<Response> {
         })
         .collect::<StdResult<Vec<SubMsg>>>()?;
 
+    // rewards paid in other denoms are converted to the Native Token before they are reinvested
+    let reward_denoms = state
+        .reward_denoms
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let convert_msg = if reward_denoms.iter().any(|d| *d != denom)
+        && state.reward_converter.may_load(deps.storage)?.is_some()
+    {
+        Some(CallbackMsg::ConvertRewards {}.into_cosmos_msg(&env.contract.address)?)
+    } else {
+        None
+    };
     let callback_msg = CallbackMsg::Reinvest {}.into_cosmos_msg(&env.contract.address)?;
 
+    let event = Event::new("steakhub/harvest_triggered")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("triggered_by", sender)
+        .add_attributes(
+            (!deferred.is_empty()).then(|| ("withdrawals_deferred", deferred.len().to_string())),
+        );
+
     Ok(Response::new()
         .add_submessages(withdraw_submsgs)
+        .add_messages(convert_msg)
         .add_message(callback_msg)
+        .add_event(event)
         .add_attribute("action", "steakhub/harvest"))
 }
 
+/// Send the rewards received in reward denoms other than the Native Token to the converter. What it
+/// sends back arrives before `reinvest` measures the balance, so it is reinvested along with the
+/// rewards paid in the Native Token
+pub fn convert_rewards(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let state = State::default();
+    state.assert_not_wound_down(deps.storage)?;
+    let denom = state.denom.load(deps.storage)?;
+    let reward_denoms = state
+        .reward_denoms
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let converter = state.reward_converter.load(deps.storage)?;
+
+    let mut unlocked_coins = state.unlocked_coins.load(deps.storage)?;
+    let mut to_convert: Vec<Coin> = unlocked_coins
+        .iter()
+        .filter(|coin| coin.denom != denom && reward_denoms.contains(&coin.denom))
+        .filter(|coin| !coin.amount.is_zero())
+        .cloned()
+        .collect();
+    if to_convert.is_empty() {
+        return Ok(Response::new().add_attribute("action", "steakhub/convert_rewards"));
+    }
+    to_convert.sort_by(|a, b| a.denom.cmp(&b.denom));
+    unlocked_coins.retain(|coin| !to_convert.iter().any(|c| c.denom == coin.denom));
+    state.unlocked_coins.save(deps.storage, &unlocked_coins)?;
+
+    let event = Event::new("steakhub/rewards_converted")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("converter", &converter)
+        .add_attribute(
+            "coins_in",
+            to_convert
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        )
+        .add_attribute("denom_out", &denom);
+
+    let convert_msg = RewardConverterMsg::ConvertRewards { denom_out: denom }
+        .into_cosmos_msg(converter.to_string(), to_convert)?;
+
+    Ok(Response::new()
+        .add_message(convert_msg)
+        .add_event(event)
+        .add_attribute("action", "steakhub/convert_rewards"))
+}
+
+pub fn deposit_rebate(
+    deps: DepsMut,
+    sender: Addr,
+    validator: String,
+    funds: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_not_wound_down(deps.storage)?;
+    let validators = state.validators.load(deps.storage)?;
+    if !validators.contains(&validator) {
+        return Err(ContractError::ValidatorNotWhitelisted { validator });
+    }
+    let denom = state.denom.load(deps.storage)?;
+    let (amount, refunds) = received_deposit(deps.storage, &funds, &denom)?;
+
+    let pending_rebates = state
+        .pending_rebates
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    state
+        .pending_rebates
+        .save(deps.storage, &(pending_rebates + amount))?;
+    let rebates = state
+        .validator_rebates
+        .may_load(deps.storage, validator.clone())?
+        .unwrap_or_default()
+        + amount;
+    state
+        .validator_rebates
+        .save(deps.storage, validator.clone(), &rebates)?;
+
+    let refund_msg = refund_msg(&sender, refunds);
+    let event = Event::new("steakhub/rebate_deposited")
+        .add_attribute("validator", validator)
+        .add_attribute("depositor", sender)
+        .add_attribute("amount", amount)
+        .add_attribute("total_rebates", rebates);
+
+    Ok(Response::new()
+        .add_messages(refund_msg)
+        .add_event(event)
+        .add_attribute("action", "steakhub/deposit_rebate"))
+}
+
+/// Fold the rewards about to be withdrawn from each validator into its smoothed reward rate
+fn record_validator_rewards(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+    denom: &str,
+    delegations: &[cosmwasm_std::Delegation],
+) -> StdResult<()> {
+    let state = State::default();
+    let now = env.block.time.seconds();
+
+    for d in delegations {
+        let mut performance = state
+            .validator_performance
+            .may_load(storage, d.validator.clone())?
+            .unwrap_or_default();
+        // the first harvest only marks the start of the observation period
+        if performance.last_observed > 0
+            && now > performance.last_observed
+            && !d.amount.amount.is_zero()
+        {
+            let rewards: Uint128 = querier
+                .query_delegation(&env.contract.address, &d.validator)?
+                .map(|fd| {
+                    fd.accumulated_rewards
+                        .iter()
+                        .filter(|c| c.denom == denom)
+                        .map(|c| c.amount)
+                        .sum()
+                })
+                .unwrap_or_default();
+            let observed = Decimal::from_ratio(
+                rewards.checked_mul(SECONDS_PER_DAY.into())?,
+                d.amount
+                    .amount
+                    .checked_mul((now - performance.last_observed).into())?,
+            );
+            let smoothing = Decimal::percent(REWARD_RATE_SMOOTHING_PERCENT);
+            performance.reward_rate = if performance.observations == 0 {
+                observed
+            } else {
+                performance.reward_rate * (Decimal::one() - smoothing) + observed * smoothing
+            };
+            performance.observations += 1;
+            if rewards.is_zero() {
+                // a streak recorded before `idle_since` was tracked is counted from here on
+                if performance.zero_reward_harvests == 0 || performance.idle_since.is_none() {
+                    performance.idle_since = Some(performance.last_observed);
+                }
+                performance.zero_reward_harvests += 1;
+            } else {
+                performance.zero_reward_harvests = 0;
+                performance.idle_since = None;
+            }
+        }
+        performance.last_observed = now;
+        state
+            .validator_performance
+            .save(storage, d.validator.clone(), &performance)?;
+    }
+
+    Ok(())
+}
+
 /// NOTE:
 /// 1. When delegation Native denom here, we don't need to use a `SubMsg` to handle the received coins,
-/// because we have already withdrawn all claimable staking rewards previously in the same atomic
-/// execution.
+///    because we have already withdrawn all claimable staking rewards previously in the same atomic
+///    execution.
 /// 2. Same as with `bond`, in the latest implementation we only delegate staking rewards with the
-/// validator that has the smallest delegation amount.
-pub fn reinvest(deps: DepsMut, env: Env) -> StdResult<Response> {
+///    validator that has the smallest delegation amount.
+pub fn reinvest(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
     let state = State::default();
+    state.assert_not_wound_down(deps.storage)?;
     let denom = state.denom.load(deps.storage)?;
-    let fee = state.fee_rate.load(deps.storage)?;
+    // with the fee module disabled, every fee below comes out zero and nothing is forwarded
+    let fee = match state.fee_account_type.load(deps.storage)? {
+        FeeType::None => Decimal::zero(),
+        _ => state.fee_rate.load(deps.storage)?,
+    };
 
     let validators = state.validators_active.load(deps.storage)?;
     let prev_coin = state.prev_denom.load(deps.storage)?;
@@ -277,11 +845,32 @@ pub fn reinvest(deps: DepsMut, env: Env) -> StdResult<Response> {
         get_denom_balance(&deps.querier, env.contract.address.clone(), denom.clone())?;
 
     if current_coin <= prev_coin {
-        return Err(StdError::generic_err("no rewards"));
+        return Err(ContractError::NoRewards {});
     }
     let amount_to_bond = current_coin.saturating_sub(prev_coin);
     let mut unlocked_coins = state.unlocked_coins.load(deps.storage)?;
 
+    // rather than delegating dust, small rewards are kept for the next reinvest to fold in
+    let min_reinvest_amount = state
+        .min_reinvest_amount
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if amount_to_bond < min_reinvest_amount {
+        state.pending_reinvest.save(deps.storage, &amount_to_bond)?;
+        unlocked_coins.retain(|coin| coin.denom != denom);
+        state.unlocked_coins.save(deps.storage, &unlocked_coins)?;
+
+        let event = Event::new("steakhub/reinvest_deferred")
+            .add_attribute("time", env.block.time.seconds().to_string())
+            .add_attribute("height", env.block.height.to_string())
+            .add_attribute("pending_reinvest", amount_to_bond)
+            .add_attribute("min_reinvest_amount", min_reinvest_amount);
+
+        return Ok(Response::new()
+            .add_event(event)
+            .add_attribute("action", "steakhub/reinvest"));
+    }
+
     /*
 
         if unlocked_coins.is_empty() {
@@ -299,53 +888,66 @@ pub fn reinvest(deps: DepsMut, env: Env) -> StdResult<Response> {
         .unwrap_or_default();
     let delegations = query_delegations(&deps.querier, &validators, &env.contract.address, &denom)?;
     let total_bonded = delegations.iter().fold(0u128, |acc, d| acc + d.amount);
-    let mut validator = &delegations[0].validator;
-    let validator_mining_power = state
-        .validator_mining_powers
-        .may_load(deps.storage, validator.to_string())?
-        .unwrap_or_default();
-    let target_delegation = compute_target_delegation_from_mining_power(
-        total_bonded.into(),
-        validator_mining_power,
-        total_mining_power,
-    )?;
-    println!(
-        "total mining power: {} total bonded: {}",
-        total_mining_power, total_bonded
-    );
-
-    let mut cmp = target_delegation.u128().cmp(&delegations[0].amount);
+    // until the first proof is accepted, the rewards go to whichever validator is furthest below an
+    // even split. Targets are computed for every validator, so that what the voting power cap cuts
+    // from some is spread over the others
+    let targets = delegations
+        .iter()
+        .map(|d| {
+            let mining_power = state
+                .validator_mining_powers
+                .may_load(deps.storage, d.validator.clone())?
+                .unwrap_or_default();
+            let target = compute_target_delegation(
+                total_bonded.into(),
+                mining_power,
+                total_mining_power,
+                delegations.len(),
+            )?;
+            Ok((d.validator.clone(), target))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    let targets = apply_voting_power_caps(deps.storage, &delegations, targets)?;
+    let target_for = |validator: &str| {
+        targets
+            .iter()
+            .find(|(v, _)| v == validator)
+            .map(|(_, target)| *target)
+            .unwrap_or_default()
+    };
+    let candidates = receiving_delegations(deps.storage, &delegations)?;
+    let mut validator = &candidates[0].validator;
+    let target_delegation = target_for(validator);
+    #[cfg(feature = "debug-events")]
+    let mut debug_attrs = vec![
+        attr("total_mining_power", total_mining_power),
+        attr("total_bonded", total_bonded.to_string()),
+    ];
+
+    let mut cmp = target_delegation.u128().cmp(&candidates[0].amount);
     let mut diff = if cmp.is_gt() {
-        target_delegation.u128().abs_diff(delegations[0].amount)
+        target_delegation.u128().abs_diff(candidates[0].amount)
     } else {
         0u128
     };
-    println!(
-        "validator: {} amount: {} target: {} diff: {}",
+    #[cfg(feature = "debug-events")]
+    debug_attrs.push(debug_validator_attr(
         validator,
-        delegations[0].amount,
+        candidates[0].amount,
         target_delegation.u128(),
-        diff
-    );
+        diff,
+    ));
 
-    for d in &delegations[1..] {
-        let current_validator_mining_power = state
-            .validator_mining_powers
-            .may_load(deps.storage, d.validator.to_string())?
-            .unwrap_or_default();
-        let current_td = compute_target_delegation_from_mining_power(
-            total_bonded.into(),
-            current_validator_mining_power,
-            total_mining_power,
-        )?;
+    for d in &candidates[1..] {
+        let current_td = target_for(&d.validator);
         let current_diff = current_td.u128().abs_diff(d.amount);
-        println!(
-            "validator: {} amount: {} target: {} diff: {}",
-            d.validator,
+        #[cfg(feature = "debug-events")]
+        debug_attrs.push(debug_validator_attr(
+            &d.validator,
             d.amount,
             current_td.u128(),
-            current_diff
-        );
+            current_diff,
+        ));
         let current_cmp = current_td.u128().cmp(&d.amount);
         // if there is a bigger gap to fill with the current validator, use it
         if current_cmp > cmp || (current_cmp.is_gt() && current_diff > diff) {
@@ -354,12 +956,69 @@ pub fn reinvest(deps: DepsMut, env: Env) -> StdResult<Response> {
             cmp = current_cmp;
         }
     }
-    let fee_amount = if fee.is_zero() {
-        Uint128::zero()
-    } else {
-        fee.checked_mul_uint(amount_to_bond)?
+    let fee_carry = state.fee_carry.may_load(deps.storage)?.unwrap_or_default();
+    let (mut fee_amount, fee_carry) = compute_fee_with_carry(fee, amount_to_bond, fee_carry)?;
+    state.fee_carry.save(deps.storage, &fee_carry)?;
+    let mut amount_to_bond_minus_fees = amount_to_bond.saturating_sub(fee_amount);
+
+    // part of the fees is kept by the hub as a boost for locked bonds
+    let lock_boost = accrue_lock_boost(deps.storage, fee_amount)?;
+    fee_amount -= lock_boost;
+
+    // and part is kept to fund protocol-owned liquidity
+    let pol_amount = accrue_pol(deps.storage, fee_amount)?;
+    fee_amount -= pol_amount;
+
+    // the miner only receives the share emitted in the current epoch; the rest stays bonded on
+    // behalf of all holders
+    let fees_unemitted = match state.mining_schedule.may_load(deps.storage)? {
+        Some(schedule) => {
+            let (_, miner_share) = compute_miner_share(&schedule, env.block.time.seconds());
+            fee_amount - miner_share.checked_mul_uint(fee_amount)?
+        }
+        None => Uint128::zero(),
+    };
+    fee_amount -= fees_unemitted;
+    amount_to_bond_minus_fees += fees_unemitted;
+
+    // in drip mode, what is bonded now is released into the exchange rate over the drip period,
+    // along with whatever was left of the previous release
+    let current_time = env.block.time.seconds();
+    let rewards_unreleased = match state.reward_drip_period.may_load(deps.storage)? {
+        Some(period) => {
+            let drip = RewardDrip {
+                amount: unreleased_rewards(deps.storage, current_time)? + amount_to_bond_minus_fees,
+                start_time: current_time,
+                end_time: current_time + period,
+            };
+            state.reward_drip.save(deps.storage, &drip)?;
+            drip.amount
+        }
+        None => Uint128::zero(),
+    };
+
+    // the last miner is paid its share of the emitted fees, the rest going to the fee account
+    let miner = state.miner.may_load(deps.storage)?;
+    let miner_fees = match &miner {
+        Some(_) => {
+            let share_bps = state
+                .miner_fee_share_bps
+                .may_load(deps.storage)?
+                .unwrap_or(MAX_BPS);
+            fee_amount.multiply_ratio(share_bps, MAX_BPS)
+        }
+        None => Uint128::zero(),
     };
-    let amount_to_bond_minus_fees = amount_to_bond.saturating_sub(fee_amount);
+    let protocol_fees = fee_amount - miner_fees;
+
+    let pending_fees = state
+        .snapshot_pending_fees
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    state.snapshot_pending_fees.save(
+        deps.storage,
+        &(pending_fees + fee_amount + lock_boost + pol_amount),
+    )?;
 
     let new_delegation = Delegation::new(validator, amount_to_bond_minus_fees.u128(), &denom);
 
@@ -371,87 +1030,318 @@ pub fn reinvest(deps: DepsMut, env: Env) -> StdResult<Response> {
         .add_attribute("height", env.block.height.to_string())
         .add_attribute("denom", &denom)
         .add_attribute("fees_deducted", fee_amount)
-        .add_attribute("denom_bonded", amount_to_bond_minus_fees);
-
-    if fee_amount > Uint128::zero() {
-        let fee_account = state.fee_account.load(deps.storage)?;
-        let fee_type = state.fee_account_type.load(deps.storage)?;
-
-        let send_msgs = match fee_type {
-            FeeType::Wallet => vec![CosmosMsg::Bank(BankMsg::Send {
-                to_address: fee_account.to_string(),
-                amount: vec![Coin::new(fee_amount.into(), &denom)],
-            })],
-            FeeType::FeeSplit => {
-                let msg = pfc_fee_split::fee_split_msg::ExecuteMsg::Deposit { flush: false };
-
-                vec![msg.into_cosmos_msg(fee_account, vec![Coin::new(fee_amount.into(), &denom)])?]
-            }
-        };
-        Ok(Response::new()
-            .add_message(new_delegation.to_cosmos_msg(env.contract.address.to_string())?)
-            .add_messages(send_msgs)
-            .add_event(event)
-            .add_attribute("action", "steakhub/reinvest"))
-    } else {
-        Ok(Response::new()
-            .add_message(new_delegation.to_cosmos_msg(env.contract.address.to_string())?)
-            .add_event(event)
-            .add_attribute("action", "steakhub/reinvest"))
+        .add_attribute("miner_fees", miner_fees)
+        .add_attribute("lock_boost", lock_boost)
+        .add_attribute("pol_reserved", pol_amount)
+        .add_attribute("denom_bonded", amount_to_bond_minus_fees)
+        .add_attribute("fees_unemitted", fees_unemitted)
+        .add_attribute("rewards_unreleased", rewards_unreleased);
+    #[cfg(feature = "debug-events")]
+    let event = event.add_attributes(debug_attrs);
+
+    // A fee account failing to take the fees, e.g. a migrated fee split contract, must not stop
+    // rewards from compounding; the fees are escrowed for `RetryFeeForward` instead. The amount in
+    // flight is kept for the reply, which clears it either way
+    let mut fee_msgs: Vec<SubMsg> = vec![];
+    if !protocol_fees.is_zero() {
+        state.fee_forward.save(deps.storage, &protocol_fees)?;
+        fee_msgs.push(SubMsg::reply_always(
+            fee_forward_msg(deps.storage, Coin::new(protocol_fees.into(), &denom))?,
+            REPLY_FEE_FORWARD_DONE,
+        ));
     }
-}
 
-/// NOTE: a `SubMsgResponse` may contain multiple coin-receiving events, must handle them individually
-pub fn register_received_coins(
-    deps: DepsMut,
-    env: Env,
-    mut events: Vec<Event>,
-) -> StdResult<Response> {
-    events.retain(|event| event.ty == "coin_received");
-    if events.is_empty() {
-        return Ok(Response::new());
+    let mut send_msgs: Vec<CosmosMsg> = vec![];
+    if let Some(mut miner) = miner.filter(|_| !miner_fees.is_zero()) {
+        send_msgs.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: miner.miner.to_string(),
+            amount: vec![Coin::new(miner_fees.into(), &denom)],
+        }));
+        miner.earned += miner_fees;
+        state.miner.save(deps.storage, &miner)?;
     }
 
-    let mut received_coins = Coins(vec![]);
-    for event in &events {
-        received_coins.add_many(&parse_coin_receiving_event(&env, event)?)?;
-    }
+    Ok(Response::new()
+        .add_message(new_delegation.to_cosmos_msg(env.contract.address.to_string())?)
+        .add_submessages(fee_msgs)
+        .add_messages(send_msgs)
+        .add_event(event)
+        .add_attribute("action", "steakhub/reinvest"))
+}
 
+/// Message sending protocol fees to the fee account, the way its type takes them
+fn fee_forward_msg(storage: &dyn Storage, fees: Coin) -> Result<CosmosMsg, ContractError> {
     let state = State::default();
-    state
-        .unlocked_coins
-        .update(deps.storage, |coins| -> StdResult<_> {
-            let mut coins = Coins(coins);
-            coins.add_many(&received_coins)?;
-            Ok(coins.0)
-        })?;
-
-    Ok(Response::new().add_attribute("action", "steakhub/register_received_coins"))
+    let fee_account = state.fee_account.load(storage)?;
+
+    match state.fee_account_type.load(storage)? {
+        FeeType::Wallet => Ok(CosmosMsg::Bank(BankMsg::Send {
+            to_address: fee_account.to_string(),
+            amount: vec![fees],
+        })),
+        FeeType::FeeSplit => {
+            let msg = pfc_fee_split::fee_split_msg::ExecuteMsg::Deposit { flush: false };
+            Ok(msg.into_cosmos_msg(fee_account, vec![fees])?)
+        }
+        FeeType::None => Err(ContractError::FeesDisabled {}),
+    }
 }
 
-fn parse_coin_receiving_event(env: &Env, event: &Event) -> StdResult<Coins> {
-    let receiver = &event
-        .attributes
-        .iter()
-        .find(|attr| attr.key == "receiver")
-        .ok_or_else(|| StdError::generic_err("cannot find `receiver` attribute"))?
-        .value;
-
-    let amount_str = &event
-        .attributes
-        .iter()
-        .find(|attr| attr.key == "amount")
-        .ok_or_else(|| StdError::generic_err("cannot find `amount` attribute"))?
-        .value;
+/// NOTE: fees are forwarded with `reply_always`, so the reinvest still goes through when the fee
+/// account fails to take them; they are kept by the hub until `RetryFeeForward`
+pub fn settle_fee_forward(deps: DepsMut, result: SubMsgResult) -> Result<Response, ContractError> {
+    let state = State::default();
 
-    let amount = if *receiver == env.contract.address {
-        Coins::from_str(amount_str)?
-    } else {
-        Coins(vec![])
+    let fees = state.fee_forward.load(deps.storage)?;
+    state.fee_forward.remove(deps.storage);
+    let error = match result {
+        SubMsgResult::Ok(_) => return Ok(Response::new()),
+        SubMsgResult::Err(error) => error,
     };
+    let escrowed = state
+        .escrowed_fees
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + fees;
+    state.escrowed_fees.save(deps.storage, &escrowed)?;
+    bump_metrics(deps.storage, |metrics| metrics.fee_forward_failures += 1)?;
 
-    Ok(amount)
-}
+    let event = Event::new("steakhub/fee_forward_failed")
+        .add_attribute("fees", fees)
+        .add_attribute("escrowed_fees", escrowed)
+        .add_attribute("error", error);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/escrow_fee_forward"))
+}
+
+pub fn retry_fee_forward(deps: DepsMut) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let escrowed = state
+        .escrowed_fees
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if escrowed.is_zero() {
+        return Err(ContractError::NoEscrowedFees {});
+    }
+    state.escrowed_fees.remove(deps.storage);
+
+    let denom = state.denom.load(deps.storage)?;
+    let msg = fee_forward_msg(deps.storage, Coin::new(escrowed.into(), denom))?;
+
+    let event = Event::new("steakhub/fee_forward_retried")
+        .add_attribute("fees", escrowed)
+        .add_attribute("fee_account", state.fee_account.load(deps.storage)?);
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_event(event)
+        .add_attribute("action", "steakhub/retry_fee_forward"))
+}
+
+/// Rewards bonded by past reinvests but not yet released into the exchange rate at `current_time`
+pub(crate) fn unreleased_rewards(storage: &dyn Storage, current_time: u64) -> StdResult<Uint128> {
+    let state = State::default();
+
+    Ok(state
+        .reward_drip
+        .may_load(storage)?
+        .map(|drip| compute_unreleased_rewards(&drip, current_time))
+        .unwrap_or_default())
+}
+
+/// The exchange rate bonds and unbonds are priced at in the current block: the Steak supply and the
+/// Native Token bonded, net of unreleased rewards, as of the block's first bond or unbond. Later
+/// ones in the same block reuse it, so that nothing landing in between, a harvest in particular,
+/// moves the price within a block.
+///
+/// NOTE: this only protects against bonding and exiting around a harvest within one block. A bond
+/// made in the block before a harvest still shares in its rewards once it lands; spreading them out
+/// over time is left to the reward drip set with `UpdateRewardDrip`.
+pub(crate) fn block_exchange_rate(
+    storage: &mut dyn Storage,
+    env: &Env,
+    usteak_supply: Uint128,
+    delegations: &[Delegation],
+) -> StdResult<ExchangeRateSnapshot> {
+    let state = State::default();
+
+    if let Some(snapshot) = state.block_exchange_rate.may_load(storage)? {
+        if snapshot.height == env.block.height {
+            return Ok(snapshot);
+        }
+    }
+    let native_bonded = Uint128::new(delegations.iter().map(|d| d.amount).sum())
+        .saturating_sub(unreleased_rewards(storage, env.block.time.seconds())?);
+    let snapshot = ExchangeRateSnapshot {
+        height: env.block.height,
+        usteak_supply,
+        native_bonded,
+    };
+    state.block_exchange_rate.save(storage, &snapshot)?;
+
+    Ok(snapshot)
+}
+
+pub fn update_min_reinvest_amount(
+    deps: DepsMut,
+    sender: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.min_reinvest_amount.save(deps.storage, &amount)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "steakhub/update_min_reinvest_amount")
+        .add_attribute("amount", amount))
+}
+
+pub fn update_reward_drip(
+    deps: DepsMut,
+    sender: Addr,
+    period: Option<u64>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    match period {
+        Some(period) if period == 0 || period > MAX_REWARD_DRIP_SECONDS => {
+            return Err(ContractError::InvalidRewardDripPeriod {
+                max: MAX_REWARD_DRIP_SECONDS,
+            })
+        }
+        Some(period) => state.reward_drip_period.save(deps.storage, &period)?,
+        None => {
+            // whatever is still dripping is released at once
+            state.reward_drip_period.remove(deps.storage);
+            state.reward_drip.remove(deps.storage);
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "steakhub/update_reward_drip")
+        .add_attribute("period", period.map(|p| p.to_string()).unwrap_or_default()))
+}
+
+/// Row of the per-validator target/diff table attached to the harvest event in debug builds
+#[cfg(feature = "debug-events")]
+fn debug_validator_attr(validator: &str, amount: u128, target: u128, diff: u128) -> Attribute {
+    attr(
+        format!("debug_{}", validator),
+        format!("amount={} target={} diff={}", amount, target, diff),
+    )
+}
+
+/// NOTE: a `SubMsgResponse` may contain multiple coin-receiving events, must handle them individually
+pub fn register_received_coins(
+    deps: DepsMut,
+    env: Env,
+    mut events: Vec<Event>,
+) -> Result<Response, ContractError> {
+    record_withdrawn_rewards(deps.storage, &events)?;
+
+    events.retain(|event| event.ty == "coin_received");
+    if events.is_empty() {
+        return Ok(Response::new());
+    }
+
+    let mut received_coins = Coins::default();
+    for event in &events {
+        received_coins.add_many(&parse_coin_receiving_event(&env, event)?)?;
+    }
+
+    let state = State::default();
+    let denom = state.denom.load(deps.storage)?;
+    // once the reward denoms are configured, airdrops and dust in any other denom are ignored
+    if let Some(reward_denoms) = state.reward_denoms.may_load(deps.storage)? {
+        received_coins.retain(|d| d == denom || reward_denoms.iter().any(|rd| rd == d));
+    }
+    if let Some(id) = state.emergency_batch.may_load(deps.storage)? {
+        let native_received = received_coins.find(&denom).amount;
+        if !native_received.is_zero() {
+            credit_emergency_native(deps.storage, id, native_received)?;
+            received_coins.retain(|d| d != denom);
+        }
+    }
+    state
+        .unlocked_coins
+        .update(deps.storage, |coins| -> StdResult<_> {
+            let mut coins = Coins::try_from(coins)?;
+            coins.add_many(&received_coins)?;
+            Ok(coins.into_vec())
+        })?;
+
+    Ok(Response::new().add_attribute("action", "steakhub/register_received_coins"))
+}
+
+/// Add the rewards in each `withdraw_rewards` event to the validator it names
+fn record_withdrawn_rewards(storage: &mut dyn Storage, events: &[Event]) -> StdResult<()> {
+    let state = State::default();
+
+    for event in events.iter().filter(|event| event.ty == "withdraw_rewards") {
+        let attr = |key: &str| {
+            event
+                .attributes
+                .iter()
+                .find(|attr| attr.key == key)
+                .map(|attr| attr.value.as_str())
+                .ok_or_else(|| StdError::generic_err(format!("cannot find `{}` attribute", key)))
+        };
+        let validator = attr("validator")?;
+        let withdrawn = Coins::from_str(attr("amount")?)?;
+        if withdrawn.is_empty() {
+            continue;
+        }
+
+        state.validator_rewards.update(
+            storage,
+            validator.to_string(),
+            |rewards| -> StdResult<_> {
+                let mut rewards = Coins::try_from(rewards.unwrap_or_default())?;
+                rewards.add_many(&withdrawn)?;
+                Ok(rewards.into_vec())
+            },
+        )?;
+    }
+
+    Ok(())
+}
+
+/// An event may carry several `receiver`/`amount` pairs when the chain merges the events of one
+/// message, so each amount is attributed to the receiver that precedes it
+fn parse_coin_receiving_event(env: &Env, event: &Event) -> StdResult<Coins> {
+    let mut amount = Coins::default();
+    let mut receiver: Option<&str> = None;
+    let mut found_amount = false;
+
+    for attr in &event.attributes {
+        match attr.key.as_str() {
+            "receiver" => receiver = Some(&attr.value),
+            "amount" => {
+                let receiver = receiver
+                    .ok_or_else(|| StdError::generic_err("cannot find `receiver` attribute"))?;
+                if receiver == env.contract.address {
+                    amount.add_many(&Coins::from_str(&attr.value)?)?;
+                }
+                found_amount = true;
+            }
+            _ => {}
+        }
+    }
+
+    if receiver.is_none() {
+        return Err(StdError::generic_err("cannot find `receiver` attribute"));
+    }
+    if !found_amount {
+        return Err(StdError::generic_err("cannot find `amount` attribute"));
+    }
+
+    Ok(amount)
+}
 
 //--------------------------------------------------------------------------------------------------
 // Unbonding logics
@@ -460,12 +1350,26 @@ fn parse_coin_receiving_event(env: &Env, event: &Event) -> StdResult<Coins> {
 pub fn queue_unbond(
     deps: DepsMut,
     env: Env,
+    sender: Addr,
     receiver: Addr,
     usteak_to_burn: Uint128,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let state = State::default();
 
+    // the hub's own requests are all taken to be arbitrage, and settled against its in-flight cost
+    if receiver == env.contract.address && sender != env.contract.address {
+        return Err(ContractError::HubAsUnbondReceiver {});
+    }
+    state.assert_not_in_safe_mode(deps.storage, &env.block)?;
+    if let Some(id) = state.emergency_batch.may_load(deps.storage)? {
+        return join_emergency_batch(deps, env, id, sender, receiver, usteak_to_burn);
+    }
+
     let mut pending_batch = state.pending_batch.load(deps.storage)?;
+    // the hub's own requests, from arbitrage, are settled by the hub itself
+    if receiver != env.contract.address {
+        assert_unbond_request_room(deps.storage, &sender, &receiver, pending_batch.id)?;
+    }
     pending_batch.usteak_to_burn += usteak_to_burn;
     state.pending_batch.save(deps.storage, &pending_batch)?;
 
@@ -477,14 +1381,34 @@ pub fn queue_unbond(
                 id: pending_batch.id,
                 user: receiver.clone(),
                 shares: Uint128::zero(),
+                sender: request_sender(&sender, &receiver),
             });
             request.shares += usteak_to_burn;
             Ok(request)
         },
     )?;
+    record_batch_funder(
+        deps.storage,
+        pending_batch.id,
+        &sender,
+        &receiver,
+        usteak_to_burn,
+    )?;
+
+    record_history(
+        deps.storage,
+        &env,
+        &receiver,
+        HistoryAction::Unbond,
+        Uint128::zero(),
+        usteak_to_burn,
+    )?;
 
     let mut msgs: Vec<CosmosMsg> = vec![];
-    if env.block.time.seconds() >= pending_batch.est_unbond_start_time {
+    if let Some(reserve_msg) = coordinator_reserve_msg(deps.storage, &sender, usteak_to_burn)? {
+        msgs.push(reserve_msg);
+    }
+    if batch_submittable(deps.storage, &pending_batch, env.block.time.seconds())? {
         msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: env.contract.address.into(),
             msg: to_binary(&ExecuteMsg::SubmitBatch {})?,
@@ -496,6 +1420,7 @@ pub fn queue_unbond(
         .add_attribute("time", env.block.time.seconds().to_string())
         .add_attribute("height", env.block.height.to_string())
         .add_attribute("id", pending_batch.id.to_string())
+        .add_attribute("funder", sender)
         .add_attribute("receiver", receiver)
         .add_attribute("usteak_to_burn", usteak_to_burn);
 
@@ -505,28 +1430,372 @@ pub fn queue_unbond(
         .add_attribute("action", "steakhub/queue_unbond"))
 }
 
-pub fn submit_batch(deps: DepsMut, env: Env) -> StdResult<Response> {
+/// Reject a request that would open a new unbonding request for `receiver` once they hold
+/// `max_unbond_requests` of the same kind; adding to their request in the pending batch is always
+/// allowed. Requests the receiver queued themselves and requests queued on their behalf are capped
+/// separately, so that others can't use up the room they have for their own, while the receiver
+/// never holds more than twice the cap
+fn assert_unbond_request_room(
+    storage: &dyn Storage,
+    sender: &Addr,
+    receiver: &Addr,
+    pending_id: u64,
+) -> Result<(), ContractError> {
     let state = State::default();
-    let denom = state.denom.load(deps.storage)?;
+
+    let max = match state.max_unbond_requests.may_load(storage)? {
+        Some(max) => max,
+        None => return Ok(()),
+    };
+    if state.unbond_requests.has(storage, (pending_id, receiver)) {
+        return Ok(());
+    }
+    let third_party = sender != receiver;
+    let mut outstanding = 0;
+    for item in state
+        .unbond_requests
+        .idx
+        .user
+        .prefix(receiver.to_string())
+        .range(storage, None, None, Order::Ascending)
+        .take(2 * max as usize)
+    {
+        if item?.1.sender.is_some() == third_party {
+            outstanding += 1;
+        }
+    }
+    if outstanding >= max as usize {
+        return Err(ContractError::TooManyUnbondRequests { max });
+    }
+    Ok(())
+}
+
+/// Reject a request relayed past its `deadline`, the way a DEX rejects a stale swap
+pub(crate) fn assert_before_deadline(
+    env: &Env,
+    deadline: Option<u64>,
+) -> Result<(), ContractError> {
+    let now = env.block.time.seconds();
+    match deadline {
+        Some(deadline) if now > deadline => Err(ContractError::DeadlinePassed { deadline, now }),
+        _ => Ok(()),
+    }
+}
+
+/// Reject an unbonding request whose Steak is worth less than `min_out` at the current exchange
+/// rate, e.g. because it was priced by a router against a rate that has since dropped
+pub(crate) fn assert_unbond_min_out(
+    deps: Deps,
+    env: &Env,
+    usteak_to_burn: Uint128,
+    min_out: Uint128,
+) -> Result<(), ContractError> {
+    let (usteak_supply, native_bonded) =
+        query_supply_and_bonded(deps.storage, &deps.querier, &env.contract.address)?;
+    let estimated = compute_native_for_usteak(usteak_to_burn, usteak_supply, native_bonded);
+    if estimated < min_out {
+        return Err(ContractError::MinOutNotMet { estimated, min_out });
+    }
+    Ok(())
+}
+
+/// Same as `queue_unbond`, but once the request has been queued, and the batch submitted if it was
+/// due, the integrator is called back with the hub's state
+pub fn queue_unbond_with_callback(
+    deps: DepsMut,
+    env: Env,
+    integrator: Addr,
+    receiver: Addr,
+    usteak_to_burn: Uint128,
+    callback: Binary,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_integrator(deps.storage, &integrator)?;
+
+    let contract_addr = env.contract.address.clone();
+    let response = queue_unbond(
+        deps,
+        env,
+        integrator.clone(),
+        receiver.clone(),
+        usteak_to_burn,
+    )?;
+
+    let notify_msg = CallbackMsg::NotifyIntegrator {
+        integrator: integrator.to_string(),
+        flow: IntegratorFlow::QueueUnbond {
+            receiver: receiver.into(),
+            usteak_queued: usteak_to_burn,
+        },
+        callback,
+    }
+    .into_cosmos_msg(&contract_addr)?;
+
+    Ok(response
+        .add_message(notify_msg)
+        .add_attribute("callback_contract", integrator))
+}
+
+/// Unbonding on behalf of someone else is tallied per funder and receiver, so the flows through
+/// routers and integrations can be attributed
+fn record_batch_funder(
+    storage: &mut dyn Storage,
+    id: u64,
+    funder: &Addr,
+    receiver: &Addr,
+    usteak: Uint128,
+) -> StdResult<()> {
+    if funder == receiver {
+        return Ok(());
+    }
+    State::default().batch_funders.update(
+        storage,
+        (id, funder, receiver),
+        |amount| -> StdResult<_> { Ok(amount.unwrap_or_default().checked_add(usteak)?) },
+    )?;
+    Ok(())
+}
+
+fn clear_batch_funders(storage: &mut dyn Storage, id: u64) -> StdResult<()> {
+    let state = State::default();
+    let keys = state
+        .batch_funders
+        .sub_prefix(id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Addr, Addr)>>>()?;
+    for (funder, receiver) in keys {
+        state
+            .batch_funders
+            .remove(storage, (id, &funder, &receiver));
+    }
+    Ok(())
+}
+
+/// The address that queued a request is only recorded when it queued on behalf of someone else. A
+/// request keeps the sender it was created with when more shares are added to it
+fn request_sender(sender: &Addr, receiver: &Addr) -> Option<Addr> {
+    if sender == receiver {
+        None
+    } else {
+        Some(sender.clone())
+    }
+}
+
+/// NOTE: the simulation only decides between swapping and queueing the Steak for unbonding. The
+/// receiver is paid from what the swap actually returns, and the unstake fails if that comes to less
+/// than `min_out`.
+pub fn liquid_unstake(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    receiver: Addr,
+    usteak_amount: Uint128,
+    min_out: Uint128,
+    deadline: Option<u64>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    assert_before_deadline(&env, deadline)?;
+    state.assert_not_wound_down(deps.storage)?;
+    state.assert_not_in_safe_mode(deps.storage, &env.block)?;
+    let pair = state
+        .pol_pair
+        .may_load(deps.storage)?
+        .ok_or(ContractError::LiquidUnstakeNotConfigured {})?;
     let steak_token = state.steak_token.load(deps.storage)?;
+
+    let simulation: SimulationResponse = deps.querier.query_wasm_smart(
+        &pair,
+        &PairQueryMsg::Simulation {
+            offer_asset: Asset {
+                info: AssetInfo::Token {
+                    contract_addr: steak_token.to_string(),
+                },
+                amount: usteak_amount,
+            },
+        },
+    )?;
+
+    // the swap returns the native to the hub, which pays the receiver at most what the Steak would
+    // redeem for at the current exchange rate. Whatever the pair pays above that is kept in the
+    // arbitrage reserve rather than handed out at the expense of the pool's other holders
+    let native_simulated = simulation.return_amount;
+    let (usteak_supply, native_bonded) =
+        query_supply_and_bonded(deps.storage, &deps.querier, &env.contract.address)?;
+    let native_at_par = compute_native_for_usteak(usteak_amount, usteak_supply, native_bonded);
+    let native_to_pay = native_simulated.min(native_at_par);
+
+    if native_to_pay.is_zero() || native_to_pay < min_out {
+        // Steak shared with other hubs is only redeemed where the coordinator routes it
+        if let Some(coordinator) = state.coordinator.may_load(deps.storage)? {
+            if sender != coordinator {
+                return Err(ContractError::UnbondThroughCoordinator {});
+            }
+        }
+        let response = queue_unbond(deps, env, sender, receiver, usteak_amount)?;
+        return Ok(response
+            .add_attribute("liquid_unstake", "fallback")
+            .add_attribute("simulated_return", native_simulated));
+    }
+
+    let swap_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: steak_token.into(),
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: pair.to_string(),
+            amount: usteak_amount,
+            msg: to_binary(&PairCw20HookMsg::Swap {
+                belief_price: None,
+                max_spread: None,
+                to: None,
+            })?,
+        })?,
+        funds: vec![],
+    });
+
+    // the amount returned by the pair is only known once the swap has executed, so the receiver is
+    // paid, and the reserve credited, from the balance difference in a callback
+    let denom = state.denom.load(deps.storage)?;
+    let callback_msg = CallbackMsg::SettleLiquidUnstake {
+        receiver: receiver.to_string(),
+        native_before: get_denom_balance(&deps.querier, env.contract.address.clone(), denom)?,
+        native_at_par,
+        min_out,
+    }
+    .into_cosmos_msg(&env.contract.address)?;
+
+    let event = Event::new("steakhub/liquid_unstaked")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("pair", pair)
+        .add_attribute("funder", sender)
+        .add_attribute("receiver", receiver)
+        .add_attribute("usteak_swapped", usteak_amount)
+        .add_attribute("native_simulated", native_simulated)
+        .add_attribute("native_at_par", native_at_par);
+
+    Ok(Response::new()
+        .add_message(swap_msg)
+        .add_message(callback_msg)
+        .add_event(event)
+        .add_attribute("action", "steakhub/liquid_unstake"))
+}
+
+pub fn settle_liquid_unstake(
+    deps: DepsMut,
+    env: Env,
+    receiver: Addr,
+    native_before: Uint128,
+    native_at_par: Uint128,
+    min_out: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let denom = state.denom.load(deps.storage)?;
+    let native_returned =
+        get_denom_balance(&deps.querier, env.contract.address.clone(), denom.clone())?
+            .saturating_sub(native_before);
+    let native_paid = native_returned.min(native_at_par);
+    let native_kept = native_returned - native_paid;
+
+    if native_paid < min_out {
+        return Err(ContractError::MinOutNotMet {
+            estimated: native_paid,
+            min_out,
+        });
+    }
+
+    if !native_kept.is_zero() {
+        let reserve = state
+            .arb_reserve
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        state
+            .arb_reserve
+            .save(deps.storage, &(reserve + native_kept))?;
+    }
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    if !native_paid.is_zero() {
+        msgs.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: receiver.to_string(),
+            amount: vec![Coin::new(native_paid.u128(), denom)],
+        }));
+    }
+
+    let event = Event::new("steakhub/liquid_unstake_settled")
+        .add_attribute("receiver", receiver)
+        .add_attribute("native_returned", native_returned)
+        .add_attribute("native_paid", native_paid)
+        .add_attribute("native_kept", native_kept);
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_event(event)
+        .add_attribute("action", "steakhub/settle_liquid_unstake"))
+}
+
+pub fn submit_batch(deps: DepsMut, env: Env, sender: Addr) -> Result<Response, ContractError> {
+    let state = State::default();
+    state.assert_not_wound_down(deps.storage)?;
+    let denom = state.denom.load(deps.storage)?;
     let validators = state.validators.load(deps.storage)?;
     let unbond_period = state.unbond_period.load(deps.storage)?;
     let pending_batch = state.pending_batch.load(deps.storage)?;
 
     let current_time = env.block.time.seconds();
     if current_time < pending_batch.est_unbond_start_time {
-        return Err(StdError::generic_err(format!(
-            "batch can only be submitted for unbonding after {}",
-            pending_batch.est_unbond_start_time
-        )));
+        return Err(ContractError::BatchNotReady {
+            ready_at: pending_batch.est_unbond_start_time,
+        });
+    }
+    // the owner can submit a batch the limits would hold back, e.g. to flush a small final batch
+    if sender != state.owner.load(deps.storage)? {
+        let ready_at = batch_submittable_at(deps.storage, &pending_batch)?;
+        if current_time < ready_at {
+            return Err(ContractError::BatchNotReady { ready_at });
+        }
+        let min_size = state
+            .min_batch_size
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        if pending_batch.usteak_to_burn < min_size {
+            return Err(ContractError::BatchTooSmall { min_size });
+        }
     }
+    state
+        .last_batch_submitted
+        .save(deps.storage, &current_time)?;
 
     let delegations = query_delegations(&deps.querier, &validators, &env.contract.address, &denom)?;
-    let usteak_supply = query_cw20_total_supply(&deps.querier, &steak_token)?;
+    let usteak_supply = query_usteak_supply(deps.storage, &deps.querier, &env.contract.address)?;
+    record_daily_snapshot(deps.storage, &env, usteak_supply, &delegations)?;
 
-    let amount_to_bond =
-        compute_unbond_amount(usteak_supply, pending_batch.usteak_to_burn, &delegations);
-    let new_undelegations = compute_undelegations(amount_to_bond, &delegations, &denom);
+    let rounding = state
+        .rounding_policy
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let rate = block_exchange_rate(deps.storage, &env, usteak_supply, &delegations)?;
+    let amount_to_bond = compute_unbond_amount(
+        rate.usteak_supply,
+        pending_batch.usteak_to_burn,
+        rate.native_bonded,
+        rounding,
+    )?;
+    let mut new_undelegations = compute_undelegations(amount_to_bond, &delegations, &denom);
+    // a batch is undelegated in one go, as its unbonding time and the exchange rate assume; past
+    // `max_msgs_per_tx` it is taken from the largest delegations alone
+    if let Some(max) = state.max_msgs_per_tx.may_load(deps.storage)? {
+        if new_undelegations.len() > max as usize {
+            let mut largest = delegations.clone();
+            largest.sort_by_key(|d| std::cmp::Reverse(d.amount));
+            largest.truncate(max as usize);
+            if largest.iter().map(|d| d.amount).sum::<u128>() < amount_to_bond.u128() {
+                return Err(ContractError::BatchExceedsMaxMsgs { max });
+            }
+            new_undelegations = compute_undelegations(amount_to_bond, &largest, &denom);
+        }
+    }
 
     // NOTE: Regarding the `amount_unclaimed` value
     //
@@ -563,6 +1832,8 @@ pub fn submit_batch(deps: DepsMut, env: Env) -> StdResult<Response> {
         &get_denom_balance(&deps.querier, env.contract.address.clone(), denom)?,
     )?;
 
+    record_batch_undelegations(deps.storage, pending_batch.id, &new_undelegations)?;
+
     let undelegate_submsgs = new_undelegations
         .iter()
         .map(|d| {
@@ -573,72 +1844,76 @@ pub fn submit_batch(deps: DepsMut, env: Env) -> StdResult<Response> {
         })
         .collect::<StdResult<Vec<_>>>()?;
 
-    let burn_msg = CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: steak_token.into(),
-        msg: to_binary(&Cw20ExecuteMsg::Burn {
-            amount: pending_batch.usteak_to_burn,
-        })?,
-        funds: vec![],
-    });
+    let burn_msg = usteak_burn_msg(deps.storage, pending_batch.usteak_to_burn)?;
+
+    // keepers and indexers get the full plan here rather than reassembling it from staking events
+    let exchange_rate = if rate.usteak_supply.is_zero() {
+        Decimal::one()
+    } else {
+        Decimal::from_ratio(rate.native_bonded, rate.usteak_supply)
+    };
+    let undelegations = new_undelegations
+        .iter()
+        .map(|d| format!("{}:{}", d.validator, d.amount))
+        .collect::<Vec<_>>();
 
     let event = Event::new("steakhub/unbond_submitted")
         .add_attribute("time", env.block.time.seconds().to_string())
         .add_attribute("height", env.block.height.to_string())
         .add_attribute("id", pending_batch.id.to_string())
         .add_attribute("native_unbonded", amount_to_bond)
-        .add_attribute("usteak_burned", pending_batch.usteak_to_burn);
+        .add_attribute("usteak_burned", pending_batch.usteak_to_burn)
+        .add_attribute("exchange_rate", exchange_rate.to_string())
+        .add_attribute(
+            "est_unbond_end_time",
+            (current_time + unbond_period).to_string(),
+        )
+        .add_attribute("undelegations", undelegations.join(","));
+
+    let data = SubmitBatchResponseData {
+        batch_id: pending_batch.id,
+        usteak_burned: pending_batch.usteak_to_burn,
+        native_unbonded: amount_to_bond,
+        est_unbond_end_time: current_time + unbond_period,
+    };
 
     Ok(Response::new()
         .add_submessages(undelegate_submsgs)
         .add_message(burn_msg)
         .add_event(event)
-        .add_attribute("action", "steakhub/unbond"))
+        .add_attribute("action", "steakhub/unbond")
+        .set_data(to_binary(&data)?))
 }
 
-pub fn reconcile(deps: DepsMut, env: Env) -> StdResult<Response> {
+pub fn reconcile(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
     let state = State::default();
-    let current_time = env.block.time.seconds();
 
-    // Load batches that have not been reconciled
-    let all_batches = state
-        .previous_batches
-        .idx
-        .reconciled
-        .prefix(false.into())
-        .range(deps.storage, None, None, Order::Ascending)
-        .map(|item| {
-            let (_, v) = item?;
-            Ok(v)
-        })
-        .collect::<StdResult<Vec<_>>>()?;
-
-    let mut batches = all_batches
-        .into_iter()
-        .filter(|b| current_time > b.est_unbond_end_time)
-        .collect::<Vec<_>>();
-
-    let native_expected_received: Uint128 = batches.iter().map(|b| b.amount_unclaimed).sum();
-    let denom = state.denom.load(deps.storage)?;
-    let unlocked_coins = state.unlocked_coins.load(deps.storage)?;
-
-    let native_expected_unlocked = Coins(unlocked_coins).find(&denom).amount;
-
-    let native_expected = native_expected_received + native_expected_unlocked;
-    let native_actual = deps
-        .querier
-        .query_balance(&env.contract.address, &denom)?
-        .amount;
+    let (mut batches, native_expected, native_actual) =
+        reconcilable_batches(deps.storage, &deps.querier, &env)?;
 
     let native_to_deduct = native_expected
         .checked_sub(native_actual)
         .unwrap_or_else(|_| Uint128::zero());
+    let mut slashing_record = None;
     if !native_to_deduct.is_zero() {
         reconcile_batches(&mut batches, native_expected - native_actual);
+        bump_metrics(deps.storage, |metrics| {
+            metrics.reconcile_shortfalls += 1;
+            metrics.native_shortfall += native_to_deduct;
+        })?;
+        slashing_record = Some(record_slashing(
+            deps.storage,
+            &deps.querier,
+            &env,
+            &batches,
+            native_to_deduct,
+        )?);
     }
 
     for batch in batches.iter_mut() {
         batch.reconciled = true;
         state.previous_batches.save(deps.storage, batch.id, batch)?;
+        clear_batch_undelegations(deps.storage, batch.id)?;
     }
 
     let ids = batches
@@ -649,23 +1924,225 @@ pub fn reconcile(deps: DepsMut, env: Env) -> StdResult<Response> {
 
     let event = Event::new("steakhub/reconciled")
         .add_attribute("ids", ids)
-        .add_attribute("native_deducted", native_to_deduct.to_string());
+        .add_attribute("native_deducted", native_to_deduct.to_string())
+        .add_attributes(slashing_record.map(|id| ("slashing_record", id.to_string())));
 
     Ok(Response::new()
         .add_event(event)
         .add_attribute("action", "steakhub/reconcile"))
 }
+
+/// Batches that have finished unbonding but are not yet reconciled, along with the Native Token the
+/// hub is expected to hold (what these batches are owed plus the hub's own reserves) and the amount
+/// it actually holds
+pub(crate) fn reconcilable_batches(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+) -> StdResult<(Vec<Batch>, Uint128, Uint128)> {
+    let state = State::default();
+    let current_time = env.block.time.seconds();
+
+    // Load batches that have not been reconciled
+    let all_batches = state
+        .previous_batches
+        .idx
+        .reconciled
+        .prefix(false.into())
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let batches = all_batches
+        .into_iter()
+        .filter(|b| current_time > b.est_unbond_end_time)
+        .collect::<Vec<_>>();
+
+    let native_expected_received: Uint128 = batches.iter().map(|b| b.amount_unclaimed).sum();
+    let denom = state.denom.load(storage)?;
+    let unlocked_coins = state.unlocked_coins.load(storage)?;
+
+    let native_expected_unlocked = Coins::try_from(unlocked_coins)?.find(&denom).amount;
+
+    let native_expected_boost = state
+        .lock_boost_unclaimed
+        .may_load(storage)?
+        .unwrap_or_default();
+
+    let native_expected_pol = state
+        .pol_native_reserve
+        .may_load(storage)?
+        .unwrap_or_default();
+
+    let native_expected_arb = state.arb_reserve.may_load(storage)?.unwrap_or_default();
+
+    let native_expected_fees = state.escrowed_fees.may_load(storage)?.unwrap_or_default();
+
+    let native_expected_rebates = state.pending_rebates.may_load(storage)?.unwrap_or_default();
+
+    let native_expected_reinvest = state
+        .pending_reinvest
+        .may_load(storage)?
+        .unwrap_or_default();
+
+    let native_expected_held = state
+        .emergency_held
+        .may_load(storage)?
+        .unwrap_or_default()
+        .native;
+
+    let native_expected = native_expected_received
+        + native_expected_unlocked
+        + native_expected_boost
+        + native_expected_pol
+        + native_expected_arb
+        + native_expected_fees
+        + native_expected_rebates
+        + native_expected_reinvest
+        + native_expected_held;
+    let native_actual = querier.query_balance(&env.contract.address, &denom)?.amount;
+
+    Ok((batches, native_expected, native_actual))
+}
+
+/// Attribute a shortfall found when reconciling `batches` to the validators it was lost with, log it
+/// and return the ID of the record. Slashing also cuts into the entries still unbonding from a
+/// validator, so the validators whose entries hold less on chain than the batches undelegated from
+/// them are taken to be the ones slashed, and have the incident counted against their score. If the
+/// chain reports no such validator, or does not serve the query, the shortfall is only split by what
+/// the reconciled batches undelegated from each validator
+fn record_slashing(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+    batches: &[Batch],
+    native_shortfall: Uint128,
+) -> StdResult<u64> {
+    let state = State::default();
+    let batch_ids = batches.iter().map(|b| b.id).collect::<Vec<_>>();
+
+    let mut undelegated: BTreeMap<String, Uint128> = BTreeMap::new();
+    let mut still_unbonding: BTreeMap<String, Uint128> = BTreeMap::new();
+    for item in state
+        .batch_undelegations
+        .range(storage, None, None, Order::Ascending)
+    {
+        let ((id, validator), amount) = item?;
+        let totals = if batch_ids.contains(&id) {
+            &mut undelegated
+        } else {
+            &mut still_unbonding
+        };
+        *totals.entry(validator).or_default() += amount;
+    }
+
+    // validators the chain holds no entries for have finished unbonding, and tell nothing
+    let slashed = query_chain_unbondings(querier, &env.contract.address)
+        .map(|chain_unbondings| {
+            chain_unbondings
+                .into_iter()
+                .filter_map(|(validator, chain_amount, _)| {
+                    let missing = still_unbonding
+                        .get(&validator)?
+                        .saturating_sub(chain_amount);
+                    (!missing.is_zero()).then_some((validator, missing))
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let chain_attributed = !slashed.is_empty();
+    let weights = if chain_attributed {
+        slashed
+    } else {
+        undelegated.into_iter().collect()
+    };
+
+    let losses = compute_loss_attribution(native_shortfall, &weights);
+    if chain_attributed {
+        for (validator, _) in &losses {
+            record_validator_incident(storage, env, validator, true)?;
+        }
+    }
+
+    let id = state
+        .last_slashing_record_id
+        .may_load(storage)?
+        .unwrap_or_default()
+        + 1;
+    state.last_slashing_record_id.save(storage, &id)?;
+    state.slashing_records.save(
+        storage,
+        id,
+        &SlashingRecord {
+            id,
+            batch_ids,
+            native_shortfall,
+            validators: losses
+                .into_iter()
+                .map(|(validator, amount)| ValidatorLoss { validator, amount })
+                .collect(),
+            chain_attributed,
+            height: env.block.height,
+            time: env.block.time.seconds(),
+        },
+    )?;
+
+    Ok(id)
+}
+
+/// Remember how much a batch undelegated from each validator, until the batch is reconciled
+fn record_batch_undelegations(
+    storage: &mut dyn Storage,
+    id: u64,
+    undelegations: &[Undelegation],
+) -> StdResult<()> {
+    let state = State::default();
+    for u in undelegations {
+        state.batch_undelegations.update(
+            storage,
+            (id, u.validator.clone()),
+            |amount| -> StdResult<_> {
+                Ok(amount
+                    .unwrap_or_default()
+                    .checked_add(Uint128::new(u.amount))?)
+            },
+        )?;
+    }
+    Ok(())
+}
+
+fn clear_batch_undelegations(storage: &mut dyn Storage, id: u64) -> StdResult<()> {
+    let state = State::default();
+    let validators = state
+        .batch_undelegations
+        .prefix(id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<String>>>()?;
+    for validator in validators {
+        state.batch_undelegations.remove(storage, (id, validator));
+    }
+    Ok(())
+}
+
+/// DEPRECATED: users should call `withdraw_unbonded` themselves, choosing the receiver explicitly.
+/// The owner can only trigger a withdrawal back to the address that owns the unbond requests; the
+/// refund can never be redirected elsewhere.
 pub fn withdraw_unbonded_admin(
     deps: DepsMut,
     env: Env,
+    sender: Addr,
     user: Addr,
-    receiver: Addr,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let state = State::default();
 
-    state.assert_owner(deps.storage, &user)?;
+    state.assert_owner(deps.storage, &sender)?;
 
-    withdraw_unbonded(deps, env, receiver.clone(), receiver)
+    Ok(withdraw_unbonded(deps, env, user.clone(), user)?
+        .add_attribute("admin", sender)
+        .add_attribute("deprecated", "withdraw_unbonded_admin"))
 }
 
 pub fn withdraw_unbonded(
@@ -673,20 +2150,148 @@ pub fn withdraw_unbonded(
     env: Env,
     user: Addr,
     receiver: Addr,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let state = State::default();
     let denom = state.denom.load(deps.storage)?;
-    let current_time = env.block.time.seconds();
+
+    let (mut total_native_to_refund, mut total_usteak_to_refund, _, ids) =
+        claim_unbond_requests(deps.storage, &user, env.block.time.seconds())?;
+    if let Some(claim) = state.consolidated_claims.may_load(deps.storage, &user)? {
+        let mut total = state.consolidated_total.load(deps.storage)?;
+        total.native -= claim.native;
+        total.usteak -= claim.usteak;
+        state.consolidated_total.save(deps.storage, &total)?;
+        state.consolidated_claims.remove(deps.storage, &user);
+
+        total_native_to_refund += claim.native;
+        total_usteak_to_refund += claim.usteak;
+    }
+
+    if total_native_to_refund.is_zero() && total_usteak_to_refund.is_zero() {
+        return Err(ContractError::NothingToWithdraw {});
+    }
+
+    record_history(
+        deps.storage,
+        &env,
+        &user,
+        HistoryAction::Withdraw,
+        total_native_to_refund,
+        total_usteak_to_refund,
+    )?;
+
+    let mut refund_msgs: Vec<CosmosMsg> = vec![];
+    if !total_native_to_refund.is_zero() {
+        refund_msgs.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: receiver.clone().into(),
+            amount: vec![Coin::new(total_native_to_refund.u128(), &denom)],
+        }));
+    }
+    // claims on swept batches are refunded in the Steak their unclaimed Native Token was re-bonded for
+    if !total_usteak_to_refund.is_zero() {
+        refund_msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: state.steak_token.load(deps.storage)?.into(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: receiver.to_string(),
+                amount: total_usteak_to_refund,
+            })?,
+            funds: vec![],
+        }));
+    }
+
+    // the receiver is always chosen by the user who owns the requests; flag when it differs so
+    // indexers can tell refunds to third parties apart
+    let receiver_type = if receiver == user {
+        "self"
+    } else {
+        "third_party"
+    };
+
+    let event = Event::new("steakhub/unbonded_withdrawn")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("ids", ids.join(","))
+        .add_attribute("user", user)
+        .add_attribute("receiver", receiver)
+        .add_attribute("receiver_type", receiver_type)
+        .add_attribute("amount_refunded", total_native_to_refund);
+    let event = if total_usteak_to_refund.is_zero() {
+        event
+    } else {
+        event.add_attribute("usteak_refunded", total_usteak_to_refund)
+    };
+
+    Ok(Response::new()
+        .add_messages(refund_msgs)
+        .add_event(event)
+        .add_attribute("action", "steakhub/withdraw_unbonded"))
+}
+
+/// Claim the user's requests in batches that are ready to be withdrawn, and add what they are worth
+/// to the user's consolidated claim rather than refunding it
+pub fn consolidate_requests(
+    deps: DepsMut,
+    env: Env,
+    user: Addr,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let (native_claimed, usteak_claimed, _, ids) =
+        claim_unbond_requests(deps.storage, &user, env.block.time.seconds())?;
+    if ids.is_empty() {
+        return Err(ContractError::NothingToConsolidate {});
+    }
+
+    let mut claim = state
+        .consolidated_claims
+        .may_load(deps.storage, &user)?
+        .unwrap_or_default();
+    claim.native += native_claimed;
+    claim.usteak += usteak_claimed;
+    state
+        .consolidated_claims
+        .save(deps.storage, &user, &claim)?;
+
+    let mut total = state
+        .consolidated_total
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    total.native += native_claimed;
+    total.usteak += usteak_claimed;
+    state.consolidated_total.save(deps.storage, &total)?;
+
+    let event = Event::new("steakhub/requests_consolidated")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("ids", ids.join(","))
+        .add_attribute("user", user)
+        .add_attribute("native_consolidated", native_claimed)
+        .add_attribute("usteak_consolidated", usteak_claimed);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/consolidate_requests"))
+}
+
+/// Remove the user's unbonding requests in batches that are ready to be withdrawn. Returns the amount
+/// of native and of `usteak` (from swept batches) to refund, the shares claimed, and the ids of the
+/// batches claimed from
+fn claim_unbond_requests(
+    storage: &mut dyn Storage,
+    user: &Addr,
+    current_time: u64,
+) -> StdResult<(Uint128, Uint128, Uint128, Vec<String>)> {
+    let state = State::default();
 
     // NOTE: If the user has too many unclaimed requests, this may not fit in the WASM memory...
-    // However, this is practically never going to happen. Who would create hundreds of unbonding
-    // requests and never claim them?
+    // The owner can bound this with `max_unbond_requests`, which users stay under by withdrawing or
+    // consolidating their requests
     let requests = state
         .unbond_requests
         .idx
         .user
         .prefix(user.to_string())
-        .range(deps.storage, None, None, Order::Ascending)
+        .range(storage, None, None, Order::Ascending)
         .map(|item| {
             let (_, v) = item?;
             Ok(v)
@@ -700,388 +2305,5084 @@ pub fn withdraw_unbonded(
     // If not sure whether the batches have been reconciled, the user should first invoke `ExecuteMsg::Reconcile`
     // before withdrawing.
     let mut total_native_to_refund = Uint128::zero();
+    let mut total_usteak_to_refund = Uint128::zero();
+    let mut total_shares_claimed = Uint128::zero();
     let mut ids: Vec<String> = vec![];
     for request in &requests {
-        if let Ok(mut batch) = state.previous_batches.load(deps.storage, request.id) {
+        if let Ok(mut batch) = state.previous_batches.load(storage, request.id) {
             if batch.reconciled && batch.est_unbond_end_time < current_time {
                 let native_to_refund = batch
                     .amount_unclaimed
                     .multiply_ratio(request.shares, batch.total_shares);
 
+                let swept = state.swept_batches.may_load(storage, request.id)?;
+
                 ids.push(request.id.to_string());
 
                 total_native_to_refund += native_to_refund;
+                total_shares_claimed += request.shares;
+                if let Some(mut swept) = swept {
+                    let usteak_to_refund = swept
+                        .usteak_unclaimed
+                        .multiply_ratio(request.shares, batch.total_shares);
+                    total_usteak_to_refund += usteak_to_refund;
+                    swept.usteak_unclaimed -= usteak_to_refund;
+                    if batch.total_shares == request.shares {
+                        state.swept_batches.remove(storage, request.id);
+                    } else {
+                        state.swept_batches.save(storage, request.id, &swept)?;
+                    }
+                }
                 batch.total_shares -= request.shares;
                 batch.amount_unclaimed -= native_to_refund;
 
                 if batch.total_shares.is_zero() {
-                    state.previous_batches.remove(deps.storage, request.id)?;
+                    state.previous_batches.remove(storage, request.id)?;
                 } else {
-                    state
-                        .previous_batches
-                        .save(deps.storage, batch.id, &batch)?;
+                    state.previous_batches.save(storage, batch.id, &batch)?;
                 }
 
-                state
-                    .unbond_requests
-                    .remove(deps.storage, (request.id, &user))?;
+                state.unbond_requests.remove(storage, (request.id, user))?;
             }
         }
     }
 
-    if total_native_to_refund.is_zero() {
-        return Err(StdError::generic_err("withdrawable amount is zero"));
-    }
+    Ok((
+        total_native_to_refund,
+        total_usteak_to_refund,
+        total_shares_claimed,
+        ids,
+    ))
+}
 
-    let refund_msg = CosmosMsg::Bank(BankMsg::Send {
-        to_address: receiver.clone().into(),
-        amount: vec![Coin::new(total_native_to_refund.u128(), &denom)],
-    });
+pub fn update_batch_limits(
+    deps: DepsMut,
+    sender: Addr,
+    min_gap: u64,
+    min_size: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
 
-    let event = Event::new("steakhub/unbonded_withdrawn")
-        .add_attribute("time", env.block.time.seconds().to_string())
-        .add_attribute("height", env.block.height.to_string())
-        .add_attribute("ids", ids.join(","))
-        .add_attribute("user", user)
-        .add_attribute("receiver", receiver)
-        .add_attribute("amount_refunded", total_native_to_refund);
+    state.assert_owner(deps.storage, &sender)?;
+    state.min_batch_gap.save(deps.storage, &min_gap)?;
+    state.min_batch_size.save(deps.storage, &min_size)?;
 
     Ok(Response::new()
-        .add_message(refund_msg)
-        .add_event(event)
-        .add_attribute("action", "steakhub/withdraw_unbonded"))
+        .add_attribute("action", "steakhub/update_batch_limits")
+        .add_attribute("min_gap", min_gap.to_string())
+        .add_attribute("min_size", min_size))
 }
 
-//--------------------------------------------------------------------------------------------------
-// Ownership and management logics
-//--------------------------------------------------------------------------------------------------
-
-pub fn rebalance(deps: DepsMut, env: Env, minimum: Uint128) -> StdResult<Response> {
+pub fn update_max_unbond_requests(
+    deps: DepsMut,
+    sender: Addr,
+    max: Option<u32>,
+) -> Result<Response, ContractError> {
     let state = State::default();
-    let denom = state.denom.load(deps.storage)?;
-    let validators = state.validators.load(deps.storage)?;
-    let validators_active = state.validators_active.load(deps.storage)?;
-
-    let delegations = query_delegations(&deps.querier, &validators, &env.contract.address, &denom)?;
-
-    let total_delegated_amount = delegations.iter().fold(0u128, |acc, d| acc + d.amount);
-
-    let total_mining_power = state.total_mining_power.load(deps.storage)?;
-
-    let new_redelegations =
-        compute_redelegations_for_rebalancing(validators_active, &delegations, minimum, |d| {
-            compute_target_delegation_from_mining_power(
-                total_delegated_amount.into(),
-                state
-                    .validator_mining_powers
-                    .may_load(deps.storage, d.validator.clone())?
-                    .unwrap_or_default(),
-                total_mining_power,
-            )
-        })?;
 
-    state.prev_denom.save(
-        deps.storage,
-        &get_denom_balance(&deps.querier, env.contract.address.clone(), denom)?,
-    )?;
+    state.assert_owner(deps.storage, &sender)?;
+    match max {
+        Some(max) => state.max_unbond_requests.save(deps.storage, &max)?,
+        None => state.max_unbond_requests.remove(deps.storage),
+    }
 
-    let redelegate_submsgs = new_redelegations
-        .iter()
-        .map(|rd| {
-            Ok(SubMsg::reply_on_success(
-                rd.to_cosmos_msg(env.contract.address.to_string())?,
-                REPLY_REGISTER_RECEIVED_COINS,
-            ))
-        })
-        .collect::<StdResult<Vec<_>>>()?;
+    Ok(Response::new()
+        .add_attribute("action", "steakhub/update_max_unbond_requests")
+        .add_attribute("max", max.map(|m| m.to_string()).unwrap_or_default()))
+}
 
-    let amount: u128 = new_redelegations.iter().map(|rd| rd.amount).sum();
+/// Earliest time the pending batch can be submitted: once its epoch has passed and the minimum gap
+/// since the last submission has elapsed
+pub(crate) fn batch_submittable_at(
+    storage: &dyn Storage,
+    pending_batch: &PendingBatch,
+) -> StdResult<u64> {
+    let state = State::default();
 
-    let event = Event::new("steakhub/rebalanced").add_attribute("amount_moved", amount.to_string());
+    let min_gap = state.min_batch_gap.may_load(storage)?.unwrap_or_default();
+    let gap_end = match state.last_batch_submitted.may_load(storage)? {
+        Some(last_submitted) => last_submitted.saturating_add(min_gap),
+        None => 0,
+    };
+    Ok(pending_batch.est_unbond_start_time.max(gap_end))
+}
 
-    Ok(Response::new()
-        .add_submessages(redelegate_submsgs)
-        .add_event(event)
-        .add_attribute("action", "steakhub/rebalance"))
+/// Whether anyone can submit the pending batch at `current_time`
+fn batch_submittable(
+    storage: &dyn Storage,
+    pending_batch: &PendingBatch,
+    current_time: u64,
+) -> StdResult<bool> {
+    let min_size = State::default()
+        .min_batch_size
+        .may_load(storage)?
+        .unwrap_or_default();
+    Ok(
+        current_time >= batch_submittable_at(storage, pending_batch)?
+            && pending_batch.usteak_to_burn >= min_size,
+    )
 }
 
-pub fn add_validator(deps: DepsMut, sender: Addr, validator: String) -> StdResult<Response> {
+pub fn update_batch_expiry(
+    deps: DepsMut,
+    sender: Addr,
+    expiry: Option<u64>,
+) -> Result<Response, ContractError> {
     let state = State::default();
 
     state.assert_owner(deps.storage, &sender)?;
-
-    state.validators.update(deps.storage, |mut validators| {
-        if validators.contains(&validator) {
-            return Err(StdError::generic_err("validator is already whitelisted"));
+    match expiry {
+        Some(expiry) if expiry < MIN_BATCH_EXPIRY_SECONDS => {
+            return Err(ContractError::BatchExpiryTooShort {
+                min: MIN_BATCH_EXPIRY_SECONDS,
+            })
         }
-        validators.push(validator.clone());
-        Ok(validators)
-    })?;
-
-    let mut validators_active = state.validators_active.load(deps.storage)?;
-    if !validators_active.contains(&validator) {
-        validators_active.push(validator.clone());
+        Some(expiry) => state.batch_expiry.save(deps.storage, &expiry)?,
+        None => state.batch_expiry.remove(deps.storage),
     }
-    state
-        .validators_active
-        .save(deps.storage, &validators_active)?;
-    let event = Event::new("steakhub/validator_added").add_attribute("validator", validator);
 
     Ok(Response::new()
-        .add_event(event)
-        .add_attribute("action", "steakhub/add_validator"))
+        .add_attribute("action", "steakhub/update_batch_expiry")
+        .add_attribute("expiry", expiry.map(|e| e.to_string()).unwrap_or_default()))
 }
 
-pub fn remove_validator(
-    deps: DepsMut,
-    env: Env,
-    sender: Addr,
-    validator: String,
-) -> StdResult<Response> {
+/// Re-bond the Native Token left unclaimed in a batch that expired, minting Steak for it at the
+/// current exchange rate. The Steak is held by the hub and refunded to the batch's unbonders, pro
+/// rata to their shares, when they withdraw; the batch itself is kept until every share is claimed.
+pub fn sweep_expired_batch(deps: DepsMut, env: Env, id: u64) -> Result<Response, ContractError> {
     let state = State::default();
+    state.assert_not_wound_down(deps.storage)?;
 
-    state.assert_owner(deps.storage, &sender)?;
-    let denom = state.denom.load(deps.storage)?;
-
-    let validators = state.validators.update(deps.storage, |mut validators| {
-        if !validators.contains(&validator) {
-            return Err(StdError::generic_err(
-                "validator is not already whitelisted",
-            ));
-        }
-        validators.retain(|v| *v != validator);
-        Ok(validators)
-    })?;
-    let mut validators_active = state.validators_active.load(deps.storage)?;
-    if !validators_active.contains(&validator) {
-        validators_active.push(validator.clone());
+    let expiry = state
+        .batch_expiry
+        .may_load(deps.storage)?
+        .ok_or(ContractError::BatchExpiryNotConfigured {})?;
+    let mut batch = state.previous_batches.load(deps.storage, id)?;
+    let expires_at = batch.est_unbond_end_time.saturating_add(expiry);
+    if !batch.reconciled || env.block.time.seconds() <= expires_at {
+        return Err(ContractError::BatchNotExpired { id, expires_at });
+    }
+    if batch.amount_unclaimed.is_zero() {
+        return Err(ContractError::NothingToSweep { id });
     }
-    state
-        .validators_active
-        .save(deps.storage, &validators_active)?;
 
+    let denom = state.denom.load(deps.storage)?;
+    let validators = state.validators_active.load(deps.storage)?;
     let delegations = query_delegations(&deps.querier, &validators, &env.contract.address, &denom)?;
-    let delegation_to_remove =
-        query_delegation(&deps.querier, &validator, &env.contract.address, &denom)?;
-    let new_redelegations =
-        compute_redelegations_for_removal(&delegation_to_remove, &delegations, &denom);
+    let native_swept = batch.amount_unclaimed;
+    let new_delegation = Delegation {
+        validator: smallest_delegation(&receiving_delegations(deps.storage, &delegations)?)
+            .validator
+            .clone(),
+        amount: native_swept.u128(),
+        denom: denom.clone(),
+    };
+
+    let usteak_supply = query_usteak_supply(deps.storage, &deps.querier, &env.contract.address)?;
+    let rounding = state
+        .rounding_policy
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let rate = block_exchange_rate(deps.storage, &env, usteak_supply, &delegations)?;
+    let usteak_to_mint = compute_mint_amount(
+        rate.usteak_supply,
+        native_swept,
+        rate.native_bonded,
+        rounding,
+    )?;
+
+    let mut swept = state
+        .swept_batches
+        .may_load(deps.storage, id)?
+        .unwrap_or(SweptBatch {
+            id,
+            native_swept: Uint128::zero(),
+            usteak_unclaimed: Uint128::zero(),
+            swept_time: 0,
+        });
+    swept.native_swept += native_swept;
+    swept.usteak_unclaimed += usteak_to_mint;
+    swept.swept_time = env.block.time.seconds();
+    state.swept_batches.save(deps.storage, id, &swept)?;
+
+    batch.amount_unclaimed = Uint128::zero();
+    state.previous_batches.save(deps.storage, id, &batch)?;
 
     state.prev_denom.save(
         deps.storage,
-        &get_denom_balance(&deps.querier, env.contract.address.clone(), denom)?,
+        &get_denom_balance(&deps.querier, env.contract.address.clone(), denom.clone())?,
+    )?;
+
+    let delegate_submsg = SubMsg::reply_on_success(
+        new_delegation.to_cosmos_msg(env.contract.address.to_string())?,
+        REPLY_REGISTER_RECEIVED_COINS,
+    );
+
+    let mint_msg = usteak_mint_msg(deps.storage, &env.contract.address, usteak_to_mint)?;
+
+    let event = Event::new("steakhub/batch_swept")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("id", id.to_string())
+        .add_attribute("validator", new_delegation.validator)
+        .add_attribute("native_swept", native_swept)
+        .add_attribute("usteak_minted", usteak_to_mint)
+        .add_attribute("shares_outstanding", batch.total_shares);
+
+    Ok(Response::new()
+        .add_submessage(delegate_submsg)
+        .add_message(mint_msg)
+        .add_event(event)
+        .add_attribute("action", "steakhub/sweep_expired_batch"))
+}
+
+/// Scan up to `limit` entries of the given kind, deleting the stale ones. Batches and unbonding
+/// requests are scanned from where the previous call stopped, so repeated calls eventually cover
+/// the whole map; snapshots are keyed by day, so the stale ones always come first
+pub fn prune(
+    deps: DepsMut,
+    env: Env,
+    kind: PruneKind,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(MAX_PRUNE_LIMIT).min(MAX_PRUNE_LIMIT) as usize;
+
+    let (scanned, pruned) = match kind {
+        PruneKind::Batches => prune_batches(deps.storage, limit)?,
+        PruneKind::UnbondRequests => prune_unbond_requests(deps.storage, limit)?,
+        PruneKind::Snapshots => prune_snapshots(deps.storage, &env, limit)?,
+    };
+
+    let event = Event::new("steakhub/pruned")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("kind", kind.as_str())
+        .add_attribute("scanned", scanned.to_string())
+        .add_attribute("pruned", pruned.to_string())
+        .add_attribute("complete", (scanned < limit).to_string());
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/prune"))
+}
+
+fn prune_batches(storage: &mut dyn Storage, limit: usize) -> StdResult<(usize, usize)> {
+    let state = State::default();
+
+    let start = state
+        .prune_batches_cursor
+        .may_load(storage)?
+        .map(Bound::exclusive);
+    let batches = state
+        .previous_batches
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut pruned = 0;
+    for batch in &batches {
+        // unreconciled batches still count towards what `reconcile` expects to receive
+        if batch.reconciled && batch.total_shares.is_zero() {
+            state.previous_batches.remove(storage, batch.id)?;
+            state.swept_batches.remove(storage, batch.id);
+            clear_batch_undelegations(storage, batch.id)?;
+            clear_batch_funders(storage, batch.id)?;
+            pruned += 1;
+        }
+    }
+
+    match batches.last() {
+        Some(batch) if batches.len() == limit => {
+            state.prune_batches_cursor.save(storage, &batch.id)?
+        }
+        _ => state.prune_batches_cursor.remove(storage),
+    }
+
+    Ok((batches.len(), pruned))
+}
+
+fn prune_unbond_requests(storage: &mut dyn Storage, limit: usize) -> StdResult<(usize, usize)> {
+    let state = State::default();
+
+    let pending_id = state.pending_batch.load(storage)?.id;
+    let cursor = state.prune_requests_cursor.may_load(storage)?;
+    let start = cursor
+        .as_ref()
+        .map(|(id, user)| Bound::exclusive((*id, user)));
+    let requests = state
+        .unbond_requests
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut pruned = 0;
+    for request in &requests {
+        let orphaned = request.id != pending_id && !state.previous_batches.has(storage, request.id);
+        if request.shares.is_zero() || orphaned {
+            state
+                .unbond_requests
+                .remove(storage, (request.id, &request.user))?;
+            pruned += 1;
+        }
+    }
+
+    match requests.last() {
+        Some(request) if requests.len() == limit => state
+            .prune_requests_cursor
+            .save(storage, &(request.id, request.user.clone()))?,
+        _ => state.prune_requests_cursor.remove(storage),
+    }
+
+    Ok((requests.len(), pruned))
+}
+
+fn prune_snapshots(
+    storage: &mut dyn Storage,
+    env: &Env,
+    limit: usize,
+) -> StdResult<(usize, usize)> {
+    let state = State::default();
+
+    let today = env.block.time.seconds() / SECONDS_PER_DAY;
+    let cutoff = today.saturating_sub(SNAPSHOT_RETENTION_DAYS);
+    let days = state
+        .daily_snapshots
+        .keys(
+            storage,
+            None,
+            Some(Bound::exclusive(cutoff)),
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    for day in &days {
+        state.daily_snapshots.remove(storage, *day);
+    }
+
+    Ok((days.len(), days.len()))
+}
+
+//--------------------------------------------------------------------------------------------------
+// Locked bonding logics
+//--------------------------------------------------------------------------------------------------
+
+/// The minted Steak is held by the hub itself, so locked shares can not be transferred. They are
+/// only released through `unlock_bond`, which queues them for unbonding on behalf of the user.
+pub fn bond_locked(
+    mut deps: DepsMut,
+    env: Env,
+    funder: Addr,
+    receiver: Addr,
+    duration: u64,
+    funds: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    assert_lock_duration(duration)?;
+
+    // what rides along the deposit is refunded by the bond itself
+    let (amount_bonded, _) =
+        received_deposit(deps.storage, &funds, &state.denom.load(deps.storage)?)?;
+    let (response, usteak_minted) = bond_internal(
+        deps.branch(),
+        env.clone(),
+        funder.clone(),
+        env.contract.address.clone(),
+        funds,
     )?;
 
-    let redelegate_submsgs = new_redelegations
+    // the Steak is minted to the hub, so the bond is recorded in the locker's history here
+    record_history(
+        deps.storage,
+        &env,
+        &receiver,
+        HistoryAction::Bond,
+        amount_bonded,
+        usteak_minted,
+    )?;
+
+    let lock = create_lock(deps.storage, &env, &receiver, usteak_minted, duration)?;
+
+    let event = Event::new("steakhub/bond_locked")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("id", lock.id.to_string())
+        .add_attribute("funder", funder)
+        .add_attribute("receiver", receiver)
+        .add_attribute("usteak_locked", usteak_minted)
+        .add_attribute("unlock_time", lock.unlock_time.to_string())
+        .add_attribute("multiplier", lock.multiplier.to_string());
+
+    Ok(response
+        .add_event(event)
+        .add_attribute("action", "steakhub/bond_locked"))
+}
+
+/// Lock Steak the user already holds. Unlike `bond_locked`, the Steak is returned as is by
+/// `unlock` once the lock expires
+pub fn lock(
+    deps: DepsMut,
+    env: Env,
+    user: Addr,
+    usteak_to_lock: Uint128,
+    duration: u64,
+) -> Result<Response, ContractError> {
+    State::default().assert_not_wound_down(deps.storage)?;
+    assert_lock_duration(duration)?;
+
+    let lock = create_lock(deps.storage, &env, &user, usteak_to_lock, duration)?;
+
+    let event = Event::new("steakhub/locked")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("id", lock.id.to_string())
+        .add_attribute("user", user)
+        .add_attribute("usteak_locked", usteak_to_lock)
+        .add_attribute("unlock_time", lock.unlock_time.to_string())
+        .add_attribute("multiplier", lock.multiplier.to_string());
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/lock"))
+}
+
+pub fn unlock(deps: DepsMut, env: Env, user: Addr) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let current_time = env.block.time.seconds();
+    let expired_locks = state
+        .locked_bonds
+        .prefix(&user)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect::<StdResult<Vec<LockedBond>>>()?
+        .into_iter()
+        .filter(|lock| lock.unlock_time <= current_time)
+        .collect::<Vec<_>>();
+
+    if expired_locks.is_empty() {
+        return Err(ContractError::NothingToUnlock {});
+    }
+
+    let mut usteak_unlocked = Uint128::zero();
+    let mut total_boost = Uint128::zero();
+    for lock in &expired_locks {
+        total_boost += release_lock(deps.storage, lock)?;
+        usteak_unlocked += lock.shares;
+    }
+
+    let steak_token = state.steak_token.load(deps.storage)?;
+    let denom = state.denom.load(deps.storage)?;
+    let mut response = Response::new().add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: steak_token.into(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: user.to_string(),
+            amount: usteak_unlocked,
+        })?,
+        funds: vec![],
+    }));
+    if !total_boost.is_zero() {
+        response = response.add_message(boost_payout_msg(&user, total_boost, &denom));
+    }
+
+    let ids = expired_locks
+        .iter()
+        .map(|lock| lock.id.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let event = Event::new("steakhub/unlocked")
+        .add_attribute("time", current_time.to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("user", user)
+        .add_attribute("ids", ids)
+        .add_attribute("usteak_unlocked", usteak_unlocked)
+        .add_attribute("boost_claimed", total_boost);
+
+    Ok(response
+        .add_event(event)
+        .add_attribute("action", "steakhub/unlock"))
+}
+
+pub fn unlock_bond(
+    deps: DepsMut,
+    env: Env,
+    user: Addr,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let lock = state
+        .locked_bonds
+        .may_load(deps.storage, (&user, id))?
+        .ok_or(ContractError::LockedBondNotFound { id })?;
+
+    if env.block.time.seconds() < lock.unlock_time {
+        return Err(ContractError::BondStillLocked {
+            unlock_time: lock.unlock_time,
+        });
+    }
+
+    let denom = state.denom.load(deps.storage)?;
+    let boost = release_lock(deps.storage, &lock)?;
+
+    let mut response = queue_unbond(deps, env.clone(), user.clone(), user.clone(), lock.shares)?;
+    if !boost.is_zero() {
+        response = response.add_message(boost_payout_msg(&user, boost, &denom));
+    }
+
+    let event = Event::new("steakhub/bond_unlocked")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("id", id.to_string())
+        .add_attribute("user", user)
+        .add_attribute("usteak_unlocked", lock.shares)
+        .add_attribute("boost_claimed", boost);
+
+    Ok(response
+        .add_event(event)
+        .add_attribute("action", "steakhub/unlock_bond"))
+}
+
+pub fn claim_lock_boost(deps: DepsMut, env: Env, user: Addr) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let locks = state
+        .locked_bonds
+        .prefix(&user)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let denom = state.denom.load(deps.storage)?;
+    let mut total_boost = Uint128::zero();
+    for mut lock in locks {
+        total_boost += settle_lock_boost(deps.storage, &lock)?;
+        lock.boost_index = state.lock_boost_index.load(deps.storage)?;
+        state
+            .locked_bonds
+            .save(deps.storage, (&user, lock.id), &lock)?;
+    }
+
+    if total_boost.is_zero() {
+        return Err(ContractError::NoBoostToClaim {});
+    }
+
+    let event = Event::new("steakhub/lock_boost_claimed")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("user", &user)
+        .add_attribute("boost_claimed", total_boost);
+
+    Ok(Response::new()
+        .add_message(boost_payout_msg(&user, total_boost, &denom))
+        .add_event(event)
+        .add_attribute("action", "steakhub/claim_lock_boost"))
+}
+
+pub fn update_lock_boost(
+    deps: DepsMut,
+    sender: Addr,
+    boost_rate: Decimal,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    if boost_rate > Decimal::one() {
+        return Err(ContractError::RateAboveOne {
+            name: "boost rate".to_string(),
+        });
+    }
+    state.lock_boost_rate.save(deps.storage, &boost_rate)?;
+
+    let event = Event::new("steakhub/lock_boost_updated")
+        .add_attribute("boost_rate", boost_rate.to_string());
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/update_lock_boost"))
+}
+
+pub fn update_lock_tiers(
+    deps: DepsMut,
+    sender: Addr,
+    tiers: Vec<LockTier>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    if tiers.len() > MAX_LOCK_TIERS {
+        return Err(ContractError::InvalidLockTiers {
+            reason: format!("at most {} tiers are allowed", MAX_LOCK_TIERS),
+        });
+    }
+    for tier in &tiers {
+        if tier.min_duration > MAX_LOCK_DURATION_SECONDS {
+            return Err(ContractError::InvalidLockTiers {
+                reason: format!(
+                    "minimum duration {} exceeds the maximum lock duration",
+                    tier.min_duration
+                ),
+            });
+        }
+        if tier.multiplier < Decimal::one() {
+            return Err(ContractError::InvalidLockTiers {
+                reason: format!("multiplier {} is below one", tier.multiplier),
+            });
+        }
+    }
+    for pair in tiers.windows(2) {
+        if pair[1].min_duration <= pair[0].min_duration || pair[1].multiplier < pair[0].multiplier {
+            return Err(ContractError::InvalidLockTiers {
+                reason: "tiers must be ordered by increasing duration and multiplier".to_string(),
+            });
+        }
+    }
+    state.lock_tiers.save(deps.storage, &tiers)?;
+
+    let tiers_str = tiers
+        .iter()
+        .map(|tier| format!("{}:{}", tier.min_duration, tier.multiplier))
+        .collect::<Vec<_>>()
+        .join(",");
+    let event = Event::new("steakhub/lock_tiers_updated").add_attribute("tiers", tiers_str);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/update_lock_tiers"))
+}
+
+fn assert_lock_duration(duration: u64) -> Result<(), ContractError> {
+    if duration == 0 || duration > MAX_LOCK_DURATION_SECONDS {
+        return Err(ContractError::InvalidLockDuration {
+            max: MAX_LOCK_DURATION_SECONDS,
+        });
+    }
+    Ok(())
+}
+
+/// Record a lock of `shares` already held by the hub on behalf of `user`, boosted by the highest
+/// tier `duration` qualifies for
+fn create_lock(
+    storage: &mut dyn Storage,
+    env: &Env,
+    user: &Addr,
+    shares: Uint128,
+    duration: u64,
+) -> StdResult<LockedBond> {
+    let state = State::default();
+
+    let id = state.next_lock_id.may_load(storage)?.unwrap_or(1);
+    state.next_lock_id.save(storage, &(id + 1))?;
+
+    let multiplier = state
+        .lock_tiers
+        .may_load(storage)?
+        .unwrap_or_default()
+        .iter()
+        .rev()
+        .find(|tier| tier.min_duration <= duration)
+        .map_or(Decimal::one(), |tier| tier.multiplier);
+    let lock = LockedBond {
+        id,
+        user: user.clone(),
+        shares,
+        unlock_time: env.block.time.seconds() + duration,
+        boost_index: state
+            .lock_boost_index
+            .may_load(storage)?
+            .unwrap_or_default(),
+        multiplier,
+    };
+    state.locked_bonds.save(storage, (user, id), &lock)?;
+
+    let total_lock_weight = load_total_lock_weight(storage)?;
+    state
+        .total_lock_weight
+        .save(storage, &(total_lock_weight + lock_weight(&lock)?))?;
+    let total_locked_shares = state
+        .total_locked_shares
+        .may_load(storage)?
+        .unwrap_or_default();
+    state
+        .total_locked_shares
+        .save(storage, &(total_locked_shares + shares))?;
+
+    Ok(lock)
+}
+
+/// Remove `lock`, returning the boost it accrued since it was last settled
+fn release_lock(storage: &mut dyn Storage, lock: &LockedBond) -> StdResult<Uint128> {
+    let state = State::default();
+
+    let boost = settle_lock_boost(storage, lock)?;
+    state.locked_bonds.remove(storage, (&lock.user, lock.id));
+
+    let total_lock_weight = load_total_lock_weight(storage)?;
+    state
+        .total_lock_weight
+        .save(storage, &total_lock_weight.checked_sub(lock_weight(lock)?)?)?;
+    state
+        .total_locked_shares
+        .update(storage, |total| -> StdResult<_> {
+            Ok(total.checked_sub(lock.shares)?)
+        })?;
+
+    Ok(boost)
+}
+
+/// Locks created before tiers were introduced all weigh their shares, so until a lock is created or
+/// released under tiers the total weight is the total locked shares
+pub(crate) fn load_total_lock_weight(storage: &dyn Storage) -> StdResult<Uint128> {
+    let state = State::default();
+    match state.total_lock_weight.may_load(storage)? {
+        Some(total_lock_weight) => Ok(total_lock_weight),
+        None => Ok(state
+            .total_locked_shares
+            .may_load(storage)?
+            .unwrap_or_default()),
+    }
+}
+
+fn lock_weight(lock: &LockedBond) -> StdResult<Uint128> {
+    lock.multiplier.checked_mul_uint(lock.shares)
+}
+
+/// Carve the lockers' share out of `fee_amount`, crediting it to the boost index. Returns the amount
+/// kept by the hub for the lockers
+fn accrue_lock_boost(storage: &mut dyn Storage, fee_amount: Uint128) -> StdResult<Uint128> {
+    let state = State::default();
+
+    let boost_rate = state.lock_boost_rate.may_load(storage)?.unwrap_or_default();
+    let total_lock_weight = load_total_lock_weight(storage)?;
+    if boost_rate.is_zero() || total_lock_weight.is_zero() || fee_amount.is_zero() {
+        return Ok(Uint128::zero());
+    }
+
+    let boost = boost_rate.checked_mul_uint(fee_amount)?;
+    // only the amount that is fully reflected in the index is kept, so the index never promises
+    // more than the hub holds
+    let index_increment = Decimal::from_ratio(boost, total_lock_weight);
+    let boost = index_increment.checked_mul_uint(total_lock_weight)?;
+
+    let boost_index = state
+        .lock_boost_index
+        .may_load(storage)?
+        .unwrap_or_default();
+    state
+        .lock_boost_index
+        .save(storage, &boost_index.checked_add(index_increment)?)?;
+    let boost_unclaimed = state
+        .lock_boost_unclaimed
+        .may_load(storage)?
+        .unwrap_or_default();
+    state
+        .lock_boost_unclaimed
+        .save(storage, &(boost_unclaimed + boost))?;
+
+    Ok(boost)
+}
+
+/// Compute the boost accrued by `lock` since it was last settled, and deduct it from the unclaimed
+/// boost held by the hub
+fn settle_lock_boost(storage: &mut dyn Storage, lock: &LockedBond) -> StdResult<Uint128> {
+    let state = State::default();
+
+    let boost = compute_lock_boost(
+        state
+            .lock_boost_index
+            .may_load(storage)?
+            .unwrap_or_default(),
+        lock,
+    )?;
+    if !boost.is_zero() {
+        state
+            .lock_boost_unclaimed
+            .update(storage, |unclaimed| -> StdResult<_> {
+                Ok(unclaimed.saturating_sub(boost))
+            })?;
+    }
+    Ok(boost)
+}
+
+pub(crate) fn compute_lock_boost(boost_index: Decimal, lock: &LockedBond) -> StdResult<Uint128> {
+    (boost_index - lock.boost_index).checked_mul_uint(lock_weight(lock)?)
+}
+
+fn boost_payout_msg(user: &Addr, amount: Uint128, denom: &str) -> CosmosMsg {
+    CosmosMsg::Bank(BankMsg::Send {
+        to_address: user.to_string(),
+        amount: vec![Coin::new(amount.u128(), denom)],
+    })
+}
+
+//--------------------------------------------------------------------------------------------------
+// Principal/yield split logics
+//--------------------------------------------------------------------------------------------------
+
+pub fn enable_yield_split(
+    deps: DepsMut,
+    sender: Addr,
+    principal_token: String,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    if state.principal_token.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::YieldSplittingEnabled {});
+    }
+
+    let principal_token = deps.api.addr_validate(&principal_token)?;
+    state.principal_token.save(deps.storage, &principal_token)?;
+    state.yield_pool.save(deps.storage, &YieldPool::default())?;
+
+    let event = Event::new("steakhub/yield_split_enabled")
+        .add_attribute("principal_token", principal_token);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/enable_yield_split"))
+}
+
+/// Load the principal/yield pool with yield accrued up to the current exchange rate. Also returns
+/// the Steak supply and the amount of native bonded the exchange rate was derived from
+pub(crate) fn load_yield_pool(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper,
+    contract_addr: &Addr,
+) -> StdResult<(YieldPool, Uint128, Uint128)> {
+    let state = State::default();
+
+    let (usteak_supply, native_bonded) = query_supply_and_bonded(storage, querier, contract_addr)?;
+
+    let mut pool = state.yield_pool.may_load(storage)?.unwrap_or_default();
+    accrue_yield(&mut pool, usteak_supply, native_bonded)?;
+
+    Ok((pool, usteak_supply, native_bonded))
+}
+
+/// Load a user's yield position, settled against `pool`
+pub(crate) fn load_yield_position(
+    storage: &dyn Storage,
+    pool: &YieldPool,
+    user: &Addr,
+) -> StdResult<Option<YieldPosition>> {
+    let state = State::default();
+
+    let mut position = match state.yield_positions.may_load(storage, user)? {
+        Some(position) => position,
+        None => return Ok(None),
+    };
+    let retired_index = if position.epoch < pool.epoch {
+        Some(state.yield_epoch_indexes.load(storage, position.epoch)?)
+    } else {
+        None
+    };
+    settle_yield_position(pool, &mut position, retired_index)?;
+
+    Ok(Some(position))
+}
+
+/// Once all principal has been redeemed, whatever the pool still holds beyond the reserved yield
+/// goes to the yield shares outstanding, which are then retired. Shares issued by later splits
+/// would otherwise be diluted by shares no principal backs anymore
+fn retire_yield_shares(
+    storage: &mut dyn Storage,
+    pool: &mut YieldPool,
+    usteak_supply: Uint128,
+    native_bonded: Uint128,
+) -> StdResult<()> {
+    if !pool.principal.is_zero() || pool.shares.is_zero() {
+        return Ok(());
+    }
+    accrue_yield(pool, usteak_supply, native_bonded)?;
+    State::default()
+        .yield_epoch_indexes
+        .save(storage, pool.epoch, &pool.index)?;
+    pool.epoch += 1;
+    pool.shares = Uint128::zero();
+    Ok(())
+}
+
+/// NOTE: yield shares track the outstanding principal pro-rata, so redeeming principal shrinks the
+/// yield earned by every share rather than that of the original splitter.
+pub fn split_yield(
+    deps: DepsMut,
+    env: Env,
+    user: Addr,
+    receiver: Addr,
+    usteak_amount: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_not_wound_down(deps.storage)?;
+    let principal_token = state
+        .principal_token
+        .may_load(deps.storage)?
+        .ok_or(ContractError::YieldSplittingNotEnabled {})?;
+
+    let (mut pool, usteak_supply, native_bonded) =
+        load_yield_pool(deps.storage, &deps.querier, &env.contract.address)?;
+    retire_yield_shares(deps.storage, &mut pool, usteak_supply, native_bonded)?;
+
+    let principal = usteak_amount.multiply_ratio(native_bonded, usteak_supply);
+    if principal.is_zero() {
+        return Err(ContractError::SplitAmountTooSmall {});
+    }
+    let shares = if pool.shares.is_zero() || pool.principal.is_zero() {
+        principal
+    } else {
+        principal.multiply_ratio(pool.shares, pool.principal)
+    };
+
+    let mut position = load_yield_position(deps.storage, &pool, &user)?.unwrap_or(YieldPosition {
+        index: pool.index,
+        epoch: pool.epoch,
+        ..Default::default()
+    });
+    position.shares += shares;
+    state.yield_positions.save(deps.storage, &user, &position)?;
+
+    pool.principal += principal;
+    pool.usteak += usteak_amount;
+    pool.shares += shares;
+    state.yield_pool.save(deps.storage, &pool)?;
+
+    let mint_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: principal_token.into(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: receiver.to_string(),
+            amount: principal,
+        })?,
+        funds: vec![],
+    });
+
+    let event = Event::new("steakhub/yield_split")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("user", user)
+        .add_attribute("receiver", receiver)
+        .add_attribute("usteak_deposited", usteak_amount)
+        .add_attribute("principal_minted", principal)
+        .add_attribute("yield_shares", shares);
+
+    Ok(Response::new()
+        .add_message(mint_msg)
+        .add_event(event)
+        .add_attribute("action", "steakhub/split_yield"))
+}
+
+pub fn redeem_principal(
+    deps: DepsMut,
+    env: Env,
+    receiver: Addr,
+    principal_amount: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let principal_token = state
+        .principal_token
+        .may_load(deps.storage)?
+        .ok_or(ContractError::YieldSplittingNotEnabled {})?;
+    let steak_token = state.steak_token.load(deps.storage)?;
+
+    let (mut pool, usteak_supply, native_bonded) =
+        load_yield_pool(deps.storage, &deps.querier, &env.contract.address)?;
+
+    // if validators were slashed, the principal may no longer be fully backed; it never eats into
+    // yield that has already been reserved
+    let usteak_to_return =
+        compute_usteak_for_native(principal_amount, usteak_supply, native_bonded, false)?
+            .min(pool.usteak.saturating_sub(pool.reserved));
+
+    pool.principal = pool.principal.checked_sub(principal_amount)?;
+    pool.usteak = pool.usteak.checked_sub(usteak_to_return)?;
+    retire_yield_shares(deps.storage, &mut pool, usteak_supply, native_bonded)?;
+    state.yield_pool.save(deps.storage, &pool)?;
+
+    let burn_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: principal_token.into(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn {
+            amount: principal_amount,
+        })?,
+        funds: vec![],
+    });
+    let transfer_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: steak_token.into(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: receiver.to_string(),
+            amount: usteak_to_return,
+        })?,
+        funds: vec![],
+    });
+
+    let event = Event::new("steakhub/principal_redeemed")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("receiver", receiver)
+        .add_attribute("principal_burned", principal_amount)
+        .add_attribute("usteak_returned", usteak_to_return);
+
+    Ok(Response::new()
+        .add_message(burn_msg)
+        .add_message(transfer_msg)
+        .add_event(event)
+        .add_attribute("action", "steakhub/redeem_principal"))
+}
+
+pub fn claim_yield(
+    deps: DepsMut,
+    env: Env,
+    user: Addr,
+    receiver: Addr,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let steak_token = state.steak_token.load(deps.storage)?;
+    let (mut pool, _, _) = load_yield_pool(deps.storage, &deps.querier, &env.contract.address)?;
+
+    let mut position =
+        load_yield_position(deps.storage, &pool, &user)?.ok_or(ContractError::NoYieldShares {})?;
+
+    let usteak_to_claim = position.pending;
+    if usteak_to_claim.is_zero() {
+        return Err(ContractError::NoYieldToClaim {});
+    }
+    position.pending = Uint128::zero();
+    state.yield_positions.save(deps.storage, &user, &position)?;
+
+    pool.reserved = pool.reserved.saturating_sub(usteak_to_claim);
+    pool.usteak = pool.usteak.checked_sub(usteak_to_claim)?;
+    state.yield_pool.save(deps.storage, &pool)?;
+
+    let transfer_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: steak_token.into(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: receiver.to_string(),
+            amount: usteak_to_claim,
+        })?,
+        funds: vec![],
+    });
+
+    let event = Event::new("steakhub/yield_claimed")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("user", user)
+        .add_attribute("receiver", receiver)
+        .add_attribute("usteak_claimed", usteak_to_claim);
+
+    Ok(Response::new()
+        .add_message(transfer_msg)
+        .add_event(event)
+        .add_attribute("action", "steakhub/claim_yield"))
+}
+
+//--------------------------------------------------------------------------------------------------
+// Rebasing logics
+//--------------------------------------------------------------------------------------------------
+
+/// Query the Steak supply and the amount of native bonded, which together set the exchange rate
+pub(crate) fn query_supply_and_bonded(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper,
+    contract_addr: &Addr,
+) -> StdResult<(Uint128, Uint128)> {
+    let state = State::default();
+
+    let denom = state.denom.load(storage)?;
+    let validators = state.validators.load(storage)?;
+
+    let delegations = query_delegations(querier, &validators, contract_addr, &denom)?;
+    let native_bonded = Uint128::new(delegations.iter().map(|d| d.amount).sum());
+    let usteak_supply = query_usteak_supply(storage, querier, contract_addr)?;
+
+    Ok((usteak_supply, native_bonded))
+}
+
+/// Query the Steak supply backed by this hub: the token's total supply or, when the token is shared
+/// with other hubs through a coordinator, the part of it the coordinator attributes to this hub
+pub(crate) fn query_usteak_supply(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper,
+    contract_addr: &Addr,
+) -> StdResult<Uint128> {
+    let state = State::default();
+
+    match state.coordinator.may_load(storage)? {
+        Some(coordinator) => {
+            let hub: HubSupply = querier.query_wasm_smart(
+                coordinator,
+                &CoordinatorQueryMsg::HubSupply {
+                    hub: contract_addr.to_string(),
+                },
+            )?;
+            Ok(hub.supply)
+        }
+        None => query_cw20_total_supply(querier, &state.steak_token.load(storage)?),
+    }
+}
+
+/// Mint `amount` of Steak to `recipient`, through the coordinator if the token is shared
+pub(crate) fn usteak_mint_msg(
+    storage: &dyn Storage,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    let state = State::default();
+
+    let msg = match state.coordinator.may_load(storage)? {
+        Some(coordinator) => WasmMsg::Execute {
+            contract_addr: coordinator.into(),
+            msg: to_binary(&CoordinatorExecuteMsg::Mint {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        },
+        None => WasmMsg::Execute {
+            contract_addr: state.steak_token.load(storage)?.into(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: recipient.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        },
+    };
+    Ok(CosmosMsg::Wasm(msg))
+}
+
+/// Burn `amount` of the Steak held by the hub, through the coordinator if the token is shared
+pub(crate) fn usteak_burn_msg(storage: &dyn Storage, amount: Uint128) -> StdResult<CosmosMsg> {
+    let state = State::default();
+
+    let cw20_msg = match state.coordinator.may_load(storage)? {
+        Some(coordinator) => Cw20ExecuteMsg::Send {
+            contract: coordinator.into(),
+            amount,
+            msg: to_binary(&CoordinatorReceiveMsg::Burn {})?,
+        },
+        None => Cw20ExecuteMsg::Burn { amount },
+    };
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: state.steak_token.load(storage)?.into(),
+        msg: to_binary(&cw20_msg)?,
+        funds: vec![],
+    }))
+}
+
+/// Steak queued for unbonding without going through the coordinator, e.g. bought back by arbitrage,
+/// must be set aside from the hub's supply there, or the coordinator refuses to burn it
+fn coordinator_reserve_msg(
+    storage: &dyn Storage,
+    sender: &Addr,
+    amount: Uint128,
+) -> StdResult<Option<CosmosMsg>> {
+    match State::default().coordinator.may_load(storage)? {
+        Some(coordinator) if *sender != coordinator => {
+            Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: coordinator.into(),
+                msg: to_binary(&CoordinatorExecuteMsg::Reserve { amount })?,
+                funds: vec![],
+            })))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Switch the hub over to a Steak token shared with other hubs, minting and burning through
+/// `coordinator`. Only a hub that has not minted anything yet can join a coordinator
+pub fn set_coordinator(
+    deps: DepsMut,
+    sender: Addr,
+    coordinator: Addr,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.assert_not_wound_down(deps.storage)?;
+    if state.coordinator.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::CoordinatorAlreadySet {});
+    }
+    let usteak_supply =
+        query_cw20_total_supply(&deps.querier, &state.steak_token.load(deps.storage)?)?;
+    if !usteak_supply.is_zero() {
+        return Err(ContractError::HubNotEmpty {});
+    }
+
+    let config: CoordinatorConfigResponse = deps
+        .querier
+        .query_wasm_smart(&coordinator, &CoordinatorQueryMsg::Config {})?;
+    let steak_token = deps.api.addr_validate(&config.steak_token)?;
+    state.steak_token.save(deps.storage, &steak_token)?;
+    state.coordinator.save(deps.storage, &coordinator)?;
+    // the admin of the coordinator's token is not the hub's to know
+    state.token_admin.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("action", "steakhub/set_coordinator")
+        .add_attribute("coordinator", coordinator)
+        .add_attribute("steak_token", steak_token))
+}
+
+/// NOTE: rebasing balances are a ledger of `usteak` held by the hub; the balance of each account is
+/// derived from its shares at the current exchange rate, so it grows as rewards are reinvested.
+pub fn wrap_rebasing(
+    deps: DepsMut,
+    env: Env,
+    user: Addr,
+    receiver: Addr,
+    usteak_amount: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_not_wound_down(deps.storage)?;
+    state
+        .rebasing_shares
+        .update(deps.storage, &receiver, |shares| -> StdResult<_> {
+            Ok(shares.unwrap_or_default() + usteak_amount)
+        })?;
+    let total_rebasing_shares = state
+        .total_rebasing_shares
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    state
+        .total_rebasing_shares
+        .save(deps.storage, &(total_rebasing_shares + usteak_amount))?;
+
+    let event = Event::new("steakhub/rebasing_wrapped")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("user", user)
+        .add_attribute("receiver", receiver)
+        .add_attribute("usteak_wrapped", usteak_amount);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/wrap_rebasing"))
+}
+
+pub fn transfer_rebasing(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    recipient: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let (usteak_supply, native_bonded) =
+        query_supply_and_bonded(deps.storage, &deps.querier, &env.contract.address)?;
+    let shares = compute_usteak_for_native(amount, usteak_supply, native_bonded, true)?;
+
+    debit_rebasing_shares(deps.storage, &sender, shares)?;
+    state
+        .rebasing_shares
+        .update(deps.storage, &recipient, |balance| -> StdResult<_> {
+            Ok(balance.unwrap_or_default() + shares)
+        })?;
+
+    let event = Event::new("steakhub/rebasing_transferred")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("sender", sender)
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", amount)
+        .add_attribute("shares", shares);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/transfer_rebasing"))
+}
+
+pub fn unwrap_rebasing(
+    deps: DepsMut,
+    env: Env,
+    user: Addr,
+    receiver: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let steak_token = state.steak_token.load(deps.storage)?;
+    let (usteak_supply, native_bonded) =
+        query_supply_and_bonded(deps.storage, &deps.querier, &env.contract.address)?;
+    let shares = compute_usteak_for_native(amount, usteak_supply, native_bonded, true)?;
+
+    debit_rebasing_shares(deps.storage, &user, shares)?;
+    state
+        .total_rebasing_shares
+        .update(deps.storage, |total| -> StdResult<_> {
+            Ok(total.checked_sub(shares)?)
+        })?;
+
+    let transfer_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: steak_token.into(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: receiver.to_string(),
+            amount: shares,
+        })?,
+        funds: vec![],
+    });
+
+    let event = Event::new("steakhub/rebasing_unwrapped")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("user", user)
+        .add_attribute("receiver", receiver)
+        .add_attribute("amount", amount)
+        .add_attribute("usteak_unwrapped", shares);
+
+    Ok(Response::new()
+        .add_message(transfer_msg)
+        .add_event(event)
+        .add_attribute("action", "steakhub/unwrap_rebasing"))
+}
+
+fn debit_rebasing_shares(
+    storage: &mut dyn Storage,
+    user: &Addr,
+    shares: Uint128,
+) -> Result<(), ContractError> {
+    let state = State::default();
+
+    let balance = state
+        .rebasing_shares
+        .may_load(storage, user)?
+        .unwrap_or_default();
+    if shares.is_zero() || shares > balance {
+        return Err(ContractError::InsufficientRebasingBalance {
+            balance,
+            requested: shares,
+        });
+    }
+
+    let remaining = balance - shares;
+    if remaining.is_zero() {
+        state.rebasing_shares.remove(storage, user);
+    } else {
+        state.rebasing_shares.save(storage, user, &remaining)?;
+    }
+    Ok(())
+}
+
+//--------------------------------------------------------------------------------------------------
+// Protocol-owned liquidity logics
+//--------------------------------------------------------------------------------------------------
+
+pub fn configure_pol(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    pair: String,
+    lp_token: String,
+    fee_rate: Decimal,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    if fee_rate > Decimal::one() {
+        return Err(ContractError::RateAboveOne {
+            name: "pol fee rate".to_string(),
+        });
+    }
+
+    let pair = deps.api.addr_validate(&pair)?;
+    let lp_token = deps.api.addr_validate(&lp_token)?;
+    if let Some(current_lp_token) = state.pol_lp_token.may_load(deps.storage)? {
+        if current_lp_token != lp_token
+            && !query_cw20_balance(&deps.querier, &current_lp_token, &env.contract.address)?
+                .is_zero()
+        {
+            return Err(ContractError::PolLiquidityNotRemoved {});
+        }
+    }
+    state.pol_pair.save(deps.storage, &pair)?;
+    state.pol_lp_token.save(deps.storage, &lp_token)?;
+    state.pol_fee_rate.save(deps.storage, &fee_rate)?;
+
+    let event = Event::new("steakhub/pol_configured")
+        .add_attribute("pair", pair)
+        .add_attribute("lp_token", lp_token)
+        .add_attribute("fee_rate", fee_rate.to_string());
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/configure_pol"))
+}
+
+/// NOTE: the split between the native bonded and the native paired depends on the pair's current
+/// price, so it is left to the owner; the pair enforces `slippage_tolerance` on the deposit.
+pub fn add_pol(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    bond_amount: Uint128,
+    slippage_tolerance: Option<Decimal>,
+    funds: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.assert_not_wound_down(deps.storage)?;
+    let pair = state
+        .pol_pair
+        .may_load(deps.storage)?
+        .ok_or(ContractError::PolNotConfigured {})?;
+    let denom = state.denom.load(deps.storage)?;
+    let steak_token = state.steak_token.load(deps.storage)?;
+
+    let native_sent = funds
+        .iter()
+        .filter(|coin| coin.denom == denom)
+        .map(|coin| coin.amount)
+        .sum::<Uint128>();
+    let native_reserve = state
+        .pol_native_reserve
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let native_available = native_reserve + native_sent;
+    let native_to_pair = native_available.checked_sub(bond_amount).map_err(|_| {
+        ContractError::InsufficientPolNative {
+            available: native_available,
+        }
+    })?;
+
+    let mut response = Response::new();
+    let mut usteak_to_pair = state
+        .pol_usteak_reserve
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if !bond_amount.is_zero() {
+        let (bond_response, usteak_minted) = bond_internal(
+            deps.branch(),
+            env.clone(),
+            env.contract.address.clone(),
+            env.contract.address.clone(),
+            vec![Coin::new(bond_amount.u128(), &denom)],
+        )?;
+        response = bond_response;
+        usteak_to_pair += usteak_minted;
+    }
+    if native_to_pair.is_zero() || usteak_to_pair.is_zero() {
+        return Err(ContractError::PolAssetsMissing {});
+    }
+
+    state
+        .pol_native_reserve
+        .save(deps.storage, &Uint128::zero())?;
+    state
+        .pol_usteak_reserve
+        .save(deps.storage, &Uint128::zero())?;
+
+    let allowance_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: steak_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+            spender: pair.to_string(),
+            amount: usteak_to_pair,
+            expires: None,
+        })?,
+        funds: vec![],
+    });
+    let provide_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: pair.to_string(),
+        msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
+            assets: vec![
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: steak_token.into(),
+                    },
+                    amount: usteak_to_pair,
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: denom.clone(),
+                    },
+                    amount: native_to_pair,
+                },
+            ],
+            slippage_tolerance,
+            receiver: None,
+        })?,
+        funds: vec![Coin::new(native_to_pair.u128(), &denom)],
+    });
+
+    let event = Event::new("steakhub/pol_added")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("pair", pair)
+        .add_attribute("native_bonded", bond_amount)
+        .add_attribute("native_provided", native_to_pair)
+        .add_attribute("usteak_provided", usteak_to_pair);
+
+    Ok(response
+        .add_message(allowance_msg)
+        .add_message(provide_msg)
+        .add_event(event)
+        .add_attribute("action", "steakhub/add_pol"))
+}
+
+pub fn remove_pol(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    lp_amount: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    let pair = state
+        .pol_pair
+        .may_load(deps.storage)?
+        .ok_or(ContractError::PolNotConfigured {})?;
+    let lp_token = state.pol_lp_token.load(deps.storage)?;
+    let denom = state.denom.load(deps.storage)?;
+    let steak_token = state.steak_token.load(deps.storage)?;
+
+    let withdraw_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: lp_token.into(),
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: pair.to_string(),
+            amount: lp_amount,
+            msg: to_binary(&PairCw20HookMsg::WithdrawLiquidity {})?,
+        })?,
+        funds: vec![],
+    });
+
+    // the amounts returned by the pair are only known once the withdrawal has executed, so they are
+    // credited to the reserves from the balance differences in a callback
+    let callback_msg = CallbackMsg::SettlePolWithdrawal {
+        native_before: get_denom_balance(&deps.querier, env.contract.address.clone(), denom)?,
+        usteak_before: query_cw20_balance(&deps.querier, &steak_token, &env.contract.address)?,
+    }
+    .into_cosmos_msg(&env.contract.address)?;
+
+    let event = Event::new("steakhub/pol_removed")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("pair", pair)
+        .add_attribute("lp_amount", lp_amount);
+
+    Ok(Response::new()
+        .add_message(withdraw_msg)
+        .add_message(callback_msg)
+        .add_event(event)
+        .add_attribute("action", "steakhub/remove_pol"))
+}
+
+pub fn settle_pol_withdrawal(
+    deps: DepsMut,
+    env: Env,
+    native_before: Uint128,
+    usteak_before: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let denom = state.denom.load(deps.storage)?;
+    let steak_token = state.steak_token.load(deps.storage)?;
+
+    let native_returned = get_denom_balance(&deps.querier, env.contract.address.clone(), denom)?
+        .saturating_sub(native_before);
+    let usteak_returned = query_cw20_balance(&deps.querier, &steak_token, &env.contract.address)?
+        .saturating_sub(usteak_before);
+
+    let native_reserve = state
+        .pol_native_reserve
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    state
+        .pol_native_reserve
+        .save(deps.storage, &(native_reserve + native_returned))?;
+    let usteak_reserve = state
+        .pol_usteak_reserve
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    state
+        .pol_usteak_reserve
+        .save(deps.storage, &(usteak_reserve + usteak_returned))?;
+
+    let event = Event::new("steakhub/pol_withdrawal_settled")
+        .add_attribute("native_returned", native_returned)
+        .add_attribute("usteak_returned", usteak_returned);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/settle_pol_withdrawal"))
+}
+
+pub fn collect_pol(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    receiver: Addr,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.assert_not_wound_down(deps.storage)?;
+    let denom = state.denom.load(deps.storage)?;
+    let steak_token = state.steak_token.load(deps.storage)?;
+
+    let native_reserve = state
+        .pol_native_reserve
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let usteak_reserve = state
+        .pol_usteak_reserve
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if native_reserve.is_zero() && usteak_reserve.is_zero() {
+        return Err(ContractError::NoPolReserves {});
+    }
+    state
+        .pol_native_reserve
+        .save(deps.storage, &Uint128::zero())?;
+    state
+        .pol_usteak_reserve
+        .save(deps.storage, &Uint128::zero())?;
+
+    let mut msgs = vec![];
+    if !native_reserve.is_zero() {
+        msgs.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: receiver.to_string(),
+            amount: vec![Coin::new(native_reserve.u128(), &denom)],
+        }));
+    }
+    if !usteak_reserve.is_zero() {
+        msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: steak_token.into(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: receiver.to_string(),
+                amount: usteak_reserve,
+            })?,
+            funds: vec![],
+        }));
+    }
+
+    let event = Event::new("steakhub/pol_collected")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("receiver", receiver)
+        .add_attribute("native_collected", native_reserve)
+        .add_attribute("usteak_collected", usteak_reserve);
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_event(event)
+        .add_attribute("action", "steakhub/collect_pol"))
+}
+
+/// Carve the protocol-owned liquidity's share out of `fee_amount`, crediting it to the native reserve.
+/// Returns the amount kept by the hub
+fn accrue_pol(storage: &mut dyn Storage, fee_amount: Uint128) -> StdResult<Uint128> {
+    let state = State::default();
+
+    let fee_rate = state.pol_fee_rate.may_load(storage)?.unwrap_or_default();
+    if fee_rate.is_zero() || fee_amount.is_zero() {
+        return Ok(Uint128::zero());
+    }
+
+    let pol_amount = fee_rate.checked_mul_uint(fee_amount)?;
+    let native_reserve = state
+        .pol_native_reserve
+        .may_load(storage)?
+        .unwrap_or_default();
+    state
+        .pol_native_reserve
+        .save(storage, &(native_reserve + pol_amount))?;
+
+    Ok(pol_amount)
+}
+
+//--------------------------------------------------------------------------------------------------
+// Arbitrage logics
+//--------------------------------------------------------------------------------------------------
+
+pub fn update_arb_config(
+    deps: DepsMut,
+    sender: Addr,
+    min_discount: Decimal,
+    max_per_execution: Uint128,
+    max_in_flight: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    if min_discount.is_zero() || min_discount >= Decimal::one() {
+        return Err(ContractError::InvalidMinDiscount {});
+    }
+    state.arb_min_discount.save(deps.storage, &min_discount)?;
+    state
+        .arb_max_per_execution
+        .save(deps.storage, &max_per_execution)?;
+    state.arb_max_in_flight.save(deps.storage, &max_in_flight)?;
+
+    let event = Event::new("steakhub/arb_config_updated")
+        .add_attribute("min_discount", min_discount.to_string())
+        .add_attribute("max_per_execution", max_per_execution)
+        .add_attribute("max_in_flight", max_in_flight);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/update_arb_config"))
+}
+
+pub fn fund_arb_reserve(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    funds: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_not_wound_down(deps.storage)?;
+    let denom = state.denom.load(deps.storage)?;
+    let (amount, refunds) = received_deposit(deps.storage, &funds, &denom)?;
+
+    let reserve = state
+        .arb_reserve
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    state.arb_reserve.save(deps.storage, &(reserve + amount))?;
+
+    let event = Event::new("steakhub/arb_reserve_funded")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("amount", amount);
+
+    Ok(Response::new()
+        .add_messages(refund_msg(&sender, refunds))
+        .add_event(event)
+        .add_attribute("action", "steakhub/fund_arb_reserve"))
+}
+
+pub fn withdraw_arb_reserve(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    amount: Uint128,
+    receiver: Addr,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    let denom = state.denom.load(deps.storage)?;
+
+    let reserve = state
+        .arb_reserve
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let reserve = reserve
+        .checked_sub(amount)
+        .map_err(|_| ContractError::InsufficientArbReserve { available: reserve })?;
+    state.arb_reserve.save(deps.storage, &reserve)?;
+
+    let send_msg = CosmosMsg::Bank(BankMsg::Send {
+        to_address: receiver.to_string(),
+        amount: vec![Coin::new(amount.u128(), &denom)],
+    });
+
+    let event = Event::new("steakhub/arb_reserve_withdrawn")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("receiver", receiver)
+        .add_attribute("amount", amount);
+
+    Ok(Response::new()
+        .add_message(send_msg)
+        .add_event(event)
+        .add_attribute("action", "steakhub/withdraw_arb_reserve"))
+}
+
+/// NOTE: the Steak bought is queued for unbonding on behalf of the hub itself. The swap is simulated
+/// against the pair's state within the same atomic execution, so the amount queued is exactly the
+/// amount the swap returns.
+pub fn arbitrage(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let state = State::default();
+    state.assert_not_wound_down(deps.storage)?;
+
+    let pair = state
+        .pol_pair
+        .may_load(deps.storage)?
+        .ok_or(ContractError::ArbPairNotConfigured {})?;
+    let min_discount = state
+        .arb_min_discount
+        .may_load(deps.storage)?
+        .ok_or(ContractError::ArbNotConfigured {})?;
+    let max_per_execution = state.arb_max_per_execution.load(deps.storage)?;
+    let max_in_flight = state.arb_max_in_flight.load(deps.storage)?;
+    let denom = state.denom.load(deps.storage)?;
+
+    let reserve = state
+        .arb_reserve
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let in_flight_cost = state
+        .arb_in_flight_cost
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let in_flight_shares = state
+        .arb_in_flight_shares
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+
+    let native_to_swap = reserve
+        .min(max_per_execution)
+        .min(max_in_flight.saturating_sub(in_flight_cost));
+    if native_to_swap.is_zero() {
+        return Err(ContractError::NoArbCapacity {});
+    }
+
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: denom.clone(),
+        },
+        amount: native_to_swap,
+    };
+    let simulation: SimulationResponse = deps.querier.query_wasm_smart(
+        &pair,
+        &PairQueryMsg::Simulation {
+            offer_asset: offer_asset.clone(),
+        },
+    )?;
+    let usteak_bought = simulation.return_amount;
+
+    let (usteak_supply, native_bonded) =
+        query_supply_and_bonded(deps.storage, &deps.querier, &env.contract.address)?;
+    let native_redeemable = compute_native_for_usteak(usteak_bought, usteak_supply, native_bonded);
+    if native_redeemable <= native_to_swap
+        || Decimal::from_ratio(native_redeemable - native_to_swap, native_redeemable) < min_discount
+    {
+        return Err(ContractError::DiscountBelowThreshold { min_discount });
+    }
+
+    state
+        .arb_reserve
+        .save(deps.storage, &(reserve - native_to_swap))?;
+    state
+        .arb_in_flight_cost
+        .save(deps.storage, &(in_flight_cost + native_to_swap))?;
+    state
+        .arb_in_flight_shares
+        .save(deps.storage, &(in_flight_shares + usteak_bought))?;
+
+    let swap_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: pair.to_string(),
+        msg: to_binary(&PairExecuteMsg::Swap {
+            offer_asset,
+            belief_price: None,
+            max_spread: None,
+            to: None,
+        })?,
+        funds: vec![Coin::new(native_to_swap.u128(), &denom)],
+    });
+
+    let event = Event::new("steakhub/arbitraged")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("pair", pair)
+        .add_attribute("native_swapped", native_to_swap)
+        .add_attribute("usteak_bought", usteak_bought)
+        .add_attribute("native_redeemable", native_redeemable);
+
+    let contract_addr = env.contract.address.clone();
+    let queue_response = queue_unbond(
+        deps,
+        env,
+        contract_addr.clone(),
+        contract_addr,
+        usteak_bought,
+    )?;
+
+    Ok(Response::new()
+        .add_message(swap_msg)
+        .add_submessages(queue_response.messages)
+        .add_events(queue_response.events)
+        .add_event(event)
+        .add_attribute("action", "steakhub/arbitrage"))
+}
+
+pub fn settle_arbitrage(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let denom = state.denom.load(deps.storage)?;
+    let validators = state.validators_active.load(deps.storage)?;
+
+    let (native_claimed, _, shares_claimed, ids) = claim_unbond_requests(
+        deps.storage,
+        &env.contract.address,
+        env.block.time.seconds(),
+    )?;
+    if native_claimed.is_zero() {
+        return Err(ContractError::NoArbToSettle {});
+    }
+
+    let in_flight_cost = state
+        .arb_in_flight_cost
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let in_flight_shares = state
+        .arb_in_flight_shares
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let cost = in_flight_cost.multiply_ratio(shares_claimed, in_flight_shares);
+
+    state
+        .arb_in_flight_cost
+        .save(deps.storage, &(in_flight_cost - cost))?;
+    state
+        .arb_in_flight_shares
+        .save(deps.storage, &(in_flight_shares - shares_claimed))?;
+
+    // the cost is returned to the reserve; whatever is left is the spread, which is bonded without
+    // minting any Steak, raising the exchange rate for all holders. Once the hub is wound down
+    // nothing is bonded anymore, and the spread stays in the reserve as well
+    let native_returned = if state.emergency_batch.may_load(deps.storage)?.is_some() {
+        native_claimed
+    } else {
+        native_claimed.min(cost)
+    };
+    let spread = native_claimed - native_returned;
+
+    let reserve = state
+        .arb_reserve
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    state
+        .arb_reserve
+        .save(deps.storage, &(reserve + native_returned))?;
+
+    let mut submsgs = vec![];
+    if !spread.is_zero() {
+        let delegations =
+            query_delegations(&deps.querier, &validators, &env.contract.address, &denom)?;
+        let validator = &delegations
+            .iter()
+            .min_by_key(|d| d.amount)
+            .ok_or(ContractError::NoActiveValidators {})?
+            .validator;
+        submsgs.push(SubMsg::reply_on_success(
+            Delegation::new(validator, spread.u128(), &denom)
+                .to_cosmos_msg(env.contract.address.to_string())?,
+            REPLY_REGISTER_RECEIVED_COINS,
+        ));
+    }
+
+    let event = Event::new("steakhub/arbitrage_settled")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("ids", ids.join(","))
+        .add_attribute("native_claimed", native_claimed)
+        .add_attribute("native_returned", native_returned)
+        .add_attribute("spread_bonded", spread);
+
+    Ok(Response::new()
+        .add_submessages(submsgs)
+        .add_event(event)
+        .add_attribute("action", "steakhub/settle_arbitrage"))
+}
+
+//--------------------------------------------------------------------------------------------------
+// User history logics
+//--------------------------------------------------------------------------------------------------
+
+/// Add an entry to the user's history and update their cumulative totals. Bonds and unbonds made by
+/// the hub on its own behalf, e.g. for locked bonds or arbitrage, are not recorded
+fn record_history(
+    storage: &mut dyn Storage,
+    env: &Env,
+    user: &Addr,
+    action: HistoryAction,
+    native_amount: Uint128,
+    usteak_amount: Uint128,
+) -> StdResult<()> {
+    if *user == env.contract.address {
+        return Ok(());
+    }
+    let state = State::default();
+
+    let mut totals = state
+        .user_totals
+        .may_load(storage, user)?
+        .unwrap_or_default();
+    match action {
+        HistoryAction::Bond => {
+            totals.bonded += native_amount;
+            totals.minted += usteak_amount;
+        }
+        HistoryAction::Unbond => totals.unbonded += usteak_amount,
+        HistoryAction::Withdraw => totals.withdrawn += native_amount,
+    }
+
+    let id = totals.entries;
+    totals.entries += 1;
+    state.user_totals.save(storage, user, &totals)?;
+    state.user_history.save(
+        storage,
+        (user, id),
+        &UserHistoryEntry {
+            id,
+            time: env.block.time.seconds(),
+            height: env.block.height,
+            action,
+            native_amount,
+            usteak_amount,
+        },
+    )
+}
+
+//--------------------------------------------------------------------------------------------------
+// Snapshot logics
+//--------------------------------------------------------------------------------------------------
+
+/// Record the accounting state if no snapshot has been taken yet today. Called from paths which
+/// already query the Steak supply and the delegations, so the snapshot costs no extra queries
+fn record_daily_snapshot(
+    storage: &mut dyn Storage,
+    env: &Env,
+    usteak_supply: Uint128,
+    delegations: &[Delegation],
+) -> StdResult<()> {
+    let state = State::default();
+
+    let day = env.block.time.seconds() / SECONDS_PER_DAY;
+    if state.daily_snapshots.has(storage, day) {
+        return Ok(());
+    }
+
+    let total_native = Uint128::new(delegations.iter().map(|d| d.amount).sum());
+    let exchange_rate = if usteak_supply.is_zero() {
+        Decimal::one()
+    } else {
+        Decimal::from_ratio(total_native, usteak_supply)
+    };
+    let fees = state
+        .snapshot_pending_fees
+        .may_load(storage)?
+        .unwrap_or_default();
+    state
+        .snapshot_pending_fees
+        .save(storage, &Uint128::zero())?;
+
+    state.daily_snapshots.save(
+        storage,
+        day,
+        &DailySnapshot {
+            day,
+            time: env.block.time.seconds(),
+            exchange_rate,
+            total_native,
+            total_usteak: usteak_supply,
+            fees,
+        },
+    )
+}
+
+//--------------------------------------------------------------------------------------------------
+// Ownership and management logics
+//--------------------------------------------------------------------------------------------------
+
+pub fn rebalance(deps: DepsMut, env: Env, minimum: Uint128) -> Result<Response, ContractError> {
+    let state = State::default();
+    state.assert_not_wound_down(deps.storage)?;
+    let denom = state.denom.load(deps.storage)?;
+    let validators = state.validators.load(deps.storage)?;
+    let paused = state
+        .receive_paused_validators
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let validators_active = state.validators_active.load(deps.storage)?;
+    // validators paused from receiving keep their stake, but are not topped up either
+    let validators_receiving = validators_active
+        .iter()
+        .filter(|v| !paused.contains(v))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let delegations = query_delegations(&deps.querier, &validators, &env.contract.address, &denom)?;
+
+    let total_delegated_amount = delegations.iter().fold(0u128, |acc, d| acc + d.amount);
+
+    let total_mining_power = state
+        .total_mining_power
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+
+    // without mining power, stake is split evenly across the active validators and moved off the
+    // ones that left the active set
+    let targets = delegations
+        .iter()
+        .map(|d| {
+            if total_mining_power.is_zero() && !validators_active.contains(&d.validator) {
+                return Ok((d.validator.clone(), Uint128::zero()));
+            }
+            let target = compute_target_delegation(
+                total_delegated_amount.into(),
+                state
+                    .validator_mining_powers
+                    .may_load(deps.storage, d.validator.clone())?
+                    .unwrap_or_default(),
+                total_mining_power,
+                validators_active.len(),
+            )?;
+            Ok((d.validator.clone(), target))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    let targets = apply_voting_power_caps(deps.storage, &delegations, targets)?;
+    let new_redelegations =
+        compute_redelegations_for_rebalancing(validators_receiving, &delegations, minimum, |d| {
+            Ok(targets
+                .iter()
+                .find(|(v, _)| *v == d.validator)
+                .map(|(_, target)| *target)
+                .unwrap_or_default())
+        })?;
+
+    state.prev_denom.save(
+        deps.storage,
+        &get_denom_balance(&deps.querier, env.contract.address.clone(), denom)?,
+    )?;
+
+    // redelegations past `max_msgs_per_tx` are left to the next rebalance
+    let (new_redelegations, deferred) = split_msgs(
+        new_redelegations,
+        state.max_msgs_per_tx.may_load(deps.storage)?,
+    );
+    let redelegate_submsgs = new_redelegations
+        .iter()
+        .map(|rd| {
+            Ok(SubMsg::reply_on_success(
+                rd.to_cosmos_msg(env.contract.address.to_string())?,
+                REPLY_REGISTER_RECEIVED_COINS,
+            ))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let amount: u128 = new_redelegations.iter().map(|rd| rd.amount).sum();
+
+    let event = Event::new("steakhub/rebalanced")
+        .add_attribute("amount_moved", amount.to_string())
+        .add_attributes(
+            (!deferred.is_empty()).then(|| ("redelegations_deferred", deferred.len().to_string())),
+        );
+
+    Ok(Response::new()
+        .add_submessages(redelegate_submsgs)
+        .add_event(event)
+        .add_attribute("action", "steakhub/rebalance"))
+}
+
+pub fn update_drift_tolerance(
+    deps: DepsMut,
+    sender: Addr,
+    bps: u16,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    if bps > MAX_BPS {
+        return Err(ContractError::DriftToleranceTooHigh { max: MAX_BPS });
+    }
+    state.drift_tolerance_bps.save(deps.storage, &bps)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "steakhub/update_drift_tolerance")
+        .add_attribute("bps", bps.to_string()))
+}
+
+pub fn update_max_msgs_per_tx(
+    deps: DepsMut,
+    sender: Addr,
+    max: Option<u32>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    match max {
+        Some(0) => return Err(ContractError::ZeroMaxMsgsPerTx {}),
+        Some(max) => state.max_msgs_per_tx.save(deps.storage, &max)?,
+        None => state.max_msgs_per_tx.remove(deps.storage),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "steakhub/update_max_msgs_per_tx")
+        .add_attribute("max", max.map(|m| m.to_string()).unwrap_or_default()))
+}
+
+pub fn update_voting_power_cap(
+    deps: DepsMut,
+    sender: Addr,
+    max_share: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    match max_share {
+        Some(max_share) if max_share.is_zero() || max_share > Decimal::one() => {
+            return Err(ContractError::InvalidVotingPowerCap {})
+        }
+        Some(max_share) => state.voting_power_cap.save(deps.storage, &max_share)?,
+        None => state.voting_power_cap.remove(deps.storage),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "steakhub/update_voting_power_cap")
+        .add_attribute(
+            "max_share",
+            max_share.map(|s| s.to_string()).unwrap_or_default(),
+        ))
+}
+
+/// Voting power moves with every delegation made on the chain, so the snapshot the cap is derived
+/// from is meant to be refreshed regularly, e.g. along with each harvest
+pub fn refresh_voting_power(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let state = State::default();
+    let validators = state.validators.load(deps.storage)?;
+
+    let (total_voting_power, powers) = query_chain_voting_power(&deps.querier, &validators)?;
+
+    // validators that have left the whitelist since the last refresh are dropped
+    let stale = state
+        .validator_voting_powers
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for validator in stale {
+        state
+            .validator_voting_powers
+            .remove(deps.storage, validator);
+    }
+    for (validator, power) in &powers {
+        state
+            .validator_voting_powers
+            .save(deps.storage, validator.clone(), power)?;
+    }
+    state
+        .total_voting_power
+        .save(deps.storage, &total_voting_power)?;
+    state
+        .voting_power_refreshed_at
+        .save(deps.storage, &env.block.time.seconds())?;
+
+    let event = Event::new("steakhub/voting_power_refreshed")
+        .add_attribute("total_voting_power", total_voting_power)
+        .add_attribute("validators", powers.len().to_string());
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/refresh_voting_power"))
+}
+
+/// Most the hub may delegate to each validator under the voting power cap, if one is configured and
+/// the voting power has been refreshed since. Validators missing from the last refresh are uncapped
+pub(crate) fn voting_power_caps(
+    storage: &dyn Storage,
+    delegations: &[Delegation],
+) -> StdResult<Option<BTreeMap<String, Uint128>>> {
+    let state = State::default();
+
+    let (max_share, total_voting_power) = match (
+        state.voting_power_cap.may_load(storage)?,
+        state.total_voting_power.may_load(storage)?,
+    ) {
+        (Some(max_share), Some(total_voting_power)) => (max_share, total_voting_power),
+        _ => return Ok(None),
+    };
+
+    let mut caps = BTreeMap::new();
+    for d in delegations {
+        if let Some(power) = state
+            .validator_voting_powers
+            .may_load(storage, d.validator.clone())?
+        {
+            caps.insert(
+                d.validator.clone(),
+                compute_voting_power_cap(
+                    max_share,
+                    total_voting_power,
+                    power,
+                    Uint128::new(d.amount),
+                ),
+            );
+        }
+    }
+    Ok(Some(caps))
+}
+
+/// Apply the voting power cap, if any, to the `targets` computed for `delegations`
+pub(crate) fn apply_voting_power_caps(
+    storage: &dyn Storage,
+    delegations: &[Delegation],
+    targets: Vec<(String, Uint128)>,
+) -> StdResult<Vec<(String, Uint128)>> {
+    Ok(match voting_power_caps(storage, delegations)? {
+        Some(caps) => cap_target_delegations(&targets, &caps),
+        None => targets,
+    })
+}
+
+pub fn add_validator(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    validator: String,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.assert_not_blocked(deps.storage, &validator)?;
+
+    state.validators.update(deps.storage, |mut validators| {
+        if validators.contains(&validator) {
+            return Err(ContractError::ValidatorAlreadyWhitelisted {
+                validator: validator.clone(),
+            });
+        }
+        validators.push(validator.clone());
+        Ok(validators)
+    })?;
+
+    let mut validators_active = state.validators_active.load(deps.storage)?;
+    if !validators_active.contains(&validator) {
+        validators_active.push(validator.clone());
+    }
+    state
+        .validators_active
+        .save(deps.storage, &validators_active)?;
+    record_validator_set_change(
+        deps.storage,
+        &env,
+        &validator,
+        ValidatorSetAction::Add,
+        &sender,
+        reason.clone(),
+    )?;
+    let event = Event::new("steakhub/validator_added")
+        .add_attribute("validator", validator)
+        .add_attributes(reason.map(|reason| ("reason", reason)));
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/add_validator"))
+}
+
+/// Removing a validator used to redelegate all of its stake at once, which for a large delegation
+/// risks running out of gas or redelegation entries. Instead it stops receiving new delegations and
+/// stays whitelisted, so its stake keeps counting towards the exchange rate, while
+/// `drain_validators` moves the stake away over as many calls as needed.
+pub fn remove_validator(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    validator: String,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.assert_not_wound_down(deps.storage)?;
+    let denom = state.denom.load(deps.storage)?;
+
+    if !state.validators.load(deps.storage)?.contains(&validator) {
+        return Err(ContractError::ValidatorNotWhitelisted { validator });
+    }
+    let mut draining = state
+        .draining_validators
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if draining.contains(&validator) {
+        return Err(ContractError::ValidatorDraining { validator });
+    }
+
+    let mut validators_active = state.validators_active.load(deps.storage)?;
+    validators_active.retain(|v| *v != validator);
+    if validators_active.is_empty() {
+        return Err(ContractError::NoActiveValidators {});
+    }
+    state
+        .validators_active
+        .save(deps.storage, &validators_active)?;
+
+    // the removed validator's mining power would otherwise keep diluting the remaining ones
+    let mining_power_purged = purge_validator_mining_power(deps.storage, &validator)?;
+    record_validator_set_change(
+        deps.storage,
+        &env,
+        &validator,
+        ValidatorSetAction::Remove,
+        &sender,
+        reason.clone(),
+    )?;
+
+    let delegation = query_delegation(&deps.querier, &validator, &env.contract.address, &denom)?;
+    let event = if delegation.amount == 0 {
+        drop_validator(deps.storage, &validator)?;
+        Event::new("steak/validator_removed").add_attribute("validator", validator)
+    } else {
+        draining.push(validator.clone());
+        state.draining_validators.save(deps.storage, &draining)?;
+        Event::new("steakhub/validator_draining")
+            .add_attribute("validator", validator)
+            .add_attribute("amount", delegation.amount.to_string())
+    };
+
+    Ok(Response::new()
+        .add_event(
+            event
+                .add_attribute("mining_power_purged", mining_power_purged)
+                .add_attributes(reason.map(|reason| ("reason", reason))),
+        )
+        .add_attribute("action", "steakhub/remove_validator"))
+}
+
+/// Move at most `max_amount` of stake from the validators being removed to the active validators,
+/// the same way `redelegate_idle` does. A validator left with nothing delegated is dropped from the
+/// whitelist.
+pub fn drain_validators(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    max_amount: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.assert_not_wound_down(deps.storage)?;
+    let denom = state.denom.load(deps.storage)?;
+    let draining = state
+        .draining_validators
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if draining.is_empty() {
+        return Err(ContractError::NoDrainingValidators {});
+    }
+    let validators_active = state.validators_active.load(deps.storage)?;
+    if validators_active.is_empty() {
+        return Err(ContractError::NoActiveValidators {});
+    }
+
+    let mut delegations = receiving_delegations(
+        deps.storage,
+        &query_delegations(
+            &deps.querier,
+            &validators_active,
+            &env.contract.address,
+            &denom,
+        )?,
+    )?;
+    let mut remaining = max_amount.u128();
+    let mut new_redelegations = vec![];
+    let mut drained = vec![];
+    for validator in &draining {
+        let mut delegation_to_move =
+            query_delegation(&deps.querier, validator, &env.contract.address, &denom)?;
+        if delegation_to_move.amount <= remaining {
+            drained.push(validator.clone());
+        } else {
+            delegation_to_move.amount = remaining;
+        }
+        remaining -= delegation_to_move.amount;
+        if delegation_to_move.amount == 0 {
+            continue;
+        }
+
+        let redelegations =
+            compute_redelegations_for_removal(&delegation_to_move, &delegations, &denom);
+        for rd in &redelegations {
+            if let Some(d) = delegations.iter_mut().find(|d| d.validator == rd.dst) {
+                d.amount += rd.amount;
+            }
+        }
+        new_redelegations.extend(redelegations);
+        if remaining == 0 {
+            break;
+        }
+    }
+    for validator in &drained {
+        drop_validator(deps.storage, validator)?;
+        record_validator_set_change(
+            deps.storage,
+            &env,
+            validator,
+            ValidatorSetAction::Drop,
+            &sender,
+            None,
+        )?;
+    }
+
+    state.prev_denom.save(
+        deps.storage,
+        &get_denom_balance(&deps.querier, env.contract.address.clone(), denom)?,
+    )?;
+
+    let redelegate_submsgs = new_redelegations
+        .iter()
+        .map(|d| {
+            Ok(SubMsg::reply_on_success(
+                d.to_cosmos_msg(env.contract.address.to_string())?,
+                REPLY_REGISTER_RECEIVED_COINS,
+            ))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut events = vec![Event::new("steakhub/validators_drained")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("amount", (max_amount.u128() - remaining).to_string())];
+    for validator in drained {
+        events.push(Event::new("steak/validator_removed").add_attribute("validator", validator));
+    }
+
+    Ok(Response::new()
+        .add_submessages(redelegate_submsgs)
+        .add_events(events)
+        .add_attribute("action", "steakhub/drain_validators"))
+}
+
+/// Drop a validator from the whitelist, and from the validators being removed
+fn drop_validator(storage: &mut dyn Storage, validator: &str) -> StdResult<()> {
+    let state = State::default();
+
+    state
+        .validators
+        .update(storage, |mut validators| -> StdResult<_> {
+            validators.retain(|v| v != validator);
+            Ok(validators)
+        })?;
+    if let Some(mut draining) = state.draining_validators.may_load(storage)? {
+        draining.retain(|v| v != validator);
+        state.draining_validators.save(storage, &draining)?;
+    }
+    if let Some(mut paused) = state.receive_paused_validators.may_load(storage)? {
+        paused.retain(|v| v != validator);
+        state.receive_paused_validators.save(storage, &paused)?;
+    }
+    Ok(())
+}
+
+/// Append a change to the validator set to the log governance reviews curation from
+fn record_validator_set_change(
+    storage: &mut dyn Storage,
+    env: &Env,
+    validator: &str,
+    action: ValidatorSetAction,
+    actor: &Addr,
+    reason: Option<String>,
+) -> StdResult<()> {
+    let state = State::default();
+
+    let id = state
+        .last_validator_set_change_id
+        .may_load(storage)?
+        .unwrap_or_default()
+        + 1;
+    state.last_validator_set_change_id.save(storage, &id)?;
+    state.validator_set_changes.save(
+        storage,
+        id,
+        &ValidatorSetChange {
+            id,
+            validator: validator.to_string(),
+            action,
+            actor: actor.to_string(),
+            reason,
+            height: env.block.height,
+            time: env.block.time.seconds(),
+        },
+    )
+}
+
+pub fn remove_validator_ex(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    validator: String,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.assert_not_wound_down(deps.storage)?;
+
+    if !state.validators.load(deps.storage)?.contains(&validator) {
+        return Err(ContractError::ValidatorNotWhitelisted { validator });
+    }
+    drop_validator(deps.storage, &validator)?;
+    let mining_power_purged = purge_validator_mining_power(deps.storage, &validator)?;
+    record_validator_set_change(
+        deps.storage,
+        &env,
+        &validator,
+        ValidatorSetAction::Drop,
+        &sender,
+        reason.clone(),
+    )?;
+
+    let event = Event::new("steak/validator_removed_ex")
+        .add_attribute("validator", validator)
+        .add_attribute("mining_power_purged", mining_power_purged)
+        .add_attributes(reason.map(|reason| ("reason", reason)));
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/remove_validator_ex"))
+}
+
+pub fn pause_validator(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    validator: String,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner_or_guardian(deps.storage, &sender)?;
+
+    state
+        .validators_active
+        .update(deps.storage, |mut validators| {
+            if !validators.contains(&validator) {
+                return Err(ContractError::ValidatorNotWhitelisted {
+                    validator: validator.clone(),
+                });
+            }
+            validators.retain(|v| *v != validator);
+            Ok(validators)
+        })?;
+    record_validator_incident(deps.storage, &env, &validator, false)?;
+    record_validator_set_change(
+        deps.storage,
+        &env,
+        &validator,
+        ValidatorSetAction::Pause,
+        &sender,
+        reason.clone(),
+    )?;
+
+    let event = Event::new("steak/pause_validator")
+        .add_attribute("validator", validator)
+        .add_attributes(reason.map(|reason| ("reason", reason)));
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/pause_validator"))
+}
+
+pub fn update_idle_redelegation(
+    deps: DepsMut,
+    sender: Addr,
+    zero_reward_harvests: u32,
+    min_idle_period: u64,
+    max_amount: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    if zero_reward_harvests == 0 {
+        return Err(ContractError::ZeroIdleHarvests {});
+    }
+    if min_idle_period == 0 {
+        return Err(ContractError::ZeroIdlePeriod {});
+    }
+    state.idle_redelegation.save(
+        deps.storage,
+        &IdleRedelegation {
+            zero_reward_harvests,
+            min_idle_period,
+            max_amount,
+        },
+    )?;
+
+    let event = Event::new("steakhub/idle_redelegation_updated")
+        .add_attribute("zero_reward_harvests", zero_reward_harvests.to_string())
+        .add_attribute("min_idle_period", min_idle_period.to_string())
+        .add_attribute("max_amount", max_amount);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/update_idle_redelegation"))
+}
+
+/// Validators that keep earning nothing are most likely jailed or have fallen out of the active
+/// set. They are paused, so rewards are no longer reinvested with them, and their stake is moved to
+/// the remaining active validators the same way `remove_validator` does, at most
+/// `max_amount` per call so a large move can be spread over several blocks. Harvests can be
+/// triggered back to back, so the zero-reward harvests only count once they span
+/// `min_idle_period`, and only while the last of them is recent.
+pub fn redelegate_idle(deps: DepsMut, env: Env, sender: Addr) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_not_wound_down(deps.storage)?;
+    // a config saved before `min_idle_period` was added has to be updated before it is used
+    let config = state
+        .idle_redelegation
+        .may_load(deps.storage)?
+        .filter(|config| config.min_idle_period > 0)
+        .ok_or(ContractError::IdleRedelegationNotConfigured {})?;
+    let denom = state.denom.load(deps.storage)?;
+    let validators = state.validators.load(deps.storage)?;
+    let mut validators_active = state.validators_active.load(deps.storage)?;
+
+    let now = env.block.time.seconds();
+    let mut idle = vec![];
+    for validator in &validators {
+        let performance = state
+            .validator_performance
+            .may_load(deps.storage, validator.clone())?
+            .unwrap_or_default();
+        let idle_since = match performance.idle_since {
+            Some(idle_since) => idle_since,
+            None => continue,
+        };
+        if performance.zero_reward_harvests >= config.zero_reward_harvests
+            && performance.last_observed - idle_since >= config.min_idle_period
+            && now - performance.last_observed <= config.min_idle_period
+        {
+            idle.push(validator.clone());
+        }
+    }
+    if idle.is_empty() {
+        return Err(ContractError::NoIdleValidators {});
+    }
+
+    for validator in &idle {
+        if validators_active.contains(validator) {
+            validators_active.retain(|v| v != validator);
+            record_validator_incident(deps.storage, &env, validator, false)?;
+            record_validator_set_change(
+                deps.storage,
+                &env,
+                validator,
+                ValidatorSetAction::Pause,
+                &sender,
+                Some("idle".to_string()),
+            )?;
+        }
+    }
+    if validators_active.is_empty() {
+        return Err(ContractError::NoActiveValidators {});
+    }
+    state
+        .validators_active
+        .save(deps.storage, &validators_active)?;
+
+    let mut delegations = receiving_delegations(
+        deps.storage,
+        &query_delegations(
+            &deps.querier,
+            &validators_active,
+            &env.contract.address,
+            &denom,
+        )?,
+    )?;
+    let mut remaining = config.max_amount.u128();
+    let mut new_redelegations = vec![];
+    for validator in &idle {
+        if remaining == 0 {
+            break;
+        }
+        let mut delegation_to_move =
+            query_delegation(&deps.querier, validator, &env.contract.address, &denom)?;
+        delegation_to_move.amount = delegation_to_move.amount.min(remaining);
+        if delegation_to_move.amount == 0 {
+            continue;
+        }
+        remaining -= delegation_to_move.amount;
+
+        let redelegations =
+            compute_redelegations_for_removal(&delegation_to_move, &delegations, &denom);
+        for rd in &redelegations {
+            if let Some(d) = delegations.iter_mut().find(|d| d.validator == rd.dst) {
+                d.amount += rd.amount;
+            }
+        }
+        new_redelegations.extend(redelegations);
+    }
+
+    state.prev_denom.save(
+        deps.storage,
+        &get_denom_balance(&deps.querier, env.contract.address.clone(), denom)?,
+    )?;
+
+    let redelegate_submsgs = new_redelegations
+        .iter()
+        .map(|d| {
+            Ok(SubMsg::reply_on_success(
+                d.to_cosmos_msg(env.contract.address.to_string())?,
+                REPLY_REGISTER_RECEIVED_COINS,
+            ))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let event = Event::new("steakhub/idle_redelegated")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("validators", idle.join(","))
+        .add_attribute("amount", (config.max_amount.u128() - remaining).to_string());
+
+    Ok(Response::new()
+        .add_submessages(redelegate_submsgs)
+        .add_event(event)
+        .add_attribute("action", "steakhub/redelegate_idle"))
+}
+
+pub fn report_slashing(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    validator: String,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    if !state.validators.load(deps.storage)?.contains(&validator) {
+        return Err(ContractError::ValidatorNotWhitelisted { validator });
+    }
+    record_validator_incident(deps.storage, &env, &validator, true)?;
+
+    let event = Event::new("steakhub/slashing_reported")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("validator", validator);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/report_slashing"))
+}
+
+/// Record a pause or slashing incident against `validator`, dropping those which have left the
+/// scoring window
+fn record_validator_incident(
+    storage: &mut dyn Storage,
+    env: &Env,
+    validator: &str,
+    slashing: bool,
+) -> StdResult<()> {
+    let state = State::default();
+    let now = env.block.time.seconds();
+    let window_start = now.saturating_sub(SCORE_WINDOW_SECONDS);
+
+    let mut performance = state
+        .validator_performance
+        .may_load(storage, validator.to_string())?
+        .unwrap_or_default();
+    performance.pauses.retain(|t| *t >= window_start);
+    performance.slashes.retain(|t| *t >= window_start);
+    if slashing {
+        performance.slashes.push(now);
+    } else {
+        performance.pauses.push(now);
+    }
+    state
+        .validator_performance
+        .save(storage, validator.to_string(), &performance)
+}
+
+pub fn receive_only_pause_validator(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    validator: String,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner_or_guardian(deps.storage, &sender)?;
+    let validators_active = state.validators_active.load(deps.storage)?;
+    if !validators_active.contains(&validator) {
+        return Err(ContractError::ValidatorNotActive { validator });
+    }
+    let mut paused = state
+        .receive_paused_validators
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if !paused.contains(&validator) {
+        paused.push(validator.clone());
+    }
+    if validators_active.iter().all(|v| paused.contains(v)) {
+        return Err(ContractError::NoActiveValidators {});
+    }
+    state
+        .receive_paused_validators
+        .save(deps.storage, &paused)?;
+    record_validator_set_change(
+        deps.storage,
+        &env,
+        &validator,
+        ValidatorSetAction::ReceiveOnlyPause,
+        &sender,
+        reason.clone(),
+    )?;
+
+    let event = Event::new("steak/receive_only_pause_validator")
+        .add_attribute("validator", validator)
+        .add_attributes(reason.map(|reason| ("reason", reason)));
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/receive_only_pause_validator"))
+}
+
+pub fn unpause_validator(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    validator: String,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.assert_not_blocked(deps.storage, &validator)?;
+    if state
+        .draining_validators
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .contains(&validator)
+    {
+        return Err(ContractError::ValidatorDraining { validator });
+    }
+    let mut validators_active = state.validators_active.load(deps.storage)?;
+    if !validators_active.contains(&validator) {
+        validators_active.push(validator.clone());
+    }
+    state
+        .validators_active
+        .save(deps.storage, &validators_active)?;
+    if let Some(mut paused) = state.receive_paused_validators.may_load(deps.storage)? {
+        paused.retain(|v| *v != validator);
+        state
+            .receive_paused_validators
+            .save(deps.storage, &paused)?;
+    }
+    // the zero-reward harvests that got the validator paused must not get it paused again right away
+    if let Some(mut performance) = state
+        .validator_performance
+        .may_load(deps.storage, validator.clone())?
+    {
+        performance.zero_reward_harvests = 0;
+        performance.idle_since = None;
+        state
+            .validator_performance
+            .save(deps.storage, validator.clone(), &performance)?;
+    }
+    record_validator_set_change(
+        deps.storage,
+        &env,
+        &validator,
+        ValidatorSetAction::Unpause,
+        &sender,
+        reason.clone(),
+    )?;
+
+    let event = Event::new("steak/unpause_validator")
+        .add_attribute("validator", validator)
+        .add_attributes(reason.map(|reason| ("reason", reason)));
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/unpause_validator"))
+}
+
+pub fn block_validator(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    validator: String,
+    reason: String,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state
+        .validator_blocklist
+        .save(deps.storage, validator.clone(), &reason)?;
+
+    // stop new delegations right away; existing ones are moved with `RemoveValidator`
+    let mut validators_active = state.validators_active.load(deps.storage)?;
+    validators_active.retain(|v| *v != validator);
+    state
+        .validators_active
+        .save(deps.storage, &validators_active)?;
+    record_validator_set_change(
+        deps.storage,
+        &env,
+        &validator,
+        ValidatorSetAction::Block,
+        &sender,
+        Some(reason.clone()),
+    )?;
+
+    let event = Event::new("steakhub/validator_blocked")
+        .add_attribute("validator", validator)
+        .add_attribute("reason", reason);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/block_validator"))
+}
+
+pub fn unblock_validator(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    validator: String,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    if !state
+        .validator_blocklist
+        .has(deps.storage, validator.clone())
+    {
+        return Err(ContractError::ValidatorNotBlocked { validator });
+    }
+    state
+        .validator_blocklist
+        .remove(deps.storage, validator.clone());
+    record_validator_set_change(
+        deps.storage,
+        &env,
+        &validator,
+        ValidatorSetAction::Unblock,
+        &sender,
+        reason.clone(),
+    )?;
+
+    let event = Event::new("steakhub/validator_unblocked")
+        .add_attribute("validator", validator)
+        .add_attributes(reason.map(|reason| ("reason", reason)));
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/unblock_validator"))
+}
+pub fn set_unbond_period(
+    deps: DepsMut,
+    _env: Env,
+    sender: Addr,
+    unbond_period: u64,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.unbond_period.save(deps.storage, &unbond_period)?;
+    let event = Event::new("steak/set_unbond_period")
+        .add_attribute("unbond_period", format!("{}", unbond_period));
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/set_unbond_period"))
+}
+
+pub fn transfer_ownership(
+    deps: DepsMut,
+    sender: Addr,
+    new_owner: String,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state
+        .new_owner
+        .save(deps.storage, &deps.api.addr_validate(&new_owner)?)?;
+
+    Ok(Response::new().add_attribute("action", "steakhub/transfer_ownership"))
+}
+
+pub fn accept_ownership(deps: DepsMut, sender: Addr) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let previous_owner = state.owner.load(deps.storage)?;
+    let new_owner = state.new_owner.load(deps.storage)?;
+
+    if sender != new_owner {
+        return Err(ContractError::UnauthorizedNewOwner {});
+    }
+
+    state.owner.save(deps.storage, &sender)?;
+    state.new_owner.remove(deps.storage);
+
+    let event = Event::new("steakhub/ownership_transferred")
+        .add_attribute("new_owner", new_owner)
+        .add_attribute("previous_owner", previous_owner);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/transfer_ownership"))
+}
+
+fn transfer_fee_account_internal(
+    deps: DepsMut,
+    fee_account_type: String,
+    new_fee_account: String,
+) -> Result<(), ContractError> {
+    let state = State::default();
+    let fee_type =
+        FeeType::from_str(&fee_account_type).map_err(|_| ContractError::InvalidFeeType {})?;
+
+    // disabling the fee module keeps the previous fee account, which is only ever paid again once
+    // the module is enabled back
+    if fee_type == FeeType::None {
+        if !state.fee_rate.load(deps.storage)?.is_zero() {
+            return Err(ContractError::FeesDisabled {});
+        }
+        state.fee_account_type.save(deps.storage, &fee_type)?;
+        return Ok(());
+    }
+    let fee_account = deps.api.addr_validate(&new_fee_account)?;
+
+    // Fees are deposited into a fee split contract on every reinvest, so an account that is not one
+    // would make every reinvest fail
+    if fee_type == FeeType::FeeSplit {
+        deps.querier
+            .query_wasm_smart::<pfc_fee_split::fee_split_msg::GovContractResponse>(
+                &fee_account,
+                &pfc_fee_split::fee_split_msg::QueryMsg::GovContract {},
+            )
+            .map_err(|_| ContractError::NotFeeSplitContract {
+                address: fee_account.to_string(),
+            })?;
+    }
+
+    state.fee_account_type.save(deps.storage, &fee_type)?;
+    state.fee_account.save(deps.storage, &fee_account)?;
+    Ok(())
+}
+
+pub fn transfer_fee_account(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    fee_account_type: String,
+    new_fee_account: String,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        AdminAction::TransferFeeAccount {
+            fee_account_type: fee_account_type.clone(),
+            new_fee_account: new_fee_account.clone(),
+        },
+        &sender,
+        reason.clone(),
+    )?;
+
+    let event = Event::new("steakhub/fee_account_transferred")
+        .add_attribute("fee_account_type", &fee_account_type)
+        .add_attribute("new_fee_account", &new_fee_account)
+        .add_attributes(reason.map(|reason| ("reason", reason)));
+
+    transfer_fee_account_internal(deps, fee_account_type, new_fee_account)?;
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/transfer_fee_account"))
+}
+
+pub fn update_token_marketing(
+    deps: DepsMut,
+    sender: Addr,
+    project: Option<String>,
+    description: Option<String>,
+    marketing: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+
+    let msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: state.steak_token.load(deps.storage)?.into(),
+        msg: to_binary(&Cw20ExecuteMsg::UpdateMarketing {
+            project,
+            description,
+            marketing,
+        })?,
+        funds: vec![],
+    });
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "steakhub/update_token_marketing"))
+}
+
+pub fn upload_token_logo(
+    deps: DepsMut,
+    sender: Addr,
+    logo: Logo,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+
+    let msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: state.steak_token.load(deps.storage)?.into(),
+        msg: to_binary(&Cw20ExecuteMsg::UploadLogo(logo))?,
+        funds: vec![],
+    });
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "steakhub/upload_token_logo"))
+}
+
+pub fn propose_denom_change(
+    deps: DepsMut,
+    sender: Addr,
+    new_denom: String,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.pending_denom.save(deps.storage, &new_denom)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "steakhub/propose_denom_change")
+        .add_attribute("new_denom", new_denom))
+}
+
+/// The denom can only be switched once nothing is delegated, owed or held in the current one;
+/// otherwise the accounting of the old denom would silently be read as the new one
+pub fn confirm_denom_change(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    let new_denom = state
+        .pending_denom
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingDenomChange {})?;
+
+    let blockers = denom_change_blockers(deps.as_ref(), &env)?;
+    if !blockers.is_empty() {
+        return Err(ContractError::DenomChangeBlocked { blockers });
+    }
+
+    let old_denom = state.denom.load(deps.storage)?;
+    state.denom.save(deps.storage, &new_denom)?;
+    state.pending_denom.remove(deps.storage);
+
+    // the Native Token stays among the reward denoms under its new name
+    if let Some(mut reward_denoms) = state.reward_denoms.may_load(deps.storage)? {
+        reward_denoms.retain(|d| *d != old_denom && *d != new_denom);
+        reward_denoms.insert(0, new_denom.clone());
+        state.reward_denoms.save(deps.storage, &reward_denoms)?;
+    }
+
+    // whatever the hub already holds in the new denom is not a reward
+    let balance = get_denom_balance(&deps.querier, env.contract.address, new_denom.clone())?;
+    state.prev_denom.save(deps.storage, &balance)?;
+
+    let event = Event::new("steakhub/denom_changed")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("old_denom", old_denom)
+        .add_attribute("new_denom", new_denom);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/confirm_denom_change"))
+}
+
+pub fn cancel_denom_change(deps: DepsMut, sender: Addr) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.pending_denom.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("action", "steakhub/cancel_denom_change"))
+}
+
+pub fn propose_emergency_undelegation(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.assert_not_wound_down(deps.storage)?;
+    let ready_at = env.block.time.seconds() + EMERGENCY_UNDELEGATION_DELAY_SECONDS;
+    state.emergency_ready_at.save(deps.storage, &ready_at)?;
+
+    let event = Event::new("steakhub/emergency_undelegation_proposed")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("ready_at", ready_at.to_string());
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/propose_emergency_undelegation"))
+}
+
+/// Everything delegated, along with the Native Token unlocked but not yet bonded, goes into a final
+/// batch. Steak already queued in the pending batch is burned right away and keeps its requests;
+/// every other holder claims by queueing their Steak for unbonding, which from then on is burned on
+/// receipt and credited to this batch. Steak the hub holds itself, e.g. for locks, the yield pool or
+/// protocol-owned liquidity, is left out of the batch's shares along with its part of the Native
+/// Token, and only joins the batch once released and queued. The hub stays wound down: no new bonds,
+/// batches or redelegations
+pub fn emergency_undelegate_all(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.assert_not_wound_down(deps.storage)?;
+    let ready_at = state
+        .emergency_ready_at
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingEmergency {})?;
+    let current_time = env.block.time.seconds();
+    if current_time < ready_at {
+        return Err(ContractError::EmergencyTimelocked { ready_at });
+    }
+
+    let denom = state.denom.load(deps.storage)?;
+    let validators = state.validators.load(deps.storage)?;
+    let unbond_period = state.unbond_period.load(deps.storage)?;
+    let pending_batch = state.pending_batch.load(deps.storage)?;
+
+    let delegations = query_delegations(&deps.querier, &validators, &env.contract.address, &denom)?;
+    let usteak_supply = query_usteak_supply(deps.storage, &deps.querier, &env.contract.address)?;
+    let native_unbonded: u128 = delegations.iter().map(|d| d.amount).sum();
+
+    // harvesting is over, so what was unlocked but not bonded yet is paid out with the batch
+    let mut unlocked_coins = state.unlocked_coins.load(deps.storage)?;
+    let native_unlocked = Coins::try_from(unlocked_coins.clone())?.find(&denom).amount;
+    unlocked_coins.retain(|coin| coin.denom != denom);
+    state.unlocked_coins.save(deps.storage, &unlocked_coins)?;
+
+    let steak_token = state.steak_token.load(deps.storage)?;
+    let usteak_held = query_cw20_balance(&deps.querier, &steak_token, &env.contract.address)?
+        .saturating_sub(pending_batch.usteak_to_burn)
+        .min(usteak_supply);
+    let native_total = Uint128::new(native_unbonded) + native_unlocked;
+    let native_for_batch = if usteak_held.is_zero() {
+        native_total
+    } else {
+        native_total.multiply_ratio(usteak_supply - usteak_held, usteak_supply)
+    };
+    state.emergency_held.save(
+        deps.storage,
+        &EmergencyHeld {
+            usteak: usteak_held,
+            native: native_total - native_for_batch,
+        },
+    )?;
+
+    state.previous_batches.save(
+        deps.storage,
+        pending_batch.id,
+        &Batch {
+            id: pending_batch.id,
+            reconciled: false,
+            total_shares: usteak_supply - usteak_held,
+            amount_unclaimed: native_for_batch,
+            est_unbond_end_time: current_time + unbond_period,
+        },
+    )?;
+    state.pending_batch.save(
+        deps.storage,
+        &PendingBatch {
+            id: pending_batch.id + 1,
+            usteak_to_burn: Uint128::zero(),
+            est_unbond_start_time: u64::MAX,
+        },
+    )?;
+    state
+        .emergency_batch
+        .save(deps.storage, &pending_batch.id)?;
+    state.emergency_ready_at.remove(deps.storage);
+    state.prev_denom.save(
+        deps.storage,
+        &get_denom_balance(&deps.querier, env.contract.address.clone(), denom.clone())?,
+    )?;
+
+    let undelegations = delegations
+        .iter()
+        .filter(|d| d.amount > 0)
+        .map(|d| Undelegation::new(&d.validator, d.amount, &denom))
+        .collect::<Vec<_>>();
+    record_batch_undelegations(deps.storage, pending_batch.id, &undelegations)?;
+
+    let undelegate_submsgs = undelegations
+        .iter()
+        .map(|u| {
+            Ok(SubMsg::reply_on_success(
+                u.to_cosmos_msg(env.contract.address.to_string())?,
+                REPLY_REGISTER_RECEIVED_COINS,
+            ))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    if !pending_batch.usteak_to_burn.is_zero() {
+        msgs.push(usteak_burn_msg(deps.storage, pending_batch.usteak_to_burn)?);
+    }
+
+    let event = Event::new("steakhub/emergency_undelegated")
+        .add_attribute("time", current_time.to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("id", pending_batch.id.to_string())
+        .add_attribute("native_unbonded", native_unbonded.to_string())
+        .add_attribute("native_unlocked", native_unlocked)
+        .add_attribute("usteak_supply", usteak_supply)
+        .add_attribute("usteak_held", usteak_held)
+        .add_attribute("usteak_burned", pending_batch.usteak_to_burn);
+
+    Ok(Response::new()
+        .add_submessages(undelegate_submsgs)
+        .add_messages(msgs)
+        .add_event(event)
+        .add_attribute("action", "steakhub/emergency_undelegate_all"))
+}
+
+pub fn cancel_emergency_undelegation(
+    deps: DepsMut,
+    sender: Addr,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.emergency_ready_at.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("action", "steakhub/cancel_emergency_undelegation"))
+}
+
+pub fn propose_halt_recovery(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    batch_shift: u64,
+    reset_mining_clock: bool,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    if batch_shift > MAX_HALT_SHIFT_SECONDS {
+        return Err(ContractError::HaltShiftTooLarge {
+            max: MAX_HALT_SHIFT_SECONDS,
+        });
+    }
+    if batch_shift == 0 && !reset_mining_clock {
+        return Err(ContractError::EmptyHaltRecovery {});
+    }
+    let ready_at = env.block.time.seconds() + HALT_RECOVERY_DELAY_SECONDS;
+    state.halt_recovery.save(
+        deps.storage,
+        &HaltRecovery {
+            batch_shift,
+            reset_mining_clock,
+            ready_at,
+        },
+    )?;
+
+    let event = Event::new("steakhub/halt_recovery_proposed")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("batch_shift", batch_shift.to_string())
+        .add_attribute("reset_mining_clock", reset_mining_clock.to_string())
+        .add_attribute("ready_at", ready_at.to_string());
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/propose_halt_recovery"))
+}
+
+/// Batches are only ever delayed, never brought forward, so a recovery can not make a batch
+/// withdrawable before its Native Token has actually been unbonded
+pub fn apply_halt_recovery(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    let recovery = state
+        .halt_recovery
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingHaltRecovery {})?;
+    let current_time = env.block.time.seconds();
+    if current_time < recovery.ready_at {
+        return Err(ContractError::HaltRecoveryTimelocked {
+            ready_at: recovery.ready_at,
+        });
+    }
+    state.halt_recovery.remove(deps.storage);
+
+    let mut event = Event::new("steakhub/halt_recovered")
+        .add_attribute("time", current_time.to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("batch_shift", recovery.batch_shift.to_string());
+
+    if recovery.batch_shift > 0 {
+        let mut pending_batch = state.pending_batch.load(deps.storage)?;
+        pending_batch.est_unbond_start_time = pending_batch
+            .est_unbond_start_time
+            .saturating_add(recovery.batch_shift);
+        state.pending_batch.save(deps.storage, &pending_batch)?;
+
+        let batches = state
+            .previous_batches
+            .idx
+            .reconciled
+            .prefix(false.into())
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                let (_, v) = item?;
+                Ok(v)
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        let mut ids = vec![];
+        for mut batch in batches {
+            batch.est_unbond_end_time += recovery.batch_shift;
+            state
+                .previous_batches
+                .save(deps.storage, batch.id, &batch)?;
+            ids.push(batch.id.to_string());
+        }
+
+        event = event
+            .add_attribute(
+                "est_unbond_start_time",
+                pending_batch.est_unbond_start_time.to_string(),
+            )
+            .add_attribute("batches_shifted", ids.join(","));
+    }
+
+    if recovery.reset_mining_clock {
+        let last_mined = state.miner_last_mined_timestamp.load(deps.storage)?;
+        state
+            .miner_last_mined_timestamp
+            .save(deps.storage, &Uint64::new(current_time))?;
+        event = event
+            .add_attribute("prev_last_mined_timestamp", last_mined)
+            .add_attribute("last_mined_timestamp", current_time.to_string());
+    }
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/apply_halt_recovery"))
+}
+
+pub fn cancel_halt_recovery(deps: DepsMut, sender: Addr) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.halt_recovery.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("action", "steakhub/cancel_halt_recovery"))
+}
+
+pub fn enter_safe_mode(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    until_height: Option<u64>,
+    until_time: Option<u64>,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner_or_guardian(deps.storage, &sender)?;
+    let safe_mode = SafeMode {
+        until_height,
+        until_time,
+    };
+    if (until_height.is_none() && until_time.is_none())
+        || !safe_mode.is_active(env.block.height, env.block.time.seconds())
+    {
+        return Err(ContractError::InvalidSafeModeExpiry {});
+    }
+    state.safe_mode.save(deps.storage, &safe_mode)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        AdminAction::EnterSafeMode {
+            until_height,
+            until_time,
+        },
+        &sender,
+        reason.clone(),
+    )?;
+
+    let event = Event::new("steakhub/safe_mode_entered")
+        .add_attribute(
+            "until_height",
+            until_height.map(|h| h.to_string()).unwrap_or_default(),
+        )
+        .add_attribute(
+            "until_time",
+            until_time.map(|t| t.to_string()).unwrap_or_default(),
+        )
+        .add_attributes(reason.map(|reason| ("reason", reason)));
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/enter_safe_mode"))
+}
+
+pub fn exit_safe_mode(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.safe_mode.remove(deps.storage);
+    record_admin_action(
+        deps.storage,
+        &env,
+        AdminAction::ExitSafeMode {},
+        &sender,
+        reason.clone(),
+    )?;
+
+    let event = Event::new("steakhub/safe_mode_exited")
+        .add_attributes(reason.map(|reason| ("reason", reason)));
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/exit_safe_mode"))
+}
+
+/// Append an admin action to the log multisig signers bind their justifications to
+fn record_admin_action(
+    storage: &mut dyn Storage,
+    env: &Env,
+    action: AdminAction,
+    actor: &Addr,
+    reason: Option<String>,
+) -> StdResult<()> {
+    let state = State::default();
+
+    let id = state
+        .last_admin_action_id
+        .may_load(storage)?
+        .unwrap_or_default()
+        + 1;
+    state.last_admin_action_id.save(storage, &id)?;
+    state.admin_actions.save(
+        storage,
+        id,
+        &AdminActionRecord {
+            id,
+            action,
+            actor: actor.to_string(),
+            reason,
+            height: env.block.height,
+            time: env.block.time.seconds(),
+        },
+    )
+}
+
+pub fn propose_guardian_change(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    guardian: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    let guardian = guardian
+        .map(|s| deps.api.addr_validate(&s))
+        .transpose()?
+        .map(String::from);
+    let ready_at = env.block.time.seconds() + GUARDIAN_CHANGE_DELAY_SECONDS;
+    state.guardian_change.save(
+        deps.storage,
+        &GuardianChange {
+            guardian: guardian.clone(),
+            ready_at,
+        },
+    )?;
+
+    let event = Event::new("steakhub/guardian_change_proposed")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("guardian", guardian.unwrap_or_default())
+        .add_attribute("ready_at", ready_at.to_string());
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/propose_guardian_change"))
+}
+
+pub fn apply_guardian_change(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    let change = state
+        .guardian_change
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingGuardianChange {})?;
+    if env.block.time.seconds() < change.ready_at {
+        return Err(ContractError::GuardianChangeTimelocked {
+            ready_at: change.ready_at,
+        });
+    }
+    state.guardian_change.remove(deps.storage);
+
+    let prev_guardian = state.guardian.may_load(deps.storage)?;
+    match &change.guardian {
+        Some(guardian) => state
+            .guardian
+            .save(deps.storage, &Addr::unchecked(guardian))?,
+        None => state.guardian.remove(deps.storage),
+    }
+
+    let event = Event::new("steakhub/guardian_changed")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute(
+            "prev_guardian",
+            prev_guardian.map(String::from).unwrap_or_default(),
+        )
+        .add_attribute("guardian", change.guardian.unwrap_or_default());
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/apply_guardian_change"))
+}
+
+pub fn cancel_guardian_change(deps: DepsMut, sender: Addr) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.guardian_change.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("action", "steakhub/cancel_guardian_change"))
+}
+
+/// The emergency batch already counts the whole supply among its shares, so the Steak is burned
+/// straight away and the request can be withdrawn as soon as the batch has finished unbonding
+fn join_emergency_batch(
+    deps: DepsMut,
+    env: Env,
+    id: u64,
+    sender: Addr,
+    receiver: Addr,
+    usteak_to_burn: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    release_emergency_held(deps.storage, &deps.querier, &env, id, usteak_to_burn)?;
+    state
+        .unbond_requests
+        .update(deps.storage, (id, &receiver), |x| -> StdResult<_> {
+            let mut request = x.unwrap_or_else(|| UnbondRequest {
+                id,
+                user: receiver.clone(),
+                shares: Uint128::zero(),
+                sender: request_sender(&sender, &receiver),
+            });
+            request.shares += usteak_to_burn;
+            Ok(request)
+        })?;
+    record_batch_funder(deps.storage, id, &sender, &receiver, usteak_to_burn)?;
+
+    record_history(
+        deps.storage,
+        &env,
+        &receiver,
+        HistoryAction::Unbond,
+        Uint128::zero(),
+        usteak_to_burn,
+    )?;
+
+    let reserve_msg = coordinator_reserve_msg(deps.storage, &sender, usteak_to_burn)?;
+    let burn_msg = usteak_burn_msg(deps.storage, usteak_to_burn)?;
+
+    let event = Event::new("steakhub/unbond_queued")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("id", id.to_string())
+        .add_attribute("funder", sender)
+        .add_attribute("receiver", receiver)
+        .add_attribute("usteak_to_burn", usteak_to_burn);
+
+    Ok(Response::new()
+        .add_messages(reserve_msg)
+        .add_message(burn_msg)
+        .add_event(event)
+        .add_attribute("action", "steakhub/queue_unbond"))
+}
+
+/// Steak the hub held when it was wound down and has released since, e.g. an expired lock returned
+/// to its owner, joins the final batch along with the Native Token set aside for it, before the
+/// holder's shares are credited. The Steak being queued has already been received, but not burned
+fn release_emergency_held(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    env: &Env,
+    id: u64,
+    usteak_receiving: Uint128,
+) -> StdResult<()> {
+    let state = State::default();
+
+    let mut held = state.emergency_held.may_load(storage)?.unwrap_or_default();
+    if held.usteak.is_zero() {
+        return Ok(());
+    }
+    let steak_token = state.steak_token.load(storage)?;
+    let usteak_still_held = query_cw20_balance(querier, &steak_token, &env.contract.address)?
+        .saturating_sub(usteak_receiving);
+    if usteak_still_held >= held.usteak {
+        return Ok(());
+    }
+
+    let usteak_released = held.usteak - usteak_still_held;
+    let native_released = held.native.multiply_ratio(usteak_released, held.usteak);
+    held.usteak -= usteak_released;
+    held.native -= native_released;
+    state.emergency_held.save(storage, &held)?;
+
+    // once every claim on the batch has been withdrawn it is gone, and starts over with these
+    let mut batch = state
+        .previous_batches
+        .may_load(storage, id)?
+        .unwrap_or(Batch {
+            id,
+            reconciled: true,
+            total_shares: Uint128::zero(),
+            amount_unclaimed: Uint128::zero(),
+            est_unbond_end_time: env.block.time.seconds(),
+        });
+    batch.total_shares += usteak_released;
+    batch.amount_unclaimed += native_released;
+    state.previous_batches.save(storage, id, &batch)
+}
+
+/// Native Token received once the hub is wound down, i.e. the rewards withdrawn as everything was
+/// undelegated, can no longer be reinvested, so it is split between the final batch and the Steak
+/// the hub held, by their shares
+fn credit_emergency_native(
+    storage: &mut dyn Storage,
+    id: u64,
+    native_received: Uint128,
+) -> StdResult<()> {
+    let state = State::default();
+
+    let mut held = state.emergency_held.may_load(storage)?.unwrap_or_default();
+    let mut batch = state.previous_batches.load(storage, id)?;
+    let native_for_held = if held.usteak.is_zero() {
+        Uint128::zero()
+    } else {
+        native_received.multiply_ratio(held.usteak, batch.total_shares + held.usteak)
+    };
+    held.native += native_for_held;
+    batch.amount_unclaimed += native_received - native_for_held;
+    state.emergency_held.save(storage, &held)?;
+    state.previous_batches.save(storage, id, &batch)
+}
+
+pub fn add_balance_hook(
+    deps: DepsMut,
+    sender: Addr,
+    contract: Addr,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    let mut hooks = state
+        .balance_hooks
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if hooks.contains(&contract) {
+        return Err(ContractError::BalanceHookAlreadyRegistered {
+            contract: contract.into(),
+        });
+    }
+    if hooks.len() >= MAX_BALANCE_HOOKS {
+        return Err(ContractError::TooManyBalanceHooks {
+            max: MAX_BALANCE_HOOKS,
+        });
+    }
+    hooks.push(contract.clone());
+    state.balance_hooks.save(deps.storage, &hooks)?;
+
+    let event = Event::new("steakhub/balance_hook_added").add_attribute("contract", contract);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/add_balance_hook"))
+}
+
+pub fn remove_balance_hook(
+    deps: DepsMut,
+    sender: Addr,
+    contract: Addr,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    let mut hooks = state
+        .balance_hooks
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if !hooks.contains(&contract) {
+        return Err(ContractError::BalanceHookNotRegistered {
+            contract: contract.into(),
+        });
+    }
+    hooks.retain(|hook| *hook != contract);
+    state.balance_hooks.save(deps.storage, &hooks)?;
+
+    let event = Event::new("steakhub/balance_hook_removed").add_attribute("contract", contract);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/remove_balance_hook"))
+}
+
+pub fn add_harvester(
+    deps: DepsMut,
+    sender: Addr,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    let mut harvesters = state.harvesters.may_load(deps.storage)?.unwrap_or_default();
+    if harvesters.contains(&address) {
+        return Err(ContractError::HarvesterAlreadyRegistered {
+            address: address.into(),
+        });
+    }
+    if harvesters.len() >= MAX_HARVESTERS {
+        return Err(ContractError::TooManyHarvesters {
+            max: MAX_HARVESTERS,
+        });
+    }
+    harvesters.push(address.clone());
+    state.harvesters.save(deps.storage, &harvesters)?;
+
+    let event = Event::new("steakhub/harvester_added").add_attribute("address", address);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/add_harvester"))
+}
+
+pub fn remove_harvester(
+    deps: DepsMut,
+    sender: Addr,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    let mut harvesters = state.harvesters.may_load(deps.storage)?.unwrap_or_default();
+    if !harvesters.contains(&address) {
+        return Err(ContractError::HarvesterNotRegistered {
+            address: address.into(),
+        });
+    }
+    harvesters.retain(|harvester| *harvester != address);
+    state.harvesters.save(deps.storage, &harvesters)?;
+
+    let event = Event::new("steakhub/harvester_removed").add_attribute("address", address);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/remove_harvester"))
+}
+
+pub fn add_integrator(
+    deps: DepsMut,
+    sender: Addr,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    let mut integrators = state
+        .integrators
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if integrators.contains(&address) {
+        return Err(ContractError::IntegratorAlreadyRegistered {
+            address: address.into(),
+        });
+    }
+    if integrators.len() >= MAX_INTEGRATORS {
+        return Err(ContractError::TooManyIntegrators {
+            max: MAX_INTEGRATORS,
+        });
+    }
+    integrators.push(address.clone());
+    state.integrators.save(deps.storage, &integrators)?;
+
+    let event = Event::new("steakhub/integrator_added").add_attribute("address", address);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/add_integrator"))
+}
+
+pub fn remove_integrator(
+    deps: DepsMut,
+    sender: Addr,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    let mut integrators = state
+        .integrators
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if !integrators.contains(&address) {
+        return Err(ContractError::IntegratorNotRegistered {
+            address: address.into(),
+        });
+    }
+    integrators.retain(|integrator| *integrator != address);
+    state.integrators.save(deps.storage, &integrators)?;
+
+    let event = Event::new("steakhub/integrator_removed").add_attribute("address", address);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/remove_integrator"))
+}
+
+pub fn update_vote_delegates(
+    deps: DepsMut,
+    sender: Addr,
+    delegates: Vec<Addr>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    let mut unique: Vec<Addr> = vec![];
+    for delegate in delegates {
+        if !unique.contains(&delegate) {
+            unique.push(delegate);
+        }
+    }
+    if unique.len() > MAX_VOTE_DELEGATES {
+        return Err(ContractError::TooManyVoteDelegates {
+            max: MAX_VOTE_DELEGATES,
+        });
+    }
+    state.vote_delegates.save(deps.storage, &unique)?;
+
+    let event = Event::new("steakhub/vote_delegates_updated").add_attribute(
+        "delegates",
+        unique
+            .iter()
+            .map(|delegate| delegate.as_str())
+            .collect::<Vec<_>>()
+            .join(","),
+    );
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/update_vote_delegates"))
+}
+
+/// Record a delegate's vote on a proposal, and vote the option most current delegates voted for
+/// with the hub's stake if it differs from what the hub voted so far. A tie is voted `Abstain`
+pub fn delegate_vote(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    proposal_id: u64,
+    vote: VoteOption,
+    voting_end_time: u64,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let delegates = state
+        .vote_delegates
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    if !delegates.contains(&sender) {
+        return Err(ContractError::NotVoteDelegate {
+            address: sender.into(),
+        });
+    }
+
+    let mut delegated_vote = load_delegated_vote(deps.storage, proposal_id)?;
+    if delegated_vote.opted_out {
+        return Err(ContractError::DelegatedVoteOptedOut { proposal_id });
+    }
+    let voting_end_time = *delegated_vote
+        .voting_end_time
+        .get_or_insert(voting_end_time);
+    if env.block.time.seconds() >= voting_end_time {
+        return Err(ContractError::DelegatedVotingEnded { proposal_id });
+    }
+
+    match delegated_vote
+        .ballots
+        .iter_mut()
+        .find(|ballot| ballot.delegate == sender.as_str())
+    {
+        Some(ballot) => ballot.vote = vote.clone(),
+        None => delegated_vote.ballots.push(DelegateBallot {
+            delegate: sender.to_string(),
+            vote: vote.clone(),
+        }),
+    }
+
+    let counts: Vec<(VoteOption, usize)> = vec![
+        VoteOption::Yes,
+        VoteOption::No,
+        VoteOption::NoWithVeto,
+        VoteOption::Abstain,
+    ]
+    .into_iter()
+    .map(|option| {
+        let count = delegated_vote
+            .ballots
+            .iter()
+            .filter(|ballot| ballot.vote == option)
+            .filter(|ballot| delegates.iter().any(|d| *d == ballot.delegate))
+            .count();
+        (option, count)
+    })
+    .collect();
+    let most = counts
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or_default();
+    let mut leaders = counts.into_iter().filter(|(_, count)| *count == most);
+    let leading = match (leaders.next(), leaders.next()) {
+        (Some((option, _)), None) => option,
+        _ => VoteOption::Abstain,
+    };
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    if delegated_vote.cast.as_ref() != Some(&leading) {
+        msgs.push(CosmosMsg::Gov(GovMsg::Vote {
+            proposal_id,
+            vote: leading.clone(),
+        }));
+        delegated_vote.cast = Some(leading.clone());
+    }
+    state
+        .delegated_votes
+        .save(deps.storage, proposal_id, &delegated_vote)?;
+
+    let event = Event::new("steakhub/delegate_voted")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("delegate", sender)
+        .add_attribute("vote", vote_option_str(&vote))
+        .add_attribute("hub_vote", vote_option_str(&leading));
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_event(event)
+        .add_attribute("action", "steakhub/delegate_vote"))
+}
+
+pub fn opt_out_of_delegated_vote(
+    deps: DepsMut,
+    sender: Addr,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    let mut delegated_vote = load_delegated_vote(deps.storage, proposal_id)?;
+    if delegated_vote.cast.is_some() {
+        return Err(ContractError::DelegatedVoteCast { proposal_id });
+    }
+    delegated_vote.opted_out = true;
+    state
+        .delegated_votes
+        .save(deps.storage, proposal_id, &delegated_vote)?;
+
+    let event = Event::new("steakhub/delegated_vote_opted_out")
+        .add_attribute("proposal_id", proposal_id.to_string());
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/opt_out_of_delegated_vote"))
+}
+
+fn load_delegated_vote(storage: &dyn Storage, proposal_id: u64) -> StdResult<DelegatedVote> {
+    Ok(State::default()
+        .delegated_votes
+        .may_load(storage, proposal_id)?
+        .unwrap_or(DelegatedVote {
+            proposal_id,
+            voting_end_time: None,
+            ballots: vec![],
+            opted_out: false,
+            cast: None,
+        }))
+}
+
+fn vote_option_str(vote: &VoteOption) -> &'static str {
+    match vote {
+        VoteOption::Yes => "yes",
+        VoteOption::No => "no",
+        VoteOption::Abstain => "abstain",
+        VoteOption::NoWithVeto => "no_with_veto",
+    }
+}
+
+pub fn update_automation(
+    deps: DepsMut,
+    sender: Addr,
+    contract: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    let has_tasks = state
+        .automation_tasks
+        .keys(deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_some();
+    if has_tasks {
+        return Err(ContractError::AutomationTasksRegistered {});
+    }
+    match &contract {
+        Some(contract) => state.automation_contract.save(deps.storage, contract)?,
+        None => state.automation_contract.remove(deps.storage),
+    }
+
+    let event = Event::new("steakhub/automation_updated").add_attribute(
+        "contract",
+        contract.map(|c| c.to_string()).unwrap_or_default(),
+    );
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/update_automation"))
+}
+
+/// The task hash is only known once the automation contract has stored the task, so it is picked
+/// up from the `task_hash` attribute in the reply
+pub fn create_automation_task(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    funds: Vec<Coin>,
+    task: AutomationTask,
+    interval: Interval,
+    gas_limit: Option<u64>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    let automation = state
+        .automation_contract
+        .may_load(deps.storage)?
+        .ok_or(ContractError::AutomationNotConfigured {})?;
+    if state.automation_tasks.has(deps.storage, task.to_string()) {
+        return Err(ContractError::AutomationTaskExists {
+            task: task.to_string(),
+        });
+    }
+    state.pending_automation_task.save(deps.storage, &task)?;
+
+    let request = TaskRequest {
+        interval,
+        stop_on_fail: false,
+        actions: vec![Action {
+            msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                msg: to_binary(&task.execute_msg())?,
+                funds: vec![],
+            }),
+            gas_limit,
+        }],
+    };
+    let create_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: automation.to_string(),
+        msg: to_binary(&AutomationExecuteMsg::CreateTask {
+            task: Box::new(request),
+        })?,
+        funds,
+    });
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(
+            create_msg,
+            REPLY_AUTOMATION_TASK_CREATED,
+        ))
+        .add_attribute("action", "steakhub/create_automation_task"))
+}
+
+pub fn register_automation_task(
+    deps: DepsMut,
+    response: SubMsgResponse,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let task = state.pending_automation_task.load(deps.storage)?;
+    state.pending_automation_task.remove(deps.storage);
+    let task_hash = response
+        .events
         .iter()
-        .map(|d| {
-            Ok(SubMsg::reply_on_success(
-                d.to_cosmos_msg(env.contract.address.to_string())?,
-                REPLY_REGISTER_RECEIVED_COINS,
-            ))
-        })
-        .collect::<StdResult<Vec<_>>>()?;
+        .filter(|event| event.ty == "wasm")
+        .flat_map(|event| event.attributes.iter())
+        .find(|attr| attr.key == "task_hash")
+        .map(|attr| attr.value.clone())
+        .ok_or(ContractError::MissingTaskHash {})?;
+    state
+        .automation_tasks
+        .save(deps.storage, task.to_string(), &task_hash)?;
 
-    let event = Event::new("steak/validator_removed").add_attribute("validator", validator);
+    let event = Event::new("steakhub/automation_task_created")
+        .add_attribute("task", task.to_string())
+        .add_attribute("task_hash", task_hash);
 
     Ok(Response::new()
-        .add_submessages(redelegate_submsgs)
         .add_event(event)
-        .add_attribute("action", "steakhub/remove_validator"))
+        .add_attribute("action", "steakhub/register_automation_task"))
 }
 
-pub fn remove_validator_ex(
+pub fn refill_automation_task(
     deps: DepsMut,
-    _env: Env,
     sender: Addr,
-    validator: String,
-) -> StdResult<Response> {
+    funds: Vec<Coin>,
+    task: AutomationTask,
+) -> Result<Response, ContractError> {
     let state = State::default();
 
     state.assert_owner(deps.storage, &sender)?;
+    let automation = state.automation_contract.load(deps.storage)?;
+    let task_hash = state
+        .automation_tasks
+        .may_load(deps.storage, task.to_string())?
+        .ok_or_else(|| ContractError::AutomationTaskNotFound {
+            task: task.to_string(),
+        })?;
 
-    state.validators.update(deps.storage, |mut validators| {
-        if !validators.contains(&validator) {
-            return Err(StdError::generic_err(
-                "validator is not already whitelisted",
-            ));
-        }
-        validators.retain(|v| *v != validator);
-        Ok(validators)
-    })?;
+    let refill_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: automation.to_string(),
+        msg: to_binary(&AutomationExecuteMsg::RefillTaskBalance { task_hash })?,
+        funds,
+    });
+
+    Ok(Response::new()
+        .add_message(refill_msg)
+        .add_attribute("action", "steakhub/refill_automation_task"))
+}
+
+pub fn remove_automation_task(
+    deps: DepsMut,
+    sender: Addr,
+    task: AutomationTask,
+) -> Result<Response, ContractError> {
+    let state = State::default();
 
-    let event = Event::new("steak/validator_removed_ex").add_attribute("validator", validator);
+    state.assert_owner(deps.storage, &sender)?;
+    let automation = state.automation_contract.load(deps.storage)?;
+    let task_hash = state
+        .automation_tasks
+        .may_load(deps.storage, task.to_string())?
+        .ok_or_else(|| ContractError::AutomationTaskNotFound {
+            task: task.to_string(),
+        })?;
+    state
+        .automation_tasks
+        .remove(deps.storage, task.to_string());
+
+    let remove_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: automation.to_string(),
+        msg: to_binary(&AutomationExecuteMsg::RemoveTask {
+            task_hash: task_hash.clone(),
+        })?,
+        funds: vec![],
+    });
+
+    let event = Event::new("steakhub/automation_task_removed")
+        .add_attribute("task", task.to_string())
+        .add_attribute("task_hash", task_hash);
 
     Ok(Response::new()
+        .add_message(remove_msg)
         .add_event(event)
-        .add_attribute("action", "steakhub/remove_validator_ex"))
+        .add_attribute("action", "steakhub/remove_automation_task"))
 }
 
-pub fn pause_validator(
+pub fn end_automation_task(
     deps: DepsMut,
-    _env: Env,
     sender: Addr,
-    validator: String,
-) -> StdResult<Response> {
+    task_hash: String,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    if state.automation_contract.may_load(deps.storage)?.as_ref() != Some(&sender) {
+        return Err(ContractError::AutomationOnly {});
+    }
+    let task = state
+        .automation_tasks
+        .range(deps.storage, None, None, Order::Ascending)
+        .find(|item| matches!(item, Ok((_, hash)) if *hash == task_hash))
+        .transpose()?
+        .map(|(task, _)| task);
+
+    let mut event =
+        Event::new("steakhub/automation_task_ended").add_attribute("task_hash", &task_hash);
+    if let Some(task) = task {
+        state.automation_tasks.remove(deps.storage, task.clone());
+        event = event.add_attribute("task", task);
+    }
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/end_automation_task"))
+}
+
+pub fn update_clock_harvest_interval(
+    deps: DepsMut,
+    sender: Addr,
+    interval: Option<u64>,
+) -> Result<Response, ContractError> {
     let state = State::default();
 
     state.assert_owner(deps.storage, &sender)?;
+    match interval {
+        Some(0) => return Err(ContractError::ZeroClockHarvestInterval {}),
+        Some(interval) => state.clock_harvest_interval.save(deps.storage, &interval)?,
+        None => state.clock_harvest_interval.remove(deps.storage),
+    }
 
-    state
-        .validators_active
-        .update(deps.storage, |mut validators| {
-            if !validators.contains(&validator) {
-                return Err(StdError::generic_err(
-                    "validator is not already whitelisted",
-                ));
-            }
-            validators.retain(|v| *v != validator);
-            Ok(validators)
-        })?;
+    Ok(Response::new()
+        .add_attribute("action", "steakhub/update_clock_harvest_interval")
+        .add_attribute(
+            "interval",
+            interval.map(|i| i.to_string()).unwrap_or_default(),
+        ))
+}
+
+/// Advance the upkeep that is due: harvest on the configured schedule, submit the pending batch
+/// once ripe, and reconcile matured batches.
+///
+/// NOTE: a failing sudo call would be retried every block, or get the contract dropped from the
+/// clock module, so each piece of upkeep is dispatched as its own message with `reply_on_error`
+/// and a failure is only recorded
+pub fn clock_end_block(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let state = State::default();
+    if state.emergency_batch.may_load(deps.storage)?.is_some() {
+        return Ok(Response::new());
+    }
+    let current_time = env.block.time.seconds();
+    let mut due = vec![];
+
+    // harvesting first has the batch submitted below unbond at the updated exchange rate
+    if let Some(interval) = state.clock_harvest_interval.may_load(deps.storage)? {
+        let last_harvest = state
+            .last_clock_harvest
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        if current_time >= last_harvest.saturating_add(interval) {
+            state.last_clock_harvest.save(deps.storage, &current_time)?;
+            due.push(ExecuteMsg::Harvest {});
+        }
+    }
+
+    let pending_batch = state.pending_batch.load(deps.storage)?;
+    if !pending_batch.usteak_to_burn.is_zero()
+        && batch_submittable(deps.storage, &pending_batch, current_time)?
+    {
+        due.push(ExecuteMsg::SubmitBatch {});
+    }
 
-    let event = Event::new("steak/pause_validator").add_attribute("validator", validator);
+    let has_matured_batches = state
+        .previous_batches
+        .idx
+        .reconciled
+        .prefix(false.into())
+        .range(deps.storage, None, None, Order::Ascending)
+        .any(|item| matches!(item, Ok((_, b)) if current_time > b.est_unbond_end_time));
+    if has_matured_batches {
+        due.push(ExecuteMsg::Reconcile {});
+    }
+
+    if due.is_empty() {
+        return Ok(Response::new());
+    }
+
+    let submsgs = due
+        .iter()
+        .map(|msg| -> StdResult<SubMsg> {
+            Ok(SubMsg::reply_on_error(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: env.contract.address.to_string(),
+                    msg: to_binary(msg)?,
+                    funds: vec![],
+                }),
+                REPLY_CLOCK_TASK_FAILED,
+            ))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(Response::new()
+        .add_submessages(submsgs)
+        .add_attribute("action", "steakhub/clock_end_block"))
+}
+
+pub fn record_clock_task_failure(
+    deps: DepsMut,
+    result: SubMsgResult,
+) -> Result<Response, ContractError> {
+    let error = result.unwrap_err();
+    bump_metrics(deps.storage, |metrics| metrics.clock_task_failures += 1)?;
+    let event = Event::new("steakhub/clock_task_failed").add_attribute("error", error);
 
     Ok(Response::new()
         .add_event(event)
-        .add_attribute("action", "steakhub/pause_validator"))
+        .add_attribute("action", "steakhub/record_clock_task_failure"))
 }
 
-pub fn unpause_validator(
+/// NOTE: hooks are invoked with `reply_on_error`, so a hook that fails has its own changes reverted
+/// without reverting the transfer that triggered it
+pub fn dispatch_balance_change(
     deps: DepsMut,
-    _env: Env,
     sender: Addr,
-    validator: String,
-) -> StdResult<Response> {
+    change: BalanceChange,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let steak_token = state.steak_token.load(deps.storage)?;
+    if sender != steak_token {
+        return Err(ContractError::UnexpectedToken {
+            expected: "Steak".to_string(),
+            received: sender.to_string(),
+        });
+    }
+
+    let hooks = state
+        .balance_hooks
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let hook_submsgs = hooks
+        .iter()
+        .map(|hook| {
+            Ok(SubMsg::reply_on_error(
+                BalanceHookMsg::SteakBalanceChanged(change.clone())
+                    .into_cosmos_msg(hook.to_string())?,
+                REPLY_BALANCE_HOOK_FAILED,
+            ))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(Response::new()
+        .add_submessages(hook_submsgs)
+        .add_attribute("action", "steakhub/dispatch_balance_change")
+        .add_attribute("hooks", hooks.len().to_string()))
+}
+
+/// A failing hook must not block transfers of Steak, so its error is only recorded
+pub fn record_balance_hook_failure(
+    deps: DepsMut,
+    result: SubMsgResult,
+) -> Result<Response, ContractError> {
+    let error = result.unwrap_err();
+    bump_metrics(deps.storage, |metrics| metrics.balance_hook_failures += 1)?;
+    let event = Event::new("steakhub/balance_hook_failed").add_attribute("error", error);
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/record_balance_hook_failure"))
+}
+
+fn bump_metrics(storage: &mut dyn Storage, bump: impl FnOnce(&mut Metrics)) -> StdResult<()> {
+    let state = State::default();
+    let mut metrics = state.metrics.may_load(storage)?.unwrap_or_default();
+    bump(&mut metrics);
+    state.metrics.save(storage, &metrics)
+}
+
+pub fn update_fee(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    new_fee: Decimal,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
     let state = State::default();
 
     state.assert_owner(deps.storage, &sender)?;
-    let mut validators_active = state.validators_active.load(deps.storage)?;
-    if !validators_active.contains(&validator) {
-        validators_active.push(validator.clone());
+    let max_fee = state.max_fee_rate.load(deps.storage)?;
+    if new_fee > max_fee {
+        return Err(ContractError::FeeAboveMax {
+            fee: new_fee,
+            max_fee,
+        });
     }
-    state
-        .validators_active
-        .save(deps.storage, &validators_active)?;
+    if !new_fee.is_zero() && state.fee_account_type.load(deps.storage)? == FeeType::None {
+        return Err(ContractError::FeesDisabled {});
+    }
+    state.fee_rate.save(deps.storage, &new_fee)?;
+    record_admin_action(
+        deps.storage,
+        &env,
+        AdminAction::UpdateFee { new_fee },
+        &sender,
+        reason.clone(),
+    )?;
 
-    let event = Event::new("steak/unpause_validator").add_attribute("validator", validator);
+    let event = Event::new("steakhub/fee_updated")
+        .add_attribute("new_fee", new_fee.to_string())
+        .add_attributes(reason.map(|reason| ("reason", reason)));
 
     Ok(Response::new()
         .add_event(event)
-        .add_attribute("action", "steakhub/unpause_validator"))
+        .add_attribute("action", "steakhub/update_fee"))
 }
-pub fn set_unbond_period(
+
+/// The max fee is what stakers rely on to bound the fee, so once a committee is registered, the
+/// owner alone can no longer change it, nor the committee itself
+pub fn set_fee_committee(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     sender: Addr,
-    unbond_period: u64,
-) -> StdResult<Response> {
+    members: Vec<String>,
+    threshold: u32,
+) -> Result<Response, ContractError> {
     let state = State::default();
 
     state.assert_owner(deps.storage, &sender)?;
-    state.unbond_period.save(deps.storage, &unbond_period)?;
-    let event = Event::new("steak/set_unbond_period")
-        .add_attribute("unbond_period", format!("{}", unbond_period));
+    if state.fee_committee.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::FeeCommitteeExists {});
+    }
+    let committee = validate_fee_committee(deps.api, &members, threshold)?;
+    state.fee_committee.save(deps.storage, &committee)?;
+
+    let event = Event::new("steakhub/fee_committee_updated")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("members", members.join(","))
+        .add_attribute("threshold", threshold.to_string());
 
     Ok(Response::new()
         .add_event(event)
-        .add_attribute("action", "steakhub/set_unbond_period"))
+        .add_attribute("action", "steakhub/set_fee_committee"))
+}
+
+/// Members must be distinct, and the threshold a two-thirds supermajority of them
+fn validate_fee_committee(
+    api: &dyn Api,
+    members: &[String],
+    threshold: u32,
+) -> Result<FeeCommittee, ContractError> {
+    let members = members
+        .iter()
+        .map(|m| api.addr_validate(m))
+        .collect::<StdResult<Vec<_>>>()?;
+    let distinct = members
+        .iter()
+        .enumerate()
+        .all(|(i, m)| !members[..i].contains(m));
+    if members.is_empty() || members.len() > MAX_COMMITTEE_MEMBERS || !distinct {
+        return Err(ContractError::InvalidCommitteeMembers {
+            max: MAX_COMMITTEE_MEMBERS,
+        });
+    }
+    let threshold_ok =
+        threshold as usize <= members.len() && 3 * threshold as usize >= 2 * members.len();
+    if !threshold_ok {
+        return Err(ContractError::InvalidCommitteeThreshold {
+            members: members.len(),
+        });
+    }
+    Ok(FeeCommittee { members, threshold })
+}
+
+fn assert_committee_member(
+    storage: &dyn Storage,
+    sender: &Addr,
+) -> Result<FeeCommittee, ContractError> {
+    let committee = State::default()
+        .fee_committee
+        .may_load(storage)?
+        .ok_or(ContractError::FeeCommitteeNotSet {})?;
+    if !committee.members.contains(sender) {
+        return Err(ContractError::NotCommitteeMember {});
+    }
+    Ok(committee)
+}
+
+pub fn propose_committee_action(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    action: CommitteeAction,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let committee = assert_committee_member(deps.storage, &sender)?;
+    // invalid actions are rejected up front rather than once approved
+    match &action {
+        CommitteeAction::UpdateMaxFee { max_fee } => {
+            if *max_fee > Decimal::one() {
+                return Err(ContractError::MaxFeeTooHigh {});
+            }
+        }
+        CommitteeAction::UpdateCommittee { members, threshold } => {
+            validate_fee_committee(deps.api, members, *threshold)?;
+        }
+    }
+
+    let id = state
+        .last_committee_proposal_id
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + 1;
+    state.last_committee_proposal_id.save(deps.storage, &id)?;
+    let proposal = CommitteeProposal {
+        id,
+        action,
+        approvals: vec![sender.clone()],
+    };
+
+    let event = Event::new("steakhub/committee_action_proposed")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("id", id.to_string())
+        .add_attribute("proposer", sender);
+
+    let response = Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/propose_committee_action");
+    settle_committee_proposal(deps, env, &committee, proposal, response)
+}
+
+pub fn approve_committee_action(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let committee = assert_committee_member(deps.storage, &sender)?;
+    let mut proposal = state
+        .committee_proposal
+        .may_load(deps.storage)?
+        .filter(|p| p.id == id)
+        .ok_or(ContractError::CommitteeProposalNotFound { id })?;
+    if proposal.approvals.contains(&sender) {
+        return Err(ContractError::CommitteeProposalApproved { id });
+    }
+    proposal.approvals.push(sender.clone());
+
+    let event = Event::new("steakhub/committee_action_approved")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("id", id.to_string())
+        .add_attribute("member", sender)
+        .add_attribute("approvals", proposal.approvals.len().to_string());
+
+    let response = Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/approve_committee_action");
+    settle_committee_proposal(deps, env, &committee, proposal, response)
+}
+
+/// Apply the proposal if it has enough approvals, otherwise keep it pending
+fn settle_committee_proposal(
+    deps: DepsMut,
+    env: Env,
+    committee: &FeeCommittee,
+    proposal: CommitteeProposal,
+    response: Response,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    if (proposal.approvals.len() as u32) < committee.threshold {
+        state.committee_proposal.save(deps.storage, &proposal)?;
+        return Ok(response);
+    }
+    state.committee_proposal.remove(deps.storage);
+
+    let mut event = Event::new("steakhub/committee_action_applied")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("id", proposal.id.to_string());
+    match proposal.action {
+        CommitteeAction::UpdateMaxFee { max_fee } => {
+            let fee = state.fee_rate.load(deps.storage)?;
+            if fee > max_fee {
+                return Err(ContractError::FeeAboveMax { fee, max_fee });
+            }
+            let previous = state.max_fee_rate.load(deps.storage)?;
+            state.max_fee_rate.save(deps.storage, &max_fee)?;
+            event = event
+                .add_attribute("previous_max_fee", previous.to_string())
+                .add_attribute("max_fee", max_fee.to_string());
+        }
+        CommitteeAction::UpdateCommittee { members, threshold } => {
+            let committee = validate_fee_committee(deps.api, &members, threshold)?;
+            state.fee_committee.save(deps.storage, &committee)?;
+            event = event
+                .add_attribute("members", members.join(","))
+                .add_attribute("threshold", threshold.to_string());
+        }
+    }
+
+    Ok(response.add_event(event))
+}
+
+pub fn update_refundable_denoms(
+    deps: DepsMut,
+    sender: Addr,
+    denoms: Vec<String>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    // matched against the coins sent along deposits, as the SDK prints them
+    let denoms = denoms
+        .iter()
+        .map(|d| normalize_denom(d))
+        .collect::<StdResult<Vec<String>>>()?;
+    let denom = state.denom.load(deps.storage)?;
+    if denoms.contains(&denom) {
+        return Err(ContractError::RefundableNativeDenom { denom });
+    }
+    state.refundable_denoms.save(deps.storage, &denoms)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "steakhub/update_refundable_denoms")
+        .add_attribute("denoms", denoms.join(",")))
+}
+
+pub fn update_zapper(
+    deps: DepsMut,
+    sender: Addr,
+    zapper: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    match &zapper {
+        Some(zapper) => state.zapper.save(deps.storage, zapper)?,
+        None => state.zapper.remove(deps.storage),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "steakhub/update_zapper")
+        .add_attribute("zapper", zapper.map(String::from).unwrap_or_default()))
 }
 
-pub fn transfer_ownership(deps: DepsMut, sender: Addr, new_owner: String) -> StdResult<Response> {
+pub fn update_surplus_policy(
+    deps: DepsMut,
+    sender: Addr,
+    policy: SurplusPolicy,
+) -> Result<Response, ContractError> {
     let state = State::default();
 
-    state.assert_owner(deps.storage, &sender)?;
-    state
-        .new_owner
-        .save(deps.storage, &deps.api.addr_validate(&new_owner)?)?;
+    state.assert_owner(deps.storage, &sender)?;
+    let policy = match policy {
+        SurplusPolicy::Insurance { fund } => SurplusPolicy::Insurance {
+            fund: deps.api.addr_validate(&fund)?.into(),
+        },
+        policy => policy,
+    };
+    state.surplus_policy.save(deps.storage, &policy)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "steakhub/update_surplus_policy")
+        .add_attribute("policy", surplus_destination(deps.storage, &policy)?))
+}
 
-    Ok(Response::new().add_attribute("action", "steakhub/transfer_ownership"))
+/// Where `policy` routes the surplus: the fee account or insurance fund, or "reinvest"
+fn surplus_destination(storage: &dyn Storage, policy: &SurplusPolicy) -> StdResult<String> {
+    Ok(match policy {
+        SurplusPolicy::Reinvest => "reinvest".to_string(),
+        SurplusPolicy::Insurance { fund } => fund.clone(),
+        SurplusPolicy::FeeAccount => State::default().fee_account.load(storage)?.into(),
+    })
 }
 
-pub fn accept_ownership(deps: DepsMut, sender: Addr) -> StdResult<Response> {
+/// Native sent straight to the hub, e.g. airdrops, is not attributed to anyone, and would otherwise
+/// sit unnoticed in the balance. Route whatever is held beyond the liabilities per the policy
+pub fn skim_surplus(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
     let state = State::default();
 
-    let previous_owner = state.owner.load(deps.storage)?;
-    let new_owner = state.new_owner.load(deps.storage)?;
-
-    if sender != new_owner {
-        return Err(StdError::generic_err(
-            "unauthorized: sender is not new owner",
-        ));
+    state.assert_not_wound_down(deps.storage)?;
+    let denom = state.denom.load(deps.storage)?;
+    let native_balance = deps
+        .querier
+        .query_balance(&env.contract.address, &denom)?
+        .amount;
+    let native_liabilities =
+        native_liabilities(deps.storage, &deps.querier, &env.contract.address)?;
+    let surplus = native_balance.saturating_sub(native_liabilities);
+    if surplus.is_zero() {
+        return Err(ContractError::NoSurplus {});
     }
 
-    state.owner.save(deps.storage, &sender)?;
-    state.new_owner.remove(deps.storage);
+    let policy = state
+        .surplus_policy
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let surplus_coin = Coin::new(surplus.u128(), &denom);
+    let submsg = match &policy {
+        // bonded without minting any Steak, raising the exchange rate for all holders
+        SurplusPolicy::Reinvest => {
+            let validators = state.validators_active.load(deps.storage)?;
+            let delegations =
+                query_delegations(&deps.querier, &validators, &env.contract.address, &denom)?;
+            let validator = &delegations
+                .iter()
+                .min_by_key(|d| d.amount)
+                .ok_or(ContractError::NoActiveValidators {})?
+                .validator;
+            SubMsg::reply_on_success(
+                Delegation::new(validator, surplus.u128(), &denom)
+                    .to_cosmos_msg(env.contract.address.to_string())?,
+                REPLY_REGISTER_RECEIVED_COINS,
+            )
+        }
+        SurplusPolicy::Insurance { fund } => SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: fund.clone(),
+            amount: vec![surplus_coin],
+        })),
+        SurplusPolicy::FeeAccount => SubMsg::new(fee_forward_msg(deps.storage, surplus_coin)?),
+    };
 
-    let event = Event::new("steakhub/ownership_transferred")
-        .add_attribute("new_owner", new_owner)
-        .add_attribute("previous_owner", previous_owner);
+    let event = Event::new("steakhub/surplus_skimmed")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("native_balance", native_balance)
+        .add_attribute("native_liabilities", native_liabilities)
+        .add_attribute("surplus", surplus)
+        .add_attribute("destination", surplus_destination(deps.storage, &policy)?);
 
     Ok(Response::new()
+        .add_submessage(submsg)
         .add_event(event)
-        .add_attribute("action", "steakhub/transfer_ownership"))
-}
-
-fn transfer_fee_account_internal(
-    deps: DepsMut,
-    fee_account_type: String,
-    new_fee_account: String,
-) -> StdResult<()> {
-    let state = State::default();
-    let fee_type = FeeType::from_str(&fee_account_type)
-        .map_err(|_| StdError::generic_err("Invalid Fee type: Wallet or FeeSplit only"))?;
-    state.fee_account_type.save(deps.storage, &fee_type)?;
-    state
-        .fee_account
-        .save(deps.storage, &deps.api.addr_validate(&new_fee_account)?)?;
-    Ok(())
+        .add_attribute("action", "steakhub/skim_surplus"))
 }
 
-pub fn transfer_fee_account(
+pub fn update_reward_denoms(
     deps: DepsMut,
     sender: Addr,
-    fee_account_type: String,
-    new_fee_account: String,
-) -> StdResult<Response> {
+    reward_denoms: Vec<String>,
+    converter: Option<Addr>,
+) -> Result<Response, ContractError> {
     let state = State::default();
 
     state.assert_owner(deps.storage, &sender)?;
+    // denoms are matched against the received coins, which are normalized when parsed
+    let reward_denoms = reward_denoms
+        .iter()
+        .map(|d| normalize_denom(d))
+        .collect::<StdResult<Vec<String>>>()?;
+    if reward_denoms.is_empty() {
+        return Err(ContractError::NoRewardDenoms {});
+    }
+    for (i, denom) in reward_denoms.iter().enumerate() {
+        if reward_denoms[..i].contains(denom) {
+            return Err(ContractError::DuplicateRewardDenom {
+                denom: denom.clone(),
+            });
+        }
+    }
+    let denom = state.denom.load(deps.storage)?;
+    if converter.is_none() && reward_denoms.iter().any(|d| *d != denom) {
+        return Err(ContractError::RewardConverterRequired { denom });
+    }
 
-    transfer_fee_account_internal(deps, fee_account_type, new_fee_account)?;
-
-    Ok(Response::new().add_attribute("action", "steakhub/transfer_fee_account"))
-}
-
-pub fn change_denom(deps: DepsMut, sender: Addr, new_denom: String) -> StdResult<Response> {
-    let state = State::default();
+    state.reward_denoms.save(deps.storage, &reward_denoms)?;
+    match &converter {
+        Some(converter) => state.reward_converter.save(deps.storage, converter)?,
+        None => state.reward_converter.remove(deps.storage),
+    }
 
-    state.assert_owner(deps.storage, &sender)?;
-    state.denom.save(deps.storage, &new_denom)?;
+    let event = Event::new("steakhub/reward_denoms_updated")
+        .add_attribute("reward_denoms", reward_denoms.join(","))
+        .add_attribute(
+            "converter",
+            converter.map(|c| c.to_string()).unwrap_or_default(),
+        );
 
-    Ok(Response::new().add_attribute("action", "steakhub/change_denom"))
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/update_reward_denoms"))
 }
 
-pub fn update_fee(deps: DepsMut, sender: Addr, new_fee: Decimal) -> StdResult<Response> {
+pub fn update_rounding_policy(
+    deps: DepsMut,
+    sender: Addr,
+    policy: RoundingPolicy,
+) -> Result<Response, ContractError> {
     let state = State::default();
 
     state.assert_owner(deps.storage, &sender)?;
-    if new_fee > state.max_fee_rate.load(deps.storage)? {
-        return Err(StdError::generic_err(
-            "refusing to set fee above maximum set",
-        ));
-    }
-    state.fee_rate.save(deps.storage, &new_fee)?;
+    state.rounding_policy.save(deps.storage, &policy)?;
 
-    Ok(Response::new().add_attribute("action", "steakhub/update_fee"))
+    Ok(Response::new()
+        .add_attribute("action", "steakhub/update_rounding_policy")
+        .add_attribute("policy", format!("{:?}", policy)))
 }
 
 // update entropy execute function
 pub fn update_entropy(
     deps: DepsMut,
     env: Env,
-    _sender: Addr,
+    sender: Addr,
     entropy: String,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     let state = State::default();
+    let now = env.block.time.seconds();
+
+    let mut contributor = state
+        .entropy_contributors
+        .may_load(deps.storage, &sender)?
+        .unwrap_or_default();
+    if let Some(limit) = state.entropy_rate_limit.may_load(deps.storage)? {
+        if now >= contributor.window_start + limit.window {
+            contributor.window_start = now;
+            contributor.window_contributions = 0;
+        }
+        if contributor.window_contributions >= limit.max_contributions {
+            return Err(ContractError::EntropyRateLimited {
+                next_time: contributor.window_start + limit.window,
+            });
+        }
+    }
+    contributor.total_contributions += 1;
+    contributor.window_contributions += 1;
+    contributor.last_contribution = now;
+    state
+        .entropy_contributors
+        .save(deps.storage, &sender, &contributor)?;
 
     let next_entropy =
         state
@@ -1099,13 +7400,49 @@ pub fn update_entropy(
                 Ok(entropy_hash)
             })?;
 
-    update_difficulty(deps.storage, env.block.time.seconds(), false)?;
+    update_difficulty(deps.storage, now, false)?;
+
+    let id = state
+        .next_entropy_contribution_id
+        .may_load(deps.storage)?
+        .unwrap_or(1);
+    state
+        .next_entropy_contribution_id
+        .save(deps.storage, &(id + 1))?;
+    state.entropy_contributions.save(
+        deps.storage,
+        id,
+        &EntropyContribution {
+            id,
+            contributor: sender.clone(),
+            time: now,
+            height: env.block.height,
+            entropy_draft: next_entropy.clone(),
+        },
+    )?;
+
+    let event = Event::new("steakhub/entropy_contributed")
+        .add_attribute("time", now.to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("id", id.to_string())
+        .add_attribute("contributor", sender)
+        .add_attribute("miner_entropy_draft", &next_entropy);
 
     Ok(Response::new()
+        .add_event(event)
         .add_attribute("action", "steakhub/update_entropy")
         .add_attribute("miner_entropy_draft", next_entropy))
 }
 
+/// Difficulty proofs are currently checked against
+pub fn mining_difficulty(storage: &dyn Storage) -> StdResult<Uint64> {
+    #[cfg(feature = "test-mining")]
+    if crate::test_mining::is_enabled(storage)? {
+        return Ok(Uint64::zero());
+    }
+    State::default().miner_difficulty.load(storage)
+}
+
 pub fn create_difficulty_prefix(difficulty: Uint64) -> String {
     // validate difficulty
     let mut difficulty_string = String::new();
@@ -1126,32 +7463,46 @@ fn test_create_difficulty_prefix() {
 }
 
 pub fn compute_miner_proof(
+    proof_hash: ProofHash,
     miner_entropy: &str,
     miner_address: &str,
     nonce: Uint64,
 ) -> StdResult<String> {
     // validate block hash
-    let mut hasher = Sha256::new();
-    hasher.update(&miner_entropy);
-    hasher.update(miner_address);
-    hasher.update(nonce.to_le_bytes());
-    let result = hasher.finalize();
+    let result = match proof_hash {
+        ProofHash::Sha256 => hash_proof::<Sha256>(miner_entropy, miner_address, nonce),
+        ProofHash::Keccak256 => hash_proof::<Keccak256>(miner_entropy, miner_address, nonce),
+    };
     let entropy_hash = hex::encode(result);
     let entropy_hash = String::from_utf8(entropy_hash.as_bytes().to_vec())?;
 
     Ok(entropy_hash)
 }
+
+fn hash_proof<D: Digest>(miner_entropy: &str, miner_address: &str, nonce: Uint64) -> Vec<u8> {
+    let mut hasher = D::new();
+    hasher.update(miner_entropy);
+    hasher.update(miner_address);
+    hasher.update(nonce.to_le_bytes());
+    hasher.finalize().to_vec()
+}
+
 // unit test for compute_miner_proof
 #[test]
 fn test_compute_miner_proof() {
     let miner_entropy = "abcdefg".to_string();
     let miner_address = "cosmos123".to_string();
     let nonce = Uint64::from(3825297897467829464u64);
-    let result = compute_miner_proof(&miner_entropy, &miner_address, nonce);
+    let result = compute_miner_proof(ProofHash::Sha256, &miner_entropy, &miner_address, nonce);
     assert_eq!(
         result.unwrap(),
         "eb7d03dd856d797aea48b2a080357810c50b366d2a40fd358e1f1b18d3a62d5c"
     );
+    let result = compute_miner_proof(ProofHash::Keccak256, &miner_entropy, &miner_address, nonce);
+    assert_eq!(
+        result.unwrap(),
+        "e375e9ac294418032a544fbfff48e515bc092cf4db13b6036334db634268e47d"
+    );
 }
 
 pub fn update_difficulty(
@@ -1160,31 +7511,96 @@ pub fn update_difficulty(
     did_submit_proof: bool,
 ) -> StdResult<()> {
     let state = State::default();
-    let miner_last_mined_timestamp = state.miner_last_mined_timestamp.load(store)?;
-    let difficulty = state.miner_difficulty.load(store)?;
-    // update mining difficulty based on the mining duration ceiling and floor
-    let mining_duration = block_time - miner_last_mined_timestamp.u64();
+    let miner_last_mined_timestamp = state.miner_last_mined_timestamp.load(store)?.u64();
+    let difficulty = state.miner_difficulty.load(store)?.u64();
+    let mut intervals = state.proof_intervals.may_load(store)?.unwrap_or_default();
+
+    let new_difficulty = if did_submit_proof {
+        // retarget over the moving average of the most recent proof intervals
+        intervals.push(block_time - miner_last_mined_timestamp);
+        if intervals.len() > DIFFICULTY_RETARGET_WINDOW {
+            intervals.remove(0);
+        }
+        let average = intervals.iter().sum::<u64>() / intervals.len() as u64;
+        retarget_difficulty(difficulty, average)
+    } else {
+        // without proofs the difficulty may only decrease, by at most one level per ceiling
+        // period, so that spamming entropy updates can not drive it down
+        let last_adjustment = state
+            .miner_difficulty_adjusted_at
+            .may_load(store)?
+            .unwrap_or_default()
+            .max(miner_last_mined_timestamp);
+        if block_time - last_adjustment > TARGET_MINING_DURATION_CEILING_SECONDS && difficulty > 1 {
+            difficulty - 1
+        } else {
+            difficulty
+        }
+    };
 
-    // update difficulty
-    if mining_duration > TARGET_MINING_DURATION_CEILING_SECONDS && difficulty.u64() > 1 {
-        // too hard to mine, decrease difficulty
+    if new_difficulty != difficulty {
+        // express the recorded intervals in terms of the new difficulty, so that the next
+        // retarget does not act again on samples the adjustment already accounted for
+        for interval in intervals.iter_mut() {
+            *interval = if new_difficulty > difficulty {
+                interval.saturating_mul(
+                    DIFFICULTY_LEVEL_FACTOR.pow((new_difficulty - difficulty) as u32),
+                )
+            } else {
+                *interval / DIFFICULTY_LEVEL_FACTOR.pow((difficulty - new_difficulty) as u32)
+            };
+        }
         state
             .miner_difficulty
-            .update(store, |difficulty| -> StdResult<Uint64> {
-                Ok(difficulty.checked_sub(1u64.into())?)
-            })?;
-    // we only allow difficulty to increase if a proof was submitted
-    } else if mining_duration < TARGET_MINING_DURATION_FLOOR_SECONDS && did_submit_proof {
-        // too easy to mine, increase difficulty
+            .save(store, &Uint64::new(new_difficulty))?;
         state
-            .miner_difficulty
-            .update(store, |difficulty| -> StdResult<Uint64> {
-                Ok(difficulty.checked_add(1u64.into())?)
-            })?;
+            .miner_difficulty_adjusted_at
+            .save(store, &block_time)?;
     }
+    state.proof_intervals.save(store, &intervals)?;
+
     Ok(())
 }
 
+/// Difficulty at which `average_interval` would fall within the target band, moving by no more
+/// than `MAX_DIFFICULTY_ADJUSTMENT` levels and never below one
+pub fn retarget_difficulty(difficulty: u64, average_interval: u64) -> u64 {
+    let mut expected = average_interval;
+    let mut steps = 0;
+    if average_interval < TARGET_MINING_DURATION_FLOOR_SECONDS {
+        while expected < TARGET_MINING_DURATION_FLOOR_SECONDS && steps < MAX_DIFFICULTY_ADJUSTMENT {
+            expected = expected.saturating_mul(DIFFICULTY_LEVEL_FACTOR);
+            steps += 1;
+        }
+        difficulty + steps
+    } else {
+        while expected > TARGET_MINING_DURATION_CEILING_SECONDS
+            && steps < MAX_DIFFICULTY_ADJUSTMENT
+            && difficulty - steps > 1
+        {
+            expected /= DIFFICULTY_LEVEL_FACTOR;
+            steps += 1;
+        }
+        difficulty - steps
+    }
+}
+
+#[test]
+fn test_retarget_difficulty() {
+    // within the band
+    assert_eq!(retarget_difficulty(3, 20), 3);
+    assert_eq!(retarget_difficulty(3, 300), 3);
+    // one level brings 10 seconds to 160
+    assert_eq!(retarget_difficulty(3, 10), 4);
+    // a burst of proofs in the same block moves by the maximum only
+    assert_eq!(retarget_difficulty(3, 0), 3 + MAX_DIFFICULTY_ADJUSTMENT);
+    // 4000 seconds takes one level to 250
+    assert_eq!(retarget_difficulty(3, 4000), 2);
+    // never below one
+    assert_eq!(retarget_difficulty(2, 1_000_000), 1);
+    assert_eq!(retarget_difficulty(1, 1_000_000), 1);
+}
+
 // submit proof execute function
 // * validates block hash of entropy + sender bech32 + sender nonce meets the required mining difficulty
 // * sets miner_entropy to equal a hash of the block hash and miner_entropy_draft
@@ -1195,67 +7611,142 @@ pub fn submit_proof(
     env: Env,
     sender: Addr,
     nonce: Uint64,
-    validator_address: String,
-) -> StdResult<Response> {
+    allocations: Vec<MiningAllocation>,
+) -> Result<Response, ContractError> {
     let state = State::default();
-    let validator = deps
-        .querier
-        .query_validator(validator_address)?
-        .ok_or_else(|| StdError::generic_err("validator address not found in staking module"))?;
+    state.assert_not_in_safe_mode(deps.storage, &env.block)?;
+    let next_height = next_proof_height(deps.storage, &sender)?;
+    if env.block.height < next_height {
+        return Err(ContractError::ProofRateLimited { next_height });
+    }
+    if allocations.iter().any(|a| a.weight.is_zero())
+        || allocations.iter().map(|a| a.weight).sum::<Decimal>() != Decimal::one()
+    {
+        return Err(ContractError::InvalidAllocationWeights {});
+    }
+    let validators_active = state.validators_active.load(deps.storage)?;
+    for (i, allocation) in allocations.iter().enumerate() {
+        if allocations[..i]
+            .iter()
+            .any(|a| a.validator == allocation.validator)
+        {
+            return Err(ContractError::DuplicateAllocation {
+                validator: allocation.validator.clone(),
+            });
+        }
+        state.assert_not_blocked(deps.storage, &allocation.validator)?;
+        // mining power only counts towards validators the hub may actually delegate to
+        if !validators_active.contains(&allocation.validator) {
+            return Err(ContractError::ValidatorNotActive {
+                validator: allocation.validator.clone(),
+            });
+        }
+        if deps
+            .querier
+            .query_validator(&allocation.validator)?
+            .is_none()
+        {
+            return Err(ContractError::ValidatorNotFound {
+                validator: allocation.validator.clone(),
+            });
+        }
+    }
     let miner_entropy = state.miner_entropy.load(deps.storage)?;
     let miner_entropy_draft = state.miner_entropy_draft.load(deps.storage)?;
-    let fee_account_type = state.fee_account_type.load(deps.storage)?;
-    let difficulty = state.miner_difficulty.load(deps.storage)?;
+    let difficulty = mining_difficulty(deps.storage)?;
     let miner_last_mined_block = state
         .miner_last_mined_block
         .load(deps.storage)
         // defaults to previous block height
         .or_else(|_| -> StdResult<Uint64> { Ok(Uint64::from(env.block.height - 1)) })?;
 
-    let entropy_hash = compute_miner_proof(&miner_entropy, &sender.to_string(), nonce)?;
+    let proof_hash = state.proof_hash.may_load(deps.storage)?.unwrap_or_default();
+    let entropy_hash = compute_miner_proof(proof_hash, &miner_entropy, sender.as_ref(), nonce)?;
 
     let difficulty_string = create_difficulty_prefix(difficulty);
 
     if !entropy_hash.starts_with(&difficulty_string) {
-        return Err(StdError::generic_err(
-            "block hash does not meet difficulty requirement",
-        ));
+        // a nonce that met the target the last accepted proof was mined against is merely late,
+        // which miners handle differently from a nonce that never met any target
+        let stale = match state.previous_miner_target.may_load(deps.storage)? {
+            Some((previous_entropy, previous_difficulty)) => {
+                compute_miner_proof(proof_hash, &previous_entropy, sender.as_ref(), nonce)?
+                    .starts_with(&create_difficulty_prefix(previous_difficulty))
+            }
+            None => false,
+        };
+        return Err(ContractError::DifficultyNotMet {
+            reason: if stale {
+                ProofRejection::StaleEntropy
+            } else {
+                ProofRejection::InsufficientDifficulty
+            },
+            difficulty: difficulty.u64(),
+            prefix: difficulty_string,
+            hash: entropy_hash,
+            entropy: miner_entropy,
+        });
+    }
+    if state.used_proofs.has(deps.storage, entropy_hash.clone()) {
+        return Err(ContractError::ProofReplayed {});
     }
+    state
+        .used_proofs
+        .save(deps.storage, entropy_hash.clone(), &env.block.height)?;
+    state
+        .miner_last_proof_height
+        .save(deps.storage, &sender, &env.block.height)?;
     // compute hash of miner_entropy_draft and entropy_hash
     let mut hasher = Sha256::new();
     hasher.update(&miner_entropy_draft);
     hasher.update(&entropy_hash);
     let result = hasher.finalize();
+    let previous_entropy = miner_entropy;
     let miner_entropy = hex::encode(result);
-    let miner_entropy = String::from_utf8(miner_entropy.as_bytes().to_vec())?;
+    let miner_entropy =
+        String::from_utf8(miner_entropy.as_bytes().to_vec()).map_err(StdError::from)?;
 
     // blocks since last mined block
     let mining_duration_blocks = env.block.height - miner_last_mined_block.u64();
 
     update_difficulty(deps.storage, env.block.time.seconds(), true)?;
 
-    // update validator mining power
-    state.validator_mining_powers.update(
-        deps.storage,
-        validator.address,
-        |mining_power| -> StdResult<Uint128> {
-            Ok(mining_power
-                .unwrap_or_default()
-                .checked_add(Uint128::from(mining_duration_blocks))
-                .map_err(StdError::overflow)?)
-        },
-    )?;
+    // update validator mining power, split by weight with the rounding remainder going to the
+    // last validator
+    let mining_power = Uint128::from(mining_duration_blocks);
+    let mut remaining = mining_power;
+    for (i, allocation) in allocations.iter().enumerate() {
+        let amount = if i == allocations.len() - 1 {
+            remaining
+        } else {
+            mining_power * allocation.weight
+        };
+        remaining -= amount;
+        state.validator_mining_powers.update(
+            deps.storage,
+            allocation.validator.clone(),
+            |mining_power| -> StdResult<Uint128> {
+                mining_power
+                    .unwrap_or_default()
+                    .checked_add(amount)
+                    .map_err(StdError::overflow)
+            },
+        )?;
+    }
 
     // update total mining power
     state
         .total_mining_power
         .update(deps.storage, |total_mining_power| -> StdResult<Uint128> {
-            Ok(total_mining_power
+            total_mining_power
                 .checked_add(Uint128::from(mining_duration_blocks))
-                .map_err(StdError::overflow)?)
+                .map_err(StdError::overflow)
         })?;
 
-    // set miner entropy
+    // set miner entropy, remembering the target this proof met
+    state
+        .previous_miner_target
+        .save(deps.storage, &(previous_entropy, difficulty))?;
     state.miner_entropy.save(deps.storage, &miner_entropy)?;
 
     // set miner entropy draft to the entropy hash
@@ -1273,14 +7764,17 @@ pub fn submit_proof(
         .miner_last_mined_block
         .save(deps.storage, &env.block.height.into())?;
 
-    // set fee account
-    if fee_account_type != FeeType::Wallet {
-        state
-            .fee_account_type
-            .save(deps.storage, &FeeType::Wallet)?;
-    }
-    // make the miner the fee recipient
-    state.fee_account.save(deps.storage, &sender)?;
+    // the miner is paid its share of the fees from now on; a miner that wins again keeps its tenure
+    let current_miner = match state.miner.may_load(deps.storage)? {
+        Some(current_miner) if current_miner.miner == sender => current_miner,
+        _ => CurrentMiner {
+            miner: sender,
+            since_height: env.block.height,
+            since_time: env.block.time.seconds(),
+            earned: Uint128::zero(),
+        },
+    };
+    state.miner.save(deps.storage, &current_miner)?;
 
     // execute harvest
     let harvest_msg = ExecuteMsg::Harvest {};
@@ -1295,3 +7789,220 @@ pub fn submit_proof(
         .add_message(harvest_cosmos_msg)
         .add_attribute("action", "steakhub/submit_proof"))
 }
+
+pub fn update_mining_schedule(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    epoch_duration: u64,
+    initial_share: Decimal,
+    halving_epochs: u64,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    if epoch_duration == 0 {
+        return Err(ContractError::ZeroEpochDuration {});
+    }
+    if initial_share > Decimal::one() {
+        return Err(ContractError::RateAboveOne {
+            name: "miner share".to_string(),
+        });
+    }
+
+    let schedule = MiningSchedule {
+        start_time: env.block.time.seconds(),
+        epoch_duration,
+        initial_share,
+        halving_epochs,
+    };
+    state.mining_schedule.save(deps.storage, &schedule)?;
+
+    let event = Event::new("steakhub/mining_schedule_updated")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string())
+        .add_attribute("epoch_duration", epoch_duration.to_string())
+        .add_attribute("initial_share", initial_share.to_string())
+        .add_attribute("halving_epochs", halving_epochs.to_string());
+
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "steakhub/update_mining_schedule"))
+}
+
+pub fn update_miner_fee_share(
+    deps: DepsMut,
+    sender: Addr,
+    bps: u16,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    if bps > MAX_BPS {
+        return Err(ContractError::MinerFeeShareTooHigh { max: MAX_BPS });
+    }
+    state.miner_fee_share_bps.save(deps.storage, &bps)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "steakhub/update_miner_fee_share")
+        .add_attribute("bps", bps.to_string()))
+}
+
+pub fn update_proof_cooldown(
+    deps: DepsMut,
+    sender: Addr,
+    blocks: u64,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    state.proof_cooldown_blocks.save(deps.storage, &blocks)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "steakhub/update_proof_cooldown")
+        .add_attribute("blocks", blocks.to_string()))
+}
+
+pub fn update_entropy_rate_limit(
+    deps: DepsMut,
+    sender: Addr,
+    window: u64,
+    max_contributions: u32,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    state.assert_owner(deps.storage, &sender)?;
+    if max_contributions == 0 {
+        state.entropy_rate_limit.remove(deps.storage);
+    } else {
+        if window == 0 {
+            return Err(ContractError::ZeroEntropyWindow {});
+        }
+        state.entropy_rate_limit.save(
+            deps.storage,
+            &EntropyRateLimit {
+                window,
+                max_contributions,
+            },
+        )?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "steakhub/update_entropy_rate_limit")
+        .add_attribute("window", window.to_string())
+        .add_attribute("max_contributions", max_contributions.to_string()))
+}
+
+/// First height at which `miner` may have another proof accepted
+pub(crate) fn next_proof_height(storage: &dyn Storage, miner: &Addr) -> StdResult<u64> {
+    let state = State::default();
+
+    let cooldown = state
+        .proof_cooldown_blocks
+        .may_load(storage)?
+        .unwrap_or_default();
+    Ok(state
+        .miner_last_proof_height
+        .may_load(storage, miner)?
+        .map_or(0, |height| height + cooldown))
+}
+
+/// Mining power accrued by validators outside the whitelist skews `total_mining_power` and starves
+/// the validators the hub delegates to
+pub fn purge_mining_power(
+    deps: DepsMut,
+    env: Env,
+    validators: Vec<String>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let whitelist = state.validators.load(deps.storage)?;
+    let mut purged = Uint128::zero();
+    let mut event = Event::new("steakhub/mining_power_purged")
+        .add_attribute("time", env.block.time.seconds().to_string())
+        .add_attribute("height", env.block.height.to_string());
+    for validator in validators {
+        if whitelist.contains(&validator) {
+            continue;
+        }
+        let power = purge_validator_mining_power(deps.storage, &validator)?;
+        if !power.is_zero() {
+            purged += power;
+            event = event.add_attribute(validator, power);
+        }
+    }
+
+    Ok(Response::new()
+        .add_event(event.add_attribute("purged", purged))
+        .add_attribute("action", "steakhub/purge_mining_power"))
+}
+
+/// Save every unbonding request again, so those queued before the `sender` index was added are
+/// indexed too. Returns the number of requests saved
+pub(crate) fn reindex_unbond_requests(storage: &mut dyn Storage) -> StdResult<u64> {
+    let state = State::default();
+
+    let requests = state
+        .unbond_requests
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    for request in &requests {
+        state
+            .unbond_requests
+            .save(storage, (request.id, &request.user), request)?;
+    }
+    Ok(requests.len() as u64)
+}
+
+/// Purge the mining power of every validator that is no longer whitelisted. Used to clean up
+/// power left behind by removals made before removal purged it
+pub(crate) fn purge_orphaned_mining_power(storage: &mut dyn Storage) -> StdResult<Uint128> {
+    let state = State::default();
+
+    let whitelist = state.validators.load(storage)?;
+    let orphans = state
+        .validator_mining_powers
+        .keys(storage, None, None, Order::Ascending)
+        .filter(|validator| {
+            validator
+                .as_ref()
+                .map_or(true, |validator| !whitelist.contains(validator))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut purged = Uint128::zero();
+    for validator in orphans {
+        purged += purge_validator_mining_power(storage, &validator)?;
+    }
+    Ok(purged)
+}
+
+/// Remove a validator's mining power, deducting it from the total. Returns the amount removed
+fn purge_validator_mining_power(storage: &mut dyn Storage, validator: &str) -> StdResult<Uint128> {
+    let state = State::default();
+
+    let power = state
+        .validator_mining_powers
+        .may_load(storage, validator.to_string())?
+        .unwrap_or_default();
+    if power.is_zero() {
+        return Ok(power);
+    }
+    state
+        .validator_mining_powers
+        .remove(storage, validator.to_string());
+
+    let total_mining_power = state
+        .total_mining_power
+        .may_load(storage)?
+        .unwrap_or_default();
+    state
+        .total_mining_power
+        .save(storage, &total_mining_power.saturating_sub(power))?;
+
+    Ok(power)
+}
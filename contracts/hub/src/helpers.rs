@@ -1,12 +1,31 @@
 use std::str::FromStr;
 
+use cosmos_sdk_proto::cosmos::staking::v1beta1::{
+    QueryDelegatorUnbondingDelegationsRequest, QueryDelegatorUnbondingDelegationsResponse,
+    QueryPoolRequest, QueryPoolResponse, QueryValidatorRequest, QueryValidatorResponse,
+    UnbondingDelegation,
+};
 use cosmwasm_std::{
-    Addr, BalanceResponse, BankQuery, Coin, CosmosMsg, QuerierWrapper, QueryRequest, Reply,
-    StdError, StdResult, SubMsgResponse, Uint128,
+    to_vec, Addr, BalanceResponse, BankMsg, BankQuery, Binary, Coin, ContractResult, CosmosMsg,
+    Empty, QuerierWrapper, QueryRequest, Reply, StdError, StdResult, SubMsgResponse, SystemResult,
+    Uint128,
 };
 use cw20::{Cw20QueryMsg, TokenInfoResponse};
+use prost::Message;
+
+use crate::error::ContractError;
+use crate::types::{Delegation, STAKING_MODULE};
 
-use crate::types::Delegation;
+/// Split `msgs` into those that fit in one response under `max`, and those left for a later one
+pub(crate) fn split_msgs<T>(mut msgs: Vec<T>, max: Option<u32>) -> (Vec<T>, Vec<T>) {
+    match max {
+        Some(max) if msgs.len() > max as usize => {
+            let rest = msgs.split_off(max as usize);
+            (msgs, rest)
+        }
+        _ => (msgs, vec![]),
+    }
+}
 
 /// Unwrap a `Reply` object to extract the response
 pub(crate) fn unwrap_reply(reply: Reply) -> StdResult<SubMsgResponse> {
@@ -23,6 +42,21 @@ pub(crate) fn query_cw20_total_supply(
     Ok(token_info.total_supply)
 }
 
+/// Query the balance of a CW20 token held by an account
+pub(crate) fn query_cw20_balance(
+    querier: &QuerierWrapper,
+    token_addr: &Addr,
+    account: &Addr,
+) -> StdResult<Uint128> {
+    let res: cw20::BalanceResponse = querier.query_wasm_smart(
+        token_addr,
+        &Cw20QueryMsg::Balance {
+            address: account.to_string(),
+        },
+    )?;
+    Ok(res.balance)
+}
+
 /// Query the amounts of Native Token a staker is delegating to a specific validator
 pub(crate) fn query_delegation(
     querier: &QuerierWrapper,
@@ -59,48 +93,105 @@ pub(crate) fn query_delegations(
 /// including the `regex` library. Example:
 /// https://github.com/PFC-Validator/terra-rust/blob/v1.1.8/terra-rust-api/src/client/core_types.rs#L34-L55
 ///
-/// We opt for a dirtier solution. Enumerate characters in the string, and break before the first
-/// character that is not a number. Split the string at that index.
-///
-/// This assumes the denom never starts with a number, which is true on Terra.
+/// We opt for a dirtier solution. Break before the first character that is not a digit and split
+/// the string at that index. The denom is then validated against the SDK's denom rules, which
+/// require it to start with a letter, so IBC (`ibc/{hash}`) and token factory
+/// (`factory/{creator}/{subdenom}`) denoms, whose later segments may contain digits, parse as a
+/// whole.
 pub(crate) fn parse_coin(s: &str) -> StdResult<Coin> {
-    for (i, c) in s.chars().enumerate() {
-        if c.is_alphabetic() {
-            let amount = Uint128::from_str(&s[..i])?;
-            let denom = &s[i..];
-            return Ok(Coin::new(amount.u128(), denom));
-        }
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| StdError::generic_err(format!("failed to parse coin: {}", s)))?;
+    let amount = Uint128::from_str(&s[..split])?;
+    let denom = normalize_denom(&s[split..])?;
+    Ok(Coin::new(amount.u128(), denom))
+}
+
+/// Validate a denom against the SDK's rules, `[a-zA-Z][a-zA-Z0-9/:._-]{2,127}`, and bring IBC
+/// denoms to their canonical form: the SDK prints the trace hash in upper-case hex, but accepts it
+/// in either case, so the same asset could otherwise be counted under two denoms
+pub(crate) fn normalize_denom(denom: &str) -> StdResult<String> {
+    let invalid = || StdError::generic_err(format!("invalid denom: {}", denom));
+
+    let starts_with_letter = denom.starts_with(|c: char| c.is_ascii_alphabetic());
+    let valid_chars = denom
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | ':' | '.' | '_' | '-'));
+    if !starts_with_letter || !valid_chars || denom.len() < 3 || denom.len() > 128 {
+        return Err(invalid());
     }
 
-    Err(StdError::generic_err(format!(
-        "failed to parse coin: {}",
-        s
-    )))
+    match denom.strip_prefix("ibc/") {
+        Some(hash) => {
+            if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(invalid());
+            }
+            Ok(format!("ibc/{}", hash.to_ascii_uppercase()))
+        }
+        None => Ok(denom.to_string()),
+    }
 }
 
-/// Find the amount of a denom sent along a message, assert it is non-zero, and no other denom were
-/// sent together
-pub(crate) fn parse_received_fund(funds: &[Coin], denom: &str) -> StdResult<Uint128> {
-    if funds.len() != 1 {
-        return Err(StdError::generic_err(format!(
-            "must deposit exactly one coin; received {}",
-            funds.len()
-        )));
+/// Find the amount of a denom sent along a message and assert it is non-zero. Coins of the
+/// `refundable` denoms may be sent together, and are returned to be refunded; any other denom fails
+/// the deposit, listing what was sent
+pub(crate) fn parse_received_fund(
+    funds: &[Coin],
+    denom: &str,
+    refundable: &[String],
+) -> Result<(Uint128, Vec<Coin>), ContractError> {
+    if funds.is_empty() {
+        return Err(ContractError::InvalidFundsCount { count: 0 });
     }
 
-    let fund = &funds[0];
-    if fund.denom != denom {
-        return Err(StdError::generic_err(format!(
-            "expected {} deposit, received {}",
-            denom, fund.denom
-        )));
+    let (deposit, others): (Vec<&Coin>, Vec<&Coin>) =
+        funds.iter().partition(|coin| coin.denom == denom);
+    let unexpected = others
+        .iter()
+        .filter(|coin| !refundable.contains(&coin.denom))
+        .collect::<Vec<_>>();
+    match unexpected.as_slice() {
+        [] => (),
+        [coin] if funds.len() == 1 => {
+            return Err(ContractError::UnexpectedDenom {
+                expected: denom.to_string(),
+                received: coin.denom.clone(),
+            })
+        }
+        _ => {
+            return Err(ContractError::UnexpectedFunds {
+                expected: denom.to_string(),
+                received: unexpected
+                    .iter()
+                    .map(|coin| coin.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            })
+        }
     }
 
-    if fund.amount.is_zero() {
-        return Err(StdError::generic_err("deposit amount must be non-zero"));
+    let amount = deposit.iter().map(|coin| coin.amount).sum::<Uint128>();
+    if amount.is_zero() {
+        return Err(ContractError::ZeroDeposit {});
     }
 
-    Ok(fund.amount)
+    let refunds = others
+        .into_iter()
+        .filter(|coin| !coin.amount.is_zero())
+        .cloned()
+        .collect();
+    Ok((amount, refunds))
+}
+
+/// Message returning the coins refunded from a deposit to `recipient`, if there are any
+pub(crate) fn refund_msg(recipient: &Addr, refunds: Vec<Coin>) -> Option<CosmosMsg> {
+    (!refunds.is_empty()).then(|| {
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: refunds,
+        })
+    })
 }
 
 pub fn get_denom_balance(
@@ -115,6 +206,116 @@ pub fn get_denom_balance(
     Ok(balance.amount.amount)
 }
 
+/// Native Token still unbonding from each validator and the number of unbonding entries, as reported
+/// by the staking module. Chains only serve the gRPC queries they whitelist for contracts, so this
+/// errors on those that do not
+pub(crate) fn query_chain_unbondings(
+    querier: &QuerierWrapper,
+    delegator_addr: &Addr,
+) -> StdResult<Vec<(String, Uint128, u32)>> {
+    query_delegator_unbondings(querier, delegator_addr)?
+        .into_iter()
+        .map(|ubd| {
+            let amount = ubd
+                .entries
+                .iter()
+                .map(|entry| Uint128::from_str(&entry.balance))
+                .sum::<StdResult<Uint128>>()?;
+            Ok((ubd.validator_address, amount, ubd.entries.len() as u32))
+        })
+        .collect()
+}
+
+/// Completion time, in seconds, of each unbonding entry the staking module holds for each validator;
+/// `None` for entries the chain leaves it out of. Errors where `query_chain_unbondings` does
+pub(crate) fn query_chain_unbonding_completions(
+    querier: &QuerierWrapper,
+    delegator_addr: &Addr,
+) -> StdResult<Vec<(String, Vec<Option<u64>>)>> {
+    Ok(query_delegator_unbondings(querier, delegator_addr)?
+        .into_iter()
+        .map(|ubd| {
+            let completions = ubd
+                .entries
+                .iter()
+                .map(|entry| entry.completion_time.as_ref().map(|t| t.seconds as u64))
+                .collect();
+            (ubd.validator_address, completions)
+        })
+        .collect())
+}
+
+fn query_delegator_unbondings(
+    querier: &QuerierWrapper,
+    delegator_addr: &Addr,
+) -> StdResult<Vec<UnbondingDelegation>> {
+    let response: QueryDelegatorUnbondingDelegationsResponse = query_stargate(
+        querier,
+        "DelegatorUnbondingDelegations",
+        QueryDelegatorUnbondingDelegationsRequest {
+            delegator_addr: delegator_addr.to_string(),
+            pagination: None,
+        },
+    )?;
+    Ok(response.unbonding_responses)
+}
+
+/// Voting power of each of `validators`, and the chain's total, as reported by the staking module.
+/// Like `query_chain_unbondings`, this errors on chains that do not serve the queries to contracts
+pub(crate) fn query_chain_voting_power(
+    querier: &QuerierWrapper,
+    validators: &[String],
+) -> StdResult<(Uint128, Vec<(String, Uint128)>)> {
+    let response: QueryPoolResponse = query_stargate(querier, "Pool", QueryPoolRequest {})?;
+    let total = response
+        .pool
+        .map(|pool| Uint128::from_str(&pool.bonded_tokens))
+        .transpose()?
+        .unwrap_or_default();
+
+    let powers = validators
+        .iter()
+        .map(|validator| {
+            let response: QueryValidatorResponse = query_stargate(
+                querier,
+                "Validator",
+                QueryValidatorRequest {
+                    validator_addr: validator.clone(),
+                },
+            )?;
+            let power = response
+                .validator
+                .map(|v| Uint128::from_str(&v.tokens))
+                .transpose()?
+                .unwrap_or_default();
+            Ok((validator.clone(), power))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok((total, powers))
+}
+
+/// Send `request` to the staking module's gRPC `method`, decoding the response
+fn query_stargate<Req: Message, Res: Message + Default>(
+    querier: &QuerierWrapper,
+    method: &str,
+    request: Req,
+) -> StdResult<Res> {
+    let mut data = Vec::new();
+    prost::Message::encode(&request, &mut data)
+        .map_err(|_e| StdError::generic_err("Message encoding must be infallible"))?;
+    let request: QueryRequest<Empty> = QueryRequest::Stargate {
+        path: format!("/{}.staking.v1beta1.Query/{}", STAKING_MODULE, method),
+        data: Binary(data),
+    };
+    let raw = match querier.raw_query(&to_vec(&request)?) {
+        SystemResult::Ok(ContractResult::Ok(raw)) => raw,
+        SystemResult::Ok(ContractResult::Err(err)) => return Err(StdError::generic_err(err)),
+        SystemResult::Err(err) => return Err(StdError::generic_err(err.to_string())),
+    };
+    Res::decode(raw.as_slice()).map_err(|err| StdError::parse_err(method, err))
+}
+
 // encode a protobuf into a cosmos message
 // Inspired by https://github.com/alice-ltd/smart-contracts/blob/master/contracts/alice_terra_token/src/execute.rs#L73-L76
 pub(crate) fn proto_encode<M: prost::Message>(msg: M, type_url: String) -> StdResult<CosmosMsg> {
@@ -1,11 +1,14 @@
 #[cfg(not(feature = "library"))]
 pub mod contract;
 
+pub mod error;
 pub mod execute;
 pub mod helpers;
 pub mod math;
 pub mod queries;
 pub mod state;
+#[cfg(feature = "test-mining")]
+pub mod test_mining;
 pub mod types;
 
 mod migrations;
@@ -1,8 +1,9 @@
-use std::{cmp, cmp::Ordering, ops::Mul};
+use std::{cmp, cmp::Ordering, collections::BTreeMap, convert::TryInto, ops::Mul};
 
-use cosmwasm_std::{Decimal, StdError, StdResult, Uint128};
+use cosmwasm_std::{Decimal, Decimal256, Fraction, StdError, StdResult, Uint128, Uint256};
 
-use pfc_steak::hub::Batch;
+use pfc_steak::hub::{Batch, MiningSchedule, RewardDrip, RoundingPolicy, YieldPool, YieldPosition};
+use pfc_steak::DecimalCheckedOps;
 
 use crate::types::{Delegation, Redelegation, Undelegation};
 
@@ -12,31 +13,76 @@ use crate::types::{Delegation, Redelegation, Undelegation};
 
 /// Compute the amount of Steak token to mint for a specific Native Token stake amount. If current total
 /// staked amount is zero, we use 1 usteak = 1 native; otherwise, we calculate base on the current
-/// native per ustake ratio.
+/// native per ustake ratio, rounding as `rounding` dictates.
 pub(crate) fn compute_mint_amount(
     usteak_supply: Uint128,
     native_to_bond: Uint128,
-    current_delegations: &[Delegation],
-) -> Uint128 {
-    let native_bonded: u128 = current_delegations.iter().map(|d| d.amount).sum();
-    if native_bonded == 0 {
-        native_to_bond
+    native_bonded: Uint128,
+    rounding: RoundingPolicy,
+) -> StdResult<Uint128> {
+    if native_bonded.is_zero() {
+        Ok(native_to_bond)
     } else {
-        usteak_supply.multiply_ratio(native_to_bond, native_bonded)
+        multiply_ratio_rounding(
+            usteak_supply,
+            native_to_bond,
+            native_bonded,
+            rounding == RoundingPolicy::FavorUser,
+        )
     }
 }
 
-/// Compute the amount of `native` to unbond for a specific `usteak` burn amount
+/// Compute the amount of `native` to unbond for a specific `usteak` burn amount, rounding as
+/// `rounding` dictates
 ///
 /// There is no way `usteak` total supply is zero when the user is senting a non-zero amount of `usteak`
 /// to burn, so we don't need to handle division-by-zero here
 pub(crate) fn compute_unbond_amount(
     usteak_supply: Uint128,
     usteak_to_burn: Uint128,
-    current_delegations: &[Delegation],
-) -> Uint128 {
-    let native_bonded: u128 = current_delegations.iter().map(|d| d.amount).sum();
-    Uint128::new(native_bonded).multiply_ratio(usteak_to_burn, usteak_supply)
+    native_bonded: Uint128,
+    rounding: RoundingPolicy,
+) -> StdResult<Uint128> {
+    multiply_ratio_rounding(
+        native_bonded,
+        usteak_to_burn,
+        usteak_supply,
+        rounding == RoundingPolicy::FavorUser,
+    )
+}
+
+/// `value * numerator / denominator`, rounded down, or up if `round_up` is set
+fn multiply_ratio_rounding(
+    value: Uint128,
+    numerator: Uint128,
+    denominator: Uint128,
+    round_up: bool,
+) -> StdResult<Uint128> {
+    let product = value.full_mul(numerator);
+    let denominator = Uint256::from(denominator);
+    let mut result = product / denominator;
+    if round_up && !(product % denominator).is_zero() {
+        result += Uint256::from(1u8);
+    }
+    result.try_into().map_err(StdError::from)
+}
+
+/// Apportion `rate` of `amount`, adding the fractional remainder `carry` left over by previous
+/// calls. Returns the whole amount to be apportioned now and the new remainder to carry forward,
+/// so that repeated small amounts don't lose value to truncation.
+pub(crate) fn compute_fee_with_carry(
+    rate: Decimal,
+    amount: Uint128,
+    carry: Decimal256,
+) -> StdResult<(Uint128, Decimal256)> {
+    let exact = rate
+        .to_decimal256()
+        .checked_mul(Decimal256::from_ratio(amount, 1u128))
+        .and_then(|fee| fee.checked_add(carry))
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    let whole = exact.numerator() / exact.denominator();
+    let remainder = exact - Decimal256::from_ratio(whole, 1u128);
+    Ok((whole.try_into()?, remainder))
 }
 
 //--------------------------------------------------------------------------------------------------
@@ -64,14 +110,10 @@ pub(crate) fn compute_undelegations(
     let mut new_undelegations: Vec<Undelegation> = vec![];
     let mut native_available = native_to_unbond.u128();
     for (i, d) in current_delegations.iter().enumerate() {
-        let remainder_for_validator: u128 = u128::from((i + 1) as u128 <= remainder) as u128;
+        let remainder_for_validator: u128 = u128::from((i + 1) as u128 <= remainder);
         let native_for_validator = native_per_validator + remainder_for_validator;
 
-        let mut native_to_undelegate = if d.amount < native_for_validator {
-            0
-        } else {
-            d.amount - native_for_validator
-        };
+        let mut native_to_undelegate = d.amount.saturating_sub(native_for_validator);
 
         native_to_undelegate = cmp::min(native_to_undelegate, native_available);
         native_available -= native_to_undelegate;
@@ -109,14 +151,10 @@ pub(crate) fn compute_redelegations_for_removal(
     let mut new_redelegations: Vec<Redelegation> = vec![];
     let mut native_available = delegation_to_remove.amount;
     for (i, d) in current_delegations.iter().enumerate() {
-        let remainder_for_validator: u128 = u128::from((i + 1) as u128 <= remainder) as u128;
+        let remainder_for_validator: u128 = u128::from((i + 1) as u128 <= remainder);
         let native_for_validator = native_per_validator + remainder_for_validator;
 
-        let mut native_to_redelegate = if d.amount > native_for_validator {
-            0
-        } else {
-            native_for_validator - d.amount
-        };
+        let mut native_to_redelegate = native_for_validator.saturating_sub(d.amount);
 
         native_to_redelegate = cmp::min(native_to_redelegate, native_available);
         native_available -= native_to_redelegate;
@@ -138,25 +176,178 @@ pub(crate) fn compute_redelegations_for_removal(
     new_redelegations
 }
 
+/// Compute how much of `drip` is still unreleased at `current_time`
+pub(crate) fn compute_unreleased_rewards(drip: &RewardDrip, current_time: u64) -> Uint128 {
+    if current_time >= drip.end_time {
+        return Uint128::zero();
+    }
+    let elapsed = current_time.saturating_sub(drip.start_time);
+    let duration = drip.end_time - drip.start_time;
+    drip.amount.multiply_ratio(duration - elapsed, duration)
+}
+
+/// The epoch of the mining schedule at `current_time`, and the share of the fees paid to the miner
+/// in that epoch
+pub(crate) fn compute_miner_share(schedule: &MiningSchedule, current_time: u64) -> (u64, Decimal) {
+    let epoch = current_time.saturating_sub(schedule.start_time) / schedule.epoch_duration;
+    let halvings = epoch.checked_div(schedule.halving_epochs).unwrap_or(0);
+    let share = if halvings >= 64 {
+        Decimal::zero()
+    } else {
+        schedule.initial_share * Decimal::from_ratio(1u128, 1u128 << halvings)
+    };
+    (epoch, share)
+}
+
+/// Score of a validator: its reward rate relative to the best rate among its peers, less 10% for
+/// each pause and 50% for each slashing incident, compounded
+pub(crate) fn compute_validator_score(
+    reward_rate: Decimal,
+    best_reward_rate: Decimal,
+    pauses: u32,
+    slashes: u32,
+) -> Decimal {
+    let relative_rate = if best_reward_rate.is_zero() {
+        Decimal::one()
+    } else {
+        Decimal::from_ratio(reward_rate.atomics(), best_reward_rate.atomics())
+    };
+    relative_rate * Decimal::percent(90).pow(pauses) * Decimal::percent(50).pow(slashes)
+}
+
 pub fn compute_target_delegation_from_mining_power(
     total_delegated_amount: Uint128,
     validator_mining_power: Uint128,
     total_mining_power: Uint128,
 ) -> StdResult<Uint128> {
-    println!(
-        "total_delegated_amount: {}, validator_mining_power: {}, total_mining_power: {}",
-        total_delegated_amount, validator_mining_power, total_mining_power
-    );
     if validator_mining_power > total_mining_power {
         return Err(StdError::generic_err(
             "validator mining power cannot be greater than total mining power",
         ));
     }
+    if total_mining_power.is_zero() {
+        return Err(StdError::generic_err("total mining power is zero"));
+    }
     let expected_delegated_amount =
         Decimal::from_ratio(validator_mining_power, total_mining_power).mul(total_delegated_amount);
     Ok(expected_delegated_amount)
 }
 
+/// Amount a validator should have delegated: its share of the mining power or, until a proof has
+/// been accepted and there is no mining power to weigh validators by, an even split across the
+/// `validator_count` validators stake is spread over
+pub fn compute_target_delegation(
+    total_delegated_amount: Uint128,
+    validator_mining_power: Uint128,
+    total_mining_power: Uint128,
+    validator_count: usize,
+) -> StdResult<Uint128> {
+    if !total_mining_power.is_zero() {
+        return compute_target_delegation_from_mining_power(
+            total_delegated_amount,
+            validator_mining_power,
+            total_mining_power,
+        );
+    }
+    if validator_count == 0 {
+        return Ok(Uint128::zero());
+    }
+    Ok(total_delegated_amount / Uint128::new(validator_count as u128))
+}
+
+/// Most the hub may delegate to a validator without lifting it above `max_share` of the chain's
+/// `total_voting_power`, counting the validator's voting power from others than the hub
+pub(crate) fn compute_voting_power_cap(
+    max_share: Decimal,
+    total_voting_power: Uint128,
+    validator_voting_power: Uint128,
+    hub_delegation: Uint128,
+) -> Uint128 {
+    (total_voting_power * max_share)
+        .saturating_sub(validator_voting_power.saturating_sub(hub_delegation))
+}
+
+/// Lower the targets above their validator's cap to the cap, and spread what is cut over the
+/// validators below theirs in proportion to their targets, repeating as long as that lifts more
+/// validators above their caps. Validators without a cap are never capped; if every validator is,
+/// what is cut is left unassigned
+pub(crate) fn cap_target_delegations(
+    targets: &[(String, Uint128)],
+    caps: &BTreeMap<String, Uint128>,
+) -> Vec<(String, Uint128)> {
+    let total: Uint128 = targets.iter().map(|(_, target)| *target).sum();
+    let mut capped: BTreeMap<&str, Uint128> = BTreeMap::new();
+    loop {
+        let fixed: Uint128 = capped.values().copied().sum();
+        let free = total.saturating_sub(fixed);
+        let free_weight: Uint128 = targets
+            .iter()
+            .filter(|(validator, _)| !capped.contains_key(validator.as_str()))
+            .map(|(_, target)| *target)
+            .sum();
+        let spread = |target: Uint128| {
+            if free_weight.is_zero() {
+                Uint128::zero()
+            } else {
+                free.multiply_ratio(target, free_weight)
+            }
+        };
+
+        let newly_capped = targets
+            .iter()
+            .filter(|(validator, _)| !capped.contains_key(validator.as_str()))
+            .filter_map(|(validator, target)| {
+                let cap = *caps.get(validator)?;
+                (spread(*target) > cap).then_some((validator.as_str(), cap))
+            })
+            .collect::<Vec<_>>();
+        if newly_capped.is_empty() {
+            return targets
+                .iter()
+                .map(|(validator, target)| {
+                    let target = capped
+                        .get(validator.as_str())
+                        .copied()
+                        .unwrap_or_else(|| spread(*target));
+                    (validator.clone(), target)
+                })
+                .collect();
+        }
+        capped.extend(newly_capped);
+    }
+}
+
+#[test]
+fn test_compute_target_delegation() {
+    let total_delegated_amount = Uint128::new(1_000_000);
+
+    // weighed by mining power once there is any
+    assert_eq!(
+        compute_target_delegation(total_delegated_amount, Uint128::new(1), Uint128::new(4), 3)
+            .unwrap(),
+        Uint128::new(250_000)
+    );
+
+    // split evenly without, whatever power the validator may still be recorded with
+    for validator_mining_power in [0u128, 5] {
+        assert_eq!(
+            compute_target_delegation(
+                total_delegated_amount,
+                Uint128::new(validator_mining_power),
+                Uint128::zero(),
+                3
+            )
+            .unwrap(),
+            Uint128::new(333_333)
+        );
+    }
+    assert_eq!(
+        compute_target_delegation(total_delegated_amount, Uint128::zero(), Uint128::zero(), 0)
+            .unwrap(),
+        Uint128::zero()
+    );
+}
+
 #[test]
 fn test_compute_target_delegation_from_mining_power() {
     let total_delegated_amount = Uint128::from(1_000_000u128);
@@ -216,13 +407,6 @@ pub(crate) fn compute_redelegations_for_rebalancing(
 ) -> StdResult<Vec<Redelegation>> {
     let native_staked: u128 = current_delegations.iter().map(|d| d.amount).sum();
     let validator_count = validators_active.len() as u128;
-
-    // print native_staked divided by validator count
-    println!("native_staked = {}", native_staked);
-    println!("validator_count = {}", validator_count);
-    let native_per_validator = native_staked / validator_count;
-    println!("native_per_validator = {}", native_per_validator);
-
     let remainder = native_staked % validator_count;
 
     // If a validator's current delegated amount is greater than the target amount, native will be
@@ -232,9 +416,8 @@ pub(crate) fn compute_redelegations_for_rebalancing(
     let mut src_delegations: Vec<Delegation> = vec![];
     let mut dst_delegations: Vec<Delegation> = vec![];
     for (i, d) in current_delegations.iter().enumerate() {
-        let remainder_for_validator: u128 = u128::from((i + 1) as u128 <= remainder) as u128;
+        let remainder_for_validator: u128 = u128::from((i + 1) as u128 <= remainder);
         let native_for_validator = load_target_delegation(d)?.u128() + remainder_for_validator;
-        // eprintln!("{} amount ={} native={} min={}", d.validator, d.amount, native_for_validator, min_difference);
         match d.amount.cmp(&native_for_validator) {
             Ordering::Greater => {
                 if d.amount - native_for_validator > min_difference.u128() {
@@ -284,11 +467,89 @@ pub(crate) fn compute_redelegations_for_rebalancing(
             &src_delegation.denom,
         ));
     }
-    // eprintln!("new redelegations ={:?}", new_redelegations);
 
     Ok(new_redelegations)
 }
 
+//--------------------------------------------------------------------------------------------------
+// Principal/yield split logics
+//--------------------------------------------------------------------------------------------------
+
+/// Compute the amount of `usteak` currently worth `native_amount`. If nothing is bonded, we use
+/// 1 usteak = 1 native, same as when minting
+pub(crate) fn compute_usteak_for_native(
+    native_amount: Uint128,
+    usteak_supply: Uint128,
+    native_bonded: Uint128,
+    round_up: bool,
+) -> StdResult<Uint128> {
+    if native_bonded.is_zero() || usteak_supply.is_zero() {
+        return Ok(native_amount);
+    }
+    multiply_ratio_rounding(native_amount, usteak_supply, native_bonded, round_up)
+}
+
+/// Compute the amount of native `usteak_amount` is currently worth, rounding down. The inverse of
+/// `compute_usteak_for_native`
+pub(crate) fn compute_native_for_usteak(
+    usteak_amount: Uint128,
+    usteak_supply: Uint128,
+    native_bonded: Uint128,
+) -> Uint128 {
+    if native_bonded.is_zero() || usteak_supply.is_zero() {
+        return usteak_amount;
+    }
+    usteak_amount.multiply_ratio(native_bonded, usteak_supply)
+}
+
+/// Set aside for the yield shares any `usteak` held by the pool beyond what backs the principal at
+/// the current exchange rate and what is already reserved
+pub(crate) fn accrue_yield(
+    pool: &mut YieldPool,
+    usteak_supply: Uint128,
+    native_bonded: Uint128,
+) -> StdResult<()> {
+    if pool.shares.is_zero() {
+        return Ok(());
+    }
+    let backing = compute_usteak_for_native(pool.principal, usteak_supply, native_bonded, true)?;
+    let free = pool
+        .usteak
+        .saturating_sub(pool.reserved)
+        .saturating_sub(backing);
+    if free.is_zero() {
+        return Ok(());
+    }
+
+    // only what is fully reflected in the index is reserved, the rest is picked up next time
+    let index_increment = Decimal::from_ratio(free, pool.shares);
+    pool.reserved += index_increment.checked_mul_uint(pool.shares)?;
+    pool.index = pool.index.checked_add(index_increment)?;
+    Ok(())
+}
+
+/// Move the yield accrued by `position` since it was last settled into its pending amount. Shares
+/// issued in an epoch since retired only accrue up to `retired_index`, where that epoch ended, and
+/// are then dropped
+pub(crate) fn settle_yield_position(
+    pool: &YieldPool,
+    position: &mut YieldPosition,
+    retired_index: Option<Decimal>,
+) -> StdResult<()> {
+    let end_index = match retired_index {
+        Some(index) if position.epoch < pool.epoch => index,
+        _ => pool.index,
+    };
+    let accrued = (end_index - position.index).checked_mul_uint(position.shares)?;
+    position.pending += accrued;
+    position.index = pool.index;
+    if position.epoch < pool.epoch {
+        position.shares = Uint128::zero();
+        position.epoch = pool.epoch;
+    }
+    Ok(())
+}
+
 //--------------------------------------------------------------------------------------------------
 // Batch logics
 //--------------------------------------------------------------------------------------------------
@@ -305,10 +566,43 @@ pub(crate) fn reconcile_batches(batches: &mut [Batch], native_to_deduct: Uint128
     let remainder = native_to_deduct.u128() % batch_count;
 
     for (i, batch) in batches.iter_mut().enumerate() {
-        let remainder_for_batch: u128 = u128::from((i + 1) as u128 <= remainder) as u128;
+        let remainder_for_batch: u128 = u128::from((i + 1) as u128 <= remainder);
         let native_for_batch = native_per_batch + remainder_for_batch;
 
         batch.amount_unclaimed -= Uint128::new(native_for_batch);
         batch.reconciled = true;
     }
 }
+
+/// Split `native_lost` across validators in proportion to their `weights`, handing the rounding
+/// remainder out one unit at a time from the first validator so that the parts add up exactly.
+/// Validators without weight are left out
+pub(crate) fn compute_loss_attribution(
+    native_lost: Uint128,
+    weights: &[(String, Uint128)],
+) -> Vec<(String, Uint128)> {
+    let weights = weights
+        .iter()
+        .filter(|(_, weight)| !weight.is_zero())
+        .collect::<Vec<_>>();
+    let total_weight: Uint128 = weights.iter().map(|(_, weight)| *weight).sum();
+    if total_weight.is_zero() {
+        return vec![];
+    }
+
+    let mut losses = weights
+        .iter()
+        .map(|(validator, weight)| {
+            (
+                validator.clone(),
+                native_lost.multiply_ratio(*weight, total_weight),
+            )
+        })
+        .collect::<Vec<_>>();
+    let attributed: Uint128 = losses.iter().map(|(_, loss)| *loss).sum();
+    let remainder = (native_lost - attributed).u128() as usize;
+    for (_, loss) in losses.iter_mut().take(remainder) {
+        *loss += Uint128::new(1);
+    }
+    losses
+}
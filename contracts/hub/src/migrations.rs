@@ -1,14 +1,21 @@
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
 use crate::state::{State, BATCH_KEY_V101};
 use crate::types::BooleanKey;
-use cosmwasm_std::{Addr, Order, QuerierWrapper, StdError, StdResult, Storage, Uint128};
-use cw_storage_plus::{Index, IndexList, IndexedMap, MultiIndex};
-use pfc_steak::hub::Batch;
+use cosmwasm_std::{
+    Addr, Decimal, Env, Order, QuerierWrapper, Record, StdError, StdResult, Storage, Uint128,
+};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, MultiIndex};
+use pfc_steak::hub::{Batch, FeeType};
 
 use crate::helpers::get_denom_balance;
 use serde::{Deserialize, Serialize};
 
 const BATCH_KEY_V100: &str = "previous_batches";
 const BATCH_KEY_RECONCILED_V100: &str = "previous_batches__reconciled";
+const WORKERS_KEY_TERRA: &str = "workers";
+const DENOM_TERRA: &str = "uluna";
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct BatchV100 {
@@ -24,6 +31,21 @@ pub struct BatchV100 {
     pub est_unbond_end_time: u64,
 }
 
+/// Batch as stored by the Terra `steak-hub`, under the same keys as `BatchV100`
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct BatchTerra {
+    /// ID of this batch
+    pub id: u64,
+    /// Whether this batch has already been reconciled
+    pub reconciled: bool,
+    /// Total amount of shares remaining this batch. Each `usteak` burned = 1 share
+    pub total_shares: Uint128,
+    /// Amount of `uluna` in this batch that have not been claimed
+    pub uluna_unclaimed: Uint128,
+    /// Estimated time when this batch will finish unbonding
+    pub est_unbond_end_time: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct ConfigV100 {}
 
@@ -85,6 +107,117 @@ impl ConfigV100 {
     }
 }
 
+/// Hubs deployed from the Terra `steak-hub` codebase: batches denominated in uluna, harvests
+/// triggered by whitelisted workers, and neither fee nor miner state. Unbonding requests and the
+/// pending batch are stored in the same shape, so they carry over as they are
+pub struct LegacyTerraHub {
+    /// Number of batches converted to the current shape
+    pub batches_converted: u64,
+}
+
+impl LegacyTerraHub {
+    pub fn upgrade_stores(
+        storage: &mut dyn Storage,
+        querier: &QuerierWrapper,
+        env: &Env,
+    ) -> StdResult<Self> {
+        let state = State::default();
+
+        let old = terra_batches();
+        let old_batches = old
+            .range(storage, None, None, Order::Ascending)
+            .map(|item| {
+                let (_, v) = item?;
+                Ok(v)
+            })
+            .collect::<StdResult<Vec<BatchTerra>>>()?;
+        for v in &old_batches {
+            state.previous_batches.save(
+                storage,
+                v.id,
+                &Batch {
+                    id: v.id,
+                    reconciled: v.reconciled,
+                    total_shares: v.total_shares,
+                    amount_unclaimed: v.uluna_unclaimed,
+                    est_unbond_end_time: v.est_unbond_end_time,
+                },
+            )?;
+            old.remove(storage, v.id)?;
+        }
+
+        // fees start disabled and paid to the owner, until the owner configures them
+        let owner = state.owner.load(storage)?;
+        state.denom.save(storage, &DENOM_TERRA.to_string())?;
+        state.fee_account.save(storage, &owner)?;
+        state.fee_account_type.save(storage, &FeeType::Wallet)?;
+        state.max_fee_rate.save(storage, &Decimal::zero())?;
+        state.fee_rate.save(storage, &Decimal::zero())?;
+        state.prev_denom.save(
+            storage,
+            &get_denom_balance(
+                querier,
+                env.contract.address.clone(),
+                DENOM_TERRA.to_string(),
+            )?,
+        )?;
+
+        let validators = state.validators.load(storage)?;
+        state.validators_active.save(storage, &validators)?;
+
+        // workers were the accounts allowed to trigger harvests
+        let workers: Item<'_, Vec<Addr>> = Item::new(WORKERS_KEY_TERRA);
+        if let Some(harvesters) = workers.may_load(storage)? {
+            state.harvesters.save(storage, &harvesters)?;
+            workers.remove(storage);
+        }
+
+        // mining starts afresh, as on instantiation
+        state
+            .miner_entropy
+            .save(storage, &env.contract.address.to_string())?;
+        state
+            .miner_entropy_draft
+            .save(storage, &env.contract.address.to_string())?;
+        state.miner_difficulty.save(storage, &1u64.into())?;
+        state
+            .miner_last_mined_timestamp
+            .save(storage, &env.block.time.seconds().into())?;
+        state
+            .miner_last_mined_block
+            .save(storage, &env.block.height.into())?;
+        state.total_mining_power.save(storage, &Uint128::zero())?;
+
+        Ok(LegacyTerraHub {
+            batches_converted: old_batches.len() as u64,
+        })
+    }
+}
+
+pub(crate) fn terra_batches(
+) -> IndexedMap<'static, u64, BatchTerra, PreviousBatchesIndexesTerra<'static>> {
+    let pb_indexes_terra = PreviousBatchesIndexesTerra {
+        reconciled: MultiIndex::new(
+            |d: &BatchTerra| d.reconciled.into(),
+            BATCH_KEY_V100,
+            BATCH_KEY_RECONCILED_V100,
+        ),
+    };
+    IndexedMap::new(BATCH_KEY_V100, pb_indexes_terra)
+}
+
+pub(crate) struct PreviousBatchesIndexesTerra<'a> {
+    // pk goes to second tuple element
+    pub reconciled: MultiIndex<'a, BooleanKey, BatchTerra, Vec<u8>>,
+}
+
+impl<'a> IndexList<BatchTerra> for PreviousBatchesIndexesTerra<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<BatchTerra>> + '_> {
+        let v: Vec<&dyn Index<BatchTerra>> = vec![&self.reconciled];
+        Box::new(v.into_iter())
+    }
+}
+
 pub(crate) struct PreviousBatchesIndexesV100<'a> {
     // pk goes to second tuple element
     pub reconciled: MultiIndex<'a, BooleanKey, BatchV100, Vec<u8>>,
@@ -96,3 +229,77 @@ impl<'a> IndexList<BatchV100> for PreviousBatchesIndexesV100<'a> {
         Box::new(v.into_iter())
     }
 }
+
+/// Storage buffering writes in memory on top of the contract's storage, so a migration can be run
+/// against the live state and inspected without committing anything
+pub struct DryRunStorage<'a> {
+    inner: &'a dyn Storage,
+    /// Keys written by the migration, with `None` for the keys it removed
+    writes: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+impl<'a> DryRunStorage<'a> {
+    pub fn new(inner: &'a dyn Storage) -> Self {
+        DryRunStorage {
+            inner,
+            writes: BTreeMap::new(),
+        }
+    }
+
+    /// Number of keys set or removed through this storage
+    pub fn keys_written(&self) -> u64 {
+        self.writes.len() as u64
+    }
+}
+
+impl Storage for DryRunStorage<'_> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match self.writes.get(key) {
+            Some(value) => value.clone(),
+            None => self.inner.get(key),
+        }
+    }
+
+    fn range<'b>(
+        &'b self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Record> + 'b> {
+        if let (Some(start), Some(end)) = (start, end) {
+            if start >= end {
+                return Box::new(std::iter::empty());
+            }
+        }
+
+        let mut merged = self
+            .inner
+            .range(start, end, Order::Ascending)
+            .collect::<BTreeMap<_, _>>();
+        let lower = start.map_or(Bound::Unbounded, |key| Bound::Included(key.to_vec()));
+        let upper = end.map_or(Bound::Unbounded, |key| Bound::Excluded(key.to_vec()));
+        for (key, value) in self.writes.range((lower, upper)) {
+            match value {
+                Some(value) => {
+                    merged.insert(key.clone(), value.clone());
+                }
+                None => {
+                    merged.remove(key);
+                }
+            }
+        }
+
+        match order {
+            Order::Ascending => Box::new(merged.into_iter()),
+            Order::Descending => Box::new(merged.into_iter().rev()),
+        }
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.writes.insert(key.to_vec(), Some(value.to_vec()));
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.writes.insert(key.to_vec(), None);
+    }
+}
@@ -1,19 +1,63 @@
-use cosmwasm_std::{Addr, Decimal, Deps, Env, Order, StdResult, Uint128};
-use cw_storage_plus::{Bound, CwIntKey};
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{
+    Addr, Decimal, Deps, Env, Order, QuerierWrapper, StdResult, Storage, Uint128, Uint64,
+};
+use cw_storage_plus::{Bound, CwIntKey, PrimaryKey};
 
 use pfc_steak::hub::{
-    Batch, ConfigResponse, MinerParamsResponse, PendingBatch, StateResponse,
-    UnbondRequestsByBatchResponseItem, UnbondRequestsByUserResponseItem, ValidatorMiningPower,
+    AdminActionRecord, ArbStateResponse, AutomationResponse, AutomationTask, AutomationTaskInfo,
+    Batch, BatchFunder, BlockedValidator, ConfigResponse, ConsolidatedClaim,
+    ConsolidatedClaimResponse, ContractInfoResponse, CurrentMiner, DailySnapshot, DelegatedVote,
+    DelegationDriftResponse, DenomChangePreviewResponse, EmergencyUndelegationResponse,
+    EntropyContribution, EntropyContributorResponse, FeeCommitteeResponse, HaltRecovery,
+    InvariantsResponse, LiquidityDay, LiquidityScheduleResponse, LockStateResponse,
+    LockedBondResponseItem, MinerParamsResponse, MinerStatusResponse, MiningConfig,
+    MiningEmissionResponse, MiningSummary, OwnershipResponse, PendingBatchRequestResponse,
+    PendingBatchResponse, PendingReinvestResponse, PolStateResponse, RebasingBalanceResponse,
+    RebasingSupplyResponse, ReconcileBatchPreview, RewardDenomsResponse, RewardDripResponse,
+    RoundingPolicy, RoundingPolicyResponse, SafeMode, SimulateReconcileResponse, SlashingRecord,
+    StateResponse, StateResponseV2, SurplusResponse, SweptBatch, UnbondRequest,
+    UnbondRequestDetails, UnbondRequestsByBatchResponseItem, UnbondRequestsByUserResponseItem,
+    UnbondingDelegationsResponse, UnbondingEntry, UnbondingTotals, UndelegationSlotsResponse,
+    UserHistoryResponse, ValidatorMiningPower, ValidatorRewards, ValidatorScore,
+    ValidatorSetChange, ValidatorSlots, ValidatorUnbonding, ValidatorVotingPower,
+    VerifyProofResponse, VotingPowerCapResponse, YieldPoolResponse, YieldPositionResponse,
+};
+
+use crate::execute::{
+    apply_voting_power_caps, batch_submittable_at, compute_lock_boost, compute_miner_proof,
+    create_difficulty_prefix, load_total_lock_weight, load_yield_pool, load_yield_position,
+    mining_difficulty, next_proof_height, query_supply_and_bonded, query_usteak_supply,
+    reconcilable_batches, unreleased_rewards, voting_power_caps, DEFAULT_DRIFT_TOLERANCE_BPS,
+    MAX_BPS, MAX_UNBONDING_ENTRIES, SCORE_WINDOW_SECONDS, SECONDS_PER_DAY,
+    TARGET_MINING_DURATION_CEILING_SECONDS, TARGET_MINING_DURATION_FLOOR_SECONDS,
 };
+use pfc_steak::pair::{AssetInfo, PairQueryMsg, PoolResponse};
 
-use crate::helpers::{query_cw20_total_supply, query_delegations};
+use crate::helpers::{
+    query_chain_unbonding_completions, query_chain_unbondings, query_cw20_balance,
+    query_delegations,
+};
+use crate::math::{
+    compute_miner_share, compute_native_for_usteak, compute_target_delegation,
+    compute_validator_score, reconcile_batches,
+};
 use crate::state::State;
+use crate::types::STAKING_MODULE;
 
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
+const MAX_SCHEDULE_DAYS: u32 = 60;
 
-pub fn config(deps: Deps) -> StdResult<ConfigResponse> {
+pub fn config(deps: Deps, env: Env) -> StdResult<ConfigResponse> {
     let state = State::default();
+
+    let miner_fee_share = match state.mining_schedule.may_load(deps.storage)? {
+        Some(schedule) => compute_miner_share(&schedule, env.block.time.seconds()).1,
+        None => Decimal::one(),
+    };
+
     Ok(ConfigResponse {
         owner: state.owner.load(deps.storage)?.into(),
         new_owner: state
@@ -29,6 +73,57 @@ pub fn config(deps: Deps) -> StdResult<ConfigResponse> {
         fee_rate: state.fee_rate.load(deps.storage)?,
         max_fee_rate: state.max_fee_rate.load(deps.storage)?,
         validators: state.validators.load(deps.storage)?,
+        mining: MiningConfig {
+            entropy: state.miner_entropy.load(deps.storage)?,
+            entropy_draft: state.miner_entropy_draft.load(deps.storage)?,
+            difficulty: state.miner_difficulty.load(deps.storage)?,
+            proof_hash: state.proof_hash.may_load(deps.storage)?.unwrap_or_default(),
+            target_duration_floor: TARGET_MINING_DURATION_FLOOR_SECONDS,
+            target_duration_ceiling: TARGET_MINING_DURATION_CEILING_SECONDS,
+            total_mining_power: state
+                .total_mining_power
+                .may_load(deps.storage)?
+                .unwrap_or_default(),
+            miner_fee_share,
+            miner_fee_share_bps: state
+                .miner_fee_share_bps
+                .may_load(deps.storage)?
+                .unwrap_or(MAX_BPS),
+            miner: state
+                .miner
+                .may_load(deps.storage)?
+                .map(|current_miner| current_miner.miner.into()),
+        },
+        coordinator: state
+            .coordinator
+            .may_load(deps.storage)?
+            .map(|addr| addr.into()),
+        token_admin: state
+            .token_admin
+            .may_load(deps.storage)?
+            .map(|addr| addr.into()),
+        max_msgs_per_tx: state.max_msgs_per_tx.may_load(deps.storage)?,
+    })
+}
+
+pub fn ownership(deps: Deps) -> StdResult<OwnershipResponse> {
+    let state = State::default();
+
+    Ok(OwnershipResponse {
+        owner: state.owner.load(deps.storage)?.into(),
+        new_owner: state
+            .new_owner
+            .may_load(deps.storage)?
+            .map(|addr| addr.into()),
+        pending_denom: state.pending_denom.may_load(deps.storage)?,
+        committee_proposal: state.committee_proposal.may_load(deps.storage)?,
+        emergency_undelegation_ready_at: state.emergency_ready_at.may_load(deps.storage)?,
+        halt_recovery: state.halt_recovery.may_load(deps.storage)?,
+        guardian: state
+            .guardian
+            .may_load(deps.storage)?
+            .map(|addr| addr.into()),
+        guardian_change: state.guardian_change.may_load(deps.storage)?,
     })
 }
 
@@ -36,17 +131,19 @@ pub fn state(deps: Deps, env: Env) -> StdResult<StateResponse> {
     let state = State::default();
 
     let denom = state.denom.load(deps.storage)?;
-    let steak_token = state.steak_token.load(deps.storage)?;
-    let total_usteak = query_cw20_total_supply(&deps.querier, &steak_token)?;
+    let total_usteak = query_usteak_supply(deps.storage, &deps.querier, &env.contract.address)?;
 
     let validators = state.validators.load(deps.storage)?;
     let delegations = query_delegations(&deps.querier, &validators, &env.contract.address, &denom)?;
     let total_native: u128 = delegations.iter().map(|d| d.amount).sum();
+    // rewards still dripping are not reflected in the exchange rate yet
+    let native_released = Uint128::new(total_native)
+        .saturating_sub(unreleased_rewards(deps.storage, env.block.time.seconds())?);
 
     let exchange_rate = if total_usteak.is_zero() {
         Decimal::one()
     } else {
-        Decimal::from_ratio(total_native, total_usteak)
+        Decimal::from_ratio(native_released, total_usteak)
     };
 
     Ok(StateResponse {
@@ -57,9 +154,130 @@ pub fn state(deps: Deps, env: Env) -> StdResult<StateResponse> {
     })
 }
 
-pub fn pending_batch(deps: Deps) -> StdResult<PendingBatch> {
+pub fn state_v2(deps: Deps, env: Env) -> StdResult<StateResponseV2> {
+    let legacy = state(deps, env.clone())?;
+    let state = State::default();
+    let current_time = env.block.time.seconds();
+
+    let mut native_unbonding = Uint128::zero();
+    let mut native_claimable = Uint128::zero();
+    for item in state
+        .previous_batches
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (_, batch) = item?;
+        if current_time > batch.est_unbond_end_time {
+            native_claimable += batch.amount_unclaimed;
+        } else {
+            native_unbonding += batch.amount_unclaimed;
+        }
+    }
+    native_claimable += consolidated_total(deps.storage)?.native;
+
+    let denom = state.denom.load(deps.storage)?;
+    let native_balance = deps
+        .querier
+        .query_balance(&env.contract.address, &denom)?
+        .amount;
+    let available_liquidity =
+        native_balance.saturating_sub(native_claimable + native_reserved(deps.storage)?);
+
+    Ok(StateResponseV2 {
+        total_usteak: legacy.total_usteak,
+        total_native: legacy.total_native,
+        exchange_rate: legacy.exchange_rate,
+        unlocked_coins: legacy.unlocked_coins,
+        tvl: legacy.total_native + native_unbonding + native_balance,
+        available_liquidity,
+        unbonding: UnbondingTotals {
+            usteak_pending: state.pending_batch.load(deps.storage)?.usteak_to_burn,
+            native_unbonding,
+            native_claimable,
+        },
+        mining: MiningSummary {
+            total_mining_power: state
+                .total_mining_power
+                .may_load(deps.storage)?
+                .unwrap_or_default(),
+            current_miner: state.miner.may_load(deps.storage)?,
+            miner_fee_share_bps: state
+                .miner_fee_share_bps
+                .may_load(deps.storage)?
+                .unwrap_or(MAX_BPS),
+        },
+    })
+}
+
+/// Claims merged out of reconciled batches, awaiting withdrawal
+fn consolidated_total(storage: &dyn Storage) -> StdResult<ConsolidatedClaim> {
+    Ok(State::default()
+        .consolidated_total
+        .may_load(storage)?
+        .unwrap_or_default())
+}
+
+/// Native Token the hub holds on its own account: the lock boost, the reserves, escrowed fees, and
+/// the rebates and rewards awaiting reinvestment
+fn native_reserved(storage: &dyn Storage) -> StdResult<Uint128> {
+    let state = State::default();
+
+    Ok(state
+        .lock_boost_unclaimed
+        .may_load(storage)?
+        .unwrap_or_default()
+        + state
+            .pol_native_reserve
+            .may_load(storage)?
+            .unwrap_or_default()
+        + state.arb_reserve.may_load(storage)?.unwrap_or_default()
+        + state.escrowed_fees.may_load(storage)?.unwrap_or_default()
+        + state.pending_rebates.may_load(storage)?.unwrap_or_default()
+        + state
+            .pending_reinvest
+            .may_load(storage)?
+            .unwrap_or_default())
+}
+
+pub fn reward_drip(deps: Deps, env: Env) -> StdResult<RewardDripResponse> {
+    let state = State::default();
+
+    Ok(RewardDripResponse {
+        period: state.reward_drip_period.may_load(deps.storage)?,
+        drip: state.reward_drip.may_load(deps.storage)?,
+        unreleased: unreleased_rewards(deps.storage, env.block.time.seconds())?,
+    })
+}
+
+pub fn pending_reinvest(deps: Deps) -> StdResult<PendingReinvestResponse> {
+    let state = State::default();
+
+    Ok(PendingReinvestResponse {
+        min_reinvest_amount: state
+            .min_reinvest_amount
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        pending: state
+            .pending_reinvest
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+    })
+}
+
+pub fn pending_batch(deps: Deps) -> StdResult<PendingBatchResponse> {
     let state = State::default();
-    state.pending_batch.load(deps.storage)
+
+    let pending_batch = state.pending_batch.load(deps.storage)?;
+    let next_submittable_time = batch_submittable_at(deps.storage, &pending_batch)?;
+    Ok(PendingBatchResponse {
+        id: pending_batch.id,
+        usteak_to_burn: pending_batch.usteak_to_burn,
+        est_unbond_start_time: pending_batch.est_unbond_start_time,
+        next_submittable_time,
+        min_batch_size: state
+            .min_batch_size
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+    })
 }
 
 pub fn previous_batch(deps: Deps, id: u64) -> StdResult<Batch> {
@@ -88,6 +306,27 @@ pub fn previous_batches(
         .collect()
 }
 
+pub fn swept_batches(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<SweptBatch>> {
+    let state = State::default();
+
+    let start = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    state
+        .swept_batches
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
+
 pub fn unbond_requests_by_batch(
     deps: Deps,
     id: u64,
@@ -124,6 +363,85 @@ pub fn unbond_requests_by_user(
     start_after: Option<u64>,
     limit: Option<u32>,
 ) -> StdResult<Vec<UnbondRequestsByUserResponseItem>> {
+    Ok(user_unbond_requests(deps, user, start_after, limit)?
+        .into_iter()
+        .map(|request| request.into())
+        .collect())
+}
+
+pub fn unbond_requests_by_user_details(
+    deps: Deps,
+    env: Env,
+    user: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<UnbondRequestDetails>> {
+    let state = State::default();
+
+    let current_time = env.block.time.seconds();
+    let unbond_period = state.unbond_period.load(deps.storage)?;
+    let pending_batch = state.pending_batch.load(deps.storage)?;
+
+    let mut details = vec![];
+    for request in user_unbond_requests(deps, user, start_after, limit)? {
+        if request.id == pending_batch.id {
+            let (usteak_supply, native_bonded) =
+                query_supply_and_bonded(deps.storage, &deps.querier, &env.contract.address)?;
+            details.push(UnbondRequestDetails {
+                id: request.id,
+                shares: request.shares,
+                pending: true,
+                reconciled: false,
+                est_unbond_end_time: batch_submittable_at(deps.storage, &pending_batch)?
+                    + unbond_period,
+                claimable: false,
+                native_amount: compute_native_for_usteak(
+                    request.shares,
+                    usteak_supply,
+                    native_bonded,
+                ),
+                usteak_amount: Uint128::zero(),
+            });
+            continue;
+        }
+
+        // requests whose batch was pruned are worth nothing
+        let batch = match state.previous_batches.may_load(deps.storage, request.id)? {
+            Some(batch) => batch,
+            None => continue,
+        };
+        let usteak_amount = state
+            .swept_batches
+            .may_load(deps.storage, request.id)?
+            .map(|swept| {
+                swept
+                    .usteak_unclaimed
+                    .multiply_ratio(request.shares, batch.total_shares)
+            })
+            .unwrap_or_default();
+        details.push(UnbondRequestDetails {
+            id: request.id,
+            shares: request.shares,
+            pending: false,
+            reconciled: batch.reconciled,
+            est_unbond_end_time: batch.est_unbond_end_time,
+            claimable: batch.reconciled && batch.est_unbond_end_time < current_time,
+            native_amount: batch
+                .amount_unclaimed
+                .multiply_ratio(request.shares, batch.total_shares),
+            usteak_amount,
+        });
+    }
+
+    Ok(details)
+}
+
+fn user_unbond_requests(
+    deps: Deps,
+    user: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<UnbondRequest>> {
     let state = State::default();
 
     let start = start_after.map(|id| {
@@ -142,7 +460,104 @@ pub fn unbond_requests_by_user(
         .take(limit)
         .map(|item| {
             let (_, v) = item?;
-            Ok(v.into())
+            Ok(v)
+        })
+        .collect()
+}
+
+pub fn pending_batch_request(
+    deps: Deps,
+    env: Env,
+    user: String,
+) -> StdResult<PendingBatchRequestResponse> {
+    let state = State::default();
+
+    let user = deps.api.addr_validate(&user)?;
+    let pending_batch = state.pending_batch.load(deps.storage)?;
+    let shares = state
+        .unbond_requests
+        .may_load(deps.storage, (pending_batch.id, &user))?
+        .map(|request| request.shares)
+        .unwrap_or_default();
+    let est_native = if shares.is_zero() {
+        Uint128::zero()
+    } else {
+        let (usteak_supply, native_bonded) =
+            query_supply_and_bonded(deps.storage, &deps.querier, &env.contract.address)?;
+        compute_native_for_usteak(shares, usteak_supply, native_bonded)
+    };
+
+    Ok(PendingBatchRequestResponse {
+        id: pending_batch.id,
+        shares,
+        est_native,
+        est_unbond_start_time: batch_submittable_at(deps.storage, &pending_batch)?,
+    })
+}
+
+pub fn batch_funders(
+    deps: Deps,
+    id: u64,
+    start_after: Option<(String, String)>,
+    limit: Option<u32>,
+) -> StdResult<Vec<BatchFunder>> {
+    let state = State::default();
+
+    let start_after = start_after
+        .map(|(funder, receiver)| -> StdResult<_> {
+            Ok((
+                deps.api.addr_validate(&funder)?,
+                deps.api.addr_validate(&receiver)?,
+            ))
+        })
+        .transpose()?;
+    let start = start_after
+        .as_ref()
+        .map(|(funder, receiver)| Bound::exclusive((funder, receiver)));
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    state
+        .batch_funders
+        .sub_prefix(id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let ((funder, receiver), usteak) = item?;
+            Ok(BatchFunder {
+                funder: funder.to_string(),
+                receiver: receiver.to_string(),
+                usteak,
+            })
+        })
+        .collect()
+}
+
+pub fn unbond_requests_by_sender(
+    deps: Deps,
+    sender: String,
+    start_after: Option<(u64, String)>,
+    limit: Option<u32>,
+) -> StdResult<Vec<UnbondRequest>> {
+    let state = State::default();
+
+    let start = start_after
+        .map(|(id, user)| -> StdResult<_> {
+            let user = deps.api.addr_validate(&user)?;
+            Ok(Bound::exclusive((id, &user).joined_key()))
+        })
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    state
+        .unbond_requests
+        .idx
+        .sender
+        .prefix(sender)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
         })
         .collect()
 }
@@ -151,14 +566,109 @@ pub fn unbond_requests_by_user(
 pub fn miner_params(deps: Deps) -> StdResult<MinerParamsResponse> {
     let state = State::default();
     let entropy = state.miner_entropy.load(deps.storage)?;
-    let difficulty = state.miner_difficulty.load(deps.storage)?;
-    let entropy = entropy;
+    let difficulty = mining_difficulty(deps.storage)?;
     Ok(MinerParamsResponse {
         entropy,
         difficulty,
+        proof_hash: state.proof_hash.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+pub fn verify_proof(deps: Deps, miner: String, nonce: Uint64) -> StdResult<VerifyProofResponse> {
+    let state = State::default();
+
+    let miner = deps.api.addr_validate(&miner)?;
+    let entropy = state.miner_entropy.load(deps.storage)?;
+    let difficulty = mining_difficulty(deps.storage)?;
+
+    let proof_hash = state.proof_hash.may_load(deps.storage)?.unwrap_or_default();
+
+    let hash = compute_miner_proof(proof_hash, &entropy, miner.as_ref(), nonce)?;
+    let prefix = create_difficulty_prefix(difficulty);
+
+    Ok(VerifyProofResponse {
+        valid: hash.starts_with(&prefix),
+        hash,
+        difficulty,
+        prefix,
+    })
+}
+
+pub fn mining_emission(deps: Deps, env: Env) -> StdResult<MiningEmissionResponse> {
+    let state = State::default();
+
+    let schedule = state.mining_schedule.may_load(deps.storage)?;
+    let (epoch, miner_share) = match &schedule {
+        Some(schedule) => compute_miner_share(schedule, env.block.time.seconds()),
+        None => (0, Decimal::one()),
+    };
+
+    Ok(MiningEmissionResponse {
+        schedule,
+        epoch,
+        miner_share,
     })
 }
 
+pub fn miner_status(deps: Deps, miner: String) -> StdResult<MinerStatusResponse> {
+    let state = State::default();
+
+    let miner = deps.api.addr_validate(&miner)?;
+
+    Ok(MinerStatusResponse {
+        last_proof_height: state
+            .miner_last_proof_height
+            .may_load(deps.storage, &miner)?,
+        cooldown_blocks: state
+            .proof_cooldown_blocks
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        next_proof_height: next_proof_height(deps.storage, &miner)?,
+    })
+}
+
+pub fn current_miner(deps: Deps) -> StdResult<Option<CurrentMiner>> {
+    State::default().miner.may_load(deps.storage)
+}
+
+pub fn entropy_contributor(
+    deps: Deps,
+    contributor: String,
+) -> StdResult<EntropyContributorResponse> {
+    let state = State::default();
+
+    let contributor = deps.api.addr_validate(&contributor)?;
+
+    Ok(EntropyContributorResponse {
+        contributor: state
+            .entropy_contributors
+            .may_load(deps.storage, &contributor)?
+            .unwrap_or_default(),
+        rate_limit: state.entropy_rate_limit.may_load(deps.storage)?,
+    })
+}
+
+pub fn entropy_contributions(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<EntropyContribution>> {
+    let state = State::default();
+
+    let start = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    state
+        .entropy_contributions
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
+
 pub fn validator_mining_powers(
     deps: Deps,
     start_after: Option<String>,
@@ -182,3 +692,1199 @@ pub fn validator_mining_powers(
         })
         .collect()
 }
+
+/// Targets are the validators' shares of the mining power, as `Rebalance` uses, or an even split
+/// across the active validators until there is any mining power
+pub fn delegation_drift(deps: Deps, env: Env) -> StdResult<DelegationDriftResponse> {
+    let state = State::default();
+
+    let denom = state.denom.load(deps.storage)?;
+    let validators = state.validators.load(deps.storage)?;
+    let validators_active = state.validators_active.load(deps.storage)?;
+    let delegations = query_delegations(&deps.querier, &validators, &env.contract.address, &denom)?;
+    let total_delegated = Uint128::new(delegations.iter().map(|d| d.amount).sum());
+    let total_mining_power = state
+        .total_mining_power
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+
+    let targets = delegations
+        .iter()
+        .map(|d| {
+            let target = if !validators_active.contains(&d.validator) {
+                Uint128::zero()
+            } else {
+                let mining_power = state
+                    .validator_mining_powers
+                    .may_load(deps.storage, d.validator.clone())?
+                    .unwrap_or_default();
+                compute_target_delegation(
+                    total_delegated,
+                    mining_power.min(total_mining_power),
+                    total_mining_power,
+                    validators_active.len(),
+                )?
+            };
+            Ok((d.validator.clone(), target))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    let targets = apply_voting_power_caps(deps.storage, &delegations, targets)?;
+
+    let mut max_deviation = Uint128::zero();
+    let mut total_deviation = Uint128::zero();
+    for (d, (_, target)) in delegations.iter().zip(&targets) {
+        let deviation = Uint128::new(d.amount.abs_diff(target.u128()));
+        max_deviation = max_deviation.max(deviation);
+        total_deviation += deviation;
+    }
+
+    let to_bps = |amount: Uint128| {
+        if total_delegated.is_zero() {
+            0
+        } else {
+            amount.multiply_ratio(MAX_BPS, total_delegated).u128() as u64
+        }
+    };
+    let max_deviation_bps = to_bps(max_deviation);
+    let tolerance_bps = state
+        .drift_tolerance_bps
+        .may_load(deps.storage)?
+        .unwrap_or(DEFAULT_DRIFT_TOLERANCE_BPS);
+
+    Ok(DelegationDriftResponse {
+        max_deviation_bps,
+        total_deviation_bps: to_bps(total_deviation),
+        tolerance_bps,
+        rebalance_recommended: max_deviation_bps > u64::from(tolerance_bps),
+    })
+}
+
+pub fn voting_power_cap(deps: Deps, env: Env) -> StdResult<VotingPowerCapResponse> {
+    let state = State::default();
+
+    let denom = state.denom.load(deps.storage)?;
+    let validators = state.validators.load(deps.storage)?;
+    let delegations = query_delegations(&deps.querier, &validators, &env.contract.address, &denom)?;
+    let caps = voting_power_caps(deps.storage, &delegations)?.unwrap_or_default();
+
+    Ok(VotingPowerCapResponse {
+        max_share: state.voting_power_cap.may_load(deps.storage)?,
+        total_voting_power: state
+            .total_voting_power
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        refreshed_at: state.voting_power_refreshed_at.may_load(deps.storage)?,
+        validators: validators
+            .into_iter()
+            .map(|validator| {
+                Ok(ValidatorVotingPower {
+                    voting_power: state
+                        .validator_voting_powers
+                        .may_load(deps.storage, validator.clone())?
+                        .unwrap_or_default(),
+                    cap: caps.get(&validator).copied(),
+                    validator,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?,
+    })
+}
+
+pub fn validator_rewards(deps: Deps) -> StdResult<Vec<ValidatorRewards>> {
+    let state = State::default();
+
+    // validators that deposited rebates but were never harvested from still show up
+    let mut validators: Vec<String> = state
+        .validator_rewards
+        .keys(deps.storage, None, None, Order::Ascending)
+        .chain(
+            state
+                .validator_rebates
+                .keys(deps.storage, None, None, Order::Ascending),
+        )
+        .collect::<StdResult<_>>()?;
+    validators.sort();
+    validators.dedup();
+
+    validators
+        .into_iter()
+        .map(|validator| {
+            Ok(ValidatorRewards {
+                rewards: state
+                    .validator_rewards
+                    .may_load(deps.storage, validator.clone())?
+                    .unwrap_or_default(),
+                rebates: state
+                    .validator_rebates
+                    .may_load(deps.storage, validator.clone())?
+                    .unwrap_or_default(),
+                validator,
+            })
+        })
+        .collect()
+}
+
+pub fn validator_scores(deps: Deps, env: Env) -> StdResult<Vec<ValidatorScore>> {
+    let state = State::default();
+    let window_start = env
+        .block
+        .time
+        .seconds()
+        .saturating_sub(SCORE_WINDOW_SECONDS);
+
+    let performances = state
+        .validators
+        .load(deps.storage)?
+        .into_iter()
+        .map(|validator| {
+            let performance = state
+                .validator_performance
+                .may_load(deps.storage, validator.clone())?
+                .unwrap_or_default();
+            Ok((validator, performance))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    let best_reward_rate = performances
+        .iter()
+        .map(|(_, p)| p.reward_rate)
+        .max()
+        .unwrap_or_default();
+
+    Ok(performances
+        .into_iter()
+        .map(|(address, performance)| {
+            let pauses = performance
+                .pauses
+                .iter()
+                .filter(|t| **t >= window_start)
+                .count() as u32;
+            let slashes = performance
+                .slashes
+                .iter()
+                .filter(|t| **t >= window_start)
+                .count() as u32;
+            ValidatorScore {
+                address,
+                reward_rate: performance.reward_rate,
+                pauses,
+                slashes,
+                score: compute_validator_score(
+                    performance.reward_rate,
+                    best_reward_rate,
+                    pauses,
+                    slashes,
+                ),
+            }
+        })
+        .collect())
+}
+
+pub fn receive_paused_validators(deps: Deps) -> StdResult<Vec<String>> {
+    let state = State::default();
+    Ok(state
+        .receive_paused_validators
+        .may_load(deps.storage)?
+        .unwrap_or_default())
+}
+
+pub fn draining_validators(deps: Deps) -> StdResult<Vec<String>> {
+    let state = State::default();
+    Ok(state
+        .draining_validators
+        .may_load(deps.storage)?
+        .unwrap_or_default())
+}
+
+pub fn blocked_validators(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<BlockedValidator>> {
+    let state = State::default();
+
+    let start = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    state
+        .validator_blocklist
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (address, reason) = item?;
+            Ok(BlockedValidator { address, reason })
+        })
+        .collect()
+}
+
+pub fn admin_actions(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<AdminActionRecord>> {
+    let state = State::default();
+
+    let start = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    state
+        .admin_actions
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
+
+pub fn validator_set_changes(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<ValidatorSetChange>> {
+    let state = State::default();
+
+    let start = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    state
+        .validator_set_changes
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
+
+pub fn lock_state(deps: Deps) -> StdResult<LockStateResponse> {
+    let state = State::default();
+    Ok(LockStateResponse {
+        boost_rate: state
+            .lock_boost_rate
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        total_locked_shares: state
+            .total_locked_shares
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        total_lock_weight: load_total_lock_weight(deps.storage)?,
+        boost_index: state
+            .lock_boost_index
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        boost_unclaimed: state
+            .lock_boost_unclaimed
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        tiers: state.lock_tiers.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+pub fn locked_bonds(
+    deps: Deps,
+    user: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<LockedBondResponseItem>> {
+    let state = State::default();
+
+    let user = deps.api.addr_validate(&user)?;
+    let boost_index = state
+        .lock_boost_index
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let start = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    state
+        .locked_bonds
+        .prefix(&user)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, lock) = item?;
+            Ok(LockedBondResponseItem {
+                id: lock.id,
+                shares: lock.shares,
+                unlock_time: lock.unlock_time,
+                multiplier: lock.multiplier,
+                boost_claimable: compute_lock_boost(boost_index, &lock)?,
+            })
+        })
+        .collect()
+}
+
+pub fn yield_pool(deps: Deps, env: Env) -> StdResult<YieldPoolResponse> {
+    let state = State::default();
+
+    let principal_token = state.principal_token.may_load(deps.storage)?;
+    let pool = if principal_token.is_some() {
+        load_yield_pool(deps.storage, &deps.querier, &env.contract.address)?.0
+    } else {
+        Default::default()
+    };
+
+    Ok(YieldPoolResponse {
+        principal_token: principal_token.map(|addr| addr.into()),
+        pool,
+    })
+}
+
+pub fn yield_position(deps: Deps, env: Env, user: String) -> StdResult<YieldPositionResponse> {
+    let state = State::default();
+
+    let user = deps.api.addr_validate(&user)?;
+    let position = match state.yield_positions.may_load(deps.storage, &user)? {
+        Some(position) if !position.shares.is_zero() => {
+            let (pool, _, _) = load_yield_pool(deps.storage, &deps.querier, &env.contract.address)?;
+            load_yield_position(deps.storage, &pool, &user)?.unwrap_or(position)
+        }
+        position => position.unwrap_or_default(),
+    };
+
+    Ok(YieldPositionResponse {
+        shares: position.shares,
+        claimable: position.pending,
+    })
+}
+
+pub fn rebasing_balance(deps: Deps, env: Env, user: String) -> StdResult<RebasingBalanceResponse> {
+    let state = State::default();
+
+    let user = deps.api.addr_validate(&user)?;
+    let shares = state
+        .rebasing_shares
+        .may_load(deps.storage, &user)?
+        .unwrap_or_default();
+    let (usteak_supply, native_bonded) =
+        query_supply_and_bonded(deps.storage, &deps.querier, &env.contract.address)?;
+
+    Ok(RebasingBalanceResponse {
+        shares,
+        balance: compute_native_for_usteak(shares, usteak_supply, native_bonded),
+    })
+}
+
+pub fn rebasing_supply(deps: Deps, env: Env) -> StdResult<RebasingSupplyResponse> {
+    let state = State::default();
+
+    let total_shares = state
+        .total_rebasing_shares
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let (usteak_supply, native_bonded) =
+        query_supply_and_bonded(deps.storage, &deps.querier, &env.contract.address)?;
+
+    Ok(RebasingSupplyResponse {
+        total_shares,
+        total_supply: compute_native_for_usteak(total_shares, usteak_supply, native_bonded),
+    })
+}
+
+pub fn pol_state(deps: Deps, env: Env) -> StdResult<PolStateResponse> {
+    let state = State::default();
+
+    let pair = state.pol_pair.may_load(deps.storage)?;
+    let lp_token = state.pol_lp_token.may_load(deps.storage)?;
+    let native_reserve = state
+        .pol_native_reserve
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let usteak_reserve = state
+        .pol_usteak_reserve
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+
+    let mut lp_balance = Uint128::zero();
+    let mut position_native = Uint128::zero();
+    let mut position_usteak = Uint128::zero();
+    let mut position_value = Uint128::zero();
+    if let (Some(pair), Some(lp_token)) = (&pair, &lp_token) {
+        lp_balance = query_cw20_balance(&deps.querier, lp_token, &env.contract.address)?;
+        if !lp_balance.is_zero() {
+            let pool: PoolResponse = deps
+                .querier
+                .query_wasm_smart(pair, &PairQueryMsg::Pool {})?;
+            for asset in &pool.assets {
+                let amount = asset.amount.multiply_ratio(lp_balance, pool.total_share);
+                match &asset.info {
+                    AssetInfo::Token { .. } => position_usteak += amount,
+                    AssetInfo::NativeToken { .. } => position_native += amount,
+                }
+            }
+
+            let (usteak_supply, native_bonded) =
+                query_supply_and_bonded(deps.storage, &deps.querier, &env.contract.address)?;
+            position_value = position_native
+                + compute_native_for_usteak(position_usteak, usteak_supply, native_bonded);
+        }
+    }
+
+    Ok(PolStateResponse {
+        pair: pair.map(|addr| addr.into()),
+        lp_token: lp_token.map(|addr| addr.into()),
+        fee_rate: state
+            .pol_fee_rate
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        native_reserve,
+        usteak_reserve,
+        lp_balance,
+        position_native,
+        position_usteak,
+        position_value,
+    })
+}
+
+pub fn arb_state(deps: Deps) -> StdResult<ArbStateResponse> {
+    let state = State::default();
+
+    Ok(ArbStateResponse {
+        min_discount: state
+            .arb_min_discount
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        max_per_execution: state
+            .arb_max_per_execution
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        max_in_flight: state
+            .arb_max_in_flight
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        reserve: state
+            .arb_reserve
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        in_flight_cost: state
+            .arb_in_flight_cost
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+        in_flight_shares: state
+            .arb_in_flight_shares
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+    })
+}
+
+pub fn user_history(
+    deps: Deps,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<UserHistoryResponse> {
+    let state = State::default();
+
+    let user = deps.api.addr_validate(&address)?;
+    let start = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let entries = state
+        .user_history
+        .prefix(&user)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, entry) = item?;
+            Ok(entry)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(UserHistoryResponse {
+        totals: state
+            .user_totals
+            .may_load(deps.storage, &user)?
+            .unwrap_or_default(),
+        entries,
+    })
+}
+
+pub fn daily_snapshots(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<DailySnapshot>> {
+    let state = State::default();
+
+    let start = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    state
+        .daily_snapshots
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
+
+/// NOTE: the staking module does not expose unbonding entries to contracts, so batches that are still
+/// unbonding can not be checked against what is actually unbonding. Only batches that have finished
+/// unbonding are checked against the balance; if they have not been reconciled yet, invoke
+/// `ExecuteMsg::Reconcile` first.
+pub fn check_invariants(deps: Deps, env: Env) -> StdResult<InvariantsResponse> {
+    let state = State::default();
+    let mut violations = vec![];
+
+    let denom = state.denom.load(deps.storage)?;
+    let steak_token = state.steak_token.load(deps.storage)?;
+    let current_time = env.block.time.seconds();
+
+    // Native owed to withdrawers, lockers and the reserves must be held by the hub
+    let batches = state
+        .previous_batches
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    for batch in &batches {
+        if batch.total_shares.is_zero() && !batch.amount_unclaimed.is_zero() {
+            violations.push(format!(
+                "batch {} has {} unclaimed but no shares",
+                batch.id, batch.amount_unclaimed
+            ));
+        }
+    }
+    let consolidated = consolidated_total(deps.storage)?;
+    let native_owed_to_batches: Uint128 = batches
+        .iter()
+        .filter(|b| current_time > b.est_unbond_end_time)
+        .map(|b| b.amount_unclaimed)
+        .sum::<Uint128>()
+        + consolidated.native;
+    let native_reserved = native_reserved(deps.storage)?;
+    let native_balance = deps
+        .querier
+        .query_balance(&env.contract.address, &denom)?
+        .amount;
+    if native_owed_to_batches + native_reserved > native_balance {
+        violations.push(format!(
+            "native liabilities {} exceed balance {}",
+            native_owed_to_batches + native_reserved,
+            native_balance
+        ));
+    }
+
+    // Steak held on behalf of users must be held by the hub
+    let pending_batch = state.pending_batch.load(deps.storage)?;
+    let yield_pool = state.yield_pool.may_load(deps.storage)?.unwrap_or_default();
+    let usteak_held = pending_batch.usteak_to_burn
+        + state
+            .total_locked_shares
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+        + yield_pool.usteak
+        + state
+            .total_rebasing_shares
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+        + state
+            .pol_usteak_reserve
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+        + state
+            .swept_batches
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| Ok(item?.1.usteak_unclaimed))
+            .collect::<StdResult<Vec<_>>>()?
+            .into_iter()
+            .sum::<Uint128>()
+        + consolidated.usteak;
+    let usteak_balance = query_cw20_balance(&deps.querier, &steak_token, &env.contract.address)?;
+    if usteak_held > usteak_balance {
+        violations.push(format!(
+            "usteak liabilities {} exceed balance {}",
+            usteak_held, usteak_balance
+        ));
+    }
+    if yield_pool.reserved > yield_pool.usteak {
+        violations.push(format!(
+            "reserved yield {} exceeds pooled usteak {}",
+            yield_pool.reserved, yield_pool.usteak
+        ));
+    }
+
+    // Outstanding Steak must be backed by delegations
+    let (usteak_supply, native_bonded) =
+        query_supply_and_bonded(deps.storage, &deps.querier, &env.contract.address)?;
+    if !usteak_supply.is_zero() && native_bonded.is_zero() {
+        violations.push(format!(
+            "usteak supply {} is not backed by any delegation",
+            usteak_supply
+        ));
+    }
+
+    Ok(InvariantsResponse {
+        healthy: violations.is_empty(),
+        violations,
+    })
+}
+
+pub fn rounding_policy(deps: Deps) -> StdResult<RoundingPolicyResponse> {
+    let state = State::default();
+
+    let policy = state
+        .rounding_policy
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let favors_user = policy == RoundingPolicy::FavorUser;
+
+    Ok(RoundingPolicyResponse {
+        policy,
+        mint_rounds_up: favors_user,
+        unbond_rounds_up: favors_user,
+    })
+}
+
+pub fn denom_change_preview(deps: Deps, env: Env) -> StdResult<DenomChangePreviewResponse> {
+    let state = State::default();
+
+    Ok(DenomChangePreviewResponse {
+        current_denom: state.denom.load(deps.storage)?,
+        pending_denom: state.pending_denom.may_load(deps.storage)?,
+        blockers: denom_change_blockers(deps, &env)?,
+    })
+}
+
+/// Everything still outstanding in the current denom that prevents it from being changed
+pub(crate) fn denom_change_blockers(deps: Deps, env: &Env) -> StdResult<Vec<String>> {
+    let state = State::default();
+    let mut blockers = vec![];
+
+    let denom = state.denom.load(deps.storage)?;
+    let validators = state.validators.load(deps.storage)?;
+
+    let delegated: u128 =
+        query_delegations(&deps.querier, &validators, &env.contract.address, &denom)?
+            .iter()
+            .map(|d| d.amount)
+            .sum();
+    if delegated > 0 {
+        blockers.push(format!("{}{} is still delegated", delegated, denom));
+    }
+
+    let usteak_supply = query_usteak_supply(deps.storage, &deps.querier, &env.contract.address)?;
+    if !usteak_supply.is_zero() {
+        blockers.push(format!("{} usteak is still outstanding", usteak_supply));
+    }
+
+    let pending_batch = state.pending_batch.load(deps.storage)?;
+    if !pending_batch.usteak_to_burn.is_zero() {
+        blockers.push(format!(
+            "{} usteak is queued for unbonding",
+            pending_batch.usteak_to_burn
+        ));
+    }
+
+    let unclaimed: Uint128 = state
+        .previous_batches
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v.amount_unclaimed)
+        })
+        .collect::<StdResult<Vec<_>>>()?
+        .into_iter()
+        .sum::<Uint128>()
+        + consolidated_total(deps.storage)?.native;
+    if !unclaimed.is_zero() {
+        blockers.push(format!(
+            "{}{} is unclaimed in unbonding batches",
+            unclaimed, denom
+        ));
+    }
+
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, &denom)?
+        .amount;
+    if !balance.is_zero() {
+        blockers.push(format!("{}{} is held by the hub", balance, denom));
+    }
+
+    Ok(blockers)
+}
+
+pub fn emergency_undelegation(deps: Deps) -> StdResult<EmergencyUndelegationResponse> {
+    let state = State::default();
+
+    Ok(EmergencyUndelegationResponse {
+        ready_at: state.emergency_ready_at.may_load(deps.storage)?,
+        batch_id: state.emergency_batch.may_load(deps.storage)?,
+    })
+}
+
+pub fn halt_recovery(deps: Deps) -> StdResult<Option<HaltRecovery>> {
+    let state = State::default();
+    state.halt_recovery.may_load(deps.storage)
+}
+
+pub fn safe_mode(deps: Deps, env: Env) -> StdResult<Option<SafeMode>> {
+    let state = State::default();
+    Ok(state
+        .safe_mode
+        .may_load(deps.storage)?
+        .filter(|safe_mode| safe_mode.is_active(env.block.height, env.block.time.seconds())))
+}
+
+pub fn fee_committee(deps: Deps) -> StdResult<Option<FeeCommitteeResponse>> {
+    let state = State::default();
+
+    let committee = match state.fee_committee.may_load(deps.storage)? {
+        Some(committee) => committee,
+        None => return Ok(None),
+    };
+    Ok(Some(FeeCommitteeResponse {
+        members: committee.members.iter().map(|m| m.to_string()).collect(),
+        threshold: committee.threshold,
+        proposal: state.committee_proposal.may_load(deps.storage)?,
+    }))
+}
+
+pub fn balance_hooks(deps: Deps) -> StdResult<Vec<String>> {
+    let state = State::default();
+
+    Ok(state
+        .balance_hooks
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|hook| hook.to_string())
+        .collect())
+}
+
+/// Batches still unbonding bring their `amount_unclaimed` in when they mature, at which point it
+/// becomes claimable, so it shows up as both an inflow and an outflow of the same day. What is
+/// claimable but unclaimed today is the hub's immediate liability
+pub fn liquidity_schedule(deps: Deps, env: Env, days: u32) -> StdResult<LiquidityScheduleResponse> {
+    let state = State::default();
+    let now = env.block.time.seconds();
+    let days = days.min(MAX_SCHEDULE_DAYS) as u64;
+
+    let mut claimable = consolidated_total(deps.storage)?.native;
+    let mut schedule: Vec<LiquidityDay> = (0..days)
+        .map(|i| LiquidityDay {
+            start_time: now + i * SECONDS_PER_DAY,
+            end_time: now + (i + 1) * SECONDS_PER_DAY,
+            inflow: Uint128::zero(),
+            outflow: Uint128::zero(),
+        })
+        .collect();
+
+    for item in state
+        .previous_batches
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (_, batch) = item?;
+        if batch.est_unbond_end_time < now {
+            claimable += batch.amount_unclaimed;
+            continue;
+        }
+        let day = ((batch.est_unbond_end_time - now) / SECONDS_PER_DAY) as usize;
+        if let Some(day) = schedule.get_mut(day) {
+            day.inflow += batch.amount_unclaimed;
+            day.outflow += batch.amount_unclaimed;
+        }
+    }
+
+    Ok(LiquidityScheduleResponse {
+        claimable,
+        days: schedule,
+    })
+}
+
+pub fn simulate_reconcile(deps: Deps, env: Env) -> StdResult<SimulateReconcileResponse> {
+    let (batches, native_expected, native_actual) =
+        reconcilable_batches(deps.storage, &deps.querier, &env)?;
+
+    let native_to_deduct = native_expected.saturating_sub(native_actual);
+    let mut reconciled = batches.clone();
+    // there is nothing to deduct the shortfall from without batches to reconcile
+    if !native_to_deduct.is_zero() && !reconciled.is_empty() {
+        reconcile_batches(&mut reconciled, native_to_deduct);
+    }
+
+    Ok(SimulateReconcileResponse {
+        native_expected,
+        native_actual,
+        native_to_deduct,
+        batches: batches
+            .iter()
+            .zip(&reconciled)
+            .map(|(before, after)| ReconcileBatchPreview {
+                id: before.id,
+                amount_unclaimed: before.amount_unclaimed,
+                deduction: before.amount_unclaimed - after.amount_unclaimed,
+            })
+            .collect(),
+    })
+}
+
+pub fn slashing_records(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<SlashingRecord>> {
+    let state = State::default();
+
+    let start = start_after.map(Bound::exclusive);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    state
+        .slashing_records
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
+
+pub fn unbonding_delegations(deps: Deps, env: Env) -> StdResult<UnbondingDelegationsResponse> {
+    let state = State::default();
+
+    let mut validators: BTreeMap<String, ValidatorUnbonding> = BTreeMap::new();
+    let undelegations = state
+        .batch_undelegations
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for ((batch_id, validator), amount) in undelegations {
+        let batch = state.previous_batches.load(deps.storage, batch_id)?;
+        let unbonding = validators
+            .entry(validator.clone())
+            .or_insert_with(|| empty_unbonding(validator));
+        unbonding.amount += amount;
+        unbonding.entries.push(UnbondingEntry {
+            batch_id,
+            amount,
+            completion_time: batch.est_unbond_end_time,
+        });
+    }
+
+    let chain_unbondings = query_chain_unbondings(&deps.querier, &env.contract.address).ok();
+    let chain_queried = chain_unbondings.is_some();
+    for unbonding in validators.values_mut() {
+        unbonding
+            .entries
+            .sort_by_key(|e| (e.completion_time, e.batch_id));
+        if chain_queried {
+            unbonding.chain_amount = Some(Uint128::zero());
+            unbonding.chain_entries = Some(0);
+        }
+    }
+    for (validator, amount, entries) in chain_unbondings.unwrap_or_default() {
+        let unbonding = validators
+            .entry(validator.clone())
+            .or_insert_with(|| empty_unbonding(validator));
+        unbonding.chain_amount = Some(amount);
+        unbonding.chain_entries = Some(entries);
+    }
+
+    Ok(UnbondingDelegationsResponse {
+        validators: validators.into_values().collect(),
+        chain_queried,
+    })
+}
+
+pub fn undelegation_slots(deps: Deps, env: Env) -> StdResult<UndelegationSlotsResponse> {
+    let state = State::default();
+    let now = env.block.time.seconds();
+
+    // completion times of the entries the hub's batch records account for, by validator
+    let mut recorded: BTreeMap<String, Vec<u64>> = state
+        .validators
+        .load(deps.storage)?
+        .into_iter()
+        .map(|validator| (validator, vec![]))
+        .collect();
+    for item in state
+        .batch_undelegations
+        .keys(deps.storage, None, None, Order::Ascending)
+    {
+        let (batch_id, validator) = item?;
+        let batch = state.previous_batches.load(deps.storage, batch_id)?;
+        let completions = recorded.entry(validator).or_default();
+        if batch.est_unbond_end_time > now {
+            completions.push(batch.est_unbond_end_time);
+        }
+    }
+
+    // where the chain serves its unbonding entries, they are authoritative; the batch records only
+    // stand in for the completion times it leaves out
+    let chain_completions =
+        query_chain_unbonding_completions(&deps.querier, &env.contract.address).ok();
+    let chain_queried = chain_completions.is_some();
+    let mut chain: BTreeMap<String, Vec<Option<u64>>> =
+        chain_completions.unwrap_or_default().into_iter().collect();
+    for validator in chain.keys() {
+        recorded.entry(validator.clone()).or_default();
+    }
+
+    let validators = recorded
+        .into_iter()
+        .map(|(validator, completions)| {
+            let (occupied, next_free_at) = if chain_queried {
+                let entries = chain.remove(&validator).unwrap_or_default();
+                let entries = entries
+                    .into_iter()
+                    .filter(|completion| completion.map_or(true, |t| t > now))
+                    .collect::<Vec<_>>();
+                let next = entries
+                    .iter()
+                    .flatten()
+                    .min()
+                    .or_else(|| completions.iter().min().filter(|_| !entries.is_empty()));
+                (entries.len() as u32, next.copied())
+            } else {
+                (completions.len() as u32, completions.iter().min().copied())
+            };
+            ValidatorSlots {
+                validator,
+                occupied,
+                available: MAX_UNBONDING_ENTRIES.saturating_sub(occupied),
+                next_free_at,
+            }
+        })
+        .collect();
+
+    Ok(UndelegationSlotsResponse {
+        max_entries: MAX_UNBONDING_ENTRIES,
+        chain_queried,
+        validators,
+    })
+}
+
+fn empty_unbonding(validator: String) -> ValidatorUnbonding {
+    ValidatorUnbonding {
+        validator,
+        amount: Uint128::zero(),
+        entries: vec![],
+        chain_amount: None,
+        chain_entries: None,
+    }
+}
+
+pub fn integrators(deps: Deps) -> StdResult<Vec<String>> {
+    let state = State::default();
+
+    Ok(state
+        .integrators
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|integrator| integrator.to_string())
+        .collect())
+}
+
+pub fn vote_delegates(deps: Deps) -> StdResult<Vec<String>> {
+    let state = State::default();
+
+    Ok(state
+        .vote_delegates
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|delegate| delegate.to_string())
+        .collect())
+}
+
+pub fn delegated_vote(deps: Deps, proposal_id: u64) -> StdResult<Option<DelegatedVote>> {
+    let state = State::default();
+
+    state.delegated_votes.may_load(deps.storage, proposal_id)
+}
+
+pub fn harvesters(deps: Deps) -> StdResult<Vec<String>> {
+    let state = State::default();
+
+    Ok(state
+        .harvesters
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|harvester| harvester.to_string())
+        .collect())
+}
+
+pub fn automation(deps: Deps) -> StdResult<AutomationResponse> {
+    let state = State::default();
+
+    let mut tasks = vec![];
+    for task in [
+        AutomationTask::Harvest,
+        AutomationTask::SubmitBatch,
+        AutomationTask::Reconcile,
+    ] {
+        if let Some(task_hash) = state
+            .automation_tasks
+            .may_load(deps.storage, task.to_string())?
+        {
+            tasks.push(AutomationTaskInfo { task, task_hash });
+        }
+    }
+
+    Ok(AutomationResponse {
+        contract: state
+            .automation_contract
+            .may_load(deps.storage)?
+            .map(|c| c.to_string()),
+        tasks,
+    })
+}
+
+pub fn contract_info() -> ContractInfoResponse {
+    ContractInfoResponse {
+        name: env!("CARGO_PKG_NAME").to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        commit: option_env!("GIT_COMMIT_HASH").map(String::from),
+        token_mode: "cw20".to_string(),
+        chain_adapter: STAKING_MODULE.to_string(),
+    }
+}
+
+pub fn consolidated_claim(deps: Deps, user: String) -> StdResult<ConsolidatedClaimResponse> {
+    let state = State::default();
+
+    let user = deps.api.addr_validate(&user)?;
+    let outstanding_requests = state
+        .unbond_requests
+        .idx
+        .user
+        .prefix(user.to_string())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .count() as u32;
+    Ok(ConsolidatedClaimResponse {
+        max_unbond_requests: state.max_unbond_requests.may_load(deps.storage)?,
+        outstanding_requests,
+        claim: state
+            .consolidated_claims
+            .may_load(deps.storage, &user)?
+            .unwrap_or_default(),
+    })
+}
+
+pub fn refundable_denoms(deps: Deps) -> StdResult<Vec<String>> {
+    let state = State::default();
+
+    Ok(state
+        .refundable_denoms
+        .may_load(deps.storage)?
+        .unwrap_or_default())
+}
+
+pub fn surplus(deps: Deps, env: Env) -> StdResult<SurplusResponse> {
+    let state = State::default();
+
+    let denom = state.denom.load(deps.storage)?;
+    let native_balance = deps
+        .querier
+        .query_balance(&env.contract.address, &denom)?
+        .amount;
+    let native_liabilities =
+        native_liabilities(deps.storage, &deps.querier, &env.contract.address)?;
+    Ok(SurplusResponse {
+        native_balance,
+        native_liabilities,
+        surplus: native_balance.saturating_sub(native_liabilities),
+        policy: state
+            .surplus_policy
+            .may_load(deps.storage)?
+            .unwrap_or_default(),
+    })
+}
+
+/// Native the hub holds on someone's behalf: what batches owe their withdrawers, consolidated
+/// claims, the reserves, and rewards that have been withdrawn but not reinvested yet.
+///
+/// A batch's estimated end time can fall after the chain has paid it out, e.g. once a halt recovery
+/// pushed it back or the configured unbond period is longer than the chain's, so every batch is
+/// counted in full, less what the chain still reports as unbonding on its behalf: from each
+/// validator, at most what the batches recorded undelegating from it. Undelegations made for
+/// anything else are never netted off. Where the chain does not serve that query, nothing is
+/// netted off
+pub(crate) fn native_liabilities(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper,
+    contract_addr: &Addr,
+) -> StdResult<Uint128> {
+    let state = State::default();
+
+    let denom = state.denom.load(storage)?;
+    let owed_to_batches = state
+        .previous_batches
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| Ok(item?.1.amount_unclaimed))
+        .collect::<StdResult<Vec<_>>>()?
+        .into_iter()
+        .sum::<Uint128>();
+    let owed_to_held = state
+        .emergency_held
+        .may_load(storage)?
+        .unwrap_or_default()
+        .native;
+    let mut undelegated_for_batches: BTreeMap<String, Uint128> = BTreeMap::new();
+    for item in state
+        .batch_undelegations
+        .range(storage, None, None, Order::Ascending)
+    {
+        let ((_, validator), amount) = item?;
+        *undelegated_for_batches.entry(validator).or_default() += amount;
+    }
+    let still_unbonding = query_chain_unbondings(querier, contract_addr)
+        .map(|unbondings| {
+            unbondings
+                .into_iter()
+                .map(|(validator, amount, _)| {
+                    amount.min(
+                        undelegated_for_batches
+                            .get(&validator)
+                            .copied()
+                            .unwrap_or_default(),
+                    )
+                })
+                .sum()
+        })
+        .unwrap_or_default();
+    let unlocked = state
+        .unlocked_coins
+        .load(storage)?
+        .iter()
+        .filter(|coin| coin.denom == denom)
+        .map(|coin| coin.amount)
+        .sum::<Uint128>();
+
+    Ok(
+        (owed_to_batches + owed_to_held).saturating_sub(still_unbonding)
+            + consolidated_total(storage)?.native
+            + native_reserved(storage)?
+            + unlocked,
+    )
+}
+
+pub fn zapper(deps: Deps) -> StdResult<Option<String>> {
+    let state = State::default();
+
+    Ok(state.zapper.may_load(deps.storage)?.map(String::from))
+}
+
+pub fn reward_denoms(deps: Deps) -> StdResult<RewardDenomsResponse> {
+    let state = State::default();
+    let reward_denoms = match state.reward_denoms.may_load(deps.storage)? {
+        Some(reward_denoms) => reward_denoms,
+        None => vec![state.denom.load(deps.storage)?],
+    };
+    Ok(RewardDenomsResponse {
+        reward_denoms,
+        converter: state
+            .reward_converter
+            .may_load(deps.storage)?
+            .map(|c| c.to_string()),
+    })
+}
@@ -1,7 +1,16 @@
-use cosmwasm_std::{Addr, Coin, Decimal, StdError, StdResult, Storage, Uint128, Uint64};
+use cosmwasm_std::{Addr, BlockInfo, Coin, Decimal, Decimal256, Storage, Uint128, Uint64};
 use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 
-use pfc_steak::hub::{Batch, FeeType, PendingBatch, UnbondRequest};
+use crate::error::ContractError;
+use pfc_steak::hub::{
+    AdminActionRecord, AutomationTask, Batch, CommitteeProposal, ConsolidatedClaim, CurrentMiner,
+    DailySnapshot, DelegatedVote, EmergencyHeld, EntropyContribution, EntropyContributor,
+    EntropyRateLimit, ExchangeRateSnapshot, FeeCommittee, FeeType, GuardianChange, HaltRecovery,
+    IdleRedelegation, LockTier, LockedBond, Metrics, MiningSchedule, PendingBatch, ProofHash,
+    RewardDrip, RoundingPolicy, SafeMode, SlashingRecord, SurplusPolicy, SweptBatch, UnbondRequest,
+    UserHistoryEntry, UserTotals, ValidatorPerformance, ValidatorSetChange, YieldPool,
+    YieldPosition,
+};
 
 use crate::types::BooleanKey;
 pub(crate) const BATCH_KEY_V101: &str = "previous_batches_101";
@@ -15,6 +24,8 @@ pub(crate) struct State<'a> {
     pub fee_account_type: Item<'a, FeeType>,
     /// Account to send fees to
     pub fee_account: Item<'a, Addr>,
+    /// Admin the Steak token was instantiated with
+    pub token_admin: Item<'a, Addr>,
     /// Current fee rate
     pub fee_rate: Item<'a, Decimal>,
     /// Maximum fee rate
@@ -23,6 +34,8 @@ pub(crate) struct State<'a> {
     pub denom: Item<'a, String>,
     /// Address of the Steak token
     pub steak_token: Item<'a, Addr>,
+    /// Coordinator minting and burning the Steak token on behalf of every hub sharing it, if any
+    pub coordinator: Item<'a, Addr>,
     /// How often the unbonding queue is to be executed
     pub epoch_period: Item<'a, u64>,
     /// The staking module's unbonding time, in seconds
@@ -46,16 +59,246 @@ pub(crate) struct State<'a> {
     pub miner_entropy: Item<'a, String>,
     // next entropy string for miners to target for block hash
     pub miner_entropy_draft: Item<'a, String>,
+    // entropy and difficulty the last accepted proof was mined against, to tell stale proofs apart
+    pub previous_miner_target: Item<'a, (String, Uint64)>,
     // mining difficulty for miners to target for block hash
     pub miner_difficulty: Item<'a, Uint64>,
+    // hash function proofs are computed with
+    pub proof_hash: Item<'a, ProofHash>,
+    // time the difficulty was last changed
+    pub miner_difficulty_adjusted_at: Item<'a, u64>,
+    // most recent intervals between accepted proofs, in seconds at the current difficulty
+    pub proof_intervals: Item<'a, Vec<u64>>,
+    // emission schedule of the fees paid to miners
+    pub mining_schedule: Item<'a, MiningSchedule>,
+    // share of the emitted fees paid to the miner, in basis points; the rest goes to the fee account
+    pub miner_fee_share_bps: Item<'a, u16>,
+    // miner of the last accepted proof, paid its share of the fees on each reinvest
+    pub miner: Item<'a, CurrentMiner>,
+    // blocks a miner must wait between accepted proofs
+    pub proof_cooldown_blocks: Item<'a, u64>,
+    // height of each miner's last accepted proof
+    pub miner_last_proof_height: Map<'a, &'a Addr, u64>,
+    // hashes of accepted proofs, so none can be replayed should the entropy ever repeat
+    pub used_proofs: Map<'a, String, u64>,
+    // how often a single address may update the entropy draft
+    pub entropy_rate_limit: Item<'a, EntropyRateLimit>,
+    // entropy updates made by each address
+    pub entropy_contributors: Map<'a, &'a Addr, EntropyContributor>,
+    // log of every entropy update, keyed by sequence number
+    pub entropy_contributions: Map<'a, u64, EntropyContribution>,
+    pub next_entropy_contribution_id: Item<'a, u64>,
     // last mined timestamp
     pub miner_last_mined_timestamp: Item<'a, Uint64>,
     // last mined block height
     pub miner_last_mined_block: Item<'a, Uint64>,
     // mining power by validator (map of validator address to time weighted mining power)
     pub validator_mining_powers: Map<'a, String, Uint128>,
+    // active validators that keep their stake but receive no new delegations
+    pub receive_paused_validators: Item<'a, Vec<String>>,
+    // validators being removed, still whitelisted until their stake has been moved away
+    pub draining_validators: Item<'a, Vec<String>>,
+    // validators barred from the whitelist and from mining (map of validator address to reason)
+    pub validator_blocklist: Map<'a, String, String>,
+    // append-only log of the fee and safe mode changes, keyed by ID
+    pub admin_actions: Map<'a, u64, AdminActionRecord>,
+    // ID of the last admin action logged
+    pub last_admin_action_id: Item<'a, u64>,
+    // append-only log of the changes made to the validator set, keyed by ID
+    pub validator_set_changes: Map<'a, u64, ValidatorSetChange>,
+    // ID of the last change made to the validator set
+    pub last_validator_set_change_id: Item<'a, u64>,
+    // append-only log of the shortfalls found when reconciling, keyed by ID
+    pub slashing_records: Map<'a, u64, SlashingRecord>,
+    // ID of the last shortfall logged
+    pub last_slashing_record_id: Item<'a, u64>,
+    // largest share of the chain's voting power the hub's stake may lift a validator to
+    pub voting_power_cap: Item<'a, Decimal>,
+    // voting power of each whitelisted validator, as of the last refresh
+    pub validator_voting_powers: Map<'a, String, Uint128>,
+    // the chain's total voting power, as of the last refresh
+    pub total_voting_power: Item<'a, Uint128>,
+    // time of the last voting power refresh
+    pub voting_power_refreshed_at: Item<'a, u64>,
+    // reward rate and incidents of each validator, from which its score is computed
+    pub validator_performance: Map<'a, String, ValidatorPerformance>,
+    /// Rewards withdrawn from each validator by harvests, per denom
+    pub validator_rewards: Map<'a, String, Vec<Coin>>,
+    /// Commission rebates deposited by each validator
+    pub validator_rebates: Map<'a, String, Uint128>,
+    /// Rebates deposited since the last harvest, to be reinvested by the next one
+    pub pending_rebates: Item<'a, Uint128>,
+    // when validators that stopped earning rewards are redelegated away from
+    pub idle_redelegation: Item<'a, IdleRedelegation>,
     // total mining power
     pub total_mining_power: Item<'a, Uint128>,
+    // deviation from the target delegations, in bps, past which rebalancing is recommended
+    pub drift_tolerance_bps: Item<'a, u16>,
+    /// Most staking messages a single flow fans out into
+    pub max_msgs_per_tx: Item<'a, u32>,
+    /// Last validator a harvest capped by `max_msgs_per_tx` withdrew from; the next one resumes
+    /// after it
+    pub harvest_cursor: Item<'a, String>,
+    /// Share of the fees redirected to locked bonds
+    pub lock_boost_rate: Item<'a, Decimal>,
+    /// Locked bonds by user and lock id
+    pub locked_bonds: Map<'a, (&'a Addr, u64), LockedBond>,
+    /// ID to be given to the next locked bond
+    pub next_lock_id: Item<'a, u64>,
+    /// Total usteak held by the hub on behalf of locked bonds
+    pub total_locked_shares: Item<'a, Uint128>,
+    /// Cumulative boost paid per unit of lock weight
+    pub lock_boost_index: Item<'a, Decimal>,
+    /// Tiers boosting locks by duration, ordered by minimum duration
+    pub lock_tiers: Item<'a, Vec<LockTier>>,
+    /// Sum of the locked shares, each scaled by the multiplier of its lock
+    pub total_lock_weight: Item<'a, Uint128>,
+    /// Boost held by the hub that has not yet been claimed
+    pub lock_boost_unclaimed: Item<'a, Uint128>,
+    /// Principal token minted when splitting Steak; splitting is disabled if not set
+    pub principal_token: Item<'a, Addr>,
+    /// Totals of the principal/yield split
+    pub yield_pool: Item<'a, YieldPool>,
+    /// Users' yield shares
+    pub yield_positions: Map<'a, &'a Addr, YieldPosition>,
+    /// Pool index each retired epoch of yield shares ended at
+    pub yield_epoch_indexes: Map<'a, u64, Decimal>,
+    /// Amount of `usteak` backing each user's rebasing balance
+    pub rebasing_shares: Map<'a, &'a Addr, Uint128>,
+    /// Total amount of `usteak` wrapped into rebasing balances
+    pub total_rebasing_shares: Item<'a, Uint128>,
+    /// Pair the protocol-owned liquidity is provided to
+    pub pol_pair: Item<'a, Addr>,
+    /// LP token of the pair
+    pub pol_lp_token: Item<'a, Addr>,
+    /// Share of fees kept to fund protocol-owned liquidity
+    pub pol_fee_rate: Item<'a, Decimal>,
+    /// Native Token held for protocol-owned liquidity, not yet deployed
+    pub pol_native_reserve: Item<'a, Uint128>,
+    /// `usteak` held for protocol-owned liquidity, not yet deployed
+    pub pol_usteak_reserve: Item<'a, Uint128>,
+    /// Minimum discount to the redemption rate at which the arbitrage reserve buys Steak
+    pub arb_min_discount: Item<'a, Decimal>,
+    /// Maximum amount of native deployed per arbitrage
+    pub arb_max_per_execution: Item<'a, Uint128>,
+    /// Maximum amount of native awaiting unbonding at any time
+    pub arb_max_in_flight: Item<'a, Uint128>,
+    /// Native held by the arbitrage reserve
+    pub arb_reserve: Item<'a, Uint128>,
+    /// Native spent on Steak that has not yet been settled
+    pub arb_in_flight_cost: Item<'a, Uint128>,
+    /// `usteak` bought and queued for unbonding that has not yet been settled
+    pub arb_in_flight_shares: Item<'a, Uint128>,
+    /// Time after which the proposed emergency undelegation can be executed
+    pub emergency_ready_at: Item<'a, u64>,
+    /// Batch everything was undelegated into; once set, the hub is wound down
+    pub emergency_batch: Item<'a, u64>,
+    /// Steak the hub held when it was wound down, and the Native Token set aside for it
+    pub emergency_held: Item<'a, EmergencyHeld>,
+    /// Clock corrections proposed after a chain halt, awaiting their timelock
+    pub halt_recovery: Item<'a, HaltRecovery>,
+    /// Bonds, unbonding requests and proofs paused ahead of a chain upgrade, until it expires
+    pub safe_mode: Item<'a, SafeMode>,
+    /// Account allowed to pause validators and enter safe mode alongside the owner
+    pub guardian: Item<'a, Addr>,
+    /// Guardian change proposed by the owner, awaiting its timelock
+    pub guardian_change: Item<'a, GuardianChange>,
+    /// Counters of the notable conditions the hub has run into
+    pub metrics: Item<'a, Metrics>,
+    /// Native Token undelegated from each validator by the batches not yet reconciled, keyed by
+    /// batch id and validator
+    pub batch_undelegations: Map<'a, (u64, String), Uint128>,
+    /// Minimum seconds between two batch submissions
+    pub min_batch_gap: Item<'a, u64>,
+    /// Minimum `usteak` a batch must burn to be submitted by anyone but the owner
+    pub min_batch_size: Item<'a, Uint128>,
+    /// Time the last batch was submitted for unbonding
+    pub last_batch_submitted: Item<'a, u64>,
+    /// Seconds after a batch finished unbonding that its unclaimed Native Token can be swept
+    pub batch_expiry: Item<'a, u64>,
+    /// Seconds reinvested rewards are released into the exchange rate over
+    pub reward_drip_period: Item<'a, u64>,
+    /// Reinvested rewards not yet released into the exchange rate
+    pub reward_drip: Item<'a, RewardDrip>,
+    /// Rewards below which a reinvest is deferred, to be folded into the next one
+    pub min_reinvest_amount: Item<'a, Uint128>,
+    /// Rewards harvested by deferred reinvests, to be reinvested by the next one
+    pub pending_reinvest: Item<'a, Uint128>,
+    /// Exchange rate the current block's bonds and unbonds are priced at, so that a harvest landing
+    /// mid-block does not move the price for the rest of the block
+    pub block_exchange_rate: Item<'a, ExchangeRateSnapshot>,
+    /// Batches whose unclaimed Native Token was re-bonded, and the Steak owed to their unbonders
+    pub swept_batches: Map<'a, u64, SweptBatch>,
+    /// `usteak` queued for unbonding on behalf of others, keyed by batch id, funder and receiver
+    pub batch_funders: Map<'a, (u64, &'a Addr, &'a Addr), Uint128>,
+    /// Last batch scanned by `ExecuteMsg::Prune`, if the scan has not reached the end yet
+    pub prune_batches_cursor: Item<'a, u64>,
+    /// Last unbonding request scanned by `ExecuteMsg::Prune`, if the scan has not reached the end yet
+    pub prune_requests_cursor: Item<'a, (u64, Addr)>,
+    /// Members that must approve changes to the max fee
+    pub fee_committee: Item<'a, FeeCommittee>,
+    /// Committee action awaiting approval
+    pub committee_proposal: Item<'a, CommitteeProposal>,
+    /// ID of the last committee proposal
+    pub last_committee_proposal_id: Item<'a, u64>,
+    /// Contracts notified of changes in Steak balances
+    pub balance_hooks: Item<'a, Vec<Addr>>,
+    /// Addresses allowed to trigger harvests besides the contract itself
+    pub harvesters: Item<'a, Vec<Addr>>,
+    /// Contracts allowed to request callbacks once their bonds and unbonds have completed
+    pub integrators: Item<'a, Vec<Addr>>,
+    /// Integrator being called back; set while its callback executes, so that it can not request
+    /// further callbacks from within it
+    pub integrator_callback: Item<'a, Addr>,
+    /// Delegates whose votes on governance proposals the hub mirrors with its stake
+    pub vote_delegates: Item<'a, Vec<Addr>>,
+    /// Votes of the vote delegates, keyed by proposal ID
+    pub delegated_votes: Map<'a, u64, DelegatedVote>,
+    /// CronCat-style contract upkeep tasks are registered with
+    pub automation_contract: Item<'a, Addr>,
+    /// Hashes of the registered upkeep tasks, keyed by task name
+    pub automation_tasks: Map<'a, String, String>,
+    /// Seconds between the harvests run by the chain's clock module
+    pub clock_harvest_interval: Item<'a, u64>,
+    /// Time of the last harvest run by the chain's clock module
+    pub last_clock_harvest: Item<'a, u64>,
+    /// Task whose creation is awaiting the automation contract's reply
+    pub pending_automation_task: Item<'a, AutomationTask>,
+    /// Denoms staking rewards are paid in; until set, rewards in every denom are registered
+    pub reward_denoms: Item<'a, Vec<String>>,
+    /// Contract rewards paid in denoms other than the Native Token are sent to for conversion
+    pub reward_converter: Item<'a, Addr>,
+    /// Denoms that may ride along a deposit, e.g. fee grant dust, and are refunded to the depositor
+    pub refundable_denoms: Item<'a, Vec<String>>,
+    /// Zap contract registered for this hub, bonding coins other than the Native Token
+    pub zapper: Item<'a, Addr>,
+    /// Where skimmed surplus is routed; reinvested if unset
+    pub surplus_policy: Item<'a, SurplusPolicy>,
+    /// Users' cumulative bonding totals
+    pub user_totals: Map<'a, &'a Addr, UserTotals>,
+    /// Most unbonding requests a user can hold at once; uncapped if unset
+    pub max_unbond_requests: Item<'a, u32>,
+    /// Claims merged out of users' requests in reconciled batches, awaiting withdrawal
+    pub consolidated_claims: Map<'a, &'a Addr, ConsolidatedClaim>,
+    /// Sum of all users' consolidated claims
+    pub consolidated_total: Item<'a, ConsolidatedClaim>,
+    /// Users' bonding history, keyed by user and entry id
+    pub user_history: Map<'a, (&'a Addr, u64), UserHistoryEntry>,
+    /// Daily accounting snapshots, keyed by days since the unix epoch
+    pub daily_snapshots: Map<'a, u64, DailySnapshot>,
+    /// Fees charged since the last snapshot
+    pub snapshot_pending_fees: Item<'a, Uint128>,
+    /// Protocol fees being sent to the fee account by the current reinvest, until its reply; escrowed
+    /// if sending them failed
+    pub fee_forward: Item<'a, Uint128>,
+    /// Protocol fees that could not be forwarded to the fee account, awaiting a retry
+    pub escrowed_fees: Item<'a, Uint128>,
+    /// Fractional part of the protocol fee not yet charged, carried into the next harvest
+    pub fee_carry: Item<'a, Decimal256>,
+    /// Which side rounding in share math favors
+    pub rounding_policy: Item<'a, RoundingPolicy>,
+    /// Denom proposed to replace the staked denom, awaiting confirmation
+    pub pending_denom: Item<'a, String>,
 }
 
 impl Default for State<'static> {
@@ -73,15 +316,22 @@ impl Default for State<'static> {
                 "unbond_requests",
                 "unbond_requests__user",
             ),
+            sender: MultiIndex::new(
+                |d: &UnbondRequest| d.sender.as_ref().unwrap_or(&d.user).to_string(),
+                "unbond_requests",
+                "unbond_requests__sender",
+            ),
         };
         Self {
             owner: Item::new("owner"),
             new_owner: Item::new("new_owner"),
             fee_account: Item::new("fee_account"),
+            token_admin: Item::new("token_admin"),
             fee_rate: Item::new("fee_rate"),
             max_fee_rate: Item::new("max_fee_rate"),
             denom: Item::new("denom"),
             steak_token: Item::new("steak_token"),
+            coordinator: Item::new("coordinator"),
             epoch_period: Item::new("epoch_period"),
             unbond_period: Item::new("unbond_period"),
             validators: Item::new("validators"),
@@ -94,22 +344,201 @@ impl Default for State<'static> {
             fee_account_type: Item::new("fee_account_type"),
             miner_entropy: Item::new("miner_entropy"),
             miner_entropy_draft: Item::new("miner_entropy_draft"),
+            previous_miner_target: Item::new("previous_miner_target"),
             miner_difficulty: Item::new("miner_difficulty"),
+            proof_hash: Item::new("proof_hash"),
+            miner_difficulty_adjusted_at: Item::new("miner_difficulty_adjusted_at"),
+            proof_intervals: Item::new("proof_intervals"),
+            mining_schedule: Item::new("mining_schedule"),
+            miner_fee_share_bps: Item::new("miner_fee_share_bps"),
+            miner: Item::new("miner"),
+            proof_cooldown_blocks: Item::new("proof_cooldown_blocks"),
+            miner_last_proof_height: Map::new("miner_last_proof_height"),
+            used_proofs: Map::new("used_proofs"),
+            entropy_rate_limit: Item::new("entropy_rate_limit"),
+            entropy_contributors: Map::new("entropy_contributors"),
+            entropy_contributions: Map::new("entropy_contributions"),
+            next_entropy_contribution_id: Item::new("next_entropy_contribution_id"),
             miner_last_mined_timestamp: Item::new("miner_last_mined_timestamp"),
             miner_last_mined_block: Item::new("miner_last_mined_block"),
             validator_mining_powers: Map::new("validator_mining_powers"),
+            receive_paused_validators: Item::new("receive_paused_validators"),
+            draining_validators: Item::new("draining_validators"),
+            validator_blocklist: Map::new("validator_blocklist"),
+            admin_actions: Map::new("admin_actions"),
+            last_admin_action_id: Item::new("last_admin_action_id"),
+            validator_set_changes: Map::new("validator_set_changes"),
+            last_validator_set_change_id: Item::new("last_validator_set_change_id"),
+            slashing_records: Map::new("slashing_records"),
+            last_slashing_record_id: Item::new("last_slashing_record_id"),
+            voting_power_cap: Item::new("voting_power_cap"),
+            validator_voting_powers: Map::new("validator_voting_powers"),
+            total_voting_power: Item::new("total_voting_power"),
+            voting_power_refreshed_at: Item::new("voting_power_refreshed_at"),
+            validator_performance: Map::new("validator_performance"),
+            validator_rewards: Map::new("validator_rewards"),
+            validator_rebates: Map::new("validator_rebates"),
+            pending_rebates: Item::new("pending_rebates"),
+            idle_redelegation: Item::new("idle_redelegation"),
             total_mining_power: Item::new("total_mining_power"),
+            drift_tolerance_bps: Item::new("drift_tolerance_bps"),
+            max_msgs_per_tx: Item::new("max_msgs_per_tx"),
+            harvest_cursor: Item::new("harvest_cursor"),
+            lock_boost_rate: Item::new("lock_boost_rate"),
+            locked_bonds: Map::new("locked_bonds"),
+            next_lock_id: Item::new("next_lock_id"),
+            total_locked_shares: Item::new("total_locked_shares"),
+            lock_boost_index: Item::new("lock_boost_index"),
+            lock_tiers: Item::new("lock_tiers"),
+            total_lock_weight: Item::new("total_lock_weight"),
+            lock_boost_unclaimed: Item::new("lock_boost_unclaimed"),
+            principal_token: Item::new("principal_token"),
+            yield_pool: Item::new("yield_pool"),
+            yield_positions: Map::new("yield_positions"),
+            yield_epoch_indexes: Map::new("yield_epoch_indexes"),
+            rebasing_shares: Map::new("rebasing_shares"),
+            total_rebasing_shares: Item::new("total_rebasing_shares"),
+            pol_pair: Item::new("pol_pair"),
+            pol_lp_token: Item::new("pol_lp_token"),
+            pol_fee_rate: Item::new("pol_fee_rate"),
+            pol_native_reserve: Item::new("pol_native_reserve"),
+            pol_usteak_reserve: Item::new("pol_usteak_reserve"),
+            arb_min_discount: Item::new("arb_min_discount"),
+            arb_max_per_execution: Item::new("arb_max_per_execution"),
+            arb_max_in_flight: Item::new("arb_max_in_flight"),
+            arb_reserve: Item::new("arb_reserve"),
+            arb_in_flight_cost: Item::new("arb_in_flight_cost"),
+            arb_in_flight_shares: Item::new("arb_in_flight_shares"),
+            emergency_ready_at: Item::new("emergency_ready_at"),
+            emergency_batch: Item::new("emergency_batch"),
+            emergency_held: Item::new("emergency_held"),
+            halt_recovery: Item::new("halt_recovery"),
+            safe_mode: Item::new("safe_mode"),
+            guardian: Item::new("guardian"),
+            guardian_change: Item::new("guardian_change"),
+            metrics: Item::new("metrics"),
+            batch_undelegations: Map::new("batch_undelegations"),
+            min_batch_gap: Item::new("min_batch_gap"),
+            min_batch_size: Item::new("min_batch_size"),
+            last_batch_submitted: Item::new("last_batch_submitted"),
+            batch_expiry: Item::new("batch_expiry"),
+            reward_drip_period: Item::new("reward_drip_period"),
+            reward_drip: Item::new("reward_drip"),
+            min_reinvest_amount: Item::new("min_reinvest_amount"),
+            pending_reinvest: Item::new("pending_reinvest"),
+            block_exchange_rate: Item::new("block_exchange_rate"),
+            swept_batches: Map::new("swept_batches"),
+            batch_funders: Map::new("batch_funders"),
+            prune_batches_cursor: Item::new("prune_batches_cursor"),
+            prune_requests_cursor: Item::new("prune_requests_cursor"),
+            fee_committee: Item::new("fee_committee"),
+            committee_proposal: Item::new("committee_proposal"),
+            last_committee_proposal_id: Item::new("last_committee_proposal_id"),
+            balance_hooks: Item::new("balance_hooks"),
+            harvesters: Item::new("harvesters"),
+            integrators: Item::new("integrators"),
+            integrator_callback: Item::new("integrator_callback"),
+            vote_delegates: Item::new("vote_delegates"),
+            delegated_votes: Map::new("delegated_votes"),
+            automation_contract: Item::new("automation_contract"),
+            automation_tasks: Map::new("automation_tasks"),
+            pending_automation_task: Item::new("pending_automation_task"),
+            clock_harvest_interval: Item::new("clock_harvest_interval"),
+            last_clock_harvest: Item::new("last_clock_harvest"),
+            reward_denoms: Item::new("reward_denoms"),
+            refundable_denoms: Item::new("refundable_denoms"),
+            zapper: Item::new("zapper"),
+            surplus_policy: Item::new("surplus_policy"),
+            reward_converter: Item::new("reward_converter"),
+            user_totals: Map::new("user_totals"),
+            max_unbond_requests: Item::new("max_unbond_requests"),
+            consolidated_claims: Map::new("consolidated_claims"),
+            consolidated_total: Item::new("consolidated_total"),
+            user_history: Map::new("user_history"),
+            daily_snapshots: Map::new("daily_snapshots"),
+            snapshot_pending_fees: Item::new("snapshot_pending_fees"),
+            fee_forward: Item::new("fee_forward"),
+            escrowed_fees: Item::new("escrowed_fees"),
+            fee_carry: Item::new("fee_carry"),
+            rounding_policy: Item::new("rounding_policy"),
+            pending_denom: Item::new("pending_denom"),
         }
     }
 }
 
 impl<'a> State<'a> {
-    pub fn assert_owner(&self, storage: &dyn Storage, sender: &Addr) -> StdResult<()> {
+    pub fn assert_owner(&self, storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
         let owner = self.owner.load(storage)?;
         if *sender == owner {
             Ok(())
         } else {
-            Err(StdError::generic_err("unauthorized: sender is not owner"))
+            Err(ContractError::Unauthorized {})
+        }
+    }
+
+    /// The guardian may only pause, so this guards nothing but pausing actions
+    pub fn assert_owner_or_guardian(
+        &self,
+        storage: &dyn Storage,
+        sender: &Addr,
+    ) -> Result<(), ContractError> {
+        if self.guardian.may_load(storage)?.as_ref() == Some(sender) {
+            return Ok(());
+        }
+        self.assert_owner(storage, sender)
+    }
+
+    pub fn assert_not_wound_down(&self, storage: &dyn Storage) -> Result<(), ContractError> {
+        match self.emergency_batch.may_load(storage)? {
+            Some(_) => Err(ContractError::WoundDown {}),
+            None => Ok(()),
+        }
+    }
+
+    pub fn assert_not_in_safe_mode(
+        &self,
+        storage: &dyn Storage,
+        block: &BlockInfo,
+    ) -> Result<(), ContractError> {
+        match self.safe_mode.may_load(storage)? {
+            Some(safe_mode) if safe_mode.is_active(block.height, block.time.seconds()) => {
+                Err(ContractError::InSafeMode {})
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn assert_integrator(
+        &self,
+        storage: &dyn Storage,
+        sender: &Addr,
+    ) -> Result<(), ContractError> {
+        if self.integrator_callback.may_load(storage)?.is_some() {
+            return Err(ContractError::IntegratorCallbackInProgress {});
+        }
+        let integrators = self.integrators.may_load(storage)?.unwrap_or_default();
+        if !integrators.contains(sender) {
+            return Err(ContractError::IntegratorNotRegistered {
+                address: sender.into(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn assert_not_blocked(
+        &self,
+        storage: &dyn Storage,
+        validator: &str,
+    ) -> Result<(), ContractError> {
+        match self
+            .validator_blocklist
+            .may_load(storage, validator.to_string())?
+        {
+            Some(reason) => Err(ContractError::ValidatorBlocked {
+                validator: validator.to_string(),
+                reason,
+            }),
+            None => Ok(()),
         }
     }
 }
@@ -129,11 +558,13 @@ impl<'a> IndexList<Batch> for PreviousBatchesIndexes<'a> {
 pub(crate) struct UnbondRequestsIndexes<'a> {
     // pk goes to second tuple element
     pub user: MultiIndex<'a, String, UnbondRequest, Vec<u8>>,
+    // requests the user queued themselves are indexed under the user
+    pub sender: MultiIndex<'a, String, UnbondRequest, Vec<u8>>,
 }
 
 impl<'a> IndexList<UnbondRequest> for UnbondRequestsIndexes<'a> {
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<UnbondRequest>> + '_> {
-        let v: Vec<&dyn Index<UnbondRequest>> = vec![&self.user];
+        let v: Vec<&dyn Index<UnbondRequest>> = vec![&self.user, &self.sender];
         Box::new(v.into_iter())
     }
 }
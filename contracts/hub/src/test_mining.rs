@@ -0,0 +1,21 @@
+//! Deterministic mining for integration tests.
+//!
+//! With the mode enabled every nonce meets the difficulty, so tests can drive the whole
+//! `submit_proof` → `harvest` → `reinvest` pipeline without grinding hashes. Entropy still rotates
+//! and proofs are still checked for replays, the rate limit and their allocations. The mode can
+//! only be switched on through storage, never through a message, and the feature must not be
+//! enabled in builds meant for a chain.
+
+use cosmwasm_std::{StdResult, Storage};
+use cw_storage_plus::Item;
+
+const TRIVIAL_DIFFICULTY: Item<bool> = Item::new("test_mining");
+
+/// Accept any nonce as a proof from now on
+pub fn enable(storage: &mut dyn Storage) -> StdResult<()> {
+    TRIVIAL_DIFFICULTY.save(storage, &true)
+}
+
+pub(crate) fn is_enabled(storage: &dyn Storage) -> StdResult<bool> {
+    Ok(TRIVIAL_DIFFICULTY.may_load(storage)?.unwrap_or(false))
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use cosmwasm_std::{to_binary, Addr, QuerierResult, Uint128};
+
+use pfc_steak::coordinator::{ConfigResponse, HubSupply, QueryMsg};
+
+#[derive(Default)]
+pub(super) struct CoordinatorQuerier {
+    /// Mapping coordinator address to the Steak token it mints
+    pub steak_tokens: HashMap<String, String>,
+    /// Mapping hub address to the Steak the coordinator attributes to it
+    pub hub_supplies: HashMap<String, u128>,
+}
+
+impl CoordinatorQuerier {
+    pub fn handle_query(&self, contract_addr: &str, query: QueryMsg) -> QuerierResult {
+        match query {
+            QueryMsg::Config {} => {
+                let steak_token = self
+                    .steak_tokens
+                    .get(contract_addr)
+                    .expect("[mock] coordinator not set");
+                Ok(to_binary(&ConfigResponse {
+                    owner: "coordinator_owner".to_string(),
+                    steak_token: steak_token.clone(),
+                })
+                .into())
+                .into()
+            }
+
+            QueryMsg::HubSupply { hub } => {
+                let supply = self.hub_supplies.get(&hub).copied().unwrap_or_default();
+                Ok(to_binary(&HubSupply {
+                    hub: Addr::unchecked(hub),
+                    supply: Uint128::new(supply),
+                    queued: Uint128::zero(),
+                })
+                .into())
+                .into()
+            }
+
+            QueryMsg::Hubs {} => Ok(to_binary(
+                &self
+                    .hub_supplies
+                    .iter()
+                    .map(|(hub, supply)| HubSupply {
+                        hub: Addr::unchecked(hub),
+                        supply: Uint128::new(*supply),
+                        queued: Uint128::zero(),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .into())
+            .into(),
+        }
+    }
+}
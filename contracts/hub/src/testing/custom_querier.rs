@@ -1,22 +1,44 @@
 use std::collections::HashMap;
 
+use cosmos_sdk_proto::cosmos::staking::v1beta1::{
+    Pool, QueryDelegatorUnbondingDelegationsResponse, QueryPoolResponse, QueryValidatorRequest,
+    QueryValidatorResponse, UnbondingDelegation, UnbondingDelegationEntry,
+    Validator as SdkValidator,
+};
 use cosmwasm_std::testing::{BankQuerier, StakingQuerier, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_binary, from_slice, Addr, Coin, Decimal, Empty, FullDelegation, Querier, QuerierResult,
-    QueryRequest, SystemError, Validator, WasmQuery,
+    from_binary, from_slice, Addr, Binary, Coin, ContractResult, Decimal, Empty, FullDelegation,
+    Querier, QuerierResult, QueryRequest, SystemError, SystemResult, Validator, WasmQuery,
 };
 use cw20::Cw20QueryMsg;
+use pfc_fee_split::fee_split_msg::QueryMsg as FeeSplitQueryMsg;
+use prost::Message;
+
+use pfc_steak::coordinator::QueryMsg as CoordinatorQueryMsg;
+use pfc_steak::pair::{PairQueryMsg, PoolResponse};
 
 use crate::types::Delegation;
 
+use super::coordinator_querier::CoordinatorQuerier;
 use super::cw20_querier::Cw20Querier;
+use super::fee_split_querier::FeeSplitQuerier;
 use super::helpers::err_unsupported_query;
+use super::pair_querier::PairQuerier;
 
 #[derive(Default)]
 pub(super) struct CustomQuerier {
     pub cw20_querier: Cw20Querier,
     pub bank_querier: BankQuerier,
     pub staking_querier: StakingQuerier,
+    pub pair_querier: PairQuerier,
+    pub coordinator_querier: CoordinatorQuerier,
+    pub fee_split_querier: FeeSplitQuerier,
+    /// Rewards accumulated by each delegation, applied by `set_staking_delegations`
+    pub staking_rewards: HashMap<String, Vec<Coin>>,
+    /// Balances of the unbonding entries for each validator, served over stargate when set
+    pub unbonding_delegations: Option<Vec<(String, Vec<u128>)>>,
+    /// The chain's total voting power and that of each validator, served over stargate when set
+    pub voting_power: Option<(u128, HashMap<String, u128>)>,
 }
 
 impl Querier for CustomQuerier {
@@ -58,10 +80,39 @@ impl CustomQuerier {
             .insert(token.to_string(), total_supply);
     }
 
+    pub fn set_pair_pool(&mut self, pair: &str, pool: PoolResponse) {
+        self.pair_querier.pools.insert(pair.to_string(), pool);
+    }
+
+    pub fn set_coordinator(&mut self, coordinator: &str, steak_token: &str) {
+        self.coordinator_querier
+            .steak_tokens
+            .insert(coordinator.to_string(), steak_token.to_string());
+    }
+
+    pub fn set_coordinator_hub_supply(&mut self, hub: &str, supply: u128) {
+        self.coordinator_querier
+            .hub_supplies
+            .insert(hub.to_string(), supply);
+    }
+
+    pub fn set_fee_split(&mut self, fee_split: &str, gov_contract: &str) {
+        self.fee_split_querier
+            .gov_contracts
+            .insert(fee_split.to_string(), gov_contract.to_string());
+    }
+
     pub fn set_bank_balances(&mut self, balances: &[Coin]) {
         self.bank_querier = BankQuerier::new(&[(MOCK_CONTRACT_ADDR, balances)]);
     }
 
+    pub fn set_staking_rewards(&mut self, rewards: &[(&str, Coin)]) {
+        self.staking_rewards = rewards
+            .iter()
+            .map(|(validator, coin)| (validator.to_string(), vec![coin.clone()]))
+            .collect();
+    }
+
     pub fn set_staking_delegations(&mut self, delegations: &[Delegation]) {
         let fds = delegations
             .iter()
@@ -70,7 +121,11 @@ impl CustomQuerier {
                 validator: d.validator.clone(),
                 amount: Coin::new(d.amount, "native_token"),
                 can_redelegate: Coin::new(0, "native_token"),
-                accumulated_rewards: vec![],
+                accumulated_rewards: self
+                    .staking_rewards
+                    .get(&d.validator)
+                    .cloned()
+                    .unwrap_or_default(),
             })
             .collect::<Vec<_>>();
         let validators: Vec<Validator> = delegations
@@ -85,13 +140,83 @@ impl CustomQuerier {
         self.staking_querier = StakingQuerier::new("native_token", &validators, &fds);
     }
 
+    pub fn set_unbonding_delegations(&mut self, unbondings: &[(&str, &[u128])]) {
+        self.unbonding_delegations = Some(
+            unbondings
+                .iter()
+                .map(|(validator, balances)| (validator.to_string(), balances.to_vec()))
+                .collect(),
+        );
+    }
+
+    pub fn set_voting_power(&mut self, total: u128, validators: &[(&str, u128)]) {
+        self.voting_power = Some((
+            total,
+            validators
+                .iter()
+                .map(|(validator, power)| (validator.to_string(), *power))
+                .collect(),
+        ));
+    }
+
     pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
         match request {
+            QueryRequest::Stargate { path, .. } if path.ends_with("/Pool") => {
+                match &self.voting_power {
+                    Some((total, _)) => encode_stargate(QueryPoolResponse {
+                        pool: Some(Pool {
+                            not_bonded_tokens: "0".to_string(),
+                            bonded_tokens: total.to_string(),
+                        }),
+                    }),
+                    None => err_unsupported_query(request),
+                }
+            }
+
+            QueryRequest::Stargate { path, data } if path.ends_with("/Validator") => {
+                match &self.voting_power {
+                    Some((_, powers)) => {
+                        let request = QueryValidatorRequest::decode(data.as_slice()).unwrap();
+                        encode_stargate(QueryValidatorResponse {
+                            validator: powers.get(&request.validator_addr).map(|power| {
+                                SdkValidator {
+                                    operator_address: request.validator_addr.clone(),
+                                    tokens: power.to_string(),
+                                    ..Default::default()
+                                }
+                            }),
+                        })
+                    }
+                    None => err_unsupported_query(request),
+                }
+            }
+
+            QueryRequest::Stargate { path, .. }
+                if path.ends_with("/DelegatorUnbondingDelegations") =>
+            {
+                match &self.unbonding_delegations {
+                    Some(unbondings) => self.encode_unbonding_delegations(unbondings),
+                    None => err_unsupported_query(request),
+                }
+            }
+
             QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
                 if let Ok(query) = from_binary::<Cw20QueryMsg>(msg) {
                     return self.cw20_querier.handle_query(contract_addr, query);
                 }
 
+                if let Ok(query) = from_binary::<PairQueryMsg>(msg) {
+                    return self.pair_querier.handle_query(contract_addr, query);
+                }
+
+                if let Ok(query) = from_binary::<CoordinatorQueryMsg>(msg) {
+                    return self.coordinator_querier.handle_query(contract_addr, query);
+                }
+
+                if let Ok(query) = from_binary::<FeeSplitQueryMsg>(msg) {
+                    return self.fee_split_querier.handle_query(contract_addr, query);
+                }
+
                 err_unsupported_query(msg)
             }
 
@@ -102,4 +227,29 @@ impl CustomQuerier {
             _ => err_unsupported_query(request),
         }
     }
+
+    fn encode_unbonding_delegations(&self, unbondings: &[(String, Vec<u128>)]) -> QuerierResult {
+        let response = QueryDelegatorUnbondingDelegationsResponse {
+            unbonding_responses: unbondings
+                .iter()
+                .map(|(validator, balances)| UnbondingDelegation {
+                    delegator_address: MOCK_CONTRACT_ADDR.to_string(),
+                    validator_address: validator.clone(),
+                    entries: balances
+                        .iter()
+                        .map(|balance| UnbondingDelegationEntry {
+                            balance: balance.to_string(),
+                            ..Default::default()
+                        })
+                        .collect(),
+                })
+                .collect(),
+            pagination: None,
+        };
+        encode_stargate(response)
+    }
+}
+
+fn encode_stargate<M: Message>(response: M) -> QuerierResult {
+    SystemResult::Ok(ContractResult::Ok(Binary(response.encode_to_vec())))
 }
@@ -0,0 +1,36 @@
+use std::collections::HashMap;
+
+use cosmwasm_std::{to_binary, QuerierResult, SystemError, SystemResult};
+
+use pfc_fee_split::fee_split_msg::{GovContractResponse, QueryMsg};
+
+use super::helpers::err_unsupported_query;
+
+#[derive(Default)]
+pub(super) struct FeeSplitQuerier {
+    /// Mapping fee split contract address to its governance contract
+    pub gov_contracts: HashMap<String, String>,
+}
+
+impl FeeSplitQuerier {
+    pub fn handle_query(&self, contract_addr: &str, query: QueryMsg) -> QuerierResult {
+        let gov_contract = match self.gov_contracts.get(contract_addr) {
+            Some(gov_contract) => gov_contract,
+            None => {
+                return SystemResult::Err(SystemError::NoSuchContract {
+                    addr: contract_addr.to_string(),
+                })
+            }
+        };
+
+        match query {
+            QueryMsg::GovContract {} => Ok(to_binary(&GovContractResponse {
+                gov_contract: gov_contract.clone(),
+            })
+            .into())
+            .into(),
+
+            _ => err_unsupported_query(query),
+        }
+    }
+}
@@ -44,3 +44,7 @@ pub(super) fn mock_env_at_timestamp(timestamp: u64) -> Env {
 pub(super) fn query_helper<T: DeserializeOwned>(deps: Deps, msg: QueryMsg) -> T {
     from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
 }
+
+pub(super) fn query_helper_env<T: DeserializeOwned>(deps: Deps, env: Env, msg: QueryMsg) -> T {
+    from_binary(&query(deps, env, msg).unwrap()).unwrap()
+}
@@ -0,0 +1,375 @@
+use std::collections::{BTreeMap, HashMap};
+
+use cosmwasm_std::{Decimal, Decimal256, Uint128, Uint256};
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use pfc_steak::hub::{Batch, RoundingPolicy};
+
+use crate::math::{
+    cap_target_delegations, compute_fee_with_carry, compute_loss_attribution, compute_mint_amount,
+    compute_redelegations_for_rebalancing, compute_target_delegation_from_mining_power,
+    compute_unbond_amount, compute_undelegations, reconcile_batches,
+};
+use crate::types::Delegation;
+
+const MAX_AMOUNT: u128 = 1_000_000_000_000_000_000;
+
+fn delegations(amounts: &[u128]) -> Vec<Delegation> {
+    amounts
+        .iter()
+        .enumerate()
+        .map(|(i, amount)| Delegation::new(&format!("validator{}", i), *amount, "uxyz"))
+        .collect()
+}
+
+fn rounding() -> impl Strategy<Value = RoundingPolicy> {
+    prop_oneof![
+        Just(RoundingPolicy::FavorProtocol),
+        Just(RoundingPolicy::FavorUser)
+    ]
+}
+
+proptest! {
+    #[test]
+    fn capped_targets_respect_caps_and_keep_the_total(
+        targets in vec(0..MAX_AMOUNT, 1..10),
+        caps in vec(proptest::option::of(0..MAX_AMOUNT), 1..10),
+    ) {
+        let targets = targets
+            .iter()
+            .enumerate()
+            .map(|(i, target)| (format!("validator{}", i), Uint128::new(*target)))
+            .collect::<Vec<_>>();
+        let caps = caps
+            .iter()
+            .enumerate()
+            .filter_map(|(i, cap)| cap.map(|cap| (format!("validator{}", i), Uint128::new(cap))))
+            .collect::<BTreeMap<_, _>>();
+        let capped = cap_target_delegations(&targets, &caps);
+
+        for (validator, target) in &capped {
+            if let Some(cap) = caps.get(validator) {
+                prop_assert!(target <= cap);
+            }
+        }
+        // what is cut is only lost, up to rounding, when no validator with a target is left uncapped
+        let total: u128 = targets.iter().map(|(_, target)| target.u128()).sum();
+        let capped_total: u128 = capped.iter().map(|(_, target)| target.u128()).sum();
+        prop_assert!(capped_total <= total);
+        let uncapped = capped.iter().zip(&targets).any(|((validator, target), (_, original))| {
+            !original.is_zero() && caps.get(validator).map_or(true, |cap| target < cap)
+        });
+        if uncapped {
+            prop_assert!(total - capped_total < targets.len() as u128);
+        }
+    }
+
+    #[test]
+    fn loss_attribution_adds_up(
+        weights in vec(0..MAX_AMOUNT, 1..10),
+        native_lost in 0..MAX_AMOUNT,
+    ) {
+        let weights = weights
+            .iter()
+            .enumerate()
+            .map(|(i, weight)| (format!("validator{}", i), Uint128::new(*weight)))
+            .collect::<Vec<_>>();
+        let losses = compute_loss_attribution(Uint128::new(native_lost), &weights);
+
+        let total_weight: u128 = weights.iter().map(|(_, weight)| weight.u128()).sum();
+        let attributed: u128 = losses.iter().map(|(_, loss)| loss.u128()).sum();
+        if total_weight == 0 {
+            prop_assert!(losses.is_empty());
+        } else {
+            prop_assert_eq!(attributed, native_lost);
+        }
+        // nothing is attributed to validators without weight
+        for (validator, _) in &losses {
+            prop_assert!(weights.iter().any(|(v, weight)| v == validator && !weight.is_zero()));
+        }
+    }
+
+    #[test]
+    fn minting_rounds_as_the_policy_dictates(
+        usteak_supply in 1..MAX_AMOUNT,
+        native_bonded in 1..MAX_AMOUNT,
+        native_to_bond in 0..MAX_AMOUNT,
+        rounding in rounding(),
+    ) {
+        let minted = compute_mint_amount(
+            Uint128::new(usteak_supply),
+            Uint128::new(native_to_bond),
+            Uint128::new(native_bonded),
+            rounding,
+        )
+        .unwrap();
+
+        // `minted / usteak_supply` is within one `usteak` of `native_to_bond / native_bonded`, on
+        // the side the policy favors
+        let exact = Uint128::new(usteak_supply).full_mul(Uint128::new(native_to_bond));
+        let minted_value = minted.full_mul(Uint128::new(native_bonded));
+        let one_more_value = (minted + Uint128::new(1)).full_mul(Uint128::new(native_bonded));
+        match rounding {
+            RoundingPolicy::FavorProtocol => {
+                prop_assert!(minted_value <= exact);
+                prop_assert!(one_more_value > exact);
+            }
+            RoundingPolicy::FavorUser => {
+                prop_assert!(minted_value >= exact);
+                prop_assert!(minted.is_zero() || minted_value - Uint256::from(native_bonded) < exact);
+            }
+        }
+    }
+
+    #[test]
+    fn minting_is_monotonic(
+        usteak_supply in 1..MAX_AMOUNT,
+        native_bonded in 1..MAX_AMOUNT,
+        native_to_bond in 0..MAX_AMOUNT,
+        extra in 0..MAX_AMOUNT,
+        rounding in rounding(),
+    ) {
+        let mint = |amount: u128| {
+            compute_mint_amount(
+                Uint128::new(usteak_supply),
+                Uint128::new(amount),
+                Uint128::new(native_bonded),
+                rounding,
+            )
+            .unwrap()
+        };
+        prop_assert!(mint(native_to_bond) <= mint(native_to_bond + extra));
+    }
+
+    #[test]
+    fn unbonding_never_exceeds_the_stake(
+        amounts in vec(0..MAX_AMOUNT, 1..10),
+        usteak_supply in 1..MAX_AMOUNT,
+        burn_share in 0..=100u128,
+        rounding in rounding(),
+    ) {
+        let usteak_to_burn = usteak_supply * burn_share / 100;
+        let native_bonded: u128 = amounts.iter().sum();
+        let unbonded = compute_unbond_amount(
+            Uint128::new(usteak_supply),
+            Uint128::new(usteak_to_burn),
+            Uint128::new(native_bonded),
+            rounding,
+        )
+        .unwrap();
+        prop_assert!(unbonded.u128() <= native_bonded);
+        if usteak_to_burn == usteak_supply {
+            prop_assert_eq!(unbonded.u128(), native_bonded);
+        }
+    }
+
+    #[test]
+    fn bonding_then_unbonding_does_not_create_value(
+        usteak_supply in 1..MAX_AMOUNT,
+        native_bonded in 1..MAX_AMOUNT,
+        native_to_bond in 0..MAX_AMOUNT,
+    ) {
+        let minted = compute_mint_amount(
+            Uint128::new(usteak_supply),
+            Uint128::new(native_to_bond),
+            Uint128::new(native_bonded),
+            RoundingPolicy::FavorProtocol,
+        )
+        .unwrap();
+        let unbonded = compute_unbond_amount(
+            Uint128::new(usteak_supply) + minted,
+            minted,
+            Uint128::new(native_bonded + native_to_bond),
+            RoundingPolicy::FavorProtocol,
+        )
+        .unwrap();
+        prop_assert!(unbonded.u128() <= native_to_bond);
+    }
+
+    #[test]
+    fn undelegations_add_up_without_overdrawing(
+        amounts in vec(0..MAX_AMOUNT, 1..10),
+        unbond_share in 0..=100u128,
+    ) {
+        let current_delegations = delegations(&amounts);
+        let native_staked: u128 = amounts.iter().sum();
+        let native_to_unbond = native_staked / 100 * unbond_share;
+
+        let undelegations =
+            compute_undelegations(Uint128::new(native_to_unbond), &current_delegations, "uxyz");
+
+        let total: u128 = undelegations.iter().map(|u| u.amount).sum();
+        prop_assert_eq!(total, native_to_unbond);
+        for u in &undelegations {
+            let d = current_delegations
+                .iter()
+                .find(|d| d.validator == u.validator)
+                .unwrap();
+            prop_assert!(u.amount > 0 && u.amount <= d.amount);
+            prop_assert_eq!(
+                undelegations.iter().filter(|other| other.validator == u.validator).count(),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn rebalancing_moves_stake_without_overdrawing(
+        validators in vec((0..MAX_AMOUNT, 0..1_000_000u128, any::<bool>()), 1..10),
+        min_difference in 0..1_000_000u128,
+    ) {
+        let amounts: Vec<u128> = validators.iter().map(|(amount, _, _)| *amount).collect();
+        let current_delegations = delegations(&amounts);
+        let native_staked: u128 = amounts.iter().sum();
+        let total_mining_power: u128 = validators.iter().map(|(_, power, _)| *power).sum::<u128>() + 1;
+        let powers: HashMap<String, u128> = current_delegations
+            .iter()
+            .zip(&validators)
+            .map(|(d, (_, power, _))| (d.validator.clone(), *power))
+            .collect();
+        let mut validators_active: Vec<String> = current_delegations
+            .iter()
+            .zip(&validators)
+            .filter(|(_, (_, _, active))| *active)
+            .map(|(d, _)| d.validator.clone())
+            .collect();
+        if validators_active.is_empty() {
+            validators_active.push(current_delegations[0].validator.clone());
+        }
+
+        let redelegations = compute_redelegations_for_rebalancing(
+            validators_active.clone(),
+            &current_delegations,
+            Uint128::new(min_difference),
+            |d| {
+                compute_target_delegation_from_mining_power(
+                    Uint128::new(native_staked),
+                    Uint128::new(powers[&d.validator]),
+                    Uint128::new(total_mining_power),
+                )
+            },
+        )
+        .unwrap();
+
+        let mut balances: HashMap<String, i128> = current_delegations
+            .iter()
+            .map(|d| (d.validator.clone(), d.amount as i128))
+            .collect();
+        for rd in &redelegations {
+            prop_assert!(rd.amount > 0);
+            prop_assert_ne!(&rd.src, &rd.dst);
+            prop_assert!(validators_active.contains(&rd.dst));
+            *balances.get_mut(&rd.src).unwrap() -= rd.amount as i128;
+            *balances.get_mut(&rd.dst).unwrap() += rd.amount as i128;
+        }
+        prop_assert!(balances.values().all(|balance| *balance >= 0));
+        prop_assert_eq!(balances.values().sum::<i128>(), native_staked as i128);
+    }
+
+    #[test]
+    fn mining_power_targets_are_monotonic(
+        total_delegated in 0..MAX_AMOUNT,
+        powers in vec(0..1_000_000_000u128, 1..10),
+    ) {
+        let total_mining_power: u128 = powers.iter().sum::<u128>().max(1);
+        let target = |power: u128| {
+            compute_target_delegation_from_mining_power(
+                Uint128::new(total_delegated),
+                Uint128::new(power),
+                Uint128::new(total_mining_power),
+            )
+            .unwrap()
+        };
+
+        let mut sorted = powers.clone();
+        sorted.sort_unstable();
+        for pair in sorted.windows(2) {
+            prop_assert!(target(pair[0]) <= target(pair[1]));
+        }
+        let total_target: u128 = powers.iter().map(|power| target(*power).u128()).sum();
+        prop_assert!(total_target <= total_delegated);
+    }
+
+    #[test]
+    fn reconciling_deducts_exactly_and_evenly(
+        amounts in vec(0..MAX_AMOUNT, 1..10),
+        native_to_deduct in 0..MAX_AMOUNT,
+    ) {
+        // every batch holds at least its share of the deduction
+        let share = native_to_deduct / amounts.len() as u128 + 1;
+        let mut batches: Vec<Batch> = amounts
+            .iter()
+            .enumerate()
+            .map(|(i, amount)| Batch {
+                id: i as u64,
+                reconciled: false,
+                total_shares: Uint128::new(*amount),
+                amount_unclaimed: Uint128::new(amount + share),
+                est_unbond_end_time: 0,
+            })
+            .collect();
+        let before: Vec<u128> = batches.iter().map(|b| b.amount_unclaimed.u128()).collect();
+
+        reconcile_batches(&mut batches, Uint128::new(native_to_deduct));
+
+        let deducted: Vec<u128> = batches
+            .iter()
+            .zip(&before)
+            .map(|(b, before)| before - b.amount_unclaimed.u128())
+            .collect();
+        prop_assert_eq!(deducted.iter().sum::<u128>(), native_to_deduct);
+        prop_assert!(deducted.iter().max().unwrap() - deducted.iter().min().unwrap() <= 1);
+        prop_assert!(batches.iter().all(|b| b.reconciled));
+    }
+}
+
+#[test]
+fn minting_overflow_is_an_error() {
+    let res = compute_mint_amount(
+        Uint128::MAX,
+        Uint128::MAX,
+        Uint128::new(1),
+        RoundingPolicy::FavorUser,
+    );
+    assert!(res.is_err());
+}
+
+#[test]
+fn converting_near_the_limits() {
+    let unbonded = compute_unbond_amount(
+        Uint128::MAX,
+        Uint128::MAX,
+        Uint128::new(u128::MAX),
+        RoundingPolicy::FavorUser,
+    )
+    .unwrap();
+    assert_eq!(unbonded, Uint128::MAX);
+
+    let minted = compute_mint_amount(
+        Uint128::MAX,
+        Uint128::MAX,
+        Uint128::new(u128::MAX),
+        RoundingPolicy::FavorProtocol,
+    )
+    .unwrap();
+    assert_eq!(minted, Uint128::MAX);
+
+    let target =
+        compute_target_delegation_from_mining_power(Uint128::MAX, Uint128::MAX, Uint128::MAX)
+            .unwrap();
+    assert_eq!(target, Uint128::MAX);
+
+    let undelegations = compute_undelegations(
+        Uint128::new(u128::MAX - 1),
+        &delegations(&[u128::MAX / 2, u128::MAX / 2]),
+        "uxyz",
+    );
+    let total: u128 = undelegations.iter().map(|u| u.amount).sum();
+    assert_eq!(total, u128::MAX - 1);
+
+    let carry = Decimal256::percent(99);
+    let (fee, carry) = compute_fee_with_carry(Decimal::one(), Uint128::MAX, carry).unwrap();
+    assert_eq!(fee, Uint128::MAX);
+    assert_eq!(carry, Decimal256::percent(99));
+}
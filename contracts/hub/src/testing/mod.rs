@@ -1,4 +1,8 @@
+mod coordinator_querier;
 mod custom_querier;
 mod cw20_querier;
+mod fee_split_querier;
 mod helpers;
+mod math;
+mod pair_querier;
 mod tests;
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use cosmwasm_std::{to_binary, QuerierResult, SystemError};
+
+use pfc_steak::pair::{PairQueryMsg, PoolResponse, SimulationResponse};
+
+#[derive(Default)]
+pub(super) struct PairQuerier {
+    /// Mapping pair address to its reserves and LP supply
+    pub pools: HashMap<String, PoolResponse>,
+}
+
+impl PairQuerier {
+    pub fn handle_query(&self, contract_addr: &str, query: PairQueryMsg) -> QuerierResult {
+        let pool = self
+            .pools
+            .get(contract_addr)
+            .ok_or_else(|| SystemError::InvalidRequest {
+                error: format!("[mock] pool not set for pair `{}`", contract_addr),
+                request: Default::default(),
+            })
+            .unwrap();
+
+        match query {
+            PairQueryMsg::Pool {} => Ok(to_binary(pool).into()).into(),
+
+            // constant product, without commission
+            PairQueryMsg::Simulation { offer_asset } => {
+                let offer_pool = pool
+                    .assets
+                    .iter()
+                    .find(|asset| asset.info == offer_asset.info)
+                    .unwrap()
+                    .amount;
+                let ask_pool = pool
+                    .assets
+                    .iter()
+                    .find(|asset| asset.info != offer_asset.info)
+                    .unwrap()
+                    .amount;
+                let return_amount =
+                    ask_pool.multiply_ratio(offer_asset.amount, offer_pool + offer_asset.amount);
+
+                Ok(to_binary(&SimulationResponse {
+                    return_amount,
+                    spread_amount: offer_asset
+                        .amount
+                        .multiply_ratio(ask_pool, offer_pool)
+                        .saturating_sub(return_amount),
+                    commission_amount: Default::default(),
+                })
+                .into())
+                .into()
+            }
+        }
+    }
+}
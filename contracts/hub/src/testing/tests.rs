@@ -1,35 +1,70 @@
-use std::io::Read;
 use std::ops::Mul;
 use std::str::FromStr;
 
-use cosmos_sdk_proto::cosmos::staking::v1beta1::{MsgDelegate, MsgUndelegate};
+use cosmos_sdk_proto::cosmos::staking::v1beta1::MsgDelegate;
 use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Event, Order, OwnedDeps,
-    Reply, ReplyOn, StdError, SubMsg, SubMsgResponse, Uint128, Uint64, WasmMsg,
+    attr, from_binary, from_slice, to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Decimal256,
+    Event, GovMsg, Order, OwnedDeps, Reply, ReplyOn, Response, StdError, StdResult, SubMsg,
+    SubMsgResponse, Uint128, Uint64, VoteOption, WasmMsg,
 };
-use cw20::{Cw20ExecuteMsg, MinterResponse};
-use cw20_base::msg::InstantiateMsg as Cw20InstantiateMsg;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{Cw20ExecuteMsg, Logo, MinterResponse};
+use cw20_base::msg::{InstantiateMarketingInfo, InstantiateMsg as Cw20InstantiateMsg};
+use cw_storage_plus::Item;
 
+use pfc_steak::automation::{Action, AutomationExecuteMsg, Interval, TaskRequest};
+use pfc_steak::coordinator::ExecuteMsg as CoordinatorExecuteMsg;
 use pfc_steak::hub::{
-    Batch, CallbackMsg, ConfigResponse, ExecuteMsg, InstantiateMsg, PendingBatch, QueryMsg,
-    ReceiveMsg, StateResponse, UnbondRequest, UnbondRequestsByBatchResponseItem,
-    UnbondRequestsByUserResponseItem,
+    AdminAction, AdminActionRecord, AggregateResponse, AggregateResult, ArbStateResponse,
+    AutomationResponse, AutomationTask, AutomationTaskInfo, BalanceChange, BalanceHookMsg, Batch,
+    BatchFunder, BlockedValidator, BondCallback, BondCallbackMsg, BondResponseData, CallbackMsg,
+    CommitteeAction, CommitteeProposal, ConfigResponse, ConsolidatedClaim,
+    ConsolidatedClaimResponse, ContractInfoResponse, CurrentMiner, DailySnapshot, DelegateBallot,
+    DelegatedVote, DelegationDriftResponse, DenomChangePreviewResponse, EmergencyHeld,
+    EmergencyUndelegationResponse, EntropyContribution, EntropyContributor,
+    EntropyContributorResponse, EntropyRateLimit, ExecuteMsg, FeeCommitteeResponse, FeeType,
+    GuardianChange, HaltRecovery, HistoryAction, IdleRedelegation, InstantiateMsg,
+    IntegratorCallback, IntegratorCallbackMsg, IntegratorFlow, InvariantsResponse, LiquidityDay,
+    LiquidityScheduleResponse, LockStateResponse, LockTier, LockedBondResponseItem, Metrics,
+    MigrateMsg, MigrationReport, MinerParamsResponse, MinerStatusResponse, MiningAllocation,
+    MiningConfig, MiningEmissionResponse, MiningSummary, OwnershipResponse, PendingBatch,
+    PendingBatchRequestResponse, PendingBatchResponse, PendingReinvestResponse, PolStateResponse,
+    ProofHash, ProofRejection, PruneKind, QueryMsg, RebasingBalanceResponse,
+    RebasingSupplyResponse, ReceiveMsg, ReconcileBatchPreview, RewardConverterMsg,
+    RewardDenomsResponse, RewardDrip, RewardDripResponse, RoundingPolicy, RoundingPolicyResponse,
+    SafeMode, SimulateReconcileResponse, SlashingRecord, StateResponse, StateResponseV2,
+    SubmitBatchResponseData, SurplusPolicy, SurplusResponse, SweptBatch, UnbondRequest,
+    UnbondRequestDetails, UnbondRequestsByBatchResponseItem, UnbondRequestsByUserResponseItem,
+    UnbondingDelegationsResponse, UnbondingEntry, UnbondingTotals, UndelegationSlotsResponse,
+    UserHistoryEntry, UserHistoryResponse, UserTotals, ValidatorLoss, ValidatorMiningPower,
+    ValidatorPerformance, ValidatorRewards, ValidatorScore, ValidatorSetAction, ValidatorSetChange,
+    ValidatorSlots, ValidatorUnbonding, ValidatorVotingPower, VerifyProofResponse,
+    VotingPowerCapResponse, YieldPoolResponse, YieldPositionResponse,
 };
+use pfc_steak::pair::{Asset, AssetInfo, PairCw20HookMsg, PairExecuteMsg, PoolResponse};
 
 use crate::contract::{
-    execute, instantiate, reply, REPLY_INSTANTIATE_TOKEN, REPLY_REGISTER_RECEIVED_COINS,
+    execute, instantiate, migrate, query, reply, MAX_AGGREGATE_QUERIES,
+    REPLY_AUTOMATION_TASK_CREATED, REPLY_BALANCE_HOOK_FAILED, REPLY_FEE_FORWARD_DONE,
+    REPLY_INSTANTIATE_TOKEN, REPLY_INTEGRATOR_CALLBACK_DONE, REPLY_REGISTER_RECEIVED_COINS,
+};
+use crate::error::ContractError;
+use crate::execute::{
+    compute_miner_proof, create_difficulty_prefix, queue_unbond, update_difficulty,
+    EMERGENCY_UNDELEGATION_DELAY_SECONDS, GUARDIAN_CHANGE_DELAY_SECONDS, MAX_REWARD_DRIP_SECONDS,
 };
 use crate::helpers::{parse_coin, parse_received_fund};
 use crate::math::{
     compute_redelegations_for_rebalancing, compute_redelegations_for_removal,
     compute_target_delegation_from_mining_power, compute_undelegations,
 };
+use crate::migrations::{terra_batches, BatchTerra};
 use crate::state::State;
 use crate::types::{Coins, Delegation, Redelegation, RewardWithdrawal, Undelegation};
 
 use super::custom_querier::CustomQuerier;
-use super::helpers::{mock_dependencies, mock_env_at_timestamp, query_helper};
+use super::helpers::{mock_dependencies, mock_env_at_timestamp, query_helper, query_helper_env};
 
 //--------------------------------------------------------------------------------------------------
 // Test setup
@@ -61,7 +96,9 @@ fn setup_test() -> OwnedDeps<MockStorage, MockApi, CustomQuerier> {
                 "charlie".to_string(),
             ],
             label: None,
+            token_admin: None,
             marketing: None,
+            proof_hash: None,
         },
     )
     .unwrap();
@@ -82,7 +119,12 @@ fn setup_test() -> OwnedDeps<MockStorage, MockApi, CustomQuerier> {
                         minter: MOCK_CONTRACT_ADDR.to_string(),
                         cap: None
                     }),
-                    marketing: None,
+                    marketing: Some(InstantiateMarketingInfo {
+                        project: None,
+                        description: None,
+                        marketing: Some(MOCK_CONTRACT_ADDR.to_string()),
+                        logo: None,
+                    }),
                 })
                 .unwrap(),
                 funds: vec![],
@@ -141,7 +183,9 @@ fn setup_test_fee_split() -> OwnedDeps<MockStorage, MockApi, CustomQuerier> {
                 "charlie".to_string(),
             ],
             label: None,
+            token_admin: Some("gov_module".to_string()),
             marketing: None,
+            proof_hash: None,
         },
     )
     .unwrap();
@@ -151,7 +195,7 @@ fn setup_test_fee_split() -> OwnedDeps<MockStorage, MockApi, CustomQuerier> {
         res.messages[0],
         SubMsg::reply_on_success(
             CosmosMsg::Wasm(WasmMsg::Instantiate {
-                admin: Some("larry".to_string()),
+                admin: Some("gov_module".to_string()),
                 code_id: 69420,
                 msg: to_binary(&Cw20InstantiateMsg {
                     name: "Steak Token".to_string(),
@@ -162,7 +206,12 @@ fn setup_test_fee_split() -> OwnedDeps<MockStorage, MockApi, CustomQuerier> {
                         minter: MOCK_CONTRACT_ADDR.to_string(),
                         cap: None
                     }),
-                    marketing: None,
+                    marketing: Some(InstantiateMarketingInfo {
+                        project: None,
+                        description: None,
+                        marketing: Some(MOCK_CONTRACT_ADDR.to_string()),
+                        logo: None,
+                    }),
                 })
                 .unwrap(),
                 funds: vec![],
@@ -195,6 +244,22 @@ fn setup_test_fee_split() -> OwnedDeps<MockStorage, MockApi, CustomQuerier> {
     deps
 }
 
+/// Proof-of-work parameters of a freshly instantiated hub
+fn initial_mining_config() -> MiningConfig {
+    MiningConfig {
+        entropy: MOCK_CONTRACT_ADDR.to_string(),
+        entropy_draft: MOCK_CONTRACT_ADDR.to_string(),
+        difficulty: Uint64::new(1),
+        proof_hash: ProofHash::Sha256,
+        target_duration_floor: 20,
+        target_duration_ceiling: 300,
+        total_mining_power: Uint128::zero(),
+        miner_fee_share: Decimal::one(),
+        miner_fee_share_bps: 10_000,
+        miner: None,
+    }
+}
+
 //--------------------------------------------------------------------------------------------------
 // Execution
 //--------------------------------------------------------------------------------------------------
@@ -221,7 +286,11 @@ fn proper_instantiation() {
                 "alice".to_string(),
                 "bob".to_string(),
                 "charlie".to_string()
-            ]
+            ],
+            mining: initial_mining_config(),
+            coordinator: None,
+            token_admin: Some("larry".to_string()),
+            max_msgs_per_tx: None,
         }
     );
 
@@ -236,13 +305,15 @@ fn proper_instantiation() {
         },
     );
 
-    let res: PendingBatch = query_helper(deps.as_ref(), QueryMsg::PendingBatch {});
+    let res: PendingBatchResponse = query_helper(deps.as_ref(), QueryMsg::PendingBatch {});
     assert_eq!(
         res,
-        PendingBatch {
+        PendingBatchResponse {
             id: 1,
             usteak_to_burn: Uint128::zero(),
             est_unbond_start_time: 269200, // 10,000 + 259,200
+            next_submittable_time: 269200,
+            min_batch_size: Uint128::zero(),
         },
     );
     let deps_fee_split = setup_test_fee_split();
@@ -265,7 +336,11 @@ fn proper_instantiation() {
                 "alice".to_string(),
                 "bob".to_string(),
                 "charlie".to_string()
-            ]
+            ],
+            mining: initial_mining_config(),
+            coordinator: None,
+            token_admin: Some("gov_module".to_string()),
+            max_msgs_per_tx: None,
         }
     );
 }
@@ -321,10 +396,33 @@ fn bonding() {
     ]);
     deps.querier.set_cw20_total_supply("steak_token", 1000000);
 
+    // Within the same block, bonds are still priced at the block's first exchange rate
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user_2", &[Coin::new(12345, "uxyz")]),
+        ExecuteMsg::Bond { receiver: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "user_2".to_string(),
+                amount: Uint128::new(12345)
+            })
+            .unwrap(),
+            funds: vec![]
+        })
+    );
+
     // Charlie has the smallest amount of delegation, so the full deposit goes to him
+    let mut next_block = mock_env();
+    next_block.block.height += 1;
     let res = execute(
         deps.as_mut(),
-        mock_env(),
+        next_block,
         mock_info("user_2", &[Coin::new(12345, "uxyz")]),
         ExecuteMsg::Bond {
             receiver: Some("user_3".to_string()),
@@ -360,6 +458,18 @@ fn bonding() {
         }
     );
 
+    // The result is also returned as the response data, for calling contracts to read
+    let data: BondResponseData = from_binary(&res.data.unwrap()).unwrap();
+    assert_eq!(
+        data,
+        BondResponseData {
+            receiver: "user_3".to_string(),
+            amount_bonded: Uint128::new(12345),
+            usteak_minted: Uint128::new(12043),
+            exchange_rate: Decimal::from_ratio(1025000u128, 1000000u128),
+        }
+    );
+
     // Check the state after bonding
     deps.querier.set_staking_delegations(&[
         Delegation::new("alice", 341667, "uxyz"),
@@ -396,7 +506,7 @@ fn harvesting() {
     let res = execute(
         deps.as_mut(),
         harvest_env.clone(),
-        mock_info(&harvest_env.contract.address.to_string(), &[]),
+        mock_info(harvest_env.contract.address.as_ref(), &[]),
         ExecuteMsg::Harvest {},
     )
     .unwrap();
@@ -451,1979 +561,11378 @@ fn harvesting() {
 }
 
 #[test]
-fn registering_unlocked_coins() {
+fn whitelisting_harvesters() {
     let mut deps = setup_test();
-    let state = State::default();
 
-    // After withdrawing staking rewards, we parse the `coin_received` event to find the received amounts
-    let event = Event::new("coin_received")
-        .add_attribute("receiver", MOCK_CONTRACT_ADDR.to_string())
-        .add_attribute("amount", "123ukrw,234uxyz,345uusd,69420ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B");
+    deps.querier
+        .set_staking_delegations(&[Delegation::new("alice", 1000000, "uxyz")]);
 
-    reply(
+    let err = execute(
         deps.as_mut(),
         mock_env(),
-        Reply {
-            id: 2,
-            result: cosmwasm_std::SubMsgResult::Ok(SubMsgResponse {
-                events: vec![event],
-                data: None,
-            }),
+        mock_info("keeper", &[]),
+        ExecuteMsg::Harvest {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::HarvestOnly {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::AddHarvester {
+            address: "keeper".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::AddHarvester {
+            address: "keeper".to_string(),
         },
     )
     .unwrap();
 
-    // Unlocked coins in contract state should have been updated
-    let unlocked_coins = state.unlocked_coins.load(deps.as_ref().storage).unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::AddHarvester {
+            address: "keeper".to_string(),
+        },
+    )
+    .unwrap_err();
     assert_eq!(
-        unlocked_coins,
-        vec![
-            Coin::new(123, "ukrw"),
-            Coin::new(234, "uxyz"),
-            Coin::new(345, "uusd"),
-            Coin::new(
-                69420,
-                "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B"
-            ),
-        ]
+        err,
+        ContractError::HarvesterAlreadyRegistered {
+            address: "keeper".to_string()
+        }
+    );
+
+    let res: Vec<String> = query_helper(deps.as_ref(), QueryMsg::Harvesters {});
+    assert_eq!(res, vec!["keeper".to_string()]);
+
+    // A whitelisted harvester can trigger the compound, and is credited for it
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("keeper", &[]),
+        ExecuteMsg::Harvest {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(res.events.len(), 1);
+    assert_eq!(res.events[0].ty, "steakhub/harvest_triggered");
+    assert!(res.events[0]
+        .attributes
+        .contains(&attr("triggered_by", "keeper")));
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::RemoveHarvester {
+            address: "keeper".to_string(),
+        },
+    )
+    .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::RemoveHarvester {
+            address: "keeper".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::HarvesterNotRegistered {
+            address: "keeper".to_string()
+        }
     );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("keeper", &[]),
+        ExecuteMsg::Harvest {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::HarvestOnly {});
 }
 
 #[test]
-fn reinvesting() {
+fn automating_upkeep() {
     let mut deps = setup_test();
-    let state = State::default();
 
-    deps.querier.set_staking_delegations(&[
-        Delegation::new("alice", 333334, "uxyz"),
-        Delegation::new("bob", 333333, "uxyz"),
-        Delegation::new("charlie", 333333, "uxyz"),
-    ]);
-    state
-        .prev_denom
-        .save(deps.as_mut().storage, &Uint128::from(0_u32))
-        .unwrap();
     deps.querier
-        .set_bank_balances(&[Coin::new(234u128, "uxyz")]);
+        .set_staking_delegations(&[Delegation::new("alice", 1000000, "uxyz")]);
 
-    // After the swaps, `unlocked_coins` should contain only uxyz and unknown denoms
-    state
-        .unlocked_coins
-        .save(
-            deps.as_mut().storage,
-            &vec![
-                Coin::new(234, "uxyz"),
-                Coin::new(
-                    69420,
-                    "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B",
-                ),
-            ],
+    let create = |task: AutomationTask| ExecuteMsg::CreateAutomationTask {
+        task,
+        interval: Interval::Block(1000),
+        gas_limit: Some(1_000_000),
+    };
+    let register = |deps: &mut OwnedDeps<_, _, _>, task_hash: &str| {
+        reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: REPLY_AUTOMATION_TASK_CREATED,
+                result: cosmwasm_std::SubMsgResult::Ok(SubMsgResponse {
+                    events: vec![Event::new("wasm")
+                        .add_attribute("_contract_address", "croncat")
+                        .add_attribute("task_hash", task_hash)],
+                    data: None,
+                }),
+            },
         )
-        .unwrap();
+        .unwrap()
+    };
 
-    let modifier = 1_000_000_000_000_000_000_u128;
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        create(AutomationTask::Harvest),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::AutomationNotConfigured {});
 
-    state
-        .total_mining_power
-        .save(deps.as_mut().storage, &Uint128::from(15_u128.mul(modifier)))
-        .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::UpdateAutomation {
+            contract: Some("croncat".to_string()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
 
-    state
-        .validator_mining_powers
-        .save(
-            deps.as_mut().storage,
-            "alice".to_string(),
-            &5_u128.mul(modifier).into(),
-        )
-        .unwrap();
-    state
-        .validator_mining_powers
-        .save(
-            deps.as_mut().storage,
-            "bob".to_string(),
-            &5_u128.mul(modifier).into(),
-        )
-        .unwrap();
-    state
-        .validator_mining_powers
-        .save(
-            deps.as_mut().storage,
-            "charlie".to_string(),
-            &5_u128.mul(modifier).into(),
-        )
-        .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateAutomation {
+            contract: Some("croncat".to_string()),
+        },
+    )
+    .unwrap();
 
-    let env = mock_env();
-    // Bob has the smallest amount of delegations, so all proceeds go to him
+    // Creating a task forwards the funds to the automation contract, with an action calling back
+    // into the hub
     let res = execute(
         deps.as_mut(),
-        env.clone(),
-        mock_info(MOCK_CONTRACT_ADDR, &[]),
-        ExecuteMsg::Callback(CallbackMsg::Reinvest {}),
+        mock_env(),
+        mock_info("larry", &[Coin::new(5000, "uxyz")]),
+        create(AutomationTask::Harvest),
     )
     .unwrap();
-
-    // decode first message as to MsgUndelegate
-    let decoded_message =
-        if let CosmosMsg::Stargate { type_url, value } = res.messages[0].msg.clone() {
-            // assert_eq!(type_url, "/liquidstaking.staking.v1beta1.MsgDelegate");
-            let msg_decoded: MsgDelegate = prost::Message::decode(value.as_slice()).unwrap();
-            // assert_eq!(msg_decoded.validator_address, "bob");
-            Some(msg_decoded)
-        } else {
-            None
-        };
-    // decode all messages to MsgUndelegate and transpose as result
-    let decoded_messages = res
-        .messages
-        .iter()
-        .map(|msg| {
-            if let CosmosMsg::Stargate { type_url, value } = msg.msg.clone() {
-                // assert_eq!(type_url, "/liquidstaking.staking.v1beta1.MsgDelegate");
-                let msg_decoded: MsgDelegate = prost::Message::decode(value.as_slice()).unwrap();
-                // assert_eq!(msg_decoded.validator_address, "bob");
-                Some(msg_decoded)
-            } else {
-                None
-            }
-        })
-        .filter(Option::is_some)
-        .collect::<Option<Vec<MsgDelegate>>>()
-        .unwrap();
-
-    assert_eq!(res.messages.len(), 2);
+    assert_eq!(res.messages.len(), 1);
     assert_eq!(
         res.messages[0],
-        SubMsg {
-            id: 0,
-            msg: Delegation::new("bob", 234 - 23, "uxyz")
-                .to_cosmos_msg(env.contract.address.to_string())
+        SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "croncat".to_string(),
+                msg: to_binary(&AutomationExecuteMsg::CreateTask {
+                    task: Box::new(TaskRequest {
+                        interval: Interval::Block(1000),
+                        stop_on_fail: false,
+                        actions: vec![Action {
+                            msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                                msg: to_binary(&ExecuteMsg::Harvest {}).unwrap(),
+                                funds: vec![],
+                            }),
+                            gas_limit: Some(1_000_000),
+                        }],
+                    })
+                })
                 .unwrap(),
-            gas_limit: None,
-            reply_on: ReplyOn::Never
-        },
-        "bob"
+                funds: vec![Coin::new(5000, "uxyz")],
+            }),
+            REPLY_AUTOMATION_TASK_CREATED,
+        )
     );
-    let send_msg = BankMsg::Send {
-        to_address: "the_fee_man".into(),
-        amount: vec![Coin::new(23u128, "uxyz")],
-    };
+    register(&mut deps, "hash_harvest");
+
+    let res: AutomationResponse = query_helper(deps.as_ref(), QueryMsg::Automation {});
     assert_eq!(
-        res.messages[1],
-        SubMsg {
-            id: 0,
-            msg: CosmosMsg::Bank(send_msg),
-            gas_limit: None,
-            reply_on: ReplyOn::Never
-        },
-        "fee"
+        res,
+        AutomationResponse {
+            contract: Some("croncat".to_string()),
+            tasks: vec![AutomationTaskInfo {
+                task: AutomationTask::Harvest,
+                task_hash: "hash_harvest".to_string(),
+            }],
+        }
     );
 
-    // Storage should have been updated
-    let unlocked_coins = state.unlocked_coins.load(deps.as_ref().storage).unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        create(AutomationTask::Harvest),
+    )
+    .unwrap_err();
     assert_eq!(
-        unlocked_coins,
-        vec![Coin::new(
-            69420,
-            "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B"
-        )],
-        "unlocked_coins"
+        err,
+        ContractError::AutomationTaskExists {
+            task: "harvest".to_string()
+        }
     );
-}
 
-#[test]
-fn reinvesting_with_mining() {
-    let mut deps = setup_test();
-    let state = State::default();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateAutomation { contract: None },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::AutomationTasksRegistered {});
 
-    deps.querier.set_staking_delegations(&[
-        Delegation::new("alice", 333334, "uxyz"),
-        Delegation::new("bob", 333333, "uxyz"),
-        Delegation::new("charlie", 333333, "uxyz"),
-    ]);
-    state
-        .prev_denom
-        .save(deps.as_mut().storage, &Uint128::from(0_u32))
-        .unwrap();
-    deps.querier
-        .set_bank_balances(&[Coin::new(234u128, "uxyz")]);
+    // The automation contract is allowed to harvest
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("croncat", &[]),
+        ExecuteMsg::Harvest {},
+    )
+    .unwrap();
+    assert!(res.events[0]
+        .attributes
+        .contains(&attr("triggered_by", "croncat")));
 
-    // After the swaps, `unlocked_coins` should contain only uxyz and unknown denoms
-    state
-        .unlocked_coins
-        .save(
-            deps.as_mut().storage,
-            &vec![
-                Coin::new(234, "uxyz"),
-                Coin::new(
-                    69420,
-                    "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B",
-                ),
-            ],
-        )
-        .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[Coin::new(2000, "uxyz")]),
+        ExecuteMsg::RefillAutomationTask {
+            task: AutomationTask::Harvest,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "croncat".to_string(),
+            msg: to_binary(&AutomationExecuteMsg::RefillTaskBalance {
+                task_hash: "hash_harvest".to_string()
+            })
+            .unwrap(),
+            funds: vec![Coin::new(2000, "uxyz")],
+        })
+    );
 
-    let modifier = 1_000_000_000_000_000_000_u128;
+    // Tasks the automation network ends on its own are forgotten
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::AutomationTaskEnded {
+            task_hash: "hash_harvest".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::AutomationOnly {});
 
-    state
-        .total_mining_power
-        .save(deps.as_mut().storage, &Uint128::from(15_u128.mul(modifier)))
-        .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("croncat", &[]),
+        ExecuteMsg::AutomationTaskEnded {
+            task_hash: "hash_harvest".to_string(),
+        },
+    )
+    .unwrap();
 
-    state
-        .validator_mining_powers
-        .save(
-            deps.as_mut().storage,
-            "alice".to_string(),
-            &4_u128.mul(modifier).into(),
-        )
-        .unwrap();
-    state
-        .validator_mining_powers
-        .save(
-            deps.as_mut().storage,
-            "bob".to_string(),
-            &4_u128.mul(modifier).into(),
-        )
-        .unwrap();
-    state
-        .validator_mining_powers
-        .save(
-            deps.as_mut().storage,
-            "charlie".to_string(),
-            &7_u128.mul(modifier).into(),
-        )
-        .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::RefillAutomationTask {
+            task: AutomationTask::Harvest,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::AutomationTaskNotFound {
+            task: "harvest".to_string()
+        }
+    );
 
-    let env = mock_env();
-    // Bob has the smallest amount of delegations, so all proceeds go to him
-    let res = execute(
+    // Removing a task tells the automation contract to stop it
+    execute(
         deps.as_mut(),
-        env.clone(),
-        mock_info(MOCK_CONTRACT_ADDR, &[]),
-        ExecuteMsg::Callback(CallbackMsg::Reinvest {}),
+        mock_env(),
+        mock_info("larry", &[Coin::new(5000, "uxyz")]),
+        create(AutomationTask::SubmitBatch),
     )
     .unwrap();
+    register(&mut deps, "hash_submit_batch");
 
-    // decode first message as to MsgUndelegate
-    let decoded_message =
-        if let CosmosMsg::Stargate { type_url, value } = res.messages[0].msg.clone() {
-            // assert_eq!(type_url, "/liquidstaking.staking.v1beta1.MsgDelegate");
-            let msg_decoded: MsgDelegate = prost::Message::decode(value.as_slice()).unwrap();
-            // assert_eq!(msg_decoded.validator_address, "bob");
-            Some(msg_decoded)
-        } else {
-            None
-        };
-    // decode all messages to MsgUndelegate and transpose as result
-    let decoded_messages = res
-        .messages
-        .iter()
-        .map(|msg| {
-            if let CosmosMsg::Stargate { type_url, value } = msg.msg.clone() {
-                // assert_eq!(type_url, "/liquidstaking.staking.v1beta1.MsgDelegate");
-                let msg_decoded: MsgDelegate = prost::Message::decode(value.as_slice()).unwrap();
-                // assert_eq!(msg_decoded.validator_address, "bob");
-                Some(msg_decoded)
-            } else {
-                None
-            }
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::RemoveAutomationTask {
+            task: AutomationTask::SubmitBatch,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "croncat".to_string(),
+            msg: to_binary(&AutomationExecuteMsg::RemoveTask {
+                task_hash: "hash_submit_batch".to_string()
+            })
+            .unwrap(),
+            funds: vec![],
         })
-        .filter(Option::is_some)
-        .collect::<Option<Vec<MsgDelegate>>>()
+    );
+
+    let res: AutomationResponse = query_helper(deps.as_ref(), QueryMsg::Automation {});
+    assert!(res.tasks.is_empty());
+}
+
+#[test]
+fn scoring_validators() {
+    let mut deps = setup_test();
+
+    let delegations = [
+        Delegation::new("alice", 1000000, "uxyz"),
+        Delegation::new("bob", 1000000, "uxyz"),
+        Delegation::new("charlie", 1000000, "uxyz"),
+    ];
+    let harvest = |deps: &mut OwnedDeps<MockStorage, MockApi, CustomQuerier>, time: u64| {
+        execute(
+            deps.as_mut(),
+            mock_env_at_timestamp(time),
+            mock_info(MOCK_CONTRACT_ADDR, &[]),
+            ExecuteMsg::Harvest {},
+        )
         .unwrap();
+    };
 
-    assert_eq!(res.messages.len(), 2);
+    // The first harvest only starts the observation period
+    deps.querier.set_staking_delegations(&delegations);
+    harvest(&mut deps, 10000);
+
+    deps.querier.set_staking_rewards(&[
+        ("alice", Coin::new(100, "uxyz")),
+        ("bob", Coin::new(50, "uxyz")),
+        ("charlie", Coin::new(100, "uxyz")),
+    ]);
+    deps.querier.set_staking_delegations(&delegations);
+    harvest(&mut deps, 96400);
+
+    // Later observations are smoothed: alice is at 0.0001 * 80% + 0.0002 * 20%
+    deps.querier.set_staking_rewards(&[
+        ("alice", Coin::new(200, "uxyz")),
+        ("bob", Coin::new(50, "uxyz")),
+        ("charlie", Coin::new(100, "uxyz")),
+    ]);
+    deps.querier.set_staking_delegations(&delegations);
+    harvest(&mut deps, 182800);
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(182800),
+        mock_info("jake", &[]),
+        ExecuteMsg::ReportSlashing {
+            validator: "bob".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(182800),
+        mock_info("larry", &[]),
+        ExecuteMsg::ReportSlashing {
+            validator: "dave".to_string(),
+        },
+    )
+    .unwrap_err();
     assert_eq!(
-        res.messages[0],
-        SubMsg {
-            id: 0,
-            msg: Delegation::new("charlie", 234 - 23, "uxyz")
-                .to_cosmos_msg(env.contract.address.to_string())
-                .unwrap(),
-            gas_limit: None,
-            reply_on: ReplyOn::Never
+        err,
+        ContractError::ValidatorNotWhitelisted {
+            validator: "dave".to_string()
+        }
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(182800),
+        mock_info("larry", &[]),
+        ExecuteMsg::ReportSlashing {
+            validator: "bob".to_string(),
         },
-        "charlie"
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(182800),
+        mock_info("larry", &[]),
+        ExecuteMsg::PauseValidator {
+            validator: "charlie".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap();
+
+    let res: Vec<ValidatorScore> = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(182800),
+        QueryMsg::ValidatorScores {},
     );
-    let send_msg = BankMsg::Send {
-        to_address: "the_fee_man".into(),
-        amount: vec![Coin::new(23u128, "uxyz")],
-    };
     assert_eq!(
-        res.messages[1],
-        SubMsg {
-            id: 0,
-            msg: CosmosMsg::Bank(send_msg),
-            gas_limit: None,
-            reply_on: ReplyOn::Never
-        },
-        "fee"
+        res,
+        vec![
+            ValidatorScore {
+                address: "alice".to_string(),
+                reward_rate: Decimal::from_str("0.00012").unwrap(),
+                pauses: 0,
+                slashes: 0,
+                score: Decimal::one(),
+            },
+            ValidatorScore {
+                address: "bob".to_string(),
+                reward_rate: Decimal::from_str("0.00005").unwrap(),
+                pauses: 0,
+                slashes: 1,
+                score: Decimal::from_str("0.208333333333333333").unwrap(),
+            },
+            ValidatorScore {
+                address: "charlie".to_string(),
+                reward_rate: Decimal::from_str("0.0001").unwrap(),
+                pauses: 1,
+                slashes: 0,
+                score: Decimal::from_str("0.749999999999999999").unwrap(),
+            },
+        ]
     );
 
-    // Storage should have been updated
-    let unlocked_coins = state.unlocked_coins.load(deps.as_ref().storage).unwrap();
+    // Incidents stop counting once they leave the 30 day window
+    let res: Vec<ValidatorScore> = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(182800 + 2592001),
+        QueryMsg::ValidatorScores {},
+    );
+    assert_eq!(res[1].slashes, 0);
+    assert_eq!(res[2].pauses, 0);
     assert_eq!(
-        unlocked_coins,
-        vec![Coin::new(
-            69420,
-            "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B"
-        )],
-        "unlocked_coins"
+        res[2].score,
+        Decimal::from_str("0.833333333333333333").unwrap()
     );
 }
 
 #[test]
-fn reinvesting_fee_split() {
-    let mut deps = setup_test_fee_split();
-    let state = State::default();
-    let env = mock_env();
-    deps.querier.set_staking_delegations(&[
-        Delegation::new("alice", 333334, "uxyz"),
-        Delegation::new("bob", 333333, "uxyz"),
-        Delegation::new("charlie", 333333, "uxyz"),
-    ]);
-    state
-        .prev_denom
-        .save(deps.as_mut().storage, &Uint128::from(0_u32))
-        .unwrap();
-    deps.querier
-        .set_bank_balances(&[Coin::new(234u128, "uxyz")]);
+fn redelegating_idle_validators() {
+    let mut deps = setup_test();
 
-    // After the swaps, `unlocked_coins` should contain only uxyz and unknown denoms
-    state
-        .unlocked_coins
-        .save(
-            deps.as_mut().storage,
-            &vec![
-                Coin::new(234, "uxyz"),
-                Coin::new(
-                    69420,
-                    "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B",
-                ),
-            ],
+    let delegations = [
+        Delegation::new("alice", 1000000, "uxyz"),
+        Delegation::new("bob", 1200000, "uxyz"),
+        Delegation::new("charlie", 1000000, "uxyz"),
+    ];
+    let harvest = |deps: &mut OwnedDeps<MockStorage, MockApi, CustomQuerier>, time: u64| {
+        deps.querier.set_staking_rewards(&[
+            ("alice", Coin::new(100, "uxyz")),
+            ("bob", Coin::new(100, "uxyz")),
+            ("charlie", Coin::new(0, "uxyz")),
+        ]);
+        deps.querier.set_staking_delegations(&delegations);
+        execute(
+            deps.as_mut(),
+            mock_env_at_timestamp(time),
+            mock_info(MOCK_CONTRACT_ADDR, &[]),
+            ExecuteMsg::Harvest {},
         )
         .unwrap();
+    };
 
-    let modifier = 1_000_000_000_000_000_000_u128;
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("jake", &[]),
+        ExecuteMsg::RedelegateIdle {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::IdleRedelegationNotConfigured {});
 
-    state
-        .total_mining_power
-        .save(deps.as_mut().storage, &Uint128::from(15_u128.mul(modifier)))
-        .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("jake", &[]),
+        ExecuteMsg::UpdateIdleRedelegation {
+            zero_reward_harvests: 2,
+            min_idle_period: 172800,
+            max_amount: Uint128::new(300000),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
 
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateIdleRedelegation {
+            zero_reward_harvests: 0,
+            min_idle_period: 172800,
+            max_amount: Uint128::new(300000),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ZeroIdleHarvests {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateIdleRedelegation {
+            zero_reward_harvests: 2,
+            min_idle_period: 0,
+            max_amount: Uint128::new(300000),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ZeroIdlePeriod {});
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateIdleRedelegation {
+            zero_reward_harvests: 2,
+            min_idle_period: 172800,
+            max_amount: Uint128::new(300000),
+        },
+    )
+    .unwrap();
+
+    // The first harvest only starts the observation period, so charlie is idle after the third
+    harvest(&mut deps, 10000);
+    harvest(&mut deps, 96400);
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(96400),
+        mock_info("jake", &[]),
+        ExecuteMsg::RedelegateIdle {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoIdleValidators {});
+
+    harvest(&mut deps, 182800);
+
+    // Observations older than the idle period no longer count
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(355601),
+        mock_info("jake", &[]),
+        ExecuteMsg::RedelegateIdle {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoIdleValidators {});
+
+    // Only 300000 of charlie's delegation is moved, evening out alice and bob at 1250000 each
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(182800),
+        mock_info("jake", &[]),
+        ExecuteMsg::RedelegateIdle {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::reply_on_success(
+                Redelegation::new("charlie", "alice", 250000, "uxyz")
+                    .to_cosmos_msg(MOCK_CONTRACT_ADDR.to_string())
+                    .unwrap(),
+                REPLY_REGISTER_RECEIVED_COINS
+            ),
+            SubMsg::reply_on_success(
+                Redelegation::new("charlie", "bob", 50000, "uxyz")
+                    .to_cosmos_msg(MOCK_CONTRACT_ADDR.to_string())
+                    .unwrap(),
+                REPLY_REGISTER_RECEIVED_COINS
+            ),
+        ]
+    );
+
+    // Charlie is paused so rewards are no longer reinvested with it
+    let state = State::default();
+    assert_eq!(
+        state.validators_active.load(deps.as_ref().storage).unwrap(),
+        vec!["alice".to_string(), "bob".to_string()]
+    );
+    let res: Vec<ValidatorScore> = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(182800),
+        QueryMsg::ValidatorScores {},
+    );
+    assert_eq!(res[2].pauses, 1);
+
+    // Unpausing charlie clears the zero-reward harvests that got it paused
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(182800),
+        mock_info("larry", &[]),
+        ExecuteMsg::UnPauseValidator {
+            validator: "charlie".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(182800),
+        mock_info("jake", &[]),
+        ExecuteMsg::RedelegateIdle {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoIdleValidators {});
+
+    // Back-to-back harvests don't make it idle again before the idle period has passed
+    harvest(&mut deps, 182801);
+    harvest(&mut deps, 182802);
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(182802),
+        mock_info("jake", &[]),
+        ExecuteMsg::RedelegateIdle {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoIdleValidators {});
+
+    // A validator that starts earning again is no longer idle
+    deps.querier.set_staking_rewards(&[
+        ("alice", Coin::new(100, "uxyz")),
+        ("bob", Coin::new(100, "uxyz")),
+        ("charlie", Coin::new(100, "uxyz")),
+    ]);
+    deps.querier.set_staking_delegations(&delegations);
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(269200),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Harvest {},
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(269200),
+        mock_info("jake", &[]),
+        ExecuteMsg::RedelegateIdle {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoIdleValidators {});
+
+    // A streak recorded before its start was tracked doesn't count until it is observed again
+    let state = State::default();
     state
-        .validator_mining_powers
-        .save(
-            deps.as_mut().storage,
-            "alice".to_string(),
-            &1_u128.mul(modifier).into(),
-        )
-        .unwrap();
-    state
-        .validator_mining_powers
+        .validator_performance
         .save(
             deps.as_mut().storage,
-            "bob".to_string(),
-            &12_u128.mul(modifier).into(),
+            "charlie".to_string(),
+            &ValidatorPerformance {
+                last_observed: 269200,
+                zero_reward_harvests: 5,
+                idle_since: None,
+                ..ValidatorPerformance::default()
+            },
         )
         .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(269200),
+        mock_info("jake", &[]),
+        ExecuteMsg::RedelegateIdle {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoIdleValidators {});
+
+    // A config saved before `min_idle_period` was added is not used until it is updated
     state
-        .validator_mining_powers
+        .idle_redelegation
         .save(
             deps.as_mut().storage,
-            "charlie".to_string(),
-            &2_u128.mul(modifier).into(),
+            &IdleRedelegation {
+                zero_reward_harvests: 2,
+                min_idle_period: 0,
+                max_amount: Uint128::new(300000),
+            },
         )
         .unwrap();
-
-    // Bob has the smallest amount of delegations, so all proceeds go to him
-    let res = execute(
+    let err = execute(
         deps.as_mut(),
-        env.clone(),
-        mock_info(MOCK_CONTRACT_ADDR, &[]),
-        ExecuteMsg::Callback(CallbackMsg::Reinvest {}),
+        mock_env_at_timestamp(269200),
+        mock_info("jake", &[]),
+        ExecuteMsg::RedelegateIdle {},
     )
-    .unwrap();
+    .unwrap_err();
+    assert_eq!(err, ContractError::IdleRedelegationNotConfigured {});
+}
 
-    assert_eq!(res.messages.len(), 2);
-    assert_eq!(
-        res.messages[0],
-        SubMsg {
-            id: 0,
-            msg: Delegation::new("bob", 234 - 23, "uxyz")
-                .to_cosmos_msg(env.contract.address.to_string())
-                .unwrap(),
-            gas_limit: None,
-            reply_on: ReplyOn::Never
-        }
-    );
-    let send_msg = pfc_fee_split::fee_split_msg::ExecuteMsg::Deposit { flush: false };
+#[test]
+fn registering_unlocked_coins() {
+    let mut deps = setup_test();
+    let state = State::default();
 
-    assert_eq!(
-        res.messages[1],
-        SubMsg {
-            id: 0,
-            msg: send_msg
-                .into_cosmos_msg("fee_split_contract", vec![Coin::new(23u128, "uxyz")])
-                .unwrap(),
-            gas_limit: None,
-            reply_on: ReplyOn::Never
-        }
-    );
+    // After withdrawing staking rewards, we parse the `coin_received` event to find the received amounts
+    let event = Event::new("coin_received")
+        .add_attribute("receiver", MOCK_CONTRACT_ADDR.to_string())
+        .add_attribute("amount", "123ukrw,234uxyz,345uusd,69420ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B");
 
-    // Storage should have been updated
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 2,
+            result: cosmwasm_std::SubMsgResult::Ok(SubMsgResponse {
+                events: vec![event],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+
+    // Unlocked coins in contract state should have been updated
     let unlocked_coins = state.unlocked_coins.load(deps.as_ref().storage).unwrap();
     assert_eq!(
         unlocked_coins,
-        vec![Coin::new(
-            69420,
-            "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B"
-        )],
+        vec![
+            Coin::new(
+                69420,
+                "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B"
+            ),
+            Coin::new(123, "ukrw"),
+            Coin::new(345, "uusd"),
+            Coin::new(234, "uxyz"),
+        ]
     );
 }
 
 #[test]
-fn queuing_unbond() {
+fn registering_coins_from_merged_events() {
     let mut deps = setup_test();
     let state = State::default();
 
-    // Only Steak token is accepted for unbonding requests
-    let err = execute(
+    // When the chain merges the events of one message, a single event holds several receivers
+    let event = Event::new("coin_received")
+        .add_attribute("receiver", "someone_else")
+        .add_attribute("amount", "1000uxyz")
+        .add_attribute("receiver", MOCK_CONTRACT_ADDR.to_string())
+        .add_attribute("amount", "234uxyz,5factory/creator/ucoin")
+        .add_attribute("receiver", MOCK_CONTRACT_ADDR.to_string())
+        .add_attribute("amount", "6uxyz");
+
+    reply(
         deps.as_mut(),
         mock_env(),
-        mock_info("random_token", &[]),
-        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
-            sender: "hacker".to_string(),
-            amount: Uint128::new(69420),
-            msg: to_binary(&ReceiveMsg::QueueUnbond { receiver: None }).unwrap(),
-        }),
+        Reply {
+            id: 2,
+            result: cosmwasm_std::SubMsgResult::Ok(SubMsgResponse {
+                events: vec![event],
+                data: None,
+            }),
+        },
     )
-    .unwrap_err();
+    .unwrap();
 
+    let unlocked_coins = state.unlocked_coins.load(deps.as_ref().storage).unwrap();
     assert_eq!(
-        err,
-        StdError::generic_err("expecting Steak token, received random_token")
+        unlocked_coins,
+        vec![
+            Coin::new(5, "factory/creator/ucoin"),
+            Coin::new(240, "uxyz")
+        ]
     );
+}
 
-    // User 1 creates an unbonding request before `est_unbond_start_time` is reached. The unbond
-    // request is saved, but not the pending batch is not submitted for unbonding
-    let res = execute(
-        deps.as_mut(),
-        mock_env_at_timestamp(12345), // est_unbond_start_time = 269200
-        mock_info("steak_token", &[]),
-        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
-            sender: "user_1".to_string(),
-            amount: Uint128::new(23456),
-            msg: to_binary(&ReceiveMsg::QueueUnbond { receiver: None }).unwrap(),
-        }),
-    )
-    .unwrap();
+#[test]
+fn tracking_rewards_per_validator() {
+    let mut deps = setup_test();
 
-    assert_eq!(res.messages.len(), 0);
+    // Each withdrawal's `withdraw_rewards` event names the validator the rewards came from
+    let withdrawn = |validator: &str, amount: &str| {
+        Event::new("withdraw_rewards")
+            .add_attribute("amount", amount)
+            .add_attribute("validator", validator)
+            .add_attribute("delegator", MOCK_CONTRACT_ADDR)
+    };
+    for events in [
+        vec![
+            withdrawn("alice", "123uxyz"),
+            withdrawn("bob", "45uxyz,6uusd"),
+            // no rewards appear as an empty amount
+            withdrawn("charlie", ""),
+        ],
+        vec![withdrawn("alice", "77uxyz")],
+    ] {
+        reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: REPLY_REGISTER_RECEIVED_COINS,
+                result: cosmwasm_std::SubMsgResult::Ok(SubMsgResponse { events, data: None }),
+            },
+        )
+        .unwrap();
+    }
 
-    // User 2 creates an unbonding request after `est_unbond_start_time` is reached. The unbond
-    // request is saved, and the pending is automatically submitted for unbonding
-    let res = execute(
+    let res: Vec<ValidatorRewards> = query_helper(deps.as_ref(), QueryMsg::ValidatorRewards {});
+    assert_eq!(
+        res,
+        vec![
+            ValidatorRewards {
+                validator: "alice".to_string(),
+                rewards: vec![Coin::new(200, "uxyz")],
+                rebates: Uint128::zero(),
+            },
+            ValidatorRewards {
+                validator: "bob".to_string(),
+                rewards: vec![Coin::new(6, "uusd"), Coin::new(45, "uxyz")],
+                rebates: Uint128::zero(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn depositing_commission_rebates() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    let err = execute(
         deps.as_mut(),
-        mock_env_at_timestamp(269201), // est_unbond_start_time = 269200
-        mock_info("steak_token", &[]),
-        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
-            sender: "user_2".to_string(),
-            amount: Uint128::new(69420),
-            msg: to_binary(&ReceiveMsg::QueueUnbond {
-                receiver: Some("user_3".to_string()),
-            })
-            .unwrap(),
-        }),
+        mock_env(),
+        mock_info("dave", &[Coin::new(100, "uxyz")]),
+        ExecuteMsg::DepositRebate {
+            validator: "dave".to_string(),
+        },
     )
-    .unwrap();
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ValidatorNotWhitelisted {
+            validator: "dave".to_string()
+        }
+    );
 
-    assert_eq!(res.messages.len(), 1);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("charlie", &[Coin::new(100, "uusd")]),
+        ExecuteMsg::DepositRebate {
+            validator: "charlie".to_string(),
+        },
+    )
+    .unwrap_err();
     assert_eq!(
-        res.messages[0],
-        SubMsg {
-            id: 0,
-            msg: CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
-                msg: to_binary(&ExecuteMsg::SubmitBatch {}).unwrap(),
-                funds: vec![]
-            }),
-            gas_limit: None,
-            reply_on: ReplyOn::Never
+        err,
+        ContractError::UnexpectedDenom {
+            expected: "uxyz".to_string(),
+            received: "uusd".to_string()
         }
     );
 
-    // The users' unbonding requests should have been saved
-    let ubr1 = state
-        .unbond_requests
-        .load(deps.as_ref().storage, (1u64, &Addr::unchecked("user_1")))
-        .unwrap();
-    let ubr2 = state
-        .unbond_requests
-        .load(deps.as_ref().storage, (1u64, &Addr::unchecked("user_3")))
+    for amount in [100, 50] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("charlie_operator", &[Coin::new(amount, "uxyz")]),
+            ExecuteMsg::DepositRebate {
+                validator: "charlie".to_string(),
+            },
+        )
         .unwrap();
+    }
 
+    let res: Vec<ValidatorRewards> = query_helper(deps.as_ref(), QueryMsg::ValidatorRewards {});
     assert_eq!(
-        ubr1,
-        UnbondRequest {
-            id: 1,
-            user: Addr::unchecked("user_1"),
-            shares: Uint128::new(23456)
-        }
+        res,
+        vec![ValidatorRewards {
+            validator: "charlie".to_string(),
+            rewards: vec![],
+            rebates: Uint128::new(150),
+        }]
     );
+
+    // The next harvest measures rewards from a balance that leaves the rebates out, so they are
+    // reinvested along with the rewards
+    deps.querier.set_bank_balances(&[Coin::new(1150, "uxyz")]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Harvest {},
+    )
+    .unwrap();
     assert_eq!(
-        ubr2,
-        UnbondRequest {
-            id: 1,
-            user: Addr::unchecked("user_3"),
-            shares: Uint128::new(69420)
-        }
+        state.prev_denom.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(1000)
     );
-
-    // Pending batch should have been updated
-    let pending_batch = state.pending_batch.load(deps.as_ref().storage).unwrap();
     assert_eq!(
-        pending_batch,
-        PendingBatch {
-            id: 1,
-            usteak_to_burn: Uint128::new(92876), // 23,456 + 69,420
-            est_unbond_start_time: 269200
-        }
+        state
+            .pending_rebates
+            .may_load(deps.as_ref().storage)
+            .unwrap(),
+        None
     );
 }
 
 #[test]
-fn submitting_batch() {
+fn converting_reward_denoms() {
     let mut deps = setup_test();
     let state = State::default();
 
-    // native_token bonded: 1,037,345
-    // usteak supply: 1,012,043
-    // native_token per ustake: 1.025
-    deps.querier.set_staking_delegations(&[
-        Delegation::new("alice", 345782, "uxyz"),
-        Delegation::new("bob", 345782, "uxyz"),
-        Delegation::new("charlie", 345781, "uxyz"),
-    ]);
-    deps.querier.set_cw20_total_supply("steak_token", 1012043);
-
-    // We continue from the contract state at the end of the last test
-    let unbond_requests = vec![
-        UnbondRequest {
-            id: 1,
-            user: Addr::unchecked("user_1"),
-            shares: Uint128::new(23456),
-        },
-        UnbondRequest {
-            id: 1,
-            user: Addr::unchecked("user_3"),
-            shares: Uint128::new(69420),
-        },
-    ];
+    // Until configured, rewards are expected in the Native Token only
+    let res: RewardDenomsResponse = query_helper(deps.as_ref(), QueryMsg::RewardDenoms {});
+    assert_eq!(
+        res,
+        RewardDenomsResponse {
+            reward_denoms: vec!["uxyz".to_string()],
+            converter: None,
+        }
+    );
 
-    for unbond_request in &unbond_requests {
-        state
-            .unbond_requests
-            .save(
-                deps.as_mut().storage,
-                (
-                    unbond_request.id,
-                    &Addr::unchecked(unbond_request.user.clone()),
-                ),
-                unbond_request,
-            )
-            .unwrap();
-    }
+    let update = |reward_denoms: &[&str], converter: Option<&str>| ExecuteMsg::UpdateRewardDenoms {
+        reward_denoms: reward_denoms.iter().map(|d| d.to_string()).collect(),
+        converter: converter.map(String::from),
+    };
 
-    state
-        .pending_batch
-        .save(
-            deps.as_mut().storage,
-            &PendingBatch {
-                id: 1,
-                usteak_to_burn: Uint128::new(92876), // 23,456 + 69,420
-                est_unbond_start_time: 269200,
-            },
-        )
-        .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        update(&["uxyz", "uabc"], Some("converter")),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
 
-    // Anyone can invoke `submit_batch`. Here we continue from the previous test and assume it is
-    // invoked automatically as user 2 submits the unbonding request
-    //
-    // usteak to burn: 23,456 + 69,420 = 92,876
-    // native_token to unbond: 1,037,345 * 92,876 / 1,012,043 = 95,197
-    //
-    // Target: (1,037,345 - 95,197) / 3 = 314,049
-    // Remainer: 1
-    // Alice:   345,782 - (314,049 + 1) = 31,732
-    // Bob:     345,782 - (314,049 + 0) = 31,733
-    // Charlie: 345,781 - (314,049 + 0) = 31,732
-    let env_at_ts = mock_env_at_timestamp(269201);
-    let res = execute(
+    let err = execute(
         deps.as_mut(),
-        env_at_ts.clone(),
-        mock_info(MOCK_CONTRACT_ADDR, &[]),
-        ExecuteMsg::SubmitBatch {},
+        mock_env(),
+        mock_info("larry", &[]),
+        update(&[], None),
     )
-    .unwrap();
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoRewardDenoms {});
 
-    assert_eq!(res.messages.len(), 4);
-    assert_eq!(
-        res.messages[0],
-        SubMsg::reply_on_success(
-            Undelegation::new("alice", 31732, "uxyz")
-                .to_cosmos_msg(env_at_ts.contract.address.to_string())
-                .unwrap(),
-            REPLY_REGISTER_RECEIVED_COINS
-        )
-    );
-    assert_eq!(
-        res.messages[1],
-        SubMsg::reply_on_success(
-            Undelegation::new("bob", 31733, "uxyz")
-                .to_cosmos_msg(env_at_ts.contract.address.to_string())
-                .unwrap(),
-            REPLY_REGISTER_RECEIVED_COINS
-        )
-    );
-    assert_eq!(
-        res.messages[2],
-        SubMsg::reply_on_success(
-            Undelegation::new("charlie", 31732, "uxyz")
-                .to_cosmos_msg(env_at_ts.contract.address.to_string())
-                .unwrap(),
-            REPLY_REGISTER_RECEIVED_COINS
-        )
-    );
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        update(&["uxyz", "uabc", "uxyz"], Some("converter")),
+    )
+    .unwrap_err();
     assert_eq!(
-        res.messages[3],
-        SubMsg {
-            id: 0,
-            msg: CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: "steak_token".to_string(),
-                msg: to_binary(&Cw20ExecuteMsg::Burn {
-                    amount: Uint128::new(92876)
-                })
-                .unwrap(),
-                funds: vec![]
-            }),
-            gas_limit: None,
-            reply_on: ReplyOn::Never
+        err,
+        ContractError::DuplicateRewardDenom {
+            denom: "uxyz".to_string()
         }
     );
 
-    // A new pending batch should have been created
-    let pending_batch = state.pending_batch.load(deps.as_ref().storage).unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        update(&["uxyz", "uabc"], None),
+    )
+    .unwrap_err();
     assert_eq!(
-        pending_batch,
-        PendingBatch {
-            id: 2,
-            usteak_to_burn: Uint128::zero(),
-            est_unbond_start_time: 528401 // 269,201 + 259,200
+        err,
+        ContractError::RewardConverterRequired {
+            denom: "uxyz".to_string()
         }
     );
 
-    // Previous batch should have been updated
-    let previous_batch = state
-        .previous_batches
-        .load(deps.as_ref().storage, 1u64)
-        .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        update(&["uxyz", "uabc"], Some("converter")),
+    )
+    .unwrap();
+
+    let res: RewardDenomsResponse = query_helper(deps.as_ref(), QueryMsg::RewardDenoms {});
     assert_eq!(
-        previous_batch,
-        Batch {
-            id: 1,
-            reconciled: false,
-            total_shares: Uint128::new(92876),
-            amount_unclaimed: Uint128::new(95197),
-            est_unbond_end_time: 2083601 // 269,201 + 1,814,400
+        res,
+        RewardDenomsResponse {
+            reward_denoms: vec!["uxyz".to_string(), "uabc".to_string()],
+            converter: Some("converter".to_string()),
         }
     );
-}
-
-#[test]
-fn reconciling() {
-    let mut deps = setup_test();
-    let state = State::default();
 
-    let previous_batches = vec![
-        Batch {
-            id: 1,
-            reconciled: true,
-            total_shares: Uint128::new(92876),
-            amount_unclaimed: Uint128::new(95197), // 1.025 Native Token per Steak
-            est_unbond_end_time: 10000,
-        },
-        Batch {
+    // Coins received in denoms that are not reward denoms are no longer registered
+    let event = Event::new("coin_received")
+        .add_attribute("receiver", MOCK_CONTRACT_ADDR.to_string())
+        .add_attribute("amount", "123uabc,234uxyz,345ujunk");
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
             id: 2,
-            reconciled: false,
-            total_shares: Uint128::new(1345),
-            amount_unclaimed: Uint128::new(1385), // 1.030 Native Token per Steak
-            est_unbond_end_time: 20000,
-        },
-        Batch {
-            id: 3,
-            reconciled: false,
-            total_shares: Uint128::new(1456),
-            amount_unclaimed: Uint128::new(1506), // 1.035 Native Token per Steak
-            est_unbond_end_time: 30000,
-        },
-        Batch {
-            id: 4,
-            reconciled: false,
-            total_shares: Uint128::new(1567),
-            amount_unclaimed: Uint128::new(1629), // 1.040 Native Token per Steak
-            est_unbond_end_time: 40000,           // not yet finished unbonding, ignored
+            result: cosmwasm_std::SubMsgResult::Ok(SubMsgResponse {
+                events: vec![event],
+                data: None,
+            }),
         },
-    ];
-
-    for previous_batch in &previous_batches {
-        state
-            .previous_batches
-            .save(deps.as_mut().storage, previous_batch.id, previous_batch)
-            .unwrap();
-    }
-
-    state
-        .unlocked_coins
-        .save(
-            deps.as_mut().storage,
-            &vec![
-                Coin::new(10000, "uxyz"),
-                Coin::new(234, "ukrw"),
-                Coin::new(345, "uusd"),
-                Coin::new(
-                    69420,
-                    "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B",
-                ),
-            ],
-        )
-        .unwrap();
+    )
+    .unwrap();
 
-    deps.querier.set_bank_balances(&[
-        Coin::new(12345, "uxyz"),
-        Coin::new(234, "ukrw"),
-        Coin::new(345, "uusd"),
-        Coin::new(
-            69420,
-            "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B",
-        ),
-    ]);
+    let unlocked_coins = state.unlocked_coins.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        unlocked_coins,
+        vec![Coin::new(123, "uabc"), Coin::new(234, "uxyz")]
+    );
 
-    execute(
+    // Harvesting converts the rewards before reinvesting them
+    deps.querier
+        .set_staking_delegations(&[Delegation::new("alice", 1000000, "uxyz")]);
+    let env = mock_env();
+    let res = execute(
         deps.as_mut(),
-        mock_env_at_timestamp(35000),
-        mock_info("worker", &[]),
-        ExecuteMsg::Reconcile {},
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Harvest {},
     )
     .unwrap();
-
-    // Expected received: batch 2 + batch 3 = 1385 + 1506 = 2891
-    // Expected unlocked: 10000
-    // Expected: 12891
-    // Actual: 12345
-    // Shortfall: 12891 - 12345 = 456
-    //
-    // native_token per batch: 546 / 2 = 273
-    // remainder: 0
-    // batch 2: 1385 - 273 = 1112
-    // batch 3: 1506 - 273 = 1233
-    let batch = state
-        .previous_batches
-        .load(deps.as_ref().storage, 2u64)
-        .unwrap();
+    assert_eq!(res.messages.len(), 3);
     assert_eq!(
-        batch,
-        Batch {
-            id: 2,
-            reconciled: true,
-            total_shares: Uint128::new(1345),
-            amount_unclaimed: Uint128::new(1112), // 1385 - 273
-            est_unbond_end_time: 20000,
-        }
+        res.messages[1],
+        SubMsg::new(
+            CallbackMsg::ConvertRewards {}
+                .into_cosmos_msg(&env.contract.address)
+                .unwrap()
+        )
+    );
+    assert_eq!(
+        res.messages[2],
+        SubMsg::new(
+            CallbackMsg::Reinvest {}
+                .into_cosmos_msg(&env.contract.address)
+                .unwrap()
+        )
     );
 
-    let batch = state
-        .previous_batches
-        .load(deps.as_ref().storage, 3u64)
-        .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(CallbackMsg::ConvertRewards {}),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
     assert_eq!(
-        batch,
-        Batch {
-            id: 3,
-            reconciled: true,
-            total_shares: Uint128::new(1456),
-            amount_unclaimed: Uint128::new(1233), // 1506 - 273
-            est_unbond_end_time: 30000,
-        }
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "converter".to_string(),
+            msg: to_binary(&RewardConverterMsg::ConvertRewards {
+                denom_out: "uxyz".to_string()
+            })
+            .unwrap(),
+            funds: vec![Coin::new(123, "uabc")],
+        }))
     );
 
-    // Batches 1 and 4 should not have changed
-    let batch = state
-        .previous_batches
-        .load(deps.as_ref().storage, 1u64)
-        .unwrap();
-    assert_eq!(batch, previous_batches[0]);
+    // The Native Token is left for the reinvestment
+    let unlocked_coins = state.unlocked_coins.load(deps.as_ref().storage).unwrap();
+    assert_eq!(unlocked_coins, vec![Coin::new(234, "uxyz")]);
+}
 
-    let batch = state
-        .previous_batches
-        .load(deps.as_ref().storage, 4u64)
-        .unwrap();
-    assert_eq!(batch, previous_batches[3]);
+#[test]
+fn bonding_and_calling() {
+    let mut deps = setup_test();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 341667, "uxyz"),
+        Delegation::new("bob", 341667, "uxyz"),
+        Delegation::new("charlie", 341666, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000000);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("zapper", &[Coin::new(12345, "uxyz")]),
+        ExecuteMsg::BondAndCall {
+            receiver: Some("user_1".to_string()),
+            contract: "zapper".to_string(),
+            callback: to_binary(&"stake_in_vault").unwrap(),
+        },
+    )
+    .unwrap();
+
+    // The callback comes after the mint, and carries the exact amount minted
+    assert_eq!(res.messages.len(), 3);
+    assert_eq!(
+        res.messages[2].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "zapper".to_string(),
+            msg: to_binary(&BondCallbackMsg::SteakBondCallback(BondCallback {
+                receiver: "user_1".to_string(),
+                amount_bonded: Uint128::new(12345),
+                usteak_minted: Uint128::new(12043),
+                msg: to_binary(&"stake_in_vault").unwrap(),
+            }))
+            .unwrap(),
+            funds: vec![]
+        })
+    );
 }
 
 #[test]
-fn withdrawing_unbonded() {
+fn bonding_and_unbonding_with_integrator_callbacks() {
     let mut deps = setup_test();
     let state = State::default();
 
-    // We simulate a most general case:
-    // - batches 1 and 2 have finished unbonding
-    // - batch 3 have been submitted for unbonding but have not finished
-    // - batch 4 is still pending
-    let unbond_requests = vec![
-        UnbondRequest {
-            id: 1,
-            user: Addr::unchecked("user_1"),
-            shares: Uint128::new(23456),
-        },
-        UnbondRequest {
-            id: 1,
-            user: Addr::unchecked("user_3"),
-            shares: Uint128::new(69420),
-        },
-        UnbondRequest {
-            id: 2,
-            user: Addr::unchecked("user_1"),
-            shares: Uint128::new(34567),
-        },
-        UnbondRequest {
-            id: 3,
-            user: Addr::unchecked("user_1"),
-            shares: Uint128::new(45678),
-        },
-        UnbondRequest {
-            id: 4,
-            user: Addr::unchecked("user_1"),
-            shares: Uint128::new(56789),
-        },
-    ];
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 341667, "uxyz"),
+        Delegation::new("bob", 341667, "uxyz"),
+        Delegation::new("charlie", 341666, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000000);
 
-    for unbond_request in &unbond_requests {
-        state
-            .unbond_requests
-            .save(
-                deps.as_mut().storage,
-                (
-                    unbond_request.id,
-                    &Addr::unchecked(unbond_request.user.clone()),
-                ),
-                unbond_request,
-            )
-            .unwrap();
-    }
+    let bond_msg = ExecuteMsg::BondWithCallback {
+        receiver: Some("user_1".to_string()),
+        callback: to_binary(&"route").unwrap(),
+    };
+    let unbond_msg = ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+        sender: "router".to_string(),
+        amount: Uint128::new(1000),
+        msg: to_binary(&ReceiveMsg::QueueUnbondWithCallback {
+            receiver: Some("user_1".to_string()),
+            callback: to_binary(&"route").unwrap(),
+        })
+        .unwrap(),
+    });
 
-    let previous_batches = vec![
-        Batch {
-            id: 1,
-            reconciled: true,
-            total_shares: Uint128::new(92876),
-            amount_unclaimed: Uint128::new(95197), // 1.025 Native Token per Steak
-            est_unbond_end_time: 10000,
-        },
-        Batch {
-            id: 2,
-            reconciled: true,
-            total_shares: Uint128::new(34567),
-            amount_unclaimed: Uint128::new(35604), // 1.030 Native Token per Steak
-            est_unbond_end_time: 20000,
-        },
-        Batch {
-            id: 3,
-            reconciled: false, // finished unbonding, but not reconciled; ignored
-            total_shares: Uint128::new(45678),
-            amount_unclaimed: Uint128::new(47276), // 1.035 Native Token per Steak
-            est_unbond_end_time: 20000,
-        },
-        Batch {
-            id: 4,
-            reconciled: true,
-            total_shares: Uint128::new(56789),
-            amount_unclaimed: Uint128::new(59060), // 1.040 Native Token per Steak
-            est_unbond_end_time: 30000, // reconciled, but not yet finished unbonding; ignored
-        },
-    ];
-
-    for previous_batch in &previous_batches {
-        state
-            .previous_batches
-            .save(deps.as_mut().storage, previous_batch.id, previous_batch)
-            .unwrap();
-    }
-
-    state
-        .pending_batch
-        .save(
-            deps.as_mut().storage,
-            &PendingBatch {
-                id: 4,
-                usteak_to_burn: Uint128::new(56789),
-                est_unbond_start_time: 100000,
-            },
-        )
-        .unwrap();
+    // Only whitelisted integrators may request callbacks
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("router", &[Coin::new(12345, "uxyz")]),
+        bond_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::IntegratorNotRegistered {
+            address: "router".to_string()
+        }
+    );
 
-    // Attempt to withdraw before any batch has completed unbonding. Should error
     let err = execute(
         deps.as_mut(),
-        mock_env_at_timestamp(5000),
-        mock_info("user_1", &[]),
-        ExecuteMsg::WithdrawUnbonded { receiver: None },
+        mock_env(),
+        mock_info("router", &[]),
+        ExecuteMsg::AddIntegrator {
+            address: "router".to_string(),
+        },
     )
     .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
 
-    assert_eq!(err, StdError::generic_err("withdrawable amount is zero"));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::AddIntegrator {
+            address: "router".to_string(),
+        },
+    )
+    .unwrap();
+    let integrators: Vec<String> = query_helper(deps.as_ref(), QueryMsg::Integrators {});
+    assert_eq!(integrators, vec!["router".to_string()]);
 
-    // Attempt to withdraw once batches 1 and 2 have finished unbonding, but 3 has not yet
-    //
-    // Withdrawable from batch 1: 95,197 * 23,456 / 92,876 = 24,042
-    // Withdrawable from batch 2: 35,604
-    // Total withdrawable: 24,042 + 35,604 = 59,646
-    //
-    // Batch 1 should be updated:
-    // Total shares: 92,876 - 23,456 = 69,420
-    // Unclaimed native_token: 95,197 - 24,042 = 71,155
-    //
-    // Batch 2 is completely withdrawn, should be purged from storage
+    // The notification is dispatched last, once the Steak has been minted
     let res = execute(
         deps.as_mut(),
-        mock_env_at_timestamp(25000),
-        mock_info("user_1", &[]),
-        ExecuteMsg::WithdrawUnbonded { receiver: None },
+        mock_env(),
+        mock_info("router", &[Coin::new(12345, "uxyz")]),
+        bond_msg,
     )
     .unwrap();
-
-    assert_eq!(res.messages.len(), 1);
-    assert_eq!(
-        res.messages[0],
-        SubMsg {
-            id: 0,
-            msg: CosmosMsg::Bank(BankMsg::Send {
-                to_address: "user_1".to_string(),
-                amount: vec![Coin::new(59646, "uxyz")]
-            }),
-            gas_limit: None,
-            reply_on: ReplyOn::Never
-        }
-    );
-
-    // Previous batches should have been updated
-    let batch = state
-        .previous_batches
-        .load(deps.as_ref().storage, 1u64)
-        .unwrap();
+    let flow = IntegratorFlow::Bond {
+        receiver: "user_1".to_string(),
+        amount_bonded: Uint128::new(12345),
+        usteak_minted: Uint128::new(12043),
+    };
+    let notify = CallbackMsg::NotifyIntegrator {
+        integrator: "router".to_string(),
+        flow: flow.clone(),
+        callback: to_binary(&"route").unwrap(),
+    };
+    assert_eq!(res.messages.len(), 3);
     assert_eq!(
-        batch,
-        Batch {
-            id: 1,
-            reconciled: true,
-            total_shares: Uint128::new(69420),
-            amount_unclaimed: Uint128::new(71155),
-            est_unbond_end_time: 10000,
-        }
+        res.messages[2].msg,
+        notify
+            .into_cosmos_msg(&Addr::unchecked(MOCK_CONTRACT_ADDR))
+            .unwrap()
     );
 
-    let err = state
-        .previous_batches
-        .load(deps.as_ref().storage, 2u64)
-        .unwrap_err();
-    assert_eq!(err, StdError::not_found("pfc_steak::hub::Batch"));
-
-    // User 1's unbond requests in batches 1 and 2 should have been deleted
-    let err1 = state
-        .unbond_requests
-        .load(deps.as_ref().storage, (1u64, &Addr::unchecked("user_1")))
-        .unwrap_err();
-    let err2 = state
-        .unbond_requests
-        .load(deps.as_ref().storage, (1u64, &Addr::unchecked("user_1")))
-        .unwrap_err();
+    // Notifications can only come from the hub itself
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("router", &[]),
+        ExecuteMsg::Callback(notify.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::CallbackOnly {});
 
-    assert_eq!(err1, StdError::not_found("pfc_steak::hub::UnbondRequest"));
-    assert_eq!(err2, StdError::not_found("pfc_steak::hub::UnbondRequest"));
-    // User 3 attempt to withdraw; also specifying a receiver
     let res = execute(
         deps.as_mut(),
-        mock_env_at_timestamp(25000),
-        mock_info("user_3", &[]),
-        ExecuteMsg::WithdrawUnbonded {
-            receiver: Some("user_2".to_string()),
-        },
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(notify.clone()),
     )
     .unwrap();
-
-    assert_eq!(res.messages.len(), 1);
+    let hub_state: StateResponse = query_helper(deps.as_ref(), QueryMsg::State {});
     assert_eq!(
-        res.messages[0],
-        SubMsg {
-            id: 0,
-            msg: CosmosMsg::Bank(BankMsg::Send {
-                to_address: "user_2".to_string(),
-                amount: vec![Coin::new(71155, "uxyz")]
-            }),
-            gas_limit: None,
-            reply_on: ReplyOn::Never
-        }
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            IntegratorCallbackMsg::SteakIntegratorCallback(IntegratorCallback {
+                flow,
+                total_usteak: hub_state.total_usteak,
+                total_native: hub_state.total_native,
+                exchange_rate: hub_state.exchange_rate,
+                msg: to_binary(&"route").unwrap(),
+            })
+            .into_cosmos_msg("router".to_string())
+            .unwrap(),
+            REPLY_INTEGRATOR_CALLBACK_DONE
+        )]
     );
 
-    // Batch 1 and user 2's unbonding request should have been purged from storage
-    let err = state
-        .previous_batches
-        .load(deps.as_ref().storage, 1u64)
-        .unwrap_err();
-    assert_eq!(err, StdError::not_found("pfc_steak::hub::Batch"));
-
-    let err = state
-        .unbond_requests
-        .load(deps.as_ref().storage, (1u64, &Addr::unchecked("user_3")))
-        .unwrap_err();
-
-    assert_eq!(err, StdError::not_found("pfc_steak::hub::UnbondRequest"));
-}
-
-#[test]
-fn adding_validator() {
-    let mut deps = setup_test();
-    let state = State::default();
-
+    // While its callback executes, the integrator can not request another one
     let err = execute(
         deps.as_mut(),
         mock_env(),
-        mock_info("jake", &[]),
-        ExecuteMsg::AddValidator {
-            validator: "dave".to_string(),
-        },
+        mock_info("steak_token", &[]),
+        unbond_msg.clone(),
     )
     .unwrap_err();
+    assert_eq!(err, ContractError::IntegratorCallbackInProgress {});
 
+    reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: REPLY_INTEGRATOR_CALLBACK_DONE,
+            result: cosmwasm_std::SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
     assert_eq!(
-        err,
-        StdError::generic_err("unauthorized: sender is not owner")
+        state
+            .integrator_callback
+            .may_load(deps.as_ref().storage)
+            .unwrap(),
+        None
     );
 
-    let err = execute(
+    let res = execute(
         deps.as_mut(),
         mock_env(),
-        mock_info("larry", &[]),
-        ExecuteMsg::AddValidator {
-            validator: "alice".to_string(),
-        },
+        mock_info("steak_token", &[]),
+        unbond_msg,
     )
-    .unwrap_err();
-
+    .unwrap();
     assert_eq!(
-        err,
-        StdError::generic_err("validator is already whitelisted")
+        res.messages.last().unwrap().msg,
+        CallbackMsg::NotifyIntegrator {
+            integrator: "router".to_string(),
+            flow: IntegratorFlow::QueueUnbond {
+                receiver: "user_1".to_string(),
+                usteak_queued: Uint128::new(1000),
+            },
+            callback: to_binary(&"route").unwrap(),
+        }
+        .into_cosmos_msg(&Addr::unchecked(MOCK_CONTRACT_ADDR))
+        .unwrap()
     );
 
-    let res = execute(
+    // An integrator removed mid-flow is no longer called back
+    execute(
         deps.as_mut(),
         mock_env(),
         mock_info("larry", &[]),
-        ExecuteMsg::AddValidator {
-            validator: "dave".to_string(),
+        ExecuteMsg::RemoveIntegrator {
+            address: "router".to_string(),
         },
     )
     .unwrap();
-
-    assert_eq!(res.messages.len(), 0);
-
-    let validators = state.validators.load(deps.as_ref().storage).unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(notify),
+    )
+    .unwrap_err();
     assert_eq!(
-        validators,
-        vec![
-            String::from("alice"),
-            String::from("bob"),
-            String::from("charlie"),
-            String::from("dave")
-        ],
+        err,
+        ContractError::IntegratorNotRegistered {
+            address: "router".to_string()
+        }
     );
 }
 
 #[test]
-fn removing_validator() {
+fn mirroring_votes_of_vote_delegates() {
     let mut deps = setup_test();
-    let state = State::default();
-
-    deps.querier.set_staking_delegations(&[
-        Delegation::new("alice", 341667, "uxyz"),
-        Delegation::new("bob", 341667, "uxyz"),
-        Delegation::new("charlie", 341666, "uxyz"),
-    ]);
 
+    let vote = |deps: &mut OwnedDeps<_, _, _>, delegate: &str, proposal_id: u64, vote| {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(delegate, &[]),
+            ExecuteMsg::DelegateVote {
+                proposal_id,
+                vote,
+                voting_end_time: mock_env().block.time.seconds() + 1000,
+            },
+        )
+    };
+    let gov_vote =
+        |proposal_id, vote| SubMsg::new(CosmosMsg::Gov(GovMsg::Vote { proposal_id, vote }));
+
+    let update_msg = ExecuteMsg::UpdateVoteDelegates {
+        delegates: vec![
+            "council_1".to_string(),
+            "council_2".to_string(),
+            "council_3".to_string(),
+        ],
+    };
     let err = execute(
         deps.as_mut(),
         mock_env(),
-        mock_info("jake", &[]),
-        ExecuteMsg::RemoveValidator {
-            validator: "charlie".to_string(),
-        },
+        mock_info("council_1", &[]),
+        update_msg.clone(),
     )
     .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
 
-    assert_eq!(
-        err,
-        StdError::generic_err("unauthorized: sender is not owner")
-    );
-
-    let err = execute(
+    execute(
         deps.as_mut(),
         mock_env(),
         mock_info("larry", &[]),
-        ExecuteMsg::RemoveValidator {
-            validator: "dave".to_string(),
-        },
+        update_msg,
     )
-    .unwrap_err();
+    .unwrap();
+    let delegates: Vec<String> = query_helper(deps.as_ref(), QueryMsg::VoteDelegates {});
+    assert_eq!(delegates, vec!["council_1", "council_2", "council_3"]);
 
+    let err = vote(&mut deps, "jake", 1, VoteOption::Yes).unwrap_err();
     assert_eq!(
         err,
-        StdError::generic_err("validator is not already whitelisted")
+        ContractError::NotVoteDelegate {
+            address: "jake".to_string()
+        }
     );
 
-    // Target: (341667 + 341667 + 341666) / 2 = 512500
-    // Remainder: 0
-    // Alice:   512500 + 0 - 341667 = 170833
-    // Bob:     512500 + 0 - 341667 = 170833
-    let env = mock_env();
-    let res = execute(
+    // The first vote is mirrored as is
+    let res = vote(&mut deps, "council_1", 1, VoteOption::Yes).unwrap();
+    assert_eq!(res.messages, vec![gov_vote(1, VoteOption::Yes)]);
+
+    // A tie is voted abstain
+    let res = vote(&mut deps, "council_2", 1, VoteOption::No).unwrap();
+    assert_eq!(res.messages, vec![gov_vote(1, VoteOption::Abstain)]);
+
+    let res = vote(&mut deps, "council_3", 1, VoteOption::No).unwrap();
+    assert_eq!(res.messages, vec![gov_vote(1, VoteOption::No)]);
+
+    // The hub only votes again when the leading option changes
+    let res = vote(&mut deps, "council_1", 1, VoteOption::No).unwrap();
+    assert_eq!(res.messages, vec![]);
+
+    let delegated_vote: Option<DelegatedVote> =
+        query_helper(deps.as_ref(), QueryMsg::DelegatedVote { proposal_id: 1 });
+    assert_eq!(
+        delegated_vote,
+        Some(DelegatedVote {
+            proposal_id: 1,
+            voting_end_time: Some(mock_env().block.time.seconds() + 1000),
+            ballots: vec![
+                DelegateBallot {
+                    delegate: "council_1".to_string(),
+                    vote: VoteOption::No,
+                },
+                DelegateBallot {
+                    delegate: "council_2".to_string(),
+                    vote: VoteOption::No,
+                },
+                DelegateBallot {
+                    delegate: "council_3".to_string(),
+                    vote: VoteOption::No,
+                },
+            ],
+            opted_out: false,
+            cast: Some(VoteOption::No),
+        })
+    );
+
+    // Once the hub voted, the proposal can no longer be opted out of
+    let err = execute(
         deps.as_mut(),
-        env.clone(),
+        mock_env(),
         mock_info("larry", &[]),
-        ExecuteMsg::RemoveValidator {
-            validator: "charlie".to_string(),
-        },
+        ExecuteMsg::OptOutOfDelegatedVote { proposal_id: 1 },
     )
-    .unwrap();
+    .unwrap_err();
+    assert_eq!(err, ContractError::DelegatedVoteCast { proposal_id: 1 });
 
-    assert_eq!(res.messages.len(), 2);
-    assert_eq!(
-        res.messages[0],
-        SubMsg::reply_on_success(
-            Redelegation::new("charlie", "alice", 170833, "uxyz")
-                .to_cosmos_msg(env.contract.address.to_string())
-                .unwrap(),
-            REPLY_REGISTER_RECEIVED_COINS
-        ),
-    );
-    assert_eq!(
-        res.messages[1],
-        SubMsg::reply_on_success(
-            Redelegation::new("charlie", "bob", 170833, "uxyz")
-                .to_cosmos_msg(env.contract.address.to_string())
-                .unwrap(),
-            REPLY_REGISTER_RECEIVED_COINS
-        ),
-    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::OptOutOfDelegatedVote { proposal_id: 2 },
+    )
+    .unwrap();
+    let err = vote(&mut deps, "council_1", 2, VoteOption::Yes).unwrap_err();
+    assert_eq!(err, ContractError::DelegatedVoteOptedOut { proposal_id: 2 });
 
-    let validators = state.validators.load(deps.as_ref().storage).unwrap();
-    assert_eq!(validators, vec![String::from("alice"), String::from("bob")],);
+    // No votes are taken once voting ended
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(mock_env().block.time.seconds() + 1000),
+        mock_info("council_2", &[]),
+        ExecuteMsg::DelegateVote {
+            proposal_id: 1,
+            vote: VoteOption::Yes,
+            voting_end_time: mock_env().block.time.seconds() + 5000,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::DelegatedVotingEnded { proposal_id: 1 });
 }
 
 #[test]
-fn transferring_ownership() {
+fn bonding_locked() {
     let mut deps = setup_test();
     let state = State::default();
+    let env = mock_env_at_timestamp(10000);
 
     let err = execute(
         deps.as_mut(),
-        mock_env(),
-        mock_info("jake", &[]),
-        ExecuteMsg::TransferOwnership {
-            new_owner: "jake".to_string(),
+        env.clone(),
+        mock_info("user_1", &[Coin::new(1000000, "uxyz")]),
+        ExecuteMsg::BondLocked {
+            receiver: None,
+            duration: 0,
         },
     )
     .unwrap_err();
+    assert_eq!(err, ContractError::InvalidLockDuration { max: 126144000 });
+
+    // The minted Steak is held by the hub itself
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user_1", &[Coin::new(1000000, "uxyz")]),
+        ExecuteMsg::BondLocked {
+            receiver: None,
+            duration: 5000,
+        },
+    )
+    .unwrap();
 
+    assert_eq!(res.messages.len(), 2);
     assert_eq!(
-        err,
-        StdError::generic_err("unauthorized: sender is not owner")
+        res.messages[1].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: MOCK_CONTRACT_ADDR.to_string(),
+                amount: Uint128::new(1000000)
+            })
+            .unwrap(),
+            funds: vec![]
+        })
     );
 
-    let res = execute(
+    let res: Vec<LockedBondResponseItem> = query_helper(
+        deps.as_ref(),
+        QueryMsg::LockedBonds {
+            user: "user_1".to_string(),
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert_eq!(
+        res,
+        vec![LockedBondResponseItem {
+            id: 1,
+            shares: Uint128::new(1000000),
+            unlock_time: 15000,
+            multiplier: Decimal::one(),
+            boost_claimable: Uint128::zero(),
+        }]
+    );
+
+    // Half of the fees go to the lockers
+    let err = execute(
         deps.as_mut(),
-        mock_env(),
+        env.clone(),
+        mock_info("jake", &[]),
+        ExecuteMsg::UpdateLockBoost {
+            boost_rate: Decimal::percent(50),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
         mock_info("larry", &[]),
-        ExecuteMsg::TransferOwnership {
-            new_owner: "jake".to_string(),
+        ExecuteMsg::UpdateLockBoost {
+            boost_rate: Decimal::percent(50),
         },
     )
     .unwrap();
 
-    assert_eq!(res.messages.len(), 0);
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 1000000, "uxyz"),
+        Delegation::new("bob", 0, "uxyz"),
+        Delegation::new("charlie", 0, "uxyz"),
+    ]);
+    deps.querier
+        .set_bank_balances(&[Coin::new(1000u128, "uxyz")]);
+    state
+        .prev_denom
+        .save(deps.as_mut().storage, &Uint128::zero())
+        .unwrap();
+    state
+        .total_mining_power
+        .save(deps.as_mut().storage, &Uint128::new(100))
+        .unwrap();
+    state
+        .validator_mining_powers
+        .save(
+            deps.as_mut().storage,
+            "alice".to_string(),
+            &Uint128::new(100),
+        )
+        .unwrap();
 
-    let owner = state.owner.load(deps.as_ref().storage).unwrap();
-    assert_eq!(owner, Addr::unchecked("larry"));
+    // 10% fee on 1000 reward = 100, of which 50 is kept for the lockers
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(CallbackMsg::Reinvest {}),
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "the_fee_man".to_string(),
+            amount: vec![Coin::new(50, "uxyz")]
+        })
+    );
+
+    let res: LockStateResponse = query_helper(deps.as_ref(), QueryMsg::LockState {});
+    assert_eq!(
+        res,
+        LockStateResponse {
+            boost_rate: Decimal::percent(50),
+            total_locked_shares: Uint128::new(1000000),
+            total_lock_weight: Uint128::new(1000000),
+            boost_index: Decimal::from_ratio(50u128, 1000000u128),
+            boost_unclaimed: Uint128::new(50),
+            tiers: vec![],
+        }
+    );
 
+    // The lock can not be released early, but its boost can be claimed
     let err = execute(
         deps.as_mut(),
-        mock_env(),
-        mock_info("pumpkin", &[]),
-        ExecuteMsg::AcceptOwnership {},
+        env.clone(),
+        mock_info("user_1", &[]),
+        ExecuteMsg::UnlockBond { id: 1 },
     )
     .unwrap_err();
+    assert_eq!(err, ContractError::BondStillLocked { unlock_time: 15000 });
 
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("user_1", &[]),
+        ExecuteMsg::ClaimLockBoost {},
+    )
+    .unwrap();
     assert_eq!(
-        err,
-        StdError::generic_err("unauthorized: sender is not new owner")
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "user_1".to_string(),
+            amount: vec![Coin::new(50, "uxyz")]
+        })
     );
 
+    // Once expired, the shares are queued for unbonding on behalf of the user
     let res = execute(
         deps.as_mut(),
-        mock_env(),
-        mock_info("jake", &[]),
-        ExecuteMsg::AcceptOwnership {},
+        mock_env_at_timestamp(15000),
+        mock_info("user_1", &[]),
+        ExecuteMsg::UnlockBond { id: 1 },
     )
     .unwrap();
-
     assert_eq!(res.messages.len(), 0);
 
-    let owner = state.owner.load(deps.as_ref().storage).unwrap();
-    assert_eq!(owner, Addr::unchecked("jake"));
+    let pending_batch = state.pending_batch.load(deps.as_ref().storage).unwrap();
+    assert_eq!(pending_batch.usteak_to_burn, Uint128::new(1000000));
+    let request = state
+        .unbond_requests
+        .load(deps.as_ref().storage, (1u64, &Addr::unchecked("user_1")))
+        .unwrap();
+    assert_eq!(request.shares, Uint128::new(1000000));
+
+    let res: LockStateResponse = query_helper(deps.as_ref(), QueryMsg::LockState {});
+    assert_eq!(res.total_locked_shares, Uint128::zero());
+    assert_eq!(res.boost_unclaimed, Uint128::zero());
 }
 
 #[test]
-fn splitting_fees() {
+fn locking_with_tiers() {
     let mut deps = setup_test();
+    let state = State::default();
+    let env = mock_env_at_timestamp(10000);
+
+    let tiers = vec![
+        LockTier {
+            min_duration: 1000,
+            multiplier: Decimal::from_ratio(3u128, 2u128),
+        },
+        LockTier {
+            min_duration: 10000,
+            multiplier: Decimal::from_ratio(2u128, 1u128),
+        },
+    ];
 
     let err = execute(
         deps.as_mut(),
-        mock_env(),
+        env.clone(),
         mock_info("jake", &[]),
-        ExecuteMsg::TransferFeeAccount {
-            fee_account_type: "Wallet".to_string(),
-            new_fee_account: "charlie".to_string(),
+        ExecuteMsg::UpdateLockTiers {
+            tiers: tiers.clone(),
         },
     )
     .unwrap_err();
-
-    assert_eq!(
-        err,
-        StdError::generic_err("unauthorized: sender is not owner")
-    );
+    assert_eq!(err, ContractError::Unauthorized {});
 
     let err = execute(
         deps.as_mut(),
-        mock_env(),
+        env.clone(),
         mock_info("larry", &[]),
-        ExecuteMsg::TransferFeeAccount {
-            fee_account_type: "xxxx".to_string(),
-            new_fee_account: "charlie".to_string(),
+        ExecuteMsg::UpdateLockTiers {
+            tiers: vec![tiers[1].clone(), tiers[0].clone()],
         },
     )
     .unwrap_err();
-
     assert_eq!(
         err,
-        StdError::generic_err("Invalid Fee type: Wallet or FeeSplit only")
+        ContractError::InvalidLockTiers {
+            reason: "tiers must be ordered by increasing duration and multiplier".to_string()
+        }
     );
 
     execute(
         deps.as_mut(),
-        mock_env(),
+        env.clone(),
         mock_info("larry", &[]),
-        ExecuteMsg::TransferFeeAccount {
-            fee_account_type: "Wallet".to_string(),
-            new_fee_account: "charlie".to_string(),
+        ExecuteMsg::UpdateLockTiers {
+            tiers: tiers.clone(),
         },
     )
     .unwrap();
-    let res: ConfigResponse = query_helper(deps.as_ref(), QueryMsg::Config {});
+
+    // Only Steak can be locked
+    let lock_msg = |user: &str, duration: u64| {
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: user.to_string(),
+            amount: Uint128::new(1000),
+            msg: to_binary(&ReceiveMsg::Lock { duration }).unwrap(),
+        })
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("random_token", &[]),
+        lock_msg("user_1", 500),
+    )
+    .unwrap_err();
     assert_eq!(
-        res,
-        ConfigResponse {
-            owner: "larry".to_string(),
-            new_owner: None,
-            steak_token: "steak_token".to_string(),
-            epoch_period: 259200,
-            unbond_period: 1814400,
-            denom: "uxyz".to_string(),
-            fee_type: "Wallet".to_string(),
-            fee_account: "charlie".to_string(),
-            fee_rate: Decimal::from_ratio(10_u128, 100_u128),
-            max_fee_rate: Decimal::from_ratio(20_u128, 100_u128),
-            validators: vec![
-                "alice".to_string(),
-                "bob".to_string(),
-                "charlie".to_string()
-            ]
+        err,
+        ContractError::UnexpectedToken {
+            expected: "Steak".to_string(),
+            received: "random_token".to_string(),
         }
     );
 
+    // user_1 locks below the first tier, user_2 qualifies for the second
     execute(
         deps.as_mut(),
-        mock_env(),
+        env.clone(),
+        mock_info("steak_token", &[]),
+        lock_msg("user_1", 500),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("steak_token", &[]),
+        lock_msg("user_2", 20000),
+    )
+    .unwrap();
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
         mock_info("larry", &[]),
-        ExecuteMsg::TransferFeeAccount {
-            fee_account_type: "FeeSplit".to_string(),
-            new_fee_account: "contract".to_string(),
+        ExecuteMsg::UpdateLockBoost {
+            boost_rate: Decimal::percent(50),
         },
     )
     .unwrap();
-    let res: ConfigResponse = query_helper(deps.as_ref(), QueryMsg::Config {});
-    assert_eq!(
-        res,
-        ConfigResponse {
-            owner: "larry".to_string(),
-            new_owner: None,
-            steak_token: "steak_token".to_string(),
-            epoch_period: 259200,
-            unbond_period: 1814400,
-            denom: "uxyz".to_string(),
-            fee_type: "FeeSplit".to_string(),
-            fee_account: "contract".to_string(),
-            fee_rate: Decimal::from_ratio(10_u128, 100_u128),
-            max_fee_rate: Decimal::from_ratio(20_u128, 100_u128),
-            validators: vec![
-                "alice".to_string(),
-                "bob".to_string(),
-                "charlie".to_string()
-            ]
-        }
-    );
-}
 
-#[test]
-fn submit_proof() {
-    let mut deps = setup_test();
-    let state = State::default();
-    let miner_entropy =
-        "df5c2d1c1e799c13e81ef0d24acdb338e9da760af9afcd1bfbde40d61fed8996".to_string();
-    let miner_address = "joe1gh9nds8amsy33ewpt97gj4n99436hftz2zl79q".to_string();
-    let nonce = Uint64::from(121063160u64);
     deps.querier.set_staking_delegations(&[
-        Delegation::new("alice", 341667, "uxyz"),
-        Delegation::new("bob", 341667, "uxyz"),
-        Delegation::new("charlie", 341666, "uxyz"),
+        Delegation::new("alice", 1000000, "uxyz"),
+        Delegation::new("bob", 0, "uxyz"),
+        Delegation::new("charlie", 0, "uxyz"),
     ]);
+    deps.querier
+        .set_bank_balances(&[Coin::new(3000u128, "uxyz")]);
     state
-        .miner_entropy
-        .save(deps.as_mut().storage, &miner_entropy)
-        .unwrap();
-    state
-        .miner_difficulty
-        .save(deps.as_mut().storage, &Uint64::new(5))
+        .prev_denom
+        .save(deps.as_mut().storage, &Uint128::zero())
         .unwrap();
-    let res = execute(
+
+    // 10% fee on 3000 reward = 300, of which 150 is split by weight: 1000 for user_1, 2000 for user_2
+    execute(
         deps.as_mut(),
-        mock_env(),
-        mock_info(&miner_address.to_string(), &[]),
-        ExecuteMsg::SubmitProof {
-            nonce,
-            validator: "alice".to_string(),
-        },
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(CallbackMsg::Reinvest {}),
     )
     .unwrap();
-}
-
-//--------------------------------------------------------------------------------------------------
-// Queries
-//--------------------------------------------------------------------------------------------------
-
-#[test]
-fn querying_previous_batches() {
-    let mut deps = mock_dependencies();
-
-    let batches = vec![
-        Batch {
-            id: 1,
-            reconciled: false,
-            total_shares: Uint128::new(123),
-            amount_unclaimed: Uint128::new(678),
-            est_unbond_end_time: 10000,
-        },
-        Batch {
-            id: 2,
-            reconciled: true,
-            total_shares: Uint128::new(234),
-            amount_unclaimed: Uint128::new(789),
-            est_unbond_end_time: 15000,
-        },
-        Batch {
-            id: 3,
-            reconciled: false,
-            total_shares: Uint128::new(345),
-            amount_unclaimed: Uint128::new(890),
-            est_unbond_end_time: 20000,
-        },
-        Batch {
-            id: 4,
-            reconciled: true,
-            total_shares: Uint128::new(456),
-            amount_unclaimed: Uint128::new(999),
-            est_unbond_end_time: 25000,
-        },
-    ];
-
-    let state = State::default();
-    for batch in &batches {
-        state
-            .previous_batches
-            .save(deps.as_mut().storage, batch.id, batch)
-            .unwrap();
-    }
-
-    // Querying a single batch
-    let res: Batch = query_helper(deps.as_ref(), QueryMsg::PreviousBatch(1));
-    assert_eq!(res, batches[0].clone());
-
-    let res: Batch = query_helper(deps.as_ref(), QueryMsg::PreviousBatch(2));
-    assert_eq!(res, batches[1].clone());
 
-    // Query multiple batches
-    let res: Vec<Batch> = query_helper(
-        deps.as_ref(),
-        QueryMsg::PreviousBatches {
-            start_after: None,
-            limit: None,
-        },
+    let res: LockStateResponse = query_helper(deps.as_ref(), QueryMsg::LockState {});
+    assert_eq!(
+        res,
+        LockStateResponse {
+            boost_rate: Decimal::percent(50),
+            total_locked_shares: Uint128::new(2000),
+            total_lock_weight: Uint128::new(3000),
+            boost_index: Decimal::from_ratio(5u128, 100u128),
+            boost_unclaimed: Uint128::new(150),
+            tiers,
+        }
     );
-    assert_eq!(res, batches);
 
-    let res: Vec<Batch> = query_helper(
+    let res: Vec<LockedBondResponseItem> = query_helper(
         deps.as_ref(),
-        QueryMsg::PreviousBatches {
-            start_after: Some(1),
+        QueryMsg::LockedBonds {
+            user: "user_2".to_string(),
+            start_after: None,
             limit: None,
         },
     );
     assert_eq!(
         res,
-        vec![batches[1].clone(), batches[2].clone(), batches[3].clone()]
+        vec![LockedBondResponseItem {
+            id: 2,
+            shares: Uint128::new(1000),
+            unlock_time: 30000,
+            multiplier: Decimal::from_ratio(2u128, 1u128),
+            boost_claimable: Uint128::new(100),
+        }]
     );
 
-    let res: Vec<Batch> = query_helper(
-        deps.as_ref(),
-        QueryMsg::PreviousBatches {
-            start_after: Some(4),
-            limit: None,
-        },
-    );
-    assert_eq!(res, vec![]);
+    // The Steak is returned as is once the lock expires, along with its boost
+    let err = execute(
+        deps.as_mut(),
+        env,
+        mock_info("user_1", &[]),
+        ExecuteMsg::Unlock {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NothingToUnlock {});
 
-    // Query multiple batches, indexed by whether it has been reconciled
-    let res = state
-        .previous_batches
-        .idx
-        .reconciled
-        .prefix(true.into())
-        .range(deps.as_ref().storage, None, None, Order::Ascending)
-        .map(|item| {
-            let (_, v) = item.unwrap();
-            v
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10500),
+        mock_info("user_1", &[]),
+        ExecuteMsg::Unlock {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "user_1".to_string(),
+                amount: Uint128::new(1000)
+            })
+            .unwrap(),
+            funds: vec![]
         })
-        .collect::<Vec<_>>();
-
-    assert_eq!(res, vec![batches[1].clone(), batches[3].clone()]);
-
-    let res = state
-        .previous_batches
-        .idx
-        .reconciled
-        .prefix(false.into())
-        .range(deps.as_ref().storage, None, None, Order::Ascending)
-        .map(|item| {
-            let (_, v) = item.unwrap();
-            v
+    );
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "user_1".to_string(),
+            amount: vec![Coin::new(50, "uxyz")]
         })
-        .collect::<Vec<_>>();
+    );
 
-    assert_eq!(res, vec![batches[0].clone(), batches[2].clone()]);
+    let res: LockStateResponse = query_helper(deps.as_ref(), QueryMsg::LockState {});
+    assert_eq!(res.total_locked_shares, Uint128::new(1000));
+    assert_eq!(res.total_lock_weight, Uint128::new(2000));
+    assert_eq!(res.boost_unclaimed, Uint128::new(100));
 }
 
 #[test]
-fn querying_unbond_requests() {
-    let mut deps = mock_dependencies();
-    let state = State::default();
+fn splitting_yield() {
+    let mut deps = setup_test();
 
-    let unbond_requests = vec![
-        UnbondRequest {
-            id: 1,
-            user: Addr::unchecked("alice"),
-            shares: Uint128::new(123),
-        },
-        UnbondRequest {
-            id: 1,
-            user: Addr::unchecked("bob"),
-            shares: Uint128::new(234),
-        },
-        UnbondRequest {
-            id: 1,
-            user: Addr::unchecked("charlie"),
-            shares: Uint128::new(345),
-        },
-        UnbondRequest {
-            id: 2,
-            user: Addr::unchecked("alice"),
-            shares: Uint128::new(456),
-        },
-    ];
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 333334, "uxyz"),
+        Delegation::new("bob", 333333, "uxyz"),
+        Delegation::new("charlie", 333333, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000000);
 
-    for unbond_request in &unbond_requests {
-        state
-            .unbond_requests
-            .save(
-                deps.as_mut().storage,
-                (
-                    unbond_request.id,
-                    &Addr::unchecked(unbond_request.user.clone()),
-                ),
-                unbond_request,
-            )
-            .unwrap();
-    }
+    let split_msg = ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+        sender: "user_1".to_string(),
+        amount: Uint128::new(100000),
+        msg: to_binary(&ReceiveMsg::SplitYield {
+            receiver: Some("user_2".to_string()),
+        })
+        .unwrap(),
+    });
 
-    let res: Vec<UnbondRequestsByBatchResponseItem> = query_helper(
-        deps.as_ref(),
-        QueryMsg::UnbondRequestsByBatch {
-            id: 1,
-            start_after: None,
-            limit: None,
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("steak_token", &[]),
+        split_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::YieldSplittingNotEnabled {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::EnableYieldSplit {
+            principal_token: "principal_token".to_string(),
         },
-    );
+    )
+    .unwrap();
+
+    // At an exchange rate of 1, 100,000 usteak is worth 100,000 principal
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("steak_token", &[]),
+        split_msg,
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 1);
     assert_eq!(
-        res,
-        vec![
-            unbond_requests[0].clone().into(),
-            unbond_requests[1].clone().into(),
-            unbond_requests[2].clone().into(),
-        ]
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "principal_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "user_2".to_string(),
+                amount: Uint128::new(100000)
+            })
+            .unwrap(),
+            funds: vec![]
+        })
     );
 
-    let res: Vec<UnbondRequestsByBatchResponseItem> = query_helper(
-        deps.as_ref(),
-        QueryMsg::UnbondRequestsByBatch {
-            id: 2,
-            start_after: None,
-            limit: None,
-        },
-    );
-    assert_eq!(res, vec![unbond_requests[3].clone().into()]);
+    // Rewards raise the exchange rate to 1.1
+    // Backing of the principal: ceil(100,000 / 1.1) = 90,910 usteak
+    // Yield: 100,000 - 90,910 = 9,090 usteak
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 366667, "uxyz"),
+        Delegation::new("bob", 366667, "uxyz"),
+        Delegation::new("charlie", 366666, "uxyz"),
+    ]);
 
-    let res: Vec<UnbondRequestsByUserResponseItem> = query_helper(
+    let res: YieldPositionResponse = query_helper(
         deps.as_ref(),
-        QueryMsg::UnbondRequestsByUser {
-            user: "alice".to_string(),
-            start_after: None,
-            limit: None,
+        QueryMsg::YieldPosition {
+            user: "user_1".to_string(),
         },
     );
     assert_eq!(
         res,
-        vec![
-            unbond_requests[0].clone().into(),
-            unbond_requests[3].clone().into()
-        ]
+        YieldPositionResponse {
+            shares: Uint128::new(100000),
+            claimable: Uint128::new(9090),
+        }
     );
 
-    let res: Vec<UnbondRequestsByUserResponseItem> = query_helper(
-        deps.as_ref(),
-        QueryMsg::UnbondRequestsByUser {
-            user: "alice".to_string(),
-            start_after: Some(2),
-            limit: None,
-        },
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user_1", &[]),
+        ExecuteMsg::ClaimYield { receiver: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "user_1".to_string(),
+                amount: Uint128::new(9090)
+            })
+            .unwrap(),
+            funds: vec![]
+        })
     );
-    assert_eq!(res, vec![unbond_requests[3].clone().into()]);
-}
 
-//--------------------------------------------------------------------------------------------------
-// Delegations
-//--------------------------------------------------------------------------------------------------
+    // Principal can only be redeemed by sending the principal token
+    let redeem_msg = ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+        sender: "user_2".to_string(),
+        amount: Uint128::new(50000),
+        msg: to_binary(&ReceiveMsg::RedeemPrincipal { receiver: None }).unwrap(),
+    });
 
-#[test]
-fn computing_undelegations() {
-    let current_delegations = vec![
-        Delegation::new("alice", 400, "uxyz"),
-        Delegation::new("bob", 300, "uxyz"),
-        Delegation::new("charlie", 200, "uxyz"),
-    ];
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("steak_token", &[]),
+        redeem_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnexpectedToken {
+            expected: "principal".to_string(),
+            received: "steak_token".to_string()
+        }
+    );
 
-    // Target: (400 + 300 + 200 - 451) / 3 = 149
-    // Remainder: 2
-    // Alice:   400 - (149 + 1) = 250
-    // Bob:     300 - (149 + 1) = 150
-    // Charlie: 200 - (149 + 0) = 51
-    let new_undelegations = compute_undelegations(Uint128::new(451), &current_delegations, "uxyz");
-    let expected = vec![
-        Undelegation::new("alice", 250, "uxyz"),
-        Undelegation::new("bob", 150, "uxyz"),
-        Undelegation::new("charlie", 51, "uxyz"),
-    ];
-    assert_eq!(new_undelegations, expected);
-}
+    // 50,000 principal is worth floor(50,000 / 1.1) = 45,454 usteak
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("principal_token", &[]),
+        redeem_msg,
+    )
+    .unwrap();
 
-#[test]
-fn computing_redelegations_for_removal() {
-    let current_delegations = vec![
-        Delegation::new("alice", 13000, "uxyz"),
-        Delegation::new("bob", 12000, "uxyz"),
-        Delegation::new("charlie", 11000, "uxyz"),
-        Delegation::new("dave", 10000, "uxyz"),
-    ];
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "user_2".to_string(),
+                amount: Uint128::new(45454)
+            })
+            .unwrap(),
+            funds: vec![]
+        })
+    );
 
-    // Suppose Dave will be removed
-    // native_token_per_validator = (13000 + 12000 + 11000 + 10000) / 3 = 15333
-    // remainder = 1
-    // to Alice:   15333 + 1 - 13000 = 2334
-    // to Bob:     15333 + 0 - 12000 = 3333
-    // to Charlie: 15333 + 0 - 11000 = 4333
-    let expected = vec![
-        Redelegation::new("dave", "alice", 2334, "uxyz"),
-        Redelegation::new("dave", "bob", 3333, "uxyz"),
-        Redelegation::new("dave", "charlie", 4333, "uxyz"),
-    ];
+    let res: YieldPoolResponse = query_helper(deps.as_ref(), QueryMsg::YieldPool {});
+    assert_eq!(res.principal_token, Some("principal_token".to_string()));
+    assert_eq!(res.pool.principal, Uint128::new(50000));
+    assert_eq!(res.pool.usteak, Uint128::new(45456));
+    // The remaining principal needs ceil(50,000 / 1.1) = 45,455 usteak; the rounding dust from the
+    // redemption is left to the yield holders
+    assert_eq!(res.pool.reserved, Uint128::new(1));
+
+    // Redeeming the rest of the principal hands the last of the pool to the yield shares and
+    // retires them
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("principal_token", &[]),
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "user_2".to_string(),
+            amount: Uint128::new(50000),
+            msg: to_binary(&ReceiveMsg::RedeemPrincipal { receiver: None }).unwrap(),
+        }),
+    )
+    .unwrap();
+
+    let res: YieldPoolResponse = query_helper(deps.as_ref(), QueryMsg::YieldPool {});
+    assert_eq!(res.pool.principal, Uint128::zero());
+    assert_eq!(res.pool.usteak, Uint128::new(2));
+    assert_eq!(res.pool.reserved, Uint128::new(2));
+    assert_eq!(res.pool.shares, Uint128::zero());
+    assert_eq!(res.pool.epoch, 1);
+
+    // A later split starts the pool afresh: 110,000 usteak is worth 121,000 principal, and the new
+    // shares alone earn the yield from here on
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("steak_token", &[]),
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "user_3".to_string(),
+            amount: Uint128::new(110000),
+            msg: to_binary(&ReceiveMsg::SplitYield { receiver: None }).unwrap(),
+        }),
+    )
+    .unwrap();
+
+    // Rewards raise the exchange rate to 1.2
+    // Backing of the principal: ceil(121,000 / 1.2) = 100,834 usteak
+    // Yield: 110,000 - 100,834 = 9,166 usteak, less what the index truncates
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 400000, "uxyz"),
+        Delegation::new("bob", 400000, "uxyz"),
+        Delegation::new("charlie", 400000, "uxyz"),
+    ]);
 
+    let res: YieldPositionResponse = query_helper(
+        deps.as_ref(),
+        QueryMsg::YieldPosition {
+            user: "user_3".to_string(),
+        },
+    );
     assert_eq!(
-        compute_redelegations_for_removal(
-            &current_delegations[3],
-            &current_delegations[..3],
-            "uxyz"
-        ),
-        expected,
+        res,
+        YieldPositionResponse {
+            shares: Uint128::new(121000),
+            claimable: Uint128::new(9165),
+        }
+    );
+
+    // The retired shares keep what they earned before, and nothing since
+    let res: YieldPositionResponse = query_helper(
+        deps.as_ref(),
+        QueryMsg::YieldPosition {
+            user: "user_1".to_string(),
+        },
+    );
+    assert_eq!(
+        res,
+        YieldPositionResponse {
+            shares: Uint128::zero(),
+            claimable: Uint128::new(2),
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user_1", &[]),
+        ExecuteMsg::ClaimYield { receiver: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "user_1".to_string(),
+                amount: Uint128::new(2)
+            })
+            .unwrap(),
+            funds: vec![]
+        })
     );
 }
 
 #[test]
-fn computing_redelegations_for_rebalancing() {
-    let current_delegations = vec![
-        Delegation::new("alice", 69420, "uxyz"),
-        Delegation::new("bob", 1234, "uxyz"),
-        Delegation::new("charlie", 88888, "uxyz"),
-        Delegation::new("dave", 40471, "uxyz"),
-        Delegation::new("evan", 2345, "uxyz"),
-    ];
-    let active_validators: Vec<String> = vec![
-        "alice".to_string(),
-        "bob".to_string(),
-        "charlie".to_string(),
-        "dave".to_string(),
-        "evan".to_string(),
-    ];
-    // native_token_per_validator = (69420 + 88888 + 1234 + 40471 + 2345) / 4 = 40471
-    // remainer = 3
-    // src_delegations:
-    //  - alice:   69420 - (40471 + 1) = 28948
-    //  - charlie: 88888 - (40471 + 1) = 48416
-    // dst_delegations:
-    //  - bob:     (40471 + 1) - 1234  = 39238
-    //  - evan:    (40471 + 0) - 2345  = 38126
-    //
-    // Round 1: alice --(28948)--> bob
-    // src_delegations:
-    //  - charlie: 48416
-    // dst_delegations:
-    //  - bob:     39238 - 28948 = 10290
-    //  - evan:    38126
-    //
-    // Round 2: charlie --(10290)--> bob
-    // src_delegations:
-    //  - charlie: 48416 - 10290 = 38126
-    // dst_delegations:
-    //  - evan:    38126
-    //
-    // Round 3: charlie --(38126)--> evan
-    // Queues are emptied
-    let expected = vec![
-        Redelegation::new("alice", "bob", 28948, "uxyz"),
-        Redelegation::new("charlie", "bob", 10290, "uxyz"),
-        Redelegation::new("charlie", "evan", 38126, "uxyz"),
-    ];
+fn rebasing() {
+    let mut deps = setup_test();
 
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 333334, "uxyz"),
+        Delegation::new("bob", 333333, "uxyz"),
+        Delegation::new("charlie", 333333, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000000);
+
+    let wrap_msg = ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+        sender: "user_1".to_string(),
+        amount: Uint128::new(100000),
+        msg: to_binary(&ReceiveMsg::WrapRebasing { receiver: None }).unwrap(),
+    });
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("random_token", &[]),
+        wrap_msg.clone(),
+    )
+    .unwrap_err();
     assert_eq!(
-        compute_redelegations_for_rebalancing(
-            active_validators,
-            &current_delegations,
-            Uint128::from(10_u64),
-            // mock the same mining power on every validator
-            |_| Ok(40471_u128.into())
-        )
-        .unwrap(),
-        expected,
+        err,
+        ContractError::UnexpectedToken {
+            expected: "Steak".to_string(),
+            received: "random_token".to_string()
+        }
     );
 
-    let partially_active = vec![
-        "alice".to_string(),
-        "charlie".to_string(),
-        "dave".to_string(),
-        "evan".to_string(),
-    ];
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("steak_token", &[]),
+        wrap_msg,
+    )
+    .unwrap();
 
-    let partially_expected = vec![
-        Redelegation::new("alice", "dave", 10118, "uxyz"),
-        Redelegation::new("alice", "evan", 8712, "uxyz"),
-        Redelegation::new("charlie", "evan", 38299, "uxyz"),
-    ];
+    // Rewards raise the exchange rate to 1.1; the balance rebases accordingly
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 366667, "uxyz"),
+        Delegation::new("bob", 366667, "uxyz"),
+        Delegation::new("charlie", 366666, "uxyz"),
+    ]);
+
+    let res: RebasingBalanceResponse = query_helper(
+        deps.as_ref(),
+        QueryMsg::RebasingBalance {
+            user: "user_1".to_string(),
+        },
+    );
     assert_eq!(
-        compute_redelegations_for_rebalancing(
-            partially_active.clone(),
-            &current_delegations,
-            Uint128::from(10_u64),
-            // mock the same mining power on every validator
-            |_| Ok(50589_u128.into())
-        )
-        .unwrap(),
-        partially_expected,
+        res,
+        RebasingBalanceResponse {
+            shares: Uint128::new(100000),
+            balance: Uint128::new(110000),
+        }
     );
 
-    let partially_expected_minimums = vec![
-        Redelegation::new("alice", "evan", 18830, "uxyz"),
-        Redelegation::new("charlie", "evan", 29414, "uxyz"),
-    ];
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user_1", &[]),
+        ExecuteMsg::TransferRebasing {
+            recipient: "user_2".to_string(),
+            amount: Uint128::new(110001),
+        },
+    )
+    .unwrap_err();
     assert_eq!(
-        compute_redelegations_for_rebalancing(
-            partially_active,
-            &current_delegations,
-            Uint128::from(15_000_u64),
-            // mock the same mining power on every validator
-            |d| Ok(50589u128.into())
-        )
-        .unwrap(),
-        partially_expected_minimums,
+        err,
+        ContractError::InsufficientRebasingBalance {
+            balance: Uint128::new(100000),
+            requested: Uint128::new(100001)
+        }
+    );
+
+    // 55,000 native is worth 50,000 usteak
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user_1", &[]),
+        ExecuteMsg::TransferRebasing {
+            recipient: "user_2".to_string(),
+            amount: Uint128::new(55000),
+        },
+    )
+    .unwrap();
+
+    let res: RebasingBalanceResponse = query_helper(
+        deps.as_ref(),
+        QueryMsg::RebasingBalance {
+            user: "user_2".to_string(),
+        },
+    );
+    assert_eq!(res.shares, Uint128::new(50000));
+    assert_eq!(res.balance, Uint128::new(55000));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user_2", &[]),
+        ExecuteMsg::UnwrapRebasing {
+            amount: Uint128::new(11000),
+            receiver: Some("user_3".to_string()),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "user_3".to_string(),
+                amount: Uint128::new(10000)
+            })
+            .unwrap(),
+            funds: vec![]
+        })
+    );
+
+    let res: RebasingSupplyResponse = query_helper(deps.as_ref(), QueryMsg::RebasingSupply {});
+    assert_eq!(
+        res,
+        RebasingSupplyResponse {
+            total_shares: Uint128::new(90000),
+            total_supply: Uint128::new(99000),
+        }
+    );
+}
+
+#[test]
+fn managing_pol() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 333334, "uxyz"),
+        Delegation::new("bob", 333333, "uxyz"),
+        Delegation::new("charlie", 333333, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000000);
+
+    let configure_msg = ExecuteMsg::ConfigurePol {
+        pair: "steak_pair".to_string(),
+        lp_token: "steak_lp".to_string(),
+        fee_rate: Decimal::from_ratio(1u128, 2u128),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        configure_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        configure_msg,
+    )
+    .unwrap();
+
+    // Reserves accrued from fees and previously withdrawn liquidity
+    state
+        .pol_native_reserve
+        .save(deps.as_mut().storage, &Uint128::new(1000))
+        .unwrap();
+    state
+        .pol_usteak_reserve
+        .save(deps.as_mut().storage, &Uint128::new(900))
+        .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[Coin::new(500, "uxyz")]),
+        ExecuteMsg::AddPol {
+            bond_amount: Uint128::new(1501),
+            slippage_tolerance: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientPolNative {
+            available: Uint128::new(1500)
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[Coin::new(500, "uxyz")]),
+        ExecuteMsg::AddPol {
+            bond_amount: Uint128::zero(),
+            slippage_tolerance: Some(Decimal::percent(1)),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+                spender: "steak_pair".to_string(),
+                amount: Uint128::new(900),
+                expires: None
+            })
+            .unwrap(),
+            funds: vec![]
+        })
+    );
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_pair".to_string(),
+            msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
+                assets: vec![
+                    Asset {
+                        info: AssetInfo::Token {
+                            contract_addr: "steak_token".to_string()
+                        },
+                        amount: Uint128::new(900)
+                    },
+                    Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: "uxyz".to_string()
+                        },
+                        amount: Uint128::new(1500)
+                    },
+                ],
+                slippage_tolerance: Some(Decimal::percent(1)),
+                receiver: None
+            })
+            .unwrap(),
+            funds: vec![Coin::new(1500, "uxyz")]
+        })
+    );
+
+    // The hub holds 10% of the LP supply
+    deps.querier
+        .set_cw20_balance("steak_lp", MOCK_CONTRACT_ADDR, 100);
+    deps.querier.set_pair_pool(
+        "steak_pair",
+        PoolResponse {
+            assets: vec![
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: "steak_token".to_string(),
+                    },
+                    amount: Uint128::new(9000),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uxyz".to_string(),
+                    },
+                    amount: Uint128::new(15000),
+                },
+            ],
+            total_share: Uint128::new(1000),
+        },
+    );
+
+    let res: PolStateResponse = query_helper(deps.as_ref(), QueryMsg::PolState {});
+    assert_eq!(
+        res,
+        PolStateResponse {
+            pair: Some("steak_pair".to_string()),
+            lp_token: Some("steak_lp".to_string()),
+            fee_rate: Decimal::from_ratio(1u128, 2u128),
+            native_reserve: Uint128::zero(),
+            usteak_reserve: Uint128::zero(),
+            lp_balance: Uint128::new(100),
+            position_native: Uint128::new(1500),
+            position_usteak: Uint128::new(900),
+            position_value: Uint128::new(2400),
+        }
+    );
+
+    deps.querier.set_bank_balances(&[Coin::new(0, "uxyz")]);
+    deps.querier
+        .set_cw20_balance("steak_token", MOCK_CONTRACT_ADDR, 0);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::RemovePol {
+            lp_amount: Uint128::new(100),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_lp".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "steak_pair".to_string(),
+                amount: Uint128::new(100),
+                msg: to_binary(&PairCw20HookMsg::WithdrawLiquidity {}).unwrap()
+            })
+            .unwrap(),
+            funds: vec![]
+        })
+    );
+    let callback = CallbackMsg::SettlePolWithdrawal {
+        native_before: Uint128::zero(),
+        usteak_before: Uint128::zero(),
+    };
+    assert_eq!(
+        res.messages[1].msg,
+        callback
+            .into_cosmos_msg(&Addr::unchecked(MOCK_CONTRACT_ADDR))
+            .unwrap()
+    );
+
+    // The pair returns the underlying assets to the hub
+    deps.querier.set_bank_balances(&[Coin::new(1500, "uxyz")]);
+    deps.querier
+        .set_cw20_balance("steak_token", MOCK_CONTRACT_ADDR, 900);
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(callback),
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::CollectPol {
+            receiver: "treasury".to_string(),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "treasury".to_string(),
+            amount: vec![Coin::new(1500, "uxyz")]
+        })
+    );
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "treasury".to_string(),
+                amount: Uint128::new(900)
+            })
+            .unwrap(),
+            funds: vec![]
+        })
+    );
+}
+
+#[test]
+fn liquid_unstaking() {
+    let mut deps = setup_test();
+
+    let unstake_msg = |min_out: u128| {
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "user_1".to_string(),
+            amount: Uint128::new(1000),
+            msg: to_binary(&ReceiveMsg::LiquidUnstake {
+                min_out: Uint128::new(min_out),
+                receiver: None,
+                deadline: None,
+            })
+            .unwrap(),
+        })
+    };
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("steak_token", &[]),
+        unstake_msg(1000),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::LiquidUnstakeNotConfigured {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::ConfigurePol {
+            pair: "steak_pair".to_string(),
+            lp_token: "steak_lp".to_string(),
+            fee_rate: Decimal::zero(),
+        },
+    )
+    .unwrap();
+    deps.querier.set_pair_pool(
+        "steak_pair",
+        PoolResponse {
+            assets: vec![
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: "steak_token".to_string(),
+                    },
+                    amount: Uint128::new(100000),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uxyz".to_string(),
+                    },
+                    amount: Uint128::new(105000),
+                },
+            ],
+            total_share: Uint128::new(100000),
+        },
+    );
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 336667, "uxyz"),
+        Delegation::new("bob", 336667, "uxyz"),
+        Delegation::new("charlie", 336666, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000000);
+
+    // 1000 usteak swaps for 105000 * 1000 / 101000 = 1039 uxyz, but redeems for only 1010 uxyz at
+    // the exchange rate
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("steak_token", &[]),
+        unstake_msg(1000),
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "steak_pair".to_string(),
+                amount: Uint128::new(1000),
+                msg: to_binary(&PairCw20HookMsg::Swap {
+                    belief_price: None,
+                    max_spread: None,
+                    to: None
+                })
+                .unwrap()
+            })
+            .unwrap(),
+            funds: vec![]
+        })
+    );
+    let callback = |native_before: u128| CallbackMsg::SettleLiquidUnstake {
+        receiver: "user_1".to_string(),
+        native_before: Uint128::new(native_before),
+        native_at_par: Uint128::new(1010),
+        min_out: Uint128::new(1000),
+    };
+    assert_eq!(
+        res.messages[1].msg,
+        callback(0)
+            .into_cosmos_msg(&Addr::unchecked(MOCK_CONTRACT_ADDR))
+            .unwrap()
+    );
+
+    // The pair returns 1039 uxyz; the user is paid 1010 and the other 29 are kept in the arbitrage
+    // reserve
+    deps.querier.set_bank_balances(&[Coin::new(1039, "uxyz")]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(callback(0)),
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "user_1".to_string(),
+            amount: vec![Coin::new(1010, "uxyz")]
+        })
+    );
+
+    let state = State::default();
+    assert_eq!(
+        state.arb_reserve.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(29)
+    );
+
+    // A worse fill of 1005 uxyz is all paid to the user, and nothing is kept
+    deps.querier.set_bank_balances(&[Coin::new(1005, "uxyz")]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(callback(0)),
+    )
+    .unwrap();
+
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "user_1".to_string(),
+            amount: vec![Coin::new(1005, "uxyz")]
+        })
+    );
+    assert_eq!(
+        state.arb_reserve.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(29)
+    );
+
+    // A fill below the minimum fails the unstake
+    deps.querier.set_bank_balances(&[Coin::new(990, "uxyz")]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(callback(0)),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MinOutNotMet {
+            estimated: Uint128::new(990),
+            min_out: Uint128::new(1000),
+        }
+    );
+    deps.querier.set_bank_balances(&[]);
+
+    // The pair pays more than the minimum, but the user would not
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("steak_token", &[]),
+        unstake_msg(1020),
+    )
+    .unwrap();
+
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "liquid_unstake" && attr.value == "fallback"));
+
+    // Too much slippage; the Steak is queued for unbonding instead
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("steak_token", &[]),
+        unstake_msg(1040),
+    )
+    .unwrap();
+
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "liquid_unstake" && attr.value == "fallback"));
+
+    let request = state
+        .unbond_requests
+        .load(deps.as_ref().storage, (1u64, &Addr::unchecked("user_1")))
+        .unwrap();
+    assert_eq!(request.shares, Uint128::new(2000));
+}
+
+#[test]
+fn arbitraging() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 333334, "uxyz"),
+        Delegation::new("bob", 333333, "uxyz"),
+        Delegation::new("charlie", 333333, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000000);
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::ConfigurePol {
+            pair: "steak_pair".to_string(),
+            lp_token: "steak_lp".to_string(),
+            fee_rate: Decimal::zero(),
+        },
+    )
+    .unwrap();
+    // Steak trades at roughly 0.91 native, while it redeems for 1 native
+    deps.querier.set_pair_pool(
+        "steak_pair",
+        PoolResponse {
+            assets: vec![
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: "steak_token".to_string(),
+                    },
+                    amount: Uint128::new(110000),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uxyz".to_string(),
+                    },
+                    amount: Uint128::new(100000),
+                },
+            ],
+            total_share: Uint128::new(100000),
+        },
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::Arbitrage {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ArbNotConfigured {});
+
+    let update_msg = |min_discount: u64| ExecuteMsg::UpdateArbConfig {
+        min_discount: Decimal::percent(min_discount),
+        max_per_execution: Uint128::new(1000),
+        max_in_flight: Uint128::new(1500),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        update_msg(10),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[Coin::new(5000, "uxyz")]),
+        ExecuteMsg::FundArbReserve {},
+    )
+    .unwrap();
+
+    // 1000 native buys 1089 usteak, a discount of ~8.2%
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::Arbitrage {},
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DiscountBelowThreshold {
+            min_discount: Decimal::percent(10)
+        }
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        update_msg(5),
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::Arbitrage {},
+    )
+    .unwrap();
+
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_pair".to_string(),
+            msg: to_binary(&PairExecuteMsg::Swap {
+                offer_asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uxyz".to_string()
+                    },
+                    amount: Uint128::new(1000)
+                },
+                belief_price: None,
+                max_spread: None,
+                to: None
+            })
+            .unwrap(),
+            funds: vec![Coin::new(1000, "uxyz")]
+        })
+    );
+
+    // Only 500 native of in-flight capacity is left; it buys 547 usteak
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::Arbitrage {},
+    )
+    .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::Arbitrage {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoArbCapacity {});
+
+    let res: ArbStateResponse = query_helper(deps.as_ref(), QueryMsg::ArbState {});
+    assert_eq!(
+        res,
+        ArbStateResponse {
+            min_discount: Decimal::percent(5),
+            max_per_execution: Uint128::new(1000),
+            max_in_flight: Uint128::new(1500),
+            reserve: Uint128::new(3500),
+            in_flight_cost: Uint128::new(1500),
+            in_flight_shares: Uint128::new(1636),
+        }
+    );
+
+    let request = state
+        .unbond_requests
+        .load(
+            deps.as_ref().storage,
+            (1u64, &Addr::unchecked(MOCK_CONTRACT_ADDR)),
+        )
+        .unwrap();
+    assert_eq!(request.shares, Uint128::new(1636));
+
+    // Nobody else can queue Steak for the hub, which would throw off the settlement
+    for msg in [
+        ReceiveMsg::QueueUnbond {
+            receiver: Some(MOCK_CONTRACT_ADDR.to_string()),
+            min_out: None,
+            deadline: None,
+        },
+        ReceiveMsg::LiquidUnstake {
+            min_out: Uint128::MAX,
+            receiver: Some(MOCK_CONTRACT_ADDR.to_string()),
+            deadline: None,
+        },
+    ] {
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("steak_token", &[]),
+            ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+                sender: "user_1".to_string(),
+                amount: Uint128::new(1000),
+                msg: to_binary(&msg).unwrap(),
+            }),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::HubAsUnbondReceiver {});
+    }
+
+    // The batch has been submitted, and finished unbonding at a higher exchange rate
+    state
+        .previous_batches
+        .save(
+            deps.as_mut().storage,
+            1u64,
+            &Batch {
+                id: 1,
+                reconciled: true,
+                total_shares: Uint128::new(1636),
+                amount_unclaimed: Uint128::new(1800),
+                est_unbond_end_time: 10000,
+            },
+        )
+        .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(20000),
+        mock_info("jake", &[]),
+        ExecuteMsg::SettleArbitrage {},
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.messages[0],
+        SubMsg::reply_on_success(
+            Delegation::new("bob", 300, "uxyz")
+                .to_cosmos_msg(MOCK_CONTRACT_ADDR.to_string())
+                .unwrap(),
+            REPLY_REGISTER_RECEIVED_COINS
+        )
+    );
+
+    let res: ArbStateResponse = query_helper(deps.as_ref(), QueryMsg::ArbState {});
+    assert_eq!(res.reserve, Uint128::new(5000));
+    assert_eq!(res.in_flight_cost, Uint128::zero());
+    assert_eq!(res.in_flight_shares, Uint128::zero());
+}
+
+#[test]
+fn recording_user_history() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("user_1", &[Coin::new(1000000, "uxyz")]),
+        ExecuteMsg::Bond { receiver: None },
+    )
+    .unwrap();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 341667, "uxyz"),
+        Delegation::new("bob", 341667, "uxyz"),
+        Delegation::new("charlie", 341666, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000000);
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10001),
+        mock_info("steak_token", &[]),
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "user_1".to_string(),
+            amount: Uint128::new(23456),
+            msg: to_binary(&ReceiveMsg::QueueUnbond {
+                receiver: None,
+                min_out: None,
+                deadline: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap();
+
+    // The batch has finished unbonding
+    state
+        .previous_batches
+        .save(
+            deps.as_mut().storage,
+            1u64,
+            &Batch {
+                id: 1,
+                reconciled: true,
+                total_shares: Uint128::new(23456),
+                amount_unclaimed: Uint128::new(24042),
+                est_unbond_end_time: 20000,
+            },
+        )
+        .unwrap();
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(20001),
+        mock_info("user_1", &[]),
+        ExecuteMsg::WithdrawUnbonded { receiver: None },
+    )
+    .unwrap();
+
+    let res: UserHistoryResponse = query_helper(
+        deps.as_ref(),
+        QueryMsg::UserHistory {
+            address: "user_1".to_string(),
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert_eq!(
+        res.totals,
+        UserTotals {
+            bonded: Uint128::new(1000000),
+            minted: Uint128::new(1000000),
+            unbonded: Uint128::new(23456),
+            withdrawn: Uint128::new(24042),
+            entries: 3,
+        }
+    );
+    assert_eq!(
+        res.entries
+            .iter()
+            .map(|entry| entry.action.clone())
+            .collect::<Vec<_>>(),
+        vec![
+            HistoryAction::Bond,
+            HistoryAction::Unbond,
+            HistoryAction::Withdraw
+        ]
+    );
+
+    let res: UserHistoryResponse = query_helper(
+        deps.as_ref(),
+        QueryMsg::UserHistory {
+            address: "user_1".to_string(),
+            start_after: Some(1),
+            limit: None,
+        },
+    );
+    assert_eq!(
+        res.entries,
+        vec![UserHistoryEntry {
+            id: 2,
+            time: 20001,
+            height: 12345,
+            action: HistoryAction::Withdraw,
+            native_amount: Uint128::new(24042),
+            usteak_amount: Uint128::zero(),
+        }]
+    );
+}
+
+#[test]
+fn recording_daily_snapshots() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 341667, "uxyz"),
+        Delegation::new("bob", 341667, "uxyz"),
+        Delegation::new("charlie", 341666, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000000);
+
+    let bond = |deps: &mut OwnedDeps<_, _, _>, timestamp: u64| {
+        execute(
+            deps.as_mut(),
+            mock_env_at_timestamp(timestamp),
+            mock_info("user_1", &[Coin::new(12345, "uxyz")]),
+            ExecuteMsg::Bond { receiver: None },
+        )
+        .unwrap();
+    };
+
+    // Only the first bond of the day records a snapshot
+    bond(&mut deps, 86400 + 100);
+    deps.querier.set_cw20_total_supply("steak_token", 1012043);
+    bond(&mut deps, 86400 + 200);
+
+    state
+        .snapshot_pending_fees
+        .save(deps.as_mut().storage, &Uint128::new(2500))
+        .unwrap();
+    bond(&mut deps, 3 * 86400 + 50);
+
+    let res: Vec<DailySnapshot> = query_helper(
+        deps.as_ref(),
+        QueryMsg::DailySnapshots {
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert_eq!(
+        res,
+        vec![
+            DailySnapshot {
+                day: 1,
+                time: 86400 + 100,
+                exchange_rate: Decimal::from_ratio(1025000u128, 1000000u128),
+                total_native: Uint128::new(1025000),
+                total_usteak: Uint128::new(1000000),
+                fees: Uint128::zero(),
+            },
+            DailySnapshot {
+                day: 3,
+                time: 3 * 86400 + 50,
+                exchange_rate: Decimal::from_ratio(1025000u128, 1012043u128),
+                total_native: Uint128::new(1025000),
+                total_usteak: Uint128::new(1012043),
+                fees: Uint128::new(2500),
+            },
+        ]
+    );
+
+    let pending_fees = state
+        .snapshot_pending_fees
+        .load(deps.as_ref().storage)
+        .unwrap();
+    assert_eq!(pending_fees, Uint128::zero());
+}
+
+#[test]
+fn checking_invariants() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 341667, "uxyz"),
+        Delegation::new("bob", 341667, "uxyz"),
+        Delegation::new("charlie", 341666, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000000);
+    deps.querier.set_bank_balances(&[Coin::new(1000, "uxyz")]);
+    deps.querier
+        .set_cw20_balance("steak_token", MOCK_CONTRACT_ADDR, 0);
+
+    let res: InvariantsResponse = query_helper(deps.as_ref(), QueryMsg::CheckInvariants {});
+    assert_eq!(
+        res,
+        InvariantsResponse {
+            healthy: true,
+            violations: vec![],
+        }
+    );
+    migrate(deps.as_mut(), mock_env(), MigrateMsg::Migrate {}).unwrap();
+
+    // The reserves claim more native than the hub holds, and usteak was queued for unbonding
+    // without being received
+    state
+        .arb_reserve
+        .save(deps.as_mut().storage, &Uint128::new(1001))
+        .unwrap();
+    state
+        .pending_batch
+        .update(deps.as_mut().storage, |mut batch| -> StdResult<_> {
+            batch.usteak_to_burn = Uint128::new(500);
+            Ok(batch)
+        })
+        .unwrap();
+
+    let res: InvariantsResponse = query_helper(deps.as_ref(), QueryMsg::CheckInvariants {});
+    assert_eq!(
+        res,
+        InvariantsResponse {
+            healthy: false,
+            violations: vec![
+                "native liabilities 1001 exceed balance 1000".to_string(),
+                "usteak liabilities 500 exceed balance 0".to_string(),
+            ],
+        }
+    );
+
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg::Migrate {}).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvariantsViolated {
+            violations: vec![
+                "native liabilities 1001 exceed balance 1000".to_string(),
+                "usteak liabilities 500 exceed balance 0".to_string(),
+            ]
+        }
+    );
+}
+
+#[test]
+fn updating_rounding_policy() {
+    let mut deps = setup_test();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 341667, "uxyz"),
+        Delegation::new("bob", 341667, "uxyz"),
+        Delegation::new("charlie", 341666, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000000);
+
+    let res: RoundingPolicyResponse = query_helper(deps.as_ref(), QueryMsg::RoundingPolicy {});
+    assert_eq!(
+        res,
+        RoundingPolicyResponse {
+            policy: RoundingPolicy::FavorProtocol,
+            mint_rounds_up: false,
+            unbond_rounds_up: false,
+        }
+    );
+
+    // 12345 native is worth 12043.9 usteak
+    let minted = |deps: &mut OwnedDeps<_, _, _>| {
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("user_1", &[Coin::new(12345, "uxyz")]),
+            ExecuteMsg::Bond { receiver: None },
+        )
+        .unwrap();
+        res.messages[1].msg.clone()
+    };
+    let mint_msg = |amount: u128| {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: "user_1".to_string(),
+                amount: Uint128::new(amount),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    };
+    assert_eq!(minted(&mut deps), mint_msg(12043));
+
+    let update_msg = ExecuteMsg::UpdateRoundingPolicy {
+        policy: RoundingPolicy::FavorUser,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        update_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        update_msg,
+    )
+    .unwrap();
+    assert_eq!(minted(&mut deps), mint_msg(12044));
+
+    let res: RoundingPolicyResponse = query_helper(deps.as_ref(), QueryMsg::RoundingPolicy {});
+    assert!(res.mint_rounds_up && res.unbond_rounds_up);
+}
+
+#[test]
+fn changing_denom() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 341667, "uxyz"),
+        Delegation::new("bob", 341667, "uxyz"),
+        Delegation::new("charlie", 341666, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000000);
+    deps.querier.set_bank_balances(&[Coin::new(500, "uxyz")]);
+
+    let propose_msg = ExecuteMsg::ProposeDenomChange {
+        new_denom: "uabc".to_string(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        propose_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::ConfirmDenomChange {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoPendingDenomChange {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        propose_msg,
+    )
+    .unwrap();
+
+    let blockers = vec![
+        "1025000uxyz is still delegated".to_string(),
+        "1000000 usteak is still outstanding".to_string(),
+        "500uxyz is held by the hub".to_string(),
+    ];
+    let res: DenomChangePreviewResponse =
+        query_helper(deps.as_ref(), QueryMsg::DenomChangePreview {});
+    assert_eq!(
+        res,
+        DenomChangePreviewResponse {
+            current_denom: "uxyz".to_string(),
+            pending_denom: Some("uabc".to_string()),
+            blockers: blockers.clone(),
+        }
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::ConfirmDenomChange {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::DenomChangeBlocked { blockers });
+    assert_eq!(state.denom.load(deps.as_ref().storage).unwrap(), "uxyz");
+
+    // Once everything in the old denom has been unwound, the change goes through
+    deps.querier.set_staking_delegations(&[]);
+    deps.querier.set_cw20_total_supply("steak_token", 0);
+    deps.querier.set_bank_balances(&[Coin::new(123, "uabc")]);
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::ConfirmDenomChange {},
+    )
+    .unwrap();
+
+    let res: DenomChangePreviewResponse =
+        query_helper(deps.as_ref(), QueryMsg::DenomChangePreview {});
+    assert_eq!(res.current_denom, "uabc");
+    assert_eq!(res.pending_denom, None);
+    // The new denom already held is not mistaken for rewards
+    assert_eq!(
+        state.prev_denom.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(123)
+    );
+}
+
+#[test]
+fn emergency_undelegating() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 400000, "uxyz"),
+        Delegation::new("bob", 350000, "uxyz"),
+        Delegation::new("charlie", 250000, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 900000);
+    state
+        .unlocked_coins
+        .save(
+            deps.as_mut().storage,
+            &vec![Coin::new(5000, "uxyz"), Coin::new(10, "ukrw")],
+        )
+        .unwrap();
+
+    let queue_msg = |sender: &str, amount: u128| {
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: sender.to_string(),
+            amount: Uint128::new(amount),
+            msg: to_binary(&ReceiveMsg::QueueUnbond {
+                receiver: None,
+                min_out: None,
+                deadline: None,
+            })
+            .unwrap(),
+        })
+    };
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(12345),
+        mock_info("steak_token", &[]),
+        queue_msg("user_1", 100000),
+    )
+    .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(20000),
+        mock_info("larry", &[]),
+        ExecuteMsg::EmergencyUndelegateAll {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoPendingEmergency {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(20000),
+        mock_info("jake", &[]),
+        ExecuteMsg::ProposeEmergencyUndelegation {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(20000),
+        mock_info("larry", &[]),
+        ExecuteMsg::ProposeEmergencyUndelegation {},
+    )
+    .unwrap();
+
+    let res: EmergencyUndelegationResponse =
+        query_helper(deps.as_ref(), QueryMsg::EmergencyUndelegation {});
+    assert_eq!(
+        res,
+        EmergencyUndelegationResponse {
+            ready_at: Some(279200),
+            batch_id: None,
+        }
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(279199),
+        mock_info("larry", &[]),
+        ExecuteMsg::EmergencyUndelegateAll {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::EmergencyTimelocked { ready_at: 279200 });
+
+    // Besides the 100,000 usteak queued, the hub holds 60,000 usteak of its own, e.g. for locks
+    deps.querier
+        .set_cw20_balance("steak_token", MOCK_CONTRACT_ADDR, 160000);
+
+    // Everything is undelegated, and the Steak already queued is burned
+    let env = mock_env_at_timestamp(279200);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("larry", &[]),
+        ExecuteMsg::EmergencyUndelegateAll {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 4);
+    assert_eq!(
+        res.messages[0],
+        SubMsg::reply_on_success(
+            Undelegation::new("alice", 400000, "uxyz")
+                .to_cosmos_msg(env.contract.address.to_string())
+                .unwrap(),
+            REPLY_REGISTER_RECEIVED_COINS
+        ),
+    );
+    assert_eq!(
+        res.messages[2],
+        SubMsg::reply_on_success(
+            Undelegation::new("charlie", 250000, "uxyz")
+                .to_cosmos_msg(env.contract.address.to_string())
+                .unwrap(),
+            REPLY_REGISTER_RECEIVED_COINS
+        ),
+    );
+    assert_eq!(
+        res.messages[3],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::new(100000)
+            })
+            .unwrap(),
+            funds: vec![],
+        })),
+    );
+
+    // The batch is shared by the Steak supply the hub does not hold itself, and pays out the Native
+    // Token unlocked as well: 1,005,000 * 840,000 / 900,000 = 938,000
+    assert_eq!(
+        state
+            .previous_batches
+            .load(deps.as_ref().storage, 1)
+            .unwrap(),
+        Batch {
+            id: 1,
+            reconciled: false,
+            total_shares: Uint128::new(840000),
+            amount_unclaimed: Uint128::new(938000),
+            est_unbond_end_time: 279200 + 1814400,
+        }
+    );
+    assert_eq!(
+        state.emergency_held.load(deps.as_ref().storage).unwrap(),
+        EmergencyHeld {
+            usteak: Uint128::new(60000),
+            native: Uint128::new(67000),
+        }
+    );
+    assert_eq!(
+        state.unlocked_coins.load(deps.as_ref().storage).unwrap(),
+        vec![Coin::new(10, "ukrw")]
+    );
+
+    // Rewards withdrawn by the undelegations can't be reinvested anymore, and are split between the
+    // batch and the Steak held by their shares: 9,000 * 840,000 / 900,000 = 8,400 to the batch
+    reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: REPLY_REGISTER_RECEIVED_COINS,
+            result: cosmwasm_std::SubMsgResult::Ok(SubMsgResponse {
+                events: vec![Event::new("coin_received")
+                    .add_attribute("receiver", MOCK_CONTRACT_ADDR.to_string())
+                    .add_attribute("amount", "9000uxyz")],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        state
+            .previous_batches
+            .load(deps.as_ref().storage, 1)
+            .unwrap()
+            .amount_unclaimed,
+        Uint128::new(946400)
+    );
+    assert_eq!(
+        state.emergency_held.load(deps.as_ref().storage).unwrap(),
+        EmergencyHeld {
+            usteak: Uint128::new(60000),
+            native: Uint128::new(67600),
+        }
+    );
+    assert_eq!(
+        state.unlocked_coins.load(deps.as_ref().storage).unwrap(),
+        vec![Coin::new(10, "ukrw")]
+    );
+    let res: EmergencyUndelegationResponse =
+        query_helper(deps.as_ref(), QueryMsg::EmergencyUndelegation {});
+    assert_eq!(
+        res,
+        EmergencyUndelegationResponse {
+            ready_at: None,
+            batch_id: Some(1),
+        }
+    );
+
+    // The hub is wound down for good
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(279300),
+        mock_info("user_3", &[Coin::new(12345, "uxyz")]),
+        ExecuteMsg::Bond { receiver: None },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::WoundDown {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(279300),
+        mock_info("larry", &[]),
+        ExecuteMsg::ProposeEmergencyUndelegation {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::WoundDown {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(279300),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::SubmitBatch {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::WoundDown {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(279300),
+        mock_info("jake", &[]),
+        ExecuteMsg::Rebalance {
+            minimum: Uint128::zero(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::WoundDown {});
+
+    // Other holders claim their share by queueing their Steak, which is burned right away. The hub
+    // has meanwhile released 20,000 usteak of its own, which joins the batch first along with
+    // 67,600 * 20,000 / 60,000 = 22,533 of the Native Token set aside
+    deps.querier
+        .set_cw20_balance("steak_token", MOCK_CONTRACT_ADDR, 40000 + 450000);
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(279300),
+        mock_info("steak_token", &[]),
+        queue_msg("user_2", 450000),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::new(450000)
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    assert_eq!(
+        state
+            .unbond_requests
+            .load(deps.as_ref().storage, (1, &Addr::unchecked("user_2")))
+            .unwrap(),
+        UnbondRequest {
+            id: 1,
+            user: Addr::unchecked("user_2"),
+            shares: Uint128::new(450000),
+            sender: None,
+        }
+    );
+    assert_eq!(
+        state
+            .previous_batches
+            .load(deps.as_ref().storage, 1)
+            .unwrap(),
+        Batch {
+            id: 1,
+            reconciled: false,
+            total_shares: Uint128::new(860000),
+            amount_unclaimed: Uint128::new(968933),
+            est_unbond_end_time: 279200 + 1814400,
+        }
+    );
+    assert_eq!(
+        state.emergency_held.load(deps.as_ref().storage).unwrap(),
+        EmergencyHeld {
+            usteak: Uint128::new(40000),
+            native: Uint128::new(45067),
+        }
+    );
+}
+
+#[test]
+fn entering_safe_mode() {
+    let mut deps = setup_test();
+
+    let safe_mode_msg =
+        |until_height: Option<u64>, until_time: Option<u64>| ExecuteMsg::EnterSafeMode {
+            until_height,
+            until_time,
+            reason: None,
+        };
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("jake", &[]),
+        safe_mode_msg(Some(12400), None),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Safe mode must lift by itself at some point in the future
+    for (until_height, until_time) in [(None, None), (Some(12345), None), (None, Some(10000))] {
+        let err = execute(
+            deps.as_mut(),
+            mock_env_at_timestamp(10000),
+            mock_info("larry", &[]),
+            safe_mode_msg(until_height, until_time),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidSafeModeExpiry {});
+    }
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("larry", &[]),
+        safe_mode_msg(Some(12400), Some(20000)),
+    )
+    .unwrap();
+    let res: Option<SafeMode> = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(10000),
+        QueryMsg::SafeMode {},
+    );
+    assert_eq!(
+        res,
+        Some(SafeMode {
+            until_height: Some(12400),
+            until_time: Some(20000),
+        })
+    );
+
+    // Bonds, unbonding requests and proofs are refused
+    for (sender, funds, msg) in [
+        (
+            "user_1",
+            vec![Coin::new(1000, "uxyz")],
+            ExecuteMsg::Bond { receiver: None },
+        ),
+        (
+            "steak_token",
+            vec![],
+            ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+                sender: "user_1".to_string(),
+                amount: Uint128::new(1000),
+                msg: to_binary(&ReceiveMsg::QueueUnbond {
+                    receiver: None,
+                    min_out: None,
+                    deadline: None,
+                })
+                .unwrap(),
+            }),
+        ),
+        (
+            "miner",
+            vec![],
+            ExecuteMsg::SubmitProof {
+                nonce: Uint64::new(1),
+                validator: "alice".to_string(),
+            },
+        ),
+    ] {
+        let err = execute(
+            deps.as_mut(),
+            mock_env_at_timestamp(10000),
+            mock_info(sender, &funds),
+            msg,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InSafeMode {});
+    }
+
+    // It lifts by itself once the first of its expiries is reached
+    let mut env = mock_env_at_timestamp(10000);
+    env.block.height = 12400;
+    let res: Option<SafeMode> = query_helper_env(deps.as_ref(), env.clone(), QueryMsg::SafeMode {});
+    assert_eq!(res, None);
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("user_1", &[Coin::new(1000, "uxyz")]),
+        ExecuteMsg::Bond { receiver: None },
+    )
+    .unwrap();
+
+    // Or when the owner lifts it
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("larry", &[]),
+        safe_mode_msg(None, Some(20000)),
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("jake", &[]),
+        ExecuteMsg::ExitSafeMode { reason: None },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("larry", &[]),
+        ExecuteMsg::ExitSafeMode { reason: None },
+    )
+    .unwrap();
+    let res: Option<SafeMode> = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(10000),
+        QueryMsg::SafeMode {},
+    );
+    assert_eq!(res, None);
+}
+
+#[test]
+fn guarding_with_pause_only_key() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("jake", &[]),
+        ExecuteMsg::ProposeGuardianChange {
+            guardian: Some("guardian".to_string()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("larry", &[]),
+        ExecuteMsg::ProposeGuardianChange {
+            guardian: Some("guardian".to_string()),
+        },
+    )
+    .unwrap();
+
+    // The guardian has no powers until the timelock has passed
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("guardian", &[]),
+        ExecuteMsg::PauseValidator {
+            validator: "alice".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let ready_at = 10000 + GUARDIAN_CHANGE_DELAY_SECONDS;
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(ready_at - 1),
+        mock_info("larry", &[]),
+        ExecuteMsg::ApplyGuardianChange {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::GuardianChangeTimelocked { ready_at });
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(ready_at),
+        mock_info("larry", &[]),
+        ExecuteMsg::ApplyGuardianChange {},
+    )
+    .unwrap();
+
+    let res: OwnershipResponse = query_helper(deps.as_ref(), QueryMsg::Ownership {});
+    assert_eq!(res.guardian, Some("guardian".to_string()));
+    assert_eq!(res.guardian_change, None);
+
+    // The guardian can pause validators and enter safe mode...
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(ready_at),
+        mock_info("guardian", &[]),
+        ExecuteMsg::PauseValidator {
+            validator: "alice".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(ready_at),
+        mock_info("guardian", &[]),
+        ExecuteMsg::ReceiveOnlyPauseValidator {
+            validator: "bob".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(ready_at),
+        mock_info("guardian", &[]),
+        ExecuteMsg::EnterSafeMode {
+            until_height: Some(12400),
+            until_time: None,
+            reason: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        state.validators_active.load(deps.as_ref().storage).unwrap(),
+        vec!["bob".to_string(), "charlie".to_string()]
+    );
+    assert!(state
+        .safe_mode
+        .may_load(deps.as_ref().storage)
+        .unwrap()
+        .is_some());
+
+    // ...but not lift a pause, change the configs or manage the guardian
+    for msg in [
+        ExecuteMsg::UnPauseValidator {
+            validator: "alice".to_string(),
+            reason: None,
+        },
+        ExecuteMsg::ExitSafeMode { reason: None },
+        ExecuteMsg::UpdateLockBoost {
+            boost_rate: Decimal::percent(50),
+        },
+        ExecuteMsg::ProposeGuardianChange { guardian: None },
+    ] {
+        let err = execute(
+            deps.as_mut(),
+            mock_env_at_timestamp(ready_at),
+            mock_info("guardian", &[]),
+            msg,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    // Revoking the guardian goes through the same timelock
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(ready_at),
+        mock_info("larry", &[]),
+        ExecuteMsg::ProposeGuardianChange { guardian: None },
+    )
+    .unwrap();
+    let res: OwnershipResponse = query_helper(deps.as_ref(), QueryMsg::Ownership {});
+    assert_eq!(
+        res.guardian_change,
+        Some(GuardianChange {
+            guardian: None,
+            ready_at: ready_at + GUARDIAN_CHANGE_DELAY_SECONDS,
+        })
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(ready_at + GUARDIAN_CHANGE_DELAY_SECONDS),
+        mock_info("larry", &[]),
+        ExecuteMsg::ApplyGuardianChange {},
+    )
+    .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(ready_at + GUARDIAN_CHANGE_DELAY_SECONDS),
+        mock_info("guardian", &[]),
+        ExecuteMsg::PauseValidator {
+            validator: "charlie".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(ready_at + GUARDIAN_CHANGE_DELAY_SECONDS),
+        mock_info("larry", &[]),
+        ExecuteMsg::ApplyGuardianChange {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoPendingGuardianChange {});
+}
+
+#[test]
+fn recovering_from_halt() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    let batches = vec![
+        Batch {
+            id: 1,
+            reconciled: true,
+            total_shares: Uint128::new(92876),
+            amount_unclaimed: Uint128::new(95197),
+            est_unbond_end_time: 100000,
+        },
+        Batch {
+            id: 2,
+            reconciled: false,
+            total_shares: Uint128::new(1456),
+            amount_unclaimed: Uint128::new(1506),
+            est_unbond_end_time: 2000000,
+        },
+    ];
+    for batch in &batches {
+        state
+            .previous_batches
+            .save(deps.as_mut().storage, batch.id, batch)
+            .unwrap();
+    }
+    state
+        .pending_batch
+        .save(
+            deps.as_mut().storage,
+            &PendingBatch {
+                id: 3,
+                usteak_to_burn: Uint128::zero(),
+                est_unbond_start_time: 269200,
+            },
+        )
+        .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(20000),
+        mock_info("jake", &[]),
+        ExecuteMsg::ProposeHaltRecovery {
+            batch_shift: 3600,
+            reset_mining_clock: true,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(20000),
+        mock_info("larry", &[]),
+        ExecuteMsg::ProposeHaltRecovery {
+            batch_shift: 1209601,
+            reset_mining_clock: true,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::HaltShiftTooLarge { max: 1209600 });
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(20000),
+        mock_info("larry", &[]),
+        ExecuteMsg::ProposeHaltRecovery {
+            batch_shift: 0,
+            reset_mining_clock: false,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::EmptyHaltRecovery {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(20000),
+        mock_info("larry", &[]),
+        ExecuteMsg::ApplyHaltRecovery {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoPendingHaltRecovery {});
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(20000),
+        mock_info("larry", &[]),
+        ExecuteMsg::ProposeHaltRecovery {
+            batch_shift: 3600,
+            reset_mining_clock: true,
+        },
+    )
+    .unwrap();
+
+    let res: Option<HaltRecovery> = query_helper(deps.as_ref(), QueryMsg::HaltRecovery {});
+    assert_eq!(
+        res,
+        Some(HaltRecovery {
+            batch_shift: 3600,
+            reset_mining_clock: true,
+            ready_at: 106400,
+        })
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(106399),
+        mock_info("larry", &[]),
+        ExecuteMsg::ApplyHaltRecovery {},
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::HaltRecoveryTimelocked { ready_at: 106400 }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(106400),
+        mock_info("larry", &[]),
+        ExecuteMsg::ApplyHaltRecovery {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.events[0].attributes[3..],
+        vec![
+            attr("est_unbond_start_time", "272800"),
+            attr("batches_shifted", "2"),
+            attr("prev_last_mined_timestamp", "10000"),
+            attr("last_mined_timestamp", "106400"),
+        ]
+    );
+
+    // Only the pending batch and the batches still unbonding are delayed
+    assert_eq!(
+        state
+            .pending_batch
+            .load(deps.as_ref().storage)
+            .unwrap()
+            .est_unbond_start_time,
+        272800
+    );
+    assert_eq!(
+        state
+            .previous_batches
+            .load(deps.as_ref().storage, 1)
+            .unwrap()
+            .est_unbond_end_time,
+        100000
+    );
+    assert_eq!(
+        state
+            .previous_batches
+            .load(deps.as_ref().storage, 2)
+            .unwrap()
+            .est_unbond_end_time,
+        2003600
+    );
+    assert_eq!(
+        state
+            .miner_last_mined_timestamp
+            .load(deps.as_ref().storage)
+            .unwrap(),
+        Uint64::new(106400)
+    );
+
+    let res: Option<HaltRecovery> = query_helper(deps.as_ref(), QueryMsg::HaltRecovery {});
+    assert_eq!(res, None);
+}
+
+#[test]
+fn reinvesting() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 333334, "uxyz"),
+        Delegation::new("bob", 333333, "uxyz"),
+        Delegation::new("charlie", 333333, "uxyz"),
+    ]);
+    state
+        .prev_denom
+        .save(deps.as_mut().storage, &Uint128::from(0_u32))
+        .unwrap();
+    deps.querier
+        .set_bank_balances(&[Coin::new(234u128, "uxyz")]);
+
+    // After the swaps, `unlocked_coins` should contain only uxyz and unknown denoms
+    state
+        .unlocked_coins
+        .save(
+            deps.as_mut().storage,
+            &vec![
+                Coin::new(234, "uxyz"),
+                Coin::new(
+                    69420,
+                    "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B",
+                ),
+            ],
+        )
+        .unwrap();
+
+    let modifier = 1_000_000_000_000_000_000_u128;
+
+    state
+        .total_mining_power
+        .save(deps.as_mut().storage, &Uint128::from(15_u128.mul(modifier)))
+        .unwrap();
+
+    state
+        .validator_mining_powers
+        .save(
+            deps.as_mut().storage,
+            "alice".to_string(),
+            &5_u128.mul(modifier).into(),
+        )
+        .unwrap();
+    state
+        .validator_mining_powers
+        .save(
+            deps.as_mut().storage,
+            "bob".to_string(),
+            &5_u128.mul(modifier).into(),
+        )
+        .unwrap();
+    state
+        .validator_mining_powers
+        .save(
+            deps.as_mut().storage,
+            "charlie".to_string(),
+            &5_u128.mul(modifier).into(),
+        )
+        .unwrap();
+
+    let env = mock_env();
+    // Bob has the smallest amount of delegations, so all proceeds go to him
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(CallbackMsg::Reinvest {}),
+    )
+    .unwrap();
+
+    // decode first message as to MsgUndelegate
+    let _decoded_message =
+        if let CosmosMsg::Stargate { type_url: _, value } = res.messages[0].msg.clone() {
+            // assert_eq!(type_url, "/liquidstaking.staking.v1beta1.MsgDelegate");
+            let msg_decoded: MsgDelegate = prost::Message::decode(value.as_slice()).unwrap();
+            // assert_eq!(msg_decoded.validator_address, "bob");
+            Some(msg_decoded)
+        } else {
+            None
+        };
+    // decode all messages to MsgUndelegate and transpose as result
+    let _decoded_messages = res
+        .messages
+        .iter()
+        .map(|msg| {
+            if let CosmosMsg::Stargate { type_url: _, value } = msg.msg.clone() {
+                // assert_eq!(type_url, "/liquidstaking.staking.v1beta1.MsgDelegate");
+                let msg_decoded: MsgDelegate = prost::Message::decode(value.as_slice()).unwrap();
+                // assert_eq!(msg_decoded.validator_address, "bob");
+                Some(msg_decoded)
+            } else {
+                None
+            }
+        })
+        .filter(Option::is_some)
+        .collect::<Option<Vec<MsgDelegate>>>()
+        .unwrap();
+
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0],
+        SubMsg {
+            id: 0,
+            msg: Delegation::new("bob", 234 - 23, "uxyz")
+                .to_cosmos_msg(env.contract.address.to_string())
+                .unwrap(),
+            gas_limit: None,
+            reply_on: ReplyOn::Never
+        },
+        "bob"
+    );
+    let send_msg = BankMsg::Send {
+        to_address: "the_fee_man".into(),
+        amount: vec![Coin::new(23u128, "uxyz")],
+    };
+    assert_eq!(
+        res.messages[1],
+        SubMsg {
+            id: REPLY_FEE_FORWARD_DONE,
+            msg: CosmosMsg::Bank(send_msg),
+            gas_limit: None,
+            reply_on: ReplyOn::Always
+        },
+        "fee"
+    );
+
+    // Storage should have been updated
+    let unlocked_coins = state.unlocked_coins.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        unlocked_coins,
+        vec![Coin::new(
+            69420,
+            "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B"
+        )],
+        "unlocked_coins"
+    );
+}
+
+#[test]
+fn reinvesting_with_mining() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 333334, "uxyz"),
+        Delegation::new("bob", 333333, "uxyz"),
+        Delegation::new("charlie", 333333, "uxyz"),
+    ]);
+    state
+        .prev_denom
+        .save(deps.as_mut().storage, &Uint128::from(0_u32))
+        .unwrap();
+    deps.querier
+        .set_bank_balances(&[Coin::new(234u128, "uxyz")]);
+
+    // After the swaps, `unlocked_coins` should contain only uxyz and unknown denoms
+    state
+        .unlocked_coins
+        .save(
+            deps.as_mut().storage,
+            &vec![
+                Coin::new(234, "uxyz"),
+                Coin::new(
+                    69420,
+                    "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B",
+                ),
+            ],
+        )
+        .unwrap();
+
+    let modifier = 1_000_000_000_000_000_000_u128;
+
+    state
+        .total_mining_power
+        .save(deps.as_mut().storage, &Uint128::from(15_u128.mul(modifier)))
+        .unwrap();
+
+    state
+        .validator_mining_powers
+        .save(
+            deps.as_mut().storage,
+            "alice".to_string(),
+            &4_u128.mul(modifier).into(),
+        )
+        .unwrap();
+    state
+        .validator_mining_powers
+        .save(
+            deps.as_mut().storage,
+            "bob".to_string(),
+            &4_u128.mul(modifier).into(),
+        )
+        .unwrap();
+    state
+        .validator_mining_powers
+        .save(
+            deps.as_mut().storage,
+            "charlie".to_string(),
+            &7_u128.mul(modifier).into(),
+        )
+        .unwrap();
+
+    let env = mock_env();
+    // Bob has the smallest amount of delegations, so all proceeds go to him
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(CallbackMsg::Reinvest {}),
+    )
+    .unwrap();
+
+    // decode first message as to MsgUndelegate
+    let _decoded_message =
+        if let CosmosMsg::Stargate { type_url: _, value } = res.messages[0].msg.clone() {
+            // assert_eq!(type_url, "/liquidstaking.staking.v1beta1.MsgDelegate");
+            let msg_decoded: MsgDelegate = prost::Message::decode(value.as_slice()).unwrap();
+            // assert_eq!(msg_decoded.validator_address, "bob");
+            Some(msg_decoded)
+        } else {
+            None
+        };
+    // decode all messages to MsgUndelegate and transpose as result
+    let _decoded_messages = res
+        .messages
+        .iter()
+        .map(|msg| {
+            if let CosmosMsg::Stargate { type_url: _, value } = msg.msg.clone() {
+                // assert_eq!(type_url, "/liquidstaking.staking.v1beta1.MsgDelegate");
+                let msg_decoded: MsgDelegate = prost::Message::decode(value.as_slice()).unwrap();
+                // assert_eq!(msg_decoded.validator_address, "bob");
+                Some(msg_decoded)
+            } else {
+                None
+            }
+        })
+        .filter(Option::is_some)
+        .collect::<Option<Vec<MsgDelegate>>>()
+        .unwrap();
+
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0],
+        SubMsg {
+            id: 0,
+            msg: Delegation::new("charlie", 234 - 23, "uxyz")
+                .to_cosmos_msg(env.contract.address.to_string())
+                .unwrap(),
+            gas_limit: None,
+            reply_on: ReplyOn::Never
+        },
+        "charlie"
+    );
+    let send_msg = BankMsg::Send {
+        to_address: "the_fee_man".into(),
+        amount: vec![Coin::new(23u128, "uxyz")],
+    };
+    assert_eq!(
+        res.messages[1],
+        SubMsg {
+            id: REPLY_FEE_FORWARD_DONE,
+            msg: CosmosMsg::Bank(send_msg),
+            gas_limit: None,
+            reply_on: ReplyOn::Always
+        },
+        "fee"
+    );
+
+    // Storage should have been updated
+    let unlocked_coins = state.unlocked_coins.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        unlocked_coins,
+        vec![Coin::new(
+            69420,
+            "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B"
+        )],
+        "unlocked_coins"
+    );
+}
+
+#[test]
+fn rebalancing_without_mining_power() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    // no proof has been accepted yet, so every validator's target is an even third of 1,000,000
+    assert_eq!(
+        state
+            .total_mining_power
+            .load(deps.as_ref().storage)
+            .unwrap(),
+        Uint128::zero()
+    );
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 300000, "uxyz"),
+        Delegation::new("bob", 500000, "uxyz"),
+        Delegation::new("charlie", 200000, "uxyz"),
+    ]);
+    state
+        .prev_denom
+        .save(deps.as_mut().storage, &Uint128::zero())
+        .unwrap();
+
+    // rewards are reinvested into the validator furthest below its share
+    state
+        .unlocked_coins
+        .save(deps.as_mut().storage, &vec![Coin::new(1000, "uxyz")])
+        .unwrap();
+    deps.querier
+        .set_bank_balances(&[Coin::new(1000u128, "uxyz")]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(CallbackMsg::Reinvest {}),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        Delegation::new("charlie", 900, "uxyz")
+            .to_cosmos_msg(MOCK_CONTRACT_ADDR.to_string())
+            .unwrap()
+    );
+
+    // and rebalancing evens the stake out instead of moving it all off
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::Rebalance {
+            minimum: Uint128::from(10u128),
+        },
+    )
+    .unwrap();
+    let redelegations = res
+        .messages
+        .iter()
+        .map(|m| m.msg.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        redelegations,
+        vec![
+            Redelegation::new("bob", "alice", 33334, "uxyz")
+                .to_cosmos_msg(MOCK_CONTRACT_ADDR.to_string())
+                .unwrap(),
+            Redelegation::new("bob", "charlie", 133333, "uxyz")
+                .to_cosmos_msg(MOCK_CONTRACT_ADDR.to_string())
+                .unwrap(),
+        ]
+    );
+}
+
+#[test]
+fn capping_by_voting_power() {
+    let mut deps = setup_test();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::UpdateVotingPowerCap {
+            max_share: Some(Decimal::percent(12)),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    for max_share in [Decimal::zero(), Decimal::percent(150)] {
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("larry", &[]),
+            ExecuteMsg::UpdateVotingPowerCap {
+                max_share: Some(max_share),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidVotingPowerCap {});
+    }
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateVotingPowerCap {
+            max_share: Some(Decimal::percent(12)),
+        },
+    )
+    .unwrap();
+
+    // Nothing is capped until the voting power has been refreshed, which the chain must serve
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 400000, "uxyz"),
+        Delegation::new("bob", 300000, "uxyz"),
+        Delegation::new("charlie", 300000, "uxyz"),
+    ]);
+    let res: VotingPowerCapResponse = query_helper(deps.as_ref(), QueryMsg::VotingPowerCap {});
+    assert_eq!(res.refreshed_at, None);
+    assert!(res.validators.iter().all(|v| v.cap.is_none()));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::RefreshVotingPower {},
+    )
+    .unwrap_err();
+
+    // Alice holds 1,000,000 of voting power besides the hub's, so the hub may only delegate 200,000
+    // to her before she exceeds 12% of 10,000,000
+    deps.querier.set_voting_power(
+        10_000_000,
+        &[("alice", 1_400_000), ("bob", 500_000), ("charlie", 500_000)],
+    );
+    let env = mock_env();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("jake", &[]),
+        ExecuteMsg::RefreshVotingPower {},
+    )
+    .unwrap();
+    let res: VotingPowerCapResponse = query_helper(deps.as_ref(), QueryMsg::VotingPowerCap {});
+    assert_eq!(
+        res,
+        VotingPowerCapResponse {
+            max_share: Some(Decimal::percent(12)),
+            total_voting_power: Uint128::new(10_000_000),
+            refreshed_at: Some(env.block.time.seconds()),
+            validators: vec![
+                ValidatorVotingPower {
+                    validator: "alice".to_string(),
+                    voting_power: Uint128::new(1_400_000),
+                    cap: Some(Uint128::new(200_000)),
+                },
+                ValidatorVotingPower {
+                    validator: "bob".to_string(),
+                    voting_power: Uint128::new(500_000),
+                    cap: Some(Uint128::new(1_000_000)),
+                },
+                ValidatorVotingPower {
+                    validator: "charlie".to_string(),
+                    voting_power: Uint128::new(500_000),
+                    cap: Some(Uint128::new(1_000_000)),
+                },
+            ],
+        }
+    );
+
+    // What is cut from alice's even third is spread over bob and charlie
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::Rebalance {
+            minimum: Uint128::from(10u128),
+        },
+    )
+    .unwrap();
+    let redelegations = res
+        .messages
+        .iter()
+        .map(|m| m.msg.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        redelegations,
+        vec![
+            Redelegation::new("alice", "bob", 99999, "uxyz")
+                .to_cosmos_msg(MOCK_CONTRACT_ADDR.to_string())
+                .unwrap(),
+            Redelegation::new("alice", "charlie", 99999, "uxyz")
+                .to_cosmos_msg(MOCK_CONTRACT_ADDR.to_string())
+                .unwrap(),
+        ]
+    );
+
+    // Deposits pass over validators they would lift above their cap, even the smallest
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 150000, "uxyz"),
+        Delegation::new("bob", 425000, "uxyz"),
+        Delegation::new("charlie", 425000, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000000);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user_1", &[Coin::new(1000, "uxyz")]),
+        ExecuteMsg::Bond { receiver: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        Delegation::new("bob", 1000, "uxyz")
+            .to_cosmos_msg(MOCK_CONTRACT_ADDR.to_string())
+            .unwrap()
+    );
+
+    // and once the cap is lifted, stake is split evenly again
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateVotingPowerCap { max_share: None },
+    )
+    .unwrap();
+    let res: DelegationDriftResponse = query_helper(deps.as_ref(), QueryMsg::DelegationDrift {});
+    assert_eq!(res.max_deviation_bps, 1833);
+}
+
+#[test]
+fn deferring_small_reinvests() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::UpdateMinReinvestAmount {
+            amount: Uint128::new(500),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateMinReinvestAmount {
+            amount: Uint128::new(500),
+        },
+    )
+    .unwrap();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 333334, "uxyz"),
+        Delegation::new("bob", 333333, "uxyz"),
+        Delegation::new("charlie", 333333, "uxyz"),
+    ]);
+    state
+        .prev_denom
+        .save(deps.as_mut().storage, &Uint128::zero())
+        .unwrap();
+    deps.querier
+        .set_bank_balances(&[Coin::new(234u128, "uxyz")]);
+
+    // Rewards below the minimum are kept rather than delegated
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(CallbackMsg::Reinvest {}),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(res.events[0].ty, "steakhub/reinvest_deferred");
+    let res: PendingReinvestResponse = query_helper(deps.as_ref(), QueryMsg::PendingReinvest {});
+    assert_eq!(
+        res,
+        PendingReinvestResponse {
+            min_reinvest_amount: Uint128::new(500),
+            pending: Uint128::new(234),
+        }
+    );
+
+    // The next harvest leaves them out of the balance it measures rewards against, so that the
+    // next reinvest folds them in
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Harvest {},
+    )
+    .unwrap();
+    assert_eq!(
+        state.prev_denom.load(deps.as_ref().storage).unwrap(),
+        Uint128::zero()
+    );
+    let res: PendingReinvestResponse = query_helper(deps.as_ref(), QueryMsg::PendingReinvest {});
+    assert_eq!(res.pending, Uint128::zero());
+
+    deps.querier
+        .set_bank_balances(&[Coin::new(534u128, "uxyz")]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(CallbackMsg::Reinvest {}),
+    )
+    .unwrap();
+    let harvested = res
+        .events
+        .iter()
+        .find(|event| event.ty == "steakhub/harvested")
+        .unwrap();
+    // 534 harvested, 10% of which is taken as fees
+    assert!(harvested.attributes.contains(&attr("denom_bonded", "481")));
+}
+
+#[test]
+fn dripping_reinvested_rewards() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::UpdateRewardDrip { period: Some(1000) },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateRewardDrip { period: Some(0) },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidRewardDripPeriod {
+            max: MAX_REWARD_DRIP_SECONDS
+        }
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateRewardDrip { period: Some(1000) },
+    )
+    .unwrap();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 333334, "uxyz"),
+        Delegation::new("bob", 333333, "uxyz"),
+        Delegation::new("charlie", 333333, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000000);
+    deps.querier
+        .set_bank_balances(&[Coin::new(234u128, "uxyz")]);
+    state
+        .prev_denom
+        .save(deps.as_mut().storage, &Uint128::zero())
+        .unwrap();
+    state
+        .unlocked_coins
+        .save(deps.as_mut().storage, &vec![Coin::new(234, "uxyz")])
+        .unwrap();
+
+    // The 211 bonded after fees is released over the next 1000 seconds
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(CallbackMsg::Reinvest {}),
+    )
+    .unwrap();
+    assert!(res.events[0]
+        .attributes
+        .contains(&attr("rewards_unreleased", "211")));
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 333334, "uxyz"),
+        Delegation::new("bob", 333544, "uxyz"),
+        Delegation::new("charlie", 333333, "uxyz"),
+    ]);
+
+    // The exchange rate does not jump right after the harvest
+    let res: StateResponse = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(10000),
+        QueryMsg::State {},
+    );
+    assert_eq!(res.total_native, Uint128::new(1000211));
+    assert_eq!(res.exchange_rate, Decimal::one());
+
+    let res: RewardDripResponse = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(10500),
+        QueryMsg::RewardDrip {},
+    );
+    assert_eq!(
+        res,
+        RewardDripResponse {
+            period: Some(1000),
+            drip: Some(RewardDrip {
+                amount: Uint128::new(211),
+                start_time: 10000,
+                end_time: 11000,
+            }),
+            unreleased: Uint128::new(105),
+        }
+    );
+
+    let res: StateResponse = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(11000),
+        QueryMsg::State {},
+    );
+    assert_eq!(
+        res.exchange_rate,
+        Decimal::from_ratio(1000211u128, 1000000u128)
+    );
+
+    // Disabling the drip releases the rest at once
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10500),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateRewardDrip { period: None },
+    )
+    .unwrap();
+    let res: RewardDripResponse = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(10500),
+        QueryMsg::RewardDrip {},
+    );
+    assert_eq!(res.unreleased, Uint128::zero());
+}
+
+#[test]
+fn reinvesting_fee_split() {
+    let mut deps = setup_test_fee_split();
+    let state = State::default();
+    let env = mock_env();
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 333334, "uxyz"),
+        Delegation::new("bob", 333333, "uxyz"),
+        Delegation::new("charlie", 333333, "uxyz"),
+    ]);
+    state
+        .prev_denom
+        .save(deps.as_mut().storage, &Uint128::from(0_u32))
+        .unwrap();
+    deps.querier
+        .set_bank_balances(&[Coin::new(234u128, "uxyz")]);
+
+    // After the swaps, `unlocked_coins` should contain only uxyz and unknown denoms
+    state
+        .unlocked_coins
+        .save(
+            deps.as_mut().storage,
+            &vec![
+                Coin::new(234, "uxyz"),
+                Coin::new(
+                    69420,
+                    "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B",
+                ),
+            ],
+        )
+        .unwrap();
+
+    let modifier = 1_000_000_000_000_000_000_u128;
+
+    state
+        .total_mining_power
+        .save(deps.as_mut().storage, &Uint128::from(15_u128.mul(modifier)))
+        .unwrap();
+
+    state
+        .validator_mining_powers
+        .save(
+            deps.as_mut().storage,
+            "alice".to_string(),
+            &1_u128.mul(modifier).into(),
+        )
+        .unwrap();
+    state
+        .validator_mining_powers
+        .save(
+            deps.as_mut().storage,
+            "bob".to_string(),
+            &12_u128.mul(modifier).into(),
+        )
+        .unwrap();
+    state
+        .validator_mining_powers
+        .save(
+            deps.as_mut().storage,
+            "charlie".to_string(),
+            &2_u128.mul(modifier).into(),
+        )
+        .unwrap();
+
+    // Bob has the smallest amount of delegations, so all proceeds go to him
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(CallbackMsg::Reinvest {}),
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0],
+        SubMsg {
+            id: 0,
+            msg: Delegation::new("bob", 234 - 23, "uxyz")
+                .to_cosmos_msg(env.contract.address.to_string())
+                .unwrap(),
+            gas_limit: None,
+            reply_on: ReplyOn::Never
+        }
+    );
+    let send_msg = pfc_fee_split::fee_split_msg::ExecuteMsg::Deposit { flush: false };
+
+    assert_eq!(
+        res.messages[1],
+        SubMsg {
+            id: REPLY_FEE_FORWARD_DONE,
+            msg: send_msg
+                .into_cosmos_msg("fee_split_contract", vec![Coin::new(23u128, "uxyz")])
+                .unwrap(),
+            gas_limit: None,
+            reply_on: ReplyOn::Always
+        }
+    );
+
+    // Storage should have been updated
+    let unlocked_coins = state.unlocked_coins.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        unlocked_coins,
+        vec![Coin::new(
+            69420,
+            "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B"
+        )],
+    );
+
+    // 10% of 234 is 23.4; the remainder is carried into the next harvests instead of being lost
+    assert_eq!(
+        state.fee_carry.load(deps.as_ref().storage).unwrap(),
+        Decimal256::from_ratio(4u128, 10u128)
+    );
+
+    // Once the fee account takes the fees, nothing is left behind for the reply to escrow
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: REPLY_FEE_FORWARD_DONE,
+            result: cosmwasm_std::SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    )
+    .unwrap();
+    assert!(res.events.is_empty());
+    assert_eq!(
+        state.fee_forward.may_load(deps.as_ref().storage).unwrap(),
+        None
+    );
+    let escrowed: Uint128 = query_helper(deps.as_ref(), QueryMsg::EscrowedFees {});
+    assert_eq!(escrowed, Uint128::zero());
+
+    let mut fees_charged = vec![];
+    for _ in 0..2 {
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(MOCK_CONTRACT_ADDR, &[]),
+            ExecuteMsg::Callback(CallbackMsg::Reinvest {}),
+        )
+        .unwrap();
+        fees_charged.push(res.events[0].attributes[3].value.clone());
+    }
+    assert_eq!(fees_charged, vec!["23", "24"]);
+    assert_eq!(
+        state.fee_carry.load(deps.as_ref().storage).unwrap(),
+        Decimal256::from_ratio(2u128, 10u128)
+    );
+
+    // A fee split contract failing to take the fees has them escrowed, without reverting the
+    // reinvest
+    let res = reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: REPLY_FEE_FORWARD_DONE,
+            result: cosmwasm_std::SubMsgResult::Err("unknown variant `deposit`".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.events,
+        vec![Event::new("steakhub/fee_forward_failed")
+            .add_attribute("fees", "24")
+            .add_attribute("escrowed_fees", "24")
+            .add_attribute("error", "unknown variant `deposit`")]
+    );
+    let escrowed: Uint128 = query_helper(deps.as_ref(), QueryMsg::EscrowedFees {});
+    assert_eq!(escrowed, Uint128::new(24));
+    let res: Metrics = query_helper(deps.as_ref(), QueryMsg::Metrics {});
+    assert_eq!(res.fee_forward_failures, 1);
+
+    // Anyone can retry sending them to the fee account
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("jake", &[]),
+        ExecuteMsg::RetryFeeForward {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(
+            pfc_fee_split::fee_split_msg::ExecuteMsg::Deposit { flush: false }
+                .into_cosmos_msg("fee_split_contract", vec![Coin::new(24u128, "uxyz")])
+                .unwrap()
+        )]
+    );
+    let escrowed: Uint128 = query_helper(deps.as_ref(), QueryMsg::EscrowedFees {});
+    assert_eq!(escrowed, Uint128::zero());
+
+    let err = execute(
+        deps.as_mut(),
+        env,
+        mock_info("jake", &[]),
+        ExecuteMsg::RetryFeeForward {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoEscrowedFees {});
+}
+
+#[test]
+fn operating_without_fees() {
+    let instantiate_msg = |fee_amount: Decimal| InstantiateMsg {
+        cw20_code_id: 69420,
+        owner: "larry".to_string(),
+        name: "Steak Token".to_string(),
+        symbol: "STEAK".to_string(),
+        denom: "uxyz".to_string(),
+        fee_account_type: "None".to_string(),
+        fee_account: "".to_string(),
+        fee_amount,
+        max_fee_amount: Decimal::from_ratio(20_u128, 100_u128),
+        decimals: 6,
+        epoch_period: 259200,
+        unbond_period: 1814400,
+        validators: vec!["alice".to_string(), "bob".to_string()],
+        label: None,
+        token_admin: None,
+        marketing: None,
+        proof_hash: None,
+    };
+
+    // Without a fee account, the hub cannot be instantiated charging a fee
+    let mut deps = mock_dependencies();
+    let err = instantiate(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("deployer", &[]),
+        instantiate_msg(Decimal::from_ratio(10_u128, 100_u128)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::FeesDisabled {});
+
+    // With a zero fee it can, and no fee account is needed
+    instantiate(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("deployer", &[]),
+        instantiate_msg(Decimal::zero()),
+    )
+    .unwrap();
+    let state = State::default();
+    assert_eq!(
+        state.fee_account_type.load(deps.as_ref().storage).unwrap(),
+        FeeType::None
+    );
+    assert_eq!(
+        state.fee_account.load(deps.as_ref().storage).unwrap(),
+        Addr::unchecked(MOCK_CONTRACT_ADDR)
+    );
+
+    // An existing hub can only disable its fee module once its fee is zero
+    let mut deps = setup_test();
+    let disable_msg = ExecuteMsg::TransferFeeAccount {
+        fee_account_type: "None".to_string(),
+        new_fee_account: "".to_string(),
+        reason: None,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        disable_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::FeesDisabled {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateFee {
+            new_fee: Decimal::zero(),
+            reason: None,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        disable_msg,
+    )
+    .unwrap();
+
+    // The previous fee account is kept, for when the module is enabled back
+    let res: ConfigResponse = query_helper(deps.as_ref(), QueryMsg::Config {});
+    assert_eq!(res.fee_type, "None".to_string());
+    assert_eq!(res.fee_account, "the_fee_man".to_string());
+
+    // While it is disabled, the fee cannot be raised
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateFee {
+            new_fee: Decimal::from_ratio(5_u128, 100_u128),
+            reason: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::FeesDisabled {});
+
+    // Rewards are bonded in full, with nothing forwarded
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 333334, "uxyz"),
+        Delegation::new("bob", 333333, "uxyz"),
+        Delegation::new("charlie", 333333, "uxyz"),
+    ]);
+    state
+        .prev_denom
+        .save(deps.as_mut().storage, &Uint128::zero())
+        .unwrap();
+    deps.querier
+        .set_bank_balances(&[Coin::new(234u128, "uxyz")]);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(CallbackMsg::Reinvest {}),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    let harvested = res
+        .events
+        .iter()
+        .find(|event| event.ty == "steakhub/harvested")
+        .unwrap();
+    assert!(harvested.attributes.contains(&attr("fees_deducted", "0")));
+    assert!(harvested.attributes.contains(&attr("denom_bonded", "234")));
+
+    // Fees escrowed before the module was disabled stay with the hub
+    state
+        .escrowed_fees
+        .save(deps.as_mut().storage, &Uint128::new(24))
+        .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::RetryFeeForward {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::FeesDisabled {});
+}
+
+#[test]
+fn queuing_unbond() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    // Only Steak token is accepted for unbonding requests
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("random_token", &[]),
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "hacker".to_string(),
+            amount: Uint128::new(69420),
+            msg: to_binary(&ReceiveMsg::QueueUnbond {
+                receiver: None,
+                min_out: None,
+                deadline: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        err,
+        ContractError::UnexpectedToken {
+            expected: "Steak".to_string(),
+            received: "random_token".to_string()
+        }
+    );
+
+    // User 1 creates an unbonding request before `est_unbond_start_time` is reached. The unbond
+    // request is saved, but not the pending batch is not submitted for unbonding
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(12345), // est_unbond_start_time = 269200
+        mock_info("steak_token", &[]),
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "user_1".to_string(),
+            amount: Uint128::new(23456),
+            msg: to_binary(&ReceiveMsg::QueueUnbond {
+                receiver: None,
+                min_out: None,
+                deadline: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 0);
+
+    // User 2 creates an unbonding request after `est_unbond_start_time` is reached. The unbond
+    // request is saved, and the pending is automatically submitted for unbonding
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(269201), // est_unbond_start_time = 269200
+        mock_info("steak_token", &[]),
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "user_2".to_string(),
+            amount: Uint128::new(69420),
+            msg: to_binary(&ReceiveMsg::QueueUnbond {
+                receiver: Some("user_3".to_string()),
+                min_out: None,
+                deadline: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 1);
+    assert!(res.events[0].attributes.contains(&attr("funder", "user_2")));
+    assert!(res.events[0]
+        .attributes
+        .contains(&attr("receiver", "user_3")));
+    assert_eq!(
+        res.messages[0],
+        SubMsg {
+            id: 0,
+            msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                msg: to_binary(&ExecuteMsg::SubmitBatch {}).unwrap(),
+                funds: vec![]
+            }),
+            gas_limit: None,
+            reply_on: ReplyOn::Never
+        }
+    );
+
+    // The users' unbonding requests should have been saved
+    let ubr1 = state
+        .unbond_requests
+        .load(deps.as_ref().storage, (1u64, &Addr::unchecked("user_1")))
+        .unwrap();
+    let ubr2 = state
+        .unbond_requests
+        .load(deps.as_ref().storage, (1u64, &Addr::unchecked("user_3")))
+        .unwrap();
+
+    assert_eq!(
+        ubr1,
+        UnbondRequest {
+            id: 1,
+            user: Addr::unchecked("user_1"),
+            shares: Uint128::new(23456),
+            sender: None,
+        }
+    );
+    assert_eq!(
+        ubr2,
+        UnbondRequest {
+            id: 1,
+            user: Addr::unchecked("user_3"),
+            shares: Uint128::new(69420),
+            sender: Some(Addr::unchecked("user_2")),
+        }
+    );
+
+    // Requests can be looked up by the address that queued them, as well as by their user
+    let res: Vec<UnbondRequest> = query_helper(
+        deps.as_ref(),
+        QueryMsg::UnbondRequestsBySender {
+            sender: "user_2".to_string(),
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert_eq!(res, vec![ubr2.clone()]);
+
+    let res: Vec<UnbondRequest> = query_helper(
+        deps.as_ref(),
+        QueryMsg::UnbondRequestsBySender {
+            sender: "user_1".to_string(),
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert_eq!(res, vec![ubr1]);
+
+    let res: Vec<UnbondRequest> = query_helper(
+        deps.as_ref(),
+        QueryMsg::UnbondRequestsBySender {
+            sender: "user_2".to_string(),
+            start_after: Some((1, "user_3".to_string())),
+            limit: None,
+        },
+    );
+    assert!(res.is_empty());
+
+    // Unbonding on behalf of others is tallied per funder and receiver
+    let res: Vec<BatchFunder> = query_helper(
+        deps.as_ref(),
+        QueryMsg::BatchFunders {
+            id: 1,
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert_eq!(
+        res,
+        vec![BatchFunder {
+            funder: "user_2".to_string(),
+            receiver: "user_3".to_string(),
+            usteak: Uint128::new(69420),
+        }]
+    );
+
+    // Pending batch should have been updated
+    let pending_batch = state.pending_batch.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        pending_batch,
+        PendingBatch {
+            id: 1,
+            usteak_to_burn: Uint128::new(92876), // 23,456 + 69,420
+            est_unbond_start_time: 269200
+        }
+    );
+
+    // A user's queued request is valued at the current exchange rate
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 345782, "uxyz"),
+        Delegation::new("bob", 345782, "uxyz"),
+        Delegation::new("charlie", 345781, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1012043);
+    let res: PendingBatchRequestResponse = query_helper(
+        deps.as_ref(),
+        QueryMsg::PendingBatchRequest {
+            user: "user_1".to_string(),
+        },
+    );
+    assert_eq!(
+        res,
+        PendingBatchRequestResponse {
+            id: 1,
+            shares: Uint128::new(23456),
+            est_native: Uint128::new(24042), // 23,456 * 1,037,345 / 1,012,043
+            est_unbond_start_time: 269200,
+        }
+    );
+
+    let res: PendingBatchRequestResponse = query_helper(
+        deps.as_ref(),
+        QueryMsg::PendingBatchRequest {
+            user: "user_2".to_string(),
+        },
+    );
+    assert_eq!(res.shares, Uint128::zero());
+    assert_eq!(res.est_native, Uint128::zero());
+}
+
+#[test]
+fn submitting_batch() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    // native_token bonded: 1,037,345
+    // usteak supply: 1,012,043
+    // native_token per ustake: 1.025
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 345782, "uxyz"),
+        Delegation::new("bob", 345782, "uxyz"),
+        Delegation::new("charlie", 345781, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1012043);
+
+    // We continue from the contract state at the end of the last test
+    let unbond_requests = vec![
+        UnbondRequest {
+            id: 1,
+            user: Addr::unchecked("user_1"),
+            shares: Uint128::new(23456),
+            sender: None,
+        },
+        UnbondRequest {
+            id: 1,
+            user: Addr::unchecked("user_3"),
+            shares: Uint128::new(69420),
+            sender: None,
+        },
+    ];
+
+    for unbond_request in &unbond_requests {
+        state
+            .unbond_requests
+            .save(
+                deps.as_mut().storage,
+                (
+                    unbond_request.id,
+                    &Addr::unchecked(unbond_request.user.clone()),
+                ),
+                unbond_request,
+            )
+            .unwrap();
+    }
+
+    state
+        .pending_batch
+        .save(
+            deps.as_mut().storage,
+            &PendingBatch {
+                id: 1,
+                usteak_to_burn: Uint128::new(92876), // 23,456 + 69,420
+                est_unbond_start_time: 269200,
+            },
+        )
+        .unwrap();
+
+    // Anyone can invoke `submit_batch`. Here we continue from the previous test and assume it is
+    // invoked automatically as user 2 submits the unbonding request
+    //
+    // usteak to burn: 23,456 + 69,420 = 92,876
+    // native_token to unbond: 1,037,345 * 92,876 / 1,012,043 = 95,197
+    //
+    // Target: (1,037,345 - 95,197) / 3 = 314,049
+    // Remainer: 1
+    // Alice:   345,782 - (314,049 + 1) = 31,732
+    // Bob:     345,782 - (314,049 + 0) = 31,733
+    // Charlie: 345,781 - (314,049 + 0) = 31,732
+    let env_at_ts = mock_env_at_timestamp(269201);
+    let res = execute(
+        deps.as_mut(),
+        env_at_ts.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::SubmitBatch {},
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 4);
+    assert_eq!(
+        res.messages[0],
+        SubMsg::reply_on_success(
+            Undelegation::new("alice", 31732, "uxyz")
+                .to_cosmos_msg(env_at_ts.contract.address.to_string())
+                .unwrap(),
+            REPLY_REGISTER_RECEIVED_COINS
+        )
+    );
+    assert_eq!(
+        res.messages[1],
+        SubMsg::reply_on_success(
+            Undelegation::new("bob", 31733, "uxyz")
+                .to_cosmos_msg(env_at_ts.contract.address.to_string())
+                .unwrap(),
+            REPLY_REGISTER_RECEIVED_COINS
+        )
+    );
+    assert_eq!(
+        res.messages[2],
+        SubMsg::reply_on_success(
+            Undelegation::new("charlie", 31732, "uxyz")
+                .to_cosmos_msg(env_at_ts.contract.address.to_string())
+                .unwrap(),
+            REPLY_REGISTER_RECEIVED_COINS
+        )
+    );
+    assert_eq!(
+        res.messages[3],
+        SubMsg {
+            id: 0,
+            msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "steak_token".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::new(92876)
+                })
+                .unwrap(),
+                funds: vec![]
+            }),
+            gas_limit: None,
+            reply_on: ReplyOn::Never
+        }
+    );
+
+    // The event carries the undelegation plan and the rate it was priced at
+    let event = &res.events[0];
+    assert_eq!(event.ty, "steakhub/unbond_submitted");
+    let attr = |key: &str| {
+        event
+            .attributes
+            .iter()
+            .find(|a| a.key == key)
+            .unwrap()
+            .value
+            .clone()
+    };
+    assert_eq!(
+        attr("exchange_rate"),
+        Decimal::from_ratio(1037345u128, 1012043u128).to_string()
+    );
+    assert_eq!(attr("est_unbond_end_time"), "2083601");
+    assert_eq!(attr("undelegations"), "alice:31732,bob:31733,charlie:31732");
+
+    let data: SubmitBatchResponseData = from_binary(&res.data.unwrap()).unwrap();
+    assert_eq!(
+        data,
+        SubmitBatchResponseData {
+            batch_id: 1,
+            usteak_burned: Uint128::new(92876),
+            native_unbonded: Uint128::new(95197),
+            est_unbond_end_time: 2083601,
+        }
+    );
+
+    // A new pending batch should have been created
+    let pending_batch = state.pending_batch.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        pending_batch,
+        PendingBatch {
+            id: 2,
+            usteak_to_burn: Uint128::zero(),
+            est_unbond_start_time: 528401 // 269,201 + 259,200
+        }
+    );
+
+    // Previous batch should have been updated
+    let previous_batch = state
+        .previous_batches
+        .load(deps.as_ref().storage, 1u64)
+        .unwrap();
+    assert_eq!(
+        previous_batch,
+        Batch {
+            id: 1,
+            reconciled: false,
+            total_shares: Uint128::new(92876),
+            amount_unclaimed: Uint128::new(95197),
+            est_unbond_end_time: 2083601 // 269,201 + 1,814,400
+        }
+    );
+}
+
+#[test]
+fn capping_staking_msgs_per_tx() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 345782, "uxyz"),
+        Delegation::new("bob", 345782, "uxyz"),
+        Delegation::new("charlie", 345781, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1012043);
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateMaxMsgsPerTx { max: Some(0) },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ZeroMaxMsgsPerTx {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateMaxMsgsPerTx { max: Some(2) },
+    )
+    .unwrap();
+    let res: ConfigResponse = query_helper(deps.as_ref(), QueryMsg::Config {});
+    assert_eq!(res.max_msgs_per_tx, Some(2));
+
+    // Each harvest withdraws from as many validators as fit, resuming after the last one the
+    // previous harvest withdrew from
+    let withdrawn_from = |res: Response| {
+        res.messages
+            .into_iter()
+            .filter(|msg| msg.id == REPLY_REGISTER_RECEIVED_COINS)
+            .map(|msg| msg.msg)
+            .collect::<Vec<_>>()
+    };
+    let withdrawal = |validator: &str| {
+        RewardWithdrawal {
+            validator: validator.to_string(),
+        }
+        .to_cosmos_msg(MOCK_CONTRACT_ADDR.to_string())
+        .unwrap()
+    };
+    for expected in [["alice", "bob"], ["charlie", "alice"]] {
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MOCK_CONTRACT_ADDR, &[]),
+            ExecuteMsg::Harvest {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.events[0].attributes.last().unwrap(),
+            &attr("withdrawals_deferred", "1")
+        );
+        assert_eq!(
+            withdrawn_from(res),
+            expected.iter().map(|v| withdrawal(v)).collect::<Vec<_>>()
+        );
+    }
+
+    // A batch is taken from the largest delegations alone
+    //
+    // native_token to unbond: 1,037,345 * 92,876 / 1,012,043 = 95,197
+    // Target: (691,564 - 95,197) / 2 = 298,183
+    // Remainer: 1
+    // Alice:   345,782 - (298,183 + 1) = 47,598
+    // Bob:     345,782 - (298,183 + 0) = 47,599
+    state
+        .pending_batch
+        .save(
+            deps.as_mut().storage,
+            &PendingBatch {
+                id: 1,
+                usteak_to_burn: Uint128::new(92876),
+                est_unbond_start_time: 269200,
+            },
+        )
+        .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(269201),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::SubmitBatch {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 3);
+    assert_eq!(
+        res.messages[..2],
+        [
+            SubMsg::reply_on_success(
+                Undelegation::new("alice", 47598, "uxyz")
+                    .to_cosmos_msg(MOCK_CONTRACT_ADDR.to_string())
+                    .unwrap(),
+                REPLY_REGISTER_RECEIVED_COINS
+            ),
+            SubMsg::reply_on_success(
+                Undelegation::new("bob", 47599, "uxyz")
+                    .to_cosmos_msg(MOCK_CONTRACT_ADDR.to_string())
+                    .unwrap(),
+                REPLY_REGISTER_RECEIVED_COINS
+            ),
+        ]
+    );
+}
+
+#[test]
+fn limiting_batch_submissions() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 345782, "uxyz"),
+        Delegation::new("bob", 345782, "uxyz"),
+        Delegation::new("charlie", 345781, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1012043);
+    state
+        .pending_batch
+        .save(
+            deps.as_mut().storage,
+            &PendingBatch {
+                id: 1,
+                usteak_to_burn: Uint128::new(92876),
+                est_unbond_start_time: 269200,
+            },
+        )
+        .unwrap();
+
+    let limits_msg = ExecuteMsg::UpdateBatchLimits {
+        min_gap: 604800,
+        min_size: Uint128::new(100000),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        limits_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        limits_msg,
+    )
+    .unwrap();
+
+    // A batch below the minimum size is neither submitted automatically nor by anyone else
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(269201),
+        mock_info("steak_token", &[]),
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "user_1".to_string(),
+            amount: Uint128::new(1),
+            msg: to_binary(&ReceiveMsg::QueueUnbond {
+                receiver: None,
+                min_out: None,
+                deadline: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(269201),
+        mock_info("jake", &[]),
+        ExecuteMsg::SubmitBatch {},
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::BatchTooSmall {
+            min_size: Uint128::new(100000)
+        }
+    );
+
+    // The owner can still submit it
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(269201),
+        mock_info("larry", &[]),
+        ExecuteMsg::SubmitBatch {},
+    )
+    .unwrap();
+
+    // The next batch has to wait out the minimum gap, even once its epoch has passed
+    let res: PendingBatchResponse = query_helper(deps.as_ref(), QueryMsg::PendingBatch {});
+    assert_eq!(
+        res,
+        PendingBatchResponse {
+            id: 2,
+            usteak_to_burn: Uint128::zero(),
+            est_unbond_start_time: 528401, // 269,201 + 259,200
+            next_submittable_time: 874001, // 269,201 + 604,800
+            min_batch_size: Uint128::new(100000),
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(528402),
+        mock_info("steak_token", &[]),
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "user_1".to_string(),
+            amount: Uint128::new(150000),
+            msg: to_binary(&ReceiveMsg::QueueUnbond {
+                receiver: None,
+                min_out: None,
+                deadline: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(528402),
+        mock_info("jake", &[]),
+        ExecuteMsg::SubmitBatch {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::BatchNotReady { ready_at: 874001 });
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(874001),
+        mock_info("jake", &[]),
+        ExecuteMsg::SubmitBatch {},
+    )
+    .unwrap();
+}
+
+#[test]
+fn projecting_liquidity() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    let now = 10_000_000;
+    for (id, amount, est_unbond_end_time) in [
+        (1, 100, now - 1),
+        (2, 200, now + 43_200),
+        (3, 300, now + 190_000),
+        (4, 400, now + 864_000),
+    ] {
+        state
+            .previous_batches
+            .save(
+                deps.as_mut().storage,
+                id,
+                &Batch {
+                    id,
+                    reconciled: false,
+                    total_shares: Uint128::new(amount),
+                    amount_unclaimed: Uint128::new(amount),
+                    est_unbond_end_time,
+                },
+            )
+            .unwrap();
+    }
+
+    let res: LiquidityScheduleResponse = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(now),
+        QueryMsg::LiquiditySchedule { days: 3 },
+    );
+    let day = |i: u64, amount: u128| LiquidityDay {
+        start_time: now + i * 86_400,
+        end_time: now + (i + 1) * 86_400,
+        inflow: Uint128::new(amount),
+        outflow: Uint128::new(amount),
+    };
+    assert_eq!(
+        res,
+        LiquidityScheduleResponse {
+            claimable: Uint128::new(100),
+            days: vec![day(0, 200), day(1, 0), day(2, 300)],
+        }
+    );
+
+    // The schedule is capped
+    let res: LiquidityScheduleResponse = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(now),
+        QueryMsg::LiquiditySchedule { days: 1000 },
+    );
+    assert_eq!(res.days.len(), 60);
+    assert_eq!(res.days[10], day(10, 400));
+}
+
+#[test]
+fn querying_state_v2() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    let now = 10_000_000;
+    deps.querier.set_cw20_total_supply("steak_token", 900);
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 400, "uxyz"),
+        Delegation::new("bob", 300, "uxyz"),
+        Delegation::new("charlie", 300, "uxyz"),
+    ]);
+    deps.querier
+        .set_bank_balances(&[Coin::new(250u128, "uxyz")]);
+    for (id, amount, est_unbond_end_time) in [(1, 100, now - 1), (2, 200, now + 43_200)] {
+        state
+            .previous_batches
+            .save(
+                deps.as_mut().storage,
+                id,
+                &Batch {
+                    id,
+                    reconciled: false,
+                    total_shares: Uint128::new(amount),
+                    amount_unclaimed: Uint128::new(amount),
+                    est_unbond_end_time,
+                },
+            )
+            .unwrap();
+    }
+    state
+        .escrowed_fees
+        .save(deps.as_mut().storage, &Uint128::new(30))
+        .unwrap();
+    let mut pending_batch = state.pending_batch.load(deps.as_ref().storage).unwrap();
+    pending_batch.usteak_to_burn = Uint128::new(50);
+    state
+        .pending_batch
+        .save(deps.as_mut().storage, &pending_batch)
+        .unwrap();
+    state
+        .total_mining_power
+        .save(deps.as_mut().storage, &Uint128::new(15))
+        .unwrap();
+
+    // The legacy response is unchanged
+    let legacy: StateResponse = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(now),
+        QueryMsg::State {},
+    );
+    let res: StateResponseV2 = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(now),
+        QueryMsg::StateV2 {},
+    );
+    assert_eq!(
+        res,
+        StateResponseV2 {
+            total_usteak: legacy.total_usteak,
+            total_native: legacy.total_native,
+            exchange_rate: legacy.exchange_rate,
+            unlocked_coins: legacy.unlocked_coins,
+            // 1000 staked + 200 unbonding + 250 held
+            tvl: Uint128::new(1450),
+            // 250 held - 100 claimable - 30 escrowed
+            available_liquidity: Uint128::new(120),
+            unbonding: UnbondingTotals {
+                usteak_pending: Uint128::new(50),
+                native_unbonding: Uint128::new(200),
+                native_claimable: Uint128::new(100),
+            },
+            mining: MiningSummary {
+                total_mining_power: Uint128::new(15),
+                current_miner: None,
+                miner_fee_share_bps: 10_000,
+            },
+        }
+    );
+    assert_eq!(res.exchange_rate, Decimal::from_ratio(1000u128, 900u128));
+}
+
+#[test]
+fn querying_unbonding_delegations() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 345782, "uxyz"),
+        Delegation::new("bob", 345782, "uxyz"),
+        Delegation::new("charlie", 345781, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1012043);
+    state
+        .pending_batch
+        .save(
+            deps.as_mut().storage,
+            &PendingBatch {
+                id: 1,
+                usteak_to_burn: Uint128::new(92876),
+                est_unbond_start_time: 269200,
+            },
+        )
+        .unwrap();
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(269201),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::SubmitBatch {},
+    )
+    .unwrap();
+    let batch = state
+        .previous_batches
+        .load(deps.as_ref().storage, 1)
+        .unwrap();
+
+    // The chain does not serve the query, so only the batch records are reported
+    let res: UnbondingDelegationsResponse =
+        query_helper(deps.as_ref(), QueryMsg::UnbondingDelegations {});
+    let recorded = |validator: &str, amount: u128| ValidatorUnbonding {
+        validator: validator.to_string(),
+        amount: Uint128::new(amount),
+        entries: vec![UnbondingEntry {
+            batch_id: 1,
+            amount: Uint128::new(amount),
+            completion_time: batch.est_unbond_end_time,
+        }],
+        chain_amount: None,
+        chain_entries: None,
+    };
+    assert_eq!(
+        res,
+        UnbondingDelegationsResponse {
+            validators: vec![
+                recorded("alice", 31732),
+                recorded("bob", 31733),
+                recorded("charlie", 31732),
+            ],
+            chain_queried: false,
+        }
+    );
+
+    // Where it does, the chain's totals and entry counts are reported alongside
+    deps.querier
+        .set_unbonding_delegations(&[("alice", &[31000, 732]), ("bob", &[31733])]);
+    let res: UnbondingDelegationsResponse =
+        query_helper(deps.as_ref(), QueryMsg::UnbondingDelegations {});
+    assert!(res.chain_queried);
+    let chain: Vec<_> = res
+        .validators
+        .iter()
+        .map(|v| (v.validator.as_str(), v.chain_amount, v.chain_entries))
+        .collect();
+    assert_eq!(
+        chain,
+        vec![
+            ("alice", Some(Uint128::new(31732)), Some(2)),
+            ("bob", Some(Uint128::new(31733)), Some(1)),
+            ("charlie", Some(Uint128::zero()), Some(0)),
+        ]
+    );
+
+    // Reconciled batches are no longer in flight
+    deps.querier.unbonding_delegations = None;
+    deps.querier
+        .set_bank_balances(&[Coin::new(batch.amount_unclaimed.u128(), "uxyz")]);
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(batch.est_unbond_end_time + 1),
+        mock_info("worker", &[]),
+        ExecuteMsg::Reconcile {},
+    )
+    .unwrap();
+    let res: UnbondingDelegationsResponse =
+        query_helper(deps.as_ref(), QueryMsg::UnbondingDelegations {});
+    assert!(res.validators.is_empty());
+}
+
+#[test]
+fn counting_undelegation_slots() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 345782, "uxyz"),
+        Delegation::new("bob", 345782, "uxyz"),
+        Delegation::new("charlie", 345781, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1012043);
+    state
+        .pending_batch
+        .save(
+            deps.as_mut().storage,
+            &PendingBatch {
+                id: 1,
+                usteak_to_burn: Uint128::new(92876),
+                est_unbond_start_time: 269200,
+            },
+        )
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(269201),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::SubmitBatch {},
+    )
+    .unwrap();
+    let end_time = 269201 + 1814400;
+
+    let slots = |validator: &str, occupied: u32, next_free_at: Option<u64>| ValidatorSlots {
+        validator: validator.to_string(),
+        occupied,
+        available: 7 - occupied,
+        next_free_at,
+    };
+
+    // Without the chain's entries, the batch records are counted
+    let res: UndelegationSlotsResponse = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(269201),
+        QueryMsg::UndelegationSlots {},
+    );
+    assert_eq!(
+        res,
+        UndelegationSlotsResponse {
+            max_entries: 7,
+            chain_queried: false,
+            validators: vec![
+                slots("alice", 1, Some(end_time)),
+                slots("bob", 1, Some(end_time)),
+                slots("charlie", 1, Some(end_time)),
+            ],
+        }
+    );
+
+    // The chain's entries take precedence, including those the batches do not account for
+    deps.querier
+        .set_unbonding_delegations(&[("alice", &[31000, 732]), ("dave", &[100])]);
+    let res: UndelegationSlotsResponse = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(269201),
+        QueryMsg::UndelegationSlots {},
+    );
+    assert!(res.chain_queried);
+    assert_eq!(
+        res.validators,
+        vec![
+            slots("alice", 2, Some(end_time)),
+            slots("bob", 0, None),
+            slots("charlie", 0, None),
+            slots("dave", 1, None),
+        ]
+    );
+
+    // Entries past their completion no longer hold a slot
+    deps.querier.unbonding_delegations = None;
+    let res: UndelegationSlotsResponse = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(end_time),
+        QueryMsg::UndelegationSlots {},
+    );
+    assert!(res.validators.iter().all(|v| v.occupied == 0));
+}
+
+#[test]
+fn reconciling() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    let previous_batches = vec![
+        Batch {
+            id: 1,
+            reconciled: true,
+            total_shares: Uint128::new(92876),
+            amount_unclaimed: Uint128::new(95197), // 1.025 Native Token per Steak
+            est_unbond_end_time: 10000,
+        },
+        Batch {
+            id: 2,
+            reconciled: false,
+            total_shares: Uint128::new(1345),
+            amount_unclaimed: Uint128::new(1385), // 1.030 Native Token per Steak
+            est_unbond_end_time: 20000,
+        },
+        Batch {
+            id: 3,
+            reconciled: false,
+            total_shares: Uint128::new(1456),
+            amount_unclaimed: Uint128::new(1506), // 1.035 Native Token per Steak
+            est_unbond_end_time: 30000,
+        },
+        Batch {
+            id: 4,
+            reconciled: false,
+            total_shares: Uint128::new(1567),
+            amount_unclaimed: Uint128::new(1629), // 1.040 Native Token per Steak
+            est_unbond_end_time: 40000,           // not yet finished unbonding, ignored
+        },
+    ];
+
+    for previous_batch in &previous_batches {
+        state
+            .previous_batches
+            .save(deps.as_mut().storage, previous_batch.id, previous_batch)
+            .unwrap();
+    }
+
+    state
+        .unlocked_coins
+        .save(
+            deps.as_mut().storage,
+            &vec![
+                Coin::new(10000, "uxyz"),
+                Coin::new(234, "ukrw"),
+                Coin::new(345, "uusd"),
+                Coin::new(
+                    69420,
+                    "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B",
+                ),
+            ],
+        )
+        .unwrap();
+
+    deps.querier.set_bank_balances(&[
+        Coin::new(12345, "uxyz"),
+        Coin::new(234, "ukrw"),
+        Coin::new(345, "uusd"),
+        Coin::new(
+            69420,
+            "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B",
+        ),
+    ]);
+
+    // the preview shows the haircut without writing it to the batches
+    let res: SimulateReconcileResponse = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(35000),
+        QueryMsg::SimulateReconcile {},
+    );
+    assert_eq!(
+        res,
+        SimulateReconcileResponse {
+            native_expected: Uint128::new(12891),
+            native_actual: Uint128::new(12345),
+            native_to_deduct: Uint128::new(546),
+            batches: vec![
+                ReconcileBatchPreview {
+                    id: 2,
+                    amount_unclaimed: Uint128::new(1385),
+                    deduction: Uint128::new(273),
+                },
+                ReconcileBatchPreview {
+                    id: 3,
+                    amount_unclaimed: Uint128::new(1506),
+                    deduction: Uint128::new(273),
+                },
+            ],
+        }
+    );
+    let batch = state
+        .previous_batches
+        .load(deps.as_ref().storage, 2u64)
+        .unwrap();
+    assert_eq!(batch, previous_batches[1]);
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(35000),
+        mock_info("worker", &[]),
+        ExecuteMsg::Reconcile {},
+    )
+    .unwrap();
+
+    // Expected received: batch 2 + batch 3 = 1385 + 1506 = 2891
+    // Expected unlocked: 10000
+    // Expected: 12891
+    // Actual: 12345
+    // Shortfall: 12891 - 12345 = 456
+    //
+    // native_token per batch: 546 / 2 = 273
+    // remainder: 0
+    // batch 2: 1385 - 273 = 1112
+    // batch 3: 1506 - 273 = 1233
+    let batch = state
+        .previous_batches
+        .load(deps.as_ref().storage, 2u64)
+        .unwrap();
+    assert_eq!(
+        batch,
+        Batch {
+            id: 2,
+            reconciled: true,
+            total_shares: Uint128::new(1345),
+            amount_unclaimed: Uint128::new(1112), // 1385 - 273
+            est_unbond_end_time: 20000,
+        }
+    );
+
+    let batch = state
+        .previous_batches
+        .load(deps.as_ref().storage, 3u64)
+        .unwrap();
+    assert_eq!(
+        batch,
+        Batch {
+            id: 3,
+            reconciled: true,
+            total_shares: Uint128::new(1456),
+            amount_unclaimed: Uint128::new(1233), // 1506 - 273
+            est_unbond_end_time: 30000,
+        }
+    );
+
+    // Batches 1 and 4 should not have changed
+    let batch = state
+        .previous_batches
+        .load(deps.as_ref().storage, 1u64)
+        .unwrap();
+    assert_eq!(batch, previous_batches[0]);
+
+    let batch = state
+        .previous_batches
+        .load(deps.as_ref().storage, 4u64)
+        .unwrap();
+    assert_eq!(batch, previous_batches[3]);
+
+    let res: Metrics = query_helper(deps.as_ref(), QueryMsg::Metrics {});
+    assert_eq!(res.reconcile_shortfalls, 1);
+    assert_eq!(res.native_shortfall, Uint128::new(546));
+}
+
+#[test]
+fn attributing_shortfalls_to_validators() {
+    let state = State::default();
+    let setup = || {
+        let mut deps = setup_test();
+        for (id, est_unbond_end_time) in [(2u64, 20000u64), (3, 40000)] {
+            state
+                .previous_batches
+                .save(
+                    deps.as_mut().storage,
+                    id,
+                    &Batch {
+                        id,
+                        reconciled: false,
+                        total_shares: Uint128::new(1500),
+                        amount_unclaimed: Uint128::new(1500),
+                        est_unbond_end_time,
+                    },
+                )
+                .unwrap();
+        }
+        for ((id, validator), amount) in [
+            ((2u64, "alice"), 800u128),
+            ((2, "bob"), 700),
+            ((3, "alice"), 800),
+            ((3, "bob"), 700),
+        ] {
+            state
+                .batch_undelegations
+                .save(
+                    deps.as_mut().storage,
+                    (id, validator.to_string()),
+                    &Uint128::new(amount),
+                )
+                .unwrap();
+        }
+        // batch 2 came back 101 short
+        deps.querier
+            .set_bank_balances(&[Coin::new(1399u128, "uxyz")]);
+        deps
+    };
+
+    // Without the chain's unbonding entries, the shortfall is split by what batch 2 undelegated
+    let mut deps = setup();
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(30000),
+        mock_info("worker", &[]),
+        ExecuteMsg::Reconcile {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.events[0].attributes.last().unwrap(),
+        &attr("slashing_record", "1")
+    );
+    let res: Vec<SlashingRecord> = query_helper(
+        deps.as_ref(),
+        QueryMsg::SlashingRecords {
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert_eq!(
+        res,
+        vec![SlashingRecord {
+            id: 1,
+            batch_ids: vec![2],
+            native_shortfall: Uint128::new(101),
+            validators: vec![
+                ValidatorLoss {
+                    validator: "alice".to_string(),
+                    amount: Uint128::new(54), // 101 * 800 / 1500 = 53.87
+                },
+                ValidatorLoss {
+                    validator: "bob".to_string(),
+                    amount: Uint128::new(47), // 101 * 700 / 1500 = 47.13
+                },
+            ],
+            chain_attributed: false,
+            height: 12345,
+            time: 30000,
+        }]
+    );
+    // which is no evidence of slashing
+    assert!(state
+        .validator_performance
+        .may_load(deps.as_ref().storage, "alice".to_string())
+        .unwrap()
+        .is_none());
+
+    // Where the chain shows alice's entries for batch 3 were cut, the shortfall is pinned on her
+    let mut deps = setup();
+    deps.querier
+        .set_unbonding_delegations(&[("alice", &[740]), ("bob", &[700])]);
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(30000),
+        mock_info("worker", &[]),
+        ExecuteMsg::Reconcile {},
+    )
+    .unwrap();
+    let record = state
+        .slashing_records
+        .load(deps.as_ref().storage, 1)
+        .unwrap();
+    assert!(record.chain_attributed);
+    assert_eq!(
+        record.validators,
+        vec![ValidatorLoss {
+            validator: "alice".to_string(),
+            amount: Uint128::new(101),
+        }]
+    );
+    let performance = state
+        .validator_performance
+        .load(deps.as_ref().storage, "alice".to_string())
+        .unwrap();
+    assert_eq!(performance.slashes, vec![30000]);
+
+    // Reconciling without a shortfall logs nothing
+    let mut deps = setup();
+    deps.querier
+        .set_bank_balances(&[Coin::new(1500u128, "uxyz")]);
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(30000),
+        mock_info("worker", &[]),
+        ExecuteMsg::Reconcile {},
+    )
+    .unwrap();
+    let res: Vec<SlashingRecord> = query_helper(
+        deps.as_ref(),
+        QueryMsg::SlashingRecords {
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert!(res.is_empty());
+}
+
+#[test]
+fn withdrawing_unbonded() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    // We simulate a most general case:
+    // - batches 1 and 2 have finished unbonding
+    // - batch 3 have been submitted for unbonding but have not finished
+    // - batch 4 is still pending
+    let unbond_requests = vec![
+        UnbondRequest {
+            id: 1,
+            user: Addr::unchecked("user_1"),
+            shares: Uint128::new(23456),
+            sender: None,
+        },
+        UnbondRequest {
+            id: 1,
+            user: Addr::unchecked("user_3"),
+            shares: Uint128::new(69420),
+            sender: None,
+        },
+        UnbondRequest {
+            id: 2,
+            user: Addr::unchecked("user_1"),
+            shares: Uint128::new(34567),
+            sender: None,
+        },
+        UnbondRequest {
+            id: 3,
+            user: Addr::unchecked("user_1"),
+            shares: Uint128::new(45678),
+            sender: None,
+        },
+        UnbondRequest {
+            id: 4,
+            user: Addr::unchecked("user_1"),
+            shares: Uint128::new(56789),
+            sender: None,
+        },
+    ];
+
+    for unbond_request in &unbond_requests {
+        state
+            .unbond_requests
+            .save(
+                deps.as_mut().storage,
+                (
+                    unbond_request.id,
+                    &Addr::unchecked(unbond_request.user.clone()),
+                ),
+                unbond_request,
+            )
+            .unwrap();
+    }
+
+    let previous_batches = vec![
+        Batch {
+            id: 1,
+            reconciled: true,
+            total_shares: Uint128::new(92876),
+            amount_unclaimed: Uint128::new(95197), // 1.025 Native Token per Steak
+            est_unbond_end_time: 10000,
+        },
+        Batch {
+            id: 2,
+            reconciled: true,
+            total_shares: Uint128::new(34567),
+            amount_unclaimed: Uint128::new(35604), // 1.030 Native Token per Steak
+            est_unbond_end_time: 20000,
+        },
+        Batch {
+            id: 3,
+            reconciled: false, // finished unbonding, but not reconciled; ignored
+            total_shares: Uint128::new(45678),
+            amount_unclaimed: Uint128::new(47276), // 1.035 Native Token per Steak
+            est_unbond_end_time: 20000,
+        },
+        Batch {
+            id: 4,
+            reconciled: true,
+            total_shares: Uint128::new(56789),
+            amount_unclaimed: Uint128::new(59060), // 1.040 Native Token per Steak
+            est_unbond_end_time: 30000, // reconciled, but not yet finished unbonding; ignored
+        },
+    ];
+
+    for previous_batch in &previous_batches {
+        state
+            .previous_batches
+            .save(deps.as_mut().storage, previous_batch.id, previous_batch)
+            .unwrap();
+    }
+
+    state
+        .pending_batch
+        .save(
+            deps.as_mut().storage,
+            &PendingBatch {
+                id: 4,
+                usteak_to_burn: Uint128::new(56789),
+                est_unbond_start_time: 100000,
+            },
+        )
+        .unwrap();
+
+    // Attempt to withdraw before any batch has completed unbonding. Should error
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(5000),
+        mock_info("user_1", &[]),
+        ExecuteMsg::WithdrawUnbonded { receiver: None },
+    )
+    .unwrap_err();
+
+    assert_eq!(err, ContractError::NothingToWithdraw {});
+
+    // Attempt to withdraw once batches 1 and 2 have finished unbonding, but 3 has not yet
+    //
+    // Withdrawable from batch 1: 95,197 * 23,456 / 92,876 = 24,042
+    // Withdrawable from batch 2: 35,604
+    // Total withdrawable: 24,042 + 35,604 = 59,646
+    //
+    // Batch 1 should be updated:
+    // Total shares: 92,876 - 23,456 = 69,420
+    // Unclaimed native_token: 95,197 - 24,042 = 71,155
+    //
+    // Batch 2 is completely withdrawn, should be purged from storage
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(25000),
+        mock_info("user_1", &[]),
+        ExecuteMsg::WithdrawUnbonded { receiver: None },
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.messages[0],
+        SubMsg {
+            id: 0,
+            msg: CosmosMsg::Bank(BankMsg::Send {
+                to_address: "user_1".to_string(),
+                amount: vec![Coin::new(59646, "uxyz")]
+            }),
+            gas_limit: None,
+            reply_on: ReplyOn::Never
+        }
+    );
+    assert_eq!(
+        res.events[0]
+            .attributes
+            .iter()
+            .find(|attr| attr.key == "receiver_type")
+            .unwrap()
+            .value,
+        "self"
+    );
+
+    // Previous batches should have been updated
+    let batch = state
+        .previous_batches
+        .load(deps.as_ref().storage, 1u64)
+        .unwrap();
+    assert_eq!(
+        batch,
+        Batch {
+            id: 1,
+            reconciled: true,
+            total_shares: Uint128::new(69420),
+            amount_unclaimed: Uint128::new(71155),
+            est_unbond_end_time: 10000,
+        }
+    );
+
+    let err = state
+        .previous_batches
+        .load(deps.as_ref().storage, 2u64)
+        .unwrap_err();
+    assert_eq!(err, StdError::not_found("pfc_steak::hub::Batch"));
+
+    // User 1's unbond requests in batches 1 and 2 should have been deleted
+    let err1 = state
+        .unbond_requests
+        .load(deps.as_ref().storage, (1u64, &Addr::unchecked("user_1")))
+        .unwrap_err();
+    let err2 = state
+        .unbond_requests
+        .load(deps.as_ref().storage, (1u64, &Addr::unchecked("user_1")))
+        .unwrap_err();
+
+    assert_eq!(err1, StdError::not_found("pfc_steak::hub::UnbondRequest"));
+    assert_eq!(err2, StdError::not_found("pfc_steak::hub::UnbondRequest"));
+    // User 3 attempt to withdraw; also specifying a receiver
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(25000),
+        mock_info("user_3", &[]),
+        ExecuteMsg::WithdrawUnbonded {
+            receiver: Some("user_2".to_string()),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.messages[0],
+        SubMsg {
+            id: 0,
+            msg: CosmosMsg::Bank(BankMsg::Send {
+                to_address: "user_2".to_string(),
+                amount: vec![Coin::new(71155, "uxyz")]
+            }),
+            gas_limit: None,
+            reply_on: ReplyOn::Never
+        }
+    );
+    assert_eq!(
+        res.events[0]
+            .attributes
+            .iter()
+            .find(|attr| attr.key == "receiver_type")
+            .unwrap()
+            .value,
+        "third_party"
+    );
+
+    // Batch 1 and user 2's unbonding request should have been purged from storage
+    let err = state
+        .previous_batches
+        .load(deps.as_ref().storage, 1u64)
+        .unwrap_err();
+    assert_eq!(err, StdError::not_found("pfc_steak::hub::Batch"));
+
+    let err = state
+        .unbond_requests
+        .load(deps.as_ref().storage, (1u64, &Addr::unchecked("user_3")))
+        .unwrap_err();
+
+    assert_eq!(err, StdError::not_found("pfc_steak::hub::UnbondRequest"));
+}
+
+#[test]
+fn sweeping_expired_batches() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 345782, "uxyz"),
+        Delegation::new("bob", 345782, "uxyz"),
+        Delegation::new("charlie", 345781, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1012043);
+
+    for (user, shares) in [("user_1", 400), ("user_2", 600)] {
+        state
+            .unbond_requests
+            .save(
+                deps.as_mut().storage,
+                (1, &Addr::unchecked(user)),
+                &UnbondRequest {
+                    id: 1,
+                    user: Addr::unchecked(user),
+                    shares: Uint128::new(shares),
+                    sender: None,
+                },
+            )
+            .unwrap();
+    }
+    state
+        .previous_batches
+        .save(
+            deps.as_mut().storage,
+            1,
+            &Batch {
+                id: 1,
+                reconciled: true,
+                total_shares: Uint128::new(1000),
+                amount_unclaimed: Uint128::new(1025),
+                est_unbond_end_time: 10000,
+            },
+        )
+        .unwrap();
+
+    // Sweeping is disabled until the owner sets an expiry, which can not be too short
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(100_000_000),
+        mock_info("jake", &[]),
+        ExecuteMsg::SweepExpiredBatch { id: 1 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::BatchExpiryNotConfigured {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::UpdateBatchExpiry {
+            expiry: Some(7_776_000),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateBatchExpiry {
+            expiry: Some(86_400),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::BatchExpiryTooShort { min: 7_776_000 });
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateBatchExpiry {
+            expiry: Some(7_776_000),
+        },
+    )
+    .unwrap();
+    let res: Option<u64> = query_helper(deps.as_ref(), QueryMsg::BatchExpiry {});
+    assert_eq!(res, Some(7_776_000));
+
+    // The batch expires 90 days after it finished unbonding
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(7_786_000),
+        mock_info("jake", &[]),
+        ExecuteMsg::SweepExpiredBatch { id: 1 },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::BatchNotExpired {
+            id: 1,
+            expires_at: 7_786_000
+        }
+    );
+
+    // Anyone can sweep it; the unclaimed Native Token is re-bonded to the validator with the
+    // smallest delegation, and Steak is minted to the hub at the current exchange rate:
+    // 1025 * 1012043 / 1037345 = 999
+    let env = mock_env_at_timestamp(7_786_001);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("jake", &[]),
+        ExecuteMsg::SweepExpiredBatch { id: 1 },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0],
+        SubMsg::reply_on_success(
+            Delegation::new("charlie", 1025, "uxyz")
+                .to_cosmos_msg(env.contract.address.to_string())
+                .unwrap(),
+            REPLY_REGISTER_RECEIVED_COINS
+        )
+    );
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: MOCK_CONTRACT_ADDR.to_string(),
+                amount: Uint128::new(999),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_eq!(res.events[0].ty, "steakhub/batch_swept");
+
+    let swept: Vec<SweptBatch> = query_helper(
+        deps.as_ref(),
+        QueryMsg::SweptBatches {
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert_eq!(
+        swept,
+        vec![SweptBatch {
+            id: 1,
+            native_swept: Uint128::new(1025),
+            usteak_unclaimed: Uint128::new(999),
+            swept_time: 7_786_001,
+        }]
+    );
+
+    // The batch keeps its shares, but has nothing left to sweep
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(7_786_001),
+        mock_info("jake", &[]),
+        ExecuteMsg::SweepExpiredBatch { id: 1 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NothingToSweep { id: 1 });
+
+    // Unbonders are refunded their share of the minted Steak: 999 * 400 / 1000 = 399
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(8_000_000),
+        mock_info("user_1", &[]),
+        ExecuteMsg::WithdrawUnbonded { receiver: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "user_1".to_string(),
+                amount: Uint128::new(399),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert!(res.events[0]
+        .attributes
+        .contains(&attr("usteak_refunded", "399")));
+
+    // The last unbonder gets the rest, and the batch is purged
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(8_000_000),
+        mock_info("user_2", &[]),
+        ExecuteMsg::WithdrawUnbonded { receiver: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "user_2".to_string(),
+                amount: Uint128::new(600),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert!(state
+        .previous_batches
+        .may_load(deps.as_ref().storage, 1)
+        .unwrap()
+        .is_none());
+    assert!(state
+        .swept_batches
+        .may_load(deps.as_ref().storage, 1)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn pruning_stale_storage() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    let pending_id = state.pending_batch.load(deps.as_ref().storage).unwrap().id;
+    for (id, reconciled, shares) in [
+        (pending_id + 1, true, 0),  // fully claimed; pruned
+        (pending_id + 2, false, 0), // not reconciled yet; kept
+        (pending_id + 3, true, 100),
+    ] {
+        state
+            .previous_batches
+            .save(
+                deps.as_mut().storage,
+                id,
+                &Batch {
+                    id,
+                    reconciled,
+                    total_shares: Uint128::new(shares),
+                    amount_unclaimed: Uint128::new(shares),
+                    est_unbond_end_time: 10000,
+                },
+            )
+            .unwrap();
+    }
+    for (id, user, shares) in [
+        (pending_id, "user_1", 50),
+        (pending_id + 3, "user_1", 100),
+        (pending_id + 3, "user_2", 0),   // no shares; pruned
+        (pending_id + 4, "user_3", 100), // no such batch; pruned
+    ] {
+        state
+            .unbond_requests
+            .save(
+                deps.as_mut().storage,
+                (id, &Addr::unchecked(user)),
+                &UnbondRequest {
+                    id,
+                    user: Addr::unchecked(user),
+                    shares: Uint128::new(shares),
+                    sender: None,
+                },
+            )
+            .unwrap();
+    }
+    for day in [1, 34, 35, 399] {
+        state
+            .daily_snapshots
+            .save(
+                deps.as_mut().storage,
+                day,
+                &DailySnapshot {
+                    day,
+                    time: day * 86400,
+                    exchange_rate: Decimal::one(),
+                    total_native: Uint128::zero(),
+                    total_usteak: Uint128::zero(),
+                    fees: Uint128::zero(),
+                },
+            )
+            .unwrap();
+    }
+
+    let env = mock_env_at_timestamp(400 * 86400);
+
+    // Batches are scanned in chunks, picking up where the previous call stopped
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("jake", &[]),
+        ExecuteMsg::Prune {
+            kind: PruneKind::Batches,
+            limit: Some(2),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.events[0].attributes[2..],
+        [
+            attr("kind", "batches"),
+            attr("scanned", "2"),
+            attr("pruned", "1"),
+            attr("complete", "false")
+        ]
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("jake", &[]),
+        ExecuteMsg::Prune {
+            kind: PruneKind::Batches,
+            limit: Some(2),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.events[0].attributes[3..],
+        [
+            attr("scanned", "1"),
+            attr("pruned", "0"),
+            attr("complete", "true")
+        ]
+    );
+    let ids: Vec<u64> = state
+        .previous_batches
+        .keys(deps.as_ref().storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()
+        .unwrap();
+    assert_eq!(ids, vec![pending_id + 2, pending_id + 3]);
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("jake", &[]),
+        ExecuteMsg::Prune {
+            kind: PruneKind::UnbondRequests,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let requests: Vec<(u64, String)> = state
+        .unbond_requests
+        .range(deps.as_ref().storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, r)| (r.id, r.user.to_string())))
+        .collect::<StdResult<_>>()
+        .unwrap();
+    assert_eq!(
+        requests,
+        vec![
+            (pending_id, "user_1".to_string()),
+            (pending_id + 3, "user_1".to_string())
+        ]
+    );
+
+    // Snapshots are kept for a year
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info("jake", &[]),
+        ExecuteMsg::Prune {
+            kind: PruneKind::Snapshots,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let days: Vec<u64> = state
+        .daily_snapshots
+        .keys(deps.as_ref().storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()
+        .unwrap();
+    assert_eq!(days, vec![35, 399]);
+}
+
+#[test]
+fn withdrawing_unbonded_admin() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    state
+        .unbond_requests
+        .save(
+            deps.as_mut().storage,
+            (1u64, &Addr::unchecked("user_1")),
+            &UnbondRequest {
+                id: 1,
+                user: Addr::unchecked("user_1"),
+                shares: Uint128::new(23456),
+                sender: None,
+            },
+        )
+        .unwrap();
+    state
+        .previous_batches
+        .save(
+            deps.as_mut().storage,
+            1u64,
+            &Batch {
+                id: 1,
+                reconciled: true,
+                total_shares: Uint128::new(23456),
+                amount_unclaimed: Uint128::new(24042),
+                est_unbond_end_time: 10000,
+            },
+        )
+        .unwrap();
+
+    // Only the owner can use the deprecated admin path
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(25000),
+        mock_info("jake", &[]),
+        ExecuteMsg::WithdrawUnbondedAdmin {
+            address: "user_1".to_string(),
+        },
+    )
+    .unwrap_err();
+
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // The refund always lands with the owner of the unbond requests
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(25000),
+        mock_info("larry", &[]),
+        ExecuteMsg::WithdrawUnbondedAdmin {
+            address: "user_1".to_string(),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "user_1".to_string(),
+            amount: vec![Coin::new(24042, "uxyz")]
+        })
+    );
+    assert_eq!(
+        res.events[0]
+            .attributes
+            .iter()
+            .find(|attr| attr.key == "receiver_type")
+            .unwrap()
+            .value,
+        "self"
+    );
+}
+
+#[test]
+fn adding_validator() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::AddValidator {
+            validator: "dave".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap_err();
+
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::AddValidator {
+            validator: "alice".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        err,
+        ContractError::ValidatorAlreadyWhitelisted {
+            validator: "alice".to_string()
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::AddValidator {
+            validator: "dave".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 0);
+
+    let validators = state.validators.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        validators,
+        vec![
+            String::from("alice"),
+            String::from("bob"),
+            String::from("charlie"),
+            String::from("dave")
+        ],
+    );
+}
+
+#[test]
+fn blocking_validator() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    let block_msg = ExecuteMsg::BlockValidator {
+        validator: "alice".to_string(),
+        reason: "tombstoned".to_string(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        block_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        block_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.events,
+        vec![Event::new("steakhub/validator_blocked")
+            .add_attribute("validator", "alice")
+            .add_attribute("reason", "tombstoned")]
+    );
+
+    // Blocking stops new delegations, but leaves removal to `RemoveValidator`
+    let validators_active = state.validators_active.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        validators_active,
+        vec!["bob".to_string(), "charlie".to_string()]
+    );
+
+    let res: Vec<BlockedValidator> = query_helper(
+        deps.as_ref(),
+        QueryMsg::BlockedValidators {
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert_eq!(
+        res,
+        vec![BlockedValidator {
+            address: "alice".to_string(),
+            reason: "tombstoned".to_string(),
+        }]
+    );
+
+    let blocked = ContractError::ValidatorBlocked {
+        validator: "alice".to_string(),
+        reason: "tombstoned".to_string(),
+    };
+
+    // Once removed, the validator can not be whitelisted or resumed again by mistake
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::RemoveValidatorEx {
+            validator: "alice".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::AddValidator {
+            validator: "alice".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, blocked);
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UnPauseValidator {
+            validator: "alice".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, blocked);
+
+    // Nor can it accrue mining power
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("miner", &[]),
+        ExecuteMsg::SubmitProof {
+            nonce: Uint64::new(1),
+            validator: "alice".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, blocked);
+
+    let unblock_msg = ExecuteMsg::UnblockValidator {
+        validator: "alice".to_string(),
+        reason: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        unblock_msg.clone(),
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        unblock_msg,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ValidatorNotBlocked {
+            validator: "alice".to_string()
+        }
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::AddValidator {
+            validator: "alice".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn logging_validator_set_changes() {
+    let mut deps = setup_test();
+
+    let change = |id: u64, validator: &str, action, actor: &str, reason: Option<&str>, time| {
+        ValidatorSetChange {
+            id,
+            validator: validator.to_string(),
+            action,
+            actor: actor.to_string(),
+            reason: reason.map(|r| r.to_string()),
+            height: 12345,
+            time,
+        }
+    };
+
+    // The whitelist given at instantiation opens the log
+    let res: Vec<ValidatorSetChange> = query_helper(
+        deps.as_ref(),
+        QueryMsg::ValidatorSetChanges {
+            start_after: None,
+            limit: Some(2),
+        },
+    );
+    assert_eq!(
+        res,
+        vec![
+            change(1, "alice", ValidatorSetAction::Add, "larry", None, 10000),
+            change(2, "bob", ValidatorSetAction::Add, "larry", None, 10000),
+        ]
+    );
+
+    for (sender, msg) in [
+        (
+            "larry",
+            ExecuteMsg::AddValidator {
+                validator: "dave".to_string(),
+                reason: Some("community pick".to_string()),
+            },
+        ),
+        (
+            "larry",
+            ExecuteMsg::PauseValidator {
+                validator: "charlie".to_string(),
+                reason: Some("missed blocks".to_string()),
+            },
+        ),
+        (
+            "larry",
+            ExecuteMsg::UnPauseValidator {
+                validator: "charlie".to_string(),
+                reason: None,
+            },
+        ),
+        (
+            "larry",
+            ExecuteMsg::BlockValidator {
+                validator: "dave".to_string(),
+                reason: "tombstoned".to_string(),
+            },
+        ),
+    ] {
+        execute(
+            deps.as_mut(),
+            mock_env_at_timestamp(20000),
+            mock_info(sender, &[]),
+            msg,
+        )
+        .unwrap();
+    }
+
+    // Failed changes leave no trace
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(20000),
+        mock_info("jake", &[]),
+        ExecuteMsg::RemoveValidator {
+            validator: "alice".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap_err();
+
+    let res: Vec<ValidatorSetChange> = query_helper(
+        deps.as_ref(),
+        QueryMsg::ValidatorSetChanges {
+            start_after: Some(3),
+            limit: None,
+        },
+    );
+    assert_eq!(
+        res,
+        vec![
+            change(
+                4,
+                "dave",
+                ValidatorSetAction::Add,
+                "larry",
+                Some("community pick"),
+                20000
+            ),
+            change(
+                5,
+                "charlie",
+                ValidatorSetAction::Pause,
+                "larry",
+                Some("missed blocks"),
+                20000
+            ),
+            change(
+                6,
+                "charlie",
+                ValidatorSetAction::Unpause,
+                "larry",
+                None,
+                20000
+            ),
+            change(
+                7,
+                "dave",
+                ValidatorSetAction::Block,
+                "larry",
+                Some("tombstoned"),
+                20000
+            ),
+        ]
+    );
+}
+
+#[test]
+fn logging_admin_actions() {
+    let mut deps = setup_test();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(20000),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateFee {
+            new_fee: Decimal::percent(5),
+            reason: Some("proposal 42".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.events,
+        vec![Event::new("steakhub/fee_updated")
+            .add_attribute("new_fee", "0.05")
+            .add_attribute("reason", "proposal 42")]
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(20000),
+        mock_info("larry", &[]),
+        ExecuteMsg::EnterSafeMode {
+            until_height: Some(12400),
+            until_time: None,
+            reason: Some("chain upgrade".to_string()),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(20000),
+        mock_info("larry", &[]),
+        ExecuteMsg::ExitSafeMode { reason: None },
+    )
+    .unwrap();
+
+    // The reasons of validator actions go with their events too
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(20000),
+        mock_info("larry", &[]),
+        ExecuteMsg::PauseValidator {
+            validator: "alice".to_string(),
+            reason: Some("missed blocks".to_string()),
+        },
+    )
+    .unwrap();
+    assert!(res.events[0]
+        .attributes
+        .contains(&attr("reason", "missed blocks")));
+
+    let res: Vec<AdminActionRecord> = query_helper(
+        deps.as_ref(),
+        QueryMsg::AdminActions {
+            start_after: None,
+            limit: None,
+        },
+    );
+    let record = |id, action, reason: Option<&str>| AdminActionRecord {
+        id,
+        action,
+        actor: "larry".to_string(),
+        reason: reason.map(|r| r.to_string()),
+        height: 12345,
+        time: 20000,
+    };
+    assert_eq!(
+        res,
+        vec![
+            record(
+                1,
+                AdminAction::UpdateFee {
+                    new_fee: Decimal::percent(5)
+                },
+                Some("proposal 42")
+            ),
+            record(
+                2,
+                AdminAction::EnterSafeMode {
+                    until_height: Some(12400),
+                    until_time: None
+                },
+                Some("chain upgrade")
+            ),
+            record(3, AdminAction::ExitSafeMode {}, None),
+        ]
+    );
+}
+
+#[test]
+fn receive_only_pausing_validator() {
+    let mut deps = setup_test();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 341667, "uxyz"),
+        Delegation::new("bob", 341667, "uxyz"),
+        Delegation::new("charlie", 341666, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000000);
+
+    let pause_msg = |validator: &str| ExecuteMsg::ReceiveOnlyPauseValidator {
+        validator: validator.to_string(),
+        reason: None,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        pause_msg("charlie"),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        pause_msg("dave"),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ValidatorNotActive {
+            validator: "dave".to_string()
+        }
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        pause_msg("charlie"),
+    )
+    .unwrap();
+    let res: Vec<String> = query_helper(deps.as_ref(), QueryMsg::ReceivePausedValidators {});
+    assert_eq!(res, vec![String::from("charlie")]);
+
+    // Charlie has the smallest delegation, but new stake goes elsewhere; its own stake stays put
+    let env = mock_env();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user_1", &[Coin::new(1000, "uxyz")]),
+        ExecuteMsg::Bond { receiver: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::reply_on_success(
+            Delegation::new("alice", 1000, "uxyz")
+                .to_cosmos_msg(env.contract.address.to_string())
+                .unwrap(),
+            REPLY_REGISTER_RECEIVED_COINS
+        )
+    );
+    let state = State::default();
+    assert_eq!(
+        state.validators_active.load(deps.as_ref().storage).unwrap(),
+        vec![
+            String::from("alice"),
+            String::from("bob"),
+            String::from("charlie")
+        ],
+    );
+
+    // At least one validator must keep receiving delegations
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        pause_msg("alice"),
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        pause_msg("bob"),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoActiveValidators {});
+
+    // Unpausing lifts the receive-only pause
+    for validator in ["alice", "charlie"] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("larry", &[]),
+            ExecuteMsg::UnPauseValidator {
+                validator: validator.to_string(),
+                reason: None,
+            },
+        )
+        .unwrap();
+    }
+    let res: Vec<String> = query_helper(deps.as_ref(), QueryMsg::ReceivePausedValidators {});
+    assert!(res.is_empty());
+}
+
+#[test]
+fn removing_validator() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 341667, "uxyz"),
+        Delegation::new("bob", 341667, "uxyz"),
+        Delegation::new("charlie", 341666, "uxyz"),
+    ]);
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::RemoveValidator {
+            validator: "charlie".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap_err();
+
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::RemoveValidator {
+            validator: "dave".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        err,
+        ContractError::ValidatorNotWhitelisted {
+            validator: "dave".to_string()
+        }
+    );
+
+    state
+        .validator_mining_powers
+        .save(deps.as_mut().storage, "alice".to_string(), &Uint128::new(3))
+        .unwrap();
+    state
+        .validator_mining_powers
+        .save(
+            deps.as_mut().storage,
+            "charlie".to_string(),
+            &Uint128::new(7),
+        )
+        .unwrap();
+    state
+        .total_mining_power
+        .save(deps.as_mut().storage, &Uint128::new(10))
+        .unwrap();
+
+    // The validator stops receiving delegations but keeps its stake until drained
+    let env = mock_env();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("larry", &[]),
+        ExecuteMsg::RemoveValidator {
+            validator: "charlie".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+
+    // The removed validator's mining power no longer counts towards the total
+    assert_eq!(
+        res.events,
+        vec![Event::new("steakhub/validator_draining")
+            .add_attribute("validator", "charlie")
+            .add_attribute("amount", "341666")
+            .add_attribute("mining_power_purged", "7")]
+    );
+    assert!(!state
+        .validator_mining_powers
+        .has(deps.as_ref().storage, "charlie".to_string()));
+    assert_eq!(
+        state
+            .total_mining_power
+            .load(deps.as_ref().storage)
+            .unwrap(),
+        Uint128::new(3)
+    );
+    assert_eq!(
+        state.validators_active.load(deps.as_ref().storage).unwrap(),
+        vec![String::from("alice"), String::from("bob")],
+    );
+    let res: Vec<String> = query_helper(deps.as_ref(), QueryMsg::DrainingValidators {});
+    assert_eq!(res, vec![String::from("charlie")]);
+
+    for msg in [
+        ExecuteMsg::RemoveValidator {
+            validator: "charlie".to_string(),
+            reason: None,
+        },
+        ExecuteMsg::UnPauseValidator {
+            validator: "charlie".to_string(),
+            reason: None,
+        },
+    ] {
+        let err = execute(deps.as_mut(), mock_env(), mock_info("larry", &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ValidatorDraining {
+                validator: "charlie".to_string()
+            }
+        );
+    }
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::DrainValidators {
+            max_amount: Uint128::new(200000),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Target: (341667 + 341667 + 200000) / 2 = 441667
+    // Alice:   441667 - 341667 = 100000
+    // Bob:     441667 - 341667 = 100000
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("larry", &[]),
+        ExecuteMsg::DrainValidators {
+            max_amount: Uint128::new(200000),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::reply_on_success(
+                Redelegation::new("charlie", "alice", 100000, "uxyz")
+                    .to_cosmos_msg(env.contract.address.to_string())
+                    .unwrap(),
+                REPLY_REGISTER_RECEIVED_COINS
+            ),
+            SubMsg::reply_on_success(
+                Redelegation::new("charlie", "bob", 100000, "uxyz")
+                    .to_cosmos_msg(env.contract.address.to_string())
+                    .unwrap(),
+                REPLY_REGISTER_RECEIVED_COINS
+            ),
+        ]
+    );
+    let validators = state.validators.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        validators,
+        vec![
+            String::from("alice"),
+            String::from("bob"),
+            String::from("charlie")
+        ],
+    );
+
+    // Once the rest is moved, the validator is dropped from the whitelist
+    //
+    // Target: (441667 + 441667 + 141666) / 2 = 512500
+    // Alice:   512500 - 441667 = 70833
+    // Bob:     512500 - 441667 = 70833
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 441667, "uxyz"),
+        Delegation::new("bob", 441667, "uxyz"),
+        Delegation::new("charlie", 141666, "uxyz"),
+    ]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("larry", &[]),
+        ExecuteMsg::DrainValidators {
+            max_amount: Uint128::new(1000000),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[1],
+        SubMsg::reply_on_success(
+            Redelegation::new("charlie", "bob", 70833, "uxyz")
+                .to_cosmos_msg(env.contract.address.to_string())
+                .unwrap(),
+            REPLY_REGISTER_RECEIVED_COINS
+        ),
+    );
+    assert_eq!(
+        res.events[1],
+        Event::new("steak/validator_removed").add_attribute("validator", "charlie")
+    );
+
+    let validators = state.validators.load(deps.as_ref().storage).unwrap();
+    assert_eq!(validators, vec![String::from("alice"), String::from("bob")],);
+    let res: Vec<String> = query_helper(deps.as_ref(), QueryMsg::DrainingValidators {});
+    assert!(res.is_empty());
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::DrainValidators {
+            max_amount: Uint128::new(1000000),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoDrainingValidators {});
+}
+
+#[test]
+fn dispatching_balance_changes() {
+    let mut deps = setup_test();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::AddBalanceHook {
+            contract: "booster".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    for contract in ["booster", "referrals"] {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("larry", &[]),
+            ExecuteMsg::AddBalanceHook {
+                contract: contract.to_string(),
+            },
+        )
+        .unwrap();
+    }
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::AddBalanceHook {
+            contract: "booster".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::BalanceHookAlreadyRegistered {
+            contract: "booster".to_string()
+        }
+    );
+
+    let change = BalanceChange {
+        from: Some("alice".to_string()),
+        to: Some("bob".to_string()),
+        amount: Uint128::new(12345),
+    };
+
+    // Only the Steak token can report balance changes
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::BalanceChanged(change.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnexpectedToken {
+            expected: "Steak".to_string(),
+            received: "jake".to_string()
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("steak_token", &[]),
+        ExecuteMsg::BalanceChanged(change.clone()),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        ["booster", "referrals"]
+            .iter()
+            .map(|contract| SubMsg::reply_on_error(
+                BalanceHookMsg::SteakBalanceChanged(change.clone())
+                    .into_cosmos_msg(contract.to_string())
+                    .unwrap(),
+                REPLY_BALANCE_HOOK_FAILED
+            ))
+            .collect::<Vec<_>>()
+    );
+
+    // A failing hook is recorded, without reverting the transfer
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: REPLY_BALANCE_HOOK_FAILED,
+            result: cosmwasm_std::SubMsgResult::Err("out of gas".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.events,
+        vec![Event::new("steakhub/balance_hook_failed").add_attribute("error", "out of gas")]
+    );
+    let res: Metrics = query_helper(deps.as_ref(), QueryMsg::Metrics {});
+    assert_eq!(res.balance_hook_failures, 1);
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::RemoveBalanceHook {
+            contract: "dex".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::BalanceHookNotRegistered {
+            contract: "dex".to_string()
+        }
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::RemoveBalanceHook {
+            contract: "booster".to_string(),
+        },
+    )
+    .unwrap();
+
+    let res: Vec<String> = query_helper(deps.as_ref(), QueryMsg::BalanceHooks {});
+    assert_eq!(res, vec!["referrals".to_string()]);
+}
+
+#[test]
+fn transferring_ownership() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::TransferOwnership {
+            new_owner: "jake".to_string(),
+        },
+    )
+    .unwrap_err();
+
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::TransferOwnership {
+            new_owner: "jake".to_string(),
+        },
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 0);
+
+    let owner = state.owner.load(deps.as_ref().storage).unwrap();
+    assert_eq!(owner, Addr::unchecked("larry"));
+
+    // Signers can check what is awaiting a second step before acting on it
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::ProposeEmergencyUndelegation {},
+    )
+    .unwrap();
+    let res: OwnershipResponse = query_helper(deps.as_ref(), QueryMsg::Ownership {});
+    assert_eq!(
+        res,
+        OwnershipResponse {
+            owner: "larry".to_string(),
+            new_owner: Some("jake".to_string()),
+            pending_denom: None,
+            committee_proposal: None,
+            emergency_undelegation_ready_at: Some(
+                mock_env().block.time.seconds() + EMERGENCY_UNDELEGATION_DELAY_SECONDS
+            ),
+            halt_recovery: None,
+            guardian: None,
+            guardian_change: None,
+        }
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("pumpkin", &[]),
+        ExecuteMsg::AcceptOwnership {},
+    )
+    .unwrap_err();
+
+    assert_eq!(err, ContractError::UnauthorizedNewOwner {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::AcceptOwnership {},
+    )
+    .unwrap();
+
+    assert_eq!(res.messages.len(), 0);
+
+    let owner = state.owner.load(deps.as_ref().storage).unwrap();
+    assert_eq!(owner, Addr::unchecked("jake"));
+
+    let res: OwnershipResponse = query_helper(deps.as_ref(), QueryMsg::Ownership {});
+    assert_eq!(res.owner, "jake");
+    assert_eq!(res.new_owner, None);
+}
+
+#[test]
+fn raising_max_fee_through_committee() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    // Without a committee the max fee can not be changed at all
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::ProposeCommitteeAction {
+            action: CommitteeAction::UpdateMaxFee {
+                max_fee: Decimal::percent(30),
+            },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::FeeCommitteeNotSet {});
+
+    let members = vec!["alice".to_string(), "bob".to_string(), "carol".to_string()];
+
+    // The threshold must be a two-thirds supermajority
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::SetFeeCommittee {
+            members: members.clone(),
+            threshold: 1,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidCommitteeThreshold { members: 3 });
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::SetFeeCommittee {
+            members: members.clone(),
+            threshold: 2,
+        },
+    )
+    .unwrap();
+
+    // The owner can not replace the committee once registered
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::SetFeeCommittee {
+            members: vec!["larry".to_string()],
+            threshold: 1,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::FeeCommitteeExists {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::ProposeCommitteeAction {
+            action: CommitteeAction::UpdateMaxFee {
+                max_fee: Decimal::percent(30),
+            },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NotCommitteeMember {});
+
+    // A member proposes; the proposal counts as their approval
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("alice", &[]),
+        ExecuteMsg::ProposeCommitteeAction {
+            action: CommitteeAction::UpdateMaxFee {
+                max_fee: Decimal::percent(30),
+            },
+        },
+    )
+    .unwrap();
+    let res: Option<FeeCommitteeResponse> = query_helper(deps.as_ref(), QueryMsg::FeeCommittee {});
+    assert_eq!(
+        res,
+        Some(FeeCommitteeResponse {
+            members: members.clone(),
+            threshold: 2,
+            proposal: Some(CommitteeProposal {
+                id: 1,
+                action: CommitteeAction::UpdateMaxFee {
+                    max_fee: Decimal::percent(30)
+                },
+                approvals: vec![Addr::unchecked("alice")],
+            }),
+        })
+    );
+    assert_eq!(
+        state.max_fee_rate.load(deps.as_ref().storage).unwrap(),
+        Decimal::percent(20)
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("alice", &[]),
+        ExecuteMsg::ApproveCommitteeAction { id: 1 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::CommitteeProposalApproved { id: 1 });
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("bob", &[]),
+        ExecuteMsg::ApproveCommitteeAction { id: 2 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::CommitteeProposalNotFound { id: 2 });
+
+    // The second approval reaches the threshold and applies the change
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("bob", &[]),
+        ExecuteMsg::ApproveCommitteeAction { id: 1 },
+    )
+    .unwrap();
+    assert_eq!(res.events[1].ty, "steakhub/committee_action_applied");
+    assert_eq!(
+        state.max_fee_rate.load(deps.as_ref().storage).unwrap(),
+        Decimal::percent(30)
+    );
+    assert!(state
+        .committee_proposal
+        .may_load(deps.as_ref().storage)
+        .unwrap()
+        .is_none());
+
+    // The max fee can not drop below the fee currently charged
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("alice", &[]),
+        ExecuteMsg::ProposeCommitteeAction {
+            action: CommitteeAction::UpdateMaxFee {
+                max_fee: Decimal::percent(5),
+            },
+        },
+    )
+    .unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("bob", &[]),
+        ExecuteMsg::ApproveCommitteeAction { id: 2 },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::FeeAboveMax {
+            fee: Decimal::percent(10),
+            max_fee: Decimal::percent(5)
+        }
+    );
+}
+
+#[test]
+fn splitting_fees() {
+    let mut deps = setup_test();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::TransferFeeAccount {
+            fee_account_type: "Wallet".to_string(),
+            new_fee_account: "charlie".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap_err();
+
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::TransferFeeAccount {
+            fee_account_type: "xxxx".to_string(),
+            new_fee_account: "charlie".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap_err();
+
+    assert_eq!(err, ContractError::InvalidFeeType {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::TransferFeeAccount {
+            fee_account_type: "Wallet".to_string(),
+            new_fee_account: "charlie".to_string(),
+            reason: None,
+        },
+    )
+    .unwrap();
+    let res: ConfigResponse = query_helper(deps.as_ref(), QueryMsg::Config {});
+    assert_eq!(
+        res,
+        ConfigResponse {
+            owner: "larry".to_string(),
+            new_owner: None,
+            steak_token: "steak_token".to_string(),
+            epoch_period: 259200,
+            unbond_period: 1814400,
+            denom: "uxyz".to_string(),
+            fee_type: "Wallet".to_string(),
+            fee_account: "charlie".to_string(),
+            fee_rate: Decimal::from_ratio(10_u128, 100_u128),
+            max_fee_rate: Decimal::from_ratio(20_u128, 100_u128),
+            validators: vec![
+                "alice".to_string(),
+                "bob".to_string(),
+                "charlie".to_string()
+            ],
+            mining: initial_mining_config(),
+            coordinator: None,
+            token_admin: Some("larry".to_string()),
+            max_msgs_per_tx: None,
+        }
+    );
+
+    // A fee split account must answer the fee split contract's queries
+    let transfer_msg = ExecuteMsg::TransferFeeAccount {
+        fee_account_type: "FeeSplit".to_string(),
+        new_fee_account: "contract".to_string(),
+        reason: None,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        transfer_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NotFeeSplitContract {
+            address: "contract".to_string()
+        }
+    );
+
+    deps.querier.set_fee_split("contract", "fee_split_gov");
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        transfer_msg,
+    )
+    .unwrap();
+    let res: ConfigResponse = query_helper(deps.as_ref(), QueryMsg::Config {});
+    assert_eq!(
+        res,
+        ConfigResponse {
+            owner: "larry".to_string(),
+            new_owner: None,
+            steak_token: "steak_token".to_string(),
+            epoch_period: 259200,
+            unbond_period: 1814400,
+            denom: "uxyz".to_string(),
+            fee_type: "FeeSplit".to_string(),
+            fee_account: "contract".to_string(),
+            fee_rate: Decimal::from_ratio(10_u128, 100_u128),
+            max_fee_rate: Decimal::from_ratio(20_u128, 100_u128),
+            validators: vec![
+                "alice".to_string(),
+                "bob".to_string(),
+                "charlie".to_string()
+            ],
+            mining: initial_mining_config(),
+            coordinator: None,
+            token_admin: Some("larry".to_string()),
+            max_msgs_per_tx: None,
+        }
+    );
+}
+
+#[test]
+fn updating_token_marketing() {
+    let mut deps = setup_test();
+
+    let marketing_msg = ExecuteMsg::UpdateTokenMarketing {
+        project: Some("https://steak.example".to_string()),
+        description: Some("Liquid staked XYZ".to_string()),
+        marketing: None,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        marketing_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        marketing_msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::UpdateMarketing {
+                project: Some("https://steak.example".to_string()),
+                description: Some("Liquid staked XYZ".to_string()),
+                marketing: None,
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    let logo = Logo::Url("https://steak.example/logo.png".to_string());
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UploadTokenLogo { logo: logo.clone() },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::UploadLogo(logo)).unwrap(),
+            funds: vec![],
+        })
+    );
+}
+
+#[test]
+fn sharing_token_through_coordinator() {
+    let mut deps = setup_test();
+    deps.querier.set_coordinator("coordinator", "shared_steak");
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::SetCoordinator {
+            coordinator: "coordinator".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Only a hub that has not minted anything can join
+    deps.querier.set_cw20_total_supply("steak_token", 100);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::SetCoordinator {
+            coordinator: "coordinator".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::HubNotEmpty {});
+
+    deps.querier.set_cw20_total_supply("steak_token", 0);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::SetCoordinator {
+            coordinator: "coordinator".to_string(),
+        },
+    )
+    .unwrap();
+    let res: ConfigResponse = query_helper(deps.as_ref(), QueryMsg::Config {});
+    assert_eq!(res.steak_token, "shared_steak".to_string());
+    assert_eq!(res.coordinator, Some("coordinator".to_string()));
+    assert_eq!(res.token_admin, None);
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::SetCoordinator {
+            coordinator: "coordinator".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::CoordinatorAlreadySet {});
+
+    // Steak is minted through the coordinator
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user_1", &[Coin::new(1000000, "uxyz")]),
+        ExecuteMsg::Bond { receiver: None },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "coordinator".to_string(),
+            msg: to_binary(&CoordinatorExecuteMsg::Mint {
+                recipient: "user_1".to_string(),
+                amount: Uint128::new(1000000),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // The hub's share of the supply prices its bonds and unbonds, not the token's total supply
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 341667, "uxyz"),
+        Delegation::new("bob", 341667, "uxyz"),
+        Delegation::new("charlie", 341666, "uxyz"),
+    ]);
+    deps.querier
+        .set_coordinator_hub_supply(MOCK_CONTRACT_ADDR, 1000000);
+    deps.querier.set_cw20_total_supply("shared_steak", 5000000);
+    let res: StateResponse = query_helper(deps.as_ref(), QueryMsg::State {});
+    assert_eq!(res.total_usteak, Uint128::new(1000000));
+
+    // Unbonding requests must be routed by the coordinator
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("shared_steak", &[]),
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "user_1".to_string(),
+            amount: Uint128::new(23456),
+            msg: to_binary(&ReceiveMsg::QueueUnbond {
+                receiver: None,
+                min_out: None,
+                deadline: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::UnbondThroughCoordinator {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("shared_steak", &[]),
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "coordinator".to_string(),
+            amount: Uint128::new(23456),
+            msg: to_binary(&ReceiveMsg::QueueUnbond {
+                receiver: Some("user_1".to_string()),
+                min_out: None,
+                deadline: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap();
+    assert!(res.messages.iter().all(|msg| !matches!(
+        &msg.msg,
+        CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) if contract_addr == "coordinator"
+    )));
+
+    // Nor can a liquid unstake that falls back to unbonding redeem at this hub in particular
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::ConfigurePol {
+            pair: "steak_pair".to_string(),
+            lp_token: "steak_lp".to_string(),
+            fee_rate: Decimal::zero(),
+        },
+    )
+    .unwrap();
+    deps.querier.set_pair_pool(
+        "steak_pair",
+        PoolResponse {
+            assets: vec![
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: "shared_steak".to_string(),
+                    },
+                    amount: Uint128::new(100000),
+                },
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uxyz".to_string(),
+                    },
+                    amount: Uint128::new(100000),
+                },
+            ],
+            total_share: Uint128::new(100000),
+        },
+    );
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("shared_steak", &[]),
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "user_1".to_string(),
+            amount: Uint128::new(23456),
+            msg: to_binary(&ReceiveMsg::LiquidUnstake {
+                min_out: Uint128::MAX,
+                receiver: None,
+                deadline: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::UnbondThroughCoordinator {});
+
+    // Steak the hub queues on its own, e.g. when arbitrage buys it back, is reserved with the
+    // coordinator first, so the coordinator lets the hub burn it later
+    let res = queue_unbond(
+        deps.as_mut(),
+        mock_env(),
+        Addr::unchecked("user_2"),
+        Addr::unchecked("user_2"),
+        Uint128::new(100),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "coordinator".to_string(),
+            msg: to_binary(&CoordinatorExecuteMsg::Reserve {
+                amount: Uint128::new(100),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+}
+
+#[test]
+fn submit_proof() {
+    let mut deps = setup_test();
+    let state = State::default();
+    let miner_entropy =
+        "df5c2d1c1e799c13e81ef0d24acdb338e9da760af9afcd1bfbde40d61fed8996".to_string();
+    let miner_address = "joe1gh9nds8amsy33ewpt97gj4n99436hftz2zl79q".to_string();
+    let nonce = Uint64::from(121063160u64);
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 341667, "uxyz"),
+        Delegation::new("bob", 341667, "uxyz"),
+        Delegation::new("charlie", 341666, "uxyz"),
+    ]);
+    state
+        .miner_entropy
+        .save(deps.as_mut().storage, &miner_entropy)
+        .unwrap();
+    state
+        .miner_difficulty
+        .save(deps.as_mut().storage, &Uint64::new(5))
+        .unwrap();
+
+    // Miners can check a nonce against the live state before broadcasting it
+    let res: VerifyProofResponse = query_helper(
+        deps.as_ref(),
+        QueryMsg::VerifyProof {
+            miner: miner_address.clone(),
+            nonce,
+        },
+    );
+    assert_eq!(
+        res,
+        VerifyProofResponse {
+            hash: compute_miner_proof(ProofHash::Sha256, &miner_entropy, &miner_address, nonce)
+                .unwrap(),
+            difficulty: Uint64::new(5),
+            prefix: "00000".to_string(),
+            valid: true,
+        }
+    );
+    let res: VerifyProofResponse = query_helper(
+        deps.as_ref(),
+        QueryMsg::VerifyProof {
+            miner: miner_address.clone(),
+            nonce: nonce + Uint64::one(),
+        },
+    );
+    assert!(!res.valid);
+
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&miner_address.to_string(), &[]),
+        ExecuteMsg::SubmitProof {
+            nonce,
+            validator: "alice".to_string(),
+        },
+    )
+    .unwrap();
+
+    // The same nonce no longer meets the difficulty against the new entropy, but did against the
+    // entropy it replaced, so miners can tell they were beaten rather than unlucky
+    let entropy = state.miner_entropy.load(deps.as_ref().storage).unwrap();
+    let difficulty = state.miner_difficulty.load(deps.as_ref().storage).unwrap();
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&miner_address, &[]),
+        ExecuteMsg::SubmitProof {
+            nonce,
+            validator: "alice".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DifficultyNotMet {
+            reason: ProofRejection::StaleEntropy,
+            difficulty: difficulty.u64(),
+            prefix: create_difficulty_prefix(difficulty),
+            hash: compute_miner_proof(ProofHash::Sha256, &entropy, &miner_address, nonce).unwrap(),
+            entropy,
+        }
+    );
+
+    // The miner is recorded without displacing the protocol's fee account
+    let res: ConfigResponse = query_helper(deps.as_ref(), QueryMsg::Config {});
+    assert_eq!(res.fee_account, "the_fee_man");
+    assert_eq!(res.mining.miner, Some(miner_address.clone()));
+    assert_eq!(res.mining.miner_fee_share_bps, 10_000);
+    let res: Option<CurrentMiner> = query_helper(deps.as_ref(), QueryMsg::CurrentMiner {});
+    assert_eq!(
+        res,
+        Some(CurrentMiner {
+            miner: Addr::unchecked(&miner_address),
+            since_height: mock_env().block.height,
+            since_time: mock_env().block.time.seconds(),
+            earned: Uint128::zero(),
+        })
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::UpdateMinerFeeShare { bps: 2500 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateMinerFeeShare { bps: 10_001 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::MinerFeeShareTooHigh { max: 10_000 });
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateMinerFeeShare { bps: 2500 },
+    )
+    .unwrap();
+
+    // The 23 fee is split: 25% (5) to the miner, the other 18 to the fee account
+    deps.querier
+        .set_bank_balances(&[Coin::new(234u128, "uxyz")]);
+    state
+        .prev_denom
+        .save(deps.as_mut().storage, &Uint128::zero())
+        .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(CallbackMsg::Reinvest {}),
+    )
+    .unwrap();
+    let harvested = &res.events[0].attributes;
+    assert!(harvested.contains(&attr("fees_deducted", "23")));
+    assert!(harvested.contains(&attr("miner_fees", "5")));
+    assert_eq!(res.messages.len(), 3);
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "the_fee_man".to_string(),
+            amount: vec![Coin::new(18, "uxyz")],
+        })
+    );
+    assert_eq!(
+        res.messages[2].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: miner_address,
+            amount: vec![Coin::new(5, "uxyz")],
+        })
+    );
+    let res: Option<CurrentMiner> = query_helper(deps.as_ref(), QueryMsg::CurrentMiner {});
+    assert_eq!(res.unwrap().earned, Uint128::new(5));
+}
+
+#[test]
+fn splitting_mining_power() {
+    let mut deps = setup_test();
+    let state = State::default();
+    let miner_address = "joe1gh9nds8amsy33ewpt97gj4n99436hftz2zl79q".to_string();
+    let split_msg = |allocations: &[(&str, u64)]| ExecuteMsg::SubmitProofSplit {
+        nonce: Uint64::new(121063160),
+        allocations: allocations
+            .iter()
+            .map(|(validator, percent)| MiningAllocation {
+                validator: validator.to_string(),
+                weight: Decimal::percent(*percent),
+            })
+            .collect(),
+    };
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 341667, "uxyz"),
+        Delegation::new("bob", 341667, "uxyz"),
+        Delegation::new("charlie", 341666, "uxyz"),
+    ]);
+    state
+        .miner_entropy
+        .save(
+            deps.as_mut().storage,
+            &"df5c2d1c1e799c13e81ef0d24acdb338e9da760af9afcd1bfbde40d61fed8996".to_string(),
+        )
+        .unwrap();
+    state
+        .miner_difficulty
+        .save(deps.as_mut().storage, &Uint64::new(5))
+        .unwrap();
+    state
+        .miner_last_mined_block
+        .save(deps.as_mut().storage, &Uint64::new(12334))
+        .unwrap();
+
+    for (allocations, expected) in [
+        (
+            split_msg(&[("alice", 60), ("bob", 30)]),
+            ContractError::InvalidAllocationWeights {},
+        ),
+        (
+            split_msg(&[("alice", 100), ("bob", 0)]),
+            ContractError::InvalidAllocationWeights {},
+        ),
+        (split_msg(&[]), ContractError::InvalidAllocationWeights {}),
+        (
+            split_msg(&[("alice", 60), ("alice", 40)]),
+            ContractError::DuplicateAllocation {
+                validator: "alice".to_string(),
+            },
+        ),
+        (
+            split_msg(&[("alice", 60), ("dave", 40)]),
+            ContractError::ValidatorNotActive {
+                validator: "dave".to_string(),
+            },
+        ),
+    ] {
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&miner_address, &[]),
+            allocations,
+        )
+        .unwrap_err();
+        assert_eq!(err, expected);
+    }
+
+    // 11 blocks of mining power: 60% rounds down to 6, the remaining 5 go to bob
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&miner_address, &[]),
+        split_msg(&[("alice", 60), ("bob", 40)]),
+    )
+    .unwrap();
+
+    let res: Vec<ValidatorMiningPower> = query_helper(
+        deps.as_ref(),
+        QueryMsg::ValidatorMiningPowers {
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert_eq!(
+        res,
+        vec![
+            ValidatorMiningPower {
+                address: "alice".to_string(),
+                mining_power: Uint128::new(6),
+            },
+            ValidatorMiningPower {
+                address: "bob".to_string(),
+                mining_power: Uint128::new(5),
+            },
+        ]
+    );
+    assert_eq!(
+        state
+            .total_mining_power
+            .load(deps.as_ref().storage)
+            .unwrap(),
+        Uint128::new(11)
+    );
+}
+
+#[test]
+fn submitting_keccak_proof() {
+    let mut deps = setup_test();
+    let state = State::default();
+    let miner_entropy =
+        "df5c2d1c1e799c13e81ef0d24acdb338e9da760af9afcd1bfbde40d61fed8996".to_string();
+    let miner_address = "joe1gh9nds8amsy33ewpt97gj4n99436hftz2zl79q".to_string();
+    let proof_msg = |nonce: u64| ExecuteMsg::SubmitProof {
+        nonce: Uint64::new(nonce),
+        validator: "alice".to_string(),
+    };
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 341667, "uxyz"),
+        Delegation::new("bob", 341667, "uxyz"),
+        Delegation::new("charlie", 341666, "uxyz"),
+    ]);
+
+    // SHA-256 unless chosen otherwise at instantiation
+    let res: MinerParamsResponse = query_helper(deps.as_ref(), QueryMsg::MinerParams {});
+    assert_eq!(res.proof_hash, ProofHash::Sha256);
+
+    state
+        .proof_hash
+        .save(deps.as_mut().storage, &ProofHash::Keccak256)
+        .unwrap();
+    state
+        .miner_entropy
+        .save(deps.as_mut().storage, &miner_entropy)
+        .unwrap();
+    state
+        .miner_difficulty
+        .save(deps.as_mut().storage, &Uint64::new(5))
+        .unwrap();
+
+    let res: MinerParamsResponse = query_helper(deps.as_ref(), QueryMsg::MinerParams {});
+    assert_eq!(res.proof_hash, ProofHash::Keccak256);
+
+    // The nonce found with SHA-256 does not satisfy Keccak-256
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&miner_address, &[]),
+        proof_msg(121063160),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DifficultyNotMet {
+            reason: ProofRejection::InsufficientDifficulty,
+            difficulty: 5,
+            prefix: "00000".to_string(),
+            hash: compute_miner_proof(
+                ProofHash::Keccak256,
+                &miner_entropy,
+                &miner_address,
+                Uint64::new(121063160)
+            )
+            .unwrap(),
+            entropy: miner_entropy.clone(),
+        }
+    );
+
+    let res: VerifyProofResponse = query_helper(
+        deps.as_ref(),
+        QueryMsg::VerifyProof {
+            miner: miner_address.clone(),
+            nonce: Uint64::new(915378),
+        },
+    );
+    assert_eq!(
+        res.hash,
+        "00000e8ca669ecea78606bfddd7f7898f1956d3e115116e82ec0707cfc9da0c9"
+    );
+    assert!(res.valid);
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&miner_address, &[]),
+        proof_msg(915378),
+    )
+    .unwrap();
+}
+
+#[test]
+fn rate_limiting_proofs() {
+    let mut deps = setup_test();
+    let state = State::default();
+    let miner_entropy =
+        "df5c2d1c1e799c13e81ef0d24acdb338e9da760af9afcd1bfbde40d61fed8996".to_string();
+    let miner_address = "joe1gh9nds8amsy33ewpt97gj4n99436hftz2zl79q".to_string();
+    let proof_msg = || ExecuteMsg::SubmitProof {
+        nonce: Uint64::from(121063160u64),
+        validator: "alice".to_string(),
+    };
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 341667, "uxyz"),
+        Delegation::new("bob", 341667, "uxyz"),
+        Delegation::new("charlie", 341666, "uxyz"),
+    ]);
+    let reset_entropy = |storage: &mut dyn cosmwasm_std::Storage| {
+        state.miner_entropy.save(storage, &miner_entropy).unwrap();
+        state
+            .miner_difficulty
+            .save(storage, &Uint64::new(5))
+            .unwrap();
+    };
+    reset_entropy(deps.as_mut().storage);
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::UpdateProofCooldown { blocks: 10 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateProofCooldown { blocks: 10 },
+    )
+    .unwrap();
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&miner_address, &[]),
+        proof_msg(),
+    )
+    .unwrap();
+
+    // A second proof from the same miner has to wait out the cooldown
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&miner_address, &[]),
+        proof_msg(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ProofRateLimited { next_height: 12355 });
+
+    let res: MinerStatusResponse = query_helper(
+        deps.as_ref(),
+        QueryMsg::MinerStatus {
+            miner: miner_address.clone(),
+        },
+    );
+    assert_eq!(
+        res,
+        MinerStatusResponse {
+            last_proof_height: Some(12345),
+            cooldown_blocks: 10,
+            next_proof_height: 12355,
+        }
+    );
+
+    // Once the cooldown has passed, the same proof against the same entropy is still rejected
+    reset_entropy(deps.as_mut().storage);
+    let mut env = mock_env();
+    env.block.height += 10;
+    let err = execute(
+        deps.as_mut(),
+        env,
+        mock_info(&miner_address, &[]),
+        proof_msg(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ProofReplayed {});
+}
+
+#[test]
+fn rate_limiting_entropy() {
+    let mut deps = setup_test();
+
+    let entropy_msg = |entropy: &str| ExecuteMsg::UpdateEntropy {
+        entropy: entropy.to_string(),
+    };
+
+    // Without a limit, anyone may contribute as often as they like
+    for entropy in ["a", "b"] {
+        execute(
+            deps.as_mut(),
+            mock_env_at_timestamp(10010),
+            mock_info("jake", &[]),
+            entropy_msg(entropy),
+        )
+        .unwrap();
+    }
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10010),
+        mock_info("jake", &[]),
+        ExecuteMsg::UpdateEntropyRateLimit {
+            window: 100,
+            max_contributions: 2,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10010),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateEntropyRateLimit {
+            window: 0,
+            max_contributions: 2,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ZeroEntropyWindow {});
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10010),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateEntropyRateLimit {
+            window: 100,
+            max_contributions: 2,
+        },
+    )
+    .unwrap();
+
+    // The first contribution under the limit opens a new window
+    for timestamp in [10020, 10050] {
+        let res = execute(
+            deps.as_mut(),
+            mock_env_at_timestamp(timestamp),
+            mock_info("jake", &[]),
+            entropy_msg("c"),
+        )
+        .unwrap();
+        assert_eq!(res.events[0].ty, "steakhub/entropy_contributed");
+    }
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10119),
+        mock_info("jake", &[]),
+        entropy_msg("d"),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::EntropyRateLimited { next_time: 10120 });
+
+    // Other addresses have their own allowance
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10119),
+        mock_info("pumpkin", &[]),
+        entropy_msg("d"),
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10120),
+        mock_info("jake", &[]),
+        entropy_msg("e"),
+    )
+    .unwrap();
+    assert_eq!(
+        res.events[0].attributes[..4],
+        [
+            attr("time", "10120"),
+            attr("height", "12345"),
+            attr("id", "6"),
+            attr("contributor", "jake"),
+        ]
+    );
+
+    let res: EntropyContributorResponse = query_helper(
+        deps.as_ref(),
+        QueryMsg::EntropyContributor {
+            contributor: "jake".to_string(),
+        },
+    );
+    assert_eq!(
+        res,
+        EntropyContributorResponse {
+            contributor: EntropyContributor {
+                total_contributions: 5,
+                window_start: 10120,
+                window_contributions: 1,
+                last_contribution: 10120,
+            },
+            rate_limit: Some(EntropyRateLimit {
+                window: 100,
+                max_contributions: 2,
+            }),
+        }
+    );
+
+    // Each contribution is logged, chaining to the entropy draft
+    let res: Vec<EntropyContribution> = query_helper(
+        deps.as_ref(),
+        QueryMsg::EntropyContributions {
+            start_after: Some(4),
+            limit: None,
+        },
+    );
+    assert_eq!(res.len(), 2);
+    assert_eq!(res[0].contributor, Addr::unchecked("pumpkin"));
+    assert_eq!(res[1].time, 10120);
+    assert_eq!(
+        res[1].entropy_draft,
+        State::default()
+            .miner_entropy_draft
+            .load(deps.as_ref().storage)
+            .unwrap()
+    );
+
+    // Removing the limit
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10120),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateEntropyRateLimit {
+            window: 0,
+            max_contributions: 0,
+        },
+    )
+    .unwrap();
+    for entropy in ["f", "g"] {
+        execute(
+            deps.as_mut(),
+            mock_env_at_timestamp(10120),
+            mock_info("jake", &[]),
+            entropy_msg(entropy),
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn retargeting_difficulty() {
+    let mut deps = setup_test();
+    let state = State::default();
+    let difficulty = |deps: &OwnedDeps<MockStorage, MockApi, CustomQuerier>| {
+        state
+            .miner_difficulty
+            .load(deps.as_ref().storage)
+            .unwrap()
+            .u64()
+    };
+    let prove = |deps: &mut OwnedDeps<MockStorage, MockApi, CustomQuerier>, time: u64| {
+        update_difficulty(deps.as_mut().storage, time, true).unwrap();
+        state
+            .miner_last_mined_timestamp
+            .save(deps.as_mut().storage, &Uint64::new(time))
+            .unwrap();
+    };
+
+    // Proofs coming in fast raise the difficulty once, rather than on every proof
+    prove(&mut deps, 10010);
+    assert_eq!(difficulty(&deps), 2);
+    prove(&mut deps, 10020);
+    prove(&mut deps, 10030);
+    assert_eq!(difficulty(&deps), 2);
+    assert_eq!(
+        state.proof_intervals.load(deps.as_ref().storage).unwrap(),
+        vec![160, 10, 10]
+    );
+
+    // A long gap pulls the average over the ceiling
+    prove(&mut deps, 13030);
+    assert_eq!(difficulty(&deps), 1);
+    assert_eq!(
+        state.proof_intervals.load(deps.as_ref().storage).unwrap(),
+        vec![10, 0, 0, 187]
+    );
+
+    // Without proofs, it eases by one level per ceiling period however often entropy is updated
+    state
+        .miner_difficulty
+        .save(deps.as_mut().storage, &Uint64::new(3))
+        .unwrap();
+    for time in [13331, 13332, 13333] {
+        update_difficulty(deps.as_mut().storage, time, false).unwrap();
+    }
+    assert_eq!(difficulty(&deps), 2);
+    update_difficulty(deps.as_mut().storage, 13632, false).unwrap();
+    assert_eq!(difficulty(&deps), 1);
+}
+
+#[test]
+fn purging_mining_power() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    // Proofs can only credit validators the hub delegates to
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("miner", &[]),
+        ExecuteMsg::SubmitProof {
+            nonce: Uint64::new(1),
+            validator: "dave".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::ValidatorNotActive {
+            validator: "dave".to_string()
+        }
+    );
+
+    // Mining power accrued before the check, or before the validator was removed
+    state
+        .validator_mining_powers
+        .save(deps.as_mut().storage, "alice".to_string(), &Uint128::new(5))
+        .unwrap();
+    state
+        .validator_mining_powers
+        .save(deps.as_mut().storage, "dave".to_string(), &Uint128::new(10))
+        .unwrap();
+    state
+        .total_mining_power
+        .save(deps.as_mut().storage, &Uint128::new(15))
+        .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::PurgeMiningPower {
+            validators: vec!["alice".to_string(), "dave".to_string(), "eve".to_string()],
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.events[0].attributes[2..],
+        [attr("dave", "10"), attr("purged", "10")]
+    );
+
+    let res: Vec<ValidatorMiningPower> = query_helper(
+        deps.as_ref(),
+        QueryMsg::ValidatorMiningPowers {
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert_eq!(
+        res,
+        vec![ValidatorMiningPower {
+            address: "alice".to_string(),
+            mining_power: Uint128::new(5),
+        }]
+    );
+    assert_eq!(
+        state
+            .total_mining_power
+            .load(deps.as_ref().storage)
+            .unwrap(),
+        Uint128::new(5)
+    );
+
+    // Power left behind by earlier removals is purged on migration
+    state
+        .validator_mining_powers
+        .save(deps.as_mut().storage, "frank".to_string(), &Uint128::new(4))
+        .unwrap();
+    state
+        .total_mining_power
+        .save(deps.as_mut().storage, &Uint128::new(9))
+        .unwrap();
+    deps.querier.set_cw20_total_supply("steak_token", 0);
+    deps.querier
+        .set_cw20_balance("steak_token", MOCK_CONTRACT_ADDR, 0);
+
+    // A dry run reports what the migration would do without writing anything
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg::DryRun {}).unwrap_err();
+    let report: MigrationReport = match err {
+        ContractError::MigrationDryRun { report } => from_slice(report.as_bytes()).unwrap(),
+        err => panic!("unexpected error: {}", err),
+    };
+    assert_eq!(report.mining_power_purged, Uint128::new(4));
+    assert_eq!(report.unbond_requests_reindexed, 0);
+    assert!(report.invariants.healthy);
+    // the purged power, the total and the contract version
+    assert_eq!(report.keys_written, 3);
+    assert_eq!(
+        state
+            .total_mining_power
+            .load(deps.as_ref().storage)
+            .unwrap(),
+        Uint128::new(9)
+    );
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg::Migrate {}).unwrap();
+    assert_eq!(
+        res.attributes.last().unwrap(),
+        &attr("mining_power_purged", "4")
+    );
+    assert_eq!(
+        state
+            .total_mining_power
+            .load(deps.as_ref().storage)
+            .unwrap(),
+        Uint128::new(5)
+    );
+}
+
+#[test]
+fn migrating_from_terra_hub() {
+    let mut deps = mock_dependencies();
+    let state = State::default();
+
+    // A hub deployed from the Terra codebase, with one batch still unbonding
+    let storage = deps.as_mut().storage;
+    set_contract_version(storage, "steak-hub", "1.0.0").unwrap();
+    state
+        .owner
+        .save(storage, &Addr::unchecked("larry"))
+        .unwrap();
+    state
+        .steak_token
+        .save(storage, &Addr::unchecked("steak_token"))
+        .unwrap();
+    state.epoch_period.save(storage, &259200).unwrap();
+    state.unbond_period.save(storage, &1814400).unwrap();
+    state
+        .validators
+        .save(storage, &vec!["alice".to_string(), "bob".to_string()])
+        .unwrap();
+    state.unlocked_coins.save(storage, &vec![]).unwrap();
+    state
+        .pending_batch
+        .save(
+            storage,
+            &PendingBatch {
+                id: 3,
+                usteak_to_burn: Uint128::new(100),
+                est_unbond_start_time: 20000,
+            },
+        )
+        .unwrap();
+    let legacy_batches = vec![
+        BatchTerra {
+            id: 1,
+            reconciled: true,
+            total_shares: Uint128::new(200),
+            uluna_unclaimed: Uint128::new(210),
+            est_unbond_end_time: 5000,
+        },
+        BatchTerra {
+            id: 2,
+            reconciled: false,
+            total_shares: Uint128::new(300),
+            uluna_unclaimed: Uint128::new(330),
+            est_unbond_end_time: 30000,
+        },
+    ];
+    for batch in &legacy_batches {
+        terra_batches().save(storage, batch.id, batch).unwrap();
+    }
+    Item::<Vec<Addr>>::new("workers")
+        .save(storage, &vec![Addr::unchecked("worker")])
+        .unwrap();
+
+    deps.querier.set_bank_balances(&[Coin::new(540, "uluna")]);
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 500, "uluna"),
+        Delegation::new("bob", 500, "uluna"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000);
+    deps.querier
+        .set_cw20_balance("steak_token", MOCK_CONTRACT_ADDR, 100);
+
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg::DryRun {}).unwrap_err();
+    let report: MigrationReport = match err {
+        ContractError::MigrationDryRun { report } => from_slice(report.as_bytes()).unwrap(),
+        err => panic!("unexpected error: {}", err),
+    };
+    assert_eq!(report.previous_contract_name, "steak-hub");
+    assert_eq!(report.previous_contract_version, "1.0.0");
+    assert_eq!(report.batches_converted, 2);
+    assert!(report.invariants.healthy);
+
+    migrate(deps.as_mut(), mock_env(), MigrateMsg::Migrate {}).unwrap();
+
+    // Batches are converted to the current shape under the current keys
+    for batch in &legacy_batches {
+        assert_eq!(
+            state
+                .previous_batches
+                .load(deps.as_ref().storage, batch.id)
+                .unwrap(),
+            Batch {
+                id: batch.id,
+                reconciled: batch.reconciled,
+                total_shares: batch.total_shares,
+                amount_unclaimed: batch.uluna_unclaimed,
+                est_unbond_end_time: batch.est_unbond_end_time,
+            }
+        );
+        assert!(!terra_batches().has(deps.as_ref().storage, batch.id));
+    }
+
+    // Workers become harvesters, and the fee and miner state start from their defaults
+    let res: Vec<String> = query_helper(deps.as_ref(), QueryMsg::Harvesters {});
+    assert_eq!(res, vec!["worker".to_string()]);
+    let res: ConfigResponse = query_helper(deps.as_ref(), QueryMsg::Config {});
+    assert_eq!(res.denom, "uluna".to_string());
+    assert_eq!(res.fee_account, "larry".to_string());
+    assert_eq!(res.fee_rate, Decimal::zero());
+    assert_eq!(
+        state.validators_active.load(deps.as_ref().storage).unwrap(),
+        vec!["alice".to_string(), "bob".to_string()]
+    );
+    assert_eq!(
+        state.prev_denom.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(540)
+    );
+    assert_eq!(
+        state.miner_difficulty.load(deps.as_ref().storage).unwrap(),
+        Uint64::new(1)
+    );
+    assert_eq!(
+        state
+            .total_mining_power
+            .load(deps.as_ref().storage)
+            .unwrap(),
+        Uint128::zero()
+    );
+
+    let version = get_contract_version(deps.as_ref().storage).unwrap();
+    assert_eq!(version.contract, "steak-hub");
+}
+
+#[test]
+fn scheduling_mining_emission() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    // Without a schedule the miner receives all fees
+    let res: MiningEmissionResponse = query_helper(deps.as_ref(), QueryMsg::MiningEmission {});
+    assert_eq!(
+        res,
+        MiningEmissionResponse {
+            schedule: None,
+            epoch: 0,
+            miner_share: Decimal::one(),
+        }
+    );
+
+    let schedule_msg = |epoch_duration: u64| ExecuteMsg::UpdateMiningSchedule {
+        epoch_duration,
+        initial_share: Decimal::percent(80),
+        halving_epochs: 2,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("jake", &[]),
+        schedule_msg(100),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("larry", &[]),
+        schedule_msg(0),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ZeroEpochDuration {});
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10000),
+        mock_info("larry", &[]),
+        schedule_msg(100),
+    )
+    .unwrap();
+
+    // Epoch 2 is past the first halving: 80% / 2 = 40%
+    let res: MiningEmissionResponse = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(10250),
+        QueryMsg::MiningEmission {},
+    );
+    assert_eq!(res.epoch, 2);
+    assert_eq!(res.miner_share, Decimal::percent(40));
+    let res: ConfigResponse = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(10250),
+        QueryMsg::Config {},
+    );
+    assert_eq!(res.mining.miner_fee_share, Decimal::percent(40));
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 333334, "uxyz"),
+        Delegation::new("bob", 333333, "uxyz"),
+        Delegation::new("charlie", 333333, "uxyz"),
+    ]);
+    deps.querier
+        .set_bank_balances(&[Coin::new(234u128, "uxyz")]);
+    state
+        .prev_denom
+        .save(deps.as_mut().storage, &Uint128::zero())
+        .unwrap();
+    for validator in ["alice", "bob", "charlie"] {
+        state
+            .validator_mining_powers
+            .save(
+                deps.as_mut().storage,
+                validator.to_string(),
+                &Uint128::new(5),
+            )
+            .unwrap();
+    }
+    state
+        .total_mining_power
+        .save(deps.as_mut().storage, &Uint128::new(15))
+        .unwrap();
+
+    // The 23 fee is split: 40% (9) to the miner, the other 14 stays bonded
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(10250),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(CallbackMsg::Reinvest {}),
+    )
+    .unwrap();
+    let harvested = &res.events[0].attributes;
+    assert!(harvested.contains(&attr("fees_deducted", "9")));
+    assert!(harvested.contains(&attr("denom_bonded", "225")));
+    assert!(harvested.contains(&attr("fees_unemitted", "14")));
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "the_fee_man".to_string(),
+            amount: vec![Coin::new(9, "uxyz")],
+        })
+    );
+}
+
+//--------------------------------------------------------------------------------------------------
+// Queries
+//--------------------------------------------------------------------------------------------------
+
+#[test]
+fn querying_previous_batches() {
+    let mut deps = mock_dependencies();
+
+    let batches = vec![
+        Batch {
+            id: 1,
+            reconciled: false,
+            total_shares: Uint128::new(123),
+            amount_unclaimed: Uint128::new(678),
+            est_unbond_end_time: 10000,
+        },
+        Batch {
+            id: 2,
+            reconciled: true,
+            total_shares: Uint128::new(234),
+            amount_unclaimed: Uint128::new(789),
+            est_unbond_end_time: 15000,
+        },
+        Batch {
+            id: 3,
+            reconciled: false,
+            total_shares: Uint128::new(345),
+            amount_unclaimed: Uint128::new(890),
+            est_unbond_end_time: 20000,
+        },
+        Batch {
+            id: 4,
+            reconciled: true,
+            total_shares: Uint128::new(456),
+            amount_unclaimed: Uint128::new(999),
+            est_unbond_end_time: 25000,
+        },
+    ];
+
+    let state = State::default();
+    for batch in &batches {
+        state
+            .previous_batches
+            .save(deps.as_mut().storage, batch.id, batch)
+            .unwrap();
+    }
+
+    // Querying a single batch
+    let res: Batch = query_helper(deps.as_ref(), QueryMsg::PreviousBatch(1));
+    assert_eq!(res, batches[0].clone());
+
+    let res: Batch = query_helper(deps.as_ref(), QueryMsg::PreviousBatch(2));
+    assert_eq!(res, batches[1].clone());
+
+    // Query multiple batches
+    let res: Vec<Batch> = query_helper(
+        deps.as_ref(),
+        QueryMsg::PreviousBatches {
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert_eq!(res, batches);
+
+    let res: Vec<Batch> = query_helper(
+        deps.as_ref(),
+        QueryMsg::PreviousBatches {
+            start_after: Some(1),
+            limit: None,
+        },
+    );
+    assert_eq!(
+        res,
+        vec![batches[1].clone(), batches[2].clone(), batches[3].clone()]
+    );
+
+    let res: Vec<Batch> = query_helper(
+        deps.as_ref(),
+        QueryMsg::PreviousBatches {
+            start_after: Some(4),
+            limit: None,
+        },
+    );
+    assert_eq!(res, vec![]);
+
+    // Query multiple batches, indexed by whether it has been reconciled
+    let res = state
+        .previous_batches
+        .idx
+        .reconciled
+        .prefix(true.into())
+        .range(deps.as_ref().storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (_, v) = item.unwrap();
+            v
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(res, vec![batches[1].clone(), batches[3].clone()]);
+
+    let res = state
+        .previous_batches
+        .idx
+        .reconciled
+        .prefix(false.into())
+        .range(deps.as_ref().storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (_, v) = item.unwrap();
+            v
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(res, vec![batches[0].clone(), batches[2].clone()]);
+}
+
+#[test]
+fn querying_unbond_requests() {
+    let mut deps = mock_dependencies();
+    let state = State::default();
+
+    let unbond_requests = vec![
+        UnbondRequest {
+            id: 1,
+            user: Addr::unchecked("alice"),
+            shares: Uint128::new(123),
+            sender: None,
+        },
+        UnbondRequest {
+            id: 1,
+            user: Addr::unchecked("bob"),
+            shares: Uint128::new(234),
+            sender: None,
+        },
+        UnbondRequest {
+            id: 1,
+            user: Addr::unchecked("charlie"),
+            shares: Uint128::new(345),
+            sender: None,
+        },
+        UnbondRequest {
+            id: 2,
+            user: Addr::unchecked("alice"),
+            shares: Uint128::new(456),
+            sender: None,
+        },
+    ];
+
+    for unbond_request in &unbond_requests {
+        state
+            .unbond_requests
+            .save(
+                deps.as_mut().storage,
+                (
+                    unbond_request.id,
+                    &Addr::unchecked(unbond_request.user.clone()),
+                ),
+                unbond_request,
+            )
+            .unwrap();
+    }
+
+    let res: Vec<UnbondRequestsByBatchResponseItem> = query_helper(
+        deps.as_ref(),
+        QueryMsg::UnbondRequestsByBatch {
+            id: 1,
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert_eq!(
+        res,
+        vec![
+            unbond_requests[0].clone().into(),
+            unbond_requests[1].clone().into(),
+            unbond_requests[2].clone().into(),
+        ]
+    );
+
+    let res: Vec<UnbondRequestsByBatchResponseItem> = query_helper(
+        deps.as_ref(),
+        QueryMsg::UnbondRequestsByBatch {
+            id: 2,
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert_eq!(res, vec![unbond_requests[3].clone().into()]);
+
+    let res: Vec<UnbondRequestsByUserResponseItem> = query_helper(
+        deps.as_ref(),
+        QueryMsg::UnbondRequestsByUser {
+            user: "alice".to_string(),
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert_eq!(
+        res,
+        vec![
+            unbond_requests[0].clone().into(),
+            unbond_requests[3].clone().into()
+        ]
+    );
+
+    let res: Vec<UnbondRequestsByUserResponseItem> = query_helper(
+        deps.as_ref(),
+        QueryMsg::UnbondRequestsByUser {
+            user: "alice".to_string(),
+            start_after: Some(2),
+            limit: None,
+        },
+    );
+    assert_eq!(res, vec![unbond_requests[3].clone().into()]);
+}
+
+#[test]
+fn querying_unbond_request_details() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 341667, "uxyz"),
+        Delegation::new("bob", 341667, "uxyz"),
+        Delegation::new("charlie", 341666, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000000);
+
+    state
+        .pending_batch
+        .save(
+            deps.as_mut().storage,
+            &PendingBatch {
+                id: 3,
+                usteak_to_burn: Uint128::new(1000),
+                est_unbond_start_time: 269200,
+            },
+        )
+        .unwrap();
+    for batch in [
+        Batch {
+            id: 1,
+            reconciled: true,
+            total_shares: Uint128::new(1000),
+            amount_unclaimed: Uint128::new(1050),
+            est_unbond_end_time: 5000,
+        },
+        Batch {
+            id: 2,
+            reconciled: false,
+            total_shares: Uint128::new(2000),
+            amount_unclaimed: Uint128::new(2100),
+            est_unbond_end_time: 20000,
+        },
+    ] {
+        state
+            .previous_batches
+            .save(deps.as_mut().storage, batch.id, &batch)
+            .unwrap();
+    }
+    state
+        .swept_batches
+        .save(
+            deps.as_mut().storage,
+            1,
+            &SweptBatch {
+                id: 1,
+                native_swept: Uint128::new(100),
+                usteak_unclaimed: Uint128::new(100),
+                swept_time: 8000,
+            },
+        )
+        .unwrap();
+    // the request in batch 0 outlived its batch, and is left out
+    for (id, shares) in [(0, 300), (1, 400), (2, 500), (3, 1000)] {
+        state
+            .unbond_requests
+            .save(
+                deps.as_mut().storage,
+                (id, &Addr::unchecked("alice")),
+                &UnbondRequest {
+                    id,
+                    user: Addr::unchecked("alice"),
+                    shares: Uint128::new(shares),
+                    sender: None,
+                },
+            )
+            .unwrap();
+    }
+
+    let res: Vec<UnbondRequestDetails> = query_helper_env(
+        deps.as_ref(),
+        mock_env_at_timestamp(10000),
+        QueryMsg::UnbondRequestsByUserDetails {
+            user: "alice".to_string(),
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert_eq!(
+        res,
+        vec![
+            UnbondRequestDetails {
+                id: 1,
+                shares: Uint128::new(400),
+                pending: false,
+                reconciled: true,
+                est_unbond_end_time: 5000,
+                claimable: true,
+                native_amount: Uint128::new(420),
+                usteak_amount: Uint128::new(40),
+            },
+            UnbondRequestDetails {
+                id: 2,
+                shares: Uint128::new(500),
+                pending: false,
+                reconciled: false,
+                est_unbond_end_time: 20000,
+                claimable: false,
+                native_amount: Uint128::new(525),
+                usteak_amount: Uint128::zero(),
+            },
+            UnbondRequestDetails {
+                id: 3,
+                shares: Uint128::new(1000),
+                pending: true,
+                reconciled: false,
+                est_unbond_end_time: 269200 + 1814400,
+                claimable: false,
+                native_amount: Uint128::new(1025),
+                usteak_amount: Uint128::zero(),
+            },
+        ]
+    );
+}
+
+//--------------------------------------------------------------------------------------------------
+// Delegations
+//--------------------------------------------------------------------------------------------------
+
+#[test]
+fn computing_undelegations() {
+    let current_delegations = vec![
+        Delegation::new("alice", 400, "uxyz"),
+        Delegation::new("bob", 300, "uxyz"),
+        Delegation::new("charlie", 200, "uxyz"),
+    ];
+
+    // Target: (400 + 300 + 200 - 451) / 3 = 149
+    // Remainder: 2
+    // Alice:   400 - (149 + 1) = 250
+    // Bob:     300 - (149 + 1) = 150
+    // Charlie: 200 - (149 + 0) = 51
+    let new_undelegations = compute_undelegations(Uint128::new(451), &current_delegations, "uxyz");
+    let expected = vec![
+        Undelegation::new("alice", 250, "uxyz"),
+        Undelegation::new("bob", 150, "uxyz"),
+        Undelegation::new("charlie", 51, "uxyz"),
+    ];
+    assert_eq!(new_undelegations, expected);
+}
+
+#[test]
+fn computing_redelegations_for_removal() {
+    let current_delegations = [
+        Delegation::new("alice", 13000, "uxyz"),
+        Delegation::new("bob", 12000, "uxyz"),
+        Delegation::new("charlie", 11000, "uxyz"),
+        Delegation::new("dave", 10000, "uxyz"),
+    ];
+
+    // Suppose Dave will be removed
+    // native_token_per_validator = (13000 + 12000 + 11000 + 10000) / 3 = 15333
+    // remainder = 1
+    // to Alice:   15333 + 1 - 13000 = 2334
+    // to Bob:     15333 + 0 - 12000 = 3333
+    // to Charlie: 15333 + 0 - 11000 = 4333
+    let expected = vec![
+        Redelegation::new("dave", "alice", 2334, "uxyz"),
+        Redelegation::new("dave", "bob", 3333, "uxyz"),
+        Redelegation::new("dave", "charlie", 4333, "uxyz"),
+    ];
+
+    assert_eq!(
+        compute_redelegations_for_removal(
+            &current_delegations[3],
+            &current_delegations[..3],
+            "uxyz"
+        ),
+        expected,
+    );
+}
+
+#[test]
+fn computing_redelegations_for_rebalancing() {
+    let current_delegations = vec![
+        Delegation::new("alice", 69420, "uxyz"),
+        Delegation::new("bob", 1234, "uxyz"),
+        Delegation::new("charlie", 88888, "uxyz"),
+        Delegation::new("dave", 40471, "uxyz"),
+        Delegation::new("evan", 2345, "uxyz"),
+    ];
+    let active_validators: Vec<String> = vec![
+        "alice".to_string(),
+        "bob".to_string(),
+        "charlie".to_string(),
+        "dave".to_string(),
+        "evan".to_string(),
+    ];
+    // native_token_per_validator = (69420 + 88888 + 1234 + 40471 + 2345) / 4 = 40471
+    // remainer = 3
+    // src_delegations:
+    //  - alice:   69420 - (40471 + 1) = 28948
+    //  - charlie: 88888 - (40471 + 1) = 48416
+    // dst_delegations:
+    //  - bob:     (40471 + 1) - 1234  = 39238
+    //  - evan:    (40471 + 0) - 2345  = 38126
+    //
+    // Round 1: alice --(28948)--> bob
+    // src_delegations:
+    //  - charlie: 48416
+    // dst_delegations:
+    //  - bob:     39238 - 28948 = 10290
+    //  - evan:    38126
+    //
+    // Round 2: charlie --(10290)--> bob
+    // src_delegations:
+    //  - charlie: 48416 - 10290 = 38126
+    // dst_delegations:
+    //  - evan:    38126
+    //
+    // Round 3: charlie --(38126)--> evan
+    // Queues are emptied
+    let expected = vec![
+        Redelegation::new("alice", "bob", 28948, "uxyz"),
+        Redelegation::new("charlie", "bob", 10290, "uxyz"),
+        Redelegation::new("charlie", "evan", 38126, "uxyz"),
+    ];
+
+    assert_eq!(
+        compute_redelegations_for_rebalancing(
+            active_validators,
+            &current_delegations,
+            Uint128::from(10_u64),
+            // mock the same mining power on every validator
+            |_| Ok(40471_u128.into())
+        )
+        .unwrap(),
+        expected,
+    );
+
+    let partially_active = vec![
+        "alice".to_string(),
+        "charlie".to_string(),
+        "dave".to_string(),
+        "evan".to_string(),
+    ];
+
+    let partially_expected = vec![
+        Redelegation::new("alice", "dave", 10118, "uxyz"),
+        Redelegation::new("alice", "evan", 8712, "uxyz"),
+        Redelegation::new("charlie", "evan", 38299, "uxyz"),
+    ];
+    assert_eq!(
+        compute_redelegations_for_rebalancing(
+            partially_active.clone(),
+            &current_delegations,
+            Uint128::from(10_u64),
+            // mock the same mining power on every validator
+            |_| Ok(50589_u128.into())
+        )
+        .unwrap(),
+        partially_expected,
+    );
+
+    let partially_expected_minimums = vec![
+        Redelegation::new("alice", "evan", 18830, "uxyz"),
+        Redelegation::new("charlie", "evan", 29414, "uxyz"),
+    ];
+    assert_eq!(
+        compute_redelegations_for_rebalancing(
+            partially_active,
+            &current_delegations,
+            Uint128::from(15_000_u64),
+            // mock the same mining power on every validator
+            |_d| Ok(50589u128.into())
+        )
+        .unwrap(),
+        partially_expected_minimums,
+    );
+}
+
+#[test]
+fn computing_redelegations_for_rebalancing_with_mining() {
+    let current_delegations = vec![
+        Delegation::new("alice", 69420, "uxyz"),
+        Delegation::new("bob", 1234, "uxyz"),
+        Delegation::new("charlie", 88888, "uxyz"),
+        Delegation::new("dave", 40471, "uxyz"),
+        Delegation::new("evan", 2345, "uxyz"),
+    ];
+    let total_delegated_amount = current_delegations.iter().map(|d| d.amount).sum::<u128>();
+    let active_validators: Vec<String> = vec![
+        "alice".to_string(),
+        "bob".to_string(),
+        "charlie".to_string(),
+        "dave".to_string(),
+        "evan".to_string(),
+        // add steve to ensure still works for validators with no mining power
+        "steve".to_string(),
+    ];
+    let mining_powers_by_validator = [
+        ("alice".to_string(), 1002_u128),
+        ("bob".to_string(), 3214_u128),
+        ("charlie".to_string(), 881_u128),
+        ("dave".to_string(), 5471_u128),
+        ("evan".to_string(), 9285_u128),
+    ];
+    let total_mining_power = mining_powers_by_validator
+        .iter()
+        .map(|(_, power)| power)
+        .sum::<u128>();
+
+    // total delegated amount: 69420 + 1234 + 88888 + 40471 + 2345 = 202358
+    // total mining power:         1002 + 3214 + 881 + 5471 + 9285 = 19853
+    // remainder = 3
+    //
+    // alice target:                          202358 * 1002 / 19853 = 10213 + remainder 1 = 10214
+    // bob target:                            202358 * 3214 / 19853 = 32759 + remainder 1 = 32760
+    // charlie target:                         202358 * 881 / 19853 = 8979  + remainder 1 = 8980
+    // dave target:                           202358 * 5471 / 19853 = 55764
+    // evan target:                           202358 * 9285 / 19853 = 94640
+    //
+    // sum of targets:         10213 + 32759 + 8979 + 55764 + 94640 = 202355
+    //
+    // alice delta:                                   69420 - 10214 = 59206
+    // bob delta:                                      1234 - 32760 = -31526
+    // charlie delta:                                  88888 - 8980 = 79908
+    // dave delta:                                    40471 - 55764 = -15293
+    // evan delta:                                     2345 - 94640 = -92295
+    //
+    // sum of deltas:      59206 + -31526 + 79908 + -15293 + -92295 = 0
+    //
+    // Redelegations:
+    // alice -> bob: 31526 (alice now has delta 27680)
+    // alice -> dave: 15293 (alice now has delta 12387)
+    // alice -> evan: 12387 (alice now has delta 0)
+    // charlie -> evan: 79908 (charlie now has delta 0)
+
+    let expected = vec![
+        Redelegation::new("alice", "bob", 31526, "uxyz"),
+        Redelegation::new("alice", "dave", 15293, "uxyz"),
+        Redelegation::new("alice", "evan", 12387, "uxyz"),
+        Redelegation::new("charlie", "evan", 79908, "uxyz"),
+    ];
+
+    assert_eq!(
+        compute_redelegations_for_rebalancing(
+            active_validators,
+            &current_delegations,
+            Uint128::from(10_u64),
+            // mock the same mining power on every validator
+            |d| compute_target_delegation_from_mining_power(
+                total_delegated_amount.into(),
+                mining_powers_by_validator
+                    .iter()
+                    .find(|(v, _)| v == &d.validator)
+                    .unwrap()
+                    .1
+                    .into(),
+                total_mining_power.into()
+            )
+        )
+        .unwrap(),
+        expected,
+        "round one mining weighted rebalancing"
+    );
+
+    let partially_active = vec![
+        "alice".to_string(),
+        "charlie".to_string(),
+        "dave".to_string(),
+        "evan".to_string(),
+    ];
+
+    let partially_expected = vec![
+        Redelegation::new("alice", "dave", 10118, "uxyz"),
+        Redelegation::new("alice", "evan", 8712, "uxyz"),
+        Redelegation::new("charlie", "evan", 38299, "uxyz"),
+    ];
+    assert_eq!(
+        compute_redelegations_for_rebalancing(
+            partially_active.clone(),
+            &current_delegations,
+            Uint128::from(10_u64),
+            // mock the same mining power on every validator
+            |_| Ok(50589_u128.into())
+        )
+        .unwrap(),
+        partially_expected,
+        "round 2 mining weighted rebalancing"
+    );
+
+    let partially_expected_minimums = vec![
+        Redelegation::new("alice", "evan", 18830, "uxyz"),
+        Redelegation::new("charlie", "evan", 29414, "uxyz"),
+    ];
+    assert_eq!(
+        compute_redelegations_for_rebalancing(
+            partially_active,
+            &current_delegations,
+            Uint128::from(15_000_u64),
+            // mock the same mining power on every validator
+            |_d| Ok(50589u128.into())
+        )
+        .unwrap(),
+        partially_expected_minimums,
+        "round 2 mining weighted rebalancing with minimums"
+    );
+}
+
+//--------------------------------------------------------------------------------------------------
+// Coins
+//--------------------------------------------------------------------------------------------------
+
+#[test]
+fn parsing_coin() {
+    let coin = parse_coin("12345uatom").unwrap();
+    assert_eq!(coin, Coin::new(12345, "uatom"));
+
+    let coin =
+        parse_coin("23456ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B")
+            .unwrap();
+    assert_eq!(
+        coin,
+        Coin::new(
+            23456,
+            "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B"
+        )
+    );
+
+    let err = parse_coin("69420").unwrap_err();
+    assert_eq!(err, StdError::generic_err("failed to parse coin: 69420"));
+
+    let err = parse_coin("ngmi").unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Parsing u128: cannot parse integer from empty string")
+    );
+
+    // IBC hashes are brought to upper case, so both spellings count as the same denom
+    let coin = parse_coin("1ibc/0471f1c4e7afd3f07702bef6dc365268d64570f7c1fdc98ea6098dd6de59817b")
+        .unwrap();
+    assert_eq!(
+        coin.denom,
+        "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B"
+    );
+
+    let coin = parse_coin(" 42factory/juno1creator0address/u2token ").unwrap();
+    assert_eq!(coin, Coin::new(42, "factory/juno1creator0address/u2token"));
+
+    for invalid in [
+        "1ibc/0471F1",
+        "1ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE5981ZZ",
+        "1u$d",
+        "1ab",
+        "1/uxyz",
+    ] {
+        let err = parse_coin(invalid).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err(format!("invalid denom: {}", &invalid[1..]))
+        );
+    }
+}
+
+#[test]
+fn parsing_coins() {
+    let coins = Coins::from_str("").unwrap();
+    assert_eq!(coins.clone().into_vec(), vec![]);
+
+    let coins = Coins::from_str("12345uatom").unwrap();
+    assert_eq!(coins.clone().into_vec(), vec![Coin::new(12345, "uatom")]);
+
+    let coins = Coins::from_str("12345uatom,23456uxyz").unwrap();
+    assert_eq!(
+        coins.clone().into_vec(),
+        vec![Coin::new(12345, "uatom"), Coin::new(23456, "uxyz")]
+    );
+
+    // Repeated denoms are summed up, once normalized
+    let coins = Coins::from_str(
+        "1ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B, 2uxyz,,\
+         3ibc/0471f1c4e7afd3f07702bef6dc365268d64570f7c1fdc98ea6098dd6de59817b",
+    )
+    .unwrap();
+    assert_eq!(
+        coins.clone().into_vec(),
+        vec![
+            Coin::new(
+                4,
+                "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B"
+            ),
+            Coin::new(2, "uxyz"),
+        ]
+    );
+
+    let amount = (0..1000)
+        .map(|i| format!("{}factory/creator/denom{}", i, i % 500))
+        .collect::<Vec<_>>()
+        .join(",");
+    let coins = Coins::from_str(&amount).unwrap();
+    assert_eq!(
+        coins.find("factory/creator/denom7"),
+        Coin::new(7 + 507, "factory/creator/denom7")
+    );
+
+    // Coins come out sorted by denom, whatever order they went in
+    let coins = coins.into_vec();
+    assert_eq!(coins.len(), 500);
+    assert!(coins.windows(2).all(|pair| pair[0].denom < pair[1].denom));
+}
+
+#[test]
+fn adding_coins() {
+    let mut coins = Coins::default();
+
+    coins.add(&Coin::new(12345, "uatom")).unwrap();
+    assert_eq!(coins.clone().into_vec(), vec![Coin::new(12345, "uatom")]);
+
+    coins.add(&Coin::new(23456, "uxyz")).unwrap();
+    assert_eq!(
+        coins.clone().into_vec(),
+        vec![Coin::new(12345, "uatom"), Coin::new(23456, "uxyz")]
+    );
+
+    coins
+        .add_many(&Coins::from_str("76543uatom,69420uusd").unwrap())
+        .unwrap();
+    assert_eq!(
+        coins.clone().into_vec(),
+        vec![
+            Coin::new(88888, "uatom"),
+            Coin::new(69420, "uusd"),
+            Coin::new(23456, "uxyz")
+        ]
+    );
+}
+
+#[test]
+fn receiving_funds() {
+    let err = parse_received_fund(&[], "uxyz", &[]).unwrap_err();
+    assert_eq!(err, ContractError::InvalidFundsCount { count: 0 });
+
+    let err = parse_received_fund(&[Coin::new(12345, "uatom")], "uxyz", &[]).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnexpectedDenom {
+            expected: "uxyz".to_string(),
+            received: "uatom".to_string()
+        }
+    );
+
+    // Every unexpected coin is listed
+    let err = parse_received_fund(
+        &[
+            Coin::new(12345, "uatom"),
+            Coin::new(23456, "uxyz"),
+            Coin::new(1, "uusd"),
+        ],
+        "uxyz",
+        &[],
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnexpectedFunds {
+            expected: "uxyz".to_string(),
+            received: "12345uatom,1uusd".to_string()
+        }
+    );
+
+    let err = parse_received_fund(&[Coin::new(0, "uxyz")], "uxyz", &[]).unwrap_err();
+    assert_eq!(err, ContractError::ZeroDeposit {});
+
+    let (amount, refunds) = parse_received_fund(&[Coin::new(69420, "uxyz")], "uxyz", &[]).unwrap();
+    assert_eq!(amount, Uint128::new(69420));
+    assert!(refunds.is_empty());
+
+    // Refundable denoms ride along, to be refunded, but do not make a deposit on their own
+    let refundable = vec!["uusd".to_string()];
+    let (amount, refunds) = parse_received_fund(
+        &[Coin::new(3, "uusd"), Coin::new(69420, "uxyz")],
+        "uxyz",
+        &refundable,
+    )
+    .unwrap();
+    assert_eq!(amount, Uint128::new(69420));
+    assert_eq!(refunds, vec![Coin::new(3, "uusd")]);
+
+    let err = parse_received_fund(&[Coin::new(3, "uusd")], "uxyz", &refundable).unwrap_err();
+    assert_eq!(err, ContractError::ZeroDeposit {});
+
+    let err = parse_received_fund(
+        &[
+            Coin::new(3, "uusd"),
+            Coin::new(12345, "uatom"),
+            Coin::new(69420, "uxyz"),
+        ],
+        "uxyz",
+        &refundable,
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnexpectedFunds {
+            expected: "uxyz".to_string(),
+            received: "12345uatom".to_string()
+        }
+    );
+}
+
+#[test]
+fn refunding_coins_sent_along_bonds() {
+    let mut deps = setup_test();
+    let funds = [Coin::new(1000000, "uxyz"), Coin::new(5, "ufee")];
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user_1", &funds),
+        ExecuteMsg::Bond { receiver: None },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnexpectedFunds {
+            expected: "uxyz".to_string(),
+            received: "5ufee".to_string()
+        }
+    );
+
+    let update_msg = |denoms: &[&str]| ExecuteMsg::UpdateRefundableDenoms {
+        denoms: denoms.iter().map(|d| d.to_string()).collect(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        update_msg(&["ufee"]),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        update_msg(&["ufee", "uxyz"]),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::RefundableNativeDenom {
+            denom: "uxyz".to_string()
+        }
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        update_msg(&["ufee"]),
+    )
+    .unwrap();
+    let res: Vec<String> = query_helper(deps.as_ref(), QueryMsg::RefundableDenoms {});
+    assert_eq!(res, vec!["ufee".to_string()]);
+
+    // The deposit is bonded, and the dust sent back
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user_1", &funds),
+        ExecuteMsg::Bond {
+            receiver: Some("user_2".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 3);
+    assert_eq!(
+        res.messages[2].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "user_1".to_string(),
+            amount: vec![Coin::new(5, "ufee")],
+        })
+    );
+    let data: BondResponseData = from_binary(&res.data.unwrap()).unwrap();
+    assert_eq!(data.amount_bonded, Uint128::new(1000000));
+}
+
+#[test]
+fn querying_delegation_drift() {
+    let mut deps = setup_test();
+
+    // Without mining power, each validator's target is an even third of 1,000,000
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 400000, "uxyz"),
+        Delegation::new("bob", 300000, "uxyz"),
+        Delegation::new("charlie", 300000, "uxyz"),
+    ]);
+    let res: DelegationDriftResponse = query_helper(deps.as_ref(), QueryMsg::DelegationDrift {});
+    assert_eq!(
+        res,
+        DelegationDriftResponse {
+            max_deviation_bps: 666,
+            total_deviation_bps: 1333,
+            tolerance_bps: 500,
+            rebalance_recommended: true,
+        }
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::UpdateDriftTolerance { bps: 1000 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateDriftTolerance { bps: 10001 },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::DriftToleranceTooHigh { max: 10000 });
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateDriftTolerance { bps: 1000 },
+    )
+    .unwrap();
+    let res: DelegationDriftResponse = query_helper(deps.as_ref(), QueryMsg::DelegationDrift {});
+    assert_eq!(res.tolerance_bps, 1000);
+    assert!(!res.rebalance_recommended);
+}
+
+#[test]
+fn querying_contract_info() {
+    let deps = setup_test();
+
+    let res: ContractInfoResponse = query_helper(deps.as_ref(), QueryMsg::ContractInfo {});
+    assert_eq!(res.name, "pfc-steak-hub");
+    assert_eq!(res.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(res.commit, option_env!("GIT_COMMIT_HASH").map(String::from));
+    assert_eq!(res.token_mode, "cw20");
+    assert_eq!(res.chain_adapter, "liquidstaking");
+}
+
+#[test]
+fn aggregating_queries() {
+    let deps = setup_test();
+
+    let res: AggregateResponse = query_helper(
+        deps.as_ref(),
+        QueryMsg::Aggregate {
+            queries: vec![
+                QueryMsg::PendingBatch {},
+                QueryMsg::PreviousBatch(1),
+                QueryMsg::Aggregate { queries: vec![] },
+            ],
+        },
+    );
+    assert_eq!(res.results.len(), 3);
+
+    let pending_batch: PendingBatchResponse =
+        from_binary(res.results[0].data.as_ref().unwrap()).unwrap();
+    assert_eq!(
+        pending_batch,
+        query_helper::<PendingBatchResponse>(deps.as_ref(), QueryMsg::PendingBatch {})
+    );
+    assert_eq!(res.results[0].error, None);
+
+    assert_eq!(res.results[1].data, None);
+    assert!(res.results[1].error.is_some());
+
+    assert_eq!(
+        res.results[2],
+        AggregateResult {
+            data: None,
+            error: Some("Generic error: aggregate queries can not be nested".to_string()),
+        }
+    );
+
+    let err = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Aggregate {
+            queries: vec![QueryMsg::State {}; MAX_AGGREGATE_QUERIES + 1],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(format!(
+            "at most {} queries can be aggregated",
+            MAX_AGGREGATE_QUERIES
+        ))
+    );
+}
+
+#[test]
+fn registering_zapper() {
+    let mut deps = setup_test();
+
+    let res: Option<String> = query_helper(deps.as_ref(), QueryMsg::Zapper {});
+    assert_eq!(res, None);
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::UpdateZapper {
+            zapper: Some("zap".to_string()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateZapper {
+            zapper: Some("zap".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[1], attr("zapper", "zap"));
+
+    let res: Option<String> = query_helper(deps.as_ref(), QueryMsg::Zapper {});
+    assert_eq!(res, Some("zap".to_string()));
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateZapper { zapper: None },
+    )
+    .unwrap();
+
+    let res: Option<String> = query_helper(deps.as_ref(), QueryMsg::Zapper {});
+    assert_eq!(res, None);
+}
+
+#[test]
+fn unbonding_with_min_out_and_deadline() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    // 1 usteak = 1.1 native
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 400000, "uxyz"),
+        Delegation::new("bob", 400000, "uxyz"),
+        Delegation::new("charlie", 300000, "uxyz"),
+    ]);
+    deps.querier.set_cw20_total_supply("steak_token", 1000000);
+
+    let unbond_msg = |min_out: Option<u128>, deadline: Option<u64>| {
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "user_1".to_string(),
+            amount: Uint128::new(1000),
+            msg: to_binary(&ReceiveMsg::QueueUnbond {
+                receiver: None,
+                min_out: min_out.map(Uint128::new),
+                deadline,
+            })
+            .unwrap(),
+        })
+    };
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(12345),
+        mock_info("steak_token", &[]),
+        unbond_msg(None, Some(12344)),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DeadlinePassed {
+            deadline: 12344,
+            now: 12345
+        }
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(12345),
+        mock_info("steak_token", &[]),
+        unbond_msg(Some(1101), Some(12345)),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MinOutNotMet {
+            estimated: Uint128::new(1100),
+            min_out: Uint128::new(1101)
+        }
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(12345),
+        mock_info("steak_token", &[]),
+        unbond_msg(Some(1100), Some(12345)),
+    )
+    .unwrap();
+
+    let pending_batch = state.pending_batch.load(deps.as_ref().storage).unwrap();
+    assert_eq!(pending_batch.usteak_to_burn, Uint128::new(1000));
+
+    // Liquid unstaking checks the deadline before anything else
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(12345),
+        mock_info("steak_token", &[]),
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "user_1".to_string(),
+            amount: Uint128::new(1000),
+            msg: to_binary(&ReceiveMsg::LiquidUnstake {
+                min_out: Uint128::new(1000),
+                receiver: None,
+                deadline: Some(12000),
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::DeadlinePassed {
+            deadline: 12000,
+            now: 12345
+        }
     );
 }
 
 #[test]
-fn computing_redelegations_for_rebalancing_with_mining() {
-    let current_delegations = vec![
-        Delegation::new("alice", 69420, "uxyz"),
-        Delegation::new("bob", 1234, "uxyz"),
-        Delegation::new("charlie", 88888, "uxyz"),
-        Delegation::new("dave", 40471, "uxyz"),
-        Delegation::new("evan", 2345, "uxyz"),
-    ];
-    let total_delegated_amount = current_delegations.iter().map(|d| d.amount).sum::<u128>();
-    let active_validators: Vec<String> = vec![
-        "alice".to_string(),
-        "bob".to_string(),
-        "charlie".to_string(),
-        "dave".to_string(),
-        "evan".to_string(),
-        // add steve to ensure still works for validators with no mining power
-        "steve".to_string(),
-    ];
-    let mining_powers_by_validator = vec![
-        ("alice".to_string(), 1002_u128),
-        ("bob".to_string(), 3214_u128),
-        ("charlie".to_string(), 881_u128),
-        ("dave".to_string(), 5471_u128),
-        ("evan".to_string(), 9285_u128),
+fn capping_and_consolidating_unbond_requests() {
+    let mut deps = setup_test();
+    let state = State::default();
+
+    // user_1 holds requests in batches 1 and 2, which are reconciled and have finished unbonding,
+    // and in batch 3, which is not reconciled yet
+    let previous_batches = vec![
+        Batch {
+            id: 1,
+            reconciled: true,
+            total_shares: Uint128::new(92876),
+            amount_unclaimed: Uint128::new(95197),
+            est_unbond_end_time: 10000,
+        },
+        Batch {
+            id: 2,
+            reconciled: true,
+            total_shares: Uint128::new(34567),
+            amount_unclaimed: Uint128::new(35604),
+            est_unbond_end_time: 20000,
+        },
+        Batch {
+            id: 3,
+            reconciled: false,
+            total_shares: Uint128::new(45678),
+            amount_unclaimed: Uint128::new(47276),
+            est_unbond_end_time: 20000,
+        },
     ];
-    let total_mining_power = mining_powers_by_validator
-        .iter()
-        .map(|(_, power)| power)
-        .sum::<u128>();
+    for batch in &previous_batches {
+        state
+            .previous_batches
+            .save(deps.as_mut().storage, batch.id, batch)
+            .unwrap();
+    }
+    for (id, shares) in [(1u64, 23456u128), (2, 34567), (3, 45678)] {
+        state
+            .unbond_requests
+            .save(
+                deps.as_mut().storage,
+                (id, &Addr::unchecked("user_1")),
+                &UnbondRequest {
+                    id,
+                    user: Addr::unchecked("user_1"),
+                    shares: Uint128::new(shares),
+                    sender: None,
+                },
+            )
+            .unwrap();
+    }
+    state
+        .pending_batch
+        .save(
+            deps.as_mut().storage,
+            &PendingBatch {
+                id: 4,
+                usteak_to_burn: Uint128::zero(),
+                est_unbond_start_time: 100000,
+            },
+        )
+        .unwrap();
 
-    // total delegated amount: 69420 + 1234 + 88888 + 40471 + 2345 = 202358
-    // total mining power:         1002 + 3214 + 881 + 5471 + 9285 = 19853
-    // remainder = 3
-    //
-    // alice target:                          202358 * 1002 / 19853 = 10213 + remainder 1 = 10214
-    // bob target:                            202358 * 3214 / 19853 = 32759 + remainder 1 = 32760
-    // charlie target:                         202358 * 881 / 19853 = 8979  + remainder 1 = 8980
-    // dave target:                           202358 * 5471 / 19853 = 55764
-    // evan target:                           202358 * 9285 / 19853 = 94640
-    //
-    // sum of targets:         10213 + 32759 + 8979 + 55764 + 94640 = 202355
-    //
-    // alice delta:                                   69420 - 10214 = 59206
-    // bob delta:                                      1234 - 32760 = -31526
-    // charlie delta:                                  88888 - 8980 = 79908
-    // dave delta:                                    40471 - 55764 = -15293
-    // evan delta:                                     2345 - 94640 = -92295
-    //
-    // sum of deltas:      59206 + -31526 + 79908 + -15293 + -92295 = 0
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::UpdateMaxUnbondRequests { max: Some(3) },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateMaxUnbondRequests { max: Some(3) },
+    )
+    .unwrap();
+
+    let unbond_msg = ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+        sender: "user_1".to_string(),
+        amount: Uint128::new(1000),
+        msg: to_binary(&ReceiveMsg::QueueUnbond {
+            receiver: None,
+            min_out: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+
+    // A fourth request is rejected
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(25000),
+        mock_info("steak_token", &[]),
+        unbond_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::TooManyUnbondRequests { max: 3 });
+
+    // Batches 1 and 2 are merged into a single claim
     //
-    // Redelegations:
-    // alice -> bob: 31526 (alice now has delta 27680)
-    // alice -> dave: 15293 (alice now has delta 12387)
-    // alice -> evan: 12387 (alice now has delta 0)
-    // charlie -> evan: 79908 (charlie now has delta 0)
+    // From batch 1: 95,197 * 23,456 / 92,876 = 24,042
+    // From batch 2: 35,604
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(25000),
+        mock_info("user_1", &[]),
+        ExecuteMsg::ConsolidateRequests {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+    assert!(res.events[0].attributes.contains(&attr("ids", "1,2")));
+    assert!(res.events[0]
+        .attributes
+        .contains(&attr("native_consolidated", "59646")));
 
-    let expected = vec![
-        Redelegation::new("alice", "bob", 31526, "uxyz"),
-        Redelegation::new("alice", "dave", 15293, "uxyz"),
-        Redelegation::new("alice", "evan", 12387, "uxyz"),
-        Redelegation::new("charlie", "evan", 79908, "uxyz"),
-    ];
+    let res: ConsolidatedClaimResponse = query_helper(
+        deps.as_ref(),
+        QueryMsg::ConsolidatedClaim {
+            user: "user_1".to_string(),
+        },
+    );
+    assert_eq!(
+        res,
+        ConsolidatedClaimResponse {
+            max_unbond_requests: Some(3),
+            outstanding_requests: 1,
+            claim: ConsolidatedClaim {
+                native: Uint128::new(59646),
+                usteak: Uint128::zero(),
+            },
+        }
+    );
+    assert!(state
+        .previous_batches
+        .may_load(deps.as_ref().storage, 2)
+        .unwrap()
+        .is_none());
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(25000),
+        mock_info("user_1", &[]),
+        ExecuteMsg::ConsolidateRequests {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NothingToConsolidate {});
 
+    // There is room for new requests again
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(25000),
+        mock_info("steak_token", &[]),
+        unbond_msg,
+    )
+    .unwrap();
+
+    // The consolidated claim is paid out on withdrawal
+    let res = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(25000),
+        mock_info("user_1", &[]),
+        ExecuteMsg::WithdrawUnbonded { receiver: None },
+    )
+    .unwrap();
     assert_eq!(
-        compute_redelegations_for_rebalancing(
-            active_validators,
-            &current_delegations,
-            Uint128::from(10_u64),
-            // mock the same mining power on every validator
-            |d| compute_target_delegation_from_mining_power(
-                total_delegated_amount.into(),
-                mining_powers_by_validator
-                    .iter()
-                    .find(|(v, _)| v == &d.validator)
-                    .unwrap()
-                    .1
-                    .into(),
-                total_mining_power.into()
-            )
-            .into()
-        )
-        .unwrap(),
-        expected,
-        "round one mining weighted rebalancing"
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "user_1".to_string(),
+            amount: vec![Coin::new(59646, "uxyz")],
+        })
+    );
+    assert!(state
+        .consolidated_claims
+        .may_load(deps.as_ref().storage, &Addr::unchecked("user_1"))
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        state
+            .consolidated_total
+            .load(deps.as_ref().storage)
+            .unwrap(),
+        ConsolidatedClaim::default()
     );
 
-    let partially_active = vec![
-        "alice".to_string(),
-        "charlie".to_string(),
-        "dave".to_string(),
-        "evan".to_string(),
-    ];
+    // Requests queued on someone's behalf are capped separately: jake and alice can't open any more
+    // for user_2 between them, but that leaves user_2 all the room they have for their own
+    for (id, funder) in [(1u64, "jake"), (2, "alice"), (3, "jake")] {
+        state
+            .unbond_requests
+            .save(
+                deps.as_mut().storage,
+                (id, &Addr::unchecked("user_2")),
+                &UnbondRequest {
+                    id,
+                    user: Addr::unchecked("user_2"),
+                    shares: Uint128::new(1),
+                    sender: Some(Addr::unchecked(funder)),
+                },
+            )
+            .unwrap();
+    }
+    let err = execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(25000),
+        mock_info("steak_token", &[]),
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "bob".to_string(),
+            amount: Uint128::new(1000),
+            msg: to_binary(&ReceiveMsg::QueueUnbond {
+                receiver: Some("user_2".to_string()),
+                min_out: None,
+                deadline: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::TooManyUnbondRequests { max: 3 });
+
+    execute(
+        deps.as_mut(),
+        mock_env_at_timestamp(25000),
+        mock_info("steak_token", &[]),
+        ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+            sender: "user_2".to_string(),
+            amount: Uint128::new(1000),
+            msg: to_binary(&ReceiveMsg::QueueUnbond {
+                receiver: None,
+                min_out: None,
+                deadline: None,
+            })
+            .unwrap(),
+        }),
+    )
+    .unwrap();
+}
+
+#[test]
+fn skimming_surplus() {
+    let mut deps = setup_test();
+    let state = State::default();
+    let env = mock_env_at_timestamp(25000);
+
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 341667, "uxyz"),
+        Delegation::new("bob", 341667, "uxyz"),
+        Delegation::new("charlie", 341666, "uxyz"),
+    ]);
 
-    let partially_expected = vec![
-        Redelegation::new("alice", "dave", 10118, "uxyz"),
-        Redelegation::new("alice", "evan", 8712, "uxyz"),
-        Redelegation::new("charlie", "evan", 38299, "uxyz"),
-    ];
-    assert_eq!(
-        compute_redelegations_for_rebalancing(
-            partially_active.clone(),
-            &current_delegations,
-            Uint128::from(10_u64),
-            // mock the same mining power on every validator
-            |_| Ok(50589_u128.into())
+    // The hub owes 3000 to a batch that has finished unbonding and holds 1000 in the arbitrage
+    // reserve; a batch the chain still reports as unbonding is not in the balance yet. The
+    // remaining 6000 was sent straight to the hub. The 2000 unbonding from bob was undelegated for
+    // something other than a batch, so it is not netted off
+    for (id, amount_unclaimed, est_unbond_end_time) in
+        [(1u64, 3000u128, 20000u64), (2, 5000, 30000)]
+    {
+        state
+            .previous_batches
+            .save(
+                deps.as_mut().storage,
+                id,
+                &Batch {
+                    id,
+                    reconciled: false,
+                    total_shares: Uint128::new(amount_unclaimed),
+                    amount_unclaimed: Uint128::new(amount_unclaimed),
+                    est_unbond_end_time,
+                },
+            )
+            .unwrap();
+    }
+    state
+        .arb_reserve
+        .save(deps.as_mut().storage, &Uint128::new(1000))
+        .unwrap();
+    deps.querier.set_bank_balances(&[Coin::new(10000, "uxyz")]);
+    state
+        .batch_undelegations
+        .save(
+            deps.as_mut().storage,
+            (2, "alice".to_string()),
+            &Uint128::new(5000),
         )
-        .unwrap(),
-        partially_expected,
-        "round 2 mining weighted rebalancing"
-    );
+        .unwrap();
+    deps.querier
+        .set_unbonding_delegations(&[("alice", &[5000]), ("bob", &[2000])]);
 
-    let partially_expected_minimums = vec![
-        Redelegation::new("alice", "evan", 18830, "uxyz"),
-        Redelegation::new("charlie", "evan", 29414, "uxyz"),
-    ];
+    let res: SurplusResponse = query_helper_env(deps.as_ref(), env.clone(), QueryMsg::Surplus {});
     assert_eq!(
-        compute_redelegations_for_rebalancing(
-            partially_active,
-            &current_delegations,
-            Uint128::from(15_000_u64),
-            // mock the same mining power on every validator
-            |d| Ok(50589u128.into())
-        )
-        .unwrap(),
-        partially_expected_minimums,
-        "round 2 mining weighted rebalancing with minimums"
+        res,
+        SurplusResponse {
+            native_balance: Uint128::new(10000),
+            native_liabilities: Uint128::new(4000),
+            surplus: Uint128::new(6000),
+            policy: SurplusPolicy::Reinvest,
+        }
     );
-}
 
-//--------------------------------------------------------------------------------------------------
-// Coins
-//--------------------------------------------------------------------------------------------------
+    // The chain pays the second batch out before its estimated end time; it is still owed
+    deps.querier.set_bank_balances(&[Coin::new(15000, "uxyz")]);
+    deps.querier.set_unbonding_delegations(&[]);
 
-#[test]
-fn parsing_coin() {
-    let coin = parse_coin("12345uatom").unwrap();
-    assert_eq!(coin, Coin::new(12345, "uatom"));
+    let res: SurplusResponse = query_helper_env(deps.as_ref(), env.clone(), QueryMsg::Surplus {});
+    assert_eq!(res.native_liabilities, Uint128::new(9000));
+    assert_eq!(res.surplus, Uint128::new(6000));
 
-    let coin =
-        parse_coin("23456ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B")
-            .unwrap();
+    // By default, the surplus is bonded without minting any Steak
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("jake", &[]),
+        ExecuteMsg::SkimSurplus {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
     assert_eq!(
-        coin,
-        Coin::new(
-            23456,
-            "ibc/0471F1C4E7AFD3F07702BEF6DC365268D64570F7C1FDC98EA6098DD6DE59817B"
+        res.messages[0],
+        SubMsg::reply_on_success(
+            Delegation::new("charlie", 6000, "uxyz")
+                .to_cosmos_msg(env.contract.address.to_string())
+                .unwrap(),
+            REPLY_REGISTER_RECEIVED_COINS
         )
     );
 
-    let err = parse_coin("69420").unwrap_err();
-    assert_eq!(err, StdError::generic_err("failed to parse coin: 69420"));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::UpdateSurplusPolicy {
+            policy: SurplusPolicy::FeeAccount,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
 
-    let err = parse_coin("ngmi").unwrap_err();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateSurplusPolicy {
+            policy: SurplusPolicy::Insurance {
+                fund: "insurance_fund".to_string(),
+            },
+        },
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("jake", &[]),
+        ExecuteMsg::SkimSurplus {},
+    )
+    .unwrap();
     assert_eq!(
-        err,
-        StdError::generic_err("Parsing u128: cannot parse integer from empty string")
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "insurance_fund".to_string(),
+            amount: vec![Coin::new(6000, "uxyz")],
+        })
     );
-}
-
-#[test]
-fn parsing_coins() {
-    let coins = Coins::from_str("").unwrap();
-    assert_eq!(coins.0, vec![]);
-
-    let coins = Coins::from_str("12345uatom").unwrap();
-    assert_eq!(coins.0, vec![Coin::new(12345, "uatom")]);
 
-    let coins = Coins::from_str("12345uatom,23456uxyz").unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::UpdateSurplusPolicy {
+            policy: SurplusPolicy::FeeAccount,
+        },
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("jake", &[]),
+        ExecuteMsg::SkimSurplus {},
+    )
+    .unwrap();
     assert_eq!(
-        coins.0,
-        vec![Coin::new(12345, "uatom"), Coin::new(23456, "uxyz")]
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "the_fee_man".to_string(),
+            amount: vec![Coin::new(6000, "uxyz")],
+        })
     );
+
+    // Nothing is skimmed once the balance only covers the liabilities
+    deps.querier.set_bank_balances(&[Coin::new(9000, "uxyz")]);
+    let err = execute(
+        deps.as_mut(),
+        env,
+        mock_info("jake", &[]),
+        ExecuteMsg::SkimSurplus {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoSurplus {});
 }
 
 #[test]
-fn adding_coins() {
-    let mut coins = Coins(vec![]);
-
-    coins.add(&Coin::new(12345, "uatom")).unwrap();
-    assert_eq!(coins.0, vec![Coin::new(12345, "uatom")]);
+fn surplus_excludes_every_reserve() {
+    let mut deps = setup_test();
+    let state = State::default();
+    let env = mock_env_at_timestamp(25000);
 
-    coins.add(&Coin::new(23456, "uxyz")).unwrap();
-    assert_eq!(
-        coins.0,
-        vec![Coin::new(12345, "uatom"), Coin::new(23456, "uxyz")]
-    );
+    deps.querier.set_staking_delegations(&[
+        Delegation::new("alice", 341667, "uxyz"),
+        Delegation::new("bob", 341667, "uxyz"),
+        Delegation::new("charlie", 341666, "uxyz"),
+    ]);
+    deps.querier
+        .set_cw20_balance("steak_token", MOCK_CONTRACT_ADDR, 0);
+
+    // Every reserve is built up in turn, with the balance growing by exactly what the hub takes in
+    // on someone's behalf; at no point is any of it surplus
+    let assert_no_surplus = |deps: &mut OwnedDeps<MockStorage, MockApi, CustomQuerier>,
+                             balance: u128| {
+        deps.querier
+            .set_bank_balances(&[Coin::new(balance, "uxyz")]);
+        let res: SurplusResponse =
+            query_helper_env(deps.as_ref(), env.clone(), QueryMsg::Surplus {});
+        assert_eq!(res.native_liabilities, Uint128::new(balance));
+        assert_eq!(res.surplus, Uint128::zero());
+    };
 
-    coins
-        .add_many(&Coins::from_str("76543uatom,69420uusd").unwrap())
+    // A batch that has finished unbonding, which user_1 then consolidates their claim on
+    state
+        .previous_batches
+        .save(
+            deps.as_mut().storage,
+            1,
+            &Batch {
+                id: 1,
+                reconciled: true,
+                total_shares: Uint128::new(3000),
+                amount_unclaimed: Uint128::new(3000),
+                est_unbond_end_time: 20000,
+            },
+        )
         .unwrap();
-    assert_eq!(
-        coins.0,
-        vec![
-            Coin::new(88888, "uatom"),
-            Coin::new(23456, "uxyz"),
-            Coin::new(69420, "uusd")
-        ]
-    );
-}
+    state
+        .unbond_requests
+        .save(
+            deps.as_mut().storage,
+            (1, &Addr::unchecked("user_1")),
+            &UnbondRequest {
+                id: 1,
+                user: Addr::unchecked("user_1"),
+                shares: Uint128::new(3000),
+                sender: None,
+            },
+        )
+        .unwrap();
+    assert_no_surplus(&mut deps, 3000);
 
-#[test]
-fn receiving_funds() {
-    let err = parse_received_fund(&[], "uxyz").unwrap_err();
-    assert_eq!(
-        err,
-        StdError::generic_err("must deposit exactly one coin; received 0")
-    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user_1", &[]),
+        ExecuteMsg::ConsolidateRequests {},
+    )
+    .unwrap();
+    assert_no_surplus(&mut deps, 3000);
 
-    let err = parse_received_fund(
-        &[Coin::new(12345, "uatom"), Coin::new(23456, "uxyz")],
-        "uxyz",
+    // The arbitrage reserve and a validator's commission rebate
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("jake", &[Coin::new(1000, "uxyz")]),
+        ExecuteMsg::FundArbReserve {},
     )
-    .unwrap_err();
-    assert_eq!(
-        err,
-        StdError::generic_err("must deposit exactly one coin; received 2")
-    );
+    .unwrap();
+    assert_no_surplus(&mut deps, 4000);
 
-    let err = parse_received_fund(&[Coin::new(12345, "uatom")], "uxyz").unwrap_err();
-    assert_eq!(
-        err,
-        StdError::generic_err("expected uxyz deposit, received uatom")
-    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("alice", &[Coin::new(200, "uxyz")]),
+        ExecuteMsg::DepositRebate {
+            validator: "alice".to_string(),
+        },
+    )
+    .unwrap();
+    assert_no_surplus(&mut deps, 4200);
 
-    let err = parse_received_fund(&[Coin::new(0, "uxyz")], "uxyz").unwrap_err();
-    assert_eq!(
-        err,
-        StdError::generic_err("deposit amount must be non-zero")
-    );
+    // Protocol-owned liquidity withdrawn from the pair
+    deps.querier.set_bank_balances(&[Coin::new(4700, "uxyz")]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::Callback(CallbackMsg::SettlePolWithdrawal {
+            native_before: Uint128::new(4200),
+            usteak_before: Uint128::zero(),
+        }),
+    )
+    .unwrap();
+    assert_no_surplus(&mut deps, 4700);
 
-    let amount = parse_received_fund(&[Coin::new(69420, "uxyz")], "uxyz").unwrap();
-    assert_eq!(amount, Uint128::new(69420));
+    // Fees a reinvest failed to forward to the fee account
+    state
+        .fee_forward
+        .save(deps.as_mut().storage, &Uint128::new(50))
+        .unwrap();
+    reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: REPLY_FEE_FORWARD_DONE,
+            result: cosmwasm_std::SubMsgResult::Err("fee account unavailable".to_string()),
+        },
+    )
+    .unwrap();
+    assert_no_surplus(&mut deps, 4750);
+
+    // What harvests leave behind: rewards withdrawn but not reinvested yet, rewards deferred to the
+    // next reinvest, and the boost owed to locked bonds
+    state
+        .unlocked_coins
+        .save(deps.as_mut().storage, &vec![Coin::new(30, "uxyz")])
+        .unwrap();
+    assert_no_surplus(&mut deps, 4780);
+
+    state
+        .pending_reinvest
+        .save(deps.as_mut().storage, &Uint128::new(70))
+        .unwrap();
+    assert_no_surplus(&mut deps, 4850);
+
+    state
+        .lock_boost_unclaimed
+        .save(deps.as_mut().storage, &Uint128::new(60))
+        .unwrap();
+    assert_no_surplus(&mut deps, 4910);
+
+    // The native set aside for the Steak the hub held when it was wound down
+    state
+        .emergency_held
+        .save(
+            deps.as_mut().storage,
+            &EmergencyHeld {
+                usteak: Uint128::new(100),
+                native: Uint128::new(400),
+            },
+        )
+        .unwrap();
+    assert_no_surplus(&mut deps, 5310);
+
+    let err = execute(
+        deps.as_mut(),
+        env,
+        mock_info("jake", &[]),
+        ExecuteMsg::SkimSurplus {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NoSurplus {});
 }
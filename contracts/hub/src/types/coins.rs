@@ -1,59 +1,84 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use std::str::FromStr;
 
-use cosmwasm_std::{Coin, StdError, StdResult};
+use cosmwasm_std::{Coin, StdError, StdResult, Uint128};
 
 use crate::helpers::parse_coin;
 
-pub struct Coins(pub Vec<Coin>);
+/// A set of coins, at most one per denom, kept sorted by denom. Merging is logarithmic in the
+/// number of denoms, so a reply carrying many reward denoms does not degrade quadratically
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Coins(BTreeMap<String, Uint128>);
 
 impl FromStr for Coins {
     type Err = StdError;
 
+    /// Parse a comma-separated list of coins, as found in the `amount` attribute of bank events.
+    /// Coins of the same denom, once normalized, are summed up
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.is_empty() {
-            return Ok(Self(vec![]));
+        let mut coins = Self::default();
+        for coin_str in s.split(',').map(str::trim) {
+            // coin with zero amount may appeat as an empty string in the event log
+            if !coin_str.is_empty() {
+                coins.add(&parse_coin(coin_str)?)?;
+            }
         }
+        Ok(coins)
+    }
+}
+
+impl TryFrom<Vec<Coin>> for Coins {
+    type Error = StdError;
 
-        Ok(Self(
-            s.split(',')
-                .filter(|coin_str| !coin_str.is_empty()) // coin with zero amount may appeat as an empty string in the event log
-                .collect::<Vec<&str>>()
-                .iter()
-                .map(|s| parse_coin(s))
-                .collect::<StdResult<Vec<Coin>>>()?,
-        ))
+    fn try_from(coins: Vec<Coin>) -> Result<Self, Self::Error> {
+        let mut res = Self::default();
+        for coin in &coins {
+            res.add(coin)?;
+        }
+        Ok(res)
     }
 }
 
 impl Coins {
     pub fn add(&mut self, coin_to_add: &Coin) -> StdResult<()> {
-        match self
-            .0
-            .iter_mut()
-            .find(|coin| coin.denom == coin_to_add.denom)
-        {
-            Some(coin) => {
-                coin.amount = coin.amount.checked_add(coin_to_add.amount)?;
-            }
-            None => {
-                self.0.push(coin_to_add.clone());
-            }
+        if coin_to_add.amount.is_zero() {
+            return Ok(());
         }
+        let amount = self.0.entry(coin_to_add.denom.clone()).or_default();
+        *amount = amount.checked_add(coin_to_add.amount)?;
         Ok(())
     }
 
     pub fn add_many(&mut self, coins_to_add: &Coins) -> StdResult<()> {
-        for coin_to_add in &coins_to_add.0 {
-            self.add(coin_to_add)?;
+        for (denom, amount_to_add) in &coins_to_add.0 {
+            let amount = self.0.entry(denom.clone()).or_default();
+            *amount = amount.checked_add(*amount_to_add)?;
         }
         Ok(())
     }
 
     pub fn find(&self, denom: &str) -> Coin {
+        Coin {
+            denom: denom.to_string(),
+            amount: self.0.get(denom).copied().unwrap_or_default(),
+        }
+    }
+
+    /// Keep only the coins whose denom satisfies `f`
+    pub fn retain(&mut self, mut f: impl FnMut(&str) -> bool) {
+        self.0.retain(|denom, _| f(denom));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The coins, sorted by denom
+    pub fn into_vec(self) -> Vec<Coin> {
         self.0
-            .iter()
-            .cloned()
-            .find(|coin| coin.denom == denom)
-            .unwrap_or_else(|| Coin::new(0, denom))
+            .into_iter()
+            .map(|(denom, amount)| Coin { denom, amount })
+            .collect()
     }
 }
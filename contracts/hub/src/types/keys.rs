@@ -29,13 +29,13 @@ impl<'a> PrimaryKey<'a> for BooleanKey {
     type Suffix = ();
     type SuperSuffix = ();
 
-    fn key(&self) -> Vec<Key> {
+    fn key(&self) -> Vec<Key<'_>> {
         self.wrapped.key()
     }
 }
 
 impl<'a> Prefixer<'a> for BooleanKey {
-    fn prefix(&self) -> Vec<Key> {
+    fn prefix(&self) -> Vec<Key<'_>> {
         self.wrapped.prefix()
     }
 }
@@ -4,4 +4,4 @@ mod staking;
 
 pub use coins::Coins;
 pub use keys::BooleanKey;
-pub use staking::{Delegation, Redelegation, RewardWithdrawal, Undelegation};
+pub use staking::{Delegation, Redelegation, RewardWithdrawal, Undelegation, STAKING_MODULE};
@@ -3,6 +3,9 @@ use cosmos_sdk_proto::cosmos::staking::v1beta1::{MsgBeginRedelegate, MsgDelegate
 use cosmos_sdk_proto::cosmos::{base::v1beta1::Coin as SdkCoin, staking::v1beta1::MsgUndelegate};
 use cosmwasm_std::{CosmosMsg, StdResult};
 
+/// Cosmos SDK module the staking and distribution messages are encoded for
+pub const STAKING_MODULE: &str = "liquidstaking";
+
 #[derive(Clone)]
 #[cfg_attr(test, derive(Debug, PartialEq, Eq))]
 pub struct Delegation {
@@ -10,7 +13,6 @@ pub struct Delegation {
     pub amount: u128,
     pub denom: String,
 }
-// "/liquidstaking.staking.v1beta1.MsgDelegate"
 
 impl Delegation {
     pub fn new(validator: &str, amount: u128, denom: &str) -> Self {
@@ -31,7 +33,7 @@ impl Delegation {
                 delegator_address,
                 validator_address: self.validator.clone(),
             },
-            "/liquidstaking.staking.v1beta1.MsgDelegate".to_string(),
+            format!("/{}.staking.v1beta1.MsgDelegate", STAKING_MODULE),
         )
     }
 }
@@ -63,7 +65,7 @@ impl Undelegation {
                 delegator_address,
                 validator_address: self.validator.clone(),
             },
-            "/liquidstaking.staking.v1beta1.MsgUndelegate".to_string(),
+            format!("/{}.staking.v1beta1.MsgUndelegate", STAKING_MODULE),
         )
     }
 }
@@ -98,7 +100,7 @@ impl Redelegation {
                 validator_src_address: self.src.clone(),
                 validator_dst_address: self.dst.clone(),
             },
-            "/liquidstaking.staking.v1beta1.MsgBeginRedelegate".to_string(),
+            format!("/{}.staking.v1beta1.MsgBeginRedelegate", STAKING_MODULE),
         )
     }
 }
@@ -122,7 +124,10 @@ impl RewardWithdrawal {
                 delegator_address,
                 validator_address: self.validator.clone(),
             },
-            "/liquidstaking.distribution.v1beta1.MsgWithdrawDelegatorReward".to_string(),
+            format!(
+                "/{}.distribution.v1beta1.MsgWithdrawDelegatorReward",
+                STAKING_MODULE
+            ),
         )
     }
 }
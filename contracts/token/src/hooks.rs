@@ -0,0 +1,58 @@
+//! With the `transfer-hooks` feature, every change in balances is reported to the minter, i.e. Steak
+//! Hub, which forwards it to the contracts registered as balance hooks. This lets protocols keep
+//! boost or referral books in sync without polling balances.
+
+use cosmwasm_std::{to_binary, Addr, Response, Storage, WasmMsg};
+use cw20_base::msg::ExecuteMsg;
+use cw20_base::state::TOKEN_INFO;
+use cw20_base::ContractError;
+use pfc_steak::hub::{BalanceChange, ExecuteMsg as HubExecuteMsg};
+
+/// Append the message reporting `change` to the minter, if the executed message moved balances
+pub fn notify_minter(
+    storage: &dyn Storage,
+    response: Response,
+    change: Option<BalanceChange>,
+) -> Result<Response, ContractError> {
+    let minter = TOKEN_INFO.load(storage)?.mint.map(|m| m.minter);
+    match (change, minter) {
+        (Some(change), Some(minter)) => Ok(response.add_message(WasmMsg::Execute {
+            contract_addr: minter.into(),
+            msg: to_binary(&HubExecuteMsg::BalanceChanged(change))?,
+            funds: vec![],
+        })),
+        _ => Ok(response),
+    }
+}
+
+/// The change in balances `msg` makes when executed by `sender`, if any
+pub fn balance_change(sender: &Addr, msg: &ExecuteMsg) -> Option<BalanceChange> {
+    let (from, to, amount) = match msg {
+        ExecuteMsg::Transfer { recipient, amount } => {
+            (Some(sender.to_string()), Some(recipient.clone()), amount)
+        }
+        ExecuteMsg::Send {
+            contract, amount, ..
+        } => (Some(sender.to_string()), Some(contract.clone()), amount),
+        ExecuteMsg::TransferFrom {
+            owner,
+            recipient,
+            amount,
+        } => (Some(owner.clone()), Some(recipient.clone()), amount),
+        ExecuteMsg::SendFrom {
+            owner,
+            contract,
+            amount,
+            ..
+        } => (Some(owner.clone()), Some(contract.clone()), amount),
+        ExecuteMsg::Mint { recipient, amount } => (None, Some(recipient.clone()), amount),
+        ExecuteMsg::Burn { amount } => (Some(sender.to_string()), None, amount),
+        _ => return None,
+    };
+
+    Some(BalanceChange {
+        from,
+        to,
+        amount: *amount,
+    })
+}
@@ -1,6 +1,7 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    entry_point, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
-    Storage,
+    Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Storage,
 };
 use cw20_base::contract::{
     execute as cw20_execute, instantiate as cw20_instantiate, query as cw20_query,
@@ -9,6 +10,9 @@ use cw20_base::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
 use cw20_base::state::{MinterData, TOKEN_INFO};
 use cw20_base::ContractError;
 
+#[cfg(feature = "transfer-hooks")]
+mod hooks;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -21,7 +25,7 @@ pub fn instantiate(
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
@@ -36,7 +40,15 @@ pub fn execute(
         _ => (),
     }
 
-    cw20_execute(deps, env, info, msg)
+    #[cfg(feature = "transfer-hooks")]
+    let change = hooks::balance_change(&info.sender, &msg);
+
+    let response = cw20_execute(deps.branch(), env, info, msg)?;
+
+    #[cfg(feature = "transfer-hooks")]
+    let response = hooks::notify_minter(deps.storage, response, change)?;
+
+    Ok(response)
 }
 
 fn assert_minter(storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
@@ -144,6 +156,77 @@ mod tests {
         assert_eq!(token_info.total_supply, Uint128::new(100));
     }
 
+    #[cfg(feature = "transfer-hooks")]
+    #[test]
+    fn notifying_minter() {
+        use cosmwasm_std::{to_binary, CosmosMsg, SubMsg, WasmMsg};
+        use pfc_steak::hub::{BalanceChange, ExecuteMsg as HubExecuteMsg};
+
+        let mut deps = setup_test();
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            ExecuteMsg::Transfer {
+                recipient: "bob".to_string(),
+                amount: Uint128::new(40),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "steak_hub".to_string(),
+                msg: to_binary(&HubExecuteMsg::BalanceChanged(BalanceChange {
+                    from: Some("alice".to_string()),
+                    to: Some("bob".to_string()),
+                    amount: Uint128::new(40),
+                }))
+                .unwrap(),
+                funds: vec![],
+            }))]
+        );
+
+        // Burning is reported without a recipient
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("steak_hub", &[]),
+            ExecuteMsg::Burn {
+                amount: Uint128::new(100),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "steak_hub".to_string(),
+                msg: to_binary(&HubExecuteMsg::BalanceChanged(BalanceChange {
+                    from: Some("steak_hub".to_string()),
+                    to: None,
+                    amount: Uint128::new(100),
+                }))
+                .unwrap(),
+                funds: vec![],
+            }))]
+        );
+
+        // Messages that don't move balances are not reported
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            ExecuteMsg::IncreaseAllowance {
+                spender: "bob".to_string(),
+                amount: Uint128::new(10),
+                expires: None,
+            },
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+    }
+
     #[test]
     fn disabling_burn_from() {
         let mut deps = setup_test();
@@ -0,0 +1,91 @@
+use cosmwasm_std::{
+    entry_point, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+};
+use cw2::set_contract_version;
+
+use pfc_steak::zap::{CallbackMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
+
+use crate::error::ContractError;
+use crate::{execute, queries};
+
+/// Contract name that is used for migration.
+pub const CONTRACT_NAME: &str = "steak-zap";
+/// Contract version that is used for migration.
+pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[entry_point]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    execute::instantiate(deps, msg)
+}
+
+#[entry_point]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let api = deps.api;
+    match msg {
+        ExecuteMsg::Zap {
+            receiver,
+            min_usteak,
+            max_spread,
+        } => execute::zap(
+            deps,
+            env,
+            receiver
+                .map(|s| api.addr_validate(&s))
+                .transpose()?
+                .unwrap_or_else(|| info.sender.clone()),
+            min_usteak,
+            max_spread,
+            info.funds,
+        ),
+        ExecuteMsg::SetRoute { denom, pair } => execute::set_route(
+            deps,
+            info.sender,
+            denom,
+            pair.map(|s| api.addr_validate(&s)).transpose()?,
+        ),
+        ExecuteMsg::Callback(callback_msg) => callback(deps, env, info, callback_msg),
+    }
+}
+
+fn callback(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    callback_msg: CallbackMsg,
+) -> Result<Response, ContractError> {
+    if env.contract.address != info.sender {
+        return Err(ContractError::CallbackOnly {});
+    }
+
+    match callback_msg {
+        CallbackMsg::Bond {
+            receiver,
+            min_usteak,
+            native_before,
+        } => execute::bond(deps, env, receiver, min_usteak, native_before),
+        CallbackMsg::Forward {
+            receiver,
+            min_usteak,
+            usteak_before,
+        } => execute::forward(deps, env, receiver, min_usteak, usteak_before),
+    }
+}
+
+#[entry_point]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&queries::config(deps)?),
+        QueryMsg::Routes {} => to_binary(&queries::routes(deps)?),
+    }
+}
@@ -0,0 +1,32 @@
+use cosmwasm_std::{StdError, Uint128};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("unauthorized: sender is not owner")]
+    Unauthorized {},
+
+    #[error("callbacks can only be invoked by the contract itself")]
+    CallbackOnly {},
+
+    #[error("must deposit exactly one coin; received {count}")]
+    InvalidFundsCount { count: usize },
+
+    #[error("deposit amount must be non-zero")]
+    ZeroDeposit {},
+
+    #[error("{denom} has no route to the Native Token")]
+    NoRoute { denom: String },
+
+    #[error("the swap returned no Native Token to bond")]
+    NothingToBond {},
+
+    #[error("minted {minted} usteak, less than the minimum {min_usteak}")]
+    InsufficientSteak {
+        minted: Uint128,
+        min_usteak: Uint128,
+    },
+}
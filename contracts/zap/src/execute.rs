@@ -0,0 +1,208 @@
+use cosmwasm_std::{
+    to_binary, Addr, Coin, CosmosMsg, Decimal, DepsMut, Env, Event, Response, Uint128, WasmMsg,
+};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
+
+use pfc_steak::hub::{ConfigResponse as HubConfigResponse, ExecuteMsg as HubExecuteMsg};
+use pfc_steak::pair::{Asset, AssetInfo, PairExecuteMsg};
+use pfc_steak::querier::HubQuerier;
+use pfc_steak::zap::{CallbackMsg, InstantiateMsg};
+
+use crate::error::ContractError;
+use crate::state::State;
+
+pub fn instantiate(deps: DepsMut, msg: InstantiateMsg) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let hub = deps.api.addr_validate(&msg.hub)?;
+    let hub_config: HubConfigResponse = HubQuerier::new(deps.querier, hub.as_str()).config()?;
+
+    state
+        .owner
+        .save(deps.storage, &deps.api.addr_validate(&msg.owner)?)?;
+    state.hub.save(deps.storage, &hub)?;
+    state.steak_token.save(
+        deps.storage,
+        &deps.api.addr_validate(&hub_config.steak_token)?,
+    )?;
+    state.denom.save(deps.storage, &hub_config.denom)?;
+
+    Ok(Response::new())
+}
+
+pub fn set_route(
+    deps: DepsMut,
+    sender: Addr,
+    denom: String,
+    pair: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    if sender != state.owner.load(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    match &pair {
+        Some(pair) => state.routes.save(deps.storage, &denom, pair)?,
+        None => state.routes.remove(deps.storage, &denom),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "steakzap/set_route")
+        .add_attribute("denom", denom)
+        .add_attribute("pair", pair.map(String::from).unwrap_or_default()))
+}
+
+/// Swap the deposit into the Native Token unless it already is, then bond whatever the swap returned.
+/// The amounts are measured as balance differences, so that the zap works with any pair
+pub fn zap(
+    deps: DepsMut,
+    env: Env,
+    receiver: Addr,
+    min_usteak: Option<Uint128>,
+    max_spread: Option<Decimal>,
+    funds: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    if funds.len() != 1 {
+        return Err(ContractError::InvalidFundsCount { count: funds.len() });
+    }
+    let deposit = funds[0].clone();
+    if deposit.amount.is_zero() {
+        return Err(ContractError::ZeroDeposit {});
+    }
+
+    let denom = state.denom.load(deps.storage)?;
+    let native_balance = deps
+        .querier
+        .query_balance(&env.contract.address, &denom)?
+        .amount;
+
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let native_before = if deposit.denom == denom {
+        native_balance - deposit.amount
+    } else {
+        let pair = state
+            .routes
+            .may_load(deps.storage, &deposit.denom)?
+            .ok_or_else(|| ContractError::NoRoute {
+                denom: deposit.denom.clone(),
+            })?;
+        msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: pair.into(),
+            msg: to_binary(&PairExecuteMsg::Swap {
+                offer_asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: deposit.denom.clone(),
+                    },
+                    amount: deposit.amount,
+                },
+                belief_price: None,
+                max_spread,
+                to: None,
+            })?,
+            funds: vec![deposit.clone()],
+        }));
+        native_balance
+    };
+    msgs.push(
+        CallbackMsg::Bond {
+            receiver: receiver.clone(),
+            min_usteak,
+            native_before,
+        }
+        .into_cosmos_msg(&env.contract.address)?,
+    );
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("action", "steakzap/zap")
+        .add_attribute("receiver", receiver)
+        .add_attribute("deposit", deposit.to_string()))
+}
+
+pub fn bond(
+    deps: DepsMut,
+    env: Env,
+    receiver: Addr,
+    min_usteak: Option<Uint128>,
+    native_before: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let denom = state.denom.load(deps.storage)?;
+    let native_to_bond = deps
+        .querier
+        .query_balance(&env.contract.address, &denom)?
+        .amount
+        .saturating_sub(native_before);
+    if native_to_bond.is_zero() {
+        return Err(ContractError::NothingToBond {});
+    }
+
+    let hub = state.hub.load(deps.storage)?;
+    let bond_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: hub.into(),
+        msg: to_binary(&HubExecuteMsg::Bond { receiver: None })?,
+        funds: vec![Coin::new(native_to_bond.u128(), &denom)],
+    });
+    let usteak_before = query_usteak_balance(&deps, &env)?;
+    let forward_msg = CallbackMsg::Forward {
+        receiver,
+        min_usteak,
+        usteak_before,
+    }
+    .into_cosmos_msg(&env.contract.address)?;
+
+    Ok(Response::new()
+        .add_message(bond_msg)
+        .add_message(forward_msg)
+        .add_attribute("action", "steakzap/bond")
+        .add_attribute("native_bonded", native_to_bond))
+}
+
+pub fn forward(
+    deps: DepsMut,
+    env: Env,
+    receiver: Addr,
+    min_usteak: Option<Uint128>,
+    usteak_before: Uint128,
+) -> Result<Response, ContractError> {
+    let state = State::default();
+
+    let minted = query_usteak_balance(&deps, &env)?.saturating_sub(usteak_before);
+    if let Some(min_usteak) = min_usteak {
+        if minted < min_usteak {
+            return Err(ContractError::InsufficientSteak { minted, min_usteak });
+        }
+    }
+
+    let transfer_msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: state.steak_token.load(deps.storage)?.into(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: receiver.to_string(),
+            amount: minted,
+        })?,
+        funds: vec![],
+    });
+
+    let event = Event::new("steakzap/zapped")
+        .add_attribute("receiver", receiver)
+        .add_attribute("usteak_minted", minted);
+
+    Ok(Response::new()
+        .add_message(transfer_msg)
+        .add_event(event)
+        .add_attribute("action", "steakzap/forward"))
+}
+
+fn query_usteak_balance(deps: &DepsMut, env: &Env) -> Result<Uint128, ContractError> {
+    let steak_token = State::default().steak_token.load(deps.storage)?;
+    let response: BalanceResponse = deps.querier.query_wasm_smart(
+        steak_token,
+        &Cw20QueryMsg::Balance {
+            address: env.contract.address.to_string(),
+        },
+    )?;
+    Ok(response.balance)
+}
@@ -0,0 +1,26 @@
+use cosmwasm_std::{Deps, Order, StdResult};
+
+use pfc_steak::zap::{ConfigResponse, Route};
+
+use crate::state::State;
+
+pub fn config(deps: Deps) -> StdResult<ConfigResponse> {
+    let state = State::default();
+
+    Ok(ConfigResponse {
+        owner: state.owner.load(deps.storage)?.into(),
+        hub: state.hub.load(deps.storage)?.into(),
+        steak_token: state.steak_token.load(deps.storage)?.into(),
+        denom: state.denom.load(deps.storage)?,
+    })
+}
+
+pub fn routes(deps: Deps) -> StdResult<Vec<Route>> {
+    let state = State::default();
+
+    state
+        .routes
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, pair)| Route { denom, pair }))
+        .collect()
+}
@@ -0,0 +1,27 @@
+use cosmwasm_std::Addr;
+use cw_storage_plus::{Item, Map};
+
+pub(crate) struct State<'a> {
+    /// Account who can configure the swap routes
+    pub owner: Item<'a, Addr>,
+    /// Address of the hub deposits are bonded to
+    pub hub: Item<'a, Addr>,
+    /// Address of the hub's Steak token
+    pub steak_token: Item<'a, Addr>,
+    /// The hub's Native Token
+    pub denom: Item<'a, String>,
+    /// Pair each accepted denom is swapped through
+    pub routes: Map<'a, &'a str, Addr>,
+}
+
+impl Default for State<'static> {
+    fn default() -> Self {
+        Self {
+            owner: Item::new("owner"),
+            hub: Item::new("hub"),
+            steak_token: Item::new("steak_token"),
+            denom: Item::new("denom"),
+            routes: Map::new("routes"),
+        }
+    }
+}
@@ -0,0 +1,395 @@
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Addr, Coin, ContractResult, CosmosMsg, Decimal, Empty, Env,
+    MemoryStorage, OwnedDeps, SystemError, SystemResult, Uint128, Uint64, WasmMsg, WasmQuery,
+};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
+
+use pfc_steak::hub::{
+    ConfigResponse as HubConfigResponse, ExecuteMsg as HubExecuteMsg, MiningConfig, ProofHash,
+    QueryMsg as HubQueryMsg,
+};
+use pfc_steak::pair::{Asset, AssetInfo, PairExecuteMsg};
+use pfc_steak::zap::{CallbackMsg, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, Route};
+
+use crate::contract::{execute, instantiate, query};
+use crate::error::ContractError;
+
+fn hub_config() -> HubConfigResponse {
+    HubConfigResponse {
+        owner: "larry".to_string(),
+        new_owner: None,
+        steak_token: "steak_token".to_string(),
+        epoch_period: 259200,
+        unbond_period: 1814400,
+        denom: "uxyz".to_string(),
+        fee_type: "Wallet".to_string(),
+        fee_account: "fee_account".to_string(),
+        fee_rate: Decimal::zero(),
+        max_fee_rate: Decimal::zero(),
+        validators: vec!["alice".to_string()],
+        mining: MiningConfig {
+            entropy: String::new(),
+            entropy_draft: String::new(),
+            difficulty: Uint64::zero(),
+            proof_hash: ProofHash::Sha256,
+            target_duration_floor: 0,
+            target_duration_ceiling: 0,
+            total_mining_power: Uint128::zero(),
+            miner_fee_share: Decimal::zero(),
+            miner_fee_share_bps: 0,
+            miner: None,
+        },
+        coordinator: None,
+        max_msgs_per_tx: None,
+        token_admin: None,
+    }
+}
+
+/// Answer the hub's config query, and report `usteak_balance` as the zap's Steak balance
+fn set_usteak_balance(querier: &mut MockQuerier, usteak_balance: u128) {
+    querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, msg } => match contract_addr.as_str() {
+            "hub" => match from_slice(msg).unwrap() {
+                HubQueryMsg::Config {} => {
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&hub_config()).unwrap()))
+                }
+                _ => panic!("unexpected hub query"),
+            },
+            "steak_token" => match from_slice(msg).unwrap() {
+                Cw20QueryMsg::Balance { .. } => SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&BalanceResponse {
+                        balance: Uint128::new(usteak_balance),
+                    })
+                    .unwrap(),
+                )),
+                _ => panic!("unexpected token query"),
+            },
+            _ => SystemResult::Err(SystemError::NoSuchContract {
+                addr: contract_addr.clone(),
+            }),
+        },
+        _ => panic!("unsupported wasm query"),
+    });
+}
+
+fn setup_test() -> OwnedDeps<MemoryStorage, MockApi, MockQuerier, Empty> {
+    let mut deps = mock_dependencies();
+    set_usteak_balance(&mut deps.querier, 0);
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("deployer", &[]),
+        InstantiateMsg {
+            owner: "larry".to_string(),
+            hub: "hub".to_string(),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::SetRoute {
+            denom: "uusd".to_string(),
+            pair: Some("pair".to_string()),
+        },
+    )
+    .unwrap();
+
+    deps
+}
+
+fn callback(
+    deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier, Empty>,
+    env: &Env,
+    msg: CallbackMsg,
+) -> Result<cosmwasm_std::Response, ContractError> {
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(env.contract.address.as_str(), &[]),
+        ExecuteMsg::Callback(msg),
+    )
+}
+
+#[test]
+fn proper_instantiation() {
+    let deps = setup_test();
+
+    let res: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        ConfigResponse {
+            owner: "larry".to_string(),
+            hub: "hub".to_string(),
+            steak_token: "steak_token".to_string(),
+            denom: "uxyz".to_string(),
+        }
+    );
+}
+
+#[test]
+fn setting_routes() {
+    let mut deps = setup_test();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("jake", &[]),
+        ExecuteMsg::SetRoute {
+            denom: "uluna".to_string(),
+            pair: Some("pair_2".to_string()),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::SetRoute {
+            denom: "uluna".to_string(),
+            pair: Some("pair_2".to_string()),
+        },
+    )
+    .unwrap();
+
+    let res: Vec<Route> =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Routes {}).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        vec![
+            Route {
+                denom: "uluna".to_string(),
+                pair: Addr::unchecked("pair_2"),
+            },
+            Route {
+                denom: "uusd".to_string(),
+                pair: Addr::unchecked("pair"),
+            },
+        ]
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("larry", &[]),
+        ExecuteMsg::SetRoute {
+            denom: "uusd".to_string(),
+            pair: None,
+        },
+    )
+    .unwrap();
+
+    let res: Vec<Route> =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Routes {}).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        vec![Route {
+            denom: "uluna".to_string(),
+            pair: Addr::unchecked("pair_2"),
+        }]
+    );
+}
+
+#[test]
+fn zapping_swappable_coins() {
+    let mut deps = setup_test();
+    let env = mock_env();
+
+    // The zap already holds 50 uxyz, which must not be bonded on the depositor's behalf
+    deps.querier
+        .update_balance(env.contract.address.clone(), vec![Coin::new(50, "uxyz")]);
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user_1", &[Coin::new(100, "uluna")]),
+        ExecuteMsg::Zap {
+            receiver: None,
+            min_usteak: None,
+            max_spread: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NoRoute {
+            denom: "uluna".to_string()
+        }
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user_1", &[Coin::new(100, "uusd"), Coin::new(100, "uxyz")]),
+        ExecuteMsg::Zap {
+            receiver: None,
+            min_usteak: None,
+            max_spread: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidFundsCount { count: 2 });
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user_1", &[Coin::new(100, "uusd")]),
+        ExecuteMsg::Zap {
+            receiver: Some("user_2".to_string()),
+            min_usteak: Some(Uint128::new(80)),
+            max_spread: Some(Decimal::percent(1)),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "pair".to_string(),
+            msg: to_binary(&PairExecuteMsg::Swap {
+                offer_asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string()
+                    },
+                    amount: Uint128::new(100),
+                },
+                belief_price: None,
+                max_spread: Some(Decimal::percent(1)),
+                to: None,
+            })
+            .unwrap(),
+            funds: vec![Coin::new(100, "uusd")],
+        })
+    );
+    let bond_callback = CallbackMsg::Bond {
+        receiver: Addr::unchecked("user_2"),
+        min_usteak: Some(Uint128::new(80)),
+        native_before: Uint128::new(50),
+    };
+    assert_eq!(
+        res.messages[1].msg,
+        bond_callback
+            .into_cosmos_msg(&env.contract.address)
+            .unwrap()
+    );
+
+    // Callbacks can't be invoked by anyone else
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user_1", &[]),
+        ExecuteMsg::Callback(bond_callback.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::CallbackOnly {});
+
+    // The swap returned nothing
+    let err = callback(&mut deps, &env, bond_callback.clone()).unwrap_err();
+    assert_eq!(err, ContractError::NothingToBond {});
+
+    // The swap returned 90 uxyz, and the zap holds 1000 usteak from elsewhere
+    deps.querier
+        .update_balance(env.contract.address.clone(), vec![Coin::new(140, "uxyz")]);
+    set_usteak_balance(&mut deps.querier, 1000);
+
+    let res = callback(&mut deps, &env, bond_callback).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "hub".to_string(),
+            msg: to_binary(&HubExecuteMsg::Bond { receiver: None }).unwrap(),
+            funds: vec![Coin::new(90, "uxyz")],
+        })
+    );
+    let forward_callback = CallbackMsg::Forward {
+        receiver: Addr::unchecked("user_2"),
+        min_usteak: Some(Uint128::new(80)),
+        usteak_before: Uint128::new(1000),
+    };
+    assert_eq!(
+        res.messages[1].msg,
+        forward_callback
+            .into_cosmos_msg(&env.contract.address)
+            .unwrap()
+    );
+
+    // Fewer than the minimum were minted
+    set_usteak_balance(&mut deps.querier, 1075);
+    let err = callback(&mut deps, &env, forward_callback.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InsufficientSteak {
+            minted: Uint128::new(75),
+            min_usteak: Uint128::new(80),
+        }
+    );
+
+    set_usteak_balance(&mut deps.querier, 1085);
+    let res = callback(&mut deps, &env, forward_callback).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "steak_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "user_2".to_string(),
+                amount: Uint128::new(85),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+}
+
+#[test]
+fn zapping_native_coins() {
+    let mut deps = setup_test();
+    let env = mock_env();
+
+    // The deposit has already been credited to the zap's balance
+    deps.querier
+        .update_balance(env.contract.address.clone(), vec![Coin::new(150, "uxyz")]);
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user_1", &[Coin::new(0, "uxyz")]),
+        ExecuteMsg::Zap {
+            receiver: None,
+            min_usteak: None,
+            max_spread: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ZeroDeposit {});
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("user_1", &[Coin::new(100, "uxyz")]),
+        ExecuteMsg::Zap {
+            receiver: None,
+            min_usteak: None,
+            max_spread: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.messages[0].msg,
+        CallbackMsg::Bond {
+            receiver: Addr::unchecked("user_1"),
+            min_usteak: None,
+            native_before: Uint128::new(50),
+        }
+        .into_cosmos_msg(&env.contract.address)
+        .unwrap()
+    );
+}
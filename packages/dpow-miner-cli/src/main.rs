@@ -1,19 +1,12 @@
 use anyhow::{Context, Ok, Result};
-use async_std::io::ReadExt;
 use futures::future;
 use pfc_steak::hub::MinerParamsResponse;
 use rand::distributions::Alphanumeric;
 use rand::Rng;
-use rayon::prelude::*;
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
-use std::error::Error;
-use std::hash;
 use std::io::Write;
-use std::ops::DerefMut;
 use std::process::{Command, Stdio};
 use std::sync::{Arc, Mutex};
-use tokio;
 pub struct State {
     pub miner_params: MinerParamsResponse,
     pub tx_in_flight: bool,
@@ -28,6 +21,7 @@ async fn main() {
             entropy: "0x0000000000000000000000000000000000000000000000000000000000000000"
                 .to_string(),
             difficulty: 0_u64.into(),
+            proof_hash: Default::default(),
         },
         tx_in_flight: false,
         miner_params_loaded: false,
@@ -67,7 +61,7 @@ async fn main() {
         tokio::spawn(async move {
             let mut last_nonce = i * 100_000_000_000;
             while 1 < 2 {
-                let proof = mine(last_nonce.clone() + 1, state.clone()).unwrap();
+                let proof = mine(last_nonce + 1, state.clone()).unwrap();
                 last_nonce = proof.nonce;
                 if proof.success && !state.lock().unwrap().tx_in_flight {
                     state.lock().unwrap().tx_in_flight = true;
@@ -98,10 +92,10 @@ pub fn poll_miner_params() -> Result<MinerParamsResponse> {
         .arg("contract-state")
         .arg("smart")
         .arg(get_contract_address())
-        .arg(format!(
-            "{}",
-            serde_json::to_string(&miner_params_query).context("serializing miner params query")?
-        ))
+        .arg(
+            &serde_json::to_string(&miner_params_query)
+                .context("serializing miner params query")?,
+        )
         .arg("--node")
         .arg(get_rpc_url())
         .arg("--chain-id")
@@ -172,7 +166,7 @@ pub fn submit_proof(proof: MinedProof) -> Result<()> {
         .arg("text")
         .output()
         .expect("failed to execute process");
-    println!("status: {}", joed_cosmwasm_tx_result.status.to_string());
+    println!("status: {}", joed_cosmwasm_tx_result.status);
     println!(
         "stdout: {}",
         String::from_utf8_lossy(&joed_cosmwasm_tx_result.stdout)
@@ -240,7 +234,7 @@ pub fn update_entropy() -> Result<String, anyhow::Error> {
         joed_cosmwasm_tx_result
     };
     println!("updated entropy");
-    println!("status: {}", joed_cosmwasm_tx_result.status.to_string());
+    println!("status: {}", joed_cosmwasm_tx_result.status);
     println!(
         "stdout: {}",
         String::from_utf8_lossy(&joed_cosmwasm_tx_result.stdout)
@@ -0,0 +1,68 @@
+use std::env;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+/// Miner settings, read from the environment
+pub struct Config {
+    /// LCD endpoint the hub's mining state is read from
+    pub lcd_url: String,
+    /// RPC endpoint proofs are broadcast to
+    pub rpc_url: String,
+    pub chain_id: String,
+    /// Address of the Steak hub
+    pub contract_address: String,
+    /// Address proofs are mined for; fees are paid to it
+    pub miner_address: String,
+    /// Keyring entry the CLI signs with; defaults to `miner_address`
+    pub key: String,
+    /// Validator credited with the mining power
+    pub validator_address: String,
+    /// Chain CLI used to sign and broadcast
+    pub binary: String,
+    /// Gas limit, or `auto` to simulate
+    pub gas: String,
+    pub gas_adjustment: String,
+    pub gas_prices: String,
+    pub threads: usize,
+    /// How often the hub is polled for new entropy while grinding
+    pub poll_interval: Duration,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self> {
+        let miner_address = required("MINER_ADDRESS")?;
+        Ok(Config {
+            lcd_url: required("LCD_URL")?.trim_end_matches('/').to_string(),
+            rpc_url: required("RPC_URL")?,
+            chain_id: optional("CHAIN_ID", "joe-1"),
+            contract_address: required("CONTRACT_ADDRESS")?,
+            key: optional("MINER_KEY", &miner_address),
+            miner_address,
+            validator_address: required("VALIDATOR_ADDRESS")?,
+            binary: optional("CLI_BINARY", "joed"),
+            gas: optional("GAS", "auto"),
+            gas_adjustment: optional("GAS_ADJUSTMENT", "1.5"),
+            gas_prices: optional("GAS_PRICES", "0.025ujoe"),
+            threads: match env::var("THREAD_COUNT") {
+                Ok(threads) => threads.parse().context("parsing THREAD_COUNT")?,
+                Err(_) => std::thread::available_parallelism()
+                    .map(usize::from)
+                    .unwrap_or(1),
+            },
+            poll_interval: Duration::from_secs(
+                optional("POLL_SECONDS", "6")
+                    .parse()
+                    .context("parsing POLL_SECONDS")?,
+            ),
+        })
+    }
+}
+
+fn required(name: &str) -> Result<String> {
+    env::var(name).with_context(|| format!("{} must be set", name))
+}
+
+fn optional(name: &str, default: &str) -> String {
+    env::var(name).unwrap_or_else(|_| default.to_string())
+}
@@ -0,0 +1,68 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use pfc_steak::hub::QueryMsg;
+
+#[derive(Deserialize)]
+struct SmartQueryResponse<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+struct LatestBlockResponse {
+    block: Block,
+}
+
+#[derive(Deserialize)]
+struct Block {
+    header: Header,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    height: String,
+}
+
+/// Read-only access to the hub through a node's LCD
+pub struct Lcd {
+    url: String,
+    contract_address: String,
+}
+
+impl Lcd {
+    pub fn new(url: &str, contract_address: &str) -> Self {
+        Lcd {
+            url: url.to_string(),
+            contract_address: contract_address.to_string(),
+        }
+    }
+
+    pub fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> Result<T> {
+        let msg = base64::encode_config(serde_json::to_vec(msg)?, base64::URL_SAFE);
+        let url = format!(
+            "{}/cosmwasm/wasm/v1/contract/{}/smart/{}",
+            self.url, self.contract_address, msg
+        );
+        let res: SmartQueryResponse<T> = ureq::get(&url)
+            .call()
+            .with_context(|| format!("querying {}", url))?
+            .into_json()
+            .context("parsing smart query response")?;
+        Ok(res.data)
+    }
+
+    pub fn block_height(&self) -> Result<u64> {
+        let url = format!("{}/cosmos/base/tendermint/v1beta1/blocks/latest", self.url);
+        let res: LatestBlockResponse = ureq::get(&url)
+            .call()
+            .with_context(|| format!("querying {}", url))?
+            .into_json()
+            .context("parsing latest block response")?;
+        res.block
+            .header
+            .height
+            .parse()
+            .context("parsing block height")
+    }
+}
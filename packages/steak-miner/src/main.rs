@@ -0,0 +1,120 @@
+//! Reference miner for the Steak hub's DPOW mechanism.
+//!
+//! Polls the hub's entropy and difficulty over LCD, grinds nonces across threads, checks a
+//! candidate with the hub's `VerifyProof` query and broadcasts it through the chain CLI. All
+//! settings are read from the environment; see `config.rs`.
+
+mod config;
+mod lcd;
+mod miner;
+mod submit;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use anyhow::Result;
+
+use pfc_steak::hub::{MinerParamsResponse, MinerStatusResponse, QueryMsg, VerifyProofResponse};
+
+use crate::config::Config;
+use crate::lcd::Lcd;
+use crate::miner::{grind, Work};
+
+fn main() -> Result<()> {
+    let config = Config::from_env()?;
+    let lcd = Lcd::new(&config.lcd_url, &config.contract_address);
+    println!(
+        "mining for {} on {} threads",
+        config.miner_address, config.threads
+    );
+
+    let mut start_nonce = 0;
+    loop {
+        if let Err(err) = wait_for_cooldown(&config, &lcd) {
+            println!("error checking cooldown: {:#}", err);
+            thread::sleep(config.poll_interval);
+            continue;
+        }
+        let work = match current_work(&config, &lcd) {
+            Ok(work) => work,
+            Err(err) => {
+                println!("error loading miner params: {:#}", err);
+                thread::sleep(config.poll_interval);
+                continue;
+            }
+        };
+        println!("difficulty {}, entropy {}", work.difficulty, work.entropy);
+
+        let nonce = match mine_until_stale(&config, &lcd, &work, start_nonce) {
+            Some(nonce) => nonce,
+            None => {
+                println!("entropy changed, restarting");
+                start_nonce = 0;
+                continue;
+            }
+        };
+        start_nonce = nonce + 1;
+
+        // the entropy may have moved on between the last poll and now
+        let check: VerifyProofResponse = match lcd.query(&QueryMsg::VerifyProof {
+            miner: config.miner_address.clone(),
+            nonce: nonce.into(),
+        }) {
+            Ok(check) => check,
+            Err(err) => {
+                println!("error verifying proof: {:#}", err);
+                continue;
+            }
+        };
+        if !check.valid {
+            println!("nonce {} no longer valid, restarting", nonce);
+            start_nonce = 0;
+            continue;
+        }
+
+        println!("submitting nonce {} (hash {})", nonce, check.hash);
+        match submit::submit_proof(&config, nonce) {
+            Ok(output) => println!("{}", output),
+            Err(err) => println!("{:#}", err),
+        }
+    }
+}
+
+fn current_work(config: &Config, lcd: &Lcd) -> Result<Work> {
+    let params: MinerParamsResponse = lcd.query(&QueryMsg::MinerParams {})?;
+    Ok(Work {
+        entropy: params.entropy,
+        difficulty: params.difficulty.u64(),
+        proof_hash: params.proof_hash,
+        miner_address: config.miner_address.clone(),
+    })
+}
+
+/// Block until the hub would accept another proof from this miner
+fn wait_for_cooldown(config: &Config, lcd: &Lcd) -> Result<()> {
+    let status: MinerStatusResponse = lcd.query(&QueryMsg::MinerStatus {
+        miner: config.miner_address.clone(),
+    })?;
+    while lcd.block_height()? < status.next_proof_height {
+        thread::sleep(config.poll_interval);
+    }
+    Ok(())
+}
+
+/// Grind `work` until a nonce is found, or the hub's entropy or difficulty changes
+fn mine_until_stale(config: &Config, lcd: &Lcd, work: &Work, start_nonce: u64) -> Option<u64> {
+    let stop = AtomicBool::new(false);
+
+    thread::scope(|s| {
+        let grinder = s.spawn(|| grind(work, config.threads, start_nonce, &stop));
+        while !grinder.is_finished() {
+            thread::sleep(config.poll_interval);
+            match current_work(config, lcd) {
+                Ok(current) if current != *work => stop.store(true, Ordering::Relaxed),
+                Ok(_) => {}
+                Err(err) => println!("error polling miner params: {:#}", err),
+            }
+        }
+        grinder.join().unwrap()
+    })
+}
@@ -0,0 +1,114 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use pfc_steak::hub::ProofHash;
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+/// Nonces each thread claims at a time
+const CHUNK_SIZE: u64 = 10_000;
+
+/// The puzzle set by the hub: a hash of `entropy`, the miner's address and a nonce starting with
+/// `difficulty` zeros
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Work {
+    pub entropy: String,
+    pub difficulty: u64,
+    pub proof_hash: ProofHash,
+    pub miner_address: String,
+}
+
+impl Work {
+    /// Same hash the hub computes when a proof is submitted
+    pub fn hash(&self, nonce: u64) -> String {
+        match self.proof_hash {
+            ProofHash::Sha256 => self.hash_with::<Sha256>(nonce),
+            ProofHash::Keccak256 => self.hash_with::<Keccak256>(nonce),
+        }
+    }
+
+    fn hash_with<D: Digest>(&self, nonce: u64) -> String {
+        let mut hasher = D::new();
+        hasher.update(&self.entropy);
+        hasher.update(&self.miner_address);
+        hasher.update(nonce.to_le_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    pub fn is_solved_by(&self, nonce: u64) -> bool {
+        let hash = self.hash(nonce);
+        hash.len() >= self.difficulty as usize
+            && hash
+                .bytes()
+                .take(self.difficulty as usize)
+                .all(|b| b == b'0')
+    }
+}
+
+/// Search for a nonce solving `work` across `threads` threads, starting from `start_nonce`.
+/// Returns `None` if `stop` is raised before one is found
+pub fn grind(work: &Work, threads: usize, start_nonce: u64, stop: &AtomicBool) -> Option<u64> {
+    let next_nonce = AtomicU64::new(start_nonce);
+    let found = Mutex::new(None);
+
+    thread::scope(|s| {
+        for _ in 0..threads.max(1) {
+            s.spawn(|| {
+                while !stop.load(Ordering::Relaxed) {
+                    let start = next_nonce.fetch_add(CHUNK_SIZE, Ordering::Relaxed);
+                    for nonce in start..start.saturating_add(CHUNK_SIZE) {
+                        if work.is_solved_by(nonce) {
+                            found.lock().unwrap().get_or_insert(nonce);
+                            stop.store(true, Ordering::Relaxed);
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    found.into_inner().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn work(difficulty: u64) -> Work {
+        Work {
+            entropy: "df5c2d1c1e799c13e81ef0d24acdb338e9da760af9afcd1bfbde40d61fed8996".to_string(),
+            difficulty,
+            proof_hash: ProofHash::Sha256,
+            miner_address: "joe1gh9nds8amsy33ewpt97gj4n99436hftz2zl79q".to_string(),
+        }
+    }
+
+    #[test]
+    fn hash_matches_hub() {
+        // proof accepted by the hub at difficulty 5
+        assert!(work(5).hash(121063160).starts_with("00000"));
+        assert!(work(5).is_solved_by(121063160));
+        assert!(!work(6).is_solved_by(121063160));
+
+        let keccak = Work {
+            proof_hash: ProofHash::Keccak256,
+            ..work(5)
+        };
+        assert_eq!(
+            keccak.hash(915378),
+            "00000e8ca669ecea78606bfddd7f7898f1956d3e115116e82ec0707cfc9da0c9"
+        );
+        assert!(!keccak.is_solved_by(121063160));
+    }
+
+    #[test]
+    fn grinding() {
+        let work = work(2);
+        let nonce = grind(&work, 4, 0, &AtomicBool::new(false)).unwrap();
+        assert!(work.is_solved_by(nonce));
+
+        assert_eq!(grind(&work, 4, 0, &AtomicBool::new(true)), None);
+    }
+}
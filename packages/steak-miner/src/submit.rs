@@ -0,0 +1,35 @@
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+use pfc_steak::hub::ExecuteMsg;
+
+use crate::config::Config;
+
+/// Sign and broadcast a proof through the chain CLI, returning its output
+pub fn submit_proof(config: &Config, nonce: u64) -> Result<String> {
+    let msg = serde_json::to_string(&ExecuteMsg::SubmitProof {
+        nonce: nonce.into(),
+        validator: config.validator_address.clone(),
+    })?;
+
+    let output = Command::new(&config.binary)
+        .args(["tx", "wasm", "execute", &config.contract_address, &msg])
+        .args(["--from", &config.key])
+        .args(["--node", &config.rpc_url])
+        .args(["--chain-id", &config.chain_id])
+        .args(["--gas", &config.gas])
+        .args(["--gas-adjustment", &config.gas_adjustment])
+        .args(["--gas-prices", &config.gas_prices])
+        .args(["--broadcast-mode", "sync", "--output", "json", "-y"])
+        .output()
+        .with_context(|| format!("executing {}", config.binary))?;
+
+    if !output.status.success() {
+        bail!(
+            "submitting proof failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,218 @@
+//! The hub and the Steak token wrapped for cw-multi-test.
+//!
+//! The hub delegates through stargate messages, which cw-multi-test can not execute, and reads the
+//! `coin_received` and `instantiate` events emitted by the chain, which cw-multi-test names
+//! differently. The wrappers below translate both ways, so the hub runs unmodified against the
+//! multi-test staking and distribution modules. Stargate queries, on which cw-multi-test panics, are
+//! refused the way chains that do not serve them to contracts refuse them.
+//!
+//! The hub is also instantiated in deterministic mining mode, in which any nonce is accepted as a
+//! proof, so tests can submit proofs without grinding hashes.
+
+use std::str::FromStr;
+
+use cosmos_sdk_proto::cosmos::base::v1beta1::Coin as SdkCoin;
+use cosmos_sdk_proto::cosmos::distribution::v1beta1::MsgWithdrawDelegatorReward;
+use cosmos_sdk_proto::cosmos::staking::v1beta1::{MsgBeginRedelegate, MsgDelegate, MsgUndelegate};
+use cosmwasm_std::{
+    from_slice, Binary, Coin, CosmosMsg, Deps, DepsMut, DistributionMsg, Empty, Env, Event,
+    MessageInfo, Querier, QuerierResult, QuerierWrapper, QueryRequest, Reply, Response, StakingMsg,
+    StdError, StdResult, SubMsgResult, SystemError, SystemResult, Uint128,
+};
+use cw_multi_test::{Contract, ContractWrapper};
+use prost::Message;
+
+use pfc_steak::hub::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, SudoMsg};
+use pfc_steak_hub::contract;
+use pfc_steak_hub::error::ContractError;
+use pfc_steak_hub::test_mining;
+
+/// The Steak hub, with its staking messages and the events it reads translated for cw-multi-test
+pub fn hub_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(execute, instantiate, query)
+            .with_reply(reply)
+            .with_migrate(migrate)
+            .with_sudo(sudo),
+    )
+}
+
+/// The Steak token, i.e. CW20 with burning restricted to the minter
+pub fn token_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        pfc_steak_token::execute,
+        pfc_steak_token::instantiate,
+        pfc_steak_token::query,
+    ))
+}
+
+fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    test_mining::enable(deps.storage)?;
+    Ok(translate_response(contract::instantiate(
+        deps, env, info, msg,
+    )?)?)
+}
+
+fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let querier = StargateUnsupported(deps.querier);
+    let deps = DepsMut {
+        storage: deps.storage,
+        api: deps.api,
+        querier: QuerierWrapper::new(&querier),
+    };
+    Ok(translate_response(contract::execute(
+        deps, env, info, msg,
+    )?)?)
+}
+
+fn reply(deps: DepsMut, env: Env, mut reply: Reply) -> Result<Response, ContractError> {
+    let querier = StargateUnsupported(deps.querier);
+    let deps = DepsMut {
+        storage: deps.storage,
+        api: deps.api,
+        querier: QuerierWrapper::new(&querier),
+    };
+    if let SubMsgResult::Ok(response) = &mut reply.result {
+        response.events = std::mem::take(&mut response.events)
+            .into_iter()
+            .flat_map(translate_event)
+            .collect();
+    }
+    Ok(translate_response(contract::reply(deps, env, reply)?)?)
+}
+
+fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    Ok(translate_response(contract::migrate(deps, env, msg)?)?)
+}
+
+fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    let querier = StargateUnsupported(deps.querier);
+    let deps = DepsMut {
+        storage: deps.storage,
+        api: deps.api,
+        querier: QuerierWrapper::new(&querier),
+    };
+    Ok(translate_response(contract::sudo(deps, env, msg)?)?)
+}
+
+fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    let querier = StargateUnsupported(deps.querier);
+    let deps = Deps {
+        storage: deps.storage,
+        api: deps.api,
+        querier: QuerierWrapper::new(&querier),
+    };
+    contract::query(deps, env, msg)
+}
+
+/// Passes queries through to cw-multi-test, except for stargate ones, which are refused
+struct StargateUnsupported<'a>(QuerierWrapper<'a>);
+
+impl Querier for StargateUnsupported<'_> {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        match from_slice::<QueryRequest<Empty>>(bin_request) {
+            Ok(QueryRequest::Stargate { path, .. }) => {
+                SystemResult::Err(SystemError::UnsupportedRequest { kind: path })
+            }
+            _ => self.0.raw_query(bin_request),
+        }
+    }
+}
+
+fn translate_response(mut response: Response) -> StdResult<Response> {
+    for submsg in response.messages.iter_mut() {
+        if let CosmosMsg::Stargate { type_url, value } = &submsg.msg {
+            submsg.msg = translate_stargate(type_url, value)?;
+        }
+    }
+    Ok(response)
+}
+
+/// The hub encodes staking messages under its chain's own type URLs, so they are matched by the
+/// message name only
+fn translate_stargate(type_url: &str, value: &Binary) -> StdResult<CosmosMsg> {
+    let decode_err = |err: prost::DecodeError| StdError::parse_err(type_url, err);
+    match type_url.rsplit('.').next().unwrap_or_default() {
+        "MsgDelegate" => {
+            let msg = MsgDelegate::decode(value.as_slice()).map_err(decode_err)?;
+            Ok(StakingMsg::Delegate {
+                validator: msg.validator_address,
+                amount: parse_sdk_coin(msg.amount)?,
+            }
+            .into())
+        }
+        "MsgUndelegate" => {
+            let msg = MsgUndelegate::decode(value.as_slice()).map_err(decode_err)?;
+            Ok(StakingMsg::Undelegate {
+                validator: msg.validator_address,
+                amount: parse_sdk_coin(msg.amount)?,
+            }
+            .into())
+        }
+        "MsgBeginRedelegate" => {
+            let msg = MsgBeginRedelegate::decode(value.as_slice()).map_err(decode_err)?;
+            Ok(StakingMsg::Redelegate {
+                src_validator: msg.validator_src_address,
+                dst_validator: msg.validator_dst_address,
+                amount: parse_sdk_coin(msg.amount)?,
+            }
+            .into())
+        }
+        "MsgWithdrawDelegatorReward" => {
+            let msg = MsgWithdrawDelegatorReward::decode(value.as_slice()).map_err(decode_err)?;
+            Ok(DistributionMsg::WithdrawDelegatorReward {
+                validator: msg.validator_address,
+            }
+            .into())
+        }
+        _ => Err(StdError::generic_err(format!(
+            "[harness] unsupported stargate message: {}",
+            type_url
+        ))),
+    }
+}
+
+fn parse_sdk_coin(coin: Option<SdkCoin>) -> StdResult<Coin> {
+    let coin = coin.ok_or_else(|| StdError::generic_err("[harness] missing coin"))?;
+    Ok(Coin {
+        denom: coin.denom,
+        amount: Uint128::from_str(&coin.amount)?,
+    })
+}
+
+/// Add the events the chain would emit alongside the ones cw-multi-test emits
+fn translate_event(event: Event) -> Vec<Event> {
+    let attr = |key: &str| {
+        event
+            .attributes
+            .iter()
+            .find(|attr| attr.key == key)
+            .map(|attr| attr.value.clone())
+    };
+    match event.ty.as_str() {
+        "instantiate" => match attr("_contract_addr") {
+            Some(address) => vec![event.clone().add_attribute("_contract_address", address)],
+            None => vec![event],
+        },
+        "withdraw_delegator_reward" => match (attr("sender"), attr("amount")) {
+            (Some(receiver), Some(amount)) if !amount.is_empty() => vec![
+                event.clone(),
+                Event::new("coin_received")
+                    .add_attribute("receiver", receiver)
+                    .add_attribute("amount", amount),
+            ],
+            _ => vec![event],
+        },
+        _ => vec![event],
+    }
+}
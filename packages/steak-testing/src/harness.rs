@@ -0,0 +1,308 @@
+use cosmwasm_std::testing::{mock_env, MockApi, MockStorage};
+use cosmwasm_std::{Addr, Coin, Decimal, Empty, StdResult, Timestamp, Uint128, Uint64, Validator};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
+use cw_multi_test::{
+    App, AppBuilder, AppResponse, BankKeeper, BankSudo, Executor, FailingModule, StakingInfo,
+    StakingSudo, SudoMsg, WasmKeeper,
+};
+use serde::de::DeserializeOwned;
+
+use pfc_steak::hub::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg, SudoMsg as HubSudoMsg,
+};
+
+use crate::contracts::{hub_contract, token_contract};
+use crate::staking::{SteakDistributionKeeper, SteakStakingKeeper};
+
+/// cw-multi-test app with staking and distribution modules that simulate jailing
+pub type SteakApp = App<
+    BankKeeper,
+    MockApi,
+    MockStorage,
+    FailingModule<Empty, Empty, Empty>,
+    WasmKeeper<Empty, Empty>,
+    SteakStakingKeeper,
+    SteakDistributionKeeper,
+>;
+
+/// Parameters the hub and the mock staking module are set up with
+#[derive(Clone, Debug)]
+pub struct HarnessConfig {
+    pub owner: String,
+    pub denom: String,
+    pub validators: Vec<String>,
+    /// Staking reward rate of the mock staking module
+    pub apr: Decimal,
+    pub epoch_period: u64,
+    pub unbond_period: u64,
+    pub fee_account: String,
+    pub fee_rate: Decimal,
+}
+
+impl Default for HarnessConfig {
+    fn default() -> Self {
+        Self {
+            owner: "owner".to_string(),
+            denom: "ujoe".to_string(),
+            validators: vec![
+                "validator1".to_string(),
+                "validator2".to_string(),
+                "validator3".to_string(),
+            ],
+            apr: Decimal::percent(10),
+            epoch_period: 259_200,    // 3 days
+            unbond_period: 1_814_400, // 21 days
+            fee_account: "fee_account".to_string(),
+            fee_rate: Decimal::percent(10),
+        }
+    }
+}
+
+/// A hub, its Steak token and a staking module with slashing, running in cw-multi-test
+pub struct SteakHarness {
+    pub app: SteakApp,
+    pub config: HarnessConfig,
+    pub hub: Addr,
+    pub token: Addr,
+}
+
+impl Default for SteakHarness {
+    fn default() -> Self {
+        Self::new(HarnessConfig::default())
+    }
+}
+
+impl SteakHarness {
+    pub fn new(config: HarnessConfig) -> Self {
+        let block = mock_env().block;
+        let mut app = AppBuilder::new()
+            .with_staking(SteakStakingKeeper::default())
+            .with_distribution(SteakDistributionKeeper::default())
+            .build(|router, api, storage| {
+                router
+                    .staking
+                    .setup(
+                        storage,
+                        StakingInfo {
+                            bonded_denom: config.denom.clone(),
+                            unbonding_time: config.unbond_period,
+                            apr: config.apr,
+                        },
+                    )
+                    .unwrap();
+                for validator in &config.validators {
+                    router
+                        .staking
+                        .add_validator(
+                            api,
+                            storage,
+                            &block,
+                            Validator {
+                                address: validator.clone(),
+                                commission: Decimal::zero(),
+                                max_commission: Decimal::one(),
+                                max_change_rate: Decimal::one(),
+                            },
+                        )
+                        .unwrap();
+                }
+            });
+
+        let token_code_id = app.store_code(token_contract());
+        let hub_code_id = app.store_code(hub_contract());
+        let owner = Addr::unchecked(&config.owner);
+        let hub = app
+            .instantiate_contract(
+                hub_code_id,
+                owner.clone(),
+                &InstantiateMsg {
+                    cw20_code_id: token_code_id,
+                    owner: config.owner.clone(),
+                    name: "Steak Token".to_string(),
+                    symbol: "STEAK".to_string(),
+                    decimals: 6,
+                    epoch_period: config.epoch_period,
+                    unbond_period: config.unbond_period,
+                    validators: config.validators.clone(),
+                    denom: config.denom.clone(),
+                    fee_account_type: "Wallet".to_string(),
+                    fee_account: config.fee_account.clone(),
+                    fee_amount: config.fee_rate,
+                    max_fee_amount: Decimal::percent(20).max(config.fee_rate),
+                    label: None,
+                    marketing: None,
+                    token_admin: None,
+                    proof_hash: None,
+                },
+                &[],
+                "steak-hub",
+                Some(config.owner.clone()),
+            )
+            .unwrap();
+        let hub_config: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&hub, &QueryMsg::Config {})
+            .unwrap();
+        let token = Addr::unchecked(hub_config.steak_token);
+
+        Self {
+            app,
+            config,
+            hub,
+            token,
+        }
+    }
+
+    /// Mint Native Token to `user`
+    pub fn fund(&mut self, user: &str, amount: u128) {
+        self.app
+            .sudo(SudoMsg::Bank(BankSudo::Mint {
+                to_address: user.to_string(),
+                amount: vec![Coin::new(amount, &self.config.denom)],
+            }))
+            .unwrap();
+    }
+
+    /// Execute a message on the hub as `sender`
+    pub fn execute(
+        &mut self,
+        sender: &str,
+        msg: ExecuteMsg,
+        funds: &[Coin],
+    ) -> anyhow::Result<AppResponse> {
+        self.app
+            .execute_contract(Addr::unchecked(sender), self.hub.clone(), &msg, funds)
+    }
+
+    /// Fund `user` with `amount` of Native Token and bond it
+    pub fn bond(&mut self, user: &str, amount: u128) -> anyhow::Result<AppResponse> {
+        self.fund(user, amount);
+        let funds = [Coin::new(amount, &self.config.denom)];
+        self.execute(user, ExecuteMsg::Bond { receiver: None }, &funds)
+    }
+
+    /// Send `amount` of `user`'s Steak to the hub to be unbonded
+    pub fn queue_unbond(&mut self, user: &str, amount: u128) -> anyhow::Result<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(user),
+            self.token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: self.hub.to_string(),
+                amount: Uint128::new(amount),
+                msg: cosmwasm_std::to_binary(&ReceiveMsg::QueueUnbond {
+                    receiver: None,
+                    min_out: None,
+                    deadline: None,
+                })?,
+            },
+            &[],
+        )
+    }
+
+    /// Withdraw the staking rewards and reinvest them; on chain this is triggered by the hub itself
+    pub fn harvest(&mut self) -> anyhow::Result<AppResponse> {
+        let hub = self.hub.to_string();
+        self.execute(&hub, ExecuteMsg::Harvest {}, &[])
+    }
+
+    /// Submit a proof for `miner` crediting `validator`; any nonce is accepted by the harness' hub,
+    /// which then harvests and pays `miner` its share of the fees
+    pub fn submit_proof(
+        &mut self,
+        miner: &str,
+        nonce: u64,
+        validator: &str,
+    ) -> anyhow::Result<AppResponse> {
+        let msg = ExecuteMsg::SubmitProof {
+            nonce: Uint64::new(nonce),
+            validator: validator.to_string(),
+        };
+        self.execute(miner, msg, &[])
+    }
+
+    /// Run the hub's end-of-block upkeep, as a chain with a clock module would
+    pub fn end_block(&mut self) -> anyhow::Result<AppResponse> {
+        self.app
+            .wasm_sudo(self.hub.clone(), &HubSudoMsg::ClockEndBlock {})
+    }
+
+    /// Slash `percentage` of the stake delegated to `validator`
+    pub fn slash(&mut self, validator: &str, percentage: Decimal) {
+        self.app
+            .sudo(SudoMsg::Staking(StakingSudo::Slash {
+                validator: validator.to_string(),
+                percentage,
+            }))
+            .unwrap();
+    }
+
+    /// Jail `validator`, slashing `percentage` of the stake delegated to it
+    pub fn jail(&mut self, validator: &str, percentage: Decimal) {
+        if !percentage.is_zero() {
+            self.slash(validator, percentage);
+        }
+        self.app
+            .init_modules(|router, _, storage| router.staking.jail(storage, validator))
+            .unwrap();
+    }
+
+    pub fn unjail(&mut self, validator: &str) {
+        self.app
+            .init_modules(|router, _, storage| router.staking.unjail(storage, validator))
+            .unwrap();
+    }
+
+    /// Move the chain `seconds` forward, paying out the undelegations that have matured
+    pub fn advance_time(&mut self, seconds: u64) {
+        self.app.update_block(|block| {
+            block.time = block.time.plus_seconds(seconds);
+            block.height += seconds / 5;
+        });
+        #[allow(deprecated)]
+        self.app
+            .sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {}))
+            .unwrap();
+    }
+
+    pub fn block_time(&self) -> Timestamp {
+        self.app.block_info().time
+    }
+
+    /// Query the hub
+    pub fn query<T: DeserializeOwned>(&self, msg: QueryMsg) -> StdResult<T> {
+        self.app.wrap().query_wasm_smart(&self.hub, &msg)
+    }
+
+    pub fn steak_balance(&self, user: &str) -> Uint128 {
+        let res: BalanceResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &self.token,
+                &Cw20QueryMsg::Balance {
+                    address: user.to_string(),
+                },
+            )
+            .unwrap();
+        res.balance
+    }
+
+    pub fn native_balance(&self, user: &str) -> Uint128 {
+        self.app
+            .wrap()
+            .query_balance(user, &self.config.denom)
+            .unwrap()
+            .amount
+    }
+
+    /// Native Token delegated by the hub to each validator
+    pub fn delegations(&self) -> Vec<(String, Uint128)> {
+        self.app
+            .wrap()
+            .query_all_delegations(&self.hub)
+            .unwrap()
+            .into_iter()
+            .map(|d| (d.validator, d.amount.amount))
+            .collect()
+    }
+}
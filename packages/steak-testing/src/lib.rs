@@ -0,0 +1,20 @@
+//! A cw-multi-test harness running the Steak hub, its token and a staking module with slashing.
+//!
+//! Meant as a dev-dependency for protocols integrating with Steak (routers, vaults, ...) that want
+//! their integration tests to run against the actual hub rather than a mock of it:
+//!
+//! ```ignore
+//! let mut steak = SteakHarness::default();
+//! steak.bond("alice", 1_000_000)?;
+//! steak.advance_time(86_400);
+//! steak.harvest()?;
+//! ```
+
+pub mod contracts;
+pub mod harness;
+pub mod staking;
+
+pub use harness::{HarnessConfig, SteakApp, SteakHarness};
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,210 @@
+//! Staking and distribution modules for cw-multi-test that also simulate jailing.
+//!
+//! A jailed validator keeps its delegations, but leaves the active set: it is no longer returned by
+//! `AllValidators` and it earns nothing. Its delegations report no accumulated rewards, and the
+//! rewards its delegators withdraw while it is jailed are forfeited. Slashing is left to the wrapped
+//! `StakeKeeper`, which also slashes the undelegations still in its unbonding queue.
+
+use anyhow::Result as AnyResult;
+use cosmwasm_std::{
+    from_slice, to_binary, to_vec, Addr, AllValidatorsResponse, Api, Binary, BlockInfo,
+    CustomQuery, DelegationResponse, DistributionMsg, Empty, Event, Querier, StakingMsg,
+    StakingQuery, Storage, Validator,
+};
+use cw_multi_test::{
+    AppResponse, CosmosRouter, Distribution, DistributionKeeper, Module, StakeKeeper, Staking,
+    StakingInfo, StakingSudo,
+};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+
+const JAILED_KEY: &[u8] = b"steak_testing/jailed";
+
+fn jailed_validators(storage: &dyn Storage) -> AnyResult<Vec<String>> {
+    match storage.get(JAILED_KEY) {
+        Some(bytes) => Ok(from_slice(&bytes)?),
+        None => Ok(vec![]),
+    }
+}
+
+pub fn is_jailed(storage: &dyn Storage, validator: &str) -> AnyResult<bool> {
+    Ok(jailed_validators(storage)?.iter().any(|v| v == validator))
+}
+
+/// `StakeKeeper`, with jailed validators left out of the active set
+#[derive(Default)]
+pub struct SteakStakingKeeper {
+    inner: StakeKeeper,
+}
+
+impl SteakStakingKeeper {
+    pub fn setup(&self, storage: &mut dyn Storage, staking_info: StakingInfo) -> AnyResult<()> {
+        self.inner.setup(storage, staking_info)
+    }
+
+    pub fn add_validator(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        block: &BlockInfo,
+        validator: Validator,
+    ) -> AnyResult<()> {
+        self.inner.add_validator(api, storage, block, validator)
+    }
+
+    pub fn jail(&self, storage: &mut dyn Storage, validator: &str) -> AnyResult<()> {
+        let mut jailed = jailed_validators(storage)?;
+        if !jailed.iter().any(|v| v == validator) {
+            jailed.push(validator.to_string());
+        }
+        storage.set(JAILED_KEY, &to_vec(&jailed)?);
+        Ok(())
+    }
+
+    pub fn unjail(&self, storage: &mut dyn Storage, validator: &str) -> AnyResult<()> {
+        let mut jailed = jailed_validators(storage)?;
+        jailed.retain(|v| v != validator);
+        storage.set(JAILED_KEY, &to_vec(&jailed)?);
+        Ok(())
+    }
+}
+
+impl Staking for SteakStakingKeeper {}
+
+impl Module for SteakStakingKeeper {
+    type ExecT = StakingMsg;
+    type QueryT = StakingQuery;
+    type SudoT = StakingSudo;
+
+    fn execute<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        sender: Addr,
+        msg: StakingMsg,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: std::fmt::Debug + Clone + PartialEq + JsonSchema + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        self.inner.execute(api, storage, router, block, sender, msg)
+    }
+
+    fn sudo<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        msg: StakingSudo,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: std::fmt::Debug + Clone + PartialEq + JsonSchema + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        self.inner.sudo(api, storage, router, block, msg)
+    }
+
+    fn query(
+        &self,
+        api: &dyn Api,
+        storage: &dyn Storage,
+        querier: &dyn Querier,
+        block: &BlockInfo,
+        request: StakingQuery,
+    ) -> AnyResult<Binary> {
+        let res = self
+            .inner
+            .query(api, storage, querier, block, request.clone())?;
+        match request {
+            StakingQuery::AllValidators {} => {
+                let jailed = jailed_validators(storage)?;
+                let mut all: AllValidatorsResponse = from_slice(&res)?;
+                all.validators.retain(|v| !jailed.contains(&v.address));
+                Ok(to_binary(&all)?)
+            }
+            StakingQuery::Delegation { validator, .. } if is_jailed(storage, &validator)? => {
+                let mut delegation: DelegationResponse = from_slice(&res)?;
+                if let Some(delegation) = &mut delegation.delegation {
+                    delegation.accumulated_rewards = vec![];
+                }
+                Ok(to_binary(&delegation)?)
+            }
+            _ => Ok(res),
+        }
+    }
+}
+
+/// `DistributionKeeper`, paying nothing for the time a validator spends jailed
+#[derive(Default)]
+pub struct SteakDistributionKeeper {
+    inner: DistributionKeeper,
+}
+
+impl Distribution for SteakDistributionKeeper {}
+
+impl Module for SteakDistributionKeeper {
+    type ExecT = DistributionMsg;
+    type QueryT = Empty;
+    type SudoT = Empty;
+
+    fn execute<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        sender: Addr,
+        msg: DistributionMsg,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: std::fmt::Debug + Clone + PartialEq + JsonSchema + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        match &msg {
+            DistributionMsg::WithdrawDelegatorReward { validator }
+                if is_jailed(storage, validator)? =>
+            {
+                let validator_addr = api.addr_validate(validator)?;
+                self.inner
+                    .remove_rewards(api, storage, block, &sender, &validator_addr)?;
+                Ok(AppResponse {
+                    events: vec![Event::new("withdraw_delegator_reward")
+                        .add_attribute("validator", validator)
+                        .add_attribute("sender", &sender)
+                        .add_attribute("amount", "")],
+                    data: None,
+                })
+            }
+            _ => self.inner.execute(api, storage, router, block, sender, msg),
+        }
+    }
+
+    fn sudo<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        msg: Empty,
+    ) -> AnyResult<AppResponse>
+    where
+        ExecC: std::fmt::Debug + Clone + PartialEq + JsonSchema + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        self.inner.sudo(api, storage, router, block, msg)
+    }
+
+    fn query(
+        &self,
+        api: &dyn Api,
+        storage: &dyn Storage,
+        querier: &dyn Querier,
+        block: &BlockInfo,
+        request: Empty,
+    ) -> AnyResult<Binary> {
+        self.inner.query(api, storage, querier, block, request)
+    }
+}
@@ -0,0 +1,307 @@
+use cosmwasm_std::{Decimal, Uint128};
+
+use pfc_steak::hub::{
+    Batch, CurrentMiner, ExecuteMsg, MiningEmissionResponse, QueryMsg, SlashingRecord,
+    StateResponse, ValidatorLoss, ValidatorMiningPower, ValidatorScore,
+};
+use pfc_steak::querier::HubQuerier;
+
+use crate::SteakHarness;
+
+#[test]
+fn bonding_harvesting_and_unbonding() {
+    let mut steak = SteakHarness::default();
+
+    steak.bond("alice", 3_000_000).unwrap();
+    assert_eq!(steak.steak_balance("alice"), Uint128::new(3_000_000));
+    let delegated: u128 = steak
+        .delegations()
+        .iter()
+        .map(|(_, amount)| amount.u128())
+        .sum();
+    assert_eq!(delegated, 3_000_000);
+
+    // A day of rewards is withdrawn and reinvested, raising the exchange rate
+    steak.advance_time(86_400);
+    steak.harvest().unwrap();
+    let state: StateResponse = steak.query(QueryMsg::State {}).unwrap();
+    assert!(state.total_native > Uint128::new(3_000_000));
+    assert!(state.exchange_rate > Decimal::one());
+
+    // Slashing lowers it again
+    let validator = steak.config.validators[0].clone();
+    steak.slash(&validator, Decimal::percent(50));
+    let slashed: StateResponse = steak.query(QueryMsg::State {}).unwrap();
+    assert!(slashed.exchange_rate < state.exchange_rate);
+
+    steak.queue_unbond("alice", 1_000_000).unwrap();
+    assert_eq!(steak.steak_balance("alice"), Uint128::new(2_000_000));
+
+    let epoch_period = steak.config.epoch_period;
+    steak.advance_time(epoch_period);
+    steak
+        .execute("alice", ExecuteMsg::SubmitBatch {}, &[])
+        .unwrap();
+
+    let unbond_period = steak.config.unbond_period;
+    steak.advance_time(unbond_period + 1);
+    steak
+        .execute("alice", ExecuteMsg::Reconcile {}, &[])
+        .unwrap();
+    steak
+        .execute(
+            "alice",
+            ExecuteMsg::WithdrawUnbonded { receiver: None },
+            &[],
+        )
+        .unwrap();
+    assert!(steak.native_balance("alice") > Uint128::zero());
+}
+
+#[test]
+fn driving_upkeep_from_the_clock() {
+    let mut steak = SteakHarness::default();
+    let owner = steak.config.owner.clone();
+
+    steak.bond("alice", 3_000_000).unwrap();
+    steak.queue_unbond("alice", 1_000_000).unwrap();
+    steak
+        .execute(
+            &owner,
+            ExecuteMsg::UpdateClockHarvestInterval {
+                interval: Some(86_400),
+            },
+            &[],
+        )
+        .unwrap();
+
+    // Nothing is due yet but the first harvest, which finds no rewards; its failure does not
+    // fail the block
+    let res = steak.end_block().unwrap();
+    assert!(res
+        .events
+        .iter()
+        .any(|event| event.ty == "wasm-steakhub/clock_task_failed"));
+
+    // Once the epoch is over, the batch is submitted and the rewards are harvested
+    let epoch_period = steak.config.epoch_period;
+    steak.advance_time(epoch_period);
+    let before: StateResponse = steak.query(QueryMsg::State {}).unwrap();
+    steak.end_block().unwrap();
+    let after: StateResponse = steak.query(QueryMsg::State {}).unwrap();
+    assert!(after.exchange_rate > before.exchange_rate);
+    let batch: Batch = steak.query(QueryMsg::PreviousBatch(1)).unwrap();
+    assert!(!batch.reconciled);
+
+    // A harvest does not run again before the interval is over
+    let res = steak.end_block().unwrap();
+    assert!(!res
+        .events
+        .iter()
+        .any(|event| event.ty == "wasm-steakhub/harvest_triggered"));
+
+    // Once the unbonding is over, the batch is reconciled and can be withdrawn
+    let unbond_period = steak.config.unbond_period;
+    steak.advance_time(unbond_period + 1);
+    steak.end_block().unwrap();
+    let batch: Batch = steak.query(QueryMsg::PreviousBatch(1)).unwrap();
+    assert!(batch.reconciled);
+    steak
+        .execute(
+            "alice",
+            ExecuteMsg::WithdrawUnbonded { receiver: None },
+            &[],
+        )
+        .unwrap();
+    assert!(steak.native_balance("alice") > Uint128::new(1_000_000));
+}
+
+#[test]
+fn reconciling_unbondings_slashed_in_the_queue() {
+    let mut steak = SteakHarness::default();
+
+    steak.bond("alice", 3_000_000).unwrap();
+    steak.queue_unbond("alice", 1_500_000).unwrap();
+    let epoch_period = steak.config.epoch_period;
+    steak.advance_time(epoch_period);
+    steak
+        .execute("alice", ExecuteMsg::SubmitBatch {}, &[])
+        .unwrap();
+
+    // The whole bond went to the first validator, which is slashed 10% while the batch unbonds
+    let validator = steak.config.validators[0].clone();
+    steak.slash(&validator, Decimal::percent(10));
+
+    let unbond_period = steak.config.unbond_period;
+    steak.advance_time(unbond_period + 1);
+    assert_eq!(
+        steak.native_balance(steak.hub.as_str()),
+        Uint128::new(1_350_000)
+    );
+
+    let res = steak
+        .execute("alice", ExecuteMsg::Reconcile {}, &[])
+        .unwrap();
+    let event = res
+        .events
+        .iter()
+        .find(|event| event.ty == "wasm-steakhub/reconciled")
+        .unwrap();
+    assert!(event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "native_deducted" && attr.value == "150000"));
+
+    let batch: Batch = steak.query(QueryMsg::PreviousBatch(1)).unwrap();
+    assert!(batch.reconciled);
+    assert_eq!(batch.amount_unclaimed, Uint128::new(1_350_000));
+
+    // the batch only undelegated from the slashed validator, so the whole loss is pinned on it
+    let records: Vec<SlashingRecord> = steak
+        .query(QueryMsg::SlashingRecords {
+            start_after: None,
+            limit: None,
+        })
+        .unwrap();
+    assert_eq!(
+        records[0].validators,
+        vec![ValidatorLoss {
+            validator,
+            amount: Uint128::new(150_000),
+        }]
+    );
+
+    steak
+        .execute(
+            "alice",
+            ExecuteMsg::WithdrawUnbonded { receiver: None },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(steak.native_balance("alice"), Uint128::new(1_350_000));
+}
+
+#[test]
+fn pausing_jailed_validators() {
+    let mut steak = SteakHarness::default();
+    let owner = steak.config.owner.clone();
+    let jailed = steak.config.validators[2].clone();
+
+    steak
+        .execute(
+            &owner,
+            ExecuteMsg::UpdateIdleRedelegation {
+                zero_reward_harvests: 2,
+                min_idle_period: 172_800,
+                max_amount: Uint128::new(1_000_000),
+            },
+            &[],
+        )
+        .unwrap();
+    // Each bond is delegated to the validator with the smallest delegation
+    for _ in 0..3 {
+        steak.bond("alice", 1_000_000).unwrap();
+    }
+    steak.jail(&jailed, Decimal::percent(1));
+
+    // The first harvest only starts the observation period
+    for _ in 0..3 {
+        steak.advance_time(86_400);
+        steak.harvest().unwrap();
+    }
+    // Jailed validators still accept delegations, so rewards may have been reinvested with it
+    assert!(delegation_to(&steak, &jailed) > Uint128::new(990_000));
+
+    steak
+        .execute("keeper", ExecuteMsg::RedelegateIdle {}, &[])
+        .unwrap();
+
+    let scores: Vec<ValidatorScore> = steak.query(QueryMsg::ValidatorScores {}).unwrap();
+    let score = scores.iter().find(|s| s.address == jailed).unwrap();
+    assert_eq!(score.pauses, 1);
+    assert_eq!(score.reward_rate, Decimal::zero());
+    assert_eq!(delegation_to(&steak, &jailed), Uint128::zero());
+}
+
+#[test]
+fn mining_harvesting_and_routing_fees() {
+    let mut steak = SteakHarness::default();
+    let owner = steak.config.owner.clone();
+    let validator = steak.config.validators[1].clone();
+    let fee_account = steak.config.fee_account.clone();
+
+    steak
+        .execute(&owner, ExecuteMsg::UpdateMinerFeeShare { bps: 5_000 }, &[])
+        .unwrap();
+    // Each bond is delegated to the validator with the smallest delegation
+    for _ in 0..3 {
+        steak.bond("alice", 1_000_000).unwrap();
+    }
+    steak.advance_time(86_400);
+
+    // The proof is accepted whatever its nonce, and harvests on the miner's behalf
+    let before: StateResponse = steak.query(QueryMsg::State {}).unwrap();
+    steak.submit_proof("miner", 42, &validator).unwrap();
+    let after: StateResponse = steak.query(QueryMsg::State {}).unwrap();
+    assert!(after.exchange_rate > before.exchange_rate);
+
+    let current: Option<CurrentMiner> = steak.query(QueryMsg::CurrentMiner {}).unwrap();
+    let current = current.unwrap();
+    assert_eq!(current.miner.as_str(), "miner");
+    assert!(!current.earned.is_zero());
+
+    // The fees are split between the miner and the fee account, which keeps the rounding
+    assert_eq!(steak.native_balance("miner"), current.earned);
+    let protocol_fees = steak.native_balance(&fee_account);
+    assert!(protocol_fees >= current.earned && protocol_fees <= current.earned + Uint128::one());
+
+    // Another miner takes over once the rate limit is over
+    steak.advance_time(86_400);
+    steak.submit_proof("other_miner", 42, &validator).unwrap();
+    assert!(!steak.native_balance("other_miner").is_zero());
+    let powers: Vec<ValidatorMiningPower> = steak
+        .query(QueryMsg::ValidatorMiningPowers {
+            start_after: None,
+            limit: None,
+        })
+        .unwrap();
+    assert!(powers
+        .iter()
+        .any(|p| p.address == validator && !p.mining_power.is_zero()));
+}
+
+#[test]
+fn querying_through_the_hub_querier() {
+    let mut steak = SteakHarness::default();
+    steak.bond("alice", 2_000_000).unwrap();
+    steak.queue_unbond("alice", 500_000).unwrap();
+
+    let querier = steak.app.wrap();
+    let hub = HubQuerier::new(querier, steak.hub.as_str());
+
+    let state: StateResponse = steak.query(QueryMsg::State {}).unwrap();
+    assert_eq!(hub.state().unwrap(), state);
+    assert_eq!(hub.exchange_rate().unwrap(), state.exchange_rate);
+    assert_eq!(hub.config().unwrap().denom, steak.config.denom);
+
+    let pending = hub.pending_batch().unwrap();
+    assert_eq!(pending.usteak_to_burn, Uint128::new(500_000));
+    let requests = hub.unbond_requests_by_user("alice", None, None).unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].shares, Uint128::new(500_000));
+    assert!(hub.previous_batches(None, None).unwrap().is_empty());
+    assert!(hub.previous_batch(1).is_err());
+
+    let emission = hub.mining_emission().unwrap();
+    let raw: MiningEmissionResponse = steak.query(QueryMsg::MiningEmission {}).unwrap();
+    assert_eq!(emission, raw);
+}
+
+fn delegation_to(steak: &SteakHarness, validator: &str) -> Uint128 {
+    steak
+        .delegations()
+        .into_iter()
+        .find(|(v, _)| v == validator)
+        .map(|(_, amount)| amount)
+        .unwrap_or_default()
+}
@@ -0,0 +1,109 @@
+//! Addresses on chains other than the one the hub runs on, e.g. the receivers of IBC transfers.
+//! `Api::addr_validate` only accepts the local bech32 prefix, so those are checked here instead.
+
+use std::fmt;
+
+use bech32::{FromBase32, Variant};
+use cosmwasm_std::{StdError, StdResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A bech32 address whose prefix is one of those the caller accepts, in canonical lower case.
+/// Like `Addr`, it deserializes unchecked, so untrusted input must go through `validate`
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord, JsonSchema,
+)]
+pub struct ForeignAddr(String);
+
+impl ForeignAddr {
+    /// Check `address` is a bech32 account or contract address, in lower case, whose prefix is one
+    /// of `prefixes`
+    pub fn validate(address: &str, prefixes: &[&str]) -> StdResult<Self> {
+        let invalid = |reason: &str| {
+            StdError::generic_err(format!("invalid foreign address {}: {}", address, reason))
+        };
+
+        if address != address.to_lowercase() {
+            return Err(invalid("not in lower case"));
+        }
+        let (prefix, data, variant) =
+            bech32::decode(address).map_err(|err| invalid(&err.to_string()))?;
+        if variant != Variant::Bech32 {
+            return Err(invalid("not bech32 encoded"));
+        }
+        if !prefixes.contains(&prefix.as_str()) {
+            return Err(invalid(&format!("unexpected prefix {}", prefix)));
+        }
+        // 20 bytes for accounts, 32 for contracts and module accounts
+        let bytes = Vec::<u8>::from_base32(&data).map_err(|err| invalid(&err.to_string()))?;
+        if bytes.len() != 20 && bytes.len() != 32 {
+            return Err(invalid(&format!("unexpected length {}", bytes.len())));
+        }
+
+        Ok(ForeignAddr(address.to_string()))
+    }
+
+    /// The chain's bech32 prefix, e.g. `osmo`
+    pub fn prefix(&self) -> &str {
+        // a validated address always has a separator
+        self.0.rsplit_once('1').map_or("", |(prefix, _)| prefix)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ForeignAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<ForeignAddr> for String {
+    fn from(addr: ForeignAddr) -> Self {
+        addr.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bech32::ToBase32;
+
+    use super::*;
+
+    fn encode(prefix: &str, len: usize) -> String {
+        bech32::encode(prefix, vec![7u8; len].to_base32(), Variant::Bech32).unwrap()
+    }
+
+    #[test]
+    fn validating_foreign_addresses() {
+        let account = encode("osmo", 20);
+        let addr = ForeignAddr::validate(&account, &["osmo", "juno"]).unwrap();
+        assert_eq!(addr.as_str(), account);
+        assert_eq!(addr.prefix(), "osmo");
+
+        let contract = encode("juno", 32);
+        assert_eq!(
+            ForeignAddr::validate(&contract, &["osmo", "juno"])
+                .unwrap()
+                .prefix(),
+            "juno"
+        );
+
+        // prefixes not accepted by the caller
+        assert!(ForeignAddr::validate(&account, &["juno"]).is_err());
+        // upper case, though a valid encoding, is not canonical
+        assert!(ForeignAddr::validate(&account.to_uppercase(), &["osmo"]).is_err());
+        // lengths other than those of accounts and contracts
+        assert!(ForeignAddr::validate(&encode("osmo", 19), &["osmo"]).is_err());
+        // bad checksum
+        let mut corrupted = account.clone();
+        corrupted.pop();
+        corrupted.push(if account.ends_with('q') { 'p' } else { 'q' });
+        assert!(ForeignAddr::validate(&corrupted, &["osmo"]).is_err());
+        // bech32m
+        let bech32m = bech32::encode("osmo", vec![7u8; 20].to_base32(), Variant::Bech32m).unwrap();
+        assert!(ForeignAddr::validate(&bech32m, &["osmo"]).is_err());
+    }
+}
@@ -0,0 +1,42 @@
+//! Minimal subset of the CronCat-style task manager interface the hub needs to schedule its own
+//! upkeep. Only the messages the hub actually sends are defined here.
+
+use cosmwasm_std::CosmosMsg;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// How often the automation network runs a task
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Interval {
+    /// Every given number of blocks
+    Block(u64),
+    /// On a cron schedule, e.g. `0 0 * * * *`
+    Cron(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct Action {
+    pub msg: CosmosMsg,
+    pub gas_limit: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct TaskRequest {
+    pub interval: Interval,
+    /// Whether the task is removed once one of its actions fails
+    pub stop_on_fail: bool,
+    pub actions: Vec<Action>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AutomationExecuteMsg {
+    /// Register a task, paid for by the funds sent along. The manager emits the hash identifying
+    /// the task as the `task_hash` attribute
+    CreateTask { task: Box<TaskRequest> },
+    /// Top up the balance the task's executions are paid from
+    RefillTaskBalance { task_hash: String },
+    /// Stop the task, refunding what is left of its balance to its owner
+    RemoveTask { task_hash: String },
+}
@@ -0,0 +1,77 @@
+use cosmwasm_schema::QueryResponses;
+use cosmwasm_std::{Addr, Uint128};
+use cw20::Cw20ReceiveMsg;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// Account who can register and deregister hubs
+    pub owner: String,
+    /// Address of the Steak token shared by the hubs; the coordinator must be its minter
+    pub steak_token: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Implements the Cw20 receiver interface
+    Receive(Cw20ReceiveMsg),
+    /// Allow `hub` to mint Steak; callable by the owner
+    AddHub { hub: String },
+    /// Stop `hub` from minting Steak. Only hubs with no Steak outstanding can be removed; callable
+    /// by the owner
+    RemoveHub { hub: String },
+    /// Mint `amount` of Steak to `recipient`, attributing it to the hub calling; callable by hubs
+    Mint { recipient: String, amount: Uint128 },
+    /// Set aside `amount` of the calling hub's supply for Steak it queued for unbonding without
+    /// going through the coordinator, e.g. bought back by arbitrage, so that the burn is covered;
+    /// callable by hubs
+    Reserve { amount: Uint128 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    /// Burn the Steak sent, deducting it from the supply of the hub sending it; callable by hubs,
+    /// for no more than has been queued with them
+    Burn {},
+    /// Queue the Steak sent for unbonding, split across the hubs pro rata to the supply they have
+    /// not queued yet, so that it redeems at the same blend of exchange rates whichever hub it was
+    /// minted by. `receiver` can withdraw from each of them once the batches finish unbonding
+    QueueUnbond { receiver: Option<String> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, QueryResponses)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// The coordinator's configuration. Response: `ConfigResponse`
+    #[returns(ConfigResponse)]
+    Config {},
+    /// The Steak attributed to a hub. Response: `HubSupply`
+    #[returns(HubSupply)]
+    HubSupply { hub: String },
+    /// The Steak attributed to every registered hub. Response: `Vec<HubSupply>`
+    #[returns(Vec<HubSupply>)]
+    Hubs {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    /// Account who can register and deregister hubs
+    pub owner: String,
+    /// Address of the Steak token shared by the hubs
+    pub steak_token: String,
+}
+
+/// Share of the Steak supply minted by a hub, which its delegations back
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct HubSupply {
+    /// Address of the hub
+    pub hub: Addr,
+    /// Steak minted by the hub and not yet burned
+    pub supply: Uint128,
+    /// Part of `supply` queued with the hub for unbonding, through the coordinator or reserved by
+    /// the hub, that the hub has not burned yet
+    pub queued: Uint128,
+}
@@ -1,12 +1,17 @@
+use cosmwasm_schema::QueryResponses;
 use cosmwasm_std::{
-    to_binary, Addr, Coin, CosmosMsg, Decimal, Empty, StdResult, Uint128, Uint64, WasmMsg,
+    to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, StdResult, Uint128, Uint64, VoteOption,
+    WasmMsg,
 };
-use cw20::Cw20ReceiveMsg;
+use cw20::{Cw20ReceiveMsg, Logo};
 use cw20_base::msg::InstantiateMarketingInfo as Cw20InstantiateMarketingInfo;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::str::FromStr;
 
+use crate::automation::Interval;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     /// Code ID of the CW20 token contract
@@ -37,8 +42,14 @@ pub struct InstantiateMsg {
     pub max_fee_amount: Decimal,
     /// label for the CW20 token we create
     pub label: Option<String>,
-    /// Marketing info for the CW20 we create
+    /// Admin of the CW20 token we create, able to migrate it, e.g. the chain's governance module;
+    /// the owner if not specified
+    pub token_admin: Option<String>,
+    /// Marketing info for the CW20 we create. The hub is always made its marketing admin, so that
+    /// the owner can update it through the hub
     pub marketing: Option<Cw20InstantiateMarketingInfo>,
+    /// Hash function proofs of work are computed with; SHA-256 if not specified
+    pub proof_hash: Option<ProofHash>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -46,50 +57,395 @@ pub struct InstantiateMsg {
 pub enum ExecuteMsg {
     /// Implements the Cw20 receiver interface
     Receive(Cw20ReceiveMsg),
-    /// Bond specified amount of Native Token
+    /// Bond specified amount of Native Token. Response data: `BondResponseData`
     Bond { receiver: Option<String> },
-    /// Withdraw Native Token that have finished unbonding in previous batches
+    /// Withdraw Native Token that have finished unbonding in previous batches, along with the
+    /// sender's consolidated claim. The sender's claims are refunded to `receiver` if given,
+    /// otherwise to the sender
     WithdrawUnbonded { receiver: Option<String> },
-    /// Withdraw Native Token that has finished unbonding in previous batches, for given address
+    /// Merge the sender's requests in batches that are reconciled and have finished unbonding into
+    /// a single consolidated claim, freeing room under `max_unbond_requests`. The claim is paid out
+    /// by `WithdrawUnbonded`
+    ConsolidateRequests {},
+    /// DEPRECATED: Withdraw Native Token that has finished unbonding in previous batches, for given
+    /// address; callable by the owner. Funds are always refunded to `address` itself
     WithdrawUnbondedAdmin { address: String },
-    /// Add a validator to the whitelist; callable by the owner
-    AddValidator { validator: String },
-    /// Remove a validator from the whitelist; callable by the owner
-    RemoveValidator { validator: String },
+    /// Add a validator to the whitelist; callable by the owner. Like the other changes to the
+    /// validator set, it is logged along with the optional `reason`
+    AddValidator {
+        validator: String,
+        reason: Option<String>,
+    },
+    /// Start removing a validator from the whitelist: it stops receiving new delegations and its
+    /// stake is moved to the active validators by `DrainValidators`, after which it is dropped.
+    /// Callable by the owner
+    RemoveValidator {
+        validator: String,
+        reason: Option<String>,
+    },
+    /// Redelegate at most `max_amount` from the validators being removed to the active validators,
+    /// dropping each from the whitelist once it holds no more stake; callable by the owner
+    DrainValidators { max_amount: Uint128 },
     /// Remove a validator from the whitelist; callable by the owner. Does not undelegate. use for typos
-    RemoveValidatorEx { validator: String },
+    RemoveValidatorEx {
+        validator: String,
+        reason: Option<String>,
+    },
 
-    /// Pause a validator from accepting new delegations
-    PauseValidator { validator: String },
+    /// Pause a validator from accepting new delegations; callable by the owner or the guardian
+    PauseValidator {
+        validator: String,
+        reason: Option<String>,
+    },
+    /// Stop a validator from receiving new delegations while its existing stake stays put: unlike
+    /// `PauseValidator`, rebalancing does not move its stake away, and unbonding still draws on it.
+    /// Lifted by `UnPauseValidator`; callable by the owner or the guardian
+    ReceiveOnlyPauseValidator {
+        validator: String,
+        reason: Option<String>,
+    },
     /// Unpause a validator from accepting new delegations
-    UnPauseValidator { validator: String },
+    UnPauseValidator {
+        validator: String,
+        reason: Option<String>,
+    },
+    /// Permanently bar a validator from being whitelisted or accruing mining power, e.g. after it
+    /// was tombstoned; callable by the owner. Also pauses it if currently active
+    BlockValidator { validator: String, reason: String },
+    /// Lift the block on a validator; callable by the owner
+    UnblockValidator {
+        validator: String,
+        reason: Option<String>,
+    },
 
     /// Transfer ownership to another account; will not take effect unless the new owner accepts
     TransferOwnership { new_owner: String },
     /// Accept an ownership transfer
     AcceptOwnership {},
-    /// Claim staking rewards, swap all for Native Token, and restake
+    /// Claim staking rewards, swap all for Native Token, and restake; callable by the contract
+    /// itself when a proof is accepted, or by a whitelisted harvester
     Harvest {},
+    /// Send back part of `validator`'s commission, in the Native Token; reinvested along with the
+    /// rewards of the next harvest, and credited to the validator
+    DepositRebate { validator: String },
     /// Use redelegations to balance the amounts of Native Token delegated to validators
     Rebalance { minimum: Uint128 },
+    /// Set the largest deviation of a validator's delegation from its target, in basis points of
+    /// the total delegated, below which rebalancing is not recommended; callable by the owner
+    UpdateDriftTolerance { bps: u16 },
+    /// Cap the staking messages a harvest, batch submission or rebalance fans out into, so their
+    /// responses always fit in a block, or lift the cap with `None`; callable by the owner.
+    /// Harvests and rebalances past the cap are completed by the next call
+    UpdateMaxMsgsPerTx { max: Option<u32> },
     /// Update Native Token amounts in unbonding batches to reflect any slashing or rounding errors
     Reconcile {},
-    /// Submit the current pending batch of unbonding requests to be unbonded
+    /// Submit the current pending batch of unbonding requests to be unbonded. Response data:
+    /// `SubmitBatchResponseData`
     SubmitBatch {},
+    /// Set how long, in seconds, Native Token may sit unclaimed in a batch after it finished
+    /// unbonding before it can be swept; `None` disables sweeping. Callable by the owner
+    UpdateBatchExpiry { expiry: Option<u64> },
+    /// Set the minimum number of seconds between two batch submissions, and the minimum amount of
+    /// `usteak` a batch must burn before it can be submitted; the owner can submit a batch
+    /// regardless of either. Callable by the owner
+    UpdateBatchLimits { min_gap: u64, min_size: Uint128 },
+    /// Cap the unbonding requests a user can hold at once, so that claiming them always fits in a
+    /// transaction; `None` lifts the cap. Requests queued on the user's behalf are capped
+    /// separately from their own, so they can't use up the user's own room. Callable by the owner
+    UpdateMaxUnbondRequests { max: Option<u32> },
+    /// Release reinvested rewards into the exchange rate linearly over `period` seconds rather than
+    /// all at once; `None` releases them immediately. Callable by the owner
+    UpdateRewardDrip { period: Option<u64> },
+    /// Set the rewards below which a reinvest is deferred rather than delegating dust; they are
+    /// folded into the next reinvest instead. Callable by the owner
+    UpdateMinReinvestAmount { amount: Uint128 },
+    /// Re-bond what is left unclaimed in an expired batch; its unbonders are refunded in freshly
+    /// minted Steak instead. Callable by anyone
+    SweepExpiredBatch { id: u64 },
+    /// Delete stale entries of the given `kind`, scanning at most `limit` entries (at most 100) from
+    /// where the previous call left off. Callable by anyone
+    Prune { kind: PruneKind, limit: Option<u32> },
     /// Set unbond period
     SetUnbondPeriod { unbond_period: u64 },
 
-    /// Transfer Fee collection account to another account
+    /// Transfer Fee collection account to another account. Logged along with the optional
+    /// `reason`, as are the other admin actions taking one
     TransferFeeAccount {
         fee_account_type: String,
         new_fee_account: String,
+        reason: Option<String>,
     },
+    /// Send the fees escrowed by reinvests that failed to forward them to the fee account, to the
+    /// current fee account; callable by anyone
+    RetryFeeForward {},
+    /// Update the Steak token's marketing info, of which the hub is the marketing admin. Setting
+    /// `marketing` hands the admin role over to that address; callable by the owner
+    UpdateTokenMarketing {
+        project: Option<String>,
+        description: Option<String>,
+        marketing: Option<String>,
+    },
+    /// Upload a new logo for the Steak token; callable by the owner
+    UploadTokenLogo { logo: Logo },
+    /// Mint and burn Steak through `coordinator`, sharing its token with other hubs. Only possible
+    /// while the hub has no Steak outstanding, and only once; callable by the owner
+    SetCoordinator { coordinator: String },
     /// Update fee collection amount
-    UpdateFee { new_fee: Decimal },
+    UpdateFee {
+        new_fee: Decimal,
+        reason: Option<String>,
+    },
     /// Update entropy
     UpdateEntropy { entropy: String },
     /// Submit mined proof
     SubmitProof { nonce: Uint64, validator: String },
+    /// Submit mined proof, splitting the mining power across validators by weight. Weights must
+    /// be positive and sum to 1
+    SubmitProofSplit {
+        nonce: Uint64,
+        allocations: Vec<MiningAllocation>,
+    },
+    /// Drop the mining power accrued by the given validators if they are not whitelisted, removing
+    /// it from the total. Callable by anyone
+    PurgeMiningPower { validators: Vec<String> },
+    /// Record a slashing incident against a whitelisted validator, counting against its score;
+    /// callable by the owner
+    ReportSlashing { validator: String },
+    /// Redelegate away from whitelisted validators that earned no rewards over the last
+    /// `zero_reward_harvests` harvests, spanning at least `min_idle_period` seconds, moving at most
+    /// `max_amount` per call. Neither may be zero; callable by the owner
+    UpdateIdleRedelegation {
+        zero_reward_harvests: u32,
+        min_idle_period: u64,
+        max_amount: Uint128,
+    },
+    /// Pause the validators that earned no rewards over the configured number of harvests and
+    /// redelegate their stake to the remaining active validators. Callable by anyone
+    RedelegateIdle {},
+    /// Cap each validator's target delegation so that the hub's stake does not lift it above
+    /// `max_share` of the chain's voting power; `None` lifts the cap. Callable by the owner
+    UpdateVotingPowerCap { max_share: Option<Decimal> },
+    /// Refresh the whitelisted validators' voting power, and the chain's total, which the cap is
+    /// derived from. Callable by anyone
+    RefreshVotingPower {},
+    /// Set the share of the emitted harvest fees paid to the last miner, in basis points, the rest
+    /// going to the fee account; callable by the owner
+    UpdateMinerFeeShare { bps: u16 },
+    /// Set the number of blocks a miner must wait between accepted proofs; callable by the owner
+    UpdateProofCooldown { blocks: u64 },
+    /// Limit each address to `max_contributions` entropy updates per `window` seconds. A zero
+    /// `max_contributions` removes the limit; callable by the owner
+    UpdateEntropyRateLimit { window: u64, max_contributions: u32 },
+    /// Set the schedule by which the share of harvest fees paid to the miner decays, starting at
+    /// epoch 0 from the current block; callable by the owner
+    UpdateMiningSchedule {
+        epoch_duration: u64,
+        initial_share: Decimal,
+        halving_epochs: u64,
+    },
+    /// Bond specified amount of Native Token, then invoke `contract` with a `BondCallbackMsg`
+    /// reporting the exact amount of Steak minted to `receiver`
+    BondAndCall {
+        receiver: Option<String>,
+        contract: String,
+        callback: Binary,
+    },
+    /// Bond specified amount of Native Token, then invoke the sender with an `IntegratorCallbackMsg`
+    /// reporting the hub's state once bonding has completed; callable by whitelisted integrators
+    BondWithCallback {
+        receiver: Option<String>,
+        callback: Binary,
+    },
+    /// Bond specified amount of Native Token, locking the minted Steak in the hub for `duration`
+    /// seconds. Locked shares can not be transferred, and earn a boost funded from the fee stream
+    BondLocked {
+        receiver: Option<String>,
+        duration: u64,
+    },
+    /// Once a locked bond has expired, pay out its boost and queue its shares for unbonding
+    UnlockBond { id: u64 },
+    /// Claim the boost accrued by all of the sender's locked bonds
+    ClaimLockBoost {},
+    /// Return the Steak of all of the sender's expired locks, paying out their boost
+    Unlock {},
+    /// Update the share of fees redirected to locked bonds; callable by the owner
+    UpdateLockBoost { boost_rate: Decimal },
+    /// Replace the tiers boosting locks by duration; callable by the owner. Only locks created
+    /// afterwards are affected
+    UpdateLockTiers { tiers: Vec<LockTier> },
+    /// Enable principal/yield splitting, using a CW20 token the hub is minter of as principal
+    /// token; callable by the owner
+    EnableYieldSplit { principal_token: String },
+    /// Claim the Steak accrued by the sender's yield shares
+    ClaimYield { receiver: Option<String> },
+    /// Transfer a rebasing balance, denominated in Native Token, to `recipient`
+    TransferRebasing { recipient: String, amount: Uint128 },
+    /// Unwrap a rebasing balance, denominated in Native Token, back into Steak
+    UnwrapRebasing {
+        amount: Uint128,
+        receiver: Option<String>,
+    },
+    /// Configure the Steak/native pair the hub provides liquidity to, and the share of fees kept to
+    /// fund it; callable by the owner
+    ConfigurePol {
+        pair: String,
+        lp_token: String,
+        fee_rate: Decimal,
+    },
+    /// Deploy the native reserve, along with any Native Token sent, and the Steak held for
+    /// protocol-owned liquidity into the pair. `bond_amount` of the native is bonded first to mint
+    /// the Steak side; callable by the owner
+    AddPol {
+        bond_amount: Uint128,
+        slippage_tolerance: Option<Decimal>,
+    },
+    /// Withdraw `lp_amount` of protocol-owned liquidity back into the reserves; callable by the owner
+    RemovePol { lp_amount: Uint128 },
+    /// Send the undeployed reserves of protocol-owned liquidity to `receiver`; callable by the owner
+    CollectPol { receiver: String },
+    /// Update the bounds within which the arbitrage reserve may be deployed; callable by the owner
+    UpdateArbConfig {
+        min_discount: Decimal,
+        max_per_execution: Uint128,
+        max_in_flight: Uint128,
+    },
+    /// Add the Native Token sent to the arbitrage reserve
+    FundArbReserve {},
+    /// Withdraw from the arbitrage reserve; callable by the owner
+    WithdrawArbReserve { amount: Uint128, receiver: String },
+    /// If Steak trades on the pair configured with `ConfigurePol` at a discount to the redemption
+    /// rate of at least `min_discount`, buy it with the arbitrage reserve and queue it for unbonding
+    Arbitrage {},
+    /// Withdraw the unbonded proceeds of previous arbitrages, returning their cost to the reserve and
+    /// bonding the spread on behalf of all holders
+    SettleArbitrage {},
+    /// Update which side rounding in share math favors; callable by the owner
+    UpdateRoundingPolicy { policy: RoundingPolicy },
+    /// Propose switching the staking denom; callable by the owner. Takes effect on
+    /// `ConfirmDenomChange`
+    ProposeDenomChange { new_denom: String },
+    /// Apply the proposed denom change; fails while anything is outstanding in the current denom
+    ConfirmDenomChange {},
+    /// Drop the proposed denom change
+    CancelDenomChange {},
+    /// Start the timelock after which the owner may undelegate everything; callable by the owner
+    ProposeEmergencyUndelegation {},
+    /// Once the timelock has passed, undelegate all Native Token into a final batch that Steak
+    /// holders claim from pro-rata, and stop accepting bonds for good; callable by the owner
+    EmergencyUndelegateAll {},
+    /// Drop the proposed emergency undelegation; callable by the owner
+    CancelEmergencyUndelegation {},
+    /// Propose correcting the clocks after a chain halt: delay the pending batch and the batches
+    /// still unbonding by `batch_shift` seconds, and optionally restart the mining clock from the
+    /// time the recovery is applied; callable by the owner. Takes effect on `ApplyHaltRecovery`
+    ProposeHaltRecovery {
+        batch_shift: u64,
+        reset_mining_clock: bool,
+    },
+    /// Apply the proposed halt recovery once its timelock has passed; callable by the owner
+    ApplyHaltRecovery {},
+    /// Drop the proposed halt recovery; callable by the owner
+    CancelHaltRecovery {},
+    /// Ahead of a risky chain upgrade, stop accepting bonds, unbonding requests and proofs until
+    /// `until_height` or `until_time`, whichever comes first; withdrawals and queries keep working.
+    /// At least one of the two must be given. Callable by the owner or the guardian
+    EnterSafeMode {
+        until_height: Option<u64>,
+        until_time: Option<u64>,
+        reason: Option<String>,
+    },
+    /// Lift safe mode before it expires; callable by the owner
+    ExitSafeMode { reason: Option<String> },
+    /// Propose a guardian, an account that may pause validators and enter safe mode but nothing
+    /// else, or revoke the current one if `guardian` is `None`; callable by the owner. Takes effect
+    /// on `ApplyGuardianChange`
+    ProposeGuardianChange { guardian: Option<String> },
+    /// Apply the proposed guardian change once its timelock has passed; callable by the owner
+    ApplyGuardianChange {},
+    /// Drop the proposed guardian change; callable by the owner
+    CancelGuardianChange {},
+    /// Register the committee that must approve changes to the max fee; callable by the owner, and
+    /// only while no committee is registered. Later changes to the committee need its own approval
+    SetFeeCommittee {
+        members: Vec<String>,
+        threshold: u32,
+    },
+    /// Propose a committee action, replacing any pending one; counts as the proposer's approval.
+    /// Callable by committee members
+    ProposeCommitteeAction { action: CommitteeAction },
+    /// Approve the pending committee action with the given `id`; the action is applied once
+    /// `threshold` members approved it. Callable by committee members
+    ApproveCommitteeAction { id: u64 },
+    /// Notify `contract` of every change in Steak balances with a `BalanceHookMsg`; callable by the
+    /// owner. Requires the Steak token to be built with transfer hooks
+    AddBalanceHook { contract: String },
+    /// Stop notifying `contract` of changes in Steak balances; callable by the owner
+    RemoveBalanceHook { contract: String },
+    /// Allow `address`, e.g. an automation network account, to trigger harvests; callable by the
+    /// owner
+    AddHarvester { address: String },
+    /// Stop allowing `address` to trigger harvests; callable by the owner
+    RemoveHarvester { address: String },
+    /// Allow contract `address`, e.g. a router, to request callbacks once its bonds and unbonds have
+    /// completed; callable by the owner
+    AddIntegrator { address: String },
+    /// Stop allowing contract `address` to request callbacks; callable by the owner
+    RemoveIntegrator { address: String },
+    /// Set the delegates, e.g. a council, whose votes on governance proposals the hub mirrors with
+    /// its stake; callable by the owner. An empty list stops the mirroring
+    UpdateVoteDelegates { delegates: Vec<String> },
+    /// Record the sender's vote on `proposal_id`, and vote the option most delegates voted for with
+    /// the hub's stake. The first vote on a proposal sets its `voting_end_time`, in seconds, after
+    /// which no more votes are taken; callable by the vote delegates
+    DelegateVote {
+        proposal_id: u64,
+        vote: VoteOption,
+        voting_end_time: u64,
+    },
+    /// Stop mirroring the delegates' votes on `proposal_id`, leaving the hub's stake to its
+    /// validators' votes; callable by the owner, until the hub has voted on the proposal
+    OptOutOfDelegatedVote { proposal_id: u64 },
+    /// Set the automation contract upkeep tasks are registered with; callable by the owner, once
+    /// the tasks registered with the previous contract are removed
+    UpdateAutomation { contract: Option<String> },
+    /// Register a recurring `task` with the automation contract, paid for by the funds sent along;
+    /// callable by the owner
+    CreateAutomationTask {
+        task: AutomationTask,
+        interval: Interval,
+        gas_limit: Option<u64>,
+    },
+    /// Top up the balance of a registered `task` with the funds sent along; callable by the owner
+    RefillAutomationTask { task: AutomationTask },
+    /// Remove a registered `task`, refunding what is left of its balance; callable by the owner
+    RemoveAutomationTask { task: AutomationTask },
+    /// Set how often, in seconds, the chain's clock module harvests; `None` leaves harvesting to
+    /// proofs, harvesters and automation tasks. Callable by the owner
+    UpdateClockHarvestInterval { interval: Option<u64> },
+    /// Forget a task the automation network stopped on its own, e.g. once its balance ran out; can
+    /// only be invoked by the automation contract
+    AutomationTaskEnded { task_hash: String },
+    /// Forward a change in Steak balances to the registered hooks; can only be invoked by the
+    /// Steak token
+    BalanceChanged(BalanceChange),
+    /// Set the denoms staking rewards are paid in, and the contract that rewards paid in denoms
+    /// other than the Native Token are sent to for conversion; callable by the owner
+    UpdateRewardDenoms {
+        reward_denoms: Vec<String>,
+        converter: Option<String>,
+    },
+    /// Set the denoms that may be sent along a deposit besides the Native Token; they are refunded
+    /// to the sender rather than failing the deposit. Callable by the owner
+    UpdateRefundableDenoms { denoms: Vec<String> },
+    /// Register the zap contract that swaps other coins into the Native Token and bonds them, or
+    /// unregister it if `None`; callable by the owner. Informational, so that frontends can find it
+    UpdateZapper { zapper: Option<String> },
+    /// Set where `SkimSurplus` routes the Native Token held beyond the hub's liabilities; callable
+    /// by the owner
+    UpdateSurplusPolicy { policy: SurplusPolicy },
+    /// Route the Native Token held beyond what is owed to withdrawers, the reserves and the
+    /// rewards awaiting reinvestment, e.g. coins sent straight to the hub, per the surplus policy
+    SkimSurplus {},
     /// Callbacks; can only be invoked by the contract itself
     Callback(CallbackMsg),
 }
@@ -99,14 +455,74 @@ pub enum ExecuteMsg {
 pub enum ReceiveMsg {
     /// Submit an unbonding request to the current unbonding queue; automatically invokes `unbond`
     /// if `epoch_time` has elapsed since when the last unbonding queue was executed.
-    QueueUnbond { receiver: Option<String> },
+    ///
+    /// Fails if the Steak is worth less than `min_out` native at the current exchange rate, or if
+    /// the block time is past `deadline`, in seconds. The amount finally unbonded is only set when
+    /// the batch is submitted, so `min_out` guards against a stale rate rather than guaranteeing it
+    QueueUnbond {
+        receiver: Option<String>,
+        min_out: Option<Uint128>,
+        deadline: Option<u64>,
+    },
+    /// Same as `QueueUnbond`, then invoke the sender with an `IntegratorCallbackMsg` reporting the
+    /// hub's state once the request has been queued; callable by whitelisted integrators
+    QueueUnbondWithCallback {
+        receiver: Option<String>,
+        callback: Binary,
+    },
+    /// Split Steak into principal tokens, worth 1 native each, minted to `receiver`, and yield
+    /// shares credited to the sender, which accrue the staking rewards of the Steak deposited
+    SplitYield { receiver: Option<String> },
+    /// Redeem principal tokens for the Steak currently worth the same amount of native
+    RedeemPrincipal { receiver: Option<String> },
+    /// Wrap Steak into a rebasing balance, where one unit always equals one unit of Native Token
+    /// and the balance grows as rewards are reinvested
+    WrapRebasing { receiver: Option<String> },
+    /// Swap the Steak for Native Token through the pair configured with `ConfigurePol`, paying
+    /// `receiver` the proceeds immediately, up to what the Steak redeems for at the exchange rate;
+    /// anything the pair pays above that is kept in the arbitrage reserve. If `receiver` would get
+    /// less than `min_out` at the simulated price, the Steak is queued for unbonding instead; if the
+    /// swap itself returns less, the unstake fails. Where Steak is shared through a coordinator, it
+    /// is never queued here; the unstake fails instead. Fails if the block time is past `deadline`,
+    /// in seconds
+    LiquidUnstake {
+        min_out: Uint128,
+        receiver: Option<String>,
+        deadline: Option<u64>,
+    },
+    /// Lock the Steak in the hub for `duration` seconds, earning a boost funded from the fee
+    /// stream, scaled by the tier the duration qualifies for
+    Lock { duration: u64 },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum CallbackMsg {
+    /// Following the withdrawals, send the rewards paid in denoms other than the Native Token to
+    /// the reward converter
+    ConvertRewards {},
     /// Following the swaps, stake the Native Token acquired to the whitelisted validators
     Reinvest {},
+    /// Following a withdrawal of protocol-owned liquidity, credit the assets returned by the pair to
+    /// the reserves
+    SettlePolWithdrawal {
+        native_before: Uint128,
+        usteak_before: Uint128,
+    },
+    /// Following a liquid unstake, pay `receiver` the native returned by the pair, up to
+    /// `native_at_par`, and credit the rest to the arbitrage reserve
+    SettleLiquidUnstake {
+        receiver: String,
+        native_before: Uint128,
+        native_at_par: Uint128,
+        min_out: Uint128,
+    },
+    /// Following a flow `integrator` requested a callback for, report the hub's state to it
+    NotifyIntegrator {
+        integrator: String,
+        flow: IntegratorFlow,
+        callback: Binary,
+    },
 }
 
 impl CallbackMsg {
@@ -119,44 +535,551 @@ impl CallbackMsg {
     }
 }
 
+/// Set as the response data of a bond, so that calling contracts can read the result from the reply
+/// rather than from events
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct BondResponseData {
+    /// Account the Steak was minted to
+    pub receiver: String,
+    /// Amount of Native Token bonded
+    pub amount_bonded: Uint128,
+    /// Amount of `usteak` minted
+    pub usteak_minted: Uint128,
+    /// The exchange rate the bond was priced at, in terms of native per usteak
+    pub exchange_rate: Decimal,
+}
+
+/// Set as the response data of `SubmitBatch`
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct SubmitBatchResponseData {
+    /// ID of the batch submitted
+    pub batch_id: u64,
+    /// Amount of `usteak` burned
+    pub usteak_burned: Uint128,
+    /// Amount of Native Token undelegated
+    pub native_unbonded: Uint128,
+    /// Time the unbonding is expected to complete
+    pub est_unbond_end_time: u64,
+}
+
+/// Payload sent to the contract named in `ExecuteMsg::BondAndCall`, once the Steak has been minted
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct BondCallback {
+    /// Account the Steak was minted to
+    pub receiver: String,
+    /// Amount of Native Token bonded
+    pub amount_bonded: Uint128,
+    /// Amount of `usteak` minted
+    pub usteak_minted: Uint128,
+    /// The `callback` given by the caller, passed through untouched
+    pub msg: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BondCallbackMsg {
+    SteakBondCallback(BondCallback),
+}
+
+impl BondCallbackMsg {
+    pub fn into_cosmos_msg(self, contract_addr: String) -> StdResult<CosmosMsg> {
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            msg: to_binary(&self)?,
+            funds: vec![],
+        }))
+    }
+}
+
+/// A flow an integrator requested a callback for
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegratorFlow {
+    Bond {
+        receiver: String,
+        amount_bonded: Uint128,
+        usteak_minted: Uint128,
+    },
+    QueueUnbond {
+        receiver: String,
+        usteak_queued: Uint128,
+    },
+}
+
+/// Payload sent to an integrator once a flow it requested a callback for has completed
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct IntegratorCallback {
+    pub flow: IntegratorFlow,
+    /// Total supply of `usteak` once the flow has completed
+    pub total_usteak: Uint128,
+    /// Total amount of Native Token staked once the flow has completed
+    pub total_native: Uint128,
+    /// Native Token each `usteak` is worth once the flow has completed
+    pub exchange_rate: Decimal,
+    /// The `callback` given by the integrator, passed through untouched
+    pub msg: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegratorCallbackMsg {
+    SteakIntegratorCallback(IntegratorCallback),
+}
+
+impl IntegratorCallbackMsg {
+    pub fn into_cosmos_msg(self, contract_addr: String) -> StdResult<CosmosMsg> {
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            msg: to_binary(&self)?,
+            funds: vec![],
+        }))
+    }
+}
+
+/// A vote delegate's vote on a governance proposal
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct DelegateBallot {
+    pub delegate: String,
+    pub vote: VoteOption,
+}
+
+/// Votes of the vote delegates on a governance proposal, tallied into the vote the hub casts
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct DelegatedVote {
+    pub proposal_id: u64,
+    /// End of the proposal's voting period, in seconds; unset until a delegate voted
+    pub voting_end_time: Option<u64>,
+    /// Votes of the delegates, in the order they first voted
+    pub ballots: Vec<DelegateBallot>,
+    /// Whether the owner opted the proposal out of the delegated vote
+    pub opted_out: bool,
+    /// Option the hub voted with its stake, if it voted yet
+    pub cast: Option<VoteOption>,
+}
+
+/// A change in Steak balances. `from` is empty when Steak is minted, and `to` when it is burned
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct BalanceChange {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub amount: Uint128,
+}
+
+/// Payload sent, along with the rewards to convert, to the converter set with
+/// `ExecuteMsg::UpdateRewardDenoms`. The converter must send `denom_out` back to the sender in the
+/// same transaction for it to be reinvested
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardConverterMsg {
+    ConvertRewards { denom_out: String },
+}
+
+impl RewardConverterMsg {
+    pub fn into_cosmos_msg(self, contract_addr: String, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            msg: to_binary(&self)?,
+            funds,
+        }))
+    }
+}
+
+/// Upkeep the hub can have the automation network run on a schedule
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AutomationTask {
+    Harvest,
+    SubmitBatch,
+    Reconcile,
+}
+
+impl AutomationTask {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AutomationTask::Harvest => "harvest",
+            AutomationTask::SubmitBatch => "submit_batch",
+            AutomationTask::Reconcile => "reconcile",
+        }
+    }
+
+    /// The message the automation network executes on the hub
+    pub fn execute_msg(&self) -> ExecuteMsg {
+        match self {
+            AutomationTask::Harvest => ExecuteMsg::Harvest {},
+            AutomationTask::SubmitBatch => ExecuteMsg::SubmitBatch {},
+            AutomationTask::Reconcile => ExecuteMsg::Reconcile {},
+        }
+    }
+}
+
+impl fmt::Display for AutomationTask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Storage that `ExecuteMsg::Prune` can clean up
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PruneKind {
+    /// Reconciled batches whose shares have all been claimed
+    Batches,
+    /// Unbonding requests with no shares, or whose batch no longer exists
+    UnbondRequests,
+    /// Daily snapshots older than the retention period
+    Snapshots,
+}
+
+impl PruneKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PruneKind::Batches => "batches",
+            PruneKind::UnbondRequests => "unbond_requests",
+            PruneKind::Snapshots => "snapshots",
+        }
+    }
+}
+
+impl fmt::Display for PruneKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Payload sent to the contracts registered with `ExecuteMsg::AddBalanceHook`
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
+pub enum BalanceHookMsg {
+    SteakBalanceChanged(BalanceChange),
+}
+
+impl BalanceHookMsg {
+    pub fn into_cosmos_msg(self, contract_addr: String) -> StdResult<CosmosMsg> {
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            msg: to_binary(&self)?,
+            funds: vec![],
+        }))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema, QueryResponses)]
+#[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     /// The contract's configurations. Response: `ConfigResponse`
+    #[returns(ConfigResponse)]
     Config {},
+    /// The owner, and every change awaiting a second step: the ownership transfer awaiting
+    /// acceptance, the proposed denom change, the committee action awaiting approvals and the
+    /// timelocked proposals with the time they can be executed at. Response: `OwnershipResponse`
+    #[returns(OwnershipResponse)]
+    Ownership {},
     /// The contract's current state. Response: `StateResponse`
+    #[returns(StateResponse)]
     State {},
-    /// The current batch on unbonding requests pending submission. Response: `PendingBatch`
+    /// The contract's current state, along with its liquidity, unbonding totals and mining.
+    /// Response: `StateResponseV2`
+    #[returns(StateResponseV2)]
+    StateV2 {},
+    /// The current batch on unbonding requests pending submission, and when it can be submitted.
+    /// Response: `PendingBatchResponse`
+    #[returns(PendingBatchResponse)]
     PendingBatch {},
     /// Query an individual batch that has previously been submitted for unbonding but have not yet
     /// fully withdrawn. Response: `Batch`
+    #[returns(Batch)]
     PreviousBatch(u64),
     /// Enumerate all previous batches that have previously been submitted for unbonding but have not
     /// yet fully withdrawn. Response: `Vec<Batch>`
+    #[returns(Vec<Batch>)]
     PreviousBatches {
         start_after: Option<u64>,
         limit: Option<u32>,
     },
-    /// Enumerate all outstanding unbonding requests in a given batch. Response: `Vec<UnbondRequestsResponseByBatchItem>`
+    /// Enumerate all outstanding unbonding requests in a given batch. Response: `Vec<UnbondRequestsByBatchResponseItem>`
+    #[returns(Vec<UnbondRequestsByBatchResponseItem>)]
     UnbondRequestsByBatch {
         id: u64,
         start_after: Option<String>,
         limit: Option<u32>,
     },
     /// Enumreate all outstanding unbonding requests from given a user. Response: `Vec<UnbondRequestsByUserResponseItem>`
+    #[returns(Vec<UnbondRequestsByUserResponseItem>)]
     UnbondRequestsByUser {
         user: String,
         start_after: Option<u64>,
         limit: Option<u32>,
     },
+    /// Same as `UnbondRequestsByUser`, joined with the batch of each request and the amount the
+    /// shares are worth. Response: `Vec<UnbondRequestDetails>`
+    #[returns(Vec<UnbondRequestDetails>)]
+    UnbondRequestsByUserDetails {
+        user: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// How many unbonding requests a user holds against the cap, and their consolidated claim.
+    /// Response: `ConsolidatedClaimResponse`
+    #[returns(ConsolidatedClaimResponse)]
+    ConsolidatedClaim { user: String },
+    /// A user's unbonding request in the current pending batch, with the Native Token it is worth at
+    /// the current exchange rate. Response: `PendingBatchRequestResponse`
+    #[returns(PendingBatchRequestResponse)]
+    PendingBatchRequest { user: String },
+    /// Enumerate all outstanding unbonding requests queued by a given address, whether for itself or
+    /// on behalf of their users. Response: `Vec<UnbondRequest>`
+    #[returns(Vec<UnbondRequest>)]
+    UnbondRequestsBySender {
+        sender: String,
+        start_after: Option<(u64, String)>,
+        limit: Option<u32>,
+    },
+    /// Enumerate the `usteak` queued for unbonding in a given batch on behalf of others, by funder and
+    /// receiver. Response: `Vec<BatchFunder>`
+    #[returns(Vec<BatchFunder>)]
+    BatchFunders {
+        id: u64,
+        start_after: Option<(String, String)>,
+        limit: Option<u32>,
+    },
     /// Load entropy and difficulty for the current epoch. Response: `MinerParamsResponse`
+    #[returns(MinerParamsResponse)]
     MinerParams {},
+    /// The miner fee emission schedule and current epoch. Response: `MiningEmissionResponse`
+    #[returns(MiningEmissionResponse)]
+    MiningEmission {},
+    /// Hash `nonce` for `miner` against the current entropy and check it against the current
+    /// difficulty. Response: `VerifyProofResponse`
+    #[returns(VerifyProofResponse)]
+    VerifyProof { miner: String, nonce: Uint64 },
+    /// When a miner last had a proof accepted and may submit the next one.
+    /// Response: `MinerStatusResponse`
+    #[returns(MinerStatusResponse)]
+    MinerStatus { miner: String },
+    /// The miner currently paid a share of the harvest fees, since when, and what it has earned so
+    /// far. Response: `Option<CurrentMiner>`
+    #[returns(Option<CurrentMiner>)]
+    CurrentMiner {},
+    /// An address's entropy contributions and the rate limit they are subject to.
+    /// Response: `EntropyContributorResponse`
+    #[returns(EntropyContributorResponse)]
+    EntropyContributor { contributor: String },
+    /// Enumerate the entropy contributions, oldest first. Response: `Vec<EntropyContribution>`
+    #[returns(Vec<EntropyContribution>)]
+    EntropyContributions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     /// Validator Mining Powers
     /// Response: `Vec<ValidatorMiningPower>`
+    #[returns(Vec<ValidatorMiningPower>)]
     ValidatorMiningPowers {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Performance scores of the whitelisted validators. Response: `Vec<ValidatorScore>`
+    #[returns(Vec<ValidatorScore>)]
+    ValidatorScores {},
+    /// Rewards withdrawn from each validator the hub delegated to, summed over all harvests since
+    /// they started being tracked, and the commission rebates each deposited.
+    /// Response: `Vec<ValidatorRewards>`
+    #[returns(Vec<ValidatorRewards>)]
+    ValidatorRewards {},
+    /// How far the delegations are from their targets, and whether that warrants a rebalance.
+    /// Response: `DelegationDriftResponse`
+    #[returns(DelegationDriftResponse)]
+    DelegationDrift {},
+    /// The voting power cap, the voting power it was last derived from, and each validator's cap.
+    /// Response: `VotingPowerCapResponse`
+    #[returns(VotingPowerCapResponse)]
+    VotingPowerCap {},
+    /// Validators paused from receiving new delegations only. Response: `Vec<String>`
+    #[returns(Vec<String>)]
+    ReceivePausedValidators {},
+    /// Validators being removed, whose stake is still to be moved away. Response: `Vec<String>`
+    #[returns(Vec<String>)]
+    DrainingValidators {},
+    /// Validators barred from the whitelist. Response: `Vec<BlockedValidator>`
+    #[returns(Vec<BlockedValidator>)]
+    BlockedValidators {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Fee and safe mode changes made by the owner or the guardian, oldest first.
+    /// Response: `Vec<AdminActionRecord>`
+    #[returns(Vec<AdminActionRecord>)]
+    AdminActions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Every change made to the validator set, oldest first. Response: `Vec<ValidatorSetChange>`
+    #[returns(Vec<ValidatorSetChange>)]
+    ValidatorSetChanges {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Boost parameters and totals of the locked bonds. Response: `LockStateResponse`
+    #[returns(LockStateResponse)]
+    LockState {},
+    /// Enumerate the locked bonds of a given user. Response: `Vec<LockedBondResponseItem>`
+    #[returns(Vec<LockedBondResponseItem>)]
+    LockedBonds {
+        user: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Totals of the principal/yield split. Response: `YieldPoolResponse`
+    #[returns(YieldPoolResponse)]
+    YieldPool {},
+    /// A user's yield shares and the Steak they can claim. Response: `YieldPositionResponse`
+    #[returns(YieldPositionResponse)]
+    YieldPosition { user: String },
+    /// A user's rebasing balance. Response: `RebasingBalanceResponse`
+    #[returns(RebasingBalanceResponse)]
+    RebasingBalance { user: String },
+    /// Totals of the rebasing balances. Response: `RebasingSupplyResponse`
+    #[returns(RebasingSupplyResponse)]
+    RebasingSupply {},
+    /// Configuration, reserves and position of the protocol-owned liquidity.
+    /// Response: `PolStateResponse`
+    #[returns(PolStateResponse)]
+    PolState {},
+    /// Configuration and accounting of the arbitrage reserve. Response: `ArbStateResponse`
+    #[returns(ArbStateResponse)]
+    ArbState {},
+    /// A user's cumulative bonding totals and the history they were derived from.
+    /// Response: `UserHistoryResponse`
+    #[returns(UserHistoryResponse)]
+    UserHistory {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Enumerate the daily accounting snapshots, by epoch day. Response: `Vec<DailySnapshot>`
+    #[returns(Vec<DailySnapshot>)]
+    DailySnapshots {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Verify the hub's core accounting invariants. Response: `InvariantsResponse`
+    #[returns(InvariantsResponse)]
+    CheckInvariants {},
+    /// The rounding policy of the share math. Response: `RoundingPolicyResponse`
+    #[returns(RoundingPolicyResponse)]
+    RoundingPolicy {},
+    /// The proposed denom change and what blocks it. Response: `DenomChangePreviewResponse`
+    #[returns(DenomChangePreviewResponse)]
+    DenomChangePreview {},
+    /// The state of the emergency undelegation. Response: `EmergencyUndelegationResponse`
+    #[returns(EmergencyUndelegationResponse)]
+    EmergencyUndelegation {},
+    /// The proposed halt recovery, if any. Response: `Option<HaltRecovery>`
+    #[returns(Option<HaltRecovery>)]
+    HaltRecovery {},
+    /// Safe mode, if it is in effect. Response: `Option<SafeMode>`
+    #[returns(Option<SafeMode>)]
+    SafeMode {},
+    /// The committee approving changes to the max fee, and its pending action, if any. Response:
+    /// `Option<FeeCommitteeResponse>`
+    #[returns(Option<FeeCommitteeResponse>)]
+    FeeCommittee {},
+    /// Contracts notified of changes in Steak balances. Response: `Vec<String>`
+    #[returns(Vec<String>)]
+    BalanceHooks {},
+    /// Addresses allowed to trigger harvests besides the contract itself. Response: `Vec<String>`
+    #[returns(Vec<String>)]
+    Harvesters {},
+    /// Contracts allowed to request callbacks once their bonds and unbonds have completed.
+    /// Response: `Vec<String>`
+    #[returns(Vec<String>)]
+    Integrators {},
+    /// Delegates whose votes on governance proposals the hub mirrors. Response: `Vec<String>`
+    #[returns(Vec<String>)]
+    VoteDelegates {},
+    /// The delegates' votes on `proposal_id`, and the vote the hub cast with its stake, if any.
+    /// Response: `Option<DelegatedVote>`
+    #[returns(Option<DelegatedVote>)]
+    DelegatedVote { proposal_id: u64 },
+    /// Native Token being unbonded from each validator, from the batches not yet reconciled and, where
+    /// the chain serves it, the staking module. Response: `UnbondingDelegationsResponse`
+    #[returns(UnbondingDelegationsResponse)]
+    UnbondingDelegations {},
+    /// How many of the unbonding entries the staking module allows per validator the hub occupies,
+    /// and when the next one frees. Response: `UndelegationSlotsResponse`
+    #[returns(UndelegationSlotsResponse)]
+    UndelegationSlots {},
+    /// What `ExecuteMsg::Reconcile` would do if executed now: the batches it would mark reconciled,
+    /// and the shortfall it would deduct from each. Response: `SimulateReconcileResponse`
+    #[returns(SimulateReconcileResponse)]
+    SimulateReconcile {},
+    /// Shortfalls found when reconciling batches and the validators they were attributed to, oldest
+    /// first. Response: `Vec<SlashingRecord>`
+    #[returns(Vec<SlashingRecord>)]
+    SlashingRecords {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Native Token expected to arrive from maturing batches and to become claimable by unbonders,
+    /// for each of the next `days` days (at most 60). Response: `LiquidityScheduleResponse`
+    #[returns(LiquidityScheduleResponse)]
+    LiquiditySchedule { days: u32 },
+    /// Seconds after a batch finished unbonding that its unclaimed Native Token can be swept, if
+    /// sweeping is enabled. Response: `Option<u64>`
+    #[returns(Option<u64>)]
+    BatchExpiry {},
+    /// The period reinvested rewards are released over, and the rewards not yet released.
+    /// Response: `RewardDripResponse`
+    #[returns(RewardDripResponse)]
+    RewardDrip {},
+    /// The rewards below which reinvests are deferred, and the rewards deferred so far. Response:
+    /// `PendingReinvestResponse`
+    #[returns(PendingReinvestResponse)]
+    PendingReinvest {},
+    /// Enumerate the batches that have been swept but still have Steak to refund. Response:
+    /// `Vec<SweptBatch>`
+    #[returns(Vec<SweptBatch>)]
+    SweptBatches {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Seconds between the harvests run by the chain's clock module, if any. Response: `Option<u64>`
+    #[returns(Option<u64>)]
+    ClockHarvestInterval {},
+    /// The automation contract and the upkeep tasks registered with it. Response:
+    /// `AutomationResponse`
+    #[returns(AutomationResponse)]
+    Automation {},
+    /// The build of the contract and the chain features it relies on. Response: `ContractInfoResponse`
+    #[returns(ContractInfoResponse)]
+    ContractInfo {},
+    /// The denoms staking rewards are paid in and where those other than the Native Token are
+    /// converted. Response: `RewardDenomsResponse`
+    #[returns(RewardDenomsResponse)]
+    RewardDenoms {},
+    /// The denoms refunded when sent along a deposit. Response: `Vec<String>`
+    #[returns(Vec<String>)]
+    RefundableDenoms {},
+    /// Native Token held beyond the hub's liabilities, and where skimming it routes it.
+    /// Response: `SurplusResponse`
+    #[returns(SurplusResponse)]
+    Surplus {},
+    /// The zap contract registered for this hub, if any. Response: `Option<String>`
+    #[returns(Option<String>)]
+    Zapper {},
+    /// Counters of the notable conditions the hub has run into, for monitoring without a log
+    /// pipeline. Conditions that fail the transaction, e.g. a proof below the difficulty or a bond
+    /// during safe mode, revert their count along with it, so only those surviving a failure are
+    /// counted. Response: `Metrics`
+    #[returns(Metrics)]
+    Metrics {},
+    /// Protocol fees held by the hub because forwarding them to the fee account failed, awaiting
+    /// `ExecuteMsg::RetryFeeForward`. Response: `Uint128`
+    #[returns(Uint128)]
+    EscrowedFees {},
+    /// Run several queries in one round trip; they can not themselves be `Aggregate` queries.
+    /// Response: `AggregateResponse`
+    #[returns(AggregateResponse)]
+    Aggregate { queries: Vec<QueryMsg> },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
@@ -183,6 +1106,60 @@ pub struct ConfigResponse {
     pub max_fee_rate: Decimal,
     /// Initial set of validators who will receive the delegations
     pub validators: Vec<String>,
+    /// Proof-of-work parameters
+    pub mining: MiningConfig,
+    /// Coordinator minting and burning the Steak token shared with other hubs, if any
+    pub coordinator: Option<String>,
+    /// Most staking messages a harvest, batch submission or rebalance fans out into, if capped
+    pub max_msgs_per_tx: Option<u32>,
+    /// Admin the Steak token was instantiated with; unknown for hubs instantiated before it was
+    /// recorded, and for those sharing a coordinator's token
+    pub token_admin: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct OwnershipResponse {
+    /// Account who can call certain privileged functions
+    pub owner: String,
+    /// Pending ownership transfer, awaiting acceptance by the new owner
+    pub new_owner: Option<String>,
+    /// Denom proposed to replace the staked denom, awaiting confirmation by the owner
+    pub pending_denom: Option<String>,
+    /// Fee committee action awaiting approvals
+    pub committee_proposal: Option<CommitteeProposal>,
+    /// Time after which the proposed emergency undelegation can be executed
+    pub emergency_undelegation_ready_at: Option<u64>,
+    /// Proposed halt recovery, including the time after which it can be applied
+    pub halt_recovery: Option<HaltRecovery>,
+    /// Account allowed to pause validators and enter safe mode alongside the owner
+    pub guardian: Option<String>,
+    /// Proposed guardian change, including the time after which it can be applied
+    pub guardian_change: Option<GuardianChange>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct MiningConfig {
+    /// Entropy proofs are currently computed against
+    pub entropy: String,
+    /// Entropy the next accepted proof is chained onto
+    pub entropy_draft: String,
+    /// Number of leading zeros a proof's hash must have
+    pub difficulty: Uint64,
+    /// Hash function proofs are computed with
+    pub proof_hash: ProofHash,
+    /// Average interval between proofs below which the difficulty is raised, in seconds
+    pub target_duration_floor: u64,
+    /// Average interval between proofs above which the difficulty is lowered, in seconds
+    pub target_duration_ceiling: u64,
+    /// Mining power accrued across all validators
+    pub total_mining_power: Uint128,
+    /// Share of the harvest fees currently emitted, the rest being bonded
+    pub miner_fee_share: Decimal,
+    /// Share of the emitted fees paid to the miner, in basis points, the rest going to the fee
+    /// account
+    pub miner_fee_share_bps: u16,
+    /// Address of the last accepted proof, paid its share of the emitted fees
+    pub miner: Option<String>,
 }
 
 // entropy response
@@ -192,6 +1169,123 @@ pub struct MinerParamsResponse {
     pub entropy: String,
     // mining difficulty
     pub difficulty: Uint64,
+    // hash function proofs are computed with
+    #[serde(default)]
+    pub proof_hash: ProofHash,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct VerifyProofResponse {
+    /// Hex encoded hash of the current entropy, the miner's address and the nonce
+    pub hash: String,
+    /// Current mining difficulty
+    pub difficulty: Uint64,
+    /// Prefix the hash must start with to satisfy the difficulty
+    pub prefix: String,
+    /// Whether the hash satisfies the difficulty
+    pub valid: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct MinerStatusResponse {
+    /// Height of the miner's last accepted proof
+    pub last_proof_height: Option<u64>,
+    /// Blocks a miner must wait between accepted proofs
+    pub cooldown_blocks: u64,
+    /// First height at which the miner may have another proof accepted
+    pub next_proof_height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct EntropyRateLimit {
+    /// Length of the window, in seconds
+    pub window: u64,
+    /// Entropy updates each address may make per window
+    pub max_contributions: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct IdleRedelegation {
+    /// Consecutive zero-reward harvests after which a validator is considered idle
+    pub zero_reward_harvests: u32,
+    /// Minimum time, in seconds, the zero-reward harvests must span. The last of them must also be
+    /// no older than this, so a validator is never paused on stale observations. Configs saved
+    /// before this was added load it as zero, and are treated as not configured
+    #[serde(default)]
+    pub min_idle_period: u64,
+    /// Maximum amount of Native Token redelegated per call
+    pub max_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+pub struct EntropyContributor {
+    /// Entropy updates made by the address
+    pub total_contributions: u64,
+    /// Start of the address's current rate limit window, in seconds
+    pub window_start: u64,
+    /// Entropy updates made by the address in the current window
+    pub window_contributions: u32,
+    /// Time of the address's last entropy update, in seconds
+    pub last_contribution: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct EntropyContributorResponse {
+    pub contributor: EntropyContributor,
+    /// The limit in force, if any
+    pub rate_limit: Option<EntropyRateLimit>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct EntropyContribution {
+    /// Sequence number of the contribution
+    pub id: u64,
+    /// Address which contributed the entropy
+    pub contributor: Addr,
+    /// Time of the contribution, in seconds
+    pub time: u64,
+    /// Height of the contribution
+    pub height: u64,
+    /// The entropy draft resulting from the contribution
+    pub entropy_draft: String,
+}
+
+/// Emission schedule of the fees paid to the miner: `initial_share` of the fees in epoch 0, halving
+/// every `halving_epochs` epochs (never, if zero)
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct MiningSchedule {
+    /// Start of epoch 0, in seconds
+    pub start_time: u64,
+    /// Length of an epoch, in seconds
+    pub epoch_duration: u64,
+    /// Share of the fees paid to the miner in epoch 0
+    pub initial_share: Decimal,
+    /// Number of epochs between halvings
+    pub halving_epochs: u64,
+}
+
+/// The miner whose proof was accepted last, and is paid its share of the fees until another miner
+/// has a proof accepted
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct CurrentMiner {
+    /// Address of the miner
+    pub miner: Addr,
+    /// Height of the proof that made it the current miner
+    pub since_height: u64,
+    /// Time of the proof that made it the current miner, in seconds
+    pub since_time: u64,
+    /// Fees paid to the miner since then, in `denom`
+    pub earned: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct MiningEmissionResponse {
+    /// The schedule, if one is set. Without one, the miner receives all fees
+    pub schedule: Option<MiningSchedule>,
+    /// The current epoch
+    pub epoch: u64,
+    /// Share of the fees paid to the miner in the current epoch
+    pub miner_share: Decimal,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
@@ -206,6 +1300,45 @@ pub struct StateResponse {
     pub unlocked_coins: Vec<Coin>,
 }
 
+/// `StateResponse` is kept as it is for existing clients; fields are only ever added here
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct StateResponseV2 {
+    /// Total supply to the Steak token
+    pub total_usteak: Uint128,
+    /// Total amount of native staked
+    pub total_native: Uint128,
+    /// The exchange rate between usteak and native, in terms of native per usteak
+    pub exchange_rate: Decimal,
+    /// Staking rewards currently held by the contract that are ready to be reinvested
+    pub unlocked_coins: Vec<Coin>,
+    /// Native Token the hub is responsible for: staked, unbonding and held
+    pub tvl: Uint128,
+    /// Native Token held by the hub beyond what it owes unbonders and its reserves
+    pub available_liquidity: Uint128,
+    pub unbonding: UnbondingTotals,
+    pub mining: MiningSummary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct UnbondingTotals {
+    /// `usteak` queued in the pending batch
+    pub usteak_pending: Uint128,
+    /// Native Token of submitted batches still unbonding
+    pub native_unbonding: Uint128,
+    /// Native Token of batches done unbonding, not yet withdrawn
+    pub native_claimable: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct MiningSummary {
+    /// Sum of the mining power of all validators
+    pub total_mining_power: Uint128,
+    /// The miner currently paid a share of the fees, if any
+    pub current_miner: Option<CurrentMiner>,
+    /// Share of the emitted fees paid to the miner, in basis points
+    pub miner_fee_share_bps: u16,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
 pub struct PendingBatch {
     /// ID of this batch
@@ -216,6 +1349,20 @@ pub struct PendingBatch {
     pub est_unbond_start_time: u64,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct PendingBatchResponse {
+    /// ID of this batch
+    pub id: u64,
+    /// Total amount of `usteak` to be burned in this batch
+    pub usteak_to_burn: Uint128,
+    /// Estimated time when this batch will be submitted for unbonding
+    pub est_unbond_start_time: u64,
+    /// Earliest time the batch can be submitted, accounting for the minimum gap between batches
+    pub next_submittable_time: u64,
+    /// Amount of `usteak` the batch must burn before anyone but the owner can submit it
+    pub min_batch_size: Uint128,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
 pub struct Batch {
     /// ID of this batch
@@ -230,6 +1377,48 @@ pub struct Batch {
     pub est_unbond_end_time: u64,
 }
 
+/// Steak the hub held itself when it was wound down, left out of the final batch's shares, and the
+/// Native Token set aside for it. Whatever the hub releases of it, e.g. an expired lock, moves into
+/// the batch along with its part of the Native Token once it is queued
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+pub struct EmergencyHeld {
+    /// Amount of `usteak` held by the hub and not yet released
+    pub usteak: Uint128,
+    /// Amount of `denom` set aside for that `usteak`
+    pub native: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct SweptBatch {
+    /// ID of the batch
+    pub id: u64,
+    /// Amount of `denom` that was left unclaimed and re-bonded
+    pub native_swept: Uint128,
+    /// Amount of `usteak` minted for the re-bonded `denom` and not yet refunded
+    pub usteak_unclaimed: Uint128,
+    /// Time the batch was swept
+    pub swept_time: u64,
+}
+
+/// Claims merged out of a user's requests in reconciled batches, awaiting withdrawal
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+pub struct ConsolidatedClaim {
+    /// Native Token owed
+    pub native: Uint128,
+    /// Steak owed, for requests in batches swept after going unclaimed
+    pub usteak: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct ConsolidatedClaimResponse {
+    /// Most unbonding requests a user can hold at once, if capped
+    pub max_unbond_requests: Option<u32>,
+    /// Unbonding requests the user holds
+    pub outstanding_requests: u32,
+    /// The user's consolidated claim
+    pub claim: ConsolidatedClaim,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
 pub struct UnbondRequest {
     /// ID of the batch
@@ -238,6 +1427,20 @@ pub struct UnbondRequest {
     pub user: Addr,
     /// The user's share in the batch
     pub shares: Uint128,
+    /// Address that queued the request on the user's behalf, e.g. an integration unbonding for its
+    /// customers; `None` if the user queued it
+    #[serde(default)]
+    pub sender: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct BatchFunder {
+    /// Address the `usteak` was sent to the hub by
+    pub funder: String,
+    /// Address the unbonding request was queued for
+    pub receiver: String,
+    /// Amount of `usteak` queued
+    pub usteak: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
@@ -257,6 +1460,19 @@ impl From<UnbondRequest> for UnbondRequestsByBatchResponseItem {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct PendingBatchRequestResponse {
+    /// ID of the pending batch
+    pub id: u64,
+    /// The user's share of `usteak` queued in the pending batch
+    pub shares: Uint128,
+    /// Native Token the shares are worth at the current exchange rate; the amount actually unbonded
+    /// is set when the batch is submitted
+    pub est_native: Uint128,
+    /// Estimated time when the batch will be submitted for unbonding
+    pub est_unbond_start_time: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
 pub struct UnbondRequestsByUserResponseItem {
     /// ID of the batch
@@ -275,19 +1491,856 @@ impl From<UnbondRequest> for UnbondRequestsByUserResponseItem {
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
-pub struct ValidatorMiningPower {
-    /// Validator address
-    pub address: String,
-    /// Mining power
+pub struct UnbondRequestDetails {
+    /// ID of the batch
+    pub id: u64,
+    /// The user's share in the batch
+    pub shares: Uint128,
+    /// Whether the batch is the pending one, not yet submitted
+    pub pending: bool,
+    /// Whether the batch has been reconciled
+    pub reconciled: bool,
+    /// Estimated time when the batch will finish unbonding. For the pending batch, assumes it is
+    /// submitted as soon as it can be
+    pub est_unbond_end_time: u64,
+    /// Whether the request can be withdrawn now
+    pub claimable: bool,
+    /// Native Token the shares are worth: a pro-rata share of the batch's unclaimed amount, or for
+    /// the pending batch, the amount at the current exchange rate
+    pub native_amount: Uint128,
+    /// Steak refunded along with the Native Token, for batches swept after going unclaimed
+    pub usteak_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct BlockedValidator {
+    /// Validator address
+    pub address: String,
+    /// Why the validator was blocked
+    pub reason: String,
+}
+
+/// Kind of change made to the validator set
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorSetAction {
+    Add,
+    /// Removal started; the validator is dropped once its stake has been moved away
+    Remove,
+    /// Dropped from the whitelist, once drained or with `RemoveValidatorEx`
+    Drop,
+    Pause,
+    ReceiveOnlyPause,
+    Unpause,
+    Block,
+    Unblock,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct ValidatorSetChange {
+    /// ID of this change, in the order the changes were made
+    pub id: u64,
+    /// Validator address
+    pub validator: String,
+    pub action: ValidatorSetAction,
+    /// Account that made the change
+    pub actor: String,
+    /// Why the change was made, if given
+    pub reason: Option<String>,
+    /// Block height at which the change was made
+    pub height: u64,
+    /// Time at which the change was made
+    pub time: u64,
+}
+
+/// An admin action recorded in the admin action log
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminAction {
+    UpdateFee {
+        new_fee: Decimal,
+    },
+    TransferFeeAccount {
+        fee_account_type: String,
+        new_fee_account: String,
+    },
+    EnterSafeMode {
+        until_height: Option<u64>,
+        until_time: Option<u64>,
+    },
+    ExitSafeMode {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct AdminActionRecord {
+    /// ID of this action, in the order the actions were taken
+    pub id: u64,
+    pub action: AdminAction,
+    /// Account that took the action
+    pub actor: String,
+    /// Why the action was taken, if given
+    pub reason: Option<String>,
+    /// Block height at which the action was taken
+    pub height: u64,
+    /// Time at which the action was taken
+    pub time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+pub struct ValidatorPerformance {
+    /// Smoothed staking reward rate, in Native Token earned per unit delegated per day
+    pub reward_rate: Decimal,
+    /// Number of harvests the reward rate was observed at
+    pub observations: u64,
+    /// Time rewards were last observed, in seconds
+    pub last_observed: u64,
+    /// Times the validator was paused, in seconds
+    pub pauses: Vec<u64>,
+    /// Times slashing incidents were reported, in seconds
+    pub slashes: Vec<u64>,
+    /// Consecutive harvests at which the validator was observed to have earned no rewards
+    #[serde(default)]
+    pub zero_reward_harvests: u32,
+    /// Time the validator was last observed earning rewards before its zero-reward harvests began,
+    /// in seconds. Unset while it is earning, and for streaks recorded before this was tracked
+    #[serde(default)]
+    pub idle_since: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct ValidatorRewards {
+    /// Validator address
+    pub validator: String,
+    /// Rewards withdrawn from the validator, per denom
+    pub rewards: Vec<Coin>,
+    /// Commission the validator sent back through `ExecuteMsg::DepositRebate`
+    pub rebates: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct ValidatorScore {
+    /// Validator address
+    pub address: String,
+    /// Smoothed staking reward rate, in Native Token earned per unit delegated per day
+    pub reward_rate: Decimal,
+    /// Pauses within the scoring window
+    pub pauses: u32,
+    /// Slashing incidents within the scoring window
+    pub slashes: u32,
+    /// Reward rate relative to the best validator's, discounted for each pause and slashing
+    /// incident; between 0 and 1
+    pub score: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct MiningAllocation {
+    /// Validator address
+    pub validator: String,
+    /// Share of the proof's mining power credited to the validator
+    pub weight: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct ValidatorMiningPower {
+    /// Validator address
+    pub address: String,
+    /// Mining power
     pub mining_power: Uint128,
 }
 
-pub type MigrateMsg = Empty;
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct LockedBond {
+    /// ID of this lock
+    pub id: u64,
+    /// The user's address
+    pub user: Addr,
+    /// Amount of `usteak` held by the hub on behalf of the user
+    pub shares: Uint128,
+    /// Time when the shares can be unbonded
+    pub unlock_time: u64,
+    /// Value of the boost index when the boost was last settled for this lock
+    pub boost_index: Decimal,
+    /// Multiplier of the tier the lock's duration qualified for
+    #[serde(default = "Decimal::one")]
+    pub multiplier: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct LockTier {
+    /// Minimum lock duration, in seconds, to qualify for this tier
+    pub min_duration: u64,
+    /// Multiplier applied to the shares of qualifying locks when distributing the boost
+    pub multiplier: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct LockStateResponse {
+    /// Share of the fees redirected to locked bonds "1.00 = 100%"
+    pub boost_rate: Decimal,
+    /// Total amount of `usteak` locked
+    pub total_locked_shares: Uint128,
+    /// Sum of the locked shares, each scaled by the multiplier of its lock
+    pub total_lock_weight: Uint128,
+    /// Cumulative amount of `denom` paid as boost per unit of lock weight
+    pub boost_index: Decimal,
+    /// Amount of `denom` held by the hub for boosts that have not been claimed
+    pub boost_unclaimed: Uint128,
+    /// Tiers boosting locks by duration, ordered by minimum duration
+    pub tiers: Vec<LockTier>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct LockedBondResponseItem {
+    /// ID of this lock
+    pub id: u64,
+    /// Amount of `usteak` locked
+    pub shares: Uint128,
+    /// Time when the shares can be unbonded
+    pub unlock_time: u64,
+    /// Multiplier of the tier the lock's duration qualified for
+    pub multiplier: Decimal,
+    /// Amount of `denom` boost claimable by this lock
+    pub boost_claimable: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+pub struct YieldPool {
+    /// Amount of principal tokens outstanding, each worth 1 native
+    pub principal: Uint128,
+    /// Amount of `usteak` held by the hub backing the principal and the reserved yield
+    pub usteak: Uint128,
+    /// Amount of `usteak` set aside for yield shares that has not yet been claimed
+    pub reserved: Uint128,
+    /// Total yield shares
+    pub shares: Uint128,
+    /// Cumulative amount of `usteak` accrued per yield share
+    pub index: Decimal,
+    /// Number of times all principal was redeemed and the yield shares outstanding retired
+    #[serde(default)]
+    pub epoch: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+pub struct YieldPosition {
+    /// The user's yield shares
+    pub shares: Uint128,
+    /// Value of the pool index when the position was last settled
+    pub index: Decimal,
+    /// Amount of `usteak` settled but not yet claimed
+    pub pending: Uint128,
+    /// Epoch of the pool the shares were issued in
+    #[serde(default)]
+    pub epoch: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct YieldPoolResponse {
+    /// Address of the principal token; `None` if splitting is not enabled
+    pub principal_token: Option<String>,
+    /// The pool, with yield accrued up to the current exchange rate
+    pub pool: YieldPool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct YieldPositionResponse {
+    /// The user's yield shares
+    pub shares: Uint128,
+    /// Amount of `usteak` the user can claim
+    pub claimable: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct RebasingBalanceResponse {
+    /// Amount of `usteak` backing the balance
+    pub shares: Uint128,
+    /// The balance, denominated in Native Token
+    pub balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct RebasingSupplyResponse {
+    /// Total amount of `usteak` wrapped
+    pub total_shares: Uint128,
+    /// Sum of all rebasing balances, denominated in Native Token
+    pub total_supply: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct PolStateResponse {
+    /// Pair the liquidity is provided to; `None` if not configured
+    pub pair: Option<String>,
+    /// LP token of the pair
+    pub lp_token: Option<String>,
+    /// Share of fees kept to fund protocol-owned liquidity
+    pub fee_rate: Decimal,
+    /// Native Token held by the hub, not yet deployed
+    pub native_reserve: Uint128,
+    /// `usteak` held by the hub, not yet deployed
+    pub usteak_reserve: Uint128,
+    /// LP tokens held by the hub
+    pub lp_balance: Uint128,
+    /// Native Token in the pair attributable to the hub's LP tokens
+    pub position_native: Uint128,
+    /// `usteak` in the pair attributable to the hub's LP tokens
+    pub position_usteak: Uint128,
+    /// Value of the position, in Native Token, with `usteak` valued at the exchange rate
+    pub position_value: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct ArbStateResponse {
+    /// Minimum discount to the redemption rate at which Steak is bought
+    pub min_discount: Decimal,
+    /// Maximum amount of Native Token deployed per arbitrage
+    pub max_per_execution: Uint128,
+    /// Maximum amount of Native Token awaiting unbonding at any time
+    pub max_in_flight: Uint128,
+    /// Native Token held by the reserve
+    pub reserve: Uint128,
+    /// Native Token spent on Steak that has not yet been settled
+    pub in_flight_cost: Uint128,
+    /// `usteak` bought and queued for unbonding that has not yet been settled
+    pub in_flight_shares: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryAction {
+    /// Native Token bonded, Steak minted
+    Bond,
+    /// Steak queued for unbonding
+    Unbond,
+    /// Native Token withdrawn after unbonding
+    Withdraw,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct UserHistoryEntry {
+    /// ID of this entry, unique per user
+    pub id: u64,
+    pub time: u64,
+    pub height: u64,
+    pub action: HistoryAction,
+    /// Amount of Native Token bonded or withdrawn
+    pub native_amount: Uint128,
+    /// Amount of `usteak` minted, queued for unbonding, or refunded from a swept batch
+    pub usteak_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+pub struct UserTotals {
+    /// Cumulative amount of Native Token bonded
+    pub bonded: Uint128,
+    /// Cumulative amount of `usteak` minted
+    pub minted: Uint128,
+    /// Cumulative amount of `usteak` queued for unbonding
+    pub unbonded: Uint128,
+    /// Cumulative amount of Native Token withdrawn after unbonding
+    pub withdrawn: Uint128,
+    /// Number of history entries recorded
+    pub entries: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct UserHistoryResponse {
+    pub totals: UserTotals,
+    pub entries: Vec<UserHistoryEntry>,
+}
+
+/// Accounting state recorded by the first bond or batch submission of each day
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct DailySnapshot {
+    /// Days since the unix epoch
+    pub day: u64,
+    /// Time the snapshot was taken
+    pub time: u64,
+    /// The exchange rate between usteak and native, in terms of native per usteak
+    pub exchange_rate: Decimal,
+    /// Total amount of native staked
+    pub total_native: Uint128,
+    /// Total supply of the Steak token
+    pub total_usteak: Uint128,
+    /// Fees charged on rewards since the previous snapshot
+    pub fees: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct InvariantsResponse {
+    /// Whether all invariants hold
+    pub healthy: bool,
+    /// Description of each invariant that does not hold
+    pub violations: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct DenomChangePreviewResponse {
+    /// The denom currently staked
+    pub current_denom: String,
+    /// The denom proposed to replace it, if any
+    pub pending_denom: Option<String>,
+    /// Description of everything still outstanding in the current denom. The change can only be
+    /// confirmed once this is empty
+    pub blockers: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct EmergencyUndelegationResponse {
+    /// Time after which the proposed emergency undelegation can be executed, if one is proposed
+    pub ready_at: Option<u64>,
+    /// ID of the batch everything was undelegated into, once the hub has been wound down. Steak
+    /// queued for unbonding from then on is burned and claims from this batch
+    pub batch_id: Option<u64>,
+}
+
+/// Changes that can only be made with the approval of the fee committee
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitteeAction {
+    /// Set the cap on the fee the owner can charge
+    UpdateMaxFee { max_fee: Decimal },
+    /// Replace the committee's members and threshold
+    UpdateCommittee {
+        members: Vec<String>,
+        threshold: u32,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct FeeCommittee {
+    pub members: Vec<Addr>,
+    /// Number of members that must approve an action
+    pub threshold: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct CommitteeProposal {
+    /// ID of the proposal; approvals name it so they can not carry over to a replacing proposal
+    pub id: u64,
+    pub action: CommitteeAction,
+    /// Members that approved the action so far
+    pub approvals: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct FeeCommitteeResponse {
+    pub members: Vec<String>,
+    pub threshold: u32,
+    pub proposal: Option<CommitteeProposal>,
+}
+
+/// Rewards reinvested but not yet reflected in the exchange rate, released linearly between
+/// `start_time` and `end_time`
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+pub struct RewardDrip {
+    /// Amount of `denom` still unreleased at `start_time`
+    pub amount: Uint128,
+    /// Time the release started, in seconds
+    pub start_time: u64,
+    /// Time by which everything is released, in seconds
+    pub end_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct DelegationDriftResponse {
+    /// Largest deviation of a validator's delegation from its target, in basis points of the total
+    /// delegated
+    pub max_deviation_bps: u64,
+    /// Sum of the deviations of all validators' delegations from their targets, in basis points of
+    /// the total delegated
+    pub total_deviation_bps: u64,
+    /// Largest deviation tolerated before rebalancing is recommended, in basis points
+    pub tolerance_bps: u16,
+    /// Whether the largest deviation exceeds the tolerance
+    pub rebalance_recommended: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct VotingPowerCapResponse {
+    /// Largest share of the chain's voting power the hub's stake may lift a validator to, if capped
+    pub max_share: Option<Decimal>,
+    /// The chain's total voting power, as of the last refresh
+    pub total_voting_power: Uint128,
+    /// Time of the last refresh, if any
+    pub refreshed_at: Option<u64>,
+    pub validators: Vec<ValidatorVotingPower>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct ValidatorVotingPower {
+    pub validator: String,
+    /// Voting power of the validator, as of the last refresh
+    pub voting_power: Uint128,
+    /// Most Native Token the hub may delegate to the validator, if capped
+    pub cap: Option<Uint128>,
+}
+
+/// Exchange rate every bond and unbond in the block at `height` is priced at
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct ExchangeRateSnapshot {
+    /// Height of the block
+    pub height: u64,
+    /// Supply of `usteak` at the block's first bond or unbond
+    pub usteak_supply: Uint128,
+    /// Amount of `denom` bonded, net of unreleased rewards, at the block's first bond or unbond
+    pub native_bonded: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct RewardDripResponse {
+    /// Seconds reinvested rewards are released over, if dripping is enabled
+    pub period: Option<u64>,
+    /// The release in progress
+    pub drip: Option<RewardDrip>,
+    /// Amount of `denom` bonded but not yet reflected in the exchange rate
+    pub unreleased: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct PendingReinvestResponse {
+    /// Rewards below which a reinvest is deferred
+    pub min_reinvest_amount: Uint128,
+    /// Rewards harvested by deferred reinvests, to be reinvested by the next one
+    pub pending: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct HaltRecovery {
+    /// Seconds the pending batch and the batches still unbonding are delayed by
+    pub batch_shift: u64,
+    /// Whether the mining clock restarts from the time the recovery is applied
+    pub reset_mining_clock: bool,
+    /// Time after which the recovery can be applied
+    pub ready_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, JsonSchema)]
+pub struct Metrics {
+    /// Reconciliations that found less Native Token than the batches were owed
+    pub reconcile_shortfalls: u64,
+    /// Native Token deducted from batches by those reconciliations
+    pub native_shortfall: Uint128,
+    /// Upkeep dispatched by the chain's clock module that failed, e.g. a harvest finding no rewards
+    pub clock_task_failures: u64,
+    /// Balance hooks that failed when notified of a Steak transfer
+    pub balance_hook_failures: u64,
+    /// Reinvests whose protocol fees could not be forwarded to the fee account, and were escrowed
+    #[serde(default)]
+    pub fee_forward_failures: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct GuardianChange {
+    /// Account to become the guardian, or `None` to revoke the current one
+    pub guardian: Option<String>,
+    /// Time after which the change can be applied
+    pub ready_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct SafeMode {
+    /// Height at which safe mode lifts by itself
+    pub until_height: Option<u64>,
+    /// Time at which safe mode lifts by itself
+    pub until_time: Option<u64>,
+}
+
+impl SafeMode {
+    /// Whether safe mode is still in effect at the given block
+    pub fn is_active(&self, height: u64, time: u64) -> bool {
+        self.until_height.map_or(true, |h| height < h) && self.until_time.map_or(true, |t| time < t)
+    }
+}
+
+/// Hash function a miner's proof of work is computed with
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Copy, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofHash {
+    #[default]
+    Sha256,
+    Keccak256,
+}
+
+/// Why a proof of work was rejected
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofRejection {
+    /// The nonce met the difficulty against the entropy the last accepted proof replaced
+    StaleEntropy,
+    /// The nonce does not meet the difficulty against the current entropy
+    InsufficientDifficulty,
+}
+
+impl ProofRejection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProofRejection::StaleEntropy => "stale_entropy",
+            ProofRejection::InsufficientDifficulty => "insufficient_difficulty",
+        }
+    }
+}
+
+impl fmt::Display for ProofRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Where the Native Token held beyond the hub's liabilities is routed when skimmed
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SurplusPolicy {
+    /// Bond it without minting any Steak, raising the exchange rate for all holders
+    #[default]
+    Reinvest,
+    /// Send it to a fund covering slashing losses
+    Insurance { fund: String },
+    /// Forward it to the fee account, as fees are
+    FeeAccount,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct SurplusResponse {
+    /// Native Token held by the hub
+    pub native_balance: Uint128,
+    /// Native Token owed to withdrawers, held in the reserves, or awaiting reinvestment
+    pub native_liabilities: Uint128,
+    /// Native Token held beyond the liabilities
+    pub surplus: Uint128,
+    /// Where skimming routes the surplus
+    pub policy: SurplusPolicy,
+}
+
+/// Which side the remainder goes to when converting between native and `usteak`
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Copy, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingPolicy {
+    /// Round the `usteak` minted and the native unbonded down
+    #[default]
+    FavorProtocol,
+    /// Round the `usteak` minted and the native unbonded up
+    FavorUser,
+}
+
+/// With `FavorProtocol`, bonding never mints more `usteak` than the native deposited is worth, and
+/// unbonding never returns more native than the `usteak` burned is worth. Any remainder stays with
+/// the other holders, so no sequence of bonds and unbonds can drain value from them
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct RoundingPolicyResponse {
+    pub policy: RoundingPolicy,
+    /// Whether the `usteak` minted on bonding is rounded up
+    pub mint_rounds_up: bool,
+    /// Whether the native unbonded on burning `usteak` is rounded up
+    pub unbond_rounds_up: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct ContractInfoResponse {
+    /// Name of the crate the contract was built from
+    pub name: String,
+    /// Semantic version of the crate
+    pub version: String,
+    /// Commit the contract was built from, if `GIT_COMMIT_HASH` was set at build time
+    pub commit: Option<String>,
+    /// How the liquid staking token is issued; `cw20`
+    pub token_mode: String,
+    /// Cosmos SDK module the staking messages are encoded for, e.g. `liquidstaking`
+    pub chain_adapter: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct LiquidityScheduleResponse {
+    /// Native Token already claimable by unbonders but not yet claimed
+    pub claimable: Uint128,
+    /// Consecutive 24-hour windows, the first starting at the current block time
+    pub days: Vec<LiquidityDay>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct LiquidityDay {
+    pub start_time: u64,
+    pub end_time: u64,
+    /// Native Token the batches completing their unbonding within the window return to the hub
+    pub inflow: Uint128,
+    /// Native Token that becomes claimable within the window, and may leave the hub from then on
+    pub outflow: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct UnbondingDelegationsResponse {
+    pub validators: Vec<ValidatorUnbonding>,
+    /// Whether the chain answered the staking module's `DelegatorUnbondingDelegations` query; on
+    /// chains that do not serve it to contracts, the `chain_*` fields are left empty
+    pub chain_queried: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct SimulateReconcileResponse {
+    /// Native Token the hub should hold: what the batches are owed plus the hub's own reserves
+    pub native_expected: Uint128,
+    /// Native Token the hub actually holds
+    pub native_actual: Uint128,
+    /// The shortfall, deducted evenly from the batches
+    pub native_to_deduct: Uint128,
+    pub batches: Vec<ReconcileBatchPreview>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct ReconcileBatchPreview {
+    pub id: u64,
+    /// Native Token left unclaimed in the batch before reconciling
+    pub amount_unclaimed: Uint128,
+    /// The batch's share of the shortfall
+    pub deduction: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct SlashingRecord {
+    /// ID of this record, in the order the shortfalls were found
+    pub id: u64,
+    /// Batches the shortfall was deducted from
+    pub batch_ids: Vec<u64>,
+    /// Native Token missing when the batches were reconciled
+    pub native_shortfall: Uint128,
+    /// The shortfall attributed to each validator
+    pub validators: Vec<ValidatorLoss>,
+    /// Whether the chain reported slashed unbonding entries to attribute the shortfall by; if not,
+    /// it is split by what the batches undelegated from each validator
+    pub chain_attributed: bool,
+    /// Block height at which the shortfall was found
+    pub height: u64,
+    /// Time at which the shortfall was found
+    pub time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct ValidatorLoss {
+    pub validator: String,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct ValidatorUnbonding {
+    pub validator: String,
+    /// Native Token the batches not yet reconciled undelegated from the validator
+    pub amount: Uint128,
+    /// The batches behind `amount`, by completion time
+    pub entries: Vec<UnbondingEntry>,
+    /// Native Token still unbonding from the validator according to the chain
+    pub chain_amount: Option<Uint128>,
+    /// Unbonding entries the chain holds for the validator; the chain caps these per delegator
+    pub chain_entries: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct UndelegationSlotsResponse {
+    /// Unbonding entries the staking module allows per delegator and validator
+    pub max_entries: u32,
+    /// Whether the occupied slots are as reported by the staking module; if not, they are taken
+    /// from the batches not yet reconciled
+    pub chain_queried: bool,
+    pub validators: Vec<ValidatorSlots>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct ValidatorSlots {
+    pub validator: String,
+    /// Unbonding entries the hub holds with the validator
+    pub occupied: u32,
+    /// Undelegations the hub can still make from the validator
+    pub available: u32,
+    /// Time the earliest of the occupied entries completes, freeing its slot
+    pub next_free_at: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct UnbondingEntry {
+    pub batch_id: u64,
+    pub amount: Uint128,
+    /// Estimated time the undelegation completes
+    pub completion_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct AutomationResponse {
+    pub contract: Option<String>,
+    pub tasks: Vec<AutomationTaskInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct AutomationTaskInfo {
+    pub task: AutomationTask,
+    /// Hash the automation contract identifies the task by
+    pub task_hash: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct RewardDenomsResponse {
+    /// Denoms staking rewards are paid in. Until configured, only the Native Token
+    pub reward_denoms: Vec<String>,
+    /// Contract that rewards paid in other denoms are sent to for conversion to the Native Token
+    pub converter: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct AggregateResponse {
+    /// Outcome of each query, in the order they were given
+    pub results: Vec<AggregateResult>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct AggregateResult {
+    /// The query's response, if it succeeded
+    pub data: Option<Binary>,
+    /// Why the query failed, if it did
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrateMsg {
+    /// Migrate the contract's state to the new version
+    Migrate {},
+    /// Run the migration against the current state without committing anything, failing with a
+    /// `MigrationReport` of what it would have done. The chain only switches the code when the
+    /// migration succeeds, so a dry run leaves the contract untouched even if broadcast
+    DryRun {},
+}
+
+/// What a migration does to the hub's state, as reported by `MigrateMsg::DryRun`
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct MigrationReport {
+    /// Name of the contract being migrated from
+    pub previous_contract_name: String,
+    /// Version of the contract being migrated from
+    pub previous_contract_version: String,
+    /// Version of the contract being migrated to
+    pub new_contract_version: String,
+    /// Unbonding requests added to the `sender` index
+    pub unbond_requests_reindexed: u64,
+    /// Mining power purged from validators no longer whitelisted
+    pub mining_power_purged: Uint128,
+    /// Batches converted from a legacy storage layout
+    pub batches_converted: u64,
+    /// Number of storage keys the migration sets or removes
+    pub keys_written: u64,
+    /// Invariants checked against the migrated state; the migration aborts unless they hold
+    pub invariants: InvariantsResponse,
+}
+
+/// Messages the chain sends to contracts registered with its clock module, e.g. x/clock
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SudoMsg {
+    /// Sent at the end of every block; advances the upkeep that is due
+    ClockEndBlock {},
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Copy, JsonSchema)]
 pub enum FeeType {
     Wallet,
     FeeSplit,
+    /// The fee module is disabled: no fee is charged and there is no fee account to forward to
+    None,
 }
 impl FromStr for FeeType {
     type Err = ();
@@ -295,15 +2348,17 @@ impl FromStr for FeeType {
         match s {
             "Wallet" => Ok(FeeType::Wallet),
             "FeeSplit" => Ok(FeeType::FeeSplit),
+            "None" => Ok(FeeType::None),
             _ => Err(()),
         }
     }
 }
-impl ToString for FeeType {
-    fn to_string(&self) -> String {
+impl fmt::Display for FeeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self {
-            FeeType::Wallet => String::from("Wallet"),
-            FeeType::FeeSplit => String::from("FeeSplit"),
+            FeeType::Wallet => write!(f, "Wallet"),
+            FeeType::FeeSplit => write!(f, "FeeSplit"),
+            FeeType::None => write!(f, "None"),
         }
     }
 }
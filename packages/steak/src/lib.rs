@@ -1,4 +1,10 @@
+pub mod address;
+pub mod automation;
+pub mod coordinator;
 pub mod hub;
+pub mod pair;
+pub mod querier;
+pub mod zap;
 
 // this was copied from eris-staking's branch of STEAK.
 //
@@ -0,0 +1,474 @@
+use cosmwasm_std::{Decimal, QuerierWrapper, StdResult, Uint128, Uint64};
+use serde::de::DeserializeOwned;
+
+use crate::hub::{
+    AdminActionRecord, AggregateResponse, ArbStateResponse, AutomationResponse, Batch, BatchFunder,
+    BlockedValidator, ConfigResponse, ConsolidatedClaimResponse, ContractInfoResponse,
+    CurrentMiner, DailySnapshot, DelegatedVote, DelegationDriftResponse,
+    DenomChangePreviewResponse, EmergencyUndelegationResponse, EntropyContribution,
+    EntropyContributorResponse, FeeCommitteeResponse, HaltRecovery, InvariantsResponse,
+    LiquidityScheduleResponse, LockStateResponse, LockedBondResponseItem, Metrics,
+    MinerParamsResponse, MinerStatusResponse, MiningEmissionResponse, OwnershipResponse,
+    PendingBatchRequestResponse, PendingBatchResponse, PendingReinvestResponse, PolStateResponse,
+    QueryMsg, RebasingBalanceResponse, RebasingSupplyResponse, RewardDenomsResponse,
+    RewardDripResponse, RoundingPolicyResponse, SafeMode, SimulateReconcileResponse,
+    SlashingRecord, StateResponse, StateResponseV2, SurplusResponse, SweptBatch, UnbondRequest,
+    UnbondRequestDetails, UnbondRequestsByBatchResponseItem, UnbondRequestsByUserResponseItem,
+    UnbondingDelegationsResponse, UndelegationSlotsResponse, UserHistoryResponse,
+    ValidatorMiningPower, ValidatorRewards, ValidatorScore, ValidatorSetChange,
+    VerifyProofResponse, VotingPowerCapResponse, YieldPoolResponse, YieldPositionResponse,
+};
+
+/// Typed access to the queries of a Steak hub, for contracts integrating with it
+///
+/// ```ignore
+/// let hub = HubQuerier::new(deps.querier, &hub_addr);
+/// let rate = hub.exchange_rate()?;
+/// ```
+#[derive(Clone, Copy)]
+pub struct HubQuerier<'a> {
+    querier: QuerierWrapper<'a>,
+    hub: &'a str,
+}
+
+impl<'a> HubQuerier<'a> {
+    pub fn new(querier: QuerierWrapper<'a>, hub: &'a str) -> Self {
+        HubQuerier { querier, hub }
+    }
+
+    /// Run any hub query, for those returning a type decided by the caller
+    pub fn query<T: DeserializeOwned>(&self, msg: &QueryMsg) -> StdResult<T> {
+        self.querier.query_wasm_smart(self.hub, msg)
+    }
+
+    //----------------------------------------------------------------------------------------------
+    // Configuration and state
+    //----------------------------------------------------------------------------------------------
+
+    pub fn config(&self) -> StdResult<ConfigResponse> {
+        self.query(&QueryMsg::Config {})
+    }
+
+    pub fn ownership(&self) -> StdResult<OwnershipResponse> {
+        self.query(&QueryMsg::Ownership {})
+    }
+
+    pub fn state(&self) -> StdResult<StateResponse> {
+        self.query(&QueryMsg::State {})
+    }
+
+    pub fn state_v2(&self) -> StdResult<StateResponseV2> {
+        self.query(&QueryMsg::StateV2 {})
+    }
+
+    /// Native Token each `usteak` is currently worth
+    pub fn exchange_rate(&self) -> StdResult<Decimal> {
+        Ok(self.state()?.exchange_rate)
+    }
+
+    pub fn contract_info(&self) -> StdResult<ContractInfoResponse> {
+        self.query(&QueryMsg::ContractInfo {})
+    }
+
+    pub fn reward_denoms(&self) -> StdResult<RewardDenomsResponse> {
+        self.query(&QueryMsg::RewardDenoms {})
+    }
+
+    pub fn refundable_denoms(&self) -> StdResult<Vec<String>> {
+        self.query(&QueryMsg::RefundableDenoms {})
+    }
+
+    pub fn consolidated_claim(&self, user: &str) -> StdResult<ConsolidatedClaimResponse> {
+        self.query(&QueryMsg::ConsolidatedClaim {
+            user: user.to_string(),
+        })
+    }
+
+    pub fn surplus(&self) -> StdResult<SurplusResponse> {
+        self.query(&QueryMsg::Surplus {})
+    }
+
+    pub fn zapper(&self) -> StdResult<Option<String>> {
+        self.query(&QueryMsg::Zapper {})
+    }
+
+    pub fn rounding_policy(&self) -> StdResult<RoundingPolicyResponse> {
+        self.query(&QueryMsg::RoundingPolicy {})
+    }
+
+    pub fn reward_drip(&self) -> StdResult<RewardDripResponse> {
+        self.query(&QueryMsg::RewardDrip {})
+    }
+
+    pub fn pending_reinvest(&self) -> StdResult<PendingReinvestResponse> {
+        self.query(&QueryMsg::PendingReinvest {})
+    }
+
+    pub fn check_invariants(&self) -> StdResult<InvariantsResponse> {
+        self.query(&QueryMsg::CheckInvariants {})
+    }
+
+    pub fn metrics(&self) -> StdResult<Metrics> {
+        self.query(&QueryMsg::Metrics {})
+    }
+
+    pub fn escrowed_fees(&self) -> StdResult<Uint128> {
+        self.query(&QueryMsg::EscrowedFees {})
+    }
+
+    pub fn safe_mode(&self) -> StdResult<Option<SafeMode>> {
+        self.query(&QueryMsg::SafeMode {})
+    }
+
+    pub fn halt_recovery(&self) -> StdResult<Option<HaltRecovery>> {
+        self.query(&QueryMsg::HaltRecovery {})
+    }
+
+    pub fn emergency_undelegation(&self) -> StdResult<EmergencyUndelegationResponse> {
+        self.query(&QueryMsg::EmergencyUndelegation {})
+    }
+
+    pub fn denom_change_preview(&self) -> StdResult<DenomChangePreviewResponse> {
+        self.query(&QueryMsg::DenomChangePreview {})
+    }
+
+    pub fn fee_committee(&self) -> StdResult<Option<FeeCommitteeResponse>> {
+        self.query(&QueryMsg::FeeCommittee {})
+    }
+
+    pub fn admin_actions(
+        &self,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<AdminActionRecord>> {
+        self.query(&QueryMsg::AdminActions { start_after, limit })
+    }
+
+    pub fn balance_hooks(&self) -> StdResult<Vec<String>> {
+        self.query(&QueryMsg::BalanceHooks {})
+    }
+
+    pub fn harvesters(&self) -> StdResult<Vec<String>> {
+        self.query(&QueryMsg::Harvesters {})
+    }
+
+    pub fn integrators(&self) -> StdResult<Vec<String>> {
+        self.query(&QueryMsg::Integrators {})
+    }
+
+    pub fn vote_delegates(&self) -> StdResult<Vec<String>> {
+        self.query(&QueryMsg::VoteDelegates {})
+    }
+
+    pub fn delegated_vote(&self, proposal_id: u64) -> StdResult<Option<DelegatedVote>> {
+        self.query(&QueryMsg::DelegatedVote { proposal_id })
+    }
+
+    pub fn clock_harvest_interval(&self) -> StdResult<Option<u64>> {
+        self.query(&QueryMsg::ClockHarvestInterval {})
+    }
+
+    pub fn automation(&self) -> StdResult<AutomationResponse> {
+        self.query(&QueryMsg::Automation {})
+    }
+
+    pub fn daily_snapshots(
+        &self,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<DailySnapshot>> {
+        self.query(&QueryMsg::DailySnapshots { start_after, limit })
+    }
+
+    pub fn aggregate(&self, queries: Vec<QueryMsg>) -> StdResult<AggregateResponse> {
+        self.query(&QueryMsg::Aggregate { queries })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    // Batches and unbonding requests
+    //----------------------------------------------------------------------------------------------
+
+    pub fn pending_batch(&self) -> StdResult<PendingBatchResponse> {
+        self.query(&QueryMsg::PendingBatch {})
+    }
+
+    pub fn pending_batch_request(
+        &self,
+        user: impl Into<String>,
+    ) -> StdResult<PendingBatchRequestResponse> {
+        self.query(&QueryMsg::PendingBatchRequest { user: user.into() })
+    }
+
+    pub fn previous_batch(&self, id: u64) -> StdResult<Batch> {
+        self.query(&QueryMsg::PreviousBatch(id))
+    }
+
+    pub fn previous_batches(
+        &self,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<Batch>> {
+        self.query(&QueryMsg::PreviousBatches { start_after, limit })
+    }
+
+    pub fn swept_batches(
+        &self,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<SweptBatch>> {
+        self.query(&QueryMsg::SweptBatches { start_after, limit })
+    }
+
+    pub fn batch_expiry(&self) -> StdResult<Option<u64>> {
+        self.query(&QueryMsg::BatchExpiry {})
+    }
+
+    pub fn batch_funders(
+        &self,
+        id: u64,
+        start_after: Option<(String, String)>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<BatchFunder>> {
+        self.query(&QueryMsg::BatchFunders {
+            id,
+            start_after,
+            limit,
+        })
+    }
+
+    pub fn unbond_requests_by_batch(
+        &self,
+        id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<UnbondRequestsByBatchResponseItem>> {
+        self.query(&QueryMsg::UnbondRequestsByBatch {
+            id,
+            start_after,
+            limit,
+        })
+    }
+
+    pub fn unbond_requests_by_user(
+        &self,
+        user: impl Into<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<UnbondRequestsByUserResponseItem>> {
+        self.query(&QueryMsg::UnbondRequestsByUser {
+            user: user.into(),
+            start_after,
+            limit,
+        })
+    }
+
+    pub fn unbond_requests_by_user_details(
+        &self,
+        user: impl Into<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<UnbondRequestDetails>> {
+        self.query(&QueryMsg::UnbondRequestsByUserDetails {
+            user: user.into(),
+            start_after,
+            limit,
+        })
+    }
+
+    pub fn unbond_requests_by_sender(
+        &self,
+        sender: impl Into<String>,
+        start_after: Option<(u64, String)>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<UnbondRequest>> {
+        self.query(&QueryMsg::UnbondRequestsBySender {
+            sender: sender.into(),
+            start_after,
+            limit,
+        })
+    }
+
+    pub fn unbonding_delegations(&self) -> StdResult<UnbondingDelegationsResponse> {
+        self.query(&QueryMsg::UnbondingDelegations {})
+    }
+
+    pub fn undelegation_slots(&self) -> StdResult<UndelegationSlotsResponse> {
+        self.query(&QueryMsg::UndelegationSlots {})
+    }
+
+    pub fn simulate_reconcile(&self) -> StdResult<SimulateReconcileResponse> {
+        self.query(&QueryMsg::SimulateReconcile {})
+    }
+
+    pub fn slashing_records(
+        &self,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<SlashingRecord>> {
+        self.query(&QueryMsg::SlashingRecords { start_after, limit })
+    }
+
+    pub fn liquidity_schedule(&self, days: u32) -> StdResult<LiquidityScheduleResponse> {
+        self.query(&QueryMsg::LiquiditySchedule { days })
+    }
+
+    pub fn user_history(
+        &self,
+        address: impl Into<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<UserHistoryResponse> {
+        self.query(&QueryMsg::UserHistory {
+            address: address.into(),
+            start_after,
+            limit,
+        })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    // Mining
+    //----------------------------------------------------------------------------------------------
+
+    pub fn miner_params(&self) -> StdResult<MinerParamsResponse> {
+        self.query(&QueryMsg::MinerParams {})
+    }
+
+    pub fn mining_emission(&self) -> StdResult<MiningEmissionResponse> {
+        self.query(&QueryMsg::MiningEmission {})
+    }
+
+    pub fn verify_proof(
+        &self,
+        miner: impl Into<String>,
+        nonce: Uint64,
+    ) -> StdResult<VerifyProofResponse> {
+        self.query(&QueryMsg::VerifyProof {
+            miner: miner.into(),
+            nonce,
+        })
+    }
+
+    pub fn miner_status(&self, miner: impl Into<String>) -> StdResult<MinerStatusResponse> {
+        self.query(&QueryMsg::MinerStatus {
+            miner: miner.into(),
+        })
+    }
+
+    pub fn current_miner(&self) -> StdResult<Option<CurrentMiner>> {
+        self.query(&QueryMsg::CurrentMiner {})
+    }
+
+    pub fn entropy_contributor(
+        &self,
+        contributor: impl Into<String>,
+    ) -> StdResult<EntropyContributorResponse> {
+        self.query(&QueryMsg::EntropyContributor {
+            contributor: contributor.into(),
+        })
+    }
+
+    pub fn entropy_contributions(
+        &self,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<EntropyContribution>> {
+        self.query(&QueryMsg::EntropyContributions { start_after, limit })
+    }
+
+    pub fn validator_mining_powers(
+        &self,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<ValidatorMiningPower>> {
+        self.query(&QueryMsg::ValidatorMiningPowers { start_after, limit })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    // Validators
+    //----------------------------------------------------------------------------------------------
+
+    pub fn validator_rewards(&self) -> StdResult<Vec<ValidatorRewards>> {
+        self.query(&QueryMsg::ValidatorRewards {})
+    }
+
+    pub fn validator_scores(&self) -> StdResult<Vec<ValidatorScore>> {
+        self.query(&QueryMsg::ValidatorScores {})
+    }
+
+    pub fn delegation_drift(&self) -> StdResult<DelegationDriftResponse> {
+        self.query(&QueryMsg::DelegationDrift {})
+    }
+
+    pub fn voting_power_cap(&self) -> StdResult<VotingPowerCapResponse> {
+        self.query(&QueryMsg::VotingPowerCap {})
+    }
+
+    pub fn receive_paused_validators(&self) -> StdResult<Vec<String>> {
+        self.query(&QueryMsg::ReceivePausedValidators {})
+    }
+
+    pub fn draining_validators(&self) -> StdResult<Vec<String>> {
+        self.query(&QueryMsg::DrainingValidators {})
+    }
+
+    pub fn blocked_validators(
+        &self,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<BlockedValidator>> {
+        self.query(&QueryMsg::BlockedValidators { start_after, limit })
+    }
+
+    pub fn validator_set_changes(
+        &self,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<ValidatorSetChange>> {
+        self.query(&QueryMsg::ValidatorSetChanges { start_after, limit })
+    }
+
+    //----------------------------------------------------------------------------------------------
+    // Locks, yield splitting, rebasing and liquidity
+    //----------------------------------------------------------------------------------------------
+
+    pub fn lock_state(&self) -> StdResult<LockStateResponse> {
+        self.query(&QueryMsg::LockState {})
+    }
+
+    pub fn locked_bonds(
+        &self,
+        user: impl Into<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<LockedBondResponseItem>> {
+        self.query(&QueryMsg::LockedBonds {
+            user: user.into(),
+            start_after,
+            limit,
+        })
+    }
+
+    pub fn yield_pool(&self) -> StdResult<YieldPoolResponse> {
+        self.query(&QueryMsg::YieldPool {})
+    }
+
+    pub fn yield_position(&self, user: impl Into<String>) -> StdResult<YieldPositionResponse> {
+        self.query(&QueryMsg::YieldPosition { user: user.into() })
+    }
+
+    pub fn rebasing_balance(&self, user: impl Into<String>) -> StdResult<RebasingBalanceResponse> {
+        self.query(&QueryMsg::RebasingBalance { user: user.into() })
+    }
+
+    pub fn rebasing_supply(&self) -> StdResult<RebasingSupplyResponse> {
+        self.query(&QueryMsg::RebasingSupply {})
+    }
+
+    pub fn pol_state(&self) -> StdResult<PolStateResponse> {
+        self.query(&QueryMsg::PolState {})
+    }
+
+    pub fn arb_state(&self) -> StdResult<ArbStateResponse> {
+        self.query(&QueryMsg::ArbState {})
+    }
+}
@@ -0,0 +1,87 @@
+use cosmwasm_schema::QueryResponses;
+use cosmwasm_std::{to_binary, Addr, CosmosMsg, Decimal, StdResult, Uint128, WasmMsg};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// Account who can configure the swap routes
+    pub owner: String,
+    /// Address of the hub deposits are bonded to
+    pub hub: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Swap the single coin sent into the hub's Native Token through the pair routed for its denom,
+    /// bond the proceeds and send the minted Steak to `receiver`, or the sender. Coins already in
+    /// the Native Token are bonded as they are. Fails if less than `min_usteak` is minted
+    Zap {
+        receiver: Option<String>,
+        min_usteak: Option<Uint128>,
+        max_spread: Option<Decimal>,
+    },
+    /// Swap `denom` through `pair`, or stop accepting it if `None`; callable by the owner
+    SetRoute { denom: String, pair: Option<String> },
+    /// Callbacks; can only be invoked by the contract itself
+    Callback(CallbackMsg),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CallbackMsg {
+    /// Bond the Native Token received since `native_before`, minting the Steak to the zap
+    Bond {
+        receiver: Addr,
+        min_usteak: Option<Uint128>,
+        native_before: Uint128,
+    },
+    /// Send the Steak minted since `usteak_before` to `receiver`
+    Forward {
+        receiver: Addr,
+        min_usteak: Option<Uint128>,
+        usteak_before: Uint128,
+    },
+}
+
+impl CallbackMsg {
+    pub fn into_cosmos_msg(&self, contract_addr: &Addr) -> StdResult<CosmosMsg> {
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&ExecuteMsg::Callback(self.clone()))?,
+            funds: vec![],
+        }))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, QueryResponses)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// The zap's configuration. Response: `ConfigResponse`
+    #[returns(ConfigResponse)]
+    Config {},
+    /// The denoms accepted and the pair each is swapped through. Response: `Vec<Route>`
+    #[returns(Vec<Route>)]
+    Routes {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    /// Account who can configure the swap routes
+    pub owner: String,
+    /// Address of the hub deposits are bonded to
+    pub hub: String,
+    /// Address of the hub's Steak token
+    pub steak_token: String,
+    /// The hub's Native Token
+    pub denom: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, JsonSchema)]
+pub struct Route {
+    /// Denom accepted
+    pub denom: String,
+    /// Pair swapping it into the Native Token
+    pub pair: Addr,
+}
@@ -30,6 +30,10 @@ const argv = yargs(process.argv)
       type: "string",
       demandOption: false,
     },
+    "token-admin": {
+      type: "string",
+      demandOption: false,
+    },
     msg: {
       type: "string",
       demandOption: true,
@@ -71,6 +75,9 @@ async function uploadCode(deployer: Wallet, path: string) {
 
   const msg = JSON.parse(fs.readFileSync(path.resolve(argv["msg"]), "utf8"));
   msg["cw20_code_id"] = tokenCodeId;
+  if (argv["token-admin"]) {
+    msg["token_admin"] = argv["token-admin"];
+  }
 
   await waitForConfirm("Proceed to deploy contracts?");
   const result = await instantiateWithConfirm(